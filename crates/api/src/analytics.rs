@@ -2,12 +2,16 @@ use chrono::{DateTime, Utc};
 use database::DbPool;
 use shared::{Error, Result};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::{debug, info};
 use uuid::Uuid;
 
+use crate::tax_lots::TaxLotService;
+
 /// Analytics service for portfolio performance tracking and insights
 pub struct AnalyticsService {
     db_pool: DbPool,
+    tax_lots: Option<Arc<TaxLotService>>,
 }
 
 /// Multi-chain aggregated portfolio data
@@ -48,6 +52,10 @@ pub struct PerformanceMetrics {
     pub change_7d_percent: f64,
     pub all_time_profit_loss_usd: f64,
     pub all_time_profit_loss_percent: f64,
+    /// Gain or loss realized by disposals, from the user's tax lots
+    pub realized_profit_loss_usd: f64,
+    /// Gain or loss of the positions with a known cost basis
+    pub unrealized_profit_loss_usd: f64,
     pub positions: Vec<PositionMetrics>,
     pub timestamp: DateTime<Utc>,
 }
@@ -58,6 +66,8 @@ pub struct PositionMetrics {
     pub token_symbol: String,
     pub blockchain: String,
     pub current_value_usd: f64,
+    /// Remaining cost basis of the position's tax lots, if any are known
+    pub cost_basis_usd: Option<f64>,
     pub profit_loss_usd: f64,
     pub profit_loss_percent: f64,
 }
@@ -182,7 +192,13 @@ pub struct WhaleImpact {
 impl AnalyticsService {
     /// Create a new analytics service
     pub fn new(db_pool: DbPool) -> Self {
-        Self { db_pool }
+        Self { db_pool, tax_lots: None }
+    }
+
+    /// Compute profit and loss from the user's tax lots
+    pub fn with_tax_lots(mut self, tax_lots: Arc<TaxLotService>) -> Self {
+        self.tax_lots = Some(tax_lots);
+        self
     }
 
     /// Get multi-chain aggregated portfolio for a user
@@ -366,18 +382,44 @@ impl AnalyticsService {
             0.0
         };
 
-        // Calculate per-position metrics
+        let ledger = match &self.tax_lots {
+            Some(tax_lots) => Some(tax_lots.build_ledger(user_id).await?),
+            None => None,
+        };
+        let realized_profit_loss_usd = ledger
+            .as_ref()
+            .and_then(|ledger| ledger.realized_gain_usd().to_string().parse::<f64>().ok())
+            .unwrap_or(0.0);
+
+        // Calculate per-position metrics against the average cost of the
+        // asset's open lots
         let mut positions = Vec::new();
+        let mut unrealized_profit_loss_usd = 0.0;
         for chain in multi_chain.chains {
             for asset in chain.assets {
-                // For simplicity, assume cost basis equals current value (no P/L tracking yet)
-                // In a real implementation, you'd track purchase prices
+                let cost_basis_usd = ledger
+                    .as_ref()
+                    .and_then(|ledger| ledger.average_cost(&asset.token_symbol))
+                    .zip(asset.amount.parse::<rust_decimal::Decimal>().ok())
+                    .and_then(|(unit_cost, amount)| (unit_cost * amount).to_string().parse::<f64>().ok());
+
+                let (profit_loss_usd, profit_loss_percent) = match cost_basis_usd {
+                    Some(basis) => {
+                        let profit_loss = asset.value_usd - basis;
+                        let percent = if basis > 0.0 { (profit_loss / basis) * 100.0 } else { 0.0 };
+                        (profit_loss, percent)
+                    }
+                    None => (0.0, 0.0),
+                };
+                unrealized_profit_loss_usd += profit_loss_usd;
+
                 positions.push(PositionMetrics {
                     token_symbol: asset.token_symbol,
                     blockchain: chain.blockchain.clone(),
                     current_value_usd: asset.value_usd,
-                    profit_loss_usd: 0.0, // Would need cost basis tracking
-                    profit_loss_percent: 0.0,
+                    cost_basis_usd,
+                    profit_loss_usd,
+                    profit_loss_percent,
                 });
             }
         }
@@ -391,6 +433,8 @@ impl AnalyticsService {
             change_7d_percent,
             all_time_profit_loss_usd,
             all_time_profit_loss_percent,
+            realized_profit_loss_usd,
+            unrealized_profit_loss_usd,
             positions,
             timestamp: Utc::now(),
         })
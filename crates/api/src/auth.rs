@@ -1,6 +1,6 @@
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{RawPathParams, Request, State},
+    http::{header::AUTHORIZATION, Method, StatusCode},
     middleware::Next,
     response::Response,
 };
@@ -16,11 +16,50 @@ use rand::Rng;
 use totp_lite::{totp_custom, Sha1};
 use base32::{Alphabet, encode as base32_encode, decode as base32_decode};
 use qrcode::QrCode;
+use std::sync::Arc;
+use tracing::{debug, warn};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Scope granting read and write access to every user's resources
+pub const ADMIN_SCOPE: &str = "admin";
+/// Scope granting read-only access to every user's resources
+pub const ADMIN_READ_SCOPE: &str = "admin:read";
+/// Scope granting review and resolution of P2P disputes
+pub const ARBITRATOR_SCOPE: &str = "p2p:arbitrator";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // User ID
     pub exp: usize,  // Expiration time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+impl Claims {
+    /// The authenticated user ID, if the subject is a valid UUID
+    pub fn user_id(&self) -> Option<Uuid> {
+        Uuid::parse_str(&self.sub).ok()
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// Whether the caller may act on `user_id`'s resources with `method`
+    ///
+    /// Users may always access their own resources. `admin` grants access to
+    /// everyone's, `admin:read` only for safe (read-only) methods.
+    pub fn can_access_user(&self, user_id: Uuid, method: &Method) -> bool {
+        if self.user_id() == Some(user_id) || self.has_scope(ADMIN_SCOPE) {
+            return true;
+        }
+
+        self.has_scope(ADMIN_READ_SCOPE) && method.is_safe()
+    }
+
+    /// Whether the caller may review and resolve P2P disputes
+    pub fn is_arbitrator(&self) -> bool {
+        self.has_scope(ARBITRATOR_SCOPE) || self.has_scope(ADMIN_SCOPE)
+    }
 }
 
 pub struct JwtConfig {
@@ -65,6 +104,15 @@ impl JwtConfig {
     /// Generate JWT token with 24-hour expiration
     /// Requirements: 17.6
     pub fn generate_token(&self, user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
+        self.generate_token_with_scopes(user_id, Vec::new())
+    }
+
+    /// Generate JWT token carrying additional authorization scopes
+    pub fn generate_token_with_scopes(
+        &self,
+        user_id: Uuid,
+        scopes: Vec<String>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
         let expiration = chrono::Utc::now()
             .checked_add_signed(chrono::Duration::hours(self.expiration_hours))
             .expect("valid timestamp")
@@ -73,6 +121,7 @@ impl JwtConfig {
         let claims = Claims {
             sub: user_id.to_string(),
             exp: expiration,
+            scopes,
         };
 
         encode(
@@ -525,27 +574,66 @@ impl JwtConfig {
 }
 
 /// Middleware to verify JWT tokens
+///
+/// Rejects requests without a valid, unexpired bearer token and makes the
+/// decoded `Claims` available to later middleware and handlers through
+/// request extensions.
 pub async fn auth_middleware(
-    req: Request,
+    State(jwt_config): State<Arc<JwtConfig>>,
+    mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Extract token from Authorization header
-    let auth_header = req
+    let token = req
         .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok());
-
-    if let Some(auth_header) = auth_header {
-        if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            // In production, verify token here
-            // For MVP, we'll just check if it exists
-            if !token.is_empty() {
-                return Ok(next.run(req).await);
-            }
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let claims = jwt_config.verify_token(token).map_err(|e| {
+        debug!("Rejected bearer token: {}", e);
+        StatusCode::UNAUTHORIZED
+    })?;
+
+    if claims.user_id().is_none() {
+        debug!("Rejected bearer token with non-UUID subject {}", claims.sub);
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
+
+/// Middleware restricting `:user_id` routes to that user (or an admin)
+///
+/// Must be installed with `route_layer` inside `auth_middleware` so that both
+/// the matched path parameters and the caller's `Claims` are available.
+/// Routes without a `:user_id` segment pass through unchanged.
+pub async fn user_path_authorization(
+    params: RawPathParams,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if let Some((_, raw_user_id)) = params.iter().find(|(name, _)| *name == "user_id") {
+        let user_id = Uuid::parse_str(raw_user_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        if !claims.can_access_user(user_id, req.method()) {
+            warn!(
+                "User {} denied {} access to resources of user {}",
+                claims.sub,
+                req.method(),
+                user_id
+            );
+            return Err(StatusCode::FORBIDDEN);
         }
     }
 
-    Err(StatusCode::UNAUTHORIZED)
+    Ok(next.run(req).await)
 }
 
 #[cfg(test)]
@@ -571,6 +659,151 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_token_scopes_round_trip() {
+        let config = JwtConfig::new("test_secret".to_string());
+        let user_id = Uuid::new_v4();
+
+        let token = config
+            .generate_token_with_scopes(user_id, vec![ADMIN_READ_SCOPE.to_string()])
+            .unwrap();
+        let claims = config.verify_token(&token).unwrap();
+
+        assert_eq!(claims.user_id(), Some(user_id));
+        assert!(claims.has_scope(ADMIN_READ_SCOPE));
+        assert!(!claims.has_scope(ADMIN_SCOPE));
+
+        // Tokens issued before scopes existed still decode
+        let legacy = encode(
+            &Header::default(),
+            &serde_json::json!({ "sub": user_id.to_string(), "exp": claims.exp }),
+            &EncodingKey::from_secret(b"test_secret"),
+        )
+        .unwrap();
+        assert!(config.verify_token(&legacy).unwrap().scopes.is_empty());
+    }
+
+    #[test]
+    fn test_verify_token_rejects_wrong_secret_and_expired() {
+        let config = JwtConfig::new("test_secret".to_string());
+        let other = JwtConfig::new("other_secret".to_string());
+        let token = other.generate_token(Uuid::new_v4()).unwrap();
+        assert!(config.verify_token(&token).is_err());
+
+        let expired = JwtConfig {
+            secret: "test_secret".to_string(),
+            expiration_hours: -2,
+        };
+        let token = expired.generate_token(Uuid::new_v4()).unwrap();
+        assert!(config.verify_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_can_access_user() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let claims = |scopes: &[&str]| Claims {
+            sub: owner.to_string(),
+            exp: 0,
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        };
+
+        assert!(claims(&[]).can_access_user(owner, &Method::PUT));
+        assert!(!claims(&[]).can_access_user(other, &Method::GET));
+
+        assert!(claims(&[ADMIN_READ_SCOPE]).can_access_user(other, &Method::GET));
+        assert!(!claims(&[ADMIN_READ_SCOPE]).can_access_user(other, &Method::POST));
+
+        assert!(claims(&[ADMIN_SCOPE]).can_access_user(other, &Method::DELETE));
+
+        // Arbitrating disputes does not grant access to other users' resources
+        assert!(claims(&[ARBITRATOR_SCOPE]).is_arbitrator());
+        assert!(claims(&[ADMIN_SCOPE]).is_arbitrator());
+        assert!(!claims(&[ADMIN_READ_SCOPE]).is_arbitrator());
+        assert!(!claims(&[ARBITRATOR_SCOPE]).can_access_user(other, &Method::GET));
+    }
+
+    fn protected_router(config: Arc<JwtConfig>) -> axum::Router {
+        use axum::{middleware, routing::get};
+
+        axum::Router::new()
+            .route("/api/things/:user_id", get(|| async { "ok" }).put(|| async { "ok" }))
+            .route("/api/things", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn(user_path_authorization))
+            .route_layer(middleware::from_fn_with_state(config, auth_middleware))
+    }
+
+    async fn send(
+        router: &axum::Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+    ) -> StatusCode {
+        use tower::Service;
+
+        let mut request = axum::http::Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        router
+            .clone()
+            .call(request.body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_requires_valid_token() {
+        let config = Arc::new(JwtConfig::new("test_secret".to_string()));
+        let router = protected_router(config.clone());
+        let token = config.generate_token(Uuid::new_v4()).unwrap();
+        let forged = JwtConfig::new("forged".to_string())
+            .generate_token(Uuid::new_v4())
+            .unwrap();
+
+        assert_eq!(send(&router, Method::GET, "/api/things", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            send(&router, Method::GET, "/api/things", Some("not-a-jwt")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            send(&router, Method::GET, "/api/things", Some(&forged)).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(send(&router, Method::GET, "/api/things", Some(&token)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_user_path_authorization() {
+        let config = Arc::new(JwtConfig::new("test_secret".to_string()));
+        let router = protected_router(config.clone());
+        let owner = Uuid::new_v4();
+        let other_path = format!("/api/things/{}", Uuid::new_v4());
+        let owner_path = format!("/api/things/{}", owner);
+
+        let token = config.generate_token(owner).unwrap();
+        assert_eq!(send(&router, Method::GET, &owner_path, Some(&token)).await, StatusCode::OK);
+        assert_eq!(send(&router, Method::PUT, &owner_path, Some(&token)).await, StatusCode::OK);
+        assert_eq!(send(&router, Method::GET, &other_path, Some(&token)).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            send(&router, Method::GET, "/api/things/not-a-uuid", Some(&token)).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        let auditor = config
+            .generate_token_with_scopes(Uuid::new_v4(), vec![ADMIN_READ_SCOPE.to_string()])
+            .unwrap();
+        assert_eq!(send(&router, Method::GET, &other_path, Some(&auditor)).await, StatusCode::OK);
+        assert_eq!(send(&router, Method::PUT, &other_path, Some(&auditor)).await, StatusCode::FORBIDDEN);
+
+        let admin = config
+            .generate_token_with_scopes(Uuid::new_v4(), vec![ADMIN_SCOPE.to_string()])
+            .unwrap();
+        assert_eq!(send(&router, Method::PUT, &other_path, Some(&admin)).await, StatusCode::OK);
+    }
+
     #[test]
     fn test_password_hashing() {
         let config = JwtConfig::new("test_secret".to_string());
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use blockchain::{Blockchain, ChainRegistry};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use rust_decimal::Decimal;
@@ -13,25 +14,6 @@ use crate::error::{ApiError, ApiResult};
 const BIRDEYE_API_BASE: &str = "https://public-api.birdeye.so";
 const CACHE_TTL_SECONDS: u64 = 60;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum Blockchain {
-    Solana,
-    Ethereum,
-    BinanceSmartChain,
-    Polygon,
-}
-
-impl Blockchain {
-    pub fn to_birdeye_chain(&self) -> &str {
-        match self {
-            Blockchain::Solana => "solana",
-            Blockchain::Ethereum => "ethereum",
-            Blockchain::BinanceSmartChain => "bsc",
-            Blockchain::Polygon => "polygon",
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletAddress {
     pub blockchain: Blockchain,
@@ -98,7 +80,6 @@ struct BirdeyePriceResponse {
 #[derive(Debug, Deserialize)]
 struct BirdeyePriceData {
     value: f64,
-    #[allow(dead_code)]
     #[serde(rename = "updateUnixTime")]
     update_unix_time: i64,
 }
@@ -106,8 +87,10 @@ struct BirdeyePriceData {
 pub struct BirdeyeService {
     client: Client,
     api_key: String,
+    api_base: String,
     pub redis: redis::aio::ConnectionManager,
     circuit_breaker: Arc<blockchain::circuit_breaker::CircuitBreaker>,
+    chains: Arc<ChainRegistry>,
 }
 
 impl BirdeyeService {
@@ -131,11 +114,33 @@ impl BirdeyeService {
         Self {
             client,
             api_key,
+            api_base: BIRDEYE_API_BASE.to_string(),
             redis,
             circuit_breaker,
+            chains: Arc::new(ChainRegistry::default()),
         }
     }
 
+    /// Resolve chains against `chains` instead of the built-in registry
+    pub fn with_chain_registry(mut self, chains: Arc<ChainRegistry>) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    /// Birdeye's `x-chain` name for a chain, its registry slug
+    fn birdeye_chain(&self, blockchain: &Blockchain) -> ApiResult<String> {
+        self.chains
+            .get(blockchain)
+            .map(|chain| chain.slug.clone())
+            .ok_or_else(|| ApiError::ValidationError(format!("Unsupported blockchain: {}", blockchain)))
+    }
+
+    /// Send requests to `api_base` instead of the public Birdeye API
+    pub fn with_base_url(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
     /// Fetch portfolio positions across multiple blockchains
     pub async fn get_multi_chain_portfolio(
         &self,
@@ -145,12 +150,10 @@ impl BirdeyeService {
         let mut total_value_usd = Decimal::ZERO;
 
         for wallet in wallet_addresses {
+            let chain = self.birdeye_chain(&wallet.blockchain)?;
+
             // Check cache first
-            let cache_key = format!(
-                "birdeye:portfolio:{}:{}",
-                wallet.address,
-                wallet.blockchain.to_birdeye_chain()
-            );
+            let cache_key = format!("birdeye:portfolio:{}:{}", wallet.address, chain);
 
             let cached_assets: Option<Vec<Asset>> = self.get_from_cache(&cache_key).await
                 .map_err(|e| ApiError::InternalError(format!("Cache error: {}", e)))?;
@@ -161,7 +164,7 @@ impl BirdeyeService {
             } else {
                 tracing::debug!("Cache miss for portfolio: {}", cache_key);
                 let fetched = self
-                    .fetch_portfolio_from_api(&wallet.address, &wallet.blockchain)
+                    .fetch_portfolio_from_api(&wallet.address, &wallet.blockchain, &chain)
                     .await?;
                 self.set_in_cache(&cache_key, &fetched, CACHE_TTL_SECONDS)
                     .await
@@ -175,7 +178,7 @@ impl BirdeyeService {
             }
 
             positions_by_chain
-                .entry(wallet.blockchain.to_birdeye_chain().to_string())
+                .entry(chain)
                 .or_default()
                 .extend(assets);
         }
@@ -193,11 +196,8 @@ impl BirdeyeService {
         chain: &Blockchain,
         token_address: &str,
     ) -> ApiResult<PriceData> {
-        let cache_key = format!(
-            "birdeye:price:{}:{}",
-            chain.to_birdeye_chain(),
-            token_address
-        );
+        let birdeye_chain = self.birdeye_chain(chain)?;
+        let cache_key = format!("birdeye:price:{}:{}", birdeye_chain, token_address);
 
         // Check cache first (10 second TTL for prices)
         if let Some(cached) = self.get_from_cache::<PriceData>(&cache_key).await
@@ -207,7 +207,7 @@ impl BirdeyeService {
         }
 
         tracing::debug!("Cache miss for price: {}", cache_key);
-        let price_data = self.fetch_price_from_api(chain, token_address).await?;
+        let price_data = self.fetch_price_from_api(&birdeye_chain, token_address).await?;
         self.set_in_cache(&cache_key, &price_data, 10).await
             .map_err(|e| ApiError::InternalError(format!("Cache error: {}", e)))?;
 
@@ -219,6 +219,7 @@ impl BirdeyeService {
         &self,
         wallet_address: &str,
         blockchain: &Blockchain,
+        birdeye_chain: &str,
     ) -> ApiResult<Vec<Asset>> {
         // Check circuit breaker
         if !self.circuit_breaker.is_request_allowed().await {
@@ -235,7 +236,7 @@ impl BirdeyeService {
             attempts += 1;
 
             match self
-                .fetch_portfolio_from_api_once(wallet_address, blockchain)
+                .fetch_portfolio_from_api_once(wallet_address, blockchain, birdeye_chain)
                 .await
             {
                 Ok(assets) => {
@@ -270,17 +271,18 @@ impl BirdeyeService {
         &self,
         wallet_address: &str,
         blockchain: &Blockchain,
+        birdeye_chain: &str,
     ) -> Result<Vec<Asset>> {
         let url = format!(
             "{}/v1/wallet/token_list?wallet={}",
-            BIRDEYE_API_BASE, wallet_address
+            self.api_base, wallet_address
         );
 
         let response = self
             .client
             .get(&url)
             .header("X-API-KEY", &self.api_key)
-            .header("x-chain", blockchain.to_birdeye_chain())
+            .header("x-chain", birdeye_chain)
             .send()
             .await
             .context("Failed to send request to Birdeye API")?;
@@ -310,7 +312,7 @@ impl BirdeyeService {
 
     async fn fetch_price_from_api(
         &self,
-        blockchain: &Blockchain,
+        birdeye_chain: &str,
         token_address: &str,
     ) -> ApiResult<PriceData> {
         // Check circuit breaker
@@ -321,13 +323,13 @@ impl BirdeyeService {
             ));
         }
 
-        let url = format!("{}/defi/price?address={}", BIRDEYE_API_BASE, token_address);
+        let url = format!("{}/defi/price?address={}", self.api_base, token_address);
 
         let response = self
             .client
             .get(&url)
             .header("X-API-KEY", &self.api_key)
-            .header("x-chain", blockchain.to_birdeye_chain())
+            .header("x-chain", birdeye_chain)
             .send()
             .await
             .map_err(|e| {
@@ -365,7 +367,8 @@ impl BirdeyeService {
                 .unwrap_or(Decimal::ZERO),
             price_change_24h: None,
             volume_24h: None,
-            last_updated: Utc::now(),
+            last_updated: DateTime::from_timestamp(data.update_unix_time, 0)
+                .unwrap_or_else(Utc::now),
         })
     }
 
@@ -583,8 +583,13 @@ impl ChatService {
         };
 
         let receipt = self.receipt_service.create_receipt(receipt_data).await?;
-        
-        Ok(receipt.transaction_hash)
+
+        // Batched receipts have no transaction until their batch is anchored;
+        // until then the receipt hash identifies what will be anchored
+        Ok(receipt
+            .transaction_hash
+            .or(receipt.receipt_hash)
+            .unwrap_or(message_hash))
     }
 
     /// Store encrypted message in database
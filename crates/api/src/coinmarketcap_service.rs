@@ -89,6 +89,7 @@ pub struct CmcConversionResult {
 pub struct CoinMarketCapService {
     client: Client,
     api_key: String,
+    api_base: String,
     pub redis: redis::aio::ConnectionManager,
     circuit_breaker: Arc<blockchain::circuit_breaker::CircuitBreaker>,
 }
@@ -114,11 +115,18 @@ impl CoinMarketCapService {
         Self {
             client,
             api_key,
+            api_base: CMC_API_BASE.to_string(),
             redis,
             circuit_breaker,
         }
     }
 
+    /// Send v1 API requests to `api_base` instead of the CoinMarketCap Pro API
+    pub fn with_base_url(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
     /// Get latest price data for a cryptocurrency by symbol
     pub async fn get_price_by_symbol(&self, symbol: &str) -> ApiResult<CmcPriceData> {
         let cache_key = format!("cmc:price:symbol:{}", symbol.to_uppercase());
@@ -206,7 +214,7 @@ impl CoinMarketCapService {
 
         let url = format!(
             "{}/cryptocurrency/quotes/latest?symbol={}",
-            CMC_API_BASE,
+            self.api_base,
             symbol.to_uppercase()
         );
 
@@ -276,7 +284,7 @@ impl CoinMarketCapService {
 
         let url = format!(
             "{}/cryptocurrency/quotes/latest?symbol={}",
-            CMC_API_BASE,
+            self.api_base,
             symbols.to_uppercase()
         );
 
@@ -352,7 +360,7 @@ impl CoinMarketCapService {
 
         let url = format!(
             "{}/tools/price-conversion?amount={}&symbol={}&convert={}",
-            CMC_API_BASE,
+            self.api_base,
             amount_f64,
             from_symbol.to_uppercase(),
             to_symbol.to_uppercase()
@@ -434,7 +442,9 @@ impl CoinMarketCapService {
                 .and_then(|v| Decimal::from_f64_retain(v)),
             market_cap: crypto.quote.usd.market_cap
                 .and_then(|v| Decimal::from_f64_retain(v)),
-            last_updated: Utc::now(),
+            last_updated: DateTime::parse_from_rfc3339(&crypto.quote.usd.last_updated)
+                .map(|t| t.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
         }
     }
 
@@ -0,0 +1,1449 @@
+use chrono::{DateTime, Duration, Utc};
+use database::DbPool;
+use deadpool_postgres::Transaction;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use trading::{TradeRequest, TradingService};
+use uuid::Uuid;
+
+use crate::coinmarketcap_service::CoinMarketCapService;
+use crate::position_management_service::{ManualOrder, ManualOrderRequest, PositionManagementService};
+use crate::price_cache::PriceCache;
+use crate::price_sources::DEFAULT_MESH_MIN_CONFIDENCE;
+use crate::websocket_service::WebSocketService;
+
+/// Oldest mesh consensus price an order is evaluated against; older
+/// prices fall back to CoinMarketCap
+pub const MAX_MESH_PRICE_AGE_SECS: i64 = 120;
+
+/// Slippage tolerance, in percent, of the sell placed when an order fires
+const EXIT_SLIPPAGE_TOLERANCE: f64 = 1.0;
+
+/// How long an order may stay TRIGGERED before the monitor treats its sell
+/// as interrupted, e.g. by a restart
+pub const STALE_TRIGGER_SECS: i64 = 300;
+
+/// Failed sells after which an order is marked FAILED instead of firing again
+pub const MAX_SELL_ATTEMPTS: i32 = 3;
+
+/// Wait after each failed sell before the order may fire again, multiplied
+/// by the number of failed attempts
+pub const SELL_RETRY_BACKOFF_SECS: i64 = 60;
+
+const ORDER_COLUMNS: &str = "id, user_id, asset, blockchain, order_type, amount, trigger_price, \
+     trail_percent, trail_amount, peak_price, oco_group_id, status, manual_order_id, \
+     triggered_price, error_message, failed_attempts, created_at, updated_at, triggered_at";
+
+const TRANSITION_COLUMNS: &str = "id, order_id, from_status, to_status, price, reason, created_at";
+
+/// Kind of exit a conditional order places on a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConditionalOrderType {
+    /// Sell when the price falls to the trigger price
+    StopLoss,
+    /// Sell when the price rises to the trigger price
+    TakeProfit,
+    /// Sell when the price falls a set percentage or amount below the
+    /// highest price seen since the order was placed
+    TrailingStop,
+}
+
+impl ConditionalOrderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConditionalOrderType::StopLoss => "STOP_LOSS",
+            ConditionalOrderType::TakeProfit => "TAKE_PROFIT",
+            ConditionalOrderType::TrailingStop => "TRAILING_STOP",
+        }
+    }
+}
+
+impl fmt::Display for ConditionalOrderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConditionalOrderType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "STOP_LOSS" => Ok(ConditionalOrderType::StopLoss),
+            "TAKE_PROFIT" => Ok(ConditionalOrderType::TakeProfit),
+            "TRAILING_STOP" => Ok(ConditionalOrderType::TrailingStop),
+            other => Err(Error::Internal(format!("Invalid conditional order type: {}", other))),
+        }
+    }
+}
+
+/// Where a conditional order stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConditionalOrderStatus {
+    /// Watching the price
+    Active,
+    /// The price reached the order; its sell is being executed
+    Triggered,
+    /// The sell went through
+    Executed,
+    /// The sell was interrupted and may have gone through, or kept
+    /// failing; left for the user to check instead of selling again
+    Failed,
+    /// Cancelled by the user, or by another order of its one-cancels-other group
+    Cancelled,
+}
+
+impl ConditionalOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConditionalOrderStatus::Active => "ACTIVE",
+            ConditionalOrderStatus::Triggered => "TRIGGERED",
+            ConditionalOrderStatus::Executed => "EXECUTED",
+            ConditionalOrderStatus::Failed => "FAILED",
+            ConditionalOrderStatus::Cancelled => "CANCELLED",
+        }
+    }
+
+    /// Whether the order may move from this status to `next`
+    pub fn can_transition_to(&self, next: ConditionalOrderStatus) -> bool {
+        use ConditionalOrderStatus::*;
+
+        matches!(
+            (self, next),
+            (Active, Triggered)
+                | (Active, Cancelled)
+                | (Triggered, Executed)
+                | (Triggered, Active)
+                | (Triggered, Failed)
+        )
+    }
+
+    /// Whether the order can still change
+    pub fn is_open(&self) -> bool {
+        matches!(self, ConditionalOrderStatus::Active | ConditionalOrderStatus::Triggered)
+    }
+}
+
+impl fmt::Display for ConditionalOrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConditionalOrderStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ACTIVE" => Ok(ConditionalOrderStatus::Active),
+            "TRIGGERED" => Ok(ConditionalOrderStatus::Triggered),
+            "EXECUTED" => Ok(ConditionalOrderStatus::Executed),
+            "FAILED" => Ok(ConditionalOrderStatus::Failed),
+            "CANCELLED" => Ok(ConditionalOrderStatus::Cancelled),
+            other => Err(Error::Internal(format!("Invalid conditional order status: {}", other))),
+        }
+    }
+}
+
+/// Stop-loss, take-profit or trailing stop selling part of a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalOrder {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub asset: String,
+    pub blockchain: String,
+    pub order_type: ConditionalOrderType,
+    /// Amount of the asset sold when the order fires
+    pub amount: Decimal,
+    /// Fixed trigger of a stop-loss or take-profit order
+    pub trigger_price: Option<Decimal>,
+    /// Distance of a trailing stop below its peak, in percent
+    pub trail_percent: Option<Decimal>,
+    /// Distance of a trailing stop below its peak, in USD
+    pub trail_amount: Option<Decimal>,
+    /// Highest price a trailing stop has seen
+    pub peak_price: Option<Decimal>,
+    /// Orders of a one-cancels-other group share this id
+    pub oco_group_id: Option<Uuid>,
+    pub status: ConditionalOrderStatus,
+    /// Manual order the sell was executed through, once triggered
+    pub manual_order_id: Option<Uuid>,
+    pub triggered_price: Option<Decimal>,
+    pub error_message: Option<String>,
+    /// Sells that did not go through, counted towards [`MAX_SELL_ATTEMPTS`]
+    pub failed_attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub triggered_at: Option<DateTime<Utc>>,
+}
+
+impl ConditionalOrder {
+    /// Price at which the order fires
+    ///
+    /// A trailing stop has no stop price until it has seen a price.
+    pub fn stop_price(&self) -> Option<Decimal> {
+        match self.order_type {
+            ConditionalOrderType::StopLoss | ConditionalOrderType::TakeProfit => self.trigger_price,
+            ConditionalOrderType::TrailingStop => {
+                let peak = self.peak_price?;
+                match (self.trail_percent, self.trail_amount) {
+                    (Some(percent), _) => {
+                        Some(peak * (Decimal::ONE_HUNDRED - percent) / Decimal::ONE_HUNDRED)
+                    }
+                    (None, Some(amount)) => Some(peak - amount),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    /// Raise a trailing stop's peak to `price`, returning whether it moved
+    pub fn track(&mut self, price: Decimal) -> bool {
+        if self.order_type != ConditionalOrderType::TrailingStop
+            || self.peak_price.is_some_and(|peak| peak >= price)
+        {
+            return false;
+        }
+
+        self.peak_price = Some(price);
+        true
+    }
+
+    /// Whether the order fires at `price`
+    pub fn is_triggered_by(&self, price: Decimal) -> bool {
+        match (self.order_type, self.stop_price()) {
+            (ConditionalOrderType::TakeProfit, Some(stop)) => price >= stop,
+            (_, Some(stop)) => price <= stop,
+            (_, None) => false,
+        }
+    }
+}
+
+/// One status change of a conditional order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalOrderTransition {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub from_status: Option<ConditionalOrderStatus>,
+    pub to_status: ConditionalOrderStatus,
+    /// Price the order was evaluated at, for price-driven changes
+    pub price: Option<Decimal>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A stop-loss, take-profit or trailing stop on a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionalOrderRequest {
+    pub asset: String,
+    pub blockchain: Option<String>,
+    pub order_type: ConditionalOrderType,
+    pub amount: Decimal,
+    /// Required for stop-loss and take-profit orders
+    #[serde(default)]
+    pub trigger_price: Option<Decimal>,
+    /// Trailing stops set exactly one of `trail_percent` and `trail_amount`
+    #[serde(default)]
+    pub trail_percent: Option<Decimal>,
+    #[serde(default)]
+    pub trail_amount: Option<Decimal>,
+}
+
+impl ConditionalOrderRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.asset.trim().is_empty() {
+            return Err(Error::Validation("Asset is required".to_string()));
+        }
+        if self.amount <= Decimal::ZERO {
+            return Err(Error::Validation("Amount must be positive".to_string()));
+        }
+
+        match self.order_type {
+            ConditionalOrderType::StopLoss | ConditionalOrderType::TakeProfit => {
+                match self.trigger_price {
+                    Some(price) if price > Decimal::ZERO => {}
+                    Some(_) => return Err(Error::Validation("Trigger price must be positive".to_string())),
+                    None => {
+                        return Err(Error::Validation(format!(
+                            "{} orders require a trigger price",
+                            self.order_type
+                        )))
+                    }
+                }
+                if self.trail_percent.is_some() || self.trail_amount.is_some() {
+                    return Err(Error::Validation(format!(
+                        "{} orders do not trail",
+                        self.order_type
+                    )));
+                }
+            }
+            ConditionalOrderType::TrailingStop => {
+                if self.trigger_price.is_some() {
+                    return Err(Error::Validation(
+                        "Trailing stops follow the price and take no trigger price".to_string(),
+                    ));
+                }
+                match (self.trail_percent, self.trail_amount) {
+                    (Some(percent), None) => {
+                        if percent <= Decimal::ZERO || percent >= Decimal::ONE_HUNDRED {
+                            return Err(Error::Validation(
+                                "Trail percent must be between 0 and 100".to_string(),
+                            ));
+                        }
+                    }
+                    (None, Some(amount)) => {
+                        if amount <= Decimal::ZERO {
+                            return Err(Error::Validation("Trail amount must be positive".to_string()));
+                        }
+                    }
+                    _ => {
+                        return Err(Error::Validation(
+                            "Trailing stops require exactly one of trail_percent and trail_amount"
+                                .to_string(),
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A take-profit and a protective stop on the same amount of a position,
+/// one-cancels-other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketOrderRequest {
+    pub asset: String,
+    pub blockchain: Option<String>,
+    pub amount: Decimal,
+    pub take_profit_price: Decimal,
+    /// The protective leg is a stop-loss at this price, or a trailing stop
+    /// when `trail_percent` or `trail_amount` is set instead
+    #[serde(default)]
+    pub stop_loss_price: Option<Decimal>,
+    #[serde(default)]
+    pub trail_percent: Option<Decimal>,
+    #[serde(default)]
+    pub trail_amount: Option<Decimal>,
+}
+
+impl BracketOrderRequest {
+    /// The take-profit and protective legs of the bracket
+    pub fn legs(&self) -> Result<[ConditionalOrderRequest; 2]> {
+        let trails = self.trail_percent.is_some() || self.trail_amount.is_some();
+        if self.stop_loss_price.is_some() == trails {
+            return Err(Error::Validation(
+                "A bracket needs either a stop-loss price or a trailing stop".to_string(),
+            ));
+        }
+        if let Some(stop_loss_price) = self.stop_loss_price {
+            if stop_loss_price >= self.take_profit_price {
+                return Err(Error::Validation(
+                    "Stop-loss price must be below the take-profit price".to_string(),
+                ));
+            }
+        }
+
+        let take_profit = ConditionalOrderRequest {
+            asset: self.asset.clone(),
+            blockchain: self.blockchain.clone(),
+            order_type: ConditionalOrderType::TakeProfit,
+            amount: self.amount,
+            trigger_price: Some(self.take_profit_price),
+            trail_percent: None,
+            trail_amount: None,
+        };
+        let stop = ConditionalOrderRequest {
+            order_type: if trails {
+                ConditionalOrderType::TrailingStop
+            } else {
+                ConditionalOrderType::StopLoss
+            },
+            trigger_price: self.stop_loss_price,
+            trail_percent: self.trail_percent,
+            trail_amount: self.trail_amount,
+            ..take_profit.clone()
+        };
+
+        take_profit.validate()?;
+        stop.validate()?;
+        Ok([take_profit, stop])
+    }
+}
+
+/// Places conditional exit orders and executes them when the live price
+/// reaches them
+///
+/// Orders fire through a SELL manual order, which the trading service
+/// executes on-chain. A sell that fails returns the order to ACTIVE after
+/// a backoff, or marks it FAILED after [`MAX_SELL_ATTEMPTS`], and the rest
+/// of a one-cancels-other group is only cancelled once a sell has gone
+/// through. Every status change is recorded and pushed to the
+/// user's dashboards.
+pub struct ConditionalOrderService {
+    db: DbPool,
+    position_management_service: Arc<PositionManagementService>,
+    trading_service: Arc<TradingService>,
+    websocket_service: Option<Arc<WebSocketService>>,
+    price_cache: Option<Arc<PriceCache>>,
+    coinmarketcap_service: Option<Arc<CoinMarketCapService>>,
+}
+
+impl ConditionalOrderService {
+    pub fn new(
+        db: DbPool,
+        position_management_service: Arc<PositionManagementService>,
+        trading_service: Arc<TradingService>,
+    ) -> Self {
+        Self {
+            db,
+            position_management_service,
+            trading_service,
+            websocket_service: None,
+            price_cache: None,
+            coinmarketcap_service: None,
+        }
+    }
+
+    /// Push order status changes to the user's dashboards
+    pub fn with_websocket(mut self, websocket_service: Arc<WebSocketService>) -> Self {
+        self.websocket_service = Some(websocket_service);
+        self
+    }
+
+    /// Evaluate orders against the mesh consensus price when it is fresh
+    pub fn with_price_cache(mut self, price_cache: Arc<PriceCache>) -> Self {
+        self.price_cache = Some(price_cache);
+        self
+    }
+
+    /// Evaluate orders against CoinMarketCap, as the price monitor does,
+    /// when the mesh has no fresh price
+    pub fn with_coinmarketcap(mut self, coinmarketcap_service: Arc<CoinMarketCapService>) -> Self {
+        self.coinmarketcap_service = Some(coinmarketcap_service);
+        self
+    }
+
+    /// Place a stop-loss, take-profit or trailing stop on a position
+    pub async fn create_order(
+        &self,
+        user_id: Uuid,
+        request: ConditionalOrderRequest,
+    ) -> Result<ConditionalOrder> {
+        request.validate()?;
+
+        let mut orders = self.place(user_id, &[request], None).await?;
+        Ok(orders.remove(0))
+    }
+
+    /// Place a take-profit and a protective stop that cancel each other
+    pub async fn create_bracket(
+        &self,
+        user_id: Uuid,
+        request: BracketOrderRequest,
+    ) -> Result<Vec<ConditionalOrder>> {
+        let legs = request.legs()?;
+
+        self.place(user_id, &legs, Some(Uuid::new_v4())).await
+    }
+
+    /// Insert orders on the same amount of one position
+    async fn place(
+        &self,
+        user_id: Uuid,
+        requests: &[ConditionalOrderRequest],
+        oco_group_id: Option<Uuid>,
+    ) -> Result<Vec<ConditionalOrder>> {
+        let first = &requests[0];
+        let blockchain = first.blockchain.clone().unwrap_or_else(|| "Solana".to_string());
+
+        self.position_management_service
+            .validate_balance(user_id, &first.asset, &blockchain, first.amount)
+            .await?;
+
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let mut orders = Vec::with_capacity(requests.len());
+        for request in requests {
+            let row = transaction
+                .query_one(
+                    &format!(
+                        "INSERT INTO conditional_orders
+                            (id, user_id, asset, blockchain, order_type, amount, trigger_price,
+                             trail_percent, trail_amount, oco_group_id)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                         RETURNING {}",
+                        ORDER_COLUMNS
+                    ),
+                    &[
+                        &Uuid::new_v4(),
+                        &user_id,
+                        &request.asset,
+                        &blockchain,
+                        &request.order_type.as_str(),
+                        &request.amount,
+                        &request.trigger_price,
+                        &request.trail_percent,
+                        &request.trail_amount,
+                        &oco_group_id,
+                    ],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to create conditional order: {}", e)))?;
+            let order = row_to_order(&row)?;
+
+            record_transition(
+                &transaction,
+                order.id,
+                None,
+                ConditionalOrderStatus::Active,
+                None,
+                "Order placed",
+            )
+            .await?;
+            orders.push(order);
+        }
+
+        commit(transaction).await?;
+
+        for order in &orders {
+            info!(
+                "Conditional order {} placed for user {}: {} {} {}",
+                order.id, user_id, order.order_type, order.amount, order.asset
+            );
+            self.push(order, None);
+        }
+
+        Ok(orders)
+    }
+
+    /// Get one of the user's conditional orders
+    pub async fn get_order(&self, order_id: Uuid, user_id: Uuid) -> Result<ConditionalOrder> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM conditional_orders WHERE id = $1 AND user_id = $2",
+                    ORDER_COLUMNS
+                ),
+                &[&order_id, &user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query conditional order: {}", e)))?
+            .ok_or_else(|| Error::Validation(format!("Conditional order {} not found", order_id)))?;
+
+        row_to_order(&row)
+    }
+
+    /// Get the user's conditional orders, newest first
+    pub async fn get_user_orders(
+        &self,
+        user_id: Uuid,
+        status: Option<ConditionalOrderStatus>,
+        limit: Option<i64>,
+    ) -> Result<Vec<ConditionalOrder>> {
+        let limit = limit.unwrap_or(50).min(100);
+        let status = status.map(|s| s.as_str());
+
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM conditional_orders
+                     WHERE user_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+                     ORDER BY created_at DESC
+                     LIMIT $3",
+                    ORDER_COLUMNS
+                ),
+                &[&user_id, &status, &limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query conditional orders: {}", e)))?;
+
+        rows.iter().map(row_to_order).collect()
+    }
+
+    /// Status history of one of the user's conditional orders, oldest first
+    pub async fn get_transitions(
+        &self,
+        order_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<ConditionalOrderTransition>> {
+        self.get_order(order_id, user_id).await?;
+
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM conditional_order_transitions
+                     WHERE order_id = $1
+                     ORDER BY created_at",
+                    TRANSITION_COLUMNS
+                ),
+                &[&order_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query order transitions: {}", e)))?;
+
+        rows.iter().map(row_to_transition).collect()
+    }
+
+    /// Cancel an active order together with the rest of its
+    /// one-cancels-other group, returning the cancelled orders
+    pub async fn cancel_order(&self, order_id: Uuid, user_id: Uuid) -> Result<Vec<ConditionalOrder>> {
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let order = lock_order(&transaction, order_id).await?;
+        if order.user_id != user_id {
+            return Err(Error::Validation(format!("Conditional order {} not found", order_id)));
+        }
+        check_transition(&order, ConditionalOrderStatus::Cancelled)?;
+
+        let mut cancelled = vec![
+            transition(&transaction, &order, ConditionalOrderStatus::Cancelled, None, "Cancelled by user", None)
+                .await?,
+        ];
+        for sibling in lock_active_siblings(&transaction, &order).await? {
+            let reason = format!("Cancelled with order {}", order.id);
+            cancelled.push(
+                transition(&transaction, &sibling, ConditionalOrderStatus::Cancelled, None, &reason, None)
+                    .await?,
+            );
+        }
+
+        commit(transaction).await?;
+
+        for order in &cancelled {
+            info!("Conditional order {} cancelled by user {}", order.id, user_id);
+            self.push(order, None);
+        }
+
+        Ok(cancelled)
+    }
+
+    /// Evaluate the active orders on `asset` at `price`
+    ///
+    /// Trailing stops follow the price up; orders the price reaches are
+    /// triggered and executed. Orders whose last sell failed wait out their
+    /// backoff. Returns the orders that fired, in their final state.
+    pub async fn evaluate_price(&self, asset: &str, price: Decimal) -> Result<Vec<ConditionalOrder>> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM conditional_orders
+                     WHERE asset = $1 AND status = 'ACTIVE'
+                       AND (failed_attempts = 0
+                            OR triggered_at IS NULL
+                            OR triggered_at < NOW() - make_interval(secs => $2 * failed_attempts))
+                     ORDER BY created_at",
+                    ORDER_COLUMNS
+                ),
+                &[&asset, &(SELL_RETRY_BACKOFF_SECS as f64)],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query active conditional orders: {}", e)))?;
+        drop(client);
+
+        let mut fired = Vec::new();
+        for row in rows {
+            let mut order = row_to_order(&row)?;
+            let peak_moved = order.track(price);
+
+            if order.is_triggered_by(price) {
+                match self.trigger(&order, price).await {
+                    Ok(Some(order)) => fired.push(order),
+                    // Cancelled, or fired by another evaluation, since it was read
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to trigger conditional order {}: {}", order.id, e),
+                }
+            } else if peak_moved {
+                self.raise_peak(order.id, price).await?;
+            }
+        }
+
+        Ok(fired)
+    }
+
+    /// Persist a trailing stop's new peak
+    async fn raise_peak(&self, order_id: Uuid, price: Decimal) -> Result<()> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        client
+            .execute(
+                "UPDATE conditional_orders
+                 SET peak_price = GREATEST(COALESCE(peak_price, $2), $2), updated_at = NOW()
+                 WHERE id = $1 AND status = 'ACTIVE'",
+                &[&order_id, &price],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update trailing stop: {}", e)))?;
+
+        debug!("Trailing stop {} now follows a peak of {}", order_id, price);
+        Ok(())
+    }
+
+    /// Trigger an order the price has reached and execute its sell
+    ///
+    /// Returns `None` when the order is no longer active, or another order
+    /// of its group is already selling.
+    async fn trigger(&self, order: &ConditionalOrder, price: Decimal) -> Result<Option<ConditionalOrder>> {
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let (locked, siblings) = lock_group(&transaction, order.id).await?;
+        if locked.status != ConditionalOrderStatus::Active
+            || siblings.iter().any(|sibling| sibling.status == ConditionalOrderStatus::Triggered)
+        {
+            return Ok(None);
+        }
+
+        transaction
+            .execute(
+                "UPDATE conditional_orders
+                 SET triggered_price = $2, triggered_at = NOW(), peak_price = $3, manual_order_id = NULL
+                 WHERE id = $1",
+                &[&order.id, &price, &order.peak_price],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to trigger conditional order: {}", e)))?;
+
+        let reason = match order.stop_price() {
+            Some(stop) => format!("{} at {} reached {}", order.order_type, stop, price),
+            None => format!("{} reached {}", order.order_type, price),
+        };
+        let triggered = transition(
+            &transaction,
+            &locked,
+            ConditionalOrderStatus::Triggered,
+            Some(price),
+            &reason,
+            None,
+        )
+        .await?;
+
+        commit(transaction).await?;
+        drop(client);
+
+        info!(
+            "Conditional order {} triggered for user {}: {} {} at {}",
+            triggered.id, triggered.user_id, triggered.order_type, triggered.asset, price
+        );
+        self.push(&triggered, Some(price));
+
+        self.execute(triggered, price).await.map(Some)
+    }
+
+    /// Sell a triggered order's amount through a manual order
+    async fn execute(&self, order: ConditionalOrder, price: Decimal) -> Result<ConditionalOrder> {
+        let manual_order = match self
+            .position_management_service
+            .create_manual_order(
+                order.user_id,
+                ManualOrderRequest {
+                    asset: order.asset.clone(),
+                    blockchain: Some(order.blockchain.clone()),
+                    action: "SELL".to_string(),
+                    amount: order.amount,
+                },
+            )
+            .await
+        {
+            Ok(manual_order) => manual_order,
+            Err(e) => {
+                let message = e.to_string();
+                return self
+                    .finish(&order, ConditionalOrderStatus::Active, price, &message, Some(&message))
+                    .await;
+            }
+        };
+
+        // Linked before trading, so an interrupted sell can be told apart
+        // from one never placed
+        self.link_manual_order(order.id, &manual_order).await?;
+
+        let trade = self
+            .trading_service
+            .execute_trade(
+                TradeRequest {
+                    user_id: order.user_id,
+                    action: "SELL".to_string(),
+                    token_mint: order.asset.clone(),
+                    amount: order.amount.to_string(),
+                    slippage_tolerance: EXIT_SLIPPAGE_TOLERANCE,
+                    recommendation_id: None,
+                },
+                None,
+            )
+            .await;
+
+        match trade {
+            Ok(execution) => {
+                let price_usd = execution.price_usd.and_then(Decimal::from_f64_retain);
+                let total_value_usd = execution.total_value_usd.and_then(Decimal::from_f64_retain);
+                self.position_management_service
+                    .update_order_status(
+                        manual_order.id,
+                        "executed",
+                        Some(execution.transaction_signature.clone()),
+                        price_usd,
+                        total_value_usd,
+                        None,
+                    )
+                    .await?;
+
+                if let Some(websocket_service) = &self.websocket_service {
+                    websocket_service.broadcast_trade_executed(
+                        order.user_id,
+                        execution.id,
+                        order.asset.clone(),
+                        "SELL".to_string(),
+                        order.amount.to_string(),
+                        price_usd.unwrap_or(price).to_string(),
+                    );
+                }
+
+                let reason = format!("Sold in transaction {}", execution.transaction_signature);
+                self.finish(
+                    &order,
+                    ConditionalOrderStatus::Executed,
+                    price_usd.unwrap_or(price),
+                    &reason,
+                    None,
+                )
+                .await
+            }
+            Err(e) => {
+                let message = format!("Failed to execute trade: {}", e);
+                warn!("Conditional order {} could not be executed: {}", order.id, message);
+                self.position_management_service
+                    .update_order_status(manual_order.id, "failed", None, None, None, Some(message.clone()))
+                    .await?;
+
+                self.finish(&order, ConditionalOrderStatus::Active, price, &message, Some(&message))
+                    .await
+            }
+        }
+    }
+
+    async fn link_manual_order(&self, order_id: Uuid, manual_order: &ManualOrder) -> Result<()> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        client
+            .execute(
+                "UPDATE conditional_orders SET manual_order_id = $2, updated_at = NOW() WHERE id = $1",
+                &[&order_id, &manual_order.id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to link manual order: {}", e)))?;
+        Ok(())
+    }
+
+    /// Move a triggered order on once its sell has gone through, failed or
+    /// been interrupted
+    ///
+    /// An executed order cancels the rest of its one-cancels-other group.
+    /// Any other outcome counts as a failed attempt; a failed sell returns
+    /// the order to ACTIVE to fire again unless it has used up
+    /// [`MAX_SELL_ATTEMPTS`], in which case it is marked FAILED.
+    async fn finish(
+        &self,
+        order: &ConditionalOrder,
+        status: ConditionalOrderStatus,
+        price: Decimal,
+        reason: &str,
+        error_message: Option<&str>,
+    ) -> Result<ConditionalOrder> {
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let (locked, siblings) = lock_group(&transaction, order.id).await?;
+
+        let mut status = status;
+        let mut reason = reason.to_string();
+        if status != ConditionalOrderStatus::Executed {
+            transaction
+                .execute(
+                    "UPDATE conditional_orders SET failed_attempts = failed_attempts + 1 WHERE id = $1",
+                    &[&order.id],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to count sell attempt: {}", e)))?;
+
+            let attempts = locked.failed_attempts + 1;
+            if status == ConditionalOrderStatus::Active && attempts >= MAX_SELL_ATTEMPTS {
+                status = ConditionalOrderStatus::Failed;
+                reason = format!("{}; gave up after {} attempts", reason, attempts);
+            }
+        }
+        let finished = transition(&transaction, &locked, status, Some(price), &reason, error_message).await?;
+
+        let mut cancelled = Vec::new();
+        if status == ConditionalOrderStatus::Executed {
+            for sibling in siblings.iter().filter(|s| s.status == ConditionalOrderStatus::Active) {
+                let reason = format!("One-cancels-other: order {} executed", order.id);
+                cancelled.push(
+                    transition(
+                        &transaction,
+                        sibling,
+                        ConditionalOrderStatus::Cancelled,
+                        Some(price),
+                        &reason,
+                        None,
+                    )
+                    .await?,
+                );
+            }
+        }
+
+        commit(transaction).await?;
+
+        info!("Conditional order {} {}", finished.id, finished.status);
+        self.push(&finished, Some(price));
+        for sibling in &cancelled {
+            self.push(sibling, Some(price));
+        }
+        Ok(finished)
+    }
+
+    /// Settle orders left TRIGGERED for over [`STALE_TRIGGER_SECS`], whose
+    /// sell was interrupted, returning how many were settled
+    ///
+    /// An order whose manual order executed is executed, one whose sell was
+    /// never sent or failed returns to ACTIVE, and one whose sell may have
+    /// been sent is marked FAILED rather than selling twice.
+    pub async fn recover_stale_triggers(&self) -> Result<usize> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM conditional_orders
+                     WHERE status = 'TRIGGERED'
+                       AND COALESCE(triggered_at, updated_at) < NOW() - make_interval(secs => $1)",
+                    ORDER_COLUMNS
+                ),
+                &[&(STALE_TRIGGER_SECS as f64)],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query triggered conditional orders: {}", e)))?;
+        drop(client);
+
+        let mut recovered = 0;
+        for row in rows {
+            let order = row_to_order(&row)?;
+            let price = order.triggered_price.unwrap_or(Decimal::ZERO);
+            let manual_order = match order.manual_order_id {
+                Some(id) => Some(self.position_management_service.get_manual_order(id, order.user_id).await?),
+                None => None,
+            };
+
+            let (status, reason) = match manual_order.as_ref().map(|m| m.status.as_str()) {
+                Some("executed") => (ConditionalOrderStatus::Executed, "Sell found executed after an interruption"),
+                None | Some("failed") | Some("cancelled") => {
+                    (ConditionalOrderStatus::Active, "Sell interrupted before it was sent")
+                }
+                Some(_) => (
+                    ConditionalOrderStatus::Failed,
+                    "Sell interrupted after it may have been sent; check the manual order",
+                ),
+            };
+            let error_message = (status != ConditionalOrderStatus::Executed).then_some(reason);
+
+            match self.finish(&order, status, price, reason, error_message).await {
+                Ok(order) => {
+                    warn!("Recovered conditional order {} left triggered as {}", order.id, order.status);
+                    recovered += 1;
+                }
+                // Finished by its own sell since it was read
+                Err(Error::Validation(_)) => {}
+                Err(e) => error!("Failed to recover conditional order {}: {}", order.id, e),
+            }
+        }
+
+        Ok(recovered)
+    }
+
+    fn push(&self, order: &ConditionalOrder, price: Option<Decimal>) {
+        if let Some(websocket_service) = &self.websocket_service {
+            websocket_service.broadcast_conditional_order_updated(
+                order.user_id,
+                order.id,
+                order.asset.clone(),
+                order.order_type.as_str().to_string(),
+                order.status.as_str().to_string(),
+                price.map(|p| p.to_string()),
+            );
+        }
+    }
+
+    /// Live USD price of `asset`
+    ///
+    /// Prefers the mesh consensus when it is fresh and corroborated enough,
+    /// then CoinMarketCap.
+    pub async fn current_price(&self, asset: &str) -> Option<Decimal> {
+        if let Some(price_cache) = &self.price_cache {
+            match price_cache.consensus(asset).await {
+                Ok(Some(consensus))
+                    if consensus.confidence >= DEFAULT_MESH_MIN_CONFIDENCE
+                        && Utc::now() - consensus.timestamp
+                            <= Duration::seconds(MAX_MESH_PRICE_AGE_SECS) =>
+                {
+                    if let Ok(price) = Decimal::from_str(&consensus.price) {
+                        return Some(price);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to read mesh price for {}: {}", asset, e),
+            }
+        }
+
+        if let Some(coinmarketcap_service) = &self.coinmarketcap_service {
+            match coinmarketcap_service.get_price_by_symbol(asset).await {
+                Ok(data) => return Some(data.price_usd),
+                Err(e) => warn!("Failed to fetch price for {}: {:?}", asset, e),
+            }
+        }
+
+        None
+    }
+
+    /// Evaluate every asset with active orders at its live price
+    async fn check_active_orders(&self) -> Result<()> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                "SELECT DISTINCT asset FROM conditional_orders WHERE status = 'ACTIVE'",
+                &[],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query active assets: {}", e)))?;
+        drop(client);
+
+        for row in rows {
+            let asset: String = row.get(0);
+            let Some(price) = self.current_price(&asset).await else {
+                debug!("No live price for {}, skipping its conditional orders", asset);
+                continue;
+            };
+
+            if let Err(e) = self.evaluate_price(&asset, price).await {
+                error!("Error evaluating conditional orders for {}: {}", asset, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start the background worker that evaluates active orders against
+    /// live prices
+    pub fn start_monitor(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        info!("Starting conditional order monitor with interval: {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                // The first tick is immediate, so orders a restart
+                // interrupted are settled on startup
+                match self.recover_stale_triggers().await {
+                    Ok(0) => {}
+                    Ok(recovered) => info!("Recovered {} interrupted conditional orders", recovered),
+                    Err(e) => error!("Error recovering triggered conditional orders: {}", e),
+                }
+
+                if let Err(e) = self.check_active_orders().await {
+                    error!("Error checking conditional orders: {}", e);
+                }
+            }
+        })
+    }
+}
+
+async fn lock_order(transaction: &Transaction<'_>, order_id: Uuid) -> Result<ConditionalOrder> {
+    let row = transaction
+        .query_opt(
+            &format!("SELECT {} FROM conditional_orders WHERE id = $1 FOR UPDATE", ORDER_COLUMNS),
+            &[&order_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock conditional order: {}", e)))?
+        .ok_or_else(|| Error::Validation(format!("Conditional order {} not found", order_id)))?;
+
+    row_to_order(&row)
+}
+
+/// Lock an order and the other orders of its one-cancels-other group, in
+/// id order so concurrent triggers of the group cannot deadlock
+async fn lock_group(
+    transaction: &Transaction<'_>,
+    order_id: Uuid,
+) -> Result<(ConditionalOrder, Vec<ConditionalOrder>)> {
+    let rows = transaction
+        .query(
+            &format!(
+                "SELECT {} FROM conditional_orders
+                 WHERE id = $1
+                    OR oco_group_id = (SELECT oco_group_id FROM conditional_orders WHERE id = $1)
+                 ORDER BY id
+                 FOR UPDATE",
+                ORDER_COLUMNS
+            ),
+            &[&order_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock order group: {}", e)))?;
+
+    let (orders, siblings): (Vec<_>, Vec<_>) = rows
+        .iter()
+        .map(row_to_order)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .partition(|order| order.id == order_id);
+    let order = orders
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Validation(format!("Conditional order {} not found", order_id)))?;
+
+    Ok((order, siblings))
+}
+
+/// Lock the other active orders of `order`'s one-cancels-other group
+async fn lock_active_siblings(
+    transaction: &Transaction<'_>,
+    order: &ConditionalOrder,
+) -> Result<Vec<ConditionalOrder>> {
+    let Some(oco_group_id) = order.oco_group_id else {
+        return Ok(Vec::new());
+    };
+
+    let rows = transaction
+        .query(
+            &format!(
+                "SELECT {} FROM conditional_orders
+                 WHERE oco_group_id = $1 AND id <> $2 AND status = 'ACTIVE'
+                 FOR UPDATE",
+                ORDER_COLUMNS
+            ),
+            &[&oco_group_id, &order.id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock order group: {}", e)))?;
+
+    rows.iter().map(row_to_order).collect()
+}
+
+fn check_transition(order: &ConditionalOrder, next: ConditionalOrderStatus) -> Result<()> {
+    if order.status.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "Conditional order {} cannot move from {} to {}",
+            order.id, order.status, next
+        )))
+    }
+}
+
+/// Move a locked order to `next` and record the change
+async fn transition(
+    transaction: &Transaction<'_>,
+    order: &ConditionalOrder,
+    next: ConditionalOrderStatus,
+    price: Option<Decimal>,
+    reason: &str,
+    error_message: Option<&str>,
+) -> Result<ConditionalOrder> {
+    check_transition(order, next)?;
+
+    let row = transaction
+        .query_one(
+            &format!(
+                "UPDATE conditional_orders
+                 SET status = $2, error_message = COALESCE($3, error_message), updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING {}",
+                ORDER_COLUMNS
+            ),
+            &[&order.id, &next.as_str(), &error_message],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update conditional order: {}", e)))?;
+
+    record_transition(transaction, order.id, Some(order.status), next, price, reason).await?;
+
+    row_to_order(&row)
+}
+
+async fn record_transition(
+    transaction: &Transaction<'_>,
+    order_id: Uuid,
+    from_status: Option<ConditionalOrderStatus>,
+    to_status: ConditionalOrderStatus,
+    price: Option<Decimal>,
+    reason: &str,
+) -> Result<()> {
+    transaction
+        .execute(
+            "INSERT INTO conditional_order_transitions
+                (id, order_id, from_status, to_status, price, reason, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, clock_timestamp())",
+            &[
+                &Uuid::new_v4(),
+                &order_id,
+                &from_status.map(|s| s.as_str()),
+                &to_status.as_str(),
+                &price,
+                &reason,
+            ],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record order transition: {}", e)))?;
+
+    Ok(())
+}
+
+async fn commit(transaction: Transaction<'_>) -> Result<()> {
+    transaction
+        .commit()
+        .await
+        .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+}
+
+fn row_to_order(row: &tokio_postgres::Row) -> Result<ConditionalOrder> {
+    let order_type: String = row.get("order_type");
+    let status: String = row.get("status");
+
+    Ok(ConditionalOrder {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        asset: row.get("asset"),
+        blockchain: row.get("blockchain"),
+        order_type: order_type.parse()?,
+        amount: row.get("amount"),
+        trigger_price: row.get("trigger_price"),
+        trail_percent: row.get("trail_percent"),
+        trail_amount: row.get("trail_amount"),
+        peak_price: row.get("peak_price"),
+        oco_group_id: row.get("oco_group_id"),
+        status: status.parse()?,
+        manual_order_id: row.get("manual_order_id"),
+        triggered_price: row.get("triggered_price"),
+        error_message: row.get("error_message"),
+        failed_attempts: row.get("failed_attempts"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        triggered_at: row.get("triggered_at"),
+    })
+}
+
+fn row_to_transition(row: &tokio_postgres::Row) -> Result<ConditionalOrderTransition> {
+    let from_status: Option<String> = row.get("from_status");
+    let to_status: String = row.get("to_status");
+
+    Ok(ConditionalOrderTransition {
+        id: row.get("id"),
+        order_id: row.get("order_id"),
+        from_status: from_status.map(|s| s.parse()).transpose()?,
+        to_status: to_status.parse()?,
+        price: row.get("price"),
+        reason: row.get("reason"),
+        created_at: row.get("created_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_type: ConditionalOrderType) -> ConditionalOrder {
+        ConditionalOrder {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            asset: "SOL".to_string(),
+            blockchain: "Solana".to_string(),
+            order_type,
+            amount: Decimal::from(5),
+            trigger_price: None,
+            trail_percent: None,
+            trail_amount: None,
+            peak_price: None,
+            oco_group_id: None,
+            status: ConditionalOrderStatus::Active,
+            manual_order_id: None,
+            triggered_price: None,
+            error_message: None,
+            failed_attempts: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            triggered_at: None,
+        }
+    }
+
+    fn request(order_type: ConditionalOrderType) -> ConditionalOrderRequest {
+        ConditionalOrderRequest {
+            asset: "SOL".to_string(),
+            blockchain: None,
+            order_type,
+            amount: Decimal::from(5),
+            trigger_price: None,
+            trail_percent: None,
+            trail_amount: None,
+        }
+    }
+
+    #[test]
+    fn test_stop_loss_and_take_profit_fire_on_opposite_sides() {
+        let mut stop_loss = order(ConditionalOrderType::StopLoss);
+        stop_loss.trigger_price = Some(Decimal::from(90));
+        assert!(!stop_loss.is_triggered_by(Decimal::from(95)));
+        assert!(stop_loss.is_triggered_by(Decimal::from(90)));
+        assert!(stop_loss.is_triggered_by(Decimal::from(80)));
+
+        let mut take_profit = order(ConditionalOrderType::TakeProfit);
+        take_profit.trigger_price = Some(Decimal::from(120));
+        assert!(!take_profit.is_triggered_by(Decimal::from(110)));
+        assert!(take_profit.is_triggered_by(Decimal::from(120)));
+        assert!(take_profit.is_triggered_by(Decimal::from(130)));
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_the_peak_up_only() {
+        let mut trailing = order(ConditionalOrderType::TrailingStop);
+        trailing.trail_percent = Some(Decimal::from(10));
+        assert_eq!(trailing.stop_price(), None);
+        assert!(!trailing.is_triggered_by(Decimal::from(1)));
+
+        assert!(trailing.track(Decimal::from(100)));
+        assert_eq!(trailing.stop_price(), Some(Decimal::from(90)));
+        assert!(trailing.track(Decimal::from(150)));
+        assert_eq!(trailing.stop_price(), Some(Decimal::from(135)));
+
+        // A falling price leaves the stop where it was
+        assert!(!trailing.track(Decimal::from(140)));
+        assert_eq!(trailing.stop_price(), Some(Decimal::from(135)));
+        assert!(!trailing.is_triggered_by(Decimal::from(140)));
+        assert!(trailing.is_triggered_by(Decimal::from(135)));
+
+        let mut absolute = order(ConditionalOrderType::TrailingStop);
+        absolute.trail_amount = Some(Decimal::from(15));
+        absolute.track(Decimal::from(100));
+        assert_eq!(absolute.stop_price(), Some(Decimal::from(85)));
+
+        // Fixed orders have no peak
+        let mut stop_loss = order(ConditionalOrderType::StopLoss);
+        stop_loss.trigger_price = Some(Decimal::from(90));
+        assert!(!stop_loss.track(Decimal::from(200)));
+        assert_eq!(stop_loss.peak_price, None);
+    }
+
+    #[test]
+    fn test_request_validation() {
+        let mut stop_loss = request(ConditionalOrderType::StopLoss);
+        assert!(stop_loss.validate().is_err());
+        stop_loss.trigger_price = Some(Decimal::from(90));
+        assert!(stop_loss.validate().is_ok());
+        stop_loss.trail_percent = Some(Decimal::from(5));
+        assert!(stop_loss.validate().is_err());
+
+        let mut trailing = request(ConditionalOrderType::TrailingStop);
+        assert!(trailing.validate().is_err());
+        trailing.trail_percent = Some(Decimal::from(100));
+        assert!(trailing.validate().is_err());
+        trailing.trail_percent = Some(Decimal::from(5));
+        assert!(trailing.validate().is_ok());
+        trailing.trail_amount = Some(Decimal::from(5));
+        assert!(trailing.validate().is_err());
+
+        let mut zero = request(ConditionalOrderType::TakeProfit);
+        zero.trigger_price = Some(Decimal::from(120));
+        zero.amount = Decimal::ZERO;
+        assert!(zero.validate().is_err());
+    }
+
+    #[test]
+    fn test_bracket_legs() {
+        let mut bracket = BracketOrderRequest {
+            asset: "SOL".to_string(),
+            blockchain: None,
+            amount: Decimal::from(5),
+            take_profit_price: Decimal::from(120),
+            stop_loss_price: Some(Decimal::from(90)),
+            trail_percent: None,
+            trail_amount: None,
+        };
+        let [take_profit, stop] = bracket.legs().unwrap();
+        assert_eq!(take_profit.order_type, ConditionalOrderType::TakeProfit);
+        assert_eq!(stop.order_type, ConditionalOrderType::StopLoss);
+        assert_eq!(stop.amount, take_profit.amount);
+
+        bracket.trail_amount = Some(Decimal::from(10));
+        assert!(bracket.legs().is_err());
+        bracket.stop_loss_price = None;
+        let [_, stop] = bracket.legs().unwrap();
+        assert_eq!(stop.order_type, ConditionalOrderType::TrailingStop);
+
+        bracket.trail_amount = None;
+        bracket.stop_loss_price = Some(Decimal::from(130));
+        assert!(bracket.legs().is_err());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use ConditionalOrderStatus::*;
+
+        assert!(Active.can_transition_to(Triggered));
+        assert!(Active.can_transition_to(Cancelled));
+        assert!(Triggered.can_transition_to(Executed));
+        assert!(Triggered.can_transition_to(Active));
+        assert!(Triggered.can_transition_to(Failed));
+        assert!(!Triggered.can_transition_to(Cancelled));
+        assert!(!Executed.can_transition_to(Cancelled));
+        assert!(!Cancelled.can_transition_to(Active));
+
+        for status in [Active, Triggered, Executed, Failed, Cancelled] {
+            assert_eq!(status.as_str().parse::<ConditionalOrderStatus>().unwrap(), status);
+        }
+    }
+}
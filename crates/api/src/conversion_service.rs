@@ -116,7 +116,7 @@ impl ConversionService {
         }
 
         // Execute based on provider
-        let result = match quote.provider {
+        let mut result = match quote.provider {
             ConversionProvider::SideShift => {
                 self.execute_sideshift_conversion(&quote, settle_address, refund_address)
                     .await?
@@ -149,11 +149,12 @@ impl ConversionService {
                 )
                 .await
             {
-                Ok(_) => {
+                Ok(receipt) => {
                     tracing::info!(
                         "Blockchain receipt created for conversion {}",
                         conversion_id
                     );
+                    result.receipt_id = Some(receipt.id);
                 }
                 Err(e) => {
                     tracing::warn!(
@@ -235,6 +236,7 @@ impl ConversionService {
             status: ConversionStatus::Pending,
             transaction_hash: None,
             provider: ConversionProvider::SideShift,
+            receipt_id: None,
         })
     }
 
@@ -274,6 +276,7 @@ impl ConversionService {
             status: ConversionStatus::Pending,
             transaction_hash: None,
             provider: ConversionProvider::Jupiter,
+            receipt_id: None,
         })
     }
 
@@ -470,6 +473,8 @@ pub struct ConversionResult {
     pub status: ConversionStatus,
     pub transaction_hash: Option<String>,
     pub provider: ConversionProvider,
+    /// Blockchain receipt of the conversion, when receipts are enabled
+    pub receipt_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
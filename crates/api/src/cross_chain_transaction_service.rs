@@ -1,4 +1,4 @@
-use blockchain::{Blockchain, MultiChainClient};
+use blockchain::{Blockchain, ChainConfig, ChainKind, MultiChainClient};
 use chrono::{DateTime, Utc};
 use database::DbPool;
 use rust_decimal::Decimal;
@@ -75,15 +75,14 @@ impl CrossChainTransactionService {
         transaction_hash: &str,
     ) -> Result<NormalizedTransaction> {
         debug!(
-            "Normalizing transaction {} on {:?}",
+            "Normalizing transaction {} on {}",
             transaction_hash, blockchain
         );
         
-        match blockchain {
-            Blockchain::Solana => self.normalize_solana_transaction(transaction_hash).await,
-            Blockchain::Ethereum | Blockchain::BinanceSmartChain | Blockchain::Polygon => {
-                self.normalize_evm_transaction(blockchain, transaction_hash).await
-            }
+        let chain = self.multi_chain_client.registry().require(&blockchain)?;
+        match chain.kind {
+            ChainKind::Solana => self.normalize_solana_transaction(transaction_hash).await,
+            ChainKind::Evm => self.normalize_evm_transaction(chain, transaction_hash).await,
         }
     }
     
@@ -96,7 +95,7 @@ impl CrossChainTransactionService {
         // Full implementation would query Solana RPC for transaction details
         Ok(NormalizedTransaction {
             id: Uuid::new_v4(),
-            blockchain: Blockchain::Solana,
+            blockchain: Blockchain::SOLANA,
             from_address: "".to_string(),
             to_address: "".to_string(),
             amount: Decimal::ZERO,
@@ -116,24 +115,19 @@ impl CrossChainTransactionService {
         })
     }
     
-    /// Normalize an EVM transaction (Ethereum, BSC, Polygon)
+    /// Normalize an EVM transaction
     async fn normalize_evm_transaction(
         &self,
-        blockchain: Blockchain,
+        chain: &ChainConfig,
         transaction_hash: &str,
     ) -> Result<NormalizedTransaction> {
         // For now, return a placeholder
         // Full implementation would query EVM RPC for transaction details
-        let native_token = match blockchain {
-            Blockchain::Ethereum => "ETH",
-            Blockchain::BinanceSmartChain => "BNB",
-            Blockchain::Polygon => "MATIC",
-            _ => "UNKNOWN",
-        };
+        let native_token = &chain.native_token.symbol;
         
         Ok(NormalizedTransaction {
             id: Uuid::new_v4(),
-            blockchain,
+            blockchain: chain.blockchain(),
             from_address: "".to_string(),
             to_address: "".to_string(),
             amount: Decimal::ZERO,
@@ -163,15 +157,14 @@ impl CrossChainTransactionService {
         amount: Decimal,
     ) -> Result<TransactionFees> {
         debug!(
-            "Calculating transaction fees for {:?} blockchain",
+            "Calculating transaction fees for {} blockchain",
             blockchain
         );
         
-        match blockchain {
-            Blockchain::Solana => self.calculate_solana_fees(token_address, amount).await,
-            Blockchain::Ethereum => self.calculate_ethereum_fees(token_address, amount).await,
-            Blockchain::BinanceSmartChain => self.calculate_bsc_fees(token_address, amount).await,
-            Blockchain::Polygon => self.calculate_polygon_fees(token_address, amount).await,
+        let chain = self.multi_chain_client.registry().require(&blockchain)?;
+        match chain.kind {
+            ChainKind::Solana => self.calculate_solana_fees(token_address, amount).await,
+            ChainKind::Evm => self.calculate_evm_fees(chain, token_address).await,
         }
     }
     
@@ -199,13 +192,16 @@ impl CrossChainTransactionService {
         })
     }
     
-    /// Calculate Ethereum transaction fees
-    async fn calculate_ethereum_fees(
+    /// Calculate EVM transaction fees in the chain's native token
+    /// 
+    /// Uses the chain's current base and priority fees when an RPC endpoint is
+    /// configured, and a typical gas price for the built-in chains otherwise.
+    async fn calculate_evm_fees(
         &self,
+        chain: &ChainConfig,
         token_address: Option<&str>,
-        _amount: Decimal,
     ) -> Result<TransactionFees> {
-        // Base gas units for ETH transfer: 21000
+        // Base gas units for a native transfer: 21000
         // ERC-20 transfer: ~65000
         let gas_units = if token_address.is_some() {
             Decimal::new(65000, 0)
@@ -213,74 +209,45 @@ impl CrossChainTransactionService {
             Decimal::new(21000, 0)
         };
         
-        // Typical gas price: 20 gwei (20 * 10^9 wei)
-        let gas_price_gwei = Decimal::new(20, 0);
-        let gas_price_eth = gas_price_gwei / Decimal::new(1_000_000_000, 0);
-        
-        let network_fee = gas_units * gas_price_eth;
-        
-        Ok(TransactionFees {
-            gas_fee: Some(gas_units),
-            network_fee,
-            platform_fee: None,
-            total_fee: network_fee,
-            fee_currency: "ETH".to_string(),
-        })
-    }
-    
-    /// Calculate Binance Smart Chain transaction fees
-    async fn calculate_bsc_fees(
-        &self,
-        token_address: Option<&str>,
-        _amount: Decimal,
-    ) -> Result<TransactionFees> {
-        // BSC has lower gas prices than Ethereum
-        let gas_units = if token_address.is_some() {
-            Decimal::new(65000, 0)
-        } else {
-            Decimal::new(21000, 0)
-        };
-        
-        // Typical gas price: 5 gwei
-        let gas_price_gwei = Decimal::new(5, 0);
-        let gas_price_bnb = gas_price_gwei / Decimal::new(1_000_000_000, 0);
-        
-        let network_fee = gas_units * gas_price_bnb;
-        
-        Ok(TransactionFees {
-            gas_fee: Some(gas_units),
-            network_fee,
-            platform_fee: None,
-            total_fee: network_fee,
-            fee_currency: "BNB".to_string(),
-        })
-    }
-    
-    /// Calculate Polygon transaction fees
-    async fn calculate_polygon_fees(
-        &self,
-        token_address: Option<&str>,
-        _amount: Decimal,
-    ) -> Result<TransactionFees> {
-        // Polygon has very low gas prices
-        let gas_units = if token_address.is_some() {
-            Decimal::new(65000, 0)
-        } else {
-            Decimal::new(21000, 0)
+        let gas_price_wei = match self.multi_chain_client.evm(&chain.blockchain()) {
+            Some(client) => {
+                let history = client.get_fee_history(1, &[50.0]).await?;
+                let base_fee = history.next_base_fee().unwrap_or_default();
+                let priority_fee = history
+                    .reward
+                    .last()
+                    .and_then(|rewards| rewards.first().copied())
+                    .unwrap_or_default();
+                Decimal::from(base_fee + priority_fee)
+            }
+            None => {
+                let typical_gwei = match chain.chain_id {
+                    Some(1) => 20,
+                    // BSC has lower gas prices than Ethereum
+                    Some(56) => 5,
+                    Some(137) => 30,
+                    _ => {
+                        return Err(Error::Validation(format!(
+                            "No RPC endpoint configured to estimate fees on {}",
+                            chain.name
+                        )))
+                    }
+                };
+                Decimal::new(typical_gwei, 0) * Decimal::new(1_000_000_000, 0)
+            }
         };
         
-        // Typical gas price: 30 gwei (but MATIC is cheaper than ETH)
-        let gas_price_gwei = Decimal::new(30, 0);
-        let gas_price_matic = gas_price_gwei / Decimal::new(1_000_000_000, 0);
-        
-        let network_fee = gas_units * gas_price_matic;
+        let wei = Decimal::try_new(1, u32::from(chain.native_token.decimals)).map_err(|e| {
+            Error::Internal(format!("Unsupported {} decimals: {}", chain.native_token.symbol, e))
+        })?;
+        let network_fee = gas_units * gas_price_wei * wei;
         
         Ok(TransactionFees {
             gas_fee: Some(gas_units),
             network_fee,
             platform_fee: None,
             total_fee: network_fee,
-            fee_currency: "MATIC".to_string(),
+            fee_currency: chain.native_token.symbol.clone(),
         })
     }
     
@@ -325,7 +292,7 @@ impl CrossChainTransactionService {
         fees: &TransactionFees,
     ) -> Result<()> {
         info!(
-            "Recording gas fee for transaction {} on {:?}",
+            "Recording gas fee for transaction {} on {}",
             transaction_hash, blockchain
         );
         
@@ -333,7 +300,7 @@ impl CrossChainTransactionService {
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
         
-        let blockchain_str = format!("{:?}", blockchain);
+        let blockchain_str = blockchain.id();
         
         client
             .execute(
@@ -387,12 +354,8 @@ impl CrossChainTransactionService {
             let total_fee: Decimal = row.get(1);
             let fee_currency: String = row.get(2);
             
-            let blockchain = match blockchain_str.as_str() {
-                "Solana" => Blockchain::Solana,
-                "Ethereum" => Blockchain::Ethereum,
-                "BinanceSmartChain" => Blockchain::BinanceSmartChain,
-                "Polygon" => Blockchain::Polygon,
-                _ => continue,
+            let Ok(blockchain) = self.multi_chain_client.registry().resolve(&blockchain_str) else {
+                continue;
             };
             
             results.push((blockchain, total_fee, fee_currency));
@@ -434,7 +397,7 @@ mod tests {
     fn test_normalized_transaction_structure() {
         let tx = NormalizedTransaction {
             id: Uuid::new_v4(),
-            blockchain: Blockchain::Ethereum,
+            blockchain: Blockchain::ETHEREUM,
             from_address: "0x123".to_string(),
             to_address: "0x456".to_string(),
             amount: Decimal::new(100, 0),
@@ -453,7 +416,7 @@ mod tests {
             confirmations: 12,
         };
         
-        assert_eq!(tx.blockchain, Blockchain::Ethereum);
+        assert_eq!(tx.blockchain, Blockchain::ETHEREUM);
         assert_eq!(tx.status, TransactionStatus::Confirmed);
         assert_eq!(tx.confirmations, 12);
     }
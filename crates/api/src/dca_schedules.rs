@@ -0,0 +1,1763 @@
+use blockchain::{Blockchain, ChainRegistry};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use database::DbPool;
+use deadpool_postgres::Transaction;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+use trading::{TradeRequest, TradingService};
+use uuid::Uuid;
+
+use crate::coinmarketcap_service::CoinMarketCapService;
+use crate::conversion_service::ConversionService;
+use crate::payment_receipt_service::PaymentReceiptService;
+use crate::position_management_service::{ManualOrderRequest, PositionManagementService};
+use crate::sideshift_client::AmountType;
+use crate::tax_lots::usd_value;
+
+/// Shortest interval between the runs of a schedule
+pub const MIN_INTERVAL_SECS: i64 = 60;
+
+/// Longest interval between the runs of a schedule
+pub const MAX_INTERVAL_SECS: i64 = 366 * 24 * 60 * 60;
+
+/// Most missed runs a schedule catches up on at once; older ones are dropped
+pub const MAX_CATCH_UP_RUNS: usize = 24;
+
+/// How late a run may start and still count as on time rather than missed
+pub const DEFAULT_GRACE_PERIOD_SECS: i64 = 300;
+
+/// Smallest run value, in USD, worth making; a budget with less left is spent
+pub const MIN_RUN_VALUE_USD: Decimal = Decimal::ONE;
+
+/// Slippage tolerance, in percent, of the trades placed by schedules
+const DCA_SLIPPAGE_TOLERANCE: f64 = 1.0;
+
+/// Counterparty named on receipts of scheduled trades, which swap through
+/// Jupiter
+const SWAP_COUNTERPARTY: &str = "Jupiter";
+
+/// How far ahead a cron expression is searched for its next match; long
+/// enough to reach a 29 February
+const CRON_SEARCH_DAYS: i64 = 366 * 8;
+
+const SCHEDULE_COLUMNS: &str = "id, user_id, asset, quote_asset, blockchain, action, amount, venue, \
+     settle_address, interval_secs, cron_expression, price_ceiling, price_floor, end_at, \
+     total_budget, total_spent, run_count, catch_up_policy, status, next_run_at, last_run_at, \
+     created_at, updated_at";
+
+const RUN_COLUMNS: &str = "id, schedule_id, scheduled_for, status, amount, price, value, \
+     manual_order_id, transaction_hash, receipt_id, reason, created_at";
+
+/// Five-field cron expression: minute, hour, day of month, month and day
+/// of week, evaluated in UTC
+///
+/// Fields take `*`, single values, `a-b` ranges, comma-separated lists and
+/// `/n` steps. Day of week runs 0-7 with both 0 and 7 meaning Sunday. As in
+/// cron, when both day fields are restricted a day matching either one runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Error::Validation(format!(
+                "Cron expression must have five fields: {}",
+                expression
+            )));
+        }
+
+        let mut weekdays = parse_cron_field(fields[4], 0, 7, "day of week")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59, "minute")?,
+            hours: parse_cron_field(fields[1], 0, 23, "hour")?,
+            days: parse_cron_field(fields[2], 1, 31, "day of month")?,
+            months: parse_cron_field(fields[3], 1, 12, "month")?,
+            weekdays,
+            days_restricted: !fields[2].starts_with('*'),
+            weekdays_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// First minute strictly after `after` the expression matches, if any
+    /// within the search horizon
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let horizon = time + Duration::days(CRON_SEARCH_DAYS);
+
+        while time < horizon {
+            if self.months & (1 << time.month()) == 0 {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.matches_day(time.date_naive()) {
+                time = time.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if self.hours & (1 << time.hour()) == 0 {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+}
+
+/// Bitmask of the values one cron field selects
+fn parse_cron_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64> {
+    let invalid = || Error::Validation(format!("Invalid cron {} field: {}", name, field));
+    let value = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, value(step)?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            // `a/n` runs from `a` to the end of the field
+            let start = value(range)?;
+            (start, if part.contains('/') { max } else { start })
+        };
+
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for v in (start..=end).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+
+    Ok(bits)
+}
+
+/// How often a schedule runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cadence {
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+impl Cadence {
+    /// Run following one at `after`, `None` past the representable range
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Cadence::Interval(interval) => after.checked_add_signed(*interval),
+            Cadence::Cron(cron) => cron.next_after(after),
+        }
+    }
+
+    /// First run at or after `start`
+    pub fn first_from(&self, start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Cadence::Interval(_) => Some(start),
+            Cadence::Cron(cron) => cron.next_after(start.checked_sub_signed(Duration::minutes(1))?),
+        }
+    }
+
+    /// First run strictly after `now` on the same cadence as a run that was
+    /// due at `anchor`, skipping everything in between
+    pub fn skip_to(&self, anchor: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Cadence::Interval(interval) if anchor <= now => {
+                let secs = interval.num_seconds().max(1);
+                let periods = (now - anchor).num_seconds() / secs + 1;
+                let skipped = Duration::try_seconds(secs.checked_mul(periods)?)?;
+                anchor.checked_add_signed(skipped)
+            }
+            Cadence::Interval(_) => Some(anchor),
+            Cadence::Cron(cron) => cron.next_after(now),
+        }
+    }
+}
+
+/// Where a schedule's runs are executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DcaVenue {
+    /// On-chain swap against the trading quote asset through the trading
+    /// service, recorded as a manual order
+    #[default]
+    Trade,
+    /// Cross-asset conversion through the conversion service
+    Conversion,
+}
+
+impl DcaVenue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DcaVenue::Trade => "TRADE",
+            DcaVenue::Conversion => "CONVERSION",
+        }
+    }
+}
+
+impl fmt::Display for DcaVenue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DcaVenue {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "TRADE" => Ok(DcaVenue::Trade),
+            "CONVERSION" => Ok(DcaVenue::Conversion),
+            other => Err(Error::Internal(format!("Invalid DCA venue: {}", other))),
+        }
+    }
+}
+
+/// What happens to runs that fell due while the scheduler was not running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CatchUpPolicy {
+    /// Skip every missed run and wait for the next one
+    Skip,
+    /// Make the latest missed run and skip the rest
+    #[default]
+    RunOnce,
+    /// Make every missed run, up to [`MAX_CATCH_UP_RUNS`]
+    RunAll,
+}
+
+impl CatchUpPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatchUpPolicy::Skip => "SKIP",
+            CatchUpPolicy::RunOnce => "RUN_ONCE",
+            CatchUpPolicy::RunAll => "RUN_ALL",
+        }
+    }
+}
+
+impl fmt::Display for CatchUpPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CatchUpPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "SKIP" => Ok(CatchUpPolicy::Skip),
+            "RUN_ONCE" => Ok(CatchUpPolicy::RunOnce),
+            "RUN_ALL" => Ok(CatchUpPolicy::RunAll),
+            other => Err(Error::Internal(format!("Invalid catch-up policy: {}", other))),
+        }
+    }
+}
+
+/// Where a schedule stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DcaScheduleStatus {
+    /// Running on its cadence
+    Active,
+    /// Paused by the user; runs falling due are not made up on resume
+    Paused,
+    /// Past its end date or out of budget
+    Completed,
+    /// Cancelled by the user
+    Cancelled,
+}
+
+impl DcaScheduleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DcaScheduleStatus::Active => "ACTIVE",
+            DcaScheduleStatus::Paused => "PAUSED",
+            DcaScheduleStatus::Completed => "COMPLETED",
+            DcaScheduleStatus::Cancelled => "CANCELLED",
+        }
+    }
+
+    /// Whether the schedule may move from this status to `next`
+    pub fn can_transition_to(&self, next: DcaScheduleStatus) -> bool {
+        use DcaScheduleStatus::*;
+
+        matches!(
+            (self, next),
+            (Active, Paused)
+                | (Active, Completed)
+                | (Active, Cancelled)
+                | (Paused, Active)
+                | (Paused, Completed)
+                | (Paused, Cancelled)
+        )
+    }
+}
+
+impl fmt::Display for DcaScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DcaScheduleStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ACTIVE" => Ok(DcaScheduleStatus::Active),
+            "PAUSED" => Ok(DcaScheduleStatus::Paused),
+            "COMPLETED" => Ok(DcaScheduleStatus::Completed),
+            "CANCELLED" => Ok(DcaScheduleStatus::Cancelled),
+            other => Err(Error::Internal(format!("Invalid DCA schedule status: {}", other))),
+        }
+    }
+}
+
+/// Outcome of one run of a schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DcaRunStatus {
+    Executed,
+    Failed,
+    /// Not attempted: missed, outside the price limits or over budget
+    Skipped,
+}
+
+impl DcaRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DcaRunStatus::Executed => "EXECUTED",
+            DcaRunStatus::Failed => "FAILED",
+            DcaRunStatus::Skipped => "SKIPPED",
+        }
+    }
+}
+
+impl fmt::Display for DcaRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DcaRunStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "EXECUTED" => Ok(DcaRunStatus::Executed),
+            "FAILED" => Ok(DcaRunStatus::Failed),
+            "SKIPPED" => Ok(DcaRunStatus::Skipped),
+            other => Err(Error::Internal(format!("Invalid DCA run status: {}", other))),
+        }
+    }
+}
+
+/// Recurring buy or sell of a fixed amount of an asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcaSchedule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub asset: String,
+    /// Asset paid for buys and received for sells
+    pub quote_asset: String,
+    pub blockchain: String,
+    /// BUY or SELL
+    pub action: String,
+    /// Amount of the asset each run buys or sells
+    pub amount: Decimal,
+    pub venue: DcaVenue,
+    /// Address conversions settle to
+    pub settle_address: Option<String>,
+    /// Fixed cadence; exclusive with `cron_expression`
+    pub interval_secs: Option<i64>,
+    pub cron_expression: Option<String>,
+    /// Runs are skipped while the USD price is above the ceiling
+    pub price_ceiling: Option<Decimal>,
+    /// Runs are skipped while the USD price is below the floor
+    pub price_floor: Option<Decimal>,
+    pub end_at: Option<DateTime<Utc>>,
+    /// USD value the schedule may trade in total
+    pub total_budget: Option<Decimal>,
+    pub total_spent: Decimal,
+    /// Runs executed so far
+    pub run_count: i32,
+    pub catch_up_policy: CatchUpPolicy,
+    pub status: DcaScheduleStatus,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Runs of a schedule that have fallen due, split by its catch-up policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueRuns {
+    /// Runs to make now, oldest first
+    pub execute: Vec<DateTime<Utc>>,
+    /// Missed runs to record as skipped
+    pub skip: Vec<DateTime<Utc>>,
+    /// Following run; `None` once the schedule has no runs left
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+impl DcaSchedule {
+    pub fn cadence(&self) -> Result<Cadence> {
+        match (self.interval_secs, &self.cron_expression) {
+            (Some(secs), None) => Duration::try_seconds(secs)
+                .map(Cadence::Interval)
+                .ok_or_else(|| Error::Internal(format!("DCA schedule {} has an invalid interval", self.id))),
+            (None, Some(expression)) => Ok(Cadence::Cron(CronSchedule::parse(expression)?)),
+            _ => Err(Error::Internal(format!(
+                "DCA schedule {} needs exactly one of an interval and a cron expression",
+                self.id
+            ))),
+        }
+    }
+
+    /// Whether runs are conditional on the price
+    pub fn has_price_limits(&self) -> bool {
+        self.price_ceiling.is_some() || self.price_floor.is_some()
+    }
+
+    /// Whether the schedule runs at `price`
+    pub fn price_allows(&self, price: Decimal) -> bool {
+        self.price_ceiling.is_none_or(|ceiling| price <= ceiling)
+            && self.price_floor.is_none_or(|floor| price >= floor)
+    }
+
+    /// Whether, with `spent` of the budget used, too little is left for a run
+    pub fn budget_spent_at(&self, spent: Decimal) -> bool {
+        self.total_budget.is_some_and(|budget| budget - spent < MIN_RUN_VALUE_USD)
+    }
+
+    /// Runs due by `now`
+    ///
+    /// A run is on time when it is at most `grace` late; older ones were
+    /// missed and are caught up according to the schedule's policy. At most
+    /// [`MAX_CATCH_UP_RUNS`] runs are returned and anything past them is
+    /// dropped.
+    pub fn due_runs(&self, now: DateTime<Utc>, grace: Duration) -> Result<DueRuns> {
+        let cadence = self.cadence()?;
+        let past_end = |at: DateTime<Utc>| self.end_at.is_some_and(|end| at > end);
+
+        let mut due = Vec::new();
+        let mut next = self.next_run_at;
+        while let Some(at) = next {
+            if at > now || due.len() >= MAX_CATCH_UP_RUNS {
+                break;
+            }
+            if past_end(at) {
+                next = None;
+                break;
+            }
+            due.push(at);
+            next = cadence.next_after(at);
+        }
+
+        if let Some(at) = next {
+            if at <= now {
+                next = cadence.skip_to(at, now);
+            }
+        }
+        if next.is_some_and(past_end) {
+            next = None;
+        }
+
+        let execute = match self.catch_up_policy {
+            CatchUpPolicy::RunAll => due.len(),
+            CatchUpPolicy::RunOnce => due.len().min(1),
+            CatchUpPolicy::Skip => match due.last() {
+                Some(at) if now - *at <= grace => 1,
+                _ => 0,
+            },
+        };
+        let skip: Vec<_> = due.drain(..due.len() - execute).collect();
+
+        Ok(DueRuns {
+            execute: due,
+            skip,
+            next_run_at: next,
+        })
+    }
+}
+
+/// One run of a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcaRun {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    /// When the run fell due
+    pub scheduled_for: DateTime<Utc>,
+    pub status: DcaRunStatus,
+    pub amount: Option<Decimal>,
+    /// USD price of the asset the run was made at
+    pub price: Option<Decimal>,
+    /// USD value traded, counted against the budget
+    pub value: Option<Decimal>,
+    /// Manual order a trade was placed as
+    pub manual_order_id: Option<Uuid>,
+    pub transaction_hash: Option<String>,
+    /// Blockchain receipt of the trade or conversion
+    pub receipt_id: Option<Uuid>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A recurring buy or sell to schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DcaScheduleRequest {
+    pub asset: String,
+    /// Defaults to USDC
+    #[serde(default)]
+    pub quote_asset: Option<String>,
+    #[serde(default)]
+    pub blockchain: Option<String>,
+    /// BUY or SELL
+    pub action: String,
+    pub amount: Decimal,
+    #[serde(default)]
+    pub venue: DcaVenue,
+    /// Required for conversions
+    #[serde(default)]
+    pub settle_address: Option<String>,
+    /// Exactly one of `interval_secs` and `cron_expression` is set
+    #[serde(default)]
+    pub interval_secs: Option<i64>,
+    #[serde(default)]
+    pub cron_expression: Option<String>,
+    /// First run; defaults to now for intervals and the next cron match
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub price_ceiling: Option<Decimal>,
+    #[serde(default)]
+    pub price_floor: Option<Decimal>,
+    #[serde(default)]
+    pub end_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub total_budget: Option<Decimal>,
+    #[serde(default)]
+    pub catch_up_policy: CatchUpPolicy,
+}
+
+impl DcaScheduleRequest {
+    /// Check the request against the chains in `chains`, returning its cadence
+    pub fn validate(&self, chains: &ChainRegistry) -> Result<Cadence> {
+        if self.asset.trim().is_empty() {
+            return Err(Error::Validation("Asset is required".to_string()));
+        }
+        let action = self.action.to_uppercase();
+        if action != "BUY" && action != "SELL" {
+            return Err(Error::Validation("Invalid action: must be BUY or SELL".to_string()));
+        }
+        if self.amount <= Decimal::ZERO {
+            return Err(Error::Validation("Amount must be positive".to_string()));
+        }
+
+        let cadence = match (self.interval_secs, &self.cron_expression) {
+            (Some(secs), None) => {
+                let interval = Some(secs)
+                    .filter(|secs| (MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(secs))
+                    .and_then(Duration::try_seconds)
+                    .ok_or_else(|| {
+                        Error::Validation(format!(
+                            "Interval must be between {} and {} seconds",
+                            MIN_INTERVAL_SECS, MAX_INTERVAL_SECS
+                        ))
+                    })?;
+                Cadence::Interval(interval)
+            }
+            (None, Some(expression)) => Cadence::Cron(CronSchedule::parse(expression)?),
+            _ => {
+                return Err(Error::Validation(
+                    "A schedule requires exactly one of interval_secs and cron_expression".to_string(),
+                ))
+            }
+        };
+
+        for (name, limit) in [("Price ceiling", self.price_ceiling), ("Price floor", self.price_floor)] {
+            if limit.is_some_and(|limit| limit <= Decimal::ZERO) {
+                return Err(Error::Validation(format!("{} must be positive", name)));
+            }
+        }
+        if let (Some(ceiling), Some(floor)) = (self.price_ceiling, self.price_floor) {
+            if floor >= ceiling {
+                return Err(Error::Validation(
+                    "Price floor must be below the price ceiling".to_string(),
+                ));
+            }
+        }
+        if self.total_budget.is_some_and(|budget| budget <= Decimal::ZERO) {
+            return Err(Error::Validation("Total budget must be positive".to_string()));
+        }
+        if self.end_at.is_some_and(|end| end <= self.start_at.unwrap_or_else(Utc::now)) {
+            return Err(Error::Validation("End date must be after the start".to_string()));
+        }
+
+        let blockchain = chains.resolve(self.blockchain.as_deref().unwrap_or("Solana"))?;
+        match self.venue {
+            DcaVenue::Trade if !blockchain.is_solana() => {
+                return Err(Error::Validation(
+                    "Trades execute on Solana; schedule a conversion for other chains".to_string(),
+                ))
+            }
+            DcaVenue::Conversion
+                if self.settle_address.as_deref().is_none_or(|address| address.trim().is_empty()) =>
+            {
+                return Err(Error::Validation(
+                    "Conversions require a settle address".to_string(),
+                ))
+            }
+            _ => {}
+        }
+
+        Ok(cadence)
+    }
+}
+
+/// What a run did, before it is recorded
+struct RunOutcome {
+    status: DcaRunStatus,
+    price: Option<Decimal>,
+    value: Option<Decimal>,
+    manual_order_id: Option<Uuid>,
+    transaction_hash: Option<String>,
+    receipt_id: Option<Uuid>,
+    reason: String,
+    /// What is left of the budget is below the smallest run worth making
+    budget_spent: bool,
+}
+
+impl RunOutcome {
+    fn skipped(price: Option<Decimal>, reason: String) -> Self {
+        Self {
+            status: DcaRunStatus::Skipped,
+            price,
+            value: None,
+            manual_order_id: None,
+            transaction_hash: None,
+            receipt_id: None,
+            reason,
+            budget_spent: false,
+        }
+    }
+
+    fn failed(price: Option<Decimal>, manual_order_id: Option<Uuid>, reason: String) -> Self {
+        Self {
+            status: DcaRunStatus::Failed,
+            manual_order_id,
+            ..Self::skipped(price, reason)
+        }
+    }
+}
+
+/// Runs users' dollar-cost-averaging schedules
+///
+/// A background worker picks up schedules whose next run has fallen due and
+/// executes each run through the trading service or the conversion service,
+/// recording it with its receipt. Schedules complete at their end date or
+/// once their budget is spent.
+pub struct DcaScheduleService {
+    db: DbPool,
+    position_management_service: Arc<PositionManagementService>,
+    trading_service: Arc<TradingService>,
+    conversion_service: Option<Arc<ConversionService>>,
+    payment_receipt_service: Option<Arc<PaymentReceiptService>>,
+    coinmarketcap_service: Option<Arc<CoinMarketCapService>>,
+    chains: Arc<ChainRegistry>,
+    grace_period: Duration,
+}
+
+impl DcaScheduleService {
+    pub fn new(
+        db: DbPool,
+        position_management_service: Arc<PositionManagementService>,
+        trading_service: Arc<TradingService>,
+    ) -> Self {
+        Self {
+            db,
+            position_management_service,
+            trading_service,
+            conversion_service: None,
+            payment_receipt_service: None,
+            coinmarketcap_service: None,
+            chains: Arc::new(ChainRegistry::default()),
+            grace_period: Duration::seconds(DEFAULT_GRACE_PERIOD_SECS),
+        }
+    }
+
+    /// Execute conversion schedules; conversions record their own receipts
+    pub fn with_conversion_service(mut self, conversion_service: Arc<ConversionService>) -> Self {
+        self.conversion_service = Some(conversion_service);
+        self
+    }
+
+    /// Record a receipt for every scheduled trade
+    pub fn with_receipts(mut self, payment_receipt_service: Arc<PaymentReceiptService>) -> Self {
+        self.payment_receipt_service = Some(payment_receipt_service);
+        self
+    }
+
+    /// Check price limits and budgets against CoinMarketCap prices
+    pub fn with_coinmarketcap(mut self, coinmarketcap_service: Arc<CoinMarketCapService>) -> Self {
+        self.coinmarketcap_service = Some(coinmarketcap_service);
+        self
+    }
+
+    /// Accept schedules on the chains in `chains` instead of the built-in ones
+    pub fn with_chain_registry(mut self, chains: Arc<ChainRegistry>) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    /// How late a run may start before the catch-up policy treats it as missed
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Create a schedule
+    pub async fn create_schedule(&self, user_id: Uuid, request: DcaScheduleRequest) -> Result<DcaSchedule> {
+        let cadence = request.validate(&self.chains)?;
+        if request.venue == DcaVenue::Conversion && self.conversion_service.is_none() {
+            return Err(Error::Validation("Conversions are not available".to_string()));
+        }
+
+        let action = request.action.to_uppercase();
+        // Stored by name, the way positions and receipts record chains
+        let blockchain = self
+            .chains
+            .display_name(&self.chains.resolve(request.blockchain.as_deref().unwrap_or("Solana"))?)
+            .to_string();
+        let quote_asset = request.quote_asset.clone().unwrap_or_else(|| "USDC".to_string());
+
+        if action == "SELL" {
+            self.position_management_service
+                .validate_balance(user_id, &request.asset, &blockchain, request.amount)
+                .await?;
+        }
+
+        let next_run_at = cadence
+            .first_from(request.start_at.unwrap_or_else(Utc::now))
+            .ok_or_else(|| Error::Validation("Cron expression never matches".to_string()))?;
+
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let row = client
+            .query_one(
+                &format!(
+                    "INSERT INTO dca_schedules
+                        (id, user_id, asset, quote_asset, blockchain, action, amount, venue,
+                         settle_address, interval_secs, cron_expression, price_ceiling, price_floor,
+                         end_at, total_budget, catch_up_policy, next_run_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                     RETURNING {}",
+                    SCHEDULE_COLUMNS
+                ),
+                &[
+                    &Uuid::new_v4(),
+                    &user_id,
+                    &request.asset,
+                    &quote_asset,
+                    &blockchain,
+                    &action,
+                    &request.amount,
+                    &request.venue.as_str(),
+                    &request.settle_address,
+                    &request.interval_secs,
+                    &request.cron_expression.as_deref().map(str::trim),
+                    &request.price_ceiling,
+                    &request.price_floor,
+                    &request.end_at,
+                    &request.total_budget,
+                    &request.catch_up_policy.as_str(),
+                    &next_run_at,
+                ],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create DCA schedule: {}", e)))?;
+        let schedule = row_to_schedule(&row)?;
+
+        info!(
+            "DCA schedule {} created for user {}: {} {} {} via {}, first run at {}",
+            schedule.id, user_id, schedule.action, schedule.amount, schedule.asset, schedule.venue, next_run_at
+        );
+        Ok(schedule)
+    }
+
+    /// Get one of the user's schedules
+    pub async fn get_schedule(&self, schedule_id: Uuid, user_id: Uuid) -> Result<DcaSchedule> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM dca_schedules WHERE id = $1 AND user_id = $2",
+                    SCHEDULE_COLUMNS
+                ),
+                &[&schedule_id, &user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query DCA schedule: {}", e)))?
+            .ok_or_else(|| Error::Validation(format!("DCA schedule {} not found", schedule_id)))?;
+
+        row_to_schedule(&row)
+    }
+
+    /// Get the user's schedules, newest first
+    pub async fn get_user_schedules(
+        &self,
+        user_id: Uuid,
+        status: Option<DcaScheduleStatus>,
+        limit: Option<i64>,
+    ) -> Result<Vec<DcaSchedule>> {
+        let limit = limit.unwrap_or(50).min(100);
+        let status = status.map(|s| s.as_str());
+
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM dca_schedules
+                     WHERE user_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+                     ORDER BY created_at DESC
+                     LIMIT $3",
+                    SCHEDULE_COLUMNS
+                ),
+                &[&user_id, &status, &limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query DCA schedules: {}", e)))?;
+
+        rows.iter().map(row_to_schedule).collect()
+    }
+
+    /// Runs of one of the user's schedules, newest first
+    pub async fn get_runs(&self, schedule_id: Uuid, user_id: Uuid, limit: Option<i64>) -> Result<Vec<DcaRun>> {
+        self.get_schedule(schedule_id, user_id).await?;
+        let limit = limit.unwrap_or(50).min(500);
+
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM dca_runs
+                     WHERE schedule_id = $1
+                     ORDER BY scheduled_for DESC
+                     LIMIT $2",
+                    RUN_COLUMNS
+                ),
+                &[&schedule_id, &limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query DCA runs: {}", e)))?;
+
+        rows.iter().map(row_to_run).collect()
+    }
+
+    /// Pause an active schedule
+    pub async fn pause_schedule(&self, schedule_id: Uuid, user_id: Uuid) -> Result<DcaSchedule> {
+        self.set_status(schedule_id, user_id, DcaScheduleStatus::Paused).await
+    }
+
+    /// Resume a paused schedule from its next run after now; runs that fell
+    /// due while it was paused are not made up
+    pub async fn resume_schedule(&self, schedule_id: Uuid, user_id: Uuid) -> Result<DcaSchedule> {
+        self.set_status(schedule_id, user_id, DcaScheduleStatus::Active).await
+    }
+
+    /// Cancel a schedule for good
+    pub async fn cancel_schedule(&self, schedule_id: Uuid, user_id: Uuid) -> Result<DcaSchedule> {
+        self.set_status(schedule_id, user_id, DcaScheduleStatus::Cancelled).await
+    }
+
+    async fn set_status(&self, schedule_id: Uuid, user_id: Uuid, next: DcaScheduleStatus) -> Result<DcaSchedule> {
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let schedule = lock_schedule(&transaction, schedule_id).await?;
+        if schedule.user_id != user_id {
+            return Err(Error::Validation(format!("DCA schedule {} not found", schedule_id)));
+        }
+
+        check_transition(&schedule, next)?;
+
+        let (next, next_run_at) = if next == DcaScheduleStatus::Active {
+            let now = Utc::now();
+            let cadence = schedule.cadence()?;
+            let next_run_at = match schedule.next_run_at {
+                Some(at) if at > now => Some(at),
+                Some(at) => cadence.skip_to(at, now),
+                None => cadence.first_from(now),
+            }
+            .filter(|at| schedule.end_at.is_none_or(|end| *at <= end));
+
+            match next_run_at {
+                Some(at) => (next, Some(at)),
+                // Ended while it was paused
+                None => (DcaScheduleStatus::Completed, None),
+            }
+        } else {
+            (next, schedule.next_run_at)
+        };
+
+        let updated = update_status(&transaction, &schedule, next, next_run_at).await?;
+        commit(transaction).await?;
+
+        info!("DCA schedule {} {} by user {}", schedule_id, updated.status, user_id);
+        Ok(updated)
+    }
+
+    /// Make every run that has fallen due, returning the runs recorded
+    pub async fn run_due_schedules(&self) -> Result<Vec<DcaRun>> {
+        let client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+
+        let rows = client
+            .query(
+                "SELECT id FROM dca_schedules
+                 WHERE status = 'ACTIVE' AND next_run_at <= NOW()
+                 ORDER BY next_run_at",
+                &[],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to query due DCA schedules: {}", e)))?;
+        drop(client);
+
+        let mut runs = Vec::new();
+        for row in rows {
+            let schedule_id: Uuid = row.get(0);
+            match self.run_schedule(schedule_id).await {
+                Ok(schedule_runs) => runs.extend(schedule_runs),
+                Err(e) => error!("Error running DCA schedule {}: {}", schedule_id, e),
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Make the runs of one schedule that have fallen due, returning the
+    /// runs recorded
+    ///
+    /// The runs are claimed by moving the schedule on to its next run before
+    /// any is made, so concurrent workers never make the same run twice.
+    pub async fn run_schedule(&self, schedule_id: Uuid) -> Result<Vec<DcaRun>> {
+        let now = Utc::now();
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let schedule = lock_schedule(&transaction, schedule_id).await?;
+        if schedule.status != DcaScheduleStatus::Active || schedule.next_run_at.is_none_or(|at| at > now) {
+            // Paused, or claimed by another worker, since it was read
+            return Ok(Vec::new());
+        }
+
+        let due = schedule.due_runs(now, self.grace_period)?;
+        let next_status = if due.next_run_at.is_some() {
+            DcaScheduleStatus::Active
+        } else {
+            DcaScheduleStatus::Completed
+        };
+        let mut schedule = update_status(&transaction, &schedule, next_status, due.next_run_at).await?;
+
+        let mut runs = Vec::new();
+        for at in &due.skip {
+            let outcome = RunOutcome::skipped(None, "Missed while the scheduler was not running".to_string());
+            if let Some(run) = insert_run(&transaction, &schedule, *at, &outcome).await? {
+                runs.push(run);
+            }
+        }
+
+        commit(transaction).await?;
+        drop(client);
+
+        if !due.skip.is_empty() {
+            warn!(
+                "DCA schedule {} skipped {} missed runs ({} policy)",
+                schedule.id,
+                due.skip.len(),
+                schedule.catch_up_policy
+            );
+        }
+
+        for at in due.execute {
+            let was_active = schedule.status == DcaScheduleStatus::Active;
+            runs.push(self.run(&mut schedule, at).await?);
+
+            if was_active && schedule.status == DcaScheduleStatus::Completed {
+                // Out of budget
+                break;
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Make one run and record it, bringing `schedule`'s totals up to date
+    async fn run(&self, schedule: &mut DcaSchedule, at: DateTime<Utc>) -> Result<DcaRun> {
+        let outcome = self.attempt(schedule).await?;
+
+        let mut client = self.db.get().await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let run = insert_run(&transaction, schedule, at, &outcome)
+            .await?
+            .ok_or_else(|| {
+                Error::Internal(format!("DCA schedule {} already ran for {}", schedule.id, at))
+            })?;
+
+        let value = outcome.value.unwrap_or(Decimal::ZERO);
+        let executed = outcome.status == DcaRunStatus::Executed;
+        let attempted = outcome.status != DcaRunStatus::Skipped;
+        let budget_spent = outcome.budget_spent || schedule.budget_spent_at(schedule.total_spent + value);
+        let row = transaction
+            .query_one(
+                &format!(
+                    "UPDATE dca_schedules
+                     SET total_spent = total_spent + $2,
+                         run_count = run_count + $3,
+                         last_run_at = CASE WHEN $4 THEN NOW() ELSE last_run_at END,
+                         status = CASE WHEN $5 AND status = 'ACTIVE' THEN 'COMPLETED' ELSE status END,
+                         next_run_at = CASE WHEN $5 AND status = 'ACTIVE' THEN NULL ELSE next_run_at END,
+                         updated_at = NOW()
+                     WHERE id = $1
+                     RETURNING {}",
+                    SCHEDULE_COLUMNS
+                ),
+                &[&schedule.id, &value, &i32::from(executed), &attempted, &budget_spent],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update DCA schedule: {}", e)))?;
+        let updated = row_to_schedule(&row)?;
+
+        commit(transaction).await?;
+
+        info!(
+            "DCA schedule {} run for {}: {} ({})",
+            schedule.id, at, run.status, outcome.reason
+        );
+        if updated.status == DcaScheduleStatus::Completed && schedule.status == DcaScheduleStatus::Active {
+            info!("DCA schedule {} has spent its budget and completed", schedule.id);
+        }
+
+        *schedule = updated;
+        Ok(run)
+    }
+
+    /// Decide whether a run goes ahead and make it
+    async fn attempt(&self, schedule: &DcaSchedule) -> Result<RunOutcome> {
+        let price = self.current_price(&schedule.asset).await;
+
+        if schedule.has_price_limits() {
+            match price {
+                None => {
+                    return Ok(RunOutcome::skipped(
+                        None,
+                        "No live price to check the price limits against".to_string(),
+                    ))
+                }
+                Some(price) if !schedule.price_allows(price) => {
+                    return Ok(RunOutcome::skipped(
+                        Some(price),
+                        format!("Price {} is outside the schedule's limits", price),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(budget) = schedule.total_budget {
+            // The budget is in USD, so a run it cannot be checked against is not made
+            let Some(price) = price else {
+                return Ok(RunOutcome::failed(
+                    None,
+                    None,
+                    "No live price to check the budget against".to_string(),
+                ));
+            };
+            let remaining = budget - schedule.total_spent;
+            if schedule.budget_spent_at(schedule.total_spent) {
+                return Ok(RunOutcome {
+                    budget_spent: true,
+                    ..RunOutcome::skipped(Some(price), format!("Remaining budget {} is spent", remaining))
+                });
+            }
+            // The price may fall far enough for a later run to fit
+            if schedule.amount * price > remaining {
+                return Ok(RunOutcome::skipped(
+                    Some(price),
+                    format!("Remaining budget {} does not cover a run at {}", remaining, price),
+                ));
+            }
+        }
+
+        match schedule.venue {
+            DcaVenue::Trade => self.execute_trade(schedule, price).await,
+            DcaVenue::Conversion => self.execute_conversion(schedule, price).await,
+        }
+    }
+
+    /// Run a schedule through a manual order executed by the trading service
+    async fn execute_trade(&self, schedule: &DcaSchedule, price: Option<Decimal>) -> Result<RunOutcome> {
+        let manual_order = match self
+            .position_management_service
+            .create_manual_order(
+                schedule.user_id,
+                ManualOrderRequest {
+                    asset: schedule.asset.clone(),
+                    blockchain: Some(schedule.blockchain.clone()),
+                    action: schedule.action.clone(),
+                    amount: schedule.amount,
+                },
+            )
+            .await
+        {
+            Ok(manual_order) => manual_order,
+            Err(e) => return Ok(RunOutcome::failed(price, None, format!("Failed to place order: {}", e))),
+        };
+
+        let trade = self
+            .trading_service
+            .execute_trade(
+                TradeRequest {
+                    user_id: schedule.user_id,
+                    action: schedule.action.clone(),
+                    token_mint: schedule.asset.clone(),
+                    amount: schedule.amount.to_string(),
+                    slippage_tolerance: DCA_SLIPPAGE_TOLERANCE,
+                    recommendation_id: None,
+                },
+                None,
+            )
+            .await;
+
+        let execution = match trade {
+            Ok(execution) => execution,
+            Err(e) => {
+                let message = format!("Failed to execute trade: {}", e);
+                warn!("DCA schedule {} could not trade: {}", schedule.id, message);
+                self.position_management_service
+                    .update_order_status(manual_order.id, "failed", None, None, None, Some(message.clone()))
+                    .await?;
+                return Ok(RunOutcome::failed(price, Some(manual_order.id), message));
+            }
+        };
+
+        let price = execution.price_usd.and_then(Decimal::from_f64).or(price);
+        let value = execution
+            .total_value_usd
+            .and_then(Decimal::from_f64)
+            .or_else(|| price.map(|price| price * schedule.amount));
+        self.position_management_service
+            .update_order_status(
+                manual_order.id,
+                "executed",
+                Some(execution.transaction_signature.clone()),
+                price,
+                value,
+                None,
+            )
+            .await?;
+
+        let receipt_id = match &self.payment_receipt_service {
+            Some(payment_receipt_service) => {
+                let user = schedule.user_id.to_string();
+                let (sender, recipient) = if schedule.action == "BUY" {
+                    (SWAP_COUNTERPARTY.to_string(), user)
+                } else {
+                    (user, SWAP_COUNTERPARTY.to_string())
+                };
+                match payment_receipt_service
+                    .generate_trade_receipt(
+                        execution.id,
+                        schedule.amount,
+                        schedule.asset.clone(),
+                        price,
+                        sender,
+                        recipient,
+                        Blockchain::SOLANA,
+                        None,
+                        None,
+                    )
+                    .await
+                {
+                    Ok(receipt) => Some(receipt.id),
+                    Err(e) => {
+                        // The trade went through; a missing receipt does not undo it
+                        warn!("Failed to create receipt for DCA trade {}: {}", execution.id, e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        Ok(RunOutcome {
+            status: DcaRunStatus::Executed,
+            price,
+            value,
+            manual_order_id: Some(manual_order.id),
+            reason: format!("Traded in transaction {}", execution.transaction_signature),
+            transaction_hash: Some(execution.transaction_signature),
+            receipt_id,
+            budget_spent: false,
+        })
+    }
+
+    /// Run a schedule through a quoted conversion settling to the
+    /// schedule's address
+    async fn execute_conversion(&self, schedule: &DcaSchedule, price: Option<Decimal>) -> Result<RunOutcome> {
+        let Some(conversion_service) = &self.conversion_service else {
+            return Ok(RunOutcome::failed(price, None, "Conversions are not available".to_string()));
+        };
+        let Some(settle_address) = schedule.settle_address.as_deref() else {
+            return Ok(RunOutcome::failed(price, None, "Schedule has no settle address".to_string()));
+        };
+        let blockchain = self.chains.resolve(&schedule.blockchain)?;
+
+        let buying = schedule.action == "BUY";
+        if !buying {
+            if let Err(e) = self
+                .position_management_service
+                .validate_balance(schedule.user_id, &schedule.asset, &schedule.blockchain, schedule.amount)
+                .await
+            {
+                return Ok(RunOutcome::failed(price, None, e.to_string()));
+            }
+        }
+
+        // The schedule's amount is always of the asset: what a buy receives
+        // and what a sell pays in
+        let (from_asset, to_asset, amount_type) = if buying {
+            (&schedule.quote_asset, &schedule.asset, AmountType::To)
+        } else {
+            (&schedule.asset, &schedule.quote_asset, AmountType::From)
+        };
+
+        let quote = match conversion_service
+            .get_quote(from_asset, to_asset, schedule.amount, amount_type)
+            .await
+        {
+            Ok(quote) => quote,
+            Err(e) => return Ok(RunOutcome::failed(price, None, format!("Failed to get conversion quote: {}", e))),
+        };
+        let quote_amount = if buying { quote.from_amount } else { quote.to_amount };
+        // Counted against the budget in USD, like trades
+        let value = usd_value(&schedule.quote_asset.to_uppercase(), quote_amount, None)
+            .or_else(|| price.map(|price| price * schedule.amount));
+
+        let result = match conversion_service
+            .execute_conversion(schedule.user_id, quote, settle_address, None, blockchain)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => return Ok(RunOutcome::failed(price, None, format!("Failed to execute conversion: {}", e))),
+        };
+
+        Ok(RunOutcome {
+            status: DcaRunStatus::Executed,
+            price: value.map(|value| value / schedule.amount),
+            value,
+            manual_order_id: None,
+            reason: format!("Conversion order {} placed", result.order_id),
+            transaction_hash: result.transaction_hash.or(Some(result.order_id)),
+            receipt_id: result.receipt_id,
+            budget_spent: false,
+        })
+    }
+
+    async fn current_price(&self, asset: &str) -> Option<Decimal> {
+        let coinmarketcap_service = self.coinmarketcap_service.as_ref()?;
+
+        match coinmarketcap_service.get_price_by_symbol(asset).await {
+            Ok(data) => Some(data.price_usd),
+            Err(e) => {
+                warn!("Failed to fetch price for {}: {:?}", asset, e);
+                None
+            }
+        }
+    }
+
+    /// Start the background worker that makes the runs falling due
+    pub fn start_scheduler(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        info!("Starting DCA scheduler with interval: {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                match self.run_due_schedules().await {
+                    Ok(runs) if !runs.is_empty() => debug!("Recorded {} DCA runs", runs.len()),
+                    Ok(_) => {}
+                    Err(e) => error!("Error running DCA schedules: {}", e),
+                }
+            }
+        })
+    }
+}
+
+async fn lock_schedule(transaction: &Transaction<'_>, schedule_id: Uuid) -> Result<DcaSchedule> {
+    let row = transaction
+        .query_opt(
+            &format!("SELECT {} FROM dca_schedules WHERE id = $1 FOR UPDATE", SCHEDULE_COLUMNS),
+            &[&schedule_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock DCA schedule: {}", e)))?
+        .ok_or_else(|| Error::Validation(format!("DCA schedule {} not found", schedule_id)))?;
+
+    row_to_schedule(&row)
+}
+
+fn check_transition(schedule: &DcaSchedule, next: DcaScheduleStatus) -> Result<()> {
+    if schedule.status.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "DCA schedule {} cannot move from {} to {}",
+            schedule.id, schedule.status, next
+        )))
+    }
+}
+
+/// Move a locked schedule to `next`, or keep it where it is, and set its
+/// next run
+async fn update_status(
+    transaction: &Transaction<'_>,
+    schedule: &DcaSchedule,
+    next: DcaScheduleStatus,
+    next_run_at: Option<DateTime<Utc>>,
+) -> Result<DcaSchedule> {
+    if next != schedule.status {
+        check_transition(schedule, next)?;
+    }
+
+    let row = transaction
+        .query_one(
+            &format!(
+                "UPDATE dca_schedules
+                 SET status = $2, next_run_at = $3, updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING {}",
+                SCHEDULE_COLUMNS
+            ),
+            &[&schedule.id, &next.as_str(), &next_run_at],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update DCA schedule: {}", e)))?;
+
+    row_to_schedule(&row)
+}
+
+/// Record a run, returning `None` when one was already recorded for `at`
+async fn insert_run(
+    transaction: &Transaction<'_>,
+    schedule: &DcaSchedule,
+    at: DateTime<Utc>,
+    outcome: &RunOutcome,
+) -> Result<Option<DcaRun>> {
+    let amount = (outcome.status != DcaRunStatus::Skipped).then_some(schedule.amount);
+
+    let row = transaction
+        .query_opt(
+            &format!(
+                "INSERT INTO dca_runs
+                    (id, schedule_id, scheduled_for, status, amount, price, value,
+                     manual_order_id, transaction_hash, receipt_id, reason)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT (schedule_id, scheduled_for) DO NOTHING
+                 RETURNING {}",
+                RUN_COLUMNS
+            ),
+            &[
+                &Uuid::new_v4(),
+                &schedule.id,
+                &at,
+                &outcome.status.as_str(),
+                &amount,
+                &outcome.price,
+                &outcome.value,
+                &outcome.manual_order_id,
+                &outcome.transaction_hash,
+                &outcome.receipt_id,
+                &outcome.reason,
+            ],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record DCA run: {}", e)))?;
+
+    row.as_ref().map(row_to_run).transpose()
+}
+
+async fn commit(transaction: Transaction<'_>) -> Result<()> {
+    transaction
+        .commit()
+        .await
+        .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+}
+
+fn row_to_schedule(row: &tokio_postgres::Row) -> Result<DcaSchedule> {
+    let venue: String = row.get("venue");
+    let catch_up_policy: String = row.get("catch_up_policy");
+    let status: String = row.get("status");
+
+    Ok(DcaSchedule {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        asset: row.get("asset"),
+        quote_asset: row.get("quote_asset"),
+        blockchain: row.get("blockchain"),
+        action: row.get("action"),
+        amount: row.get("amount"),
+        venue: venue.parse()?,
+        settle_address: row.get("settle_address"),
+        interval_secs: row.get("interval_secs"),
+        cron_expression: row.get("cron_expression"),
+        price_ceiling: row.get("price_ceiling"),
+        price_floor: row.get("price_floor"),
+        end_at: row.get("end_at"),
+        total_budget: row.get("total_budget"),
+        total_spent: row.get("total_spent"),
+        run_count: row.get("run_count"),
+        catch_up_policy: catch_up_policy.parse()?,
+        status: status.parse()?,
+        next_run_at: row.get("next_run_at"),
+        last_run_at: row.get("last_run_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+fn row_to_run(row: &tokio_postgres::Row) -> Result<DcaRun> {
+    let status: String = row.get("status");
+
+    Ok(DcaRun {
+        id: row.get("id"),
+        schedule_id: row.get("schedule_id"),
+        scheduled_for: row.get("scheduled_for"),
+        status: status.parse()?,
+        amount: row.get("amount"),
+        price: row.get("price"),
+        value: row.get("value"),
+        manual_order_id: row.get("manual_order_id"),
+        transaction_hash: row.get("transaction_hash"),
+        receipt_id: row.get("receipt_id"),
+        reason: row.get("reason"),
+        created_at: row.get("created_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn schedule(interval_secs: i64, next_run_at: &str) -> DcaSchedule {
+        DcaSchedule {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            asset: "SOL".to_string(),
+            quote_asset: "USDC".to_string(),
+            blockchain: "Solana".to_string(),
+            action: "BUY".to_string(),
+            amount: Decimal::ONE,
+            venue: DcaVenue::Trade,
+            settle_address: None,
+            interval_secs: Some(interval_secs),
+            cron_expression: None,
+            price_ceiling: None,
+            price_floor: None,
+            end_at: None,
+            total_budget: None,
+            total_spent: Decimal::ZERO,
+            run_count: 0,
+            catch_up_policy: CatchUpPolicy::RunOnce,
+            status: DcaScheduleStatus::Active,
+            next_run_at: Some(at(next_run_at)),
+            last_run_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn request() -> DcaScheduleRequest {
+        DcaScheduleRequest {
+            asset: "SOL".to_string(),
+            quote_asset: None,
+            blockchain: None,
+            action: "buy".to_string(),
+            amount: Decimal::ONE,
+            venue: DcaVenue::Trade,
+            settle_address: None,
+            interval_secs: Some(3600),
+            cron_expression: None,
+            start_at: None,
+            price_ceiling: None,
+            price_floor: None,
+            end_at: None,
+            total_budget: None,
+            catch_up_policy: CatchUpPolicy::default(),
+        }
+    }
+
+    #[test]
+    fn test_cron_next_match() {
+        // Weekdays at 09:30
+        let cron = CronSchedule::parse("30 9 * * 1-5").unwrap();
+        // Friday 2024-03-01 10:00 -> Monday 2024-03-04 09:30
+        assert_eq!(cron.next_after(at("2024-03-01T10:00:00Z")), Some(at("2024-03-04T09:30:00Z")));
+        assert_eq!(cron.next_after(at("2024-03-04T09:29:59Z")), Some(at("2024-03-04T09:30:00Z")));
+        assert_eq!(cron.next_after(at("2024-03-04T09:30:00Z")), Some(at("2024-03-05T09:30:00Z")));
+
+        let every_quarter_hour = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(every_quarter_hour.next_after(at("2024-03-01T10:14:00Z")), Some(at("2024-03-01T10:15:00Z")));
+        assert_eq!(every_quarter_hour.next_after(at("2024-03-01T23:50:00Z")), Some(at("2024-03-02T00:00:00Z")));
+
+        // First of the month or any Sunday, at midnight
+        let either_day = CronSchedule::parse("0 0 1 * 7").unwrap();
+        assert_eq!(either_day.next_after(at("2024-03-01T00:00:00Z")), Some(at("2024-03-03T00:00:00Z")));
+        assert_eq!(either_day.next_after(at("2024-03-31T00:00:00Z")), Some(at("2024-04-01T00:00:00Z")));
+
+        let leap_day = CronSchedule::parse("0 12 29 2 *").unwrap();
+        assert_eq!(leap_day.next_after(at("2024-03-01T00:00:00Z")), Some(at("2028-02-29T12:00:00Z")));
+        assert_eq!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(at("2024-01-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_cron_parse_errors() {
+        for expression in ["* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "* * * 13 *", "* * * * 8", "*/0 * * * *", "5-1 * * * *", "a * * * *"] {
+            assert!(CronSchedule::parse(expression).is_err(), "{}", expression);
+        }
+        assert!(CronSchedule::parse("0,30 8-18/2 1,15 */3 0").is_ok());
+    }
+
+    #[test]
+    fn test_on_time_run() {
+        let schedule = schedule(3600, "2024-03-01T10:00:00Z");
+        let due = schedule.due_runs(at("2024-03-01T10:00:30Z"), Duration::minutes(5)).unwrap();
+        assert_eq!(due.execute, vec![at("2024-03-01T10:00:00Z")]);
+        assert!(due.skip.is_empty());
+        assert_eq!(due.next_run_at, Some(at("2024-03-01T11:00:00Z")));
+
+        // Not yet due
+        let due = schedule.due_runs(at("2024-03-01T09:59:00Z"), Duration::minutes(5)).unwrap();
+        assert!(due.execute.is_empty() && due.skip.is_empty());
+        assert_eq!(due.next_run_at, Some(at("2024-03-01T10:00:00Z")));
+    }
+
+    #[test]
+    fn test_catch_up_policies() {
+        // Down from 10:00 to 13:30: runs at 10, 11, 12 and 13 were missed
+        let now = at("2024-03-01T13:30:00Z");
+        let grace = Duration::minutes(5);
+        let mut schedule = schedule(3600, "2024-03-01T10:00:00Z");
+
+        schedule.catch_up_policy = CatchUpPolicy::Skip;
+        let due = schedule.due_runs(now, grace).unwrap();
+        assert!(due.execute.is_empty());
+        assert_eq!(due.skip.len(), 4);
+        assert_eq!(due.next_run_at, Some(at("2024-03-01T14:00:00Z")));
+
+        schedule.catch_up_policy = CatchUpPolicy::RunOnce;
+        let due = schedule.due_runs(now, grace).unwrap();
+        assert_eq!(due.execute, vec![at("2024-03-01T13:00:00Z")]);
+        assert_eq!(due.skip.len(), 3);
+
+        schedule.catch_up_policy = CatchUpPolicy::RunAll;
+        let due = schedule.due_runs(now, grace).unwrap();
+        assert_eq!(due.execute.len(), 4);
+        assert!(due.skip.is_empty());
+
+        // A long outage is capped, and the schedule picks up on its grid
+        let due = schedule.due_runs(at("2024-03-05T13:30:00Z"), grace).unwrap();
+        assert_eq!(due.execute.len(), MAX_CATCH_UP_RUNS);
+        assert_eq!(due.next_run_at, Some(at("2024-03-05T14:00:00Z")));
+    }
+
+    #[test]
+    fn test_end_date_completes_schedule() {
+        let mut schedule = schedule(3600, "2024-03-01T10:00:00Z");
+        schedule.end_at = Some(at("2024-03-01T10:30:00Z"));
+
+        let due = schedule.due_runs(at("2024-03-01T10:01:00Z"), Duration::minutes(5)).unwrap();
+        assert_eq!(due.execute, vec![at("2024-03-01T10:00:00Z")]);
+        assert_eq!(due.next_run_at, None);
+    }
+
+    #[test]
+    fn test_price_limits() {
+        let mut schedule = schedule(3600, "2024-03-01T10:00:00Z");
+        assert!(!schedule.has_price_limits());
+        assert!(schedule.price_allows(Decimal::from(1_000_000)));
+
+        schedule.price_ceiling = Some(Decimal::from(150));
+        schedule.price_floor = Some(Decimal::from(100));
+        assert!(schedule.price_allows(Decimal::from(150)));
+        assert!(schedule.price_allows(Decimal::from(100)));
+        assert!(!schedule.price_allows(Decimal::from(151)));
+        assert!(!schedule.price_allows(Decimal::from(99)));
+    }
+
+    #[test]
+    fn test_request_validation() {
+        let chains = ChainRegistry::default();
+        assert_eq!(request().validate(&chains).unwrap(), Cadence::Interval(Duration::hours(1)));
+
+        let mut both = request();
+        both.cron_expression = Some("0 * * * *".to_string());
+        assert!(both.validate(&chains).is_err());
+
+        let mut cron = request();
+        cron.interval_secs = None;
+        cron.cron_expression = Some("0 9 * * 1".to_string());
+        assert!(matches!(cron.validate(&chains).unwrap(), Cadence::Cron(_)));
+
+        let mut too_often = request();
+        too_often.interval_secs = Some(30);
+        assert!(too_often.validate(&chains).is_err());
+
+        // Out-of-range intervals are rejected rather than overflowing
+        for secs in [MAX_INTERVAL_SECS + 1, i64::MAX / 1000 + 1, i64::MAX] {
+            let mut too_rare = request();
+            too_rare.interval_secs = Some(secs);
+            assert!(too_rare.validate(&chains).is_err(), "{}", secs);
+        }
+        let mut yearly = request();
+        yearly.interval_secs = Some(MAX_INTERVAL_SECS);
+        assert!(yearly.validate(&chains).is_ok());
+
+        let mut inverted = request();
+        inverted.price_ceiling = Some(Decimal::from(100));
+        inverted.price_floor = Some(Decimal::from(120));
+        assert!(inverted.validate(&chains).is_err());
+
+        let mut conversion = request();
+        conversion.venue = DcaVenue::Conversion;
+        conversion.blockchain = Some("ethereum".to_string());
+        assert!(conversion.validate(&chains).is_err());
+        conversion.settle_address = Some("0xabc".to_string());
+        assert!(conversion.validate(&chains).is_ok());
+
+        let mut off_chain_trade = request();
+        off_chain_trade.blockchain = Some("polygon".to_string());
+        assert!(off_chain_trade.validate(&chains).is_err());
+
+        let mut unknown_chain = conversion.clone();
+        unknown_chain.blockchain = Some("dogecoin".to_string());
+        assert!(unknown_chain.validate(&chains).is_err());
+
+        let mut ended = request();
+        ended.end_at = Some(Utc::now() - Duration::days(1));
+        assert!(ended.validate(&chains).is_err());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use DcaScheduleStatus::*;
+
+        assert!(Active.can_transition_to(Paused));
+        assert!(Paused.can_transition_to(Active));
+        assert!(Paused.can_transition_to(Cancelled));
+        assert!(Active.can_transition_to(Completed));
+        assert!(!Completed.can_transition_to(Paused));
+        assert!(!Completed.can_transition_to(Active));
+        assert!(!Cancelled.can_transition_to(Active));
+
+        for status in [Active, Paused, Completed, Cancelled] {
+            assert_eq!(status.as_str().parse::<DcaScheduleStatus>().unwrap(), status);
+        }
+        for policy in [CatchUpPolicy::Skip, CatchUpPolicy::RunOnce, CatchUpPolicy::RunAll] {
+            assert_eq!(policy.as_str().parse::<CatchUpPolicy>().unwrap(), policy);
+        }
+    }
+
+    #[test]
+    fn test_budget_spent_below_min_run_value() {
+        let mut schedule = schedule(3600, "2024-01-01T00:00:00Z");
+        assert!(!schedule.budget_spent_at(Decimal::from(1_000_000)));
+
+        schedule.total_budget = Some(Decimal::from(100));
+        assert!(!schedule.budget_spent_at(Decimal::from(50)));
+        assert!(!schedule.budget_spent_at(Decimal::from(99)));
+        assert!(schedule.budget_spent_at(Decimal::new(9950, 2)));
+        assert!(schedule.budget_spent_at(Decimal::from(100)));
+    }
+
+    #[test]
+    fn test_interval_arithmetic_does_not_overflow() {
+        let interval = Cadence::Interval(Duration::seconds(MAX_INTERVAL_SECS));
+        assert_eq!(interval.next_after(DateTime::<Utc>::MAX_UTC), None);
+
+        // A start far in the past skips more periods than fit in an i32
+        let minutely = Cadence::Interval(Duration::seconds(MIN_INTERVAL_SECS));
+        let now = at("2024-03-01T10:00:30Z");
+        let anchor = NaiveDate::from_ymd_opt(-3000, 3, 1).unwrap().and_hms_opt(10, 0, 0).unwrap().and_utc();
+        assert_eq!(minutely.skip_to(anchor, now), Some(at("2024-03-01T10:01:00Z")));
+        assert_eq!(minutely.skip_to(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC), None);
+
+        let mut stored = schedule(3600, "2024-03-01T10:00:00Z");
+        stored.interval_secs = Some(i64::MAX);
+        assert!(stored.cadence().is_err());
+    }
+}
@@ -59,6 +59,7 @@ pub enum ApiError {
     // Mesh network errors
     MeshNetworkError(String),
     InvalidPriceUpdate(String),
+    UntrustedPriceSource(String),
     ProviderNotConfigured(String),
     NoProvidersAvailable(String),
 }
@@ -92,6 +93,7 @@ impl fmt::Display for ApiError {
             ApiError::NotImplemented(msg) => write!(f, "Not implemented: {}", msg),
             ApiError::MeshNetworkError(msg) => write!(f, "Mesh network error: {}", msg),
             ApiError::InvalidPriceUpdate(msg) => write!(f, "Invalid price update: {}", msg),
+            ApiError::UntrustedPriceSource(msg) => write!(f, "Untrusted price source: {}", msg),
             ApiError::ProviderNotConfigured(msg) => write!(f, "Provider not configured: {}", msg),
             ApiError::NoProvidersAvailable(msg) => write!(f, "No providers available: {}", msg),
         }
@@ -192,6 +194,9 @@ impl IntoResponse for ApiError {
             ApiError::InvalidPriceUpdate(msg) => {
                 (StatusCode::BAD_REQUEST, "invalid_price_update", msg.clone())
             }
+            ApiError::UntrustedPriceSource(msg) => {
+                (StatusCode::FORBIDDEN, "untrusted_price_source", msg.clone())
+            }
             ApiError::ProviderNotConfigured(msg) => {
                 (StatusCode::BAD_REQUEST, "provider_not_configured", msg.clone())
             }
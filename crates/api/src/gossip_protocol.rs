@@ -8,6 +8,7 @@ use crate::message_tracker::MessageTracker;
 use crate::mesh_metrics::MeshMetricsCollector;
 use crate::price_cache::PriceCache;
 use crate::price_update_validator::PriceUpdateValidator;
+use crate::provider_identity::ProviderKeyStore;
 use crate::websocket_service::WebSocketService;
 
 /// Implements the gossip protocol for message relay and deduplication
@@ -22,6 +23,8 @@ pub struct GossipProtocol {
     websocket_service: Arc<WebSocketService>,
     /// Metrics collector for tracking gossip operations
     metrics: Arc<MeshMetricsCollector>,
+    /// Provider keys used to authenticate updates before caching
+    provider_keys: Arc<ProviderKeyStore>,
 }
 
 impl GossipProtocol {
@@ -32,6 +35,7 @@ impl GossipProtocol {
         price_cache: Arc<PriceCache>,
         websocket_service: Arc<WebSocketService>,
         metrics: Arc<MeshMetricsCollector>,
+        provider_keys: Arc<ProviderKeyStore>,
     ) -> Self {
         Self {
             peer_manager,
@@ -39,6 +43,7 @@ impl GossipProtocol {
             price_cache,
             websocket_service,
             metrics,
+            provider_keys,
         }
     }
 
@@ -46,10 +51,11 @@ impl GossipProtocol {
     /// 
     /// This method:
     /// 1. Validates the price update message
-    /// 2. Checks if the message has been seen before (deduplication)
-    /// 3. Stores valid price data in the cache
-    /// 4. Pushes updates to WebSocket clients
-    /// 5. Relays the message to other peers if TTL > 0
+    /// 2. Verifies the provider signature against the trusted or pinned key
+    /// 3. Checks if the message has been seen before (deduplication)
+    /// 4. Stores valid price data in the cache
+    /// 5. Pushes updates to WebSocket clients
+    /// 6. Relays the message, signature intact, to other peers if TTL > 0
     /// 
     /// Requirements: 4.3, 4.4, 6.1, 12.1, 14.1, 14.2, 14.3, 14.4, 14.5
     pub async fn process_update(
@@ -77,6 +83,24 @@ impl GossipProtocol {
             
             return Err(e.into());
         }
+
+        // Only cache and relay updates signed by an acceptable provider key
+        if let Err(e) = self.provider_keys.verify(&update).await {
+            tracing::warn!(
+                message_id = %update.message_id,
+                source_node = %update.source_node_id,
+                from_peer = %from_peer,
+                error = %e,
+                "Rejecting unauthenticated price update"
+            );
+
+            self.metrics.record_validation_failure(
+                update.source_node_id,
+                &e.to_string()
+            ).await;
+
+            return Err(e.into());
+        }
         
         // Record validation success
         self.metrics.record_validation_success().await;
@@ -112,6 +136,7 @@ impl GossipProtocol {
                 source_node_id: update.source_node_id,
                 blockchain: price_data.blockchain.clone(),
                 change_24h: price_data.change_24h.clone(),
+                signer: update.signer.clone(),
             };
 
             if let Err(e) = self.price_cache.store(asset.clone(), cached_data.clone()).await {
@@ -127,23 +152,33 @@ impl GossipProtocol {
                     "Stored price data in cache"
                 );
 
-                // Calculate freshness for display
-                let freshness = crate::mesh_types::DataFreshness::from_timestamp(update.timestamp);
-                let freshness_str = match freshness {
-                    crate::mesh_types::DataFreshness::JustNow => "Just now".to_string(),
-                    crate::mesh_types::DataFreshness::MinutesAgo(m) => format!("{} minutes ago", m),
-                    crate::mesh_types::DataFreshness::HoursAgo(h) => format!("{} hours ago", h),
-                    crate::mesh_types::DataFreshness::Stale => "Stale".to_string(),
+                // Clients get the consensus across providers rather than this
+                // provider's raw quote
+                let consensus = match self.price_cache.consensus(asset).await {
+                    Ok(Some(consensus)) => consensus,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        tracing::error!(
+                            asset = %asset,
+                            error = %e,
+                            "Failed to compute consensus price"
+                        );
+                        continue;
+                    }
                 };
 
+                // Calculate freshness for display
+                let freshness_str =
+                    crate::mesh_types::DataFreshness::from_timestamp(consensus.timestamp).label();
+
                 // Push update to WebSocket clients using mesh-specific broadcast
                 self.websocket_service.broadcast_mesh_price_update(
                     asset.clone(),
-                    price_data.blockchain.clone(),
-                    price_data.price.clone(),
-                    price_data.change_24h.clone(),
-                    update.timestamp,
-                    update.source_node_id,
+                    consensus.blockchain,
+                    consensus.price,
+                    consensus.change_24h,
+                    consensus.timestamp,
+                    consensus.source_node_id,
                     freshness_str,
                 );
             }
@@ -230,6 +265,8 @@ impl GossipProtocol {
             timestamp: update.timestamp,
             prices: serde_json::to_value(&update.prices)?,
             ttl: update.ttl,
+            signer: update.signer.clone(),
+            signature: update.signature.clone(),
         };
 
         let mut relay_count = 0;
@@ -1,14 +1,15 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{Method, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::auth::{Claims, ADMIN_SCOPE};
 use crate::AppState;
 
 // Response types
@@ -37,6 +38,56 @@ impl<T: Serialize> ApiResponse<T> {
     }
 }
 
+/// Reject requests that name another user's ID in the body
+///
+/// Path `:user_id` segments are checked by `auth::user_path_authorization`;
+/// handlers taking the user ID from the payload call this instead.
+fn authorize_body_user<T: Serialize>(
+    claims: &Claims,
+    user_id: Uuid,
+) -> Result<(), (StatusCode, Json<ApiResponse<T>>)> {
+    if claims.can_access_user(user_id, &Method::POST) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(
+                "Not authorized to access this user's resources".to_string(),
+            )),
+        ))
+    }
+}
+
+/// The authenticated caller's user ID
+fn caller_id<T: Serialize>(claims: &Claims) -> Result<Uuid, (StatusCode, Json<ApiResponse<T>>)> {
+    claims.user_id().ok_or_else(|| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Token does not identify a user".to_string())),
+        )
+    })
+}
+
+/// Reject callers without the `admin` scope
+fn require_admin<T: Serialize>(claims: &Claims) -> Result<(), (StatusCode, Json<ApiResponse<T>>)> {
+    if claims.has_scope(ADMIN_SCOPE) {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Admin access required".to_string())),
+        ))
+    }
+}
+
+/// Status for a receipt lookup error; receipts of other users are forbidden
+fn receipt_error_status(e: &shared::Error) -> StatusCode {
+    match e {
+        shared::Error::Unauthorized => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 // Request types
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -179,6 +230,7 @@ pub async fn login_user(
 /// Requirements: 17.5
 pub async fn enable_2fa(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<Enable2FARequest>,
 ) -> Result<Json<ApiResponse<Enable2FAResponse>>, (StatusCode, Json<ApiResponse<Enable2FAResponse>>)> {
     let user_id = match Uuid::parse_str(&payload.user_id) {
@@ -190,6 +242,7 @@ pub async fn enable_2fa(
             ));
         }
     };
+    authorize_body_user(&claims, user_id)?;
 
     match state.jwt_config.enable_2fa(&state.db_pool, user_id).await {
         Ok((secret, qr_code)) => {
@@ -207,6 +260,7 @@ pub async fn enable_2fa(
 /// Requirements: 17.5
 pub async fn verify_2fa(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<Verify2FARequest>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, (StatusCode, Json<ApiResponse<serde_json::Value>>)> {
     let user_id = match Uuid::parse_str(&payload.user_id) {
@@ -218,6 +272,7 @@ pub async fn verify_2fa(
             ));
         }
     };
+    authorize_body_user(&claims, user_id)?;
 
     match state
         .jwt_config
@@ -241,6 +296,7 @@ pub async fn verify_2fa(
 /// Requirements: 17.5
 pub async fn disable_2fa(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<Disable2FARequest>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, (StatusCode, Json<ApiResponse<serde_json::Value>>)> {
     let user_id = match Uuid::parse_str(&payload.user_id) {
@@ -252,6 +308,7 @@ pub async fn disable_2fa(
             ));
         }
     };
+    authorize_body_user(&claims, user_id)?;
 
     match state
         .jwt_config
@@ -281,6 +338,7 @@ pub async fn connect_wallet(
 
 pub async fn get_portfolio(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(address): Path<String>,
 ) -> Result<Json<ApiResponse<shared::models::Portfolio>>, (StatusCode, Json<ApiResponse<shared::models::Portfolio>>)> {
     tracing::info!("get_portfolio handler called for address: {}", address);
@@ -293,12 +351,10 @@ pub async fn get_portfolio(
         },
         Err(shared::Error::WalletNotFound(_)) => {
             tracing::info!("Wallet not found, attempting to connect: {}", address);
-            // Wallet not found - try to connect it first with a demo user
-            // In production, this should use the authenticated user's ID
-            let demo_user_id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-                .expect("Invalid demo user UUID");
-            
-            match state.wallet_service.connect_wallet(&address, demo_user_id).await {
+            // Wallet not found - connect it to the caller first
+            let user_id = caller_id(&claims)?;
+
+            match state.wallet_service.connect_wallet(&address, user_id).await {
                 Ok(portfolio) => {
                     tracing::info!("Successfully connected wallet and retrieved portfolio for {}", address);
                     Ok(Json(ApiResponse::success(portfolio)))
@@ -478,6 +534,7 @@ pub async fn get_portfolio_performance(
 
 pub async fn get_whale_impact(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<WhaleImpactRequest>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, (StatusCode, Json<ApiResponse<serde_json::Value>>)> {
     use chrono::{Duration, Utc};
@@ -494,6 +551,7 @@ pub async fn get_whale_impact(
             ))
         }
     };
+    authorize_body_user(&claims, user_id)?;
 
     // Parse the period parameter
     let (start_date, end_date) = match payload.period.as_str() {
@@ -532,6 +590,7 @@ pub async fn get_whale_impact(
 
 pub async fn get_recommendation_accuracy(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<RecommendationAccuracyRequest>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, (StatusCode, Json<ApiResponse<serde_json::Value>>)> {
     use chrono::{Duration, Utc};
@@ -548,6 +607,7 @@ pub async fn get_recommendation_accuracy(
             ))
         }
     };
+    authorize_body_user(&claims, user_id)?;
 
     // Parse the period parameter
     let (start_date, end_date) = match payload.period.as_str() {
@@ -789,17 +849,17 @@ pub async fn execute_conversion(
 
     // Parse blockchain (default to Solana if not provided)
     let blockchain = match request.blockchain.as_deref() {
-        Some("ethereum") => blockchain::Blockchain::Ethereum,
-        Some("binance smart chain") | Some("bsc") => blockchain::Blockchain::BinanceSmartChain,
-        Some("polygon") => blockchain::Blockchain::Polygon,
-        Some("solana") | None => blockchain::Blockchain::Solana,
-        Some(other) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error(format!("Invalid blockchain: {}", other))),
-            )
-                .into_response();
-        }
+        None => blockchain::Blockchain::SOLANA,
+        Some(name) => match state.chains.resolve(name) {
+            Ok(blockchain) => blockchain,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(format!("Invalid blockchain: {}", name))),
+                )
+                    .into_response();
+            }
+        },
     };
 
     // Reconstruct quote
@@ -1009,9 +1069,30 @@ pub async fn create_staking_request(
 /// Approve or reject a staking request
 pub async fn approve_staking_request(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(request_id): Path<Uuid>,
     Json(payload): Json<ApproveStakingRequest>,
 ) -> impl IntoResponse {
+    match state.staking_service.get_approval_request_owner(request_id).await {
+        Ok(Some(owner)) => {
+            if let Err(rejection) = authorize_body_user(&claims, owner) {
+                return rejection;
+            }
+        }
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Staking approval request not found".to_string())),
+            )
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(e.to_string())),
+            )
+        }
+    }
+
     match state
         .staking_service
         .initiate_staking(request_id, payload.approved)
@@ -1042,6 +1123,7 @@ pub async fn get_staking_positions(
 /// Get a specific staking position
 pub async fn get_staking_position(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(position_id): Path<Uuid>,
 ) -> impl IntoResponse {
     match state
@@ -1049,6 +1131,12 @@ pub async fn get_staking_position(
         .get_staking_position(position_id)
         .await
     {
+        Ok(position) if !claims.can_access_user(position.user_id, &Method::GET) => (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(
+                "Not authorized to access this user's resources".to_string(),
+            )),
+        ),
         Ok(position) => (StatusCode::OK, Json(ApiResponse::success(position))),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -1142,14 +1230,20 @@ pub struct SearchReceiptsRequest {
     pub page_size: Option<u32>,
 }
 
-/// Search receipts with filters and pagination
+/// Search the caller's receipts with filters and pagination
 pub async fn search_receipts(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<SearchReceiptsRequest>,
 ) -> impl IntoResponse {
     use crate::{Pagination, ReceiptSearchFilters, TransactionType};
     use chrono::DateTime;
 
+    let user_id = match caller_id::<crate::ReceiptSearchResults>(&claims) {
+        Ok(id) => id,
+        Err(e) => return e,
+    };
+
     // Parse transaction type
     let transaction_type = payload.transaction_type.and_then(|t| match t.to_uppercase().as_str() {
         "PAYMENT" => Some(TransactionType::Payment),
@@ -1186,7 +1280,7 @@ pub async fn search_receipts(
     // Search receipts
     match state
         .payment_receipt_service
-        .search_receipts(filters, pagination)
+        .search_receipts(user_id, filters, pagination)
         .await
     {
         Ok(results) => (StatusCode::OK, Json(ApiResponse::success(results))),
@@ -1198,14 +1292,20 @@ pub async fn search_receipts(
 }
 
 
-/// Export a single receipt as PDF
+/// Export one of the caller's receipts as PDF
 pub async fn export_receipt_pdf(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(receipt_id): Path<Uuid>,
 ) -> impl IntoResponse {
+    let user_id = match caller_id::<()>(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
     match state
         .payment_receipt_service
-        .export_receipt_pdf(receipt_id)
+        .export_receipt_pdf(user_id, receipt_id)
         .await
     {
         Ok(pdf_bytes) => (
@@ -1217,20 +1317,77 @@ pub async fn export_receipt_pdf(
             pdf_bytes,
         ).into_response(),
         Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            receipt_error_status(&e),
+            Json(ApiResponse::<()>::error(e.to_string())),
+        ).into_response(),
+    }
+}
+
+/// Export one of the caller's receipts as a signed receipt file
+pub async fn export_signed_receipt(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    Path(receipt_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match caller_id::<()>(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match state
+        .payment_receipt_service
+        .export_signed_receipt(user_id, receipt_id)
+        .await
+    {
+        Ok(signed) => (
+            StatusCode::OK,
+            [
+                ("Content-Type", "application/json"),
+                ("Content-Disposition", &format!("attachment; filename=\"receipt_{}.json\"", receipt_id)),
+            ],
+            signed.to_json(),
+        ).into_response(),
+        Err(e @ shared::Error::Validation(_)) => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        ).into_response(),
+        Err(e) => (
+            receipt_error_status(&e),
             Json(ApiResponse::<()>::error(e.to_string())),
         ).into_response(),
     }
 }
 
-/// Export receipt history as CSV
+#[derive(Serialize)]
+pub struct ReceiptSigningKeyResponse {
+    pub public_key: String,
+    pub format_version: String,
+}
+
+/// Public key receipt files are signed with
+pub async fn get_receipt_signing_key(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<ReceiptSigningKeyResponse>> {
+    Json(ApiResponse::success(ReceiptSigningKeyResponse {
+        public_key: state.payment_receipt_service.signing_key(),
+        format_version: crate::RECEIPT_FORMAT_VERSION.to_string(),
+    }))
+}
+
+/// Export the caller's receipt history as CSV
 pub async fn export_receipts_csv(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<SearchReceiptsRequest>,
 ) -> impl IntoResponse {
     use crate::{ReceiptSearchFilters, TransactionType};
     use chrono::DateTime;
 
+    let user_id = match caller_id::<()>(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
     // Parse transaction type
     let transaction_type = payload.transaction_type.and_then(|t| match t.to_uppercase().as_str() {
         "PAYMENT" => Some(TransactionType::Payment),
@@ -1261,7 +1418,7 @@ pub async fn export_receipts_csv(
     // Export as CSV
     match state
         .payment_receipt_service
-        .export_receipts_csv(filters)
+        .export_receipts_csv(user_id, filters)
         .await
     {
         Ok(csv_bytes) => {
@@ -1335,11 +1492,8 @@ pub async fn send_message(
 
     // Parse blockchain if provided
     let blockchain = if req.verify_on_chain {
-        match req.blockchain.as_deref() {
-            Some("Solana") => Some(blockchain::Blockchain::Solana),
-            Some("Ethereum") => Some(blockchain::Blockchain::Ethereum),
-            Some("BinanceSmartChain") | Some("BSC") => Some(blockchain::Blockchain::BinanceSmartChain),
-            Some("Polygon") => Some(blockchain::Blockchain::Polygon),
+        match req.blockchain.as_deref().map(|name| state.chains.resolve(name)) {
+            Some(Ok(blockchain)) => Some(blockchain),
             _ => {
                 return Err((
                     StatusCode::BAD_REQUEST,
@@ -1736,6 +1890,281 @@ pub async fn cancel_manual_order(
     }
 }
 
+// ============================================================================
+// Conditional Order Handlers
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct ConditionalOrdersQuery {
+    #[serde(default)]
+    pub status: Option<crate::ConditionalOrderStatus>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Place a stop-loss, take-profit or trailing stop on a position
+pub async fn create_conditional_order(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<crate::ConditionalOrderRequest>,
+) -> Result<Json<ApiResponse<crate::ConditionalOrder>>, (StatusCode, Json<ApiResponse<crate::ConditionalOrder>>)> {
+    match state
+        .conditional_order_service
+        .create_order(user_id, req)
+        .await
+    {
+        Ok(order) => Ok(Json(ApiResponse::success(order))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to create conditional order: {}", e))),
+        )),
+    }
+}
+
+/// Place a one-cancels-other take-profit and stop on a position
+pub async fn create_bracket_order(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<crate::BracketOrderRequest>,
+) -> Result<Json<ApiResponse<Vec<crate::ConditionalOrder>>>, (StatusCode, Json<ApiResponse<Vec<crate::ConditionalOrder>>>)> {
+    match state
+        .conditional_order_service
+        .create_bracket(user_id, req)
+        .await
+    {
+        Ok(orders) => Ok(Json(ApiResponse::success(orders))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to create bracket order: {}", e))),
+        )),
+    }
+}
+
+/// Get the user's conditional orders, optionally only those in one status
+pub async fn get_user_conditional_orders(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<ConditionalOrdersQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::ConditionalOrder>>>, (StatusCode, Json<ApiResponse<Vec<crate::ConditionalOrder>>>)> {
+    match state
+        .conditional_order_service
+        .get_user_orders(user_id, query.status, query.limit)
+        .await
+    {
+        Ok(orders) => Ok(Json(ApiResponse::success(orders))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get conditional orders: {}", e))),
+        )),
+    }
+}
+
+pub async fn get_conditional_order(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, order_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::ConditionalOrder>>, (StatusCode, Json<ApiResponse<crate::ConditionalOrder>>)> {
+    match state
+        .conditional_order_service
+        .get_order(order_id, user_id)
+        .await
+    {
+        Ok(order) => Ok(Json(ApiResponse::success(order))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get conditional order: {}", e))),
+        )),
+    }
+}
+
+/// Get the status history of a conditional order
+pub async fn get_conditional_order_transitions(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, order_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<crate::ConditionalOrderTransition>>>, (StatusCode, Json<ApiResponse<Vec<crate::ConditionalOrderTransition>>>)> {
+    match state
+        .conditional_order_service
+        .get_transitions(order_id, user_id)
+        .await
+    {
+        Ok(transitions) => Ok(Json(ApiResponse::success(transitions))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get order history: {}", e))),
+        )),
+    }
+}
+
+/// Cancel a conditional order and the rest of its one-cancels-other group
+pub async fn cancel_conditional_order(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, order_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<crate::ConditionalOrder>>>, (StatusCode, Json<ApiResponse<Vec<crate::ConditionalOrder>>>)> {
+    match state
+        .conditional_order_service
+        .cancel_order(order_id, user_id)
+        .await
+    {
+        Ok(orders) => Ok(Json(ApiResponse::success(orders))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to cancel conditional order: {}", e))),
+        )),
+    }
+}
+
+/// Status code for a conditional order or DCA schedule error; like manual
+/// orders, an insufficient balance is the caller's error
+fn order_error_status(error: &shared::Error) -> StatusCode {
+    match error {
+        shared::Error::Database(message) if message.contains("Insufficient balance") => StatusCode::BAD_REQUEST,
+        _ => p2p_error_status(error),
+    }
+}
+
+// ============================================================================
+// DCA Schedule Handlers
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct DcaSchedulesQuery {
+    #[serde(default)]
+    pub status: Option<crate::DcaScheduleStatus>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct DcaRunsQuery {
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Create a recurring buy or sell
+pub async fn create_dca_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<crate::DcaScheduleRequest>,
+) -> Result<Json<ApiResponse<crate::DcaSchedule>>, (StatusCode, Json<ApiResponse<crate::DcaSchedule>>)> {
+    match state
+        .dca_schedule_service
+        .create_schedule(user_id, req)
+        .await
+    {
+        Ok(schedule) => Ok(Json(ApiResponse::success(schedule))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to create DCA schedule: {}", e))),
+        )),
+    }
+}
+
+/// Get the user's DCA schedules, optionally only those in one status
+pub async fn get_user_dca_schedules(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<DcaSchedulesQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::DcaSchedule>>>, (StatusCode, Json<ApiResponse<Vec<crate::DcaSchedule>>>)> {
+    match state
+        .dca_schedule_service
+        .get_user_schedules(user_id, query.status, query.limit)
+        .await
+    {
+        Ok(schedules) => Ok(Json(ApiResponse::success(schedules))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get DCA schedules: {}", e))),
+        )),
+    }
+}
+
+pub async fn get_dca_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::DcaSchedule>>, (StatusCode, Json<ApiResponse<crate::DcaSchedule>>)> {
+    match state
+        .dca_schedule_service
+        .get_schedule(schedule_id, user_id)
+        .await
+    {
+        Ok(schedule) => Ok(Json(ApiResponse::success(schedule))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get DCA schedule: {}", e))),
+        )),
+    }
+}
+
+/// Get the runs of a DCA schedule, newest first
+pub async fn get_dca_runs(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, schedule_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<DcaRunsQuery>,
+) -> Result<Json<ApiResponse<Vec<crate::DcaRun>>>, (StatusCode, Json<ApiResponse<Vec<crate::DcaRun>>>)> {
+    match state
+        .dca_schedule_service
+        .get_runs(schedule_id, user_id, query.limit)
+        .await
+    {
+        Ok(runs) => Ok(Json(ApiResponse::success(runs))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get DCA runs: {}", e))),
+        )),
+    }
+}
+
+pub async fn pause_dca_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::DcaSchedule>>, (StatusCode, Json<ApiResponse<crate::DcaSchedule>>)> {
+    match state
+        .dca_schedule_service
+        .pause_schedule(schedule_id, user_id)
+        .await
+    {
+        Ok(schedule) => Ok(Json(ApiResponse::success(schedule))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to pause DCA schedule: {}", e))),
+        )),
+    }
+}
+
+/// Resume a paused DCA schedule from its next run
+pub async fn resume_dca_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::DcaSchedule>>, (StatusCode, Json<ApiResponse<crate::DcaSchedule>>)> {
+    match state
+        .dca_schedule_service
+        .resume_schedule(schedule_id, user_id)
+        .await
+    {
+        Ok(schedule) => Ok(Json(ApiResponse::success(schedule))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to resume DCA schedule: {}", e))),
+        )),
+    }
+}
+
+pub async fn cancel_dca_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::DcaSchedule>>, (StatusCode, Json<ApiResponse<crate::DcaSchedule>>)> {
+    match state
+        .dca_schedule_service
+        .cancel_schedule(schedule_id, user_id)
+        .await
+    {
+        Ok(schedule) => Ok(Json(ApiResponse::success(schedule))),
+        Err(e) => Err((
+            order_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to cancel DCA schedule: {}", e))),
+        )),
+    }
+}
+
 // ============================================================================
 // Benchmark Handlers
 // ============================================================================
@@ -1967,6 +2396,14 @@ pub struct CreateP2POfferRequest {
     pub from_amount: String,
     pub to_amount: String,
     pub price: String,
+    /// Transaction depositing the sold asset into escrow; required for sell
+    /// offers when escrow is enabled
+    #[serde(default)]
+    pub escrow_deposit_tx: Option<String>,
+    /// Id of the escrow the deposit opens; the deposit must carry its memo
+    /// (`p2p-escrow:<escrow_id>`)
+    #[serde(default)]
+    pub escrow_id: Option<Uuid>,
 }
 
 #[derive(Serialize)]
@@ -2029,9 +2466,20 @@ pub async fn create_p2p_offer(
         )
     })?;
 
+    let escrow_deposit = match (req.escrow_deposit_tx, req.escrow_id) {
+        (Some(tx_hash), Some(escrow_id)) => Some(crate::OfferDeposit { escrow_id, tx_hash }),
+        (Some(_), None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("escrow_id is required with escrow_deposit_tx".to_string())),
+            ));
+        }
+        (None, _) => None,
+    };
+
     match state
         .p2p_service
-        .create_offer(
+        .create_offer_with_deposit(
             user_id,
             offer_type,
             req.from_asset,
@@ -2040,12 +2488,13 @@ pub async fn create_p2p_offer(
             to_amount,
             price,
             false, // Regular P2P offers are not proximity offers by default
+            escrow_deposit,
         )
         .await
     {
         Ok(offer) => Ok(Json(ApiResponse::success(offer))),
         Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
+            p2p_error_status(&e),
             Json(ApiResponse::error(format!("Failed to create offer: {}", e))),
         )),
     }
@@ -2133,7 +2582,8 @@ pub async fn get_offer(
     let row = match client
         .query_opt(
             "SELECT id, user_id, offer_type, from_asset, to_asset, from_amount, to_amount, 
-                    price, status, escrow_tx_hash, matched_with_offer_id, is_proximity_offer, created_at, expires_at
+                    price, status, escrow_tx_hash, matched_with_offer_id, is_proximity_offer, created_at, expires_at,
+                    remaining_amount
              FROM p2p_offers WHERE id = $1",
             &[&offer_id],
         )
@@ -2187,6 +2637,8 @@ pub async fn get_offer(
         acceptor_id: None,
         accepted_at: None,
         conversation_id: None,
+        remaining_amount: row.get(14),
+        creator_reputation: None,
         created_at: row.get(12),
         expires_at: row.get(13),
     };
@@ -2320,6 +2772,23 @@ pub async fn cancel_p2p_offer(
     }
 }
 
+/// Fill a P2P offer against the order book
+///
+/// Proximity offers may only trade with peers the user's own active
+/// discovery session found nearby.
+pub async fn match_p2p_offer(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, offer_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<crate::P2PFill>>>, (StatusCode, Json<ApiResponse<Vec<crate::P2PFill>>>)> {
+    match state.p2p_service.match_offer(offer_id, user_id).await {
+        Ok(fills) => Ok(Json(ApiResponse::success(fills))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to match offer: {}", e))),
+        )),
+    }
+}
+
 /// Get user's P2P exchanges
 pub async fn get_user_exchanges(
     State(state): State<Arc<AppState>>,
@@ -2334,6 +2803,502 @@ pub async fn get_user_exchanges(
     }
 }
 
+#[derive(Deserialize)]
+pub struct EscrowDepositRequest {
+    pub tx_hash: String,
+}
+
+#[derive(Serialize)]
+pub struct EscrowAddressResponse {
+    pub blockchain: blockchain::Blockchain,
+    pub address: String,
+}
+
+#[derive(Serialize)]
+pub struct EscrowDetailsResponse {
+    pub escrow: crate::P2PEscrow,
+    pub transitions: Vec<crate::EscrowTransition>,
+}
+
+/// Get the address sellers deposit escrowed funds to
+pub async fn get_p2p_escrow_address(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<EscrowAddressResponse>>, (StatusCode, Json<ApiResponse<EscrowAddressResponse>>)> {
+    match state.p2p_service.escrow() {
+        Ok(escrow) => Ok(Json(ApiResponse::success(EscrowAddressResponse {
+            blockchain: escrow.blockchain(),
+            address: escrow.escrow_address(),
+        }))),
+        Err(e) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::error(e.to_string())),
+        )),
+    }
+}
+
+/// Fund the escrow of a matched buy offer
+pub async fn deposit_p2p_escrow(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, exchange_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<EscrowDepositRequest>,
+) -> Result<Json<ApiResponse<crate::P2PEscrow>>, (StatusCode, Json<ApiResponse<crate::P2PEscrow>>)> {
+    match state
+        .p2p_service
+        .deposit_escrow(exchange_id, user_id, &req.tx_hash)
+        .await
+    {
+        Ok(escrow) => Ok(Json(ApiResponse::success(escrow))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to fund escrow: {}", e))),
+        )),
+    }
+}
+
+/// Confirm a P2P exchange; the escrow is released once both sides confirm
+pub async fn confirm_p2p_exchange(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, exchange_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::P2PEscrow>>, (StatusCode, Json<ApiResponse<crate::P2PEscrow>>)> {
+    match state.p2p_service.confirm_exchange(exchange_id, user_id).await {
+        Ok(escrow) => Ok(Json(ApiResponse::success(escrow))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to confirm exchange: {}", e))),
+        )),
+    }
+}
+
+/// Get the escrow of a P2P exchange with its status history
+pub async fn get_p2p_exchange_escrow(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, exchange_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<EscrowDetailsResponse>>, (StatusCode, Json<ApiResponse<EscrowDetailsResponse>>)> {
+    let result = async {
+        let escrow_service = state.p2p_service.escrow()?;
+        let escrow = escrow_service.get_escrow_for_exchange(exchange_id).await?;
+        if escrow.seller_user_id != user_id && escrow.buyer_user_id != Some(user_id) {
+            return Err(shared::Error::Unauthorized);
+        }
+        let transitions = escrow_service.get_transitions(escrow.id).await?;
+        Ok::<_, shared::Error>(EscrowDetailsResponse { escrow, transitions })
+    }
+    .await;
+
+    match result {
+        Ok(details) => Ok(Json(ApiResponse::success(details))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get escrow: {}", e))),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RateExchangeRequest {
+    pub score: i16,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenDisputeRequest {
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+pub struct AddDisputeEvidenceRequest {
+    pub kind: crate::EvidenceKind,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveDisputeRequest {
+    pub outcome: crate::DisputeOutcome,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TraderReputationResponse {
+    pub reputation: crate::UserReputation,
+    pub recent_ratings: Vec<crate::P2PRating>,
+}
+
+/// Rate the counterparty of a settled P2P exchange
+pub async fn rate_p2p_exchange(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, exchange_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<RateExchangeRequest>,
+) -> Result<Json<ApiResponse<crate::P2PRating>>, (StatusCode, Json<ApiResponse<crate::P2PRating>>)> {
+    match state
+        .reputation_service
+        .rate_exchange(exchange_id, user_id, req.score, req.comment)
+        .await
+    {
+        Ok(rating) => Ok(Json(ApiResponse::success(rating))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to rate exchange: {}", e))),
+        )),
+    }
+}
+
+/// Get a trader's reputation and most recent ratings
+pub async fn get_p2p_trader_reputation(
+    State(state): State<Arc<AppState>>,
+    Path(trader_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TraderReputationResponse>>, (StatusCode, Json<ApiResponse<TraderReputationResponse>>)> {
+    let result = async {
+        let reputation = state.reputation_service.get_reputation(trader_id).await?;
+        let recent_ratings = state.reputation_service.get_ratings(trader_id, 20).await?;
+        Ok::<_, shared::Error>(TraderReputationResponse { reputation, recent_ratings })
+    }
+    .await;
+
+    match result {
+        Ok(response) => Ok(Json(ApiResponse::success(response))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get reputation: {}", e))),
+        )),
+    }
+}
+
+/// Dispute a P2P exchange, holding its escrow until an arbitrator decides
+pub async fn open_p2p_dispute(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, exchange_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<OpenDisputeRequest>,
+) -> Result<Json<ApiResponse<crate::P2PDispute>>, (StatusCode, Json<ApiResponse<crate::P2PDispute>>)> {
+    match state
+        .p2p_dispute_service
+        .open_dispute(exchange_id, user_id, &req.reason)
+        .await
+    {
+        Ok(dispute) => Ok(Json(ApiResponse::success(dispute))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to open dispute: {}", e))),
+        )),
+    }
+}
+
+/// Get a dispute with its evidence, as a party to it or an arbitrator
+pub async fn get_p2p_dispute(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    Path((user_id, dispute_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<crate::DisputeDetails>>, (StatusCode, Json<ApiResponse<crate::DisputeDetails>>)> {
+    match state
+        .p2p_dispute_service
+        .get_dispute(dispute_id, user_id, claims.is_arbitrator())
+        .await
+    {
+        Ok(details) => Ok(Json(ApiResponse::success(details))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get dispute: {}", e))),
+        )),
+    }
+}
+
+/// Attach a chat message, transaction hash or note to an open dispute
+pub async fn add_p2p_dispute_evidence(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    Path((user_id, dispute_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<AddDisputeEvidenceRequest>,
+) -> Result<Json<ApiResponse<crate::DisputeEvidence>>, (StatusCode, Json<ApiResponse<crate::DisputeEvidence>>)> {
+    match state
+        .p2p_dispute_service
+        .add_evidence(dispute_id, user_id, claims.is_arbitrator(), req.kind, req.reference, req.note)
+        .await
+    {
+        Ok(evidence) => Ok(Json(ApiResponse::success(evidence))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to add evidence: {}", e))),
+        )),
+    }
+}
+
+/// List open disputes (arbitrators only)
+pub async fn list_p2p_disputes(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<ApiResponse<Vec<crate::P2PDispute>>>, (StatusCode, Json<ApiResponse<Vec<crate::P2PDispute>>>)> {
+    if !claims.is_arbitrator() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Arbitrator access required".to_string())),
+        ));
+    }
+    let limit = params
+        .get("limit")
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(50);
+
+    match state.p2p_dispute_service.list_open_disputes(limit).await {
+        Ok(disputes) => Ok(Json(ApiResponse::success(disputes))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to list disputes: {}", e))),
+        )),
+    }
+}
+
+/// Resolve a dispute, releasing or refunding the exchange's escrow
+/// (arbitrators only)
+pub async fn resolve_p2p_dispute(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    Path(dispute_id): Path<Uuid>,
+    Json(req): Json<ResolveDisputeRequest>,
+) -> Result<Json<ApiResponse<crate::P2PDispute>>, (StatusCode, Json<ApiResponse<crate::P2PDispute>>)> {
+    let arbitrator_id = match claims.user_id() {
+        Some(id) if claims.is_arbitrator() => id,
+        _ => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::error("Arbitrator access required".to_string())),
+            ));
+        }
+    };
+
+    match state
+        .p2p_dispute_service
+        .resolve_dispute(dispute_id, arbitrator_id, req.outcome, req.note)
+        .await
+    {
+        Ok(dispute) => Ok(Json(ApiResponse::success(dispute))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to resolve dispute: {}", e))),
+        )),
+    }
+}
+
+fn p2p_error_status(error: &shared::Error) -> StatusCode {
+    match error {
+        shared::Error::Validation(message) if message.contains("not found") => StatusCode::NOT_FOUND,
+        shared::Error::Validation(_)
+        | shared::Error::InvalidWalletAddress(_)
+        | shared::Error::WalletNotFound(_) => StatusCode::BAD_REQUEST,
+        shared::Error::Unauthorized => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// ============================================================================
+// Tax Lot Handlers
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct TaxSettingsRequest {
+    pub disposal_method: crate::DisposalMethod,
+    /// When the method starts applying, now by default; must be after the
+    /// user's last recorded disposal
+    #[serde(default)]
+    pub effective_from: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct TaxSettingsResponse {
+    /// Method in effect now
+    pub disposal_method: crate::DisposalMethod,
+    pub method_changes: Vec<crate::DisposalMethodChange>,
+}
+
+impl TaxSettingsResponse {
+    fn from_changes(method_changes: Vec<crate::DisposalMethodChange>) -> Self {
+        Self {
+            disposal_method: crate::tax_lots::method_at(&method_changes, chrono::Utc::now()),
+            method_changes,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SelectedLot {
+    pub lot_id: Uuid,
+    pub amount: rust_decimal::Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct SelectLotsRequest {
+    pub lots: Vec<SelectedLot>,
+}
+
+/// Get the user's open tax lots with unrealized P&L and their realized disposals
+pub async fn get_tax_lots(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<crate::TaxLotReport>>, (StatusCode, Json<ApiResponse<crate::TaxLotReport>>)> {
+    match state.tax_lot_service.get_lot_report(user_id).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get tax lots: {}", e))),
+        )),
+    }
+}
+
+pub async fn get_tax_settings(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TaxSettingsResponse>>, (StatusCode, Json<ApiResponse<TaxSettingsResponse>>)> {
+    match state.tax_lot_service.get_method_changes(user_id).await {
+        Ok(changes) => Ok(Json(ApiResponse::success(TaxSettingsResponse::from_changes(changes)))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to get tax settings: {}", e))),
+        )),
+    }
+}
+
+/// Set the order in which the user's future disposals consume tax lots
+pub async fn update_tax_settings(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<TaxSettingsRequest>,
+) -> Result<Json<ApiResponse<TaxSettingsResponse>>, (StatusCode, Json<ApiResponse<TaxSettingsResponse>>)> {
+    let effective_from = req.effective_from.unwrap_or_else(chrono::Utc::now);
+    let result = match state
+        .tax_lot_service
+        .set_disposal_method(user_id, req.disposal_method, effective_from)
+        .await
+    {
+        Ok(()) => state.tax_lot_service.get_method_changes(user_id).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(changes) => Ok(Json(ApiResponse::success(TaxSettingsResponse::from_changes(changes)))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to update tax settings: {}", e))),
+        )),
+    }
+}
+
+/// Choose the lots a disposal consumes under the specific-ID method
+pub async fn select_tax_lots(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, source, disposal_id)): Path<(Uuid, String, Uuid)>,
+    Json(req): Json<SelectLotsRequest>,
+) -> Result<Json<ApiResponse<Vec<crate::LotSelection>>>, (StatusCode, Json<ApiResponse<Vec<crate::LotSelection>>>)> {
+    let source = match source.parse::<crate::TaxEventSource>() {
+        Ok(source) => source,
+        Err(e) => {
+            return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::error(e.to_string()))));
+        }
+    };
+    let lots: Vec<(Uuid, rust_decimal::Decimal)> = req.lots.iter().map(|l| (l.lot_id, l.amount)).collect();
+
+    match state
+        .tax_lot_service
+        .select_lots(user_id, source, disposal_id, &lots)
+        .await
+    {
+        Ok(selections) => Ok(Json(ApiResponse::success(selections))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to select tax lots: {}", e))),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TaxReportQuery {
+    /// `US` (default), `UK`, `CA`, `AU` or `DE`
+    #[serde(default)]
+    pub jurisdiction: Option<crate::TaxJurisdiction>,
+    /// `csv` (default) or `pdf`
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Generate the user's tax report for a tax year
+pub async fn get_tax_report(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, tax_year)): Path<(Uuid, i32)>,
+    Query(query): Query<TaxReportQuery>,
+) -> Result<Json<ApiResponse<crate::TaxReport>>, (StatusCode, Json<ApiResponse<crate::TaxReport>>)> {
+    let jurisdiction = query.jurisdiction.unwrap_or(crate::TaxJurisdiction::Us);
+
+    match state
+        .tax_report_service
+        .generate_report(user_id, tax_year, jurisdiction)
+        .await
+    {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err((
+            p2p_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to generate tax report: {}", e))),
+        )),
+    }
+}
+
+/// Download the capital gains, income or holdings part of a tax report as CSV or PDF
+pub async fn export_tax_report(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, tax_year, kind)): Path<(Uuid, i32, String)>,
+    Query(query): Query<TaxReportQuery>,
+) -> impl IntoResponse {
+    let jurisdiction = query.jurisdiction.unwrap_or(crate::TaxJurisdiction::Us);
+    let kind = match kind.parse::<crate::TaxReportKind>() {
+        Ok(kind) => kind,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(e.to_string()))).into_response();
+        }
+    };
+    let format = query.format.unwrap_or_else(|| "csv".to_string()).to_lowercase();
+    if format != "csv" && format != "pdf" {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("Unsupported report format: {}", format))),
+        )
+            .into_response();
+    }
+
+    let exported = match state
+        .tax_report_service
+        .generate_report(user_id, tax_year, jurisdiction)
+        .await
+    {
+        Ok(report) if format == "pdf" => report.to_pdf(kind),
+        Ok(report) => report.to_csv(kind),
+        Err(e) => Err(e),
+    };
+
+    match exported {
+        Ok(bytes) => (
+            StatusCode::OK,
+            [
+                ("Content-Type", if format == "pdf" { "application/pdf" } else { "text/csv" }),
+                (
+                    "Content-Disposition",
+                    &format!(
+                        "attachment; filename=\"{}_{}_{}.{}\"",
+                        kind.as_str(),
+                        jurisdiction.as_str().to_lowercase(),
+                        tax_year,
+                        format
+                    ),
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => (p2p_error_status(&e), Json(ApiResponse::<()>::error(e.to_string()))).into_response(),
+    }
+}
+
 // ============================================================================
 // Verification Handlers
 // ============================================================================
@@ -2344,13 +3309,35 @@ pub struct SubmitIdentityVerificationRequest {
     pub document_data: String, // Base64 encoded
 }
 
+#[derive(Deserialize)]
+pub struct CreateWalletChallengeRequest {
+    pub wallet_address: String,
+    pub blockchain: String,
+}
+
 #[derive(Deserialize)]
 pub struct VerifyWalletRequest {
     pub wallet_address: String,
     pub blockchain: String,
+    pub challenge_id: Uuid,
     pub signature: String,
 }
 
+fn wallet_verification_error_status(error: &crate::WalletVerificationError) -> StatusCode {
+    use crate::WalletVerificationError;
+
+    match error {
+        WalletVerificationError::InvalidWalletAddress(_)
+        | WalletVerificationError::UnsupportedBlockchain(_)
+        | WalletVerificationError::ChallengeMismatch => StatusCode::BAD_REQUEST,
+        WalletVerificationError::ChallengeNotFound => StatusCode::NOT_FOUND,
+        WalletVerificationError::ChallengeExpired => StatusCode::GONE,
+        WalletVerificationError::ChallengeReplayed => StatusCode::CONFLICT,
+        WalletVerificationError::InvalidSignature(_) => StatusCode::UNAUTHORIZED,
+        WalletVerificationError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 /// Get verification status
 pub async fn get_verification_status(
     State(state): State<Arc<AppState>>,
@@ -2387,18 +3374,44 @@ pub async fn submit_identity_verification(
     }
 }
 
+/// Issue a wallet ownership challenge to be signed by the wallet
+pub async fn create_wallet_challenge(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<CreateWalletChallengeRequest>,
+) -> Result<Json<ApiResponse<crate::WalletChallenge>>, (StatusCode, Json<ApiResponse<crate::WalletChallenge>>)> {
+    let blockchain = match state.chains.resolve(&req.blockchain) {
+        Ok(blockchain) => blockchain,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Invalid blockchain".to_string())),
+            ));
+        }
+    };
+
+    match state
+        .verification_service
+        .create_wallet_ownership_challenge(user_id, req.wallet_address, blockchain)
+        .await
+    {
+        Ok(challenge) => Ok(Json(ApiResponse::success(challenge))),
+        Err(e) => Err((
+            wallet_verification_error_status(&e),
+            Json(ApiResponse::error(format!("Failed to create challenge: {}", e))),
+        )),
+    }
+}
+
 /// Verify wallet ownership
 pub async fn verify_wallet_ownership(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<Uuid>,
     Json(req): Json<VerifyWalletRequest>,
 ) -> Result<Json<ApiResponse<crate::WalletVerification>>, (StatusCode, Json<ApiResponse<crate::WalletVerification>>)> {
-    let blockchain = match req.blockchain.as_str() {
-        "Solana" => blockchain::Blockchain::Solana,
-        "Ethereum" => blockchain::Blockchain::Ethereum,
-        "BinanceSmartChain" | "BSC" => blockchain::Blockchain::BinanceSmartChain,
-        "Polygon" => blockchain::Blockchain::Polygon,
-        _ => {
+    let blockchain = match state.chains.resolve(&req.blockchain) {
+        Ok(blockchain) => blockchain,
+        Err(_) => {
             return Err((
                 StatusCode::BAD_REQUEST,
                 Json(ApiResponse::error("Invalid blockchain".to_string())),
@@ -2408,12 +3421,12 @@ pub async fn verify_wallet_ownership(
 
     match state
         .verification_service
-        .verify_wallet_ownership(user_id, req.wallet_address, blockchain, req.signature)
+        .verify_wallet_ownership(user_id, req.wallet_address, blockchain, req.challenge_id, req.signature)
         .await
     {
         Ok(verification) => Ok(Json(ApiResponse::success(verification))),
         Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
+            wallet_verification_error_status(&e),
             Json(ApiResponse::error(format!("Failed to verify wallet: {}", e))),
         )),
     }
@@ -2477,12 +3490,9 @@ pub async fn create_temporary_wallet(
 ) -> Result<Json<ApiResponse<crate::TemporaryWallet>>, (StatusCode, Json<ApiResponse<crate::TemporaryWallet>>)> {
     use chrono::DateTime;
 
-    let blockchain = match req.blockchain.to_lowercase().as_str() {
-        "solana" => blockchain::Blockchain::Solana,
-        "ethereum" => blockchain::Blockchain::Ethereum,
-        "binancesmartchain" | "bsc" => blockchain::Blockchain::BinanceSmartChain,
-        "polygon" => blockchain::Blockchain::Polygon,
-        _ => {
+    let blockchain = match state.chains.resolve(&req.blockchain) {
+        Ok(blockchain) => blockchain,
+        Err(_) => {
             return Err((
                 StatusCode::BAD_REQUEST,
                 Json(ApiResponse::error("Invalid blockchain".to_string())),
@@ -2746,8 +3756,10 @@ pub struct ProviderStatusResponse {
 /// Requirements: 1.1, 1.2
 pub async fn enable_mesh_provider(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<EnableProviderRequest>,
 ) -> Result<Json<ApiResponse<ProviderStatusResponse>>, (StatusCode, Json<ApiResponse<ProviderStatusResponse>>)> {
+    require_admin(&claims)?;
     match state
         .mesh_price_service
         .enable_provider_mode(payload.api_key)
@@ -2777,7 +3789,9 @@ pub async fn enable_mesh_provider(
 /// Requirements: 1.2, 1.5
 pub async fn disable_mesh_provider(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<Json<ApiResponse<ProviderStatusResponse>>, (StatusCode, Json<ApiResponse<ProviderStatusResponse>>)> {
+    require_admin(&claims)?;
     match state.mesh_price_service.disable_provider_mode().await {
         Ok(_) => {
             let node_id = state.mesh_price_service.node_id().to_string();
@@ -23,13 +23,21 @@ pub mod trim_config_service;
 pub mod position_evaluator;
 pub mod trim_executor;
 pub mod receipt_service;
+pub mod receipt_format;
 pub mod payment_receipt_service;
 pub mod chat_service;
 pub mod p2p_service;
+pub mod p2p_escrow;
+pub mod p2p_matching;
+pub mod p2p_disputes;
+pub mod p2p_reputation;
 pub mod verification_service;
+pub mod wallet_signature;
 pub mod privacy_service;
 pub mod websocket_service;
 pub mod position_management_service;
+pub mod conditional_orders;
+pub mod dca_schedules;
 pub mod token_metadata_service;
 pub mod cross_chain_transaction_service;
 pub mod proximity_receipt_integration;
@@ -45,7 +53,12 @@ pub mod provider_node;
 pub mod network_status_tracker;
 pub mod mesh_price_service;
 pub mod price_update_validator;
+pub mod provider_identity;
 pub mod mesh_metrics;
+pub mod price_sources;
+pub mod merkle;
+pub mod tax_lots;
+pub mod tax_reports;
 
 pub use wallet_service::WalletService;
 pub use portfolio_cache::PortfolioCache;
@@ -62,20 +75,52 @@ pub use staking_service::{StakingService, StakingConfig, StakingPosition, Stakin
 pub use trim_config_service::{TrimConfigService, TrimConfig, UpdateTrimConfigRequest};
 pub use position_evaluator::{PositionEvaluator, Position, TrimRecommendation};
 pub use trim_executor::{TrimExecutor, TrimExecution, PendingTrim};
-pub use receipt_service::{ReceiptService, Receipt, ReceiptData, VerificationStatus};
+pub use receipt_service::{
+    AnchoringMode, InclusionProof, OnChainReceiptAnchor, Receipt, ReceiptAnchor, ReceiptData, ReceiptService,
+    VerificationStatus, ANCHOR_MEMO_PREFIX,
+};
+pub use receipt_format::{verify_signed_receipt, ReceiptPayload, ReceiptSigner, SignedReceipt, RECEIPT_FORMAT_VERSION};
 pub use payment_receipt_service::{
     PaymentReceiptService, PaymentReceipt, TransactionType, TransactionFees, 
     BlockchainConfirmation, ReceiptSearchFilters, Pagination, ReceiptSearchResults
 };
 pub use chat_service::{ChatService, ChatMessage};
 pub use p2p_service::{P2PService, P2POffer, P2PExchange, OfferType, OfferStatus};
-pub use verification_service::{VerificationService, WalletVerification, VerificationLevel, VerificationStatus as IdentityVerificationStatus};
+pub use p2p_escrow::{
+    deposit_memo, EscrowCustodian, EscrowDeposit, EscrowService, EscrowStatus, EscrowTransition, OfferDeposit,
+    P2PEscrow, PayoutStatus, SignedPayout, SolanaEscrowCustodian, TransitionReason,
+};
+pub use p2p_matching::{MatchingEngine, P2PFill};
+pub use p2p_disputes::{
+    DisputeDetails, DisputeEvidence, DisputeOutcome, DisputeService, DisputeStatus, EvidenceKind, P2PDispute,
+};
+pub use p2p_reputation::{P2PRating, ReputationService, UserReputation};
+pub use tax_lots::{
+    Disposal, DisposalMethod, DisposalMethodChange, HoldingPeriod, LotLedger, LotSelection, LotValuation, RealizedGain, TaxEvent,
+    TaxEventKind, TaxEventSource, TaxLot, TaxLotReport, TaxLotService,
+};
+pub use tax_reports::{
+    CapitalGainLine, HoldingLine, IncomeCategory, IncomeLine, TaxJurisdiction, TaxReport, TaxReportKind,
+    TaxReportService, TaxReportSummary,
+};
+pub use verification_service::{VerificationService, WalletVerification, WalletChallenge, WalletVerificationError, VerificationLevel, VerificationStatus as IdentityVerificationStatus};
 pub use privacy_service::{PrivacyService, TemporaryWallet};
-pub use websocket_service::{WebSocketService, DashboardUpdate, websocket_handler};
+pub use websocket_service::{
+    websocket_handler, ClientMessage, DashboardUpdate, Subscriptions, Topic, WebSocketPushChannel,
+    WebSocketService,
+};
 pub use position_management_service::{
     PositionManagementService, PositionMode, PositionModeConfig, ManualOrder, 
     ManualOrderRequest, PendingAutomaticOrder
 };
+pub use conditional_orders::{
+    BracketOrderRequest, ConditionalOrder, ConditionalOrderRequest, ConditionalOrderService,
+    ConditionalOrderStatus, ConditionalOrderTransition, ConditionalOrderType,
+};
+pub use dca_schedules::{
+    Cadence, CatchUpPolicy, CronSchedule, DcaRun, DcaRunStatus, DcaSchedule, DcaScheduleRequest,
+    DcaScheduleService, DcaScheduleStatus, DcaVenue, DueRuns,
+};
 pub use token_metadata_service::{TokenMetadataService, TokenMetadata, TokenType};
 pub use cross_chain_transaction_service::{
     CrossChainTransactionService, NormalizedTransaction, TransactionStatus,
@@ -91,11 +136,13 @@ pub use provider_node::ProviderNode;
 pub use network_status_tracker::NetworkStatusTracker;
 pub use mesh_price_service::MeshPriceService;
 pub use price_update_validator::PriceUpdateValidator;
+pub use provider_identity::{ProviderIdentity, ProviderKeyStore, ProviderTrustPolicy};
 pub use mesh_metrics::{MeshMetricsCollector, MeshMetrics, MeshMetricsSummary};
+pub use price_sources::{BirdeyePriceSource, CoinMarketCapPriceSource, MeshPriceSource, price_feed_from_config};
 pub use error::{ApiError, ApiResult, ErrorResponse};
 pub use monitoring::{MetricsCollector, ServiceMetrics, ServiceMetric, HealthStatus, RequestTimer, AlertManager};
 
-use blockchain::SolanaClient;
+use blockchain::{ChainRegistry, SolanaClient};
 use deadpool_postgres::Pool;
 use redis::aio::ConnectionManager;
 use std::sync::Arc;
@@ -114,10 +161,16 @@ pub struct AppState {
     pub trim_config_service: Arc<TrimConfigService>,
     pub trim_executor: Arc<TrimExecutor>,
     pub payment_receipt_service: Arc<PaymentReceiptService>,
+    pub tax_lot_service: Arc<TaxLotService>,
+    pub tax_report_service: Arc<TaxReportService>,
     pub chat_service: Arc<ChatService>,
     pub websocket_service: Arc<WebSocketService>,
     pub position_management_service: Arc<PositionManagementService>,
+    pub conditional_order_service: Arc<ConditionalOrderService>,
+    pub dca_schedule_service: Arc<DcaScheduleService>,
     pub p2p_service: Arc<P2PService>,
+    pub p2p_dispute_service: Arc<DisputeService>,
+    pub reputation_service: Arc<ReputationService>,
     pub verification_service: Arc<VerificationService>,
     pub privacy_service: Arc<PrivacyService>,
     pub proximity_discovery_service: Arc<DiscoveryService>,
@@ -129,6 +182,7 @@ pub struct AppState {
     pub db_pool: Pool,
     pub redis_pool: ConnectionManager,
     pub solana_client: Arc<SolanaClient>,
+    pub chains: Arc<ChainRegistry>,
     pub metrics: MetricsCollector,
 }
 
@@ -144,10 +198,16 @@ impl AppState {
         trim_config_service: Arc<TrimConfigService>,
         trim_executor: Arc<TrimExecutor>,
         payment_receipt_service: Arc<PaymentReceiptService>,
+        tax_lot_service: Arc<TaxLotService>,
+        tax_report_service: Arc<TaxReportService>,
         chat_service: Arc<ChatService>,
         websocket_service: Arc<WebSocketService>,
         position_management_service: Arc<PositionManagementService>,
+        conditional_order_service: Arc<ConditionalOrderService>,
+        dca_schedule_service: Arc<DcaScheduleService>,
         p2p_service: Arc<P2PService>,
+        p2p_dispute_service: Arc<DisputeService>,
+        reputation_service: Arc<ReputationService>,
         verification_service: Arc<VerificationService>,
         privacy_service: Arc<PrivacyService>,
         proximity_discovery_service: Arc<DiscoveryService>,
@@ -159,6 +219,7 @@ impl AppState {
         db_pool: Pool,
         redis_pool: ConnectionManager,
         solana_client: Arc<SolanaClient>,
+        chains: Arc<ChainRegistry>,
         metrics: MetricsCollector,
     ) -> Self {
         Self {
@@ -172,10 +233,16 @@ impl AppState {
             trim_config_service,
             trim_executor,
             payment_receipt_service,
+            tax_lot_service,
+            tax_report_service,
             chat_service,
             websocket_service,
             position_management_service,
+            conditional_order_service,
+            dca_schedule_service,
             p2p_service,
+            p2p_dispute_service,
+            reputation_service,
             verification_service,
             privacy_service,
             proximity_discovery_service,
@@ -187,6 +254,7 @@ impl AppState {
             db_pool,
             redis_pool,
             solana_client,
+            chains,
             metrics,
         }
     }
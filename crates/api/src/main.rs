@@ -1,5 +1,5 @@
 use anyhow::Result;
-use api::{AnalyticsService, AppState, BenchmarkService, ChatService, CoinMarketCapService, ConversionService, MeshPriceService, P2PService, PaymentReceiptService, PortfolioMonitor, PositionEvaluator, PositionManagementService, PriceMonitor, PrivacyService, ReceiptService, SideShiftClient, StakingService, TrimConfigService, TrimExecutor, VerificationService, WalletService, WebSocketService, WhaleDetectionService};
+use api::{AnalyticsService, AppState, BenchmarkService, ChatService, CoinMarketCapService, ConversionService, MeshPriceService, P2PService, PaymentReceiptService, PortfolioMonitor, PositionEvaluator, PositionManagementService, PriceMonitor, PrivacyService, ReceiptService, SideShiftClient, StakingService, TrimConfigService, TrimExecutor, VerificationService, WalletService, WebSocketPushChannel, WebSocketService, WhaleDetectionService};
 use blockchain::SolanaClient;
 use database::{create_pool, create_redis_client, create_redis_pool, run_migrations};
 use notification::NotificationService;
@@ -46,6 +46,13 @@ async fn main() -> Result<()> {
     let solana_client = Arc::new(SolanaClient::new(config.solana.rpc_url.clone(), None));
     tracing::info!("Solana client initialized");
 
+    // Chains the services accept, built-in ones extended by the chains config
+    let chains = Arc::new(match &config.chains.config_path {
+        Some(path) => blockchain::ChainRegistry::load(path)?,
+        None => blockchain::ChainRegistry::default(),
+    });
+    tracing::info!("Chain registry loaded ({} chains)", chains.chains().len());
+
     // Initialize Helius client for wallet analytics
     let helius_api_key = std::env::var("HELIUS_API_KEY")
         .unwrap_or_else(|_| "1266cbb3-f966-49e2-91f0-d3d04e52e69a".to_string());
@@ -60,6 +67,74 @@ async fn main() -> Result<()> {
     ));
     tracing::info!("Helius client initialized (using {})", if use_mainnet { "mainnet" } else { "devnet" });
 
+    // Initialize CoinMarketCap service for real-time crypto prices
+    let coinmarketcap_api_key = std::env::var("COINMARKETCAP_API_KEY")
+        .unwrap_or_else(|_| "7c900818e1a14a3eb98ce42e9ac293e5".to_string());
+    let coinmarketcap_service = Arc::new(CoinMarketCapService::new(
+        coinmarketcap_api_key,
+        redis_pool.clone(),
+    ));
+    tracing::info!("CoinMarketCap service initialized");
+
+    // Initialize WebSocket service for real-time dashboard updates
+    let websocket_service = Arc::new(WebSocketService::new());
+    tracing::info!("WebSocket service initialized");
+
+    // Initialize mesh price service for P2P price data distribution
+    // Uses the proximity P2P connection infrastructure for message routing
+    let peer_connection_manager = Arc::new(proximity::PeerConnectionManager::new());
+    let provider_identity = match &config.mesh_network.provider_keypair_path {
+        Some(path) => api::ProviderIdentity::from_file(path)?,
+        None => api::ProviderIdentity::generate(),
+    };
+    let provider_keys = api::ProviderKeyStore::from_config(
+        &config.mesh_network.trusted_provider_keys,
+        config.mesh_network.trust_providers_on_first_use,
+    )?;
+    if matches!(provider_keys.policy(), api::ProviderTrustPolicy::TrustedKeys(keys) if keys.is_empty()) {
+        tracing::warn!("No trusted mesh price providers configured; received price updates will be rejected");
+    }
+    let mesh_price_service = Arc::new(
+        MeshPriceService::new(
+            coinmarketcap_service.clone(),
+            peer_connection_manager,
+            redis_pool.clone(),
+            db_pool.clone(),
+            websocket_service.clone(),
+        )
+        .with_provider_keys(Arc::new(provider_identity), Arc::new(provider_keys))
+        .with_price_consensus(
+            redis_pool.clone(),
+            db_pool.clone(),
+            config.mesh_network.price_discrepancy_threshold_percent,
+            config.mesh_network.price_consensus_window_secs,
+            config.mesh_network.price_consensus_min_providers,
+        ),
+    );
+    tracing::info!(
+        "Mesh price service initialized (provider key {})",
+        mesh_price_service.provider_public_key()
+    );
+    // Push mesh network health to dashboards subscribed to `mesh_status`
+    let _mesh_status_handle = mesh_price_service
+        .clone()
+        .start_status_broadcast(std::time::Duration::from_secs(30));
+
+    // Token prices for portfolio valuation, falling back through the
+    // configured sources
+    let birdeye_service = Arc::new(api::birdeye_service::BirdeyeService::new(
+        config.birdeye.api_key.clone(),
+        redis_pool.clone(),
+    ).with_chain_registry(chains.clone()));
+    let price_feed = api::price_feed_from_config(
+        &config.price_feed,
+        birdeye_service,
+        coinmarketcap_service.clone(),
+        mesh_price_service.price_cache(),
+        redis_pool.clone(),
+    )?;
+    tracing::info!("Price feed initialized (sources: {})", price_feed.source_names().join(", "));
+
     // Initialize services
     let wallet_service = Arc::new(WalletService::new_with_tantum(
         solana_client.clone(),
@@ -67,7 +142,7 @@ async fn main() -> Result<()> {
         db_pool.clone(),
         redis_pool.clone(),
         use_mainnet,
-    ));
+    ).with_price_feed(price_feed));
     tracing::info!("Wallet service initialized with Helius API integration");
 
     let whale_detection_service = Arc::new(WhaleDetectionService::new(
@@ -77,21 +152,27 @@ async fn main() -> Result<()> {
     ));
     tracing::info!("Whale detection service initialized");
 
-    let analytics_service = Arc::new(AnalyticsService::new(db_pool.clone()));
+    // Initialize tax-lot accounting, valuing transactions from the recorded mesh consensus prices
+    let tax_lot_service = Arc::new(api::TaxLotService::new(db_pool.clone()));
+    let price_record_interval_secs = std::env::var("TAX_PRICE_RECORD_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(900);
+    tax_lot_service.clone().start_price_recorder(
+        mesh_price_service.price_cache(),
+        std::time::Duration::from_secs(price_record_interval_secs),
+    );
+    let tax_report_service = Arc::new(api::TaxReportService::new(tax_lot_service.clone()));
+    tracing::info!("Tax lot and tax report services initialized");
+
+    let analytics_service = Arc::new(
+        AnalyticsService::new(db_pool.clone()).with_tax_lots(tax_lot_service.clone()),
+    );
     tracing::info!("Analytics service initialized");
 
     let benchmark_service = Arc::new(BenchmarkService::new(db_pool.clone()));
     tracing::info!("Benchmark service initialized");
 
-    // Initialize CoinMarketCap service for real-time crypto prices
-    let coinmarketcap_api_key = std::env::var("COINMARKETCAP_API_KEY")
-        .unwrap_or_else(|_| "7c900818e1a14a3eb98ce42e9ac293e5".to_string());
-    let coinmarketcap_service = Arc::new(CoinMarketCapService::new(
-        coinmarketcap_api_key,
-        redis_pool.clone(),
-    ));
-    tracing::info!("CoinMarketCap service initialized");
-
     // Initialize SideShift client for conversions
     let sideshift_client = Arc::new(SideShiftClient::new(
         config.sideshift.affiliate_id.clone(),
@@ -99,21 +180,71 @@ async fn main() -> Result<()> {
     tracing::info!("SideShift client initialized");
 
     // Initialize multi-chain blockchain client for receipts
-    let multi_chain_client = Arc::new(blockchain::MultiChainClient::new());
+    let multi_chain_client = Arc::new(blockchain::MultiChainClient::new().with_registry((*chains).clone()));
     tracing::info!("Multi-chain blockchain client initialized");
 
     // Initialize receipt service for blockchain receipts
-    let receipt_service = Arc::new(ReceiptService::new(
-        db_pool.clone(),
-        multi_chain_client.clone(),
-    ));
-    tracing::info!("Receipt service initialized");
+    let anchoring_mode = match std::env::var("RECEIPT_ANCHOR_MODE") {
+        Ok(mode) => mode.parse::<api::AnchoringMode>().unwrap_or_else(|e| {
+            tracing::warn!("{}; anchoring receipts in batches", e);
+            api::AnchoringMode::Batched
+        }),
+        Err(_) => api::AnchoringMode::Batched,
+    };
+    let receipt_signer = match std::env::var("RECEIPT_SIGNING_KEYPAIR_PATH") {
+        // A configured key that cannot be loaded is fatal: receipts signed
+        // with a stand-in key would not verify against the published one
+        Ok(path) => api::ReceiptSigner::from_file(&path)?,
+        Err(_) => {
+            tracing::warn!("RECEIPT_SIGNING_KEYPAIR_PATH not set; signing receipts with an ephemeral key");
+            api::ReceiptSigner::generate()
+        }
+    };
+    let mut receipt_anchor = api::OnChainReceiptAnchor::new(multi_chain_client.clone());
+    match std::env::var("RECEIPT_ANCHOR_SOLANA_KEYPAIR_PATH") {
+        Ok(path) => {
+            let keypair = solana_sdk::signature::read_keypair_file(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to read receipt anchor keypair: {}", e))?;
+            receipt_anchor = receipt_anchor.with_solana_payer(keypair);
+        }
+        Err(_) => tracing::warn!("RECEIPT_ANCHOR_SOLANA_KEYPAIR_PATH not set; receipts will not be anchored on Solana"),
+    }
+    match std::env::var("RECEIPT_ANCHOR_EVM_KEYSTORE_PATH") {
+        Ok(path) => {
+            let passphrase = std::env::var("RECEIPT_ANCHOR_EVM_KEYSTORE_PASSPHRASE").unwrap_or_default();
+            let signer = blockchain::EvmKeystore::load(&path)?.decrypt(&passphrase)?;
+            receipt_anchor = receipt_anchor.with_evm_signer(signer);
+        }
+        Err(_) => tracing::warn!("RECEIPT_ANCHOR_EVM_KEYSTORE_PATH not set; receipts will not be anchored on EVM chains"),
+    }
+    let receipt_service = Arc::new(
+        ReceiptService::new(db_pool.clone(), multi_chain_client.clone())
+            .with_anchor(Arc::new(receipt_anchor))
+            .with_anchoring_mode(anchoring_mode)
+            .with_signer(Arc::new(receipt_signer)),
+    );
+    tracing::info!(
+        "Receipt service initialized ({:?} anchoring, signing key {})",
+        anchoring_mode,
+        receipt_service.signing_key()
+    );
+
+    if anchoring_mode == api::AnchoringMode::Batched {
+        let interval_secs = std::env::var("RECEIPT_ANCHOR_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        receipt_service
+            .clone()
+            .start_anchoring_worker(std::time::Duration::from_secs(interval_secs));
+        tracing::info!("Receipt anchoring worker started");
+    }
 
     // Initialize payment receipt service for user-facing receipts
-    let payment_receipt_service = Arc::new(PaymentReceiptService::new(
-        db_pool.clone(),
-        receipt_service.clone(),
-    ));
+    let payment_receipt_service = Arc::new(
+        PaymentReceiptService::new(db_pool.clone(), receipt_service.clone())
+            .with_tax_lots(tax_lot_service.clone()),
+    );
     tracing::info!("Payment receipt service initialized");
 
     // Initialize conversion service with receipt generation
@@ -143,20 +274,71 @@ async fn main() -> Result<()> {
     ));
     tracing::info!("Chat service initialized");
 
-    // Initialize WebSocket service for real-time dashboard updates
-    let websocket_service = Arc::new(WebSocketService::new());
-    tracing::info!("WebSocket service initialized");
-
     // Initialize position management service for manual/automatic trading
     let position_management_service = Arc::new(PositionManagementService::new(db_pool.clone()));
     tracing::info!("Position management service initialized");
 
-    // Initialize P2P exchange service
-    let p2p_service = Arc::new(P2PService::new(db_pool.clone()));
-    tracing::info!("P2P exchange service initialized");
+    // Initialize P2P exchange service, holding trades in escrow when an escrow wallet is configured
+    let escrow_service = match std::env::var("P2P_ESCROW_KEYPAIR_PATH") {
+        Ok(keypair_path) => {
+            let keypair = solana_sdk::signature::read_keypair_file(&keypair_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read P2P escrow keypair: {}", e))?;
+            let escrow_service = api::EscrowService::new(
+                db_pool.clone(),
+                Arc::new(api::SolanaEscrowCustodian::new(solana_client.clone(), keypair)),
+            )
+            .with_receipt_service(payment_receipt_service.clone());
+
+            tracing::info!("P2P escrow enabled at {}", escrow_service.escrow_address());
+            Some(Arc::new(escrow_service))
+        }
+        Err(_) => {
+            tracing::warn!("P2P_ESCROW_KEYPAIR_PATH not set; P2P trades will not be escrowed");
+            None
+        }
+    };
+
+    // Note: DiscoveryService requires user_tag, device_id, and wallet_address
+    // These would typically come from the user's session/request
+    // For now, we create a placeholder that will be initialized per-user
+    let proximity_discovery_service = Arc::new(proximity::DiscoveryService::new(
+        "system".to_string(),
+        "server".to_string(),
+        "system_wallet".to_string(),
+    ));
+    tracing::info!("Proximity discovery service initialized");
+
+    // Discovery sessions scope the peers proximity offers may match
+    let proximity_session_manager = Arc::new(proximity::SessionManager::new());
+    tracing::info!("Proximity session manager initialized");
+
+    let proximity_only_matching = std::env::var("P2P_MATCH_PROXIMITY_ONLY")
+        .map(|v| v.parse::<bool>().unwrap_or(false))
+        .unwrap_or(false);
+    let mut matching_engine = api::MatchingEngine::new(db_pool.clone())
+        .with_proximity_only(proximity_only_matching)
+        .with_proximity(proximity_session_manager.clone(), proximity_discovery_service.clone());
+    let mut p2p_service = P2PService::new(db_pool.clone());
+    let mut p2p_dispute_service = api::DisputeService::new(db_pool.clone());
+    if let Some(escrow_service) = escrow_service {
+        matching_engine = matching_engine.with_escrow(escrow_service.clone());
+        p2p_dispute_service = p2p_dispute_service.with_escrow(escrow_service.clone());
+        p2p_service = p2p_service.with_escrow(escrow_service);
+    }
+    let p2p_service = Arc::new(p2p_service.with_matching_engine(Arc::new(matching_engine)));
+    tracing::info!("P2P exchange service initialized with order matching");
+
+    let p2p_dispute_service = Arc::new(p2p_dispute_service);
+    let reputation_service = Arc::new(api::ReputationService::new(db_pool.clone()));
+
+    // Expire stale offers, match crossing offers and settle timed out escrows every minute
+    p2p_service.clone().start_expiry_worker(std::time::Duration::from_secs(60));
+    tracing::info!("P2P expiry worker started");
 
     // Initialize verification service for identity and wallet verification
-    let verification_service = Arc::new(VerificationService::new(db_pool.clone()));
+    let verification_service = Arc::new(
+        VerificationService::new(db_pool.clone()).with_chain_registry(chains.clone()),
+    );
     tracing::info!("Verification service initialized");
 
     // Initialize privacy service for temporary wallets and user tags
@@ -164,16 +346,29 @@ async fn main() -> Result<()> {
     tracing::info!("Privacy service initialized");
 
     // Initialize position evaluator for agentic trimming
-    let position_evaluator = Arc::new(PositionEvaluator::new(
-        db_pool.clone(),
-        trim_config_service.clone(),
-        config.claude.api_key.clone(),
-    ));
+    let position_evaluator = Arc::new(
+        PositionEvaluator::new(
+            db_pool.clone(),
+            trim_config_service.clone(),
+            config.claude.api_key.clone(),
+        )
+        .with_tax_lots(tax_lot_service.clone()),
+    );
     tracing::info!("Position evaluator initialized");
 
     // Initialize notification service for alerts and trade notifications
-    let notification_service = Arc::new(NotificationService::new());
-    tracing::info!("Notification service initialized");
+    let notification_service = Arc::new(
+        NotificationService::from_config(db_pool.clone(), &config.notifications)
+            .map_err(|e| anyhow::anyhow!("Invalid notification configuration: {}", e))?
+            .with_channel(Arc::new(WebSocketPushChannel::new(websocket_service.clone()))),
+    );
+    let _notification_retry_handle = notification_service
+        .clone()
+        .start_retry_worker(std::time::Duration::from_secs(config.notifications.retry_interval_secs));
+    tracing::info!(
+        "Notification service initialized with channels: {:?}",
+        notification_service.channel_kinds()
+    );
 
     // Initialize and start portfolio monitor background job
     let portfolio_monitor = Arc::new(PortfolioMonitor::new(
@@ -216,18 +411,42 @@ async fn main() -> Result<()> {
     tracing::info!("Position evaluator background job started (checking every 5 minutes)");
 
     // Initialize trading service for trim execution
-    let trading_service = Arc::new(trading::TradingService::new());
-    tracing::info!("Trading service initialized");
+    // Trades are only submitted on-chain when a signing keypair is configured
+    let trading_service = match std::env::var("TRADING_KEYPAIR_PATH") {
+        Ok(keypair_path) => {
+            let keypair = solana_sdk::signature::read_keypair_file(&keypair_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read trading keypair: {}", e))?;
+            let quote_source = match std::env::var("JUPITER_API_URL") {
+                Ok(url) => trading::JupiterQuoteSource::with_base_url(url),
+                Err(_) => trading::JupiterQuoteSource::new(),
+            };
+            let transaction_builder =
+                trading::TransactionBuilder::new(Arc::new(quote_source), solana_client.clone());
+
+            tracing::info!("Trading service initialized with on-chain execution");
+            Arc::new(trading::TradingService::new().with_executor(
+                transaction_builder,
+                Arc::new(trading::StaticKeypairSigner::new(keypair)),
+            ))
+        }
+        Err(_) => {
+            tracing::warn!("TRADING_KEYPAIR_PATH not set; trades will not be executed on-chain");
+            Arc::new(trading::TradingService::new())
+        }
+    };
 
     // Initialize trim executor for executing pending trim recommendations
     // Processes pending trims every 1 minute (Requirement 7.3, 7.4, 7.5, 7.6)
-    let trim_executor = Arc::new(TrimExecutor::new(
-        db_pool.clone(),
-        trim_config_service.clone(),
-        position_management_service.clone(),
-        trading_service.clone(),
-        notification_service.clone(),
-    ));
+    let trim_executor = Arc::new(
+        TrimExecutor::new(
+            db_pool.clone(),
+            trim_config_service.clone(),
+            position_management_service.clone(),
+            trading_service.clone(),
+            notification_service.clone(),
+        )
+        .with_tax_lots(tax_lot_service.clone()),
+    );
     tracing::info!("Trim executor initialized");
 
     // Start trim executor background job
@@ -237,6 +456,54 @@ async fn main() -> Result<()> {
     });
     tracing::info!("Trim executor background job started (processing pending trims every 1 minute)");
 
+    // Initialize conditional orders (stop-loss, take-profit, trailing stop, OCO brackets),
+    // evaluated against mesh consensus prices with CoinMarketCap as the fallback
+    let conditional_order_service = Arc::new(
+        api::ConditionalOrderService::new(
+            db_pool.clone(),
+            position_management_service.clone(),
+            trading_service.clone(),
+        )
+        .with_websocket(websocket_service.clone())
+        .with_price_cache(mesh_price_service.price_cache())
+        .with_coinmarketcap(coinmarketcap_service.clone()),
+    );
+    let conditional_order_interval_secs = std::env::var("CONDITIONAL_ORDER_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    conditional_order_service
+        .clone()
+        .start_monitor(std::time::Duration::from_secs(conditional_order_interval_secs));
+    tracing::info!("Conditional order monitor started (checking every {} seconds)", conditional_order_interval_secs);
+
+    // Initialize recurring DCA schedules; trades get payment receipts and
+    // conversions record their own
+    let dca_grace_period_secs = std::env::var("DCA_MISSED_RUN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(api::dca_schedules::DEFAULT_GRACE_PERIOD_SECS);
+    let dca_schedule_service = Arc::new(
+        api::DcaScheduleService::new(
+            db_pool.clone(),
+            position_management_service.clone(),
+            trading_service.clone(),
+        )
+        .with_conversion_service(conversion_service.clone())
+        .with_receipts(payment_receipt_service.clone())
+        .with_coinmarketcap(coinmarketcap_service.clone())
+        .with_chain_registry(chains.clone())
+        .with_grace_period(chrono::Duration::seconds(dca_grace_period_secs)),
+    );
+    let dca_interval_secs = std::env::var("DCA_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+    dca_schedule_service
+        .clone()
+        .start_scheduler(std::time::Duration::from_secs(dca_interval_secs));
+    tracing::info!("DCA scheduler started (checking every {} seconds)", dca_interval_secs);
+
     // Initialize JWT config for authentication
     let jwt_config = Arc::new(api::auth::JwtConfig::new(config.jwt.secret.clone()));
     tracing::info!("JWT config initialized");
@@ -249,37 +516,12 @@ async fn main() -> Result<()> {
     let proximity_auth_service = Arc::new(proximity::AuthenticationService::new());
     tracing::info!("Proximity authentication service initialized");
 
-    // Note: DiscoveryService requires user_tag, device_id, and wallet_address
-    // These would typically come from the user's session/request
-    // For now, we create a placeholder that will be initialized per-user
-    let proximity_discovery_service = Arc::new(proximity::DiscoveryService::new(
-        "system".to_string(),
-        "server".to_string(),
-        "system_wallet".to_string(),
-    ));
-    tracing::info!("Proximity discovery service initialized");
-
-    let proximity_session_manager = Arc::new(proximity::SessionManager::new());
-    tracing::info!("Proximity session manager initialized");
-
     let proximity_transfer_service = Arc::new(proximity::TransferService::new(
         db_pool.clone(),
         solana_client.clone(),
     ));
     tracing::info!("Proximity transfer service initialized");
 
-    // Initialize mesh price service for P2P price data distribution
-    // Uses the proximity P2P connection infrastructure for message routing
-    let peer_connection_manager = Arc::new(proximity::PeerConnectionManager::new());
-    let mesh_price_service = Arc::new(MeshPriceService::new(
-        coinmarketcap_service.clone(),
-        peer_connection_manager,
-        redis_pool.clone(),
-        db_pool.clone(),
-        websocket_service.clone(),
-    ));
-    tracing::info!("Mesh price service initialized");
-
     // Create application state
     let app_state = Arc::new(AppState::new(
         wallet_service,
@@ -292,10 +534,16 @@ async fn main() -> Result<()> {
         trim_config_service,
         trim_executor.clone(),
         payment_receipt_service,
+        tax_lot_service,
+        tax_report_service,
         chat_service,
         websocket_service,
         position_management_service,
+        conditional_order_service,
+        dca_schedule_service,
         p2p_service,
+        p2p_dispute_service,
+        reputation_service,
         verification_service,
         privacy_service,
         proximity_discovery_service,
@@ -307,6 +555,7 @@ async fn main() -> Result<()> {
         db_pool,
         redis_pool,
         solana_client,
+        chains,
         metrics.clone(),
     ));
 
@@ -0,0 +1,196 @@
+//! Binary SHA-256 Merkle trees for batching receipt hashes into one anchor
+//!
+//! Leaves and interior nodes are domain-separated (`0x00` and `0x01`
+//! prefixes) so a leaf can never be passed off as an interior node. A node
+//! without a sibling is promoted to the next level unchanged, so a proof is
+//! only valid for the leaf count the tree was built with.
+
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash a leaf value into the tree
+pub fn hash_leaf(value: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(value);
+    hasher.finalize().into()
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// A Merkle tree over a batch of leaf values
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// Every level of the tree, leaves first and the root last
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Build a tree over `values`, which must not be empty
+    pub fn new(values: &[Hash]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut levels = vec![values.iter().map(hash_leaf).collect::<Vec<_>>()];
+        while levels.last().map_or(0, Vec::len) > 1 {
+            let level = levels.last().expect("levels is never empty");
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    [single] => *single,
+                    _ => unreachable!("chunks(2) yields one or two nodes"),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Some(Self { levels })
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels.last().expect("levels is never empty")[0]
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Sibling hashes from the leaf at `index` up to the root
+    pub fn proof(&self, index: usize) -> Option<Vec<Hash>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut proof = Vec::new();
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// Recompute the root of a tree of `leaf_count` leaves from the value at
+/// `index` and its proof
+///
+/// Returns `None` if the proof has the wrong length for that position.
+pub fn root_from_proof(value: &Hash, index: usize, leaf_count: usize, proof: &[Hash]) -> Option<Hash> {
+    if index >= leaf_count {
+        return None;
+    }
+
+    let mut node = hash_leaf(value);
+    let mut siblings = proof.iter();
+    let (mut index, mut width) = (index, leaf_count);
+    while width > 1 {
+        let has_sibling = index ^ 1 < width;
+        if has_sibling {
+            let sibling = siblings.next()?;
+            node = if index % 2 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+
+    siblings.next().is_none().then_some(node)
+}
+
+/// `0x`-prefixed lowercase hex, as receipt hashes are stored
+pub fn to_hex(hash: &Hash) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Parse a hash stored by [`to_hex`]
+pub fn from_hex(value: &str) -> Option<Hash> {
+    let bytes = hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()?;
+    bytes.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(count: usize) -> Vec<Hash> {
+        (0..count)
+            .map(|i| Sha256::digest(i.to_le_bytes()).into())
+            .collect()
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_leaf_hash() {
+        let values = values(1);
+        let tree = MerkleTree::new(&values).unwrap();
+
+        assert_eq!(tree.root(), hash_leaf(&values[0]));
+        assert!(tree.proof(0).unwrap().is_empty());
+        assert_eq!(root_from_proof(&values[0], 0, 1, &[]), Some(tree.root()));
+    }
+
+    #[test]
+    fn test_every_proof_recomputes_root() {
+        for count in 1..=17 {
+            let values = values(count);
+            let tree = MerkleTree::new(&values).unwrap();
+
+            for (index, value) in values.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(
+                    root_from_proof(value, index, count, &proof),
+                    Some(tree.root()),
+                    "leaf {} of {}",
+                    index,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let values = values(5);
+        let tree = MerkleTree::new(&values).unwrap();
+        let proof = tree.proof(2).unwrap();
+
+        // Wrong value, wrong position, wrong tree size
+        assert_ne!(root_from_proof(&values[3], 2, 5, &proof), Some(tree.root()));
+        assert_ne!(root_from_proof(&values[2], 3, 5, &proof), Some(tree.root()));
+        assert_ne!(root_from_proof(&values[2], 2, 4, &proof), Some(tree.root()));
+
+        // Missing or extra siblings
+        assert_eq!(root_from_proof(&values[2], 2, 5, &proof[1..]), None);
+        let mut longer = proof.clone();
+        longer.push(values[0]);
+        assert_eq!(root_from_proof(&values[2], 2, 5, &longer), None);
+        assert_eq!(root_from_proof(&values[2], 5, 5, &proof), None);
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let value = values(1)[0];
+        let encoded = to_hex(&value);
+
+        assert!(encoded.starts_with("0x") && encoded.len() == 66);
+        assert_eq!(from_hex(&encoded), Some(value));
+        assert_eq!(from_hex("0x1234"), None);
+        assert_eq!(from_hex("not hex"), None);
+    }
+}
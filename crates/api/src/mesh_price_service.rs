@@ -9,10 +9,11 @@ use crate::coinmarketcap_service::CoinMarketCapService;
 use crate::coordination_service::CoordinationService;
 use crate::gossip_protocol::GossipProtocol;
 use crate::mesh_metrics::MeshMetricsCollector;
-use crate::mesh_types::{CachedPriceData, NetworkStatus, ProviderConfig, PriceUpdate};
+use crate::mesh_types::{ConsensusPriceData, NetworkStatus, ProviderConfig, PriceUpdate};
 use crate::message_tracker::MessageTracker;
 use crate::network_status_tracker::NetworkStatusTracker;
 use crate::price_cache::PriceCache;
+use crate::provider_identity::{ProviderIdentity, ProviderKeyStore};
 use crate::provider_node::ProviderNode;
 use crate::websocket_service::WebSocketService;
 
@@ -154,6 +155,10 @@ pub struct MeshPriceService {
     node_id: Uuid,
     /// Metrics collector for mesh network operations
     metrics: Arc<MeshMetricsCollector>,
+    /// Signing identity used when this node acts as a provider
+    provider_identity: Arc<ProviderIdentity>,
+    /// Trusted or pinned provider keys for verifying received updates
+    provider_keys: Arc<ProviderKeyStore>,
 }
 
 impl MeshPriceService {
@@ -198,6 +203,11 @@ impl MeshPriceService {
             node_id,
         ));
         
+        // Until configured otherwise, sign with an ephemeral key and trust
+        // no provider but this node
+        let provider_identity = Arc::new(ProviderIdentity::generate());
+        let provider_keys = Arc::new(ProviderKeyStore::trusted([provider_identity.public_key()]));
+        
         // Initialize gossip protocol with metrics
         let gossip_protocol = Arc::new(GossipProtocol::new(
             Arc::clone(&peer_manager),
@@ -205,6 +215,7 @@ impl MeshPriceService {
             Arc::clone(&price_cache),
             Arc::clone(&websocket_service),
             Arc::clone(&metrics),
+            Arc::clone(&provider_keys),
         ));
         
         // Initialize provider config with defaults
@@ -223,9 +234,81 @@ impl MeshPriceService {
             provider_node: Arc::new(RwLock::new(None)),
             node_id,
             metrics,
+            provider_identity,
+            provider_keys,
         }
     }
     
+    /// Use a persistent provider identity and provider key policy
+    /// 
+    /// Must be called before `start()`, since the gossip protocol is rebuilt
+    /// to verify received updates against `provider_keys`.
+    /// 
+    /// # Arguments
+    /// * `provider_identity` - Key this node signs its own price updates with
+    /// * `provider_keys` - Trusted keyset or TOFU store for received updates
+    pub fn with_provider_keys(
+        mut self,
+        provider_identity: Arc<ProviderIdentity>,
+        provider_keys: Arc<ProviderKeyStore>,
+    ) -> Self {
+        self.provider_identity = provider_identity;
+        self.provider_keys = provider_keys;
+        self.rebuild_gossip_protocol();
+        self
+    }
+    
+    /// Configure how received quotes are combined into a consensus price
+    /// 
+    /// Must be called before `start()`, since the price cache is replaced.
+    /// 
+    /// # Arguments
+    /// * `redis` - Redis connection for the replacement price cache
+    /// * `db` - Database pool for the replacement price cache
+    /// * `discrepancy_threshold_percent` - Deviation from the consensus at which a quote is an outlier
+    /// * `consensus_window_secs` - Age after which a provider's quote stops counting
+    /// * `min_providers` - Distinct providers that must agree before a price is accepted
+    pub fn with_price_consensus(
+        mut self,
+        redis: redis::aio::ConnectionManager,
+        db: database::DbPool,
+        discrepancy_threshold_percent: f64,
+        consensus_window_secs: u64,
+        min_providers: usize,
+    ) -> Self {
+        self.price_cache = Arc::new(
+            PriceCache::new(redis, db, Arc::clone(&self.metrics)).with_consensus_settings(
+                discrepancy_threshold_percent,
+                consensus_window_secs,
+                min_providers,
+            ),
+        );
+        self.rebuild_gossip_protocol();
+        self
+    }
+    
+    /// Recreate the gossip protocol after one of its dependencies changed
+    fn rebuild_gossip_protocol(&mut self) {
+        self.gossip_protocol = Arc::new(GossipProtocol::new(
+            Arc::clone(&self.peer_manager),
+            Arc::clone(&self.message_tracker),
+            Arc::clone(&self.price_cache),
+            Arc::clone(&self.websocket_service),
+            Arc::clone(&self.metrics),
+            Arc::clone(&self.provider_keys),
+        ));
+    }
+    
+    /// Price cache holding the quotes received from providers
+    pub fn price_cache(&self) -> Arc<PriceCache> {
+        Arc::clone(&self.price_cache)
+    }
+    
+    /// Public key this node signs its price updates with
+    pub fn provider_public_key(&self) -> solana_sdk::pubkey::Pubkey {
+        self.provider_identity.public_key()
+    }
+    
     /// Start the mesh price distribution service
     /// 
     /// Initializes the service by:
@@ -289,6 +372,8 @@ impl MeshPriceService {
                         timestamp,
                         prices,
                         ttl,
+                        signer,
+                        signature,
                     } => {
                         // Deserialize prices from JSON
                         let prices_map: std::collections::HashMap<String, crate::mesh_types::PriceData> = 
@@ -301,6 +386,8 @@ impl MeshPriceService {
                             timestamp,
                             prices: prices_map,
                             ttl,
+                            signer,
+                            signature,
                         };
                         
                         service.handle_price_update(update, peer_id).await
@@ -413,6 +500,7 @@ impl MeshPriceService {
             Arc::clone(&self.coordination_service),
             Arc::clone(&self.metrics),
             self.node_id,
+            Arc::clone(&self.provider_identity),
         );
         
         // Validate API key
@@ -441,6 +529,7 @@ impl MeshPriceService {
             Arc::clone(&self.coordination_service),
             Arc::clone(&self.metrics),
             self.node_id,
+            Arc::clone(&self.provider_identity),
         ));
         
         provider.start().await?;
@@ -549,10 +638,11 @@ impl MeshPriceService {
         Ok(())
     }
     
-    /// Get cached price data for a specific asset
+    /// Get the consensus price for a specific asset
     /// 
-    /// Retrieves price data from the local cache. If no providers are online,
-    /// this serves cached data as a fallback.
+    /// Combines the recent quotes of all providers into a median, ignoring
+    /// outliers, and reports how confident that consensus is. If no providers
+    /// are online, this serves cached data as a fallback.
     /// 
     /// Requirements: 6.4, 9.1
     /// 
@@ -560,11 +650,11 @@ impl MeshPriceService {
     /// * `asset` - The asset symbol to retrieve price for
     /// 
     /// # Returns
-    /// * `Ok(Some(CachedPriceData))` - Price data found in cache
+    /// * `Ok(Some(ConsensusPriceData))` - Consensus price and confidence
     /// * `Ok(None)` - No price data available for this asset
     /// * `Err(_)` - Error occurred during retrieval
-    pub async fn get_price_data(&self, asset: &str) -> Result<Option<CachedPriceData>> {
-        let data = self.price_cache.get(asset).await?;
+    pub async fn get_price_data(&self, asset: &str) -> Result<Option<ConsensusPriceData>> {
+        let data = self.price_cache.consensus(asset).await?;
         
         // Log if we're serving cached data due to no providers being online
         if data.is_some() {
@@ -580,18 +670,17 @@ impl MeshPriceService {
         Ok(data)
     }
     
-    /// Get all cached price data
+    /// Get the consensus price of every cached asset
     /// 
-    /// Retrieves all price data currently in the cache.
     /// When no providers are online, this serves as the fallback data source.
     /// 
     /// Requirements: 6.4, 9.1
     /// 
     /// # Returns
-    /// * `Ok(HashMap)` - Map of asset symbols to cached price data
+    /// * `Ok(HashMap)` - Map of asset symbols to consensus price data
     /// * `Err(_)` - Error occurred during retrieval
-    pub async fn get_all_price_data(&self) -> Result<HashMap<String, CachedPriceData>> {
-        let data = self.price_cache.get_all().await?;
+    pub async fn get_all_price_data(&self) -> Result<HashMap<String, ConsensusPriceData>> {
+        let data = self.price_cache.get_all_consensus().await?;
         
         // Log if we're serving cached data due to no providers being online
         if !data.is_empty() {
@@ -624,6 +713,23 @@ impl MeshPriceService {
         self.network_status_tracker.get_status().await
     }
     
+    /// Spawn a background task pushing network status to dashboard clients
+    /// subscribed to `mesh_status` every `interval`
+    pub fn start_status_broadcast(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            
+            loop {
+                ticker.tick().await;
+                
+                match self.get_network_status().await {
+                    Ok(status) => self.websocket_service.broadcast_mesh_status(&status),
+                    Err(e) => tracing::warn!("Failed to load mesh network status: {}", e),
+                }
+            }
+        })
+    }
+    
     /// Get the unique node identifier for this service
     /// 
     /// # Returns
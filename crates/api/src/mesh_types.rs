@@ -1,9 +1,15 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
+/// Version tag mixed into the signed bytes of a price update
+pub const PRICE_UPDATE_SIGNING_VERSION: &str = "mesh-price-update/v1";
+
 /// A price update message for mesh network distribution
+///
+/// Everything except `ttl` is covered by the provider's signature, so relays
+/// can decrement the TTL without invalidating it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceUpdate {
     pub message_id: Uuid,
@@ -11,6 +17,43 @@ pub struct PriceUpdate {
     pub timestamp: DateTime<Utc>,
     pub prices: HashMap<String, PriceData>,
     pub ttl: u32,
+    /// Base58 Ed25519 public key of the originating provider
+    #[serde(default)]
+    pub signer: Option<String>,
+    /// Base58 Ed25519 signature over `signing_bytes()`
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// Canonical signed envelope of a price update
+#[derive(Serialize)]
+struct SignedPriceUpdate<'a> {
+    version: &'static str,
+    message_id: &'a Uuid,
+    source_node_id: &'a Uuid,
+    signer: &'a str,
+    timestamp: String,
+    prices: BTreeMap<&'a String, &'a PriceData>,
+}
+
+impl PriceUpdate {
+    /// Deterministic bytes covered by the provider signature
+    ///
+    /// Prices are ordered by asset and the timestamp is rendered with full
+    /// precision so every node derives identical bytes. `ttl` and the
+    /// signature itself are excluded.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let envelope = SignedPriceUpdate {
+            version: PRICE_UPDATE_SIGNING_VERSION,
+            message_id: &self.message_id,
+            source_node_id: &self.source_node_id,
+            signer: self.signer.as_deref().unwrap_or_default(),
+            timestamp: self.timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            prices: self.prices.iter().collect(),
+        };
+
+        serde_json::to_vec(&envelope).expect("price update envelope serializes")
+    }
 }
 
 /// Price data for a single asset
@@ -31,6 +74,31 @@ pub struct CachedPriceData {
     pub source_node_id: Uuid,
     pub blockchain: String,
     pub change_24h: Option<String>,
+    /// Key that signed the update carrying this quote; quotes signed by one
+    /// key count as a single provider
+    #[serde(default)]
+    pub signer: Option<String>,
+}
+
+/// Consensus price for an asset across the recent quotes of several providers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusPriceData {
+    pub asset: String,
+    /// Median of the quotes that agree with each other
+    pub price: String,
+    /// Timestamp of the newest agreeing quote
+    pub timestamp: DateTime<Utc>,
+    /// Provider of the newest agreeing quote
+    pub source_node_id: Uuid,
+    pub blockchain: String,
+    pub change_24h: Option<String>,
+    /// How far the consensus can be trusted, from 0.0 (conflicting or
+    /// uncorroborated) to 1.0 (several providers in agreement)
+    pub confidence: f64,
+    /// Providers whose quotes make up the consensus
+    pub providers: Vec<Uuid>,
+    /// Providers whose quotes were rejected as outliers
+    pub outliers: Vec<Uuid>,
 }
 
 /// Provider node configuration
@@ -110,4 +178,14 @@ impl DataFreshness {
     pub fn is_stale(&self) -> bool {
         matches!(self, DataFreshness::HoursAgo(_) | DataFreshness::Stale)
     }
+
+    /// Human-readable age shown on the dashboard
+    pub fn label(&self) -> String {
+        match self {
+            DataFreshness::JustNow => "Just now".to_string(),
+            DataFreshness::MinutesAgo(m) => format!("{} minutes ago", m),
+            DataFreshness::HoursAgo(h) => format!("{} hours ago", h),
+            DataFreshness::Stale => "Stale".to_string(),
+        }
+    }
 }
@@ -0,0 +1,587 @@
+use chrono::{DateTime, Utc};
+use database::DbPool;
+use deadpool_postgres::Transaction;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::p2p_escrow::{hold_for_dispute, EscrowService, TransitionReason};
+
+/// Longest dispute reason, evidence note or resolution note accepted
+pub const MAX_DISPUTE_TEXT_LEN: usize = 2000;
+
+const DISPUTE_COLUMNS: &str = "id, exchange_id, opened_by, reason, status, outcome, arbitrator_id, \
+     resolution_note, created_at, resolved_at";
+const EVIDENCE_COLUMNS: &str = "id, dispute_id, submitted_by, kind, reference, note, created_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisputeStatus {
+    /// Waiting for an arbitrator; the exchange's escrow is held
+    Open,
+    Resolved,
+}
+
+impl DisputeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeStatus::Open => "OPEN",
+            DisputeStatus::Resolved => "RESOLVED",
+        }
+    }
+}
+
+impl FromStr for DisputeStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "OPEN" => Ok(DisputeStatus::Open),
+            "RESOLVED" => Ok(DisputeStatus::Resolved),
+            other => Err(Error::Internal(format!("Invalid dispute status: {}", other))),
+        }
+    }
+}
+
+/// How an arbitrator decided a dispute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisputeOutcome {
+    /// The trade stands: escrowed funds go to the buyer
+    ReleaseToBuyer,
+    /// The trade is unwound: escrowed funds go back to the seller
+    RefundToSeller,
+}
+
+impl DisputeOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeOutcome::ReleaseToBuyer => "RELEASE_TO_BUYER",
+            DisputeOutcome::RefundToSeller => "REFUND_TO_SELLER",
+        }
+    }
+}
+
+impl fmt::Display for DisputeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DisputeOutcome {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "RELEASE_TO_BUYER" => Ok(DisputeOutcome::ReleaseToBuyer),
+            "REFUND_TO_SELLER" => Ok(DisputeOutcome::RefundToSeller),
+            other => Err(Error::Internal(format!("Invalid dispute outcome: {}", other))),
+        }
+    }
+}
+
+/// What a piece of evidence points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EvidenceKind {
+    /// A chat message between the two parties, by id
+    ChatMessage,
+    /// An on-chain transaction, by hash
+    TxHash,
+    /// Free text only
+    Note,
+}
+
+impl EvidenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvidenceKind::ChatMessage => "CHAT_MESSAGE",
+            EvidenceKind::TxHash => "TX_HASH",
+            EvidenceKind::Note => "NOTE",
+        }
+    }
+}
+
+impl FromStr for EvidenceKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "CHAT_MESSAGE" => Ok(EvidenceKind::ChatMessage),
+            "TX_HASH" => Ok(EvidenceKind::TxHash),
+            "NOTE" => Ok(EvidenceKind::Note),
+            other => Err(Error::Internal(format!("Invalid evidence kind: {}", other))),
+        }
+    }
+}
+
+/// A dispute over a P2P exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct P2PDispute {
+    pub id: Uuid,
+    pub exchange_id: Uuid,
+    pub opened_by: Uuid,
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub outcome: Option<DisputeOutcome>,
+    pub arbitrator_id: Option<Uuid>,
+    pub resolution_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Evidence attached to a dispute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeEvidence {
+    pub id: Uuid,
+    pub dispute_id: Uuid,
+    pub submitted_by: Uuid,
+    pub kind: EvidenceKind,
+    /// Chat message id or transaction hash; `None` for notes
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A dispute with its evidence, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeDetails {
+    pub dispute: P2PDispute,
+    pub evidence: Vec<DisputeEvidence>,
+}
+
+/// Dispute workflow for P2P exchanges
+///
+/// Either party can dispute an exchange whose funds are locked in escrow (or
+/// any pending exchange when escrow is disabled). The escrow is held until an
+/// arbitrator resolves the dispute, which releases it to the buyer or refunds
+/// the seller.
+pub struct DisputeService {
+    db: DbPool,
+    escrow: Option<Arc<EscrowService>>,
+}
+
+impl DisputeService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db, escrow: None }
+    }
+
+    pub fn with_escrow(mut self, escrow: Arc<EscrowService>) -> Self {
+        self.escrow = Some(escrow);
+        self
+    }
+
+    /// Dispute an exchange as its buyer or seller
+    pub async fn open_dispute(&self, exchange_id: Uuid, user_id: Uuid, reason: &str) -> Result<P2PDispute> {
+        let reason = required_text(reason, "Dispute reason")?;
+
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let exchange = transaction
+            .query_opt(
+                "SELECT buyer_user_id, seller_user_id, status FROM p2p_exchanges WHERE id = $1 FOR UPDATE",
+                &[&exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch exchange: {}", e)))?
+            .ok_or_else(|| Error::Validation("Exchange not found".to_string()))?;
+        if ![exchange.get::<_, Uuid>("buyer_user_id"), exchange.get("seller_user_id")].contains(&user_id) {
+            return Err(Error::Unauthorized);
+        }
+        let status: String = exchange.get("status");
+        if status != "PENDING" {
+            return Err(Error::Validation(format!(
+                "Exchange cannot be disputed (status: {})",
+                status
+            )));
+        }
+
+        hold_for_dispute(&transaction, exchange_id).await?;
+        transaction
+            .execute(
+                "UPDATE p2p_exchanges SET status = 'DISPUTED' WHERE id = $1",
+                &[&exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update exchange: {}", e)))?;
+
+        let row = transaction
+            .query_one(
+                &format!(
+                    "INSERT INTO p2p_disputes (id, exchange_id, opened_by, reason, status)
+                     VALUES ($1, $2, $3, $4, 'OPEN')
+                     RETURNING {}",
+                    DISPUTE_COLUMNS
+                ),
+                &[&Uuid::new_v4(), &exchange_id, &user_id, &reason],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to open dispute: {}", e)))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        let dispute = row_to_dispute(&row)?;
+        info!("User {} opened dispute {} on exchange {}", user_id, dispute.id, exchange_id);
+        Ok(dispute)
+    }
+
+    /// Attach evidence to an open dispute
+    ///
+    /// Chat messages must have been exchanged between the two parties.
+    /// Arbitrators may add evidence to any dispute they are reviewing.
+    pub async fn add_evidence(
+        &self,
+        dispute_id: Uuid,
+        user_id: Uuid,
+        is_arbitrator: bool,
+        kind: EvidenceKind,
+        reference: Option<String>,
+        note: Option<String>,
+    ) -> Result<DisputeEvidence> {
+        let note = optional_text(note, "Evidence note")?;
+        let reference = reference.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+        match (kind, &reference) {
+            (EvidenceKind::Note, Some(_)) => {
+                return Err(Error::Validation("Notes cannot have a reference".to_string()))
+            }
+            (EvidenceKind::Note, None) if note.is_none() => {
+                return Err(Error::Validation("Note evidence needs a note".to_string()))
+            }
+            (EvidenceKind::ChatMessage | EvidenceKind::TxHash, None) => {
+                return Err(Error::Validation(format!("{} evidence needs a reference", kind.as_str())))
+            }
+            (_, Some(r)) if r.len() > 255 => {
+                return Err(Error::Validation("Evidence reference is too long".to_string()))
+            }
+            _ => {}
+        }
+
+        let client = self.client().await?;
+        let (dispute, buyer, seller) = fetch_dispute_parties(&client, dispute_id).await?;
+        if !is_arbitrator && user_id != buyer && user_id != seller {
+            return Err(Error::Unauthorized);
+        }
+        if dispute.status != DisputeStatus::Open {
+            return Err(Error::Validation("Dispute is already resolved".to_string()));
+        }
+
+        if let (EvidenceKind::ChatMessage, Some(message_id)) = (kind, &reference) {
+            let message_id = Uuid::parse_str(message_id)
+                .map_err(|_| Error::Validation("Chat message reference must be a message id".to_string()))?;
+            let found = client
+                .query_opt(
+                    "SELECT 1 FROM chat_messages
+                     WHERE id = $1
+                       AND from_user_id IN ($2, $3)
+                       AND to_user_id IN ($2, $3)",
+                    &[&message_id, &buyer, &seller],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to fetch chat message: {}", e)))?;
+            if found.is_none() {
+                return Err(Error::Validation(
+                    "Chat message not found between the exchange parties".to_string(),
+                ));
+            }
+        }
+
+        let row = client
+            .query_one(
+                &format!(
+                    "INSERT INTO p2p_dispute_evidence (id, dispute_id, submitted_by, kind, reference, note)
+                     VALUES ($1, $2, $3, $4, $5, $6)
+                     RETURNING {}",
+                    EVIDENCE_COLUMNS
+                ),
+                &[&Uuid::new_v4(), &dispute_id, &user_id, &kind.as_str(), &reference, &note],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add evidence: {}", e)))?;
+
+        row_to_evidence(&row)
+    }
+
+    /// A dispute and its evidence, visible to both parties and arbitrators
+    pub async fn get_dispute(&self, dispute_id: Uuid, user_id: Uuid, is_arbitrator: bool) -> Result<DisputeDetails> {
+        let client = self.client().await?;
+        let (dispute, buyer, seller) = fetch_dispute_parties(&client, dispute_id).await?;
+        if !is_arbitrator && user_id != buyer && user_id != seller {
+            return Err(Error::Unauthorized);
+        }
+
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM p2p_dispute_evidence WHERE dispute_id = $1 ORDER BY created_at",
+                    EVIDENCE_COLUMNS
+                ),
+                &[&dispute_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch evidence: {}", e)))?;
+        let evidence = rows.iter().map(row_to_evidence).collect::<Result<Vec<_>>>()?;
+
+        Ok(DisputeDetails { dispute, evidence })
+    }
+
+    /// The dispute of an exchange, if it has one
+    pub async fn get_dispute_for_exchange(&self, exchange_id: Uuid) -> Result<Option<P2PDispute>> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                &format!("SELECT {} FROM p2p_disputes WHERE exchange_id = $1", DISPUTE_COLUMNS),
+                &[&exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch dispute: {}", e)))?;
+
+        row.as_ref().map(row_to_dispute).transpose()
+    }
+
+    /// Open disputes waiting for an arbitrator, oldest first
+    pub async fn list_open_disputes(&self, limit: i64) -> Result<Vec<P2PDispute>> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM p2p_disputes WHERE status = 'OPEN' ORDER BY created_at LIMIT $1",
+                    DISPUTE_COLUMNS
+                ),
+                &[&limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch disputes: {}", e)))?;
+
+        rows.iter().map(row_to_dispute).collect()
+    }
+
+    /// Decide a dispute, releasing or refunding the exchange's escrow
+    ///
+    /// The escrow is settled before the dispute is closed: if the payout
+    /// fails the escrow stays held and the dispute open, so the arbitrator
+    /// can retry.
+    pub async fn resolve_dispute(
+        &self,
+        dispute_id: Uuid,
+        arbitrator_id: Uuid,
+        outcome: DisputeOutcome,
+        note: Option<String>,
+    ) -> Result<P2PDispute> {
+        let note = optional_text(note, "Resolution note")?;
+
+        let mut client = self.client().await?;
+        let (dispute, buyer, seller) = fetch_dispute_parties(&client, dispute_id).await?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(Error::Validation("Dispute is already resolved".to_string()));
+        }
+        if arbitrator_id == buyer || arbitrator_id == seller {
+            return Err(Error::Validation(
+                "Arbitrators cannot resolve their own disputes".to_string(),
+            ));
+        }
+
+        let escrow_id: Option<Uuid> = client
+            .query_opt(
+                "SELECT id FROM p2p_escrows WHERE exchange_id = $1",
+                &[&dispute.exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch escrow: {}", e)))?
+            .map(|row| row.get("id"));
+
+        if let Some(escrow_id) = escrow_id {
+            let escrow = self.escrow.as_ref().ok_or_else(|| {
+                Error::Internal("Disputed exchange is escrowed but escrow is not configured".to_string())
+            })?;
+            match outcome {
+                DisputeOutcome::ReleaseToBuyer => escrow.release(escrow_id, TransitionReason::DisputeResolved).await?,
+                DisputeOutcome::RefundToSeller => escrow.refund(escrow_id, TransitionReason::DisputeResolved).await?,
+            };
+        }
+
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+        // A payout that already failed put the escrow back on hold; the
+        // dispute then stays open for another decision
+        let row = transaction
+            .query_opt(
+                &format!(
+                    "UPDATE p2p_disputes
+                     SET status = 'RESOLVED', outcome = $2, arbitrator_id = $3,
+                         resolution_note = $4, resolved_at = NOW()
+                     WHERE id = $1 AND status = 'OPEN'
+                       AND NOT EXISTS (
+                           SELECT 1 FROM p2p_escrows
+                           WHERE exchange_id = p2p_disputes.exchange_id AND status = 'DISPUTED'
+                       )
+                     RETURNING {}",
+                    DISPUTE_COLUMNS
+                ),
+                &[&dispute_id, &outcome.as_str(), &arbitrator_id, &note],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to resolve dispute: {}", e)))?
+            .ok_or_else(|| {
+                Error::Validation("Dispute is already resolved or its payout failed".to_string())
+            })?;
+
+        if escrow_id.is_none() {
+            // Without escrow the outcome only settles the exchange's status
+            let exchange_status = match outcome {
+                DisputeOutcome::ReleaseToBuyer => "COMPLETED",
+                DisputeOutcome::RefundToSeller => "REFUNDED",
+            };
+            transaction
+                .execute(
+                    "UPDATE p2p_exchanges SET status = $2 WHERE id = $1",
+                    &[&dispute.exchange_id, &exchange_status],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to update exchange: {}", e)))?;
+        }
+        transaction.commit().await.map_err(|e| {
+            error!("Dispute {} was settled as {} but not closed: {}", dispute_id, outcome, e);
+            Error::Database(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        info!("Arbitrator {} resolved dispute {} as {}", arbitrator_id, dispute_id, outcome);
+        row_to_dispute(&row)
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Client> {
+        self.db
+            .get()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))
+    }
+}
+
+/// Reopen the resolved dispute of an exchange whose dispute payout failed
+///
+/// The escrow is back on hold at that point, and only an open dispute lets
+/// an arbitrator settle it again.
+pub(crate) async fn reopen_dispute(transaction: &Transaction<'_>, exchange_id: Uuid) -> Result<()> {
+    let reopened = transaction
+        .execute(
+            "UPDATE p2p_disputes
+             SET status = 'OPEN', outcome = NULL, arbitrator_id = NULL,
+                 resolution_note = NULL, resolved_at = NULL
+             WHERE exchange_id = $1 AND status = 'RESOLVED'",
+            &[&exchange_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to reopen dispute: {}", e)))?;
+    if reopened > 0 {
+        info!("Reopened dispute on exchange {} after its payout failed", exchange_id);
+    }
+    Ok(())
+}
+
+/// A dispute with the buyer and seller of its exchange
+async fn fetch_dispute_parties(client: &deadpool_postgres::Client, dispute_id: Uuid) -> Result<(P2PDispute, Uuid, Uuid)> {
+    let row = client
+        .query_opt(
+            "SELECT d.id, d.exchange_id, d.opened_by, d.reason, d.status, d.outcome, d.arbitrator_id,
+                    d.resolution_note, d.created_at, d.resolved_at, e.buyer_user_id, e.seller_user_id
+             FROM p2p_disputes d
+             JOIN p2p_exchanges e ON e.id = d.exchange_id
+             WHERE d.id = $1",
+            &[&dispute_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to fetch dispute: {}", e)))?
+        .ok_or_else(|| Error::Validation("Dispute not found".to_string()))?;
+
+    Ok((row_to_dispute(&row)?, row.get("buyer_user_id"), row.get("seller_user_id")))
+}
+
+fn required_text(text: &str, what: &str) -> Result<String> {
+    optional_text(Some(text.to_string()), what)?
+        .ok_or_else(|| Error::Validation(format!("{} is required", what)))
+}
+
+fn optional_text(text: Option<String>, what: &str) -> Result<Option<String>> {
+    let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if text.as_ref().is_some_and(|t| t.len() > MAX_DISPUTE_TEXT_LEN) {
+        return Err(Error::Validation(format!(
+            "{} must be at most {} characters",
+            what, MAX_DISPUTE_TEXT_LEN
+        )));
+    }
+    Ok(text)
+}
+
+fn row_to_dispute(row: &tokio_postgres::Row) -> Result<P2PDispute> {
+    let status: String = row.get("status");
+    let outcome: Option<String> = row.get("outcome");
+
+    Ok(P2PDispute {
+        id: row.get("id"),
+        exchange_id: row.get("exchange_id"),
+        opened_by: row.get("opened_by"),
+        reason: row.get("reason"),
+        status: status.parse()?,
+        outcome: outcome.as_deref().map(str::parse).transpose()?,
+        arbitrator_id: row.get("arbitrator_id"),
+        resolution_note: row.get("resolution_note"),
+        created_at: row.get("created_at"),
+        resolved_at: row.get("resolved_at"),
+    })
+}
+
+fn row_to_evidence(row: &tokio_postgres::Row) -> Result<DisputeEvidence> {
+    let kind: String = row.get("kind");
+
+    Ok(DisputeEvidence {
+        id: row.get("id"),
+        dispute_id: row.get("dispute_id"),
+        submitted_by: row.get("submitted_by"),
+        kind: kind.parse()?,
+        reference: row.get("reference"),
+        note: row.get("note"),
+        created_at: row.get("created_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enums_round_trip() {
+        for outcome in [DisputeOutcome::ReleaseToBuyer, DisputeOutcome::RefundToSeller] {
+            assert_eq!(outcome.as_str().parse::<DisputeOutcome>().unwrap(), outcome);
+            assert_eq!(
+                serde_json::to_string(&outcome).unwrap(),
+                format!("\"{}\"", outcome.as_str())
+            );
+        }
+        for kind in [EvidenceKind::ChatMessage, EvidenceKind::TxHash, EvidenceKind::Note] {
+            assert_eq!(kind.as_str().parse::<EvidenceKind>().unwrap(), kind);
+        }
+        for status in [DisputeStatus::Open, DisputeStatus::Resolved] {
+            assert_eq!(status.as_str().parse::<DisputeStatus>().unwrap(), status);
+        }
+        assert!("SPLIT".parse::<DisputeOutcome>().is_err());
+    }
+
+    #[test]
+    fn test_dispute_text_validation() {
+        assert_eq!(required_text("  scam  ", "Reason").unwrap(), "scam");
+        assert!(required_text("   ", "Reason").is_err());
+        assert_eq!(optional_text(Some(" ".to_string()), "Note").unwrap(), None);
+        assert!(optional_text(Some("x".repeat(MAX_DISPUTE_TEXT_LEN + 1)), "Note").is_err());
+    }
+}
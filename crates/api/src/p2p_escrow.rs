@@ -0,0 +1,1689 @@
+use async_trait::async_trait;
+use blockchain::{Blockchain, SolanaClient};
+use chrono::{DateTime, Utc};
+use database::DbPool;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use deadpool_postgres::Transaction;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::p2p_disputes::reopen_dispute;
+use crate::p2p_service::P2POffer;
+use crate::payment_receipt_service::PaymentReceiptService;
+
+/// How long both sides have to confirm a matched exchange before the
+/// seller's funds are refunded
+pub const DEFAULT_SETTLEMENT_WINDOW_MINUTES: i64 = 60;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// How often and how many times a settlement checks its payout before
+/// leaving it to `reconcile_payouts`
+const DEFAULT_PAYOUT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const DEFAULT_PAYOUT_POLL_ATTEMPTS: u32 = 15;
+
+const PAYOUT_COLUMNS: &str = "tx_hash, escrow_id, asset, amount, recipient, nonce, from_status, reason";
+
+const ESCROW_COLUMNS: &str = "id, offer_id, exchange_id, seller_user_id, buyer_user_id, asset, amount, \
+     status, depositor_address, lock_tx_hash, settlement_tx_hash, seller_confirmed_at, \
+     buyer_confirmed_at, timeout_at, created_at, updated_at";
+
+const TRANSITION_COLUMNS: &str =
+    "id, escrow_id, from_status, to_status, reason, tx_hash, receipt_id, created_at";
+
+/// Where the seller's side of a P2P trade stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EscrowStatus {
+    /// Matched, waiting for the seller to deposit
+    AwaitingDeposit,
+    /// Seller's funds are held by the escrow
+    Locked,
+    /// Held while an arbitrator decides a dispute
+    Disputed,
+    /// Payout to the buyer is in flight
+    Releasing,
+    /// Paid out to the buyer
+    Released,
+    /// Refund to the seller is in flight
+    Refunding,
+    /// Paid back to the seller
+    Refunded,
+    /// Closed before any funds were deposited
+    Cancelled,
+}
+
+impl EscrowStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscrowStatus::AwaitingDeposit => "AWAITING_DEPOSIT",
+            EscrowStatus::Locked => "LOCKED",
+            EscrowStatus::Disputed => "DISPUTED",
+            EscrowStatus::Releasing => "RELEASING",
+            EscrowStatus::Released => "RELEASED",
+            EscrowStatus::Refunding => "REFUNDING",
+            EscrowStatus::Refunded => "REFUNDED",
+            EscrowStatus::Cancelled => "CANCELLED",
+        }
+    }
+
+    /// Whether the escrow may move from this status to `next`
+    ///
+    /// Payouts are two-phase: the escrow enters `Releasing`/`Refunding`
+    /// before funds move and returns to where it was (`Locked` or
+    /// `Disputed`) if the transfer fails. A disputed escrow only settles
+    /// through the arbitrator's resolution.
+    pub fn can_transition_to(&self, next: EscrowStatus) -> bool {
+        use EscrowStatus::*;
+
+        matches!(
+            (self, next),
+            (AwaitingDeposit, Locked)
+                | (AwaitingDeposit, Cancelled)
+                | (Locked, Releasing)
+                | (Locked, Refunding)
+                | (Locked, Disputed)
+                | (Disputed, Releasing)
+                | (Disputed, Refunding)
+                | (Releasing, Released)
+                | (Releasing, Locked)
+                | (Releasing, Disputed)
+                | (Refunding, Refunded)
+                | (Refunding, Locked)
+                | (Refunding, Disputed)
+        )
+    }
+
+    /// Whether the escrow can still change
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            EscrowStatus::AwaitingDeposit
+                | EscrowStatus::Locked
+                | EscrowStatus::Disputed
+                | EscrowStatus::Releasing
+                | EscrowStatus::Refunding
+        )
+    }
+}
+
+impl fmt::Display for EscrowStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EscrowStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "AWAITING_DEPOSIT" => Ok(EscrowStatus::AwaitingDeposit),
+            "LOCKED" => Ok(EscrowStatus::Locked),
+            "DISPUTED" => Ok(EscrowStatus::Disputed),
+            "RELEASING" => Ok(EscrowStatus::Releasing),
+            "RELEASED" => Ok(EscrowStatus::Released),
+            "REFUNDING" => Ok(EscrowStatus::Refunding),
+            "REFUNDED" => Ok(EscrowStatus::Refunded),
+            "CANCELLED" => Ok(EscrowStatus::Cancelled),
+            other => Err(Error::Internal(format!("Invalid escrow status: {}", other))),
+        }
+    }
+}
+
+/// Why an escrow changed status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransitionReason {
+    /// The offer was matched before the seller deposited
+    OfferMatched,
+    /// The seller's deposit was confirmed on-chain
+    Deposit,
+    /// Part of a locked deposit was split off for a partial fill
+    PartialFill,
+    /// Buyer and seller both confirmed the trade
+    BothConfirmed,
+    /// The offer was cancelled by its creator
+    OfferCancelled,
+    /// The offer expired unmatched
+    OfferExpired,
+    /// The exchange was not settled within the settlement window
+    SettlementTimeout,
+    /// The payout transfer failed; funds are still held
+    PayoutFailed,
+    /// A party disputed the exchange
+    DisputeOpened,
+    /// An arbitrator decided the dispute
+    DisputeResolved,
+}
+
+impl TransitionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransitionReason::OfferMatched => "OFFER_MATCHED",
+            TransitionReason::Deposit => "DEPOSIT",
+            TransitionReason::PartialFill => "PARTIAL_FILL",
+            TransitionReason::BothConfirmed => "BOTH_CONFIRMED",
+            TransitionReason::OfferCancelled => "OFFER_CANCELLED",
+            TransitionReason::OfferExpired => "OFFER_EXPIRED",
+            TransitionReason::SettlementTimeout => "SETTLEMENT_TIMEOUT",
+            TransitionReason::PayoutFailed => "PAYOUT_FAILED",
+            TransitionReason::DisputeOpened => "DISPUTE_OPENED",
+            TransitionReason::DisputeResolved => "DISPUTE_RESOLVED",
+        }
+    }
+}
+
+impl FromStr for TransitionReason {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "OFFER_MATCHED" => Ok(TransitionReason::OfferMatched),
+            "DEPOSIT" => Ok(TransitionReason::Deposit),
+            "PARTIAL_FILL" => Ok(TransitionReason::PartialFill),
+            "BOTH_CONFIRMED" => Ok(TransitionReason::BothConfirmed),
+            "OFFER_CANCELLED" => Ok(TransitionReason::OfferCancelled),
+            "OFFER_EXPIRED" => Ok(TransitionReason::OfferExpired),
+            "SETTLEMENT_TIMEOUT" => Ok(TransitionReason::SettlementTimeout),
+            "PAYOUT_FAILED" => Ok(TransitionReason::PayoutFailed),
+            "DISPUTE_OPENED" => Ok(TransitionReason::DisputeOpened),
+            "DISPUTE_RESOLVED" => Ok(TransitionReason::DisputeResolved),
+            other => Err(Error::Internal(format!("Invalid escrow transition reason: {}", other))),
+        }
+    }
+}
+
+/// Escrow holding the seller's side of one P2P offer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct P2PEscrow {
+    pub id: Uuid,
+    pub offer_id: Uuid,
+    /// Exchange settled by this escrow, once the offer is matched
+    pub exchange_id: Option<Uuid>,
+    pub seller_user_id: Uuid,
+    pub buyer_user_id: Option<Uuid>,
+    pub asset: String,
+    pub amount: Decimal,
+    pub status: EscrowStatus,
+    /// Address the deposit came from; refunds go back to it
+    pub depositor_address: Option<String>,
+    pub lock_tx_hash: Option<String>,
+    /// Release or refund transaction
+    pub settlement_tx_hash: Option<String>,
+    pub seller_confirmed_at: Option<DateTime<Utc>>,
+    pub buyer_confirmed_at: Option<DateTime<Utc>>,
+    /// When the escrow is refunded (or cancelled) if still open
+    pub timeout_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One recorded status change of an escrow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowTransition {
+    pub id: Uuid,
+    pub escrow_id: Uuid,
+    pub from_status: Option<EscrowStatus>,
+    pub to_status: EscrowStatus,
+    pub reason: String,
+    pub tx_hash: Option<String>,
+    /// Receipt issued for the funds movement, if any
+    pub receipt_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A seller's deposit into escrow, checked on-chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowDeposit {
+    pub tx_hash: String,
+    /// Address the funds came from
+    pub depositor: String,
+}
+
+/// Deposit funding a new sell offer
+///
+/// The seller picks the id of the escrow the deposit opens, and the deposit
+/// transaction must carry `deposit_memo(escrow_id)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferDeposit {
+    pub escrow_id: Uuid,
+    pub tx_hash: String,
+}
+
+/// A payout out of escrow, signed but not necessarily on-chain
+///
+/// It is stored before it is sent, so its outcome can always be looked up
+/// and it is never replaced by a second transfer while it might still land.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedPayout {
+    pub tx_hash: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub recipient: String,
+    /// Chain-specific data the custodian needs to rebuild the signed
+    /// transaction (the recent blockhash on Solana)
+    pub nonce: String,
+}
+
+/// On-chain outcome of a signed payout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayoutStatus {
+    /// The payout landed
+    Confirmed,
+    /// The payout can no longer land; no funds moved
+    Failed(String),
+    /// Not on-chain yet, but it may still land
+    Pending,
+}
+
+/// Memo a deposit transaction must carry to fund `escrow_id`
+pub fn deposit_memo(escrow_id: Uuid) -> String {
+    format!("p2p-escrow:{}", escrow_id)
+}
+
+/// Holds escrowed funds and moves them on-chain
+#[async_trait]
+pub trait EscrowCustodian: Send + Sync {
+    /// Chain the escrowed funds live on
+    fn blockchain(&self) -> Blockchain;
+
+    /// Address sellers deposit escrowed funds to
+    fn escrow_address(&self) -> String;
+
+    /// Check that `tx_hash` is a confirmed deposit of at least `amount`
+    /// `asset` into the escrow address, carrying `memo`
+    async fn verify_deposit(&self, tx_hash: &str, asset: &str, amount: Decimal, memo: &str) -> Result<EscrowDeposit>;
+
+    /// Sign a payout of `amount` `asset` out of escrow to `recipient`
+    /// without sending it
+    ///
+    /// An error means nothing was signed.
+    async fn sign_payout(&self, asset: &str, amount: Decimal, recipient: &str) -> Result<SignedPayout>;
+
+    /// Broadcast a signed payout; sending the same payout again is safe
+    async fn send_payout(&self, payout: &SignedPayout) -> Result<()>;
+
+    /// Look up whether a signed payout landed, failed or may still land
+    async fn payout_status(&self, payout: &SignedPayout) -> Result<PayoutStatus>;
+}
+
+/// Custodian holding SOL in a platform-controlled escrow wallet
+pub struct SolanaEscrowCustodian {
+    client: Arc<SolanaClient>,
+    keypair: Keypair,
+}
+
+impl SolanaEscrowCustodian {
+    pub fn new(client: Arc<SolanaClient>, keypair: Keypair) -> Self {
+        Self { client, keypair }
+    }
+
+    fn check_asset(asset: &str) -> Result<()> {
+        if asset.eq_ignore_ascii_case("SOL") {
+            Ok(())
+        } else {
+            Err(Error::Validation(format!("Escrow does not support {}", asset)))
+        }
+    }
+
+    /// Rebuild the signed transfer of `payout`
+    fn payout_transaction(&self, payout: &SignedPayout) -> Result<solana_sdk::transaction::Transaction> {
+        Self::check_asset(&payout.asset)?;
+        let lamports = sol_to_lamports(payout.amount)?;
+        let recipient = Pubkey::from_str(&payout.recipient)
+            .map_err(|_| Error::InvalidWalletAddress(payout.recipient.clone()))?;
+        let blockhash = Hash::from_str(&payout.nonce)
+            .map_err(|_| Error::Internal(format!("Invalid payout blockhash: {}", payout.nonce)))?;
+
+        Ok(SolanaClient::sign_transfer(&self.keypair, &recipient, lamports, blockhash))
+    }
+}
+
+#[async_trait]
+impl EscrowCustodian for SolanaEscrowCustodian {
+    fn blockchain(&self) -> Blockchain {
+        Blockchain::SOLANA
+    }
+
+    fn escrow_address(&self) -> String {
+        self.keypair.pubkey().to_string()
+    }
+
+    async fn verify_deposit(&self, tx_hash: &str, asset: &str, amount: Decimal, memo: &str) -> Result<EscrowDeposit> {
+        Self::check_asset(asset)?;
+        let expected = sol_to_lamports(amount)?;
+        let signature = Signature::from_str(tx_hash)
+            .map_err(|_| Error::Validation(format!("Invalid deposit transaction: {}", tx_hash)))?;
+
+        let transfer = self
+            .client
+            .get_received_transfer(&signature, &self.keypair.pubkey())
+            .await?
+            .ok_or_else(|| Error::Validation(format!("Deposit {} is not confirmed yet", tx_hash)))?;
+
+        if transfer.lamports < expected {
+            return Err(Error::Validation(format!(
+                "Deposit {} sent {} lamports to escrow, expected {}",
+                tx_hash, transfer.lamports, expected
+            )));
+        }
+        if !transfer.memos.iter().any(|m| m.trim() == memo) {
+            return Err(Error::Validation(format!(
+                "Deposit {} does not carry the escrow memo {}",
+                tx_hash, memo
+            )));
+        }
+
+        Ok(EscrowDeposit {
+            tx_hash: tx_hash.to_string(),
+            depositor: transfer.sender.to_string(),
+        })
+    }
+
+    async fn sign_payout(&self, asset: &str, amount: Decimal, recipient: &str) -> Result<SignedPayout> {
+        let blockhash = self.client.get_latest_blockhash().await?;
+        let mut payout = SignedPayout {
+            tx_hash: String::new(),
+            asset: asset.to_string(),
+            amount,
+            recipient: recipient.to_string(),
+            nonce: blockhash.to_string(),
+        };
+        let transaction = self.payout_transaction(&payout)?;
+        payout.tx_hash = transaction.signatures[0].to_string();
+        Ok(payout)
+    }
+
+    async fn send_payout(&self, payout: &SignedPayout) -> Result<()> {
+        let transaction = self.payout_transaction(payout)?;
+        self.client.send_transaction(&transaction).await?;
+        Ok(())
+    }
+
+    async fn payout_status(&self, payout: &SignedPayout) -> Result<PayoutStatus> {
+        let transaction = self.payout_transaction(payout)?;
+        let signature = transaction.signatures[0];
+
+        let status = match self.client.get_signature_status(&signature).await? {
+            Some(status) => Some(status),
+            None if self.client.is_blockhash_valid(&transaction.message.recent_blockhash).await? => {
+                return Ok(PayoutStatus::Pending);
+            }
+            // The blockhash expired, so the transaction either landed
+            // before that or never will
+            None => self.client.get_signature_status_with_history(&signature).await?,
+        };
+
+        Ok(match status {
+            Some(Ok(())) => PayoutStatus::Confirmed,
+            Some(Err(e)) => PayoutStatus::Failed(e.to_string()),
+            None => PayoutStatus::Failed("blockhash expired before the payout landed".to_string()),
+        })
+    }
+}
+
+/// Whole lamports in `amount` SOL
+pub fn sol_to_lamports(amount: Decimal) -> Result<u64> {
+    let lamports = amount * Decimal::from(LAMPORTS_PER_SOL);
+
+    if lamports <= Decimal::ZERO || !lamports.fract().is_zero() {
+        return Err(Error::Validation(format!(
+            "Invalid SOL amount for escrow: {}",
+            amount
+        )));
+    }
+    lamports
+        .to_u64()
+        .ok_or_else(|| Error::Validation(format!("SOL amount too large for escrow: {}", amount)))
+}
+
+/// Escrow lifecycle for P2P offers
+///
+/// The seller's asset is locked when a sell offer is created, or when a buy
+/// offer is accepted and the seller deposits. It is released to the buyer
+/// once both sides confirm the trade, and refunded to the seller when the
+/// offer is cancelled, expires, or the exchange is not settled in time.
+/// Every transition is recorded in `p2p_escrow_transitions`, and every
+/// movement of funds gets a receipt.
+pub struct EscrowService {
+    db: DbPool,
+    custodian: Arc<dyn EscrowCustodian>,
+    receipt_service: Option<Arc<PaymentReceiptService>>,
+    settlement_window: chrono::Duration,
+    payout_poll_interval: std::time::Duration,
+    payout_poll_attempts: u32,
+}
+
+impl EscrowService {
+    pub fn new(db: DbPool, custodian: Arc<dyn EscrowCustodian>) -> Self {
+        Self {
+            db,
+            custodian,
+            receipt_service: None,
+            settlement_window: chrono::Duration::minutes(DEFAULT_SETTLEMENT_WINDOW_MINUTES),
+            payout_poll_interval: DEFAULT_PAYOUT_POLL_INTERVAL,
+            payout_poll_attempts: DEFAULT_PAYOUT_POLL_ATTEMPTS,
+        }
+    }
+
+    pub fn with_receipt_service(mut self, receipt_service: Arc<PaymentReceiptService>) -> Self {
+        self.receipt_service = Some(receipt_service);
+        self
+    }
+
+    pub fn with_settlement_window(mut self, settlement_window: chrono::Duration) -> Self {
+        self.settlement_window = settlement_window;
+        self
+    }
+
+    /// How long a settlement waits for its payout to land before leaving
+    /// it to `reconcile_payouts`
+    pub fn with_payout_polling(mut self, interval: std::time::Duration, attempts: u32) -> Self {
+        self.payout_poll_interval = interval;
+        self.payout_poll_attempts = attempts;
+        self
+    }
+
+    /// Address sellers deposit escrowed funds to
+    pub fn escrow_address(&self) -> String {
+        self.custodian.escrow_address()
+    }
+
+    pub fn blockchain(&self) -> Blockchain {
+        self.custodian.blockchain()
+    }
+
+    /// Check on-chain that `tx_hash` deposits `amount` `asset` for
+    /// `escrow_id` from one of the seller's verified wallets
+    pub async fn verify_deposit(
+        &self,
+        seller_user_id: Uuid,
+        escrow_id: Uuid,
+        tx_hash: &str,
+        asset: &str,
+        amount: Decimal,
+    ) -> Result<EscrowDeposit> {
+        let deposit = self
+            .custodian
+            .verify_deposit(tx_hash, asset, amount, &deposit_memo(escrow_id))
+            .await?;
+
+        let client = self.client().await?;
+        let verified = client
+            .query_opt(
+                "SELECT 1 FROM wallet_verifications
+                 WHERE user_id = $1 AND wallet_address = $2 AND blockchain = $3 AND verified = TRUE",
+                &[&seller_user_id, &deposit.depositor, &self.blockchain().id()],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to check depositor wallet: {}", e)))?;
+        if verified.is_none() {
+            return Err(Error::Validation(format!(
+                "Deposit {} was not sent from a verified wallet of the seller",
+                tx_hash
+            )));
+        }
+
+        Ok(deposit)
+    }
+
+    /// Record a verified deposit locking a new sell offer, inside the
+    /// transaction creating the offer
+    ///
+    /// The escrow times out when the offer expires. Returns the escrow and
+    /// the lock transition; pass both to `issue_deposit_receipt` once the
+    /// transaction commits.
+    pub async fn lock_for_offer(
+        &self,
+        transaction: &Transaction<'_>,
+        escrow_id: Uuid,
+        offer: &P2POffer,
+        deposit: &EscrowDeposit,
+    ) -> Result<(P2PEscrow, Uuid)> {
+        let row = transaction
+            .query_one(
+                &format!(
+                    "INSERT INTO p2p_escrows (
+                        id, offer_id, seller_user_id, asset, amount, status,
+                        depositor_address, lock_tx_hash, timeout_at
+                     )
+                     VALUES ($1, $2, $3, $4, $5, 'LOCKED', $6, $7, $8)
+                     RETURNING {}",
+                    ESCROW_COLUMNS
+                ),
+                &[
+                    &escrow_id,
+                    &offer.id,
+                    &offer.user_id,
+                    &offer.base_asset(),
+                    &offer.remaining_amount,
+                    &deposit.depositor,
+                    &deposit.tx_hash,
+                    &offer.expires_at,
+                ],
+            )
+            .await
+            .map_err(|e| match e.as_db_error().and_then(|db| db.constraint()) {
+                Some("p2p_escrows_pkey") => Error::Validation(format!("Escrow {} already exists", escrow_id)),
+                _ => Error::Database(format!("Failed to create escrow: {}", e)),
+            })?;
+        let escrow = row_to_escrow(&row)?;
+        consume_deposit(transaction, escrow.id, deposit).await?;
+
+        let transition_id = record_transition(
+            transaction,
+            escrow.id,
+            None,
+            EscrowStatus::Locked,
+            TransitionReason::Deposit,
+            Some(&deposit.tx_hash),
+        )
+        .await?;
+
+        Ok((escrow, transition_id))
+    }
+
+    /// Escrow `amount` of the seller's side of an exchange created when
+    /// `offer` was matched, inside the transaction creating the exchange
+    ///
+    /// If the offer's deposit is locked, the exchange takes that escrow, or
+    /// for a partial fill splits `amount` off it, and its settlement window
+    /// starts. Otherwise nothing is locked yet, so an escrow awaiting the
+    /// seller's deposit is opened.
+    pub async fn open_for_exchange(
+        &self,
+        transaction: &Transaction<'_>,
+        offer: &P2POffer,
+        exchange_id: Uuid,
+        seller_user_id: Uuid,
+        buyer_user_id: Uuid,
+        amount: Decimal,
+    ) -> Result<P2PEscrow> {
+        let timeout_at = Utc::now() + self.settlement_window;
+
+        let locked = transaction
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM p2p_escrows
+                     WHERE offer_id = $1 AND status = 'LOCKED' AND exchange_id IS NULL
+                     FOR UPDATE",
+                    ESCROW_COLUMNS
+                ),
+                &[&offer.id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to lock escrow: {}", e)))?
+            .map(|row| row_to_escrow(&row))
+            .transpose()?;
+
+        match locked {
+            Some(escrow) if escrow.amount == amount => {
+                let row = transaction
+                    .query_one(
+                        &format!(
+                            "UPDATE p2p_escrows
+                             SET exchange_id = $2, buyer_user_id = $3, timeout_at = $4, updated_at = NOW()
+                             WHERE id = $1
+                             RETURNING {}",
+                            ESCROW_COLUMNS
+                        ),
+                        &[&escrow.id, &exchange_id, &buyer_user_id, &timeout_at],
+                    )
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to attach escrow to exchange: {}", e)))?;
+                row_to_escrow(&row)
+            }
+            Some(escrow) if escrow.amount > amount => {
+                transaction
+                    .execute(
+                        "UPDATE p2p_escrows SET amount = amount - $2, updated_at = NOW() WHERE id = $1",
+                        &[&escrow.id, &amount],
+                    )
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to split escrow: {}", e)))?;
+
+                let row = transaction
+                    .query_one(
+                        &format!(
+                            "INSERT INTO p2p_escrows (
+                                id, offer_id, exchange_id, seller_user_id, buyer_user_id, asset, amount,
+                                status, depositor_address, lock_tx_hash, timeout_at
+                             )
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, 'LOCKED', $8, $9, $10)
+                             RETURNING {}",
+                            ESCROW_COLUMNS
+                        ),
+                        &[
+                            &Uuid::new_v4(),
+                            &offer.id,
+                            &exchange_id,
+                            &escrow.seller_user_id,
+                            &buyer_user_id,
+                            &escrow.asset,
+                            &amount,
+                            &escrow.depositor_address,
+                            &escrow.lock_tx_hash,
+                            &timeout_at,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to create escrow: {}", e)))?;
+                let fill = row_to_escrow(&row)?;
+
+                record_transition(
+                    transaction,
+                    fill.id,
+                    None,
+                    EscrowStatus::Locked,
+                    TransitionReason::PartialFill,
+                    escrow.lock_tx_hash.as_deref(),
+                )
+                .await?;
+
+                Ok(fill)
+            }
+            Some(escrow) => Err(Error::Validation(format!(
+                "Escrow {} holds {} {}, less than the {} being traded",
+                escrow.id, escrow.amount, escrow.asset, amount
+            ))),
+            None => {
+                let row = transaction
+                    .query_one(
+                        &format!(
+                            "INSERT INTO p2p_escrows (
+                                id, offer_id, exchange_id, seller_user_id, buyer_user_id,
+                                asset, amount, status, timeout_at
+                             )
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, 'AWAITING_DEPOSIT', $8)
+                             RETURNING {}",
+                            ESCROW_COLUMNS
+                        ),
+                        &[
+                            &Uuid::new_v4(),
+                            &offer.id,
+                            &exchange_id,
+                            &seller_user_id,
+                            &buyer_user_id,
+                            &offer.base_asset(),
+                            &amount,
+                            &timeout_at,
+                        ],
+                    )
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to create escrow: {}", e)))?;
+                let escrow = row_to_escrow(&row)?;
+
+                record_transition(
+                    transaction,
+                    escrow.id,
+                    None,
+                    EscrowStatus::AwaitingDeposit,
+                    TransitionReason::OfferMatched,
+                    None,
+                )
+                .await?;
+
+                Ok(escrow)
+            }
+        }
+    }
+
+    /// Issue the receipt for a deposit that locked `escrow`
+    pub async fn issue_deposit_receipt(&self, escrow: &P2PEscrow, transition_id: Uuid) {
+        let (Some(tx_hash), Some(depositor)) = (&escrow.lock_tx_hash, &escrow.depositor_address) else {
+            return;
+        };
+        self.issue_receipt(escrow, transition_id, tx_hash, depositor, &self.escrow_address())
+            .await;
+    }
+
+    /// Lock the seller's deposit for a matched buy offer
+    pub async fn deposit(&self, exchange_id: Uuid, seller_user_id: Uuid, tx_hash: &str) -> Result<P2PEscrow> {
+        let escrow = self.get_escrow_for_exchange(exchange_id).await?;
+
+        if escrow.seller_user_id != seller_user_id {
+            return Err(Error::Validation("Only the seller can fund the escrow".to_string()));
+        }
+        if escrow.status != EscrowStatus::AwaitingDeposit {
+            return Err(Error::Validation(format!(
+                "Escrow is not awaiting a deposit (status: {})",
+                escrow.status
+            )));
+        }
+
+        let deposit = self
+            .verify_deposit(seller_user_id, escrow.id, tx_hash, &escrow.asset, escrow.amount)
+            .await?;
+
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let escrow = lock_escrow(&transaction, escrow.id).await?;
+        check_transition(&escrow, EscrowStatus::Locked, TransitionReason::Deposit)?;
+
+        let row = transaction
+            .query_one(
+                &format!(
+                    "UPDATE p2p_escrows
+                     SET status = 'LOCKED', depositor_address = $2, lock_tx_hash = $3, updated_at = NOW()
+                     WHERE id = $1
+                     RETURNING {}",
+                    ESCROW_COLUMNS
+                ),
+                &[&escrow.id, &deposit.depositor, &deposit.tx_hash],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to lock escrow: {}", e)))?;
+        let locked = row_to_escrow(&row)?;
+        consume_deposit(&transaction, locked.id, &deposit).await?;
+
+        let transition_id = record_transition(
+            &transaction,
+            locked.id,
+            Some(EscrowStatus::AwaitingDeposit),
+            EscrowStatus::Locked,
+            TransitionReason::Deposit,
+            Some(&deposit.tx_hash),
+        )
+        .await?;
+
+        transaction
+            .execute(
+                "UPDATE p2p_offers SET escrow_tx_hash = $2 WHERE id = $1",
+                &[&locked.offer_id, &deposit.tx_hash],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update offer: {}", e)))?;
+
+        commit(transaction).await?;
+
+        info!("Escrow {} locked by deposit {}", locked.id, deposit.tx_hash);
+        self.issue_deposit_receipt(&locked, transition_id).await;
+
+        Ok(locked)
+    }
+
+    /// Record `user_id`'s confirmation that the exchange took place, and
+    /// release the escrow to the buyer once both sides have confirmed
+    pub async fn confirm(&self, exchange_id: Uuid, user_id: Uuid) -> Result<P2PEscrow> {
+        let escrow = self.get_escrow_for_exchange(exchange_id).await?;
+
+        let column = if escrow.seller_user_id == user_id {
+            "seller_confirmed_at"
+        } else if escrow.buyer_user_id == Some(user_id) {
+            "buyer_confirmed_at"
+        } else {
+            return Err(Error::Unauthorized);
+        };
+        if escrow.status != EscrowStatus::Locked {
+            return Err(Error::Validation(format!(
+                "Escrow cannot be confirmed (status: {})",
+                escrow.status
+            )));
+        }
+
+        // Only a still locked escrow takes the confirmation, so it cannot
+        // race a refund or release that claimed the escrow in the meantime
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                &format!(
+                    "UPDATE p2p_escrows
+                     SET {column} = COALESCE({column}, NOW()), updated_at = NOW()
+                     WHERE id = $1 AND status = 'LOCKED'
+                     RETURNING {}",
+                    ESCROW_COLUMNS
+                ),
+                &[&escrow.id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to record confirmation: {}", e)))?
+            .ok_or_else(|| Error::Validation("Escrow is no longer locked".to_string()))?;
+        let escrow = row_to_escrow(&row)?;
+
+        if escrow.seller_confirmed_at.is_some() && escrow.buyer_confirmed_at.is_some() {
+            return self.release(escrow.id, TransitionReason::BothConfirmed).await;
+        }
+        Ok(escrow)
+    }
+
+    /// Pay the escrowed funds out to the buyer
+    pub async fn release(&self, escrow_id: Uuid, reason: TransitionReason) -> Result<P2PEscrow> {
+        self.settle(escrow_id, EscrowStatus::Releasing, reason).await
+    }
+
+    /// Return the escrowed funds to the seller, or close the escrow if
+    /// nothing was deposited
+    pub async fn refund(&self, escrow_id: Uuid, reason: TransitionReason) -> Result<P2PEscrow> {
+        self.settle(escrow_id, EscrowStatus::Refunding, reason).await
+    }
+
+    /// Refund the open escrow of an offer that was cancelled or expired
+    /// before being matched, if it has one
+    ///
+    /// The escrow's timeout is brought forward first, so a failed refund is
+    /// retried by the timeout worker.
+    pub async fn refund_offer(&self, offer_id: Uuid, reason: TransitionReason) -> Result<Option<P2PEscrow>> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                "UPDATE p2p_escrows
+                 SET timeout_at = LEAST(timeout_at, NOW()), updated_at = NOW()
+                 WHERE offer_id = $1 AND exchange_id IS NULL AND status = 'LOCKED'
+                 RETURNING id",
+                &[&offer_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch escrow: {}", e)))?;
+        drop(client);
+
+        match row {
+            Some(row) => self.refund(row.get("id"), reason).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Settle escrows whose timeout has passed
+    ///
+    /// Escrows both sides confirmed are released (a previous payout failed)
+    /// and escrows only the buyer confirmed are held for a dispute; all
+    /// others are refunded or, without a deposit, cancelled. Returns how many
+    /// escrows were settled or held.
+    pub async fn process_timeouts(&self, limit: i64) -> Result<usize> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                "SELECT id,
+                        seller_confirmed_at IS NOT NULL AS seller_confirmed,
+                        buyer_confirmed_at IS NOT NULL AS buyer_confirmed
+                 FROM p2p_escrows
+                 WHERE status IN ('AWAITING_DEPOSIT', 'LOCKED') AND timeout_at <= NOW()
+                 ORDER BY timeout_at
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch timed out escrows: {}", e)))?;
+        drop(client);
+
+        let mut settled = 0;
+        for row in rows {
+            let escrow_id: Uuid = row.get("id");
+            let result = match (row.get::<_, bool>("seller_confirmed"), row.get::<_, bool>("buyer_confirmed")) {
+                (true, true) => self.release(escrow_id, TransitionReason::BothConfirmed).await,
+                // Refunding would let the seller keep the buyer's payment too
+                (false, true) => self.dispute_unconfirmed(escrow_id).await,
+                _ => self.refund(escrow_id, TransitionReason::SettlementTimeout).await,
+            };
+
+            match result {
+                Ok(_) => settled += 1,
+                Err(e) => warn!("Failed to settle timed out escrow {}: {}", escrow_id, e),
+            }
+        }
+
+        Ok(settled)
+    }
+
+    /// Hold a timed out escrow whose buyer confirmed payment but whose
+    /// seller did not, opening a dispute on the buyer's behalf
+    async fn dispute_unconfirmed(&self, escrow_id: Uuid) -> Result<P2PEscrow> {
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+        let escrow = lock_escrow(&transaction, escrow_id).await?;
+
+        let (Some(exchange_id), Some(buyer_id)) = (escrow.exchange_id, escrow.buyer_user_id) else {
+            return Err(Error::Internal(format!("Escrow {} has no exchange to dispute", escrow_id)));
+        };
+        if escrow.buyer_confirmed_at.is_none() || escrow.seller_confirmed_at.is_some() {
+            return Err(Error::Validation(format!(
+                "Escrow {} is not awaiting the seller's confirmation",
+                escrow_id
+            )));
+        }
+
+        let escrow = hold_for_dispute(&transaction, exchange_id)
+            .await?
+            .ok_or_else(|| Error::Internal(format!("Escrow {} not found for exchange", escrow_id)))?;
+        transaction
+            .execute(
+                "UPDATE p2p_exchanges SET status = 'DISPUTED' WHERE id = $1",
+                &[&exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update exchange: {}", e)))?;
+        transaction
+            .execute(
+                "INSERT INTO p2p_disputes (id, exchange_id, opened_by, reason, status)
+                 VALUES ($1, $2, $3, $4, 'OPEN')
+                 ON CONFLICT (exchange_id) DO NOTHING",
+                &[
+                    &Uuid::new_v4(),
+                    &exchange_id,
+                    &buyer_id,
+                    &"Seller did not confirm the buyer's payment before the settlement timeout",
+                ],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to open dispute: {}", e)))?;
+        commit(transaction).await?;
+
+        info!("Escrow {} held for a dispute: seller did not confirm before timeout", escrow_id);
+        Ok(escrow)
+    }
+
+    /// Settle payouts left in flight, returning how many escrows were
+    /// settled or reopened
+    ///
+    /// Each stored payout is sent again (resending is safe) and its
+    /// on-chain status decides the outcome: a landed payout closes the
+    /// escrow, one that can no longer land reopens it for another attempt,
+    /// and one that may still land is left alone.
+    pub async fn reconcile_payouts(&self, limit: i64) -> Result<usize> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM p2p_escrow_payouts
+                     WHERE status = 'PENDING'
+                     ORDER BY created_at
+                     LIMIT $1",
+                    PAYOUT_COLUMNS
+                ),
+                &[&limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch pending payouts: {}", e)))?;
+        drop(client);
+
+        let mut reconciled = 0;
+        for row in rows {
+            let pending = row_to_pending_payout(&row)?;
+            let escrow_id = pending.escrow_id;
+
+            if let Err(e) = self.custodian.send_payout(&pending.payout).await {
+                warn!("Failed to resend payout {} of escrow {}: {}", pending.payout.tx_hash, escrow_id, e);
+            }
+            match self.custodian.payout_status(&pending.payout).await {
+                Ok(PayoutStatus::Pending) => {}
+                Ok(status) => match self.record_payout(&pending, status).await {
+                    Ok(_) => reconciled += 1,
+                    Err(e) => warn!("Failed to settle payout of escrow {}: {}", escrow_id, e),
+                },
+                Err(e) => warn!("Failed to check payout {} of escrow {}: {}", pending.payout.tx_hash, escrow_id, e),
+            }
+        }
+
+        Ok(reconciled)
+    }
+
+    /// Move an open escrow to `pending` (`Releasing` or `Refunding`) and pay
+    /// out, recording the final status once the payout lands
+    ///
+    /// The payout is signed and stored with the pending status before it is
+    /// sent, and only its on-chain status settles the escrow. While that
+    /// status is unknown the escrow stays pending, so a payout that might
+    /// still land is never retried or followed by a refund;
+    /// `reconcile_payouts` picks it up.
+    async fn settle(&self, escrow_id: Uuid, pending: EscrowStatus, reason: TransitionReason) -> Result<P2PEscrow> {
+        let mut client = self.client().await?;
+
+        // Phase 1: claim the escrow and store the signed payout
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+        let escrow = lock_escrow(&transaction, escrow_id).await?;
+
+        if escrow.status == EscrowStatus::AwaitingDeposit && pending == EscrowStatus::Refunding {
+            let (cancelled, _) = finish(&transaction, &escrow, EscrowStatus::Cancelled, reason, None).await?;
+            commit(transaction).await?;
+            info!("Escrow {} cancelled before deposit ({})", escrow_id, reason.as_str());
+            return Ok(cancelled);
+        }
+
+        if escrow.status == EscrowStatus::Disputed && reason != TransitionReason::DisputeResolved {
+            return Err(Error::Validation(format!(
+                "Escrow {} is held for a dispute",
+                escrow_id
+            )));
+        }
+        if reason == TransitionReason::SettlementTimeout && escrow.buyer_confirmed_at.is_some() {
+            return Err(Error::Validation(format!(
+                "Escrow {} has a buyer confirmation and cannot be refunded on timeout",
+                escrow_id
+            )));
+        }
+        check_transition(&escrow, pending, reason)?;
+        let recipient = match pending {
+            EscrowStatus::Releasing => {
+                let buyer = escrow.buyer_user_id.ok_or_else(|| {
+                    Error::Validation("Escrow has no buyer to release to".to_string())
+                })?;
+                wallet_address(&transaction, buyer).await?
+            }
+            _ => escrow.depositor_address.clone().ok_or_else(|| {
+                Error::Internal(format!("Escrow {} has no depositor address", escrow_id))
+            })?,
+        };
+
+        let payout = self
+            .custodian
+            .sign_payout(&escrow.asset, escrow.amount, &recipient)
+            .await?;
+        set_status(&transaction, escrow_id, pending).await?;
+        record_transition(
+            &transaction,
+            escrow_id,
+            Some(escrow.status),
+            pending,
+            reason,
+            Some(&payout.tx_hash),
+        )
+        .await?;
+        transaction
+            .execute(
+                "INSERT INTO p2p_escrow_payouts (
+                    tx_hash, escrow_id, asset, amount, recipient, nonce, from_status, reason
+                 )
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &payout.tx_hash,
+                    &escrow_id,
+                    &payout.asset,
+                    &payout.amount,
+                    &payout.recipient,
+                    &payout.nonce,
+                    &escrow.status.as_str(),
+                    &reason.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to store payout: {}", e)))?;
+        commit(transaction).await?;
+        drop(client);
+
+        let pending_payout = PendingPayout {
+            escrow_id,
+            from_status: escrow.status,
+            reason,
+            payout,
+        };
+
+        // Phase 2: send it; whether it went out is decided by its status
+        if let Err(e) = self.custodian.send_payout(&pending_payout.payout).await {
+            warn!(
+                "Sending payout {} of escrow {} failed: {}",
+                pending_payout.payout.tx_hash, escrow_id, e
+            );
+        }
+
+        // Phase 3: wait for the outcome
+        for attempt in 0..self.payout_poll_attempts {
+            if attempt > 0 {
+                tokio::time::sleep(self.payout_poll_interval).await;
+            }
+            match self.custodian.payout_status(&pending_payout.payout).await {
+                Ok(PayoutStatus::Pending) => {}
+                Ok(status) => return self.record_payout(&pending_payout, status).await,
+                Err(e) => warn!(
+                    "Failed to check payout {} of escrow {}: {}",
+                    pending_payout.payout.tx_hash, escrow_id, e
+                ),
+            }
+        }
+
+        info!(
+            "Payout {} of escrow {} not confirmed yet; left for reconciliation",
+            pending_payout.payout.tx_hash, escrow_id
+        );
+        self.get_escrow(escrow_id).await
+    }
+
+    /// Record the final outcome of a payout: close the escrow when it
+    /// landed, or reopen it when it can no longer land
+    async fn record_payout(&self, pending: &PendingPayout, status: PayoutStatus) -> Result<P2PEscrow> {
+        let escrow_id = pending.escrow_id;
+        let tx_hash = pending.payout.tx_hash.as_str();
+        let failure = match status {
+            PayoutStatus::Confirmed => None,
+            PayoutStatus::Failed(cause) => Some(cause),
+            PayoutStatus::Pending => return self.get_escrow(escrow_id).await,
+        };
+
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+        let escrow = lock_escrow(&transaction, escrow_id).await?;
+
+        let claimed = transaction
+            .execute(
+                "UPDATE p2p_escrow_payouts SET status = $2, updated_at = NOW()
+                 WHERE tx_hash = $1 AND status = 'PENDING'",
+                &[&tx_hash, &if failure.is_none() { "CONFIRMED" } else { "FAILED" }],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update payout: {}", e)))?;
+        if claimed == 0 {
+            // Already recorded by a concurrent settlement or reconciliation
+            return Ok(escrow);
+        }
+
+        match failure {
+            None => {
+                let done = match escrow.status {
+                    EscrowStatus::Releasing => EscrowStatus::Released,
+                    _ => EscrowStatus::Refunded,
+                };
+                let (settled, transition_id) =
+                    finish(&transaction, &escrow, done, pending.reason, Some(tx_hash)).await?;
+                commit(transaction).await.map_err(|e| {
+                    error!(
+                        "Escrow {} paid out in {} but the outcome was not recorded: {}",
+                        escrow_id, tx_hash, e
+                    );
+                    e
+                })?;
+
+                info!("Escrow {} {} in {}", escrow_id, done, tx_hash);
+                self.issue_receipt(&settled, transition_id, tx_hash, &self.escrow_address(), &pending.payout.recipient)
+                    .await;
+                Ok(settled)
+            }
+            Some(cause) => {
+                error!("Escrow {} payout {} failed: {}", escrow_id, tx_hash, cause);
+                check_transition(&escrow, pending.from_status, TransitionReason::PayoutFailed)?;
+                set_status(&transaction, escrow_id, pending.from_status).await?;
+                record_transition(
+                    &transaction,
+                    escrow_id,
+                    Some(escrow.status),
+                    pending.from_status,
+                    TransitionReason::PayoutFailed,
+                    Some(tx_hash),
+                )
+                .await?;
+                if pending.from_status == EscrowStatus::Disputed {
+                    if let Some(exchange_id) = escrow.exchange_id {
+                        reopen_dispute(&transaction, exchange_id).await?;
+                    }
+                }
+                commit(transaction).await?;
+                Err(Error::SolanaRpc(format!("Payout {} failed: {}", tx_hash, cause)))
+            }
+        }
+    }
+
+    pub async fn get_escrow(&self, escrow_id: Uuid) -> Result<P2PEscrow> {
+        self.fetch_escrow("id", escrow_id).await
+    }
+
+    pub async fn get_escrow_for_exchange(&self, exchange_id: Uuid) -> Result<P2PEscrow> {
+        self.fetch_escrow("exchange_id", exchange_id).await
+    }
+
+    /// Status history of an escrow, oldest first
+    pub async fn get_transitions(&self, escrow_id: Uuid) -> Result<Vec<EscrowTransition>> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT {} FROM p2p_escrow_transitions
+                     WHERE escrow_id = $1
+                     ORDER BY created_at, id",
+                    TRANSITION_COLUMNS
+                ),
+                &[&escrow_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch escrow transitions: {}", e)))?;
+
+        rows.iter().map(row_to_transition).collect()
+    }
+
+    /// Issue a receipt for funds moving in `tx_hash` and link it to the
+    /// transition; failures are logged since the funds have already moved
+    async fn issue_receipt(&self, escrow: &P2PEscrow, transition_id: Uuid, tx_hash: &str, sender: &str, recipient: &str) {
+        let Some(receipt_service) = &self.receipt_service else {
+            return;
+        };
+
+        let receipt = match receipt_service
+            .generate_payment_receipt(
+                transition_id,
+                escrow.amount,
+                escrow.asset.clone(),
+                sender.to_string(),
+                recipient.to_string(),
+                self.custodian.blockchain(),
+                None,
+                None,
+            )
+            .await
+        {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                error!("Failed to issue receipt for escrow {} transaction {}: {}", escrow.id, tx_hash, e);
+                return;
+            }
+        };
+
+        let result = match self.client().await {
+            Ok(client) => client
+                .execute(
+                    "UPDATE p2p_escrow_transitions SET receipt_id = $2 WHERE id = $1",
+                    &[&transition_id, &receipt.id],
+                )
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            error!("Failed to link receipt {} to escrow {}: {}", receipt.id, escrow.id, e);
+        }
+    }
+
+    async fn fetch_escrow(&self, column: &str, id: Uuid) -> Result<P2PEscrow> {
+        let client = self.client().await?;
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM p2p_escrows WHERE {} = $1 ORDER BY created_at DESC LIMIT 1",
+                    ESCROW_COLUMNS, column
+                ),
+                &[&id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch escrow: {}", e)))?;
+
+        match row {
+            Some(row) => row_to_escrow(&row),
+            None => Err(Error::Validation(format!("Escrow for {} not found", id))),
+        }
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Client> {
+        self.db
+            .get()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))
+    }
+}
+
+/// A stored payout with what the escrow returns to if it fails
+struct PendingPayout {
+    escrow_id: Uuid,
+    from_status: EscrowStatus,
+    reason: TransitionReason,
+    payout: SignedPayout,
+}
+
+fn row_to_pending_payout(row: &tokio_postgres::Row) -> Result<PendingPayout> {
+    Ok(PendingPayout {
+        escrow_id: row.get("escrow_id"),
+        from_status: row.get::<_, String>("from_status").parse()?,
+        reason: row.get::<_, String>("reason").parse()?,
+        payout: SignedPayout {
+            tx_hash: row.get("tx_hash"),
+            asset: row.get("asset"),
+            amount: row.get("amount"),
+            recipient: row.get("recipient"),
+            nonce: row.get("nonce"),
+        },
+    })
+}
+
+/// Lock an escrow row for the rest of `transaction`
+async fn lock_escrow(transaction: &Transaction<'_>, escrow_id: Uuid) -> Result<P2PEscrow> {
+    let row = transaction
+        .query_opt(
+            &format!("SELECT {} FROM p2p_escrows WHERE id = $1 FOR UPDATE", ESCROW_COLUMNS),
+            &[&escrow_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock escrow: {}", e)))?
+        .ok_or_else(|| Error::Validation(format!("Escrow {} not found", escrow_id)))?;
+
+    row_to_escrow(&row)
+}
+
+/// Hold the escrow of a disputed exchange until the dispute is resolved
+///
+/// Returns `None` when the exchange is not settled through escrow.
+pub(crate) async fn hold_for_dispute(transaction: &Transaction<'_>, exchange_id: Uuid) -> Result<Option<P2PEscrow>> {
+    let row = transaction
+        .query_opt(
+            &format!(
+                "SELECT {} FROM p2p_escrows WHERE exchange_id = $1 FOR UPDATE",
+                ESCROW_COLUMNS
+            ),
+            &[&exchange_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to lock escrow: {}", e)))?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let escrow = row_to_escrow(&row)?;
+    if escrow.status != EscrowStatus::Locked {
+        return Err(Error::Validation(format!(
+            "Escrow cannot be disputed (status: {})",
+            escrow.status
+        )));
+    }
+    set_status(transaction, escrow.id, EscrowStatus::Disputed).await?;
+    record_transition(
+        transaction,
+        escrow.id,
+        Some(escrow.status),
+        EscrowStatus::Disputed,
+        TransitionReason::DisputeOpened,
+        None,
+    )
+    .await?;
+
+    Ok(Some(P2PEscrow {
+        status: EscrowStatus::Disputed,
+        ..escrow
+    }))
+}
+
+/// Reject a transition the state machine does not allow
+fn check_transition(escrow: &P2PEscrow, next: EscrowStatus, reason: TransitionReason) -> Result<()> {
+    if escrow.status.can_transition_to(next) {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "Escrow {} cannot move from {} to {} ({})",
+            escrow.id,
+            escrow.status,
+            next,
+            reason.as_str()
+        )))
+    }
+}
+
+/// Close an escrow and the exchange and offer it settles, returning the
+/// closed escrow and the final transition
+async fn finish(
+    transaction: &Transaction<'_>,
+    escrow: &P2PEscrow,
+    status: EscrowStatus,
+    reason: TransitionReason,
+    tx_hash: Option<&str>,
+) -> Result<(P2PEscrow, Uuid)> {
+    check_transition(escrow, status, reason)?;
+
+    let row = transaction
+        .query_one(
+            &format!(
+                "UPDATE p2p_escrows
+                 SET status = $2, settlement_tx_hash = $3, updated_at = NOW()
+                 WHERE id = $1
+                 RETURNING {}",
+                ESCROW_COLUMNS
+            ),
+            &[&escrow.id, &status.as_str(), &tx_hash],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update escrow: {}", e)))?;
+    let transition_id =
+        record_transition(transaction, escrow.id, Some(escrow.status), status, reason, tx_hash).await?;
+
+    let (exchange_status, offer_status) = match status {
+        EscrowStatus::Released => ("COMPLETED", "EXECUTED"),
+        EscrowStatus::Refunded => ("REFUNDED", "CANCELLED"),
+        _ => ("CANCELLED", "CANCELLED"),
+    };
+    if let Some(exchange_id) = escrow.exchange_id {
+        transaction
+            .execute(
+                "UPDATE p2p_exchanges
+                 SET status = $2, transaction_hash = COALESCE($3, transaction_hash)
+                 WHERE id = $1",
+                &[&exchange_id, &exchange_status, &tx_hash],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update exchange: {}", e)))?;
+        // Close the matched offers once none of their fills is still in escrow
+        transaction
+            .execute(
+                "UPDATE p2p_offers o
+                 SET status = $2
+                 FROM p2p_exchanges e
+                 WHERE e.id = $1
+                   AND o.id IN (e.buyer_offer_id, e.seller_offer_id)
+                   AND o.status = 'MATCHED'
+                   AND NOT EXISTS (
+                       SELECT 1 FROM p2p_escrows es
+                       JOIN p2p_exchanges other ON other.id = es.exchange_id
+                       WHERE o.id IN (other.buyer_offer_id, other.seller_offer_id)
+                         AND es.status IN ('AWAITING_DEPOSIT', 'LOCKED', 'DISPUTED', 'RELEASING', 'REFUNDING')
+                   )",
+                &[&exchange_id, &offer_status],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update offer: {}", e)))?;
+    }
+
+    Ok((row_to_escrow(&row)?, transition_id))
+}
+
+async fn set_status(transaction: &Transaction<'_>, escrow_id: Uuid, status: EscrowStatus) -> Result<()> {
+    transaction
+        .execute(
+            "UPDATE p2p_escrows SET status = $2, updated_at = NOW() WHERE id = $1",
+            &[&escrow_id, &status.as_str()],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update escrow: {}", e)))?;
+    Ok(())
+}
+
+async fn record_transition(
+    transaction: &Transaction<'_>,
+    escrow_id: Uuid,
+    from_status: Option<EscrowStatus>,
+    to_status: EscrowStatus,
+    reason: TransitionReason,
+    tx_hash: Option<&str>,
+) -> Result<Uuid> {
+    let id = Uuid::new_v4();
+    transaction
+        .execute(
+            "INSERT INTO p2p_escrow_transitions (id, escrow_id, from_status, to_status, reason, tx_hash)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                &id,
+                &escrow_id,
+                &from_status.map(|s| s.as_str()),
+                &to_status.as_str(),
+                &reason.as_str(),
+                &tx_hash,
+            ],
+        )
+        .await
+        .map_err(|e| match e.as_db_error().and_then(|db| db.constraint()) {
+            Some("idx_p2p_escrow_transitions_lock_tx") => Error::Validation(format!(
+                "Deposit {} is already used by another escrow",
+                tx_hash.unwrap_or_default()
+            )),
+            _ => Error::Database(format!("Failed to record escrow transition: {}", e)),
+        })?;
+    Ok(id)
+}
+
+/// Record `deposit` as consumed by `escrow_id`, so its transaction cannot
+/// fund another escrow
+async fn consume_deposit(transaction: &Transaction<'_>, escrow_id: Uuid, deposit: &EscrowDeposit) -> Result<()> {
+    transaction
+        .execute(
+            "INSERT INTO p2p_escrow_deposits (tx_hash, escrow_id, depositor_address) VALUES ($1, $2, $3)",
+            &[&deposit.tx_hash, &escrow_id, &deposit.depositor],
+        )
+        .await
+        .map_err(|e| match e.as_db_error().and_then(|db| db.constraint()) {
+            Some("p2p_escrow_deposits_pkey") => Error::Validation(format!(
+                "Deposit {} is already used by another escrow",
+                deposit.tx_hash
+            )),
+            _ => Error::Database(format!("Failed to record escrow deposit: {}", e)),
+        })?;
+    Ok(())
+}
+
+/// Solana wallet `user_id` receives escrow releases at
+async fn wallet_address(transaction: &Transaction<'_>, user_id: Uuid) -> Result<String> {
+    transaction
+        .query_opt(
+            "SELECT address FROM wallets WHERE user_id = $1 ORDER BY connected_at LIMIT 1",
+            &[&user_id],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to fetch wallet: {}", e)))?
+        .map(|row| row.get("address"))
+        .ok_or_else(|| Error::WalletNotFound(format!("No wallet connected for user {}", user_id)))
+}
+
+async fn commit(transaction: Transaction<'_>) -> Result<()> {
+    transaction
+        .commit()
+        .await
+        .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+}
+
+fn row_to_escrow(row: &tokio_postgres::Row) -> Result<P2PEscrow> {
+    let get_err = |column: &str, e: tokio_postgres::Error| {
+        Error::Database(format!("Failed to get {}: {}", column, e))
+    };
+    let status: String = row.try_get("status").map_err(|e| get_err("status", e))?;
+
+    Ok(P2PEscrow {
+        id: row.try_get("id").map_err(|e| get_err("id", e))?,
+        offer_id: row.try_get("offer_id").map_err(|e| get_err("offer_id", e))?,
+        exchange_id: row.try_get("exchange_id").map_err(|e| get_err("exchange_id", e))?,
+        seller_user_id: row.try_get("seller_user_id").map_err(|e| get_err("seller_user_id", e))?,
+        buyer_user_id: row.try_get("buyer_user_id").map_err(|e| get_err("buyer_user_id", e))?,
+        asset: row.try_get("asset").map_err(|e| get_err("asset", e))?,
+        amount: row.try_get("amount").map_err(|e| get_err("amount", e))?,
+        status: status.parse()?,
+        depositor_address: row
+            .try_get("depositor_address")
+            .map_err(|e| get_err("depositor_address", e))?,
+        lock_tx_hash: row.try_get("lock_tx_hash").map_err(|e| get_err("lock_tx_hash", e))?,
+        settlement_tx_hash: row
+            .try_get("settlement_tx_hash")
+            .map_err(|e| get_err("settlement_tx_hash", e))?,
+        seller_confirmed_at: row
+            .try_get("seller_confirmed_at")
+            .map_err(|e| get_err("seller_confirmed_at", e))?,
+        buyer_confirmed_at: row
+            .try_get("buyer_confirmed_at")
+            .map_err(|e| get_err("buyer_confirmed_at", e))?,
+        timeout_at: row.try_get("timeout_at").map_err(|e| get_err("timeout_at", e))?,
+        created_at: row.try_get("created_at").map_err(|e| get_err("created_at", e))?,
+        updated_at: row.try_get("updated_at").map_err(|e| get_err("updated_at", e))?,
+    })
+}
+
+fn row_to_transition(row: &tokio_postgres::Row) -> Result<EscrowTransition> {
+    let get_err = |column: &str, e: tokio_postgres::Error| {
+        Error::Database(format!("Failed to get {}: {}", column, e))
+    };
+    let from_status: Option<String> = row.try_get("from_status").map_err(|e| get_err("from_status", e))?;
+    let to_status: String = row.try_get("to_status").map_err(|e| get_err("to_status", e))?;
+
+    Ok(EscrowTransition {
+        id: row.try_get("id").map_err(|e| get_err("id", e))?,
+        escrow_id: row.try_get("escrow_id").map_err(|e| get_err("escrow_id", e))?,
+        from_status: from_status.map(|s| s.parse()).transpose()?,
+        to_status: to_status.parse()?,
+        reason: row.try_get("reason").map_err(|e| get_err("reason", e))?,
+        tx_hash: row.try_get("tx_hash").map_err(|e| get_err("tx_hash", e))?,
+        receipt_id: row.try_get("receipt_id").map_err(|e| get_err("receipt_id", e))?,
+        created_at: row.try_get("created_at").map_err(|e| get_err("created_at", e))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escrow_status_round_trip() {
+        for status in [
+            EscrowStatus::AwaitingDeposit,
+            EscrowStatus::Locked,
+            EscrowStatus::Disputed,
+            EscrowStatus::Releasing,
+            EscrowStatus::Released,
+            EscrowStatus::Refunding,
+            EscrowStatus::Refunded,
+            EscrowStatus::Cancelled,
+        ] {
+            assert_eq!(status.as_str().parse::<EscrowStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status.as_str())
+            );
+        }
+        assert!("OPEN".parse::<EscrowStatus>().is_err());
+    }
+
+    #[test]
+    fn test_escrow_transitions() {
+        use EscrowStatus::*;
+
+        assert!(AwaitingDeposit.can_transition_to(Locked));
+        assert!(AwaitingDeposit.can_transition_to(Cancelled));
+        assert!(Locked.can_transition_to(Releasing));
+        assert!(Releasing.can_transition_to(Released));
+        assert!(Locked.can_transition_to(Refunding));
+        assert!(Refunding.can_transition_to(Refunded));
+        assert!(Releasing.can_transition_to(Locked));
+        assert!(Locked.can_transition_to(Disputed));
+        assert!(Disputed.can_transition_to(Releasing));
+        assert!(Disputed.can_transition_to(Refunding));
+        assert!(Refunding.can_transition_to(Disputed));
+
+        // Funds only move through the two-phase payout states
+        assert!(!Locked.can_transition_to(Released));
+        assert!(!AwaitingDeposit.can_transition_to(Releasing));
+        assert!(!Locked.can_transition_to(Cancelled));
+        assert!(!Released.can_transition_to(Refunding));
+        assert!(!Refunded.can_transition_to(Locked));
+        assert!(!Releasing.can_transition_to(Refunded));
+        assert!(!AwaitingDeposit.can_transition_to(Disputed));
+        assert!(!Disputed.can_transition_to(Locked));
+        assert!(!Disputed.can_transition_to(Released));
+    }
+
+    #[test]
+    fn test_sol_to_lamports() {
+        assert_eq!(sol_to_lamports(Decimal::new(15, 1)).unwrap(), 1_500_000_000);
+        assert_eq!(sol_to_lamports(Decimal::new(1, 9)).unwrap(), 1);
+
+        assert!(sol_to_lamports(Decimal::ZERO).is_err());
+        assert!(sol_to_lamports(Decimal::new(-1, 0)).is_err());
+        assert!(sol_to_lamports(Decimal::new(1, 10)).is_err());
+    }
+}
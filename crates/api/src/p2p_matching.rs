@@ -0,0 +1,450 @@
+use chrono::{DateTime, Utc};
+use database::DbPool;
+use proximity::{DiscoveredPeer, DiscoveryService, DiscoverySession, SessionManager};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::p2p_escrow::EscrowService;
+use crate::p2p_service::{OfferStatus, OfferType, P2POffer};
+
+const OFFER_COLUMNS: &str = "id, user_id, offer_type, from_asset, to_asset, from_amount, to_amount, \
+     price, status, escrow_tx_hash, matched_with_offer_id, is_proximity_offer, acceptor_id, \
+     accepted_at, conversation_id, remaining_amount, created_at, expires_at";
+
+/// One crossing of a BUY and a SELL offer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct P2PFill {
+    /// Exchange recording the fill
+    pub exchange_id: Uuid,
+    pub buy_offer_id: Uuid,
+    pub sell_offer_id: Uuid,
+    pub buyer_user_id: Uuid,
+    pub seller_user_id: Uuid,
+    /// Asset changing hands
+    pub asset: String,
+    /// Asset the buyer pays in
+    pub quote_asset: String,
+    pub amount: Decimal,
+    /// Price of the earlier offer, in `quote_asset` per `asset`
+    pub price: Decimal,
+    pub quote_amount: Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Crosses compatible BUY and SELL offers
+///
+/// Offers match when one sells what the other buys for the same quote asset
+/// and the buy price is at least the sell price. Counter offers are taken
+/// best price first, then oldest first, and trade at the price of whichever
+/// offer was posted earlier. An offer can be filled by several counter
+/// offers; each fill reduces `remaining_amount` on both sides, creates a
+/// `p2p_exchanges` row and runs in its own transaction.
+pub struct MatchingEngine {
+    db: DbPool,
+    escrow: Option<Arc<EscrowService>>,
+    proximity_only: bool,
+    proximity: Option<(Arc<SessionManager>, Arc<DiscoveryService>)>,
+}
+
+impl MatchingEngine {
+    pub fn new(db: DbPool) -> Self {
+        Self {
+            db,
+            escrow: None,
+            proximity_only: false,
+            proximity: None,
+        }
+    }
+
+    /// Escrow the seller's side of every fill
+    pub fn with_escrow(mut self, escrow: Arc<EscrowService>) -> Self {
+        self.escrow = Some(escrow);
+        self
+    }
+
+    /// Only let proximity offers trade with nearby peers
+    ///
+    /// When enabled, a fill involving a proximity offer needs the counter
+    /// offer's creator to be among the nearby wallets passed to
+    /// `match_offer`, which are those of [`Self::nearby_wallets`].
+    pub fn with_proximity_only(mut self, proximity_only: bool) -> Self {
+        self.proximity_only = proximity_only;
+        self
+    }
+
+    /// Find the peers near a user through their discovery sessions
+    pub fn with_proximity(mut self, sessions: Arc<SessionManager>, discovery: Arc<DiscoveryService>) -> Self {
+        self.proximity = Some((sessions, discovery));
+        self
+    }
+
+    /// Wallets of the peers `user_id`'s active discovery sessions found;
+    /// none without a session
+    pub async fn nearby_wallets(&self, user_id: Uuid) -> Vec<String> {
+        let Some((sessions, discovery)) = &self.proximity else {
+            return Vec::new();
+        };
+
+        let sessions = match sessions.get_user_sessions(user_id).await {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                warn!("Failed to get discovery sessions of user {}: {}", user_id, e);
+                return Vec::new();
+            }
+        };
+        if sessions.is_empty() {
+            return Vec::new();
+        }
+        match discovery.get_discovered_peers().await {
+            Ok(peers) => session_peer_wallets(&sessions, &peers, Utc::now()),
+            Err(e) => {
+                warn!("Failed to get discovered peers for user {}: {}", user_id, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Fill `offer_id` against the order book until it is fully filled or
+    /// no counter offer crosses
+    ///
+    /// `nearby_wallets` are wallet addresses of peers discovered through
+    /// the proximity network.
+    pub async fn match_offer(&self, offer_id: Uuid, nearby_wallets: &[String]) -> Result<Vec<P2PFill>> {
+        let mut fills = Vec::new();
+
+        while let Some(fill) = self.fill_once(offer_id, nearby_wallets).await? {
+            info!(
+                exchange_id = %fill.exchange_id,
+                buy_offer_id = %fill.buy_offer_id,
+                sell_offer_id = %fill.sell_offer_id,
+                amount = %fill.amount,
+                asset = %fill.asset,
+                price = %fill.price,
+                "Filled P2P offers"
+            );
+            fills.push(fill);
+        }
+
+        Ok(fills)
+    }
+
+    /// Match active offers, oldest first, returning every fill made
+    ///
+    /// Each offer trades with the peers near its creator.
+    pub async fn run_matching(&self, limit: i64) -> Result<Vec<P2PFill>> {
+        let client = self.client().await?;
+        let rows = client
+            .query(
+                "SELECT id, user_id FROM p2p_offers
+                 WHERE status = 'ACTIVE' AND expires_at > NOW() AND remaining_amount > 0
+                 ORDER BY created_at, id
+                 LIMIT $1",
+                &[&limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch active offers: {}", e)))?;
+        drop(client);
+
+        let mut fills = Vec::new();
+        for row in rows {
+            let offer_id: Uuid = row.get("id");
+            let nearby_wallets = self.nearby_wallets(row.get("user_id")).await;
+            match self.match_offer(offer_id, &nearby_wallets).await {
+                Ok(offer_fills) => fills.extend(offer_fills),
+                Err(e) => warn!("Failed to match P2P offer {}: {}", offer_id, e),
+            }
+        }
+
+        Ok(fills)
+    }
+
+    /// Make the best available fill for `offer_id`, if any
+    async fn fill_once(&self, offer_id: Uuid, nearby_wallets: &[String]) -> Result<Option<P2PFill>> {
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        let taker = transaction
+            .query_opt(
+                &format!("SELECT {} FROM p2p_offers WHERE id = $1 FOR UPDATE", OFFER_COLUMNS),
+                &[&offer_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch offer: {}", e)))?
+            .ok_or_else(|| Error::Validation(format!("Offer {} not found", offer_id)))?;
+        let taker = crate::p2p_service::row_to_offer(&taker)?;
+
+        if taker.status != OfferStatus::Active
+            || taker.expires_at <= Utc::now()
+            || taker.remaining_amount <= Decimal::ZERO
+        {
+            return Ok(None);
+        }
+
+        // Best price first: the cheapest seller for a buyer, the highest
+        // bidder for a seller. Rows locked by a concurrent fill are skipped.
+        let (counter_type, price_condition, price_order) = match taker.offer_type {
+            OfferType::Buy => (OfferType::Sell, "price <= $4", "price ASC"),
+            OfferType::Sell => (OfferType::Buy, "price >= $4", "price DESC"),
+        };
+        let maker = transaction
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM p2p_offers
+                     WHERE status = 'ACTIVE'
+                       AND expires_at > NOW()
+                       AND remaining_amount > 0
+                       AND offer_type = $1
+                       AND from_asset = $2
+                       AND to_asset = $3
+                       AND {}
+                       AND user_id <> $5
+                       AND (
+                           NOT $6
+                           OR NOT ($7 OR is_proximity_offer)
+                           OR user_id IN (SELECT user_id FROM wallets WHERE address = ANY($8))
+                       )
+                     ORDER BY {}, created_at, id
+                     LIMIT 1
+                     FOR UPDATE SKIP LOCKED",
+                    OFFER_COLUMNS, price_condition, price_order
+                ),
+                &[
+                    &counter_type.as_str(),
+                    &taker.to_asset,
+                    &taker.from_asset,
+                    &taker.price,
+                    &taker.user_id,
+                    &self.proximity_only,
+                    &taker.is_proximity_offer,
+                    &nearby_wallets,
+                ],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to find counter offer: {}", e)))?;
+        let Some(maker) = maker else {
+            return Ok(None);
+        };
+        let maker = crate::p2p_service::row_to_offer(&maker)?;
+
+        let (buy, sell) = match taker.offer_type {
+            OfferType::Buy => (&taker, &maker),
+            OfferType::Sell => (&maker, &taker),
+        };
+        let amount = fill_amount(&taker, &maker);
+        let price = fill_price(&taker, &maker);
+        let exchange_id = Uuid::new_v4();
+
+        transaction
+            .execute(
+                r#"
+                INSERT INTO p2p_exchanges (
+                    id, buyer_offer_id, seller_offer_id, buyer_user_id, seller_user_id,
+                    asset, amount, price, platform_fee, status, executed_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 0, 'PENDING', NOW())
+                "#,
+                &[
+                    &exchange_id,
+                    &buy.id,
+                    &sell.id,
+                    &buy.user_id,
+                    &sell.user_id,
+                    &sell.base_asset(),
+                    &amount,
+                    &price,
+                ],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create exchange record: {}", e)))?;
+
+        for (offer, counter) in [(&taker, &maker), (&maker, &taker)] {
+            transaction
+                .execute(
+                    r#"
+                    UPDATE p2p_offers
+                    SET remaining_amount = remaining_amount - $2,
+                        matched_with_offer_id = $3,
+                        status = CASE WHEN remaining_amount - $2 <= 0 THEN 'MATCHED' ELSE status END
+                    WHERE id = $1
+                    "#,
+                    &[&offer.id, &amount, &counter.id],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to update offer: {}", e)))?;
+        }
+
+        if let Some(escrow) = &self.escrow {
+            escrow
+                .open_for_exchange(&transaction, sell, exchange_id, sell.user_id, buy.user_id, amount)
+                .await?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        Ok(Some(P2PFill {
+            exchange_id,
+            buy_offer_id: buy.id,
+            sell_offer_id: sell.id,
+            buyer_user_id: buy.user_id,
+            seller_user_id: sell.user_id,
+            asset: sell.base_asset().to_string(),
+            quote_asset: sell.quote_asset().to_string(),
+            amount,
+            price,
+            quote_amount: amount * price,
+            executed_at: Utc::now(),
+        }))
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Client> {
+        self.db
+            .get()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get database connection: {}", e)))
+    }
+}
+
+/// Wallets of the peers seen, by the same discovery method, during one of
+/// `sessions` that has not expired at `now`
+pub fn session_peer_wallets(
+    sessions: &[DiscoverySession],
+    peers: &[DiscoveredPeer],
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    peers
+        .iter()
+        .filter(|peer| {
+            sessions.iter().any(|session| {
+                session.expires_at > now
+                    && session.discovery_method == peer.discovery_method
+                    && peer.last_seen >= session.started_at
+            })
+        })
+        .map(|peer| peer.wallet_address.clone())
+        .collect()
+}
+
+/// Amount of the base asset two crossing offers can trade
+pub fn fill_amount(taker: &P2POffer, maker: &P2POffer) -> Decimal {
+    taker.remaining_amount.min(maker.remaining_amount)
+}
+
+/// Price two crossing offers trade at: that of the offer posted first
+pub fn fill_price(a: &P2POffer, b: &P2POffer) -> Decimal {
+    if (b.created_at, b.id) < (a.created_at, a.id) {
+        b.price
+    } else {
+        a.price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proximity::DiscoveryMethod;
+
+    fn offer(offer_type: OfferType, price: i64, remaining: i64, created_secs_ago: i64) -> P2POffer {
+        let created_at = Utc::now() - chrono::Duration::seconds(created_secs_ago);
+        let (from_asset, to_asset) = match offer_type {
+            OfferType::Sell => ("SOL", "USDC"),
+            OfferType::Buy => ("USDC", "SOL"),
+        };
+
+        P2POffer {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            offer_type,
+            from_asset: from_asset.to_string(),
+            to_asset: to_asset.to_string(),
+            from_amount: Decimal::new(remaining, 0),
+            to_amount: Decimal::new(remaining * price, 0),
+            price: Decimal::new(price, 0),
+            status: OfferStatus::Active,
+            escrow_tx_hash: None,
+            matched_with_offer_id: None,
+            is_proximity_offer: false,
+            acceptor_id: None,
+            accepted_at: None,
+            conversation_id: None,
+            remaining_amount: Decimal::new(remaining, 0),
+            creator_reputation: None,
+            created_at,
+            expires_at: created_at + chrono::Duration::hours(24),
+        }
+    }
+
+    #[test]
+    fn test_fill_amount_is_smaller_remainder() {
+        let buy = offer(OfferType::Buy, 100, 5, 10);
+        let sell = offer(OfferType::Sell, 95, 3, 20);
+
+        assert_eq!(fill_amount(&buy, &sell), Decimal::new(3, 0));
+        assert_eq!(fill_amount(&sell, &buy), Decimal::new(3, 0));
+    }
+
+    #[test]
+    fn test_fill_price_is_earlier_offer() {
+        let resting_sell = offer(OfferType::Sell, 95, 3, 20);
+        let incoming_buy = offer(OfferType::Buy, 100, 5, 10);
+
+        assert_eq!(fill_price(&incoming_buy, &resting_sell), Decimal::new(95, 0));
+        assert_eq!(fill_price(&resting_sell, &incoming_buy), Decimal::new(95, 0));
+
+        let resting_buy = offer(OfferType::Buy, 100, 5, 30);
+        assert_eq!(fill_price(&resting_sell, &resting_buy), Decimal::new(100, 0));
+    }
+
+    #[test]
+    fn test_base_and_quote_assets() {
+        let buy = offer(OfferType::Buy, 100, 5, 0);
+        let sell = offer(OfferType::Sell, 100, 5, 0);
+
+        assert_eq!((buy.base_asset(), buy.quote_asset()), ("SOL", "USDC"));
+        assert_eq!((sell.base_asset(), sell.quote_asset()), ("SOL", "USDC"));
+    }
+
+    #[test]
+    fn test_nearby_wallets_come_from_active_sessions() {
+        let now = Utc::now();
+        let session = DiscoverySession {
+            session_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            discovery_method: DiscoveryMethod::Bluetooth,
+            started_at: now - chrono::Duration::minutes(10),
+            expires_at: now + chrono::Duration::minutes(20),
+            auto_extend: false,
+        };
+        let peer = |wallet: &str, discovery_method, last_seen_mins_ago| DiscoveredPeer {
+            peer_id: wallet.to_string(),
+            user_tag: wallet.to_string(),
+            wallet_address: wallet.to_string(),
+            discovery_method,
+            signal_strength: None,
+            verified: true,
+            discovered_at: now - chrono::Duration::minutes(last_seen_mins_ago),
+            last_seen: now - chrono::Duration::minutes(last_seen_mins_ago),
+        };
+        let peers = vec![
+            peer("nearby", DiscoveryMethod::Bluetooth, 1),
+            peer("before-session", DiscoveryMethod::Bluetooth, 30),
+            peer("other-method", DiscoveryMethod::WiFi, 1),
+        ];
+
+        let wallets = session_peer_wallets(std::slice::from_ref(&session), &peers, now);
+        assert_eq!(wallets, vec!["nearby".to_string()]);
+
+        // Nothing is near a user without a session or after it expires
+        assert!(session_peer_wallets(&[], &peers, now).is_empty());
+        assert!(session_peer_wallets(&[session], &peers, now + chrono::Duration::hours(1)).is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+use chrono::{DateTime, Utc};
+use database::DbPool;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::collections::HashMap;
+use tracing::info;
+use uuid::Uuid;
+
+/// Longest comment accepted with a rating
+pub const MAX_RATING_COMMENT_LEN: usize = 500;
+
+/// Rating assumed for users with few ratings, and how many ratings it counts as
+const PRIOR_RATING: f64 = 3.0;
+const PRIOR_RATING_WEIGHT: f64 = 2.0;
+
+/// How much more a lost dispute weighs than a cancelled exchange
+const LOST_DISPUTE_WEIGHT: f64 = 3.0;
+
+/// A participant's rating of their counterparty after an exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct P2PRating {
+    pub id: Uuid,
+    pub exchange_id: Uuid,
+    pub rater_id: Uuid,
+    pub ratee_id: Uuid,
+    pub score: i16,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Trading record of a P2P counterparty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserReputation {
+    pub user_id: Uuid,
+    /// Exchanges settled without a dispute
+    pub completed_exchanges: i64,
+    /// Exchanges that fell through without a dispute by this user's fault:
+    /// as seller never depositing, or as buyer never confirming payment
+    pub cancelled_exchanges: i64,
+    /// Exchanges that went to dispute, whatever the outcome
+    pub disputed_exchanges: i64,
+    /// Disputes the arbitrator decided against this user
+    pub disputes_lost: i64,
+    pub rating_count: i64,
+    pub average_rating: Option<f64>,
+    /// 0-100, see [`reputation_score`]
+    pub score: f64,
+}
+
+impl UserReputation {
+    /// Reputation of a user without any exchanges or ratings
+    pub fn new(user_id: Uuid) -> Self {
+        let mut reputation = Self {
+            user_id,
+            completed_exchanges: 0,
+            cancelled_exchanges: 0,
+            disputed_exchanges: 0,
+            disputes_lost: 0,
+            rating_count: 0,
+            average_rating: None,
+            score: 0.0,
+        };
+        reputation.score = reputation_score(&reputation);
+        reputation
+    }
+}
+
+/// Score a trading record from 0 to 100
+///
+/// 70% reliability, the share of exchanges that completed (with one
+/// completed and one failed exchange assumed, and lost disputes counting
+/// triple), and 30% average rating (pulled towards 3 stars while there are
+/// few ratings). Disputes that were decided in the user's favour do not
+/// count against them.
+pub fn reputation_score(reputation: &UserReputation) -> f64 {
+    let completed = reputation.completed_exchanges as f64;
+    let failed = reputation.cancelled_exchanges as f64 + LOST_DISPUTE_WEIGHT * reputation.disputes_lost as f64;
+    let reliability = (completed + 1.0) / (completed + failed + 2.0);
+
+    let count = reputation.rating_count as f64;
+    let rating_sum = reputation.average_rating.unwrap_or(0.0) * count;
+    let rating = (rating_sum + PRIOR_RATING * PRIOR_RATING_WEIGHT) / (count + PRIOR_RATING_WEIGHT) / 5.0;
+
+    ((0.7 * reliability + 0.3 * rating) * 1000.0).round() / 10.0
+}
+
+/// Post-trade ratings and the reputation derived from them and from
+/// exchange outcomes
+pub struct ReputationService {
+    db: DbPool,
+}
+
+impl ReputationService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    /// Rate the counterparty of a settled exchange
+    ///
+    /// Each participant rates an exchange at most once.
+    pub async fn rate_exchange(
+        &self,
+        exchange_id: Uuid,
+        rater_id: Uuid,
+        score: i16,
+        comment: Option<String>,
+    ) -> Result<P2PRating> {
+        if !(1..=5).contains(&score) {
+            return Err(Error::Validation("Rating must be between 1 and 5".to_string()));
+        }
+        let comment = comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+        if comment.as_ref().is_some_and(|c| c.len() > MAX_RATING_COMMENT_LEN) {
+            return Err(Error::Validation(format!(
+                "Rating comment must be at most {} characters",
+                MAX_RATING_COMMENT_LEN
+            )));
+        }
+
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let exchange = client
+            .query_opt(
+                "SELECT buyer_user_id, seller_user_id, status FROM p2p_exchanges WHERE id = $1",
+                &[&exchange_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch exchange: {}", e)))?
+            .ok_or_else(|| Error::Validation("Exchange not found".to_string()))?;
+
+        let buyer: Uuid = exchange.get("buyer_user_id");
+        let seller: Uuid = exchange.get("seller_user_id");
+        let ratee_id = if rater_id == buyer {
+            seller
+        } else if rater_id == seller {
+            buyer
+        } else {
+            return Err(Error::Unauthorized);
+        };
+
+        let status: String = exchange.get("status");
+        if !matches!(status.as_str(), "COMPLETED" | "REFUNDED" | "CANCELLED") {
+            return Err(Error::Validation(format!(
+                "Exchange cannot be rated until it is settled (status: {})",
+                status
+            )));
+        }
+
+        let row = client
+            .query_one(
+                "INSERT INTO p2p_ratings (id, exchange_id, rater_id, ratee_id, score, comment)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 RETURNING id, exchange_id, rater_id, ratee_id, score, comment, created_at",
+                &[&Uuid::new_v4(), &exchange_id, &rater_id, &ratee_id, &score, &comment],
+            )
+            .await
+            .map_err(|e| {
+                let constraint = e.as_db_error().and_then(|db| db.constraint());
+                if constraint == Some("uq_p2p_ratings_exchange_rater") {
+                    Error::Validation("Exchange has already been rated".to_string())
+                } else {
+                    Error::Database(format!("Failed to record rating: {}", e))
+                }
+            })?;
+
+        info!("User {} rated exchange {} with {}", rater_id, exchange_id, score);
+        Ok(row_to_rating(&row))
+    }
+
+    /// Ratings a user has received, newest first
+    pub async fn get_ratings(&self, user_id: Uuid, limit: i64) -> Result<Vec<P2PRating>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                "SELECT id, exchange_id, rater_id, ratee_id, score, comment, created_at
+                 FROM p2p_ratings
+                 WHERE ratee_id = $1
+                 ORDER BY created_at DESC
+                 LIMIT $2",
+                &[&user_id, &limit],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch ratings: {}", e)))?;
+
+        Ok(rows.iter().map(row_to_rating).collect())
+    }
+
+    pub async fn get_reputation(&self, user_id: Uuid) -> Result<UserReputation> {
+        let mut reputations = fetch_reputations(&self.db, &[user_id]).await?;
+        Ok(reputations
+            .remove(&user_id)
+            .unwrap_or_else(|| UserReputation::new(user_id)))
+    }
+}
+
+/// Reputation of each of `user_ids`, including users without any history
+pub(crate) async fn fetch_reputations(db: &DbPool, user_ids: &[Uuid]) -> Result<HashMap<Uuid, UserReputation>> {
+    let mut reputations: HashMap<Uuid, UserReputation> = user_ids
+        .iter()
+        .map(|&user_id| (user_id, UserReputation::new(user_id)))
+        .collect();
+    if reputations.is_empty() {
+        return Ok(reputations);
+    }
+    let user_ids: Vec<Uuid> = reputations.keys().copied().collect();
+
+    let client = db.get().await.map_err(|e| {
+        Error::Database(format!("Failed to get database connection: {}", e))
+    })?;
+
+    // A failed exchange only counts against the party whose step is missing
+    // from its escrow: a cancelled escrow never received the seller's
+    // deposit, and one refunded on timeout was never confirmed by the buyer
+    // (a buyer confirmation sends it to dispute instead). Exchanges settled
+    // without escrow cannot be attributed and count against neither side.
+    let exchange_rows = client
+        .query(
+            "SELECT u.user_id,
+                    COUNT(*) FILTER (WHERE d.id IS NULL AND e.status = 'COMPLETED') AS completed,
+                    COUNT(*) FILTER (WHERE d.id IS NULL AND (
+                        (es.status = 'CANCELLED' AND es.lock_tx_hash IS NULL
+                            AND e.seller_user_id = u.user_id)
+                        OR (es.status = 'REFUNDED' AND es.buyer_confirmed_at IS NULL
+                            AND e.buyer_user_id = u.user_id)
+                    )) AS cancelled,
+                    COUNT(d.id) AS disputed,
+                    COUNT(*) FILTER (WHERE
+                        (d.outcome = 'RELEASE_TO_BUYER' AND e.seller_user_id = u.user_id)
+                        OR (d.outcome = 'REFUND_TO_SELLER' AND e.buyer_user_id = u.user_id)
+                    ) AS lost
+             FROM UNNEST($1::UUID[]) AS u(user_id)
+             JOIN p2p_exchanges e ON u.user_id IN (e.buyer_user_id, e.seller_user_id)
+             LEFT JOIN p2p_disputes d ON d.exchange_id = e.id
+             LEFT JOIN p2p_escrows es ON es.exchange_id = e.id
+             GROUP BY u.user_id",
+            &[&user_ids],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to fetch exchange history: {}", e)))?;
+
+    for row in exchange_rows {
+        if let Some(reputation) = reputations.get_mut(&row.get::<_, Uuid>("user_id")) {
+            reputation.completed_exchanges = row.get("completed");
+            reputation.cancelled_exchanges = row.get("cancelled");
+            reputation.disputed_exchanges = row.get("disputed");
+            reputation.disputes_lost = row.get("lost");
+        }
+    }
+
+    let rating_rows = client
+        .query(
+            "SELECT ratee_id, COUNT(*) AS count, AVG(score)::FLOAT8 AS average
+             FROM p2p_ratings
+             WHERE ratee_id = ANY($1)
+             GROUP BY ratee_id",
+            &[&user_ids],
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to fetch ratings: {}", e)))?;
+
+    for row in rating_rows {
+        if let Some(reputation) = reputations.get_mut(&row.get::<_, Uuid>("ratee_id")) {
+            reputation.rating_count = row.get("count");
+            reputation.average_rating = row.get("average");
+        }
+    }
+
+    for reputation in reputations.values_mut() {
+        reputation.score = reputation_score(reputation);
+    }
+
+    Ok(reputations)
+}
+
+fn row_to_rating(row: &tokio_postgres::Row) -> P2PRating {
+    P2PRating {
+        id: row.get("id"),
+        exchange_id: row.get("exchange_id"),
+        rater_id: row.get("rater_id"),
+        ratee_id: row.get("ratee_id"),
+        score: row.get("score"),
+        comment: row.get("comment"),
+        created_at: row.get("created_at"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(completed: i64, cancelled: i64, lost: i64, ratings: i64, average: Option<f64>) -> UserReputation {
+        UserReputation {
+            completed_exchanges: completed,
+            cancelled_exchanges: cancelled,
+            disputes_lost: lost,
+            rating_count: ratings,
+            average_rating: average,
+            ..UserReputation::new(Uuid::nil())
+        }
+    }
+
+    #[test]
+    fn test_new_user_score() {
+        // Reliability 0.5, rating 3/5
+        assert_eq!(UserReputation::new(Uuid::nil()).score, 53.0);
+    }
+
+    #[test]
+    fn test_score_rewards_completed_exchanges() {
+        let new = reputation_score(&record(0, 0, 0, 0, None));
+        let some = reputation_score(&record(5, 0, 0, 0, None));
+        let many = reputation_score(&record(50, 0, 0, 0, None));
+
+        assert!(new < some && some < many);
+        assert!(many <= 100.0);
+    }
+
+    #[test]
+    fn test_lost_disputes_weigh_more_than_cancellations() {
+        let cancelled = reputation_score(&record(10, 1, 0, 0, None));
+        let lost = reputation_score(&record(10, 0, 1, 0, None));
+
+        assert!(lost < cancelled);
+    }
+
+    #[test]
+    fn test_few_ratings_are_pulled_towards_prior() {
+        let one_perfect = reputation_score(&record(0, 0, 0, 1, Some(5.0)));
+        let many_perfect = reputation_score(&record(0, 0, 0, 20, Some(5.0)));
+        let one_bad = reputation_score(&record(0, 0, 0, 1, Some(1.0)));
+
+        assert!(one_bad < 53.0 && 53.0 < one_perfect && one_perfect < many_perfect);
+        assert_eq!(reputation_score(&record(1000, 0, 0, 1000, Some(5.0))), 99.9);
+    }
+}
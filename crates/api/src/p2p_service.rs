@@ -3,9 +3,14 @@ use database::DbPool;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use shared::{Error, Result};
-use tracing::info;
+use std::sync::Arc;
+use tracing::{error, info};
 use uuid::Uuid;
 
+use crate::p2p_escrow::{EscrowService, OfferDeposit, P2PEscrow, TransitionReason};
+use crate::p2p_matching::{MatchingEngine, P2PFill};
+use crate::p2p_reputation::{fetch_reputations, UserReputation};
+
 /// P2P offer type
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OfferType {
@@ -62,10 +67,33 @@ pub struct P2POffer {
     pub acceptor_id: Option<Uuid>,
     pub accepted_at: Option<DateTime<Utc>>,
     pub conversation_id: Option<Uuid>,
+    /// Unfilled amount of the traded asset, reduced by partial fills
+    pub remaining_amount: Decimal,
+    /// Trading record of the offer's creator, included in marketplace listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_reputation: Option<UserReputation>,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
 }
 
+impl P2POffer {
+    /// Asset changing hands: what a SELL offer sells and a BUY offer buys
+    pub fn base_asset(&self) -> &str {
+        match self.offer_type {
+            OfferType::Sell => &self.from_asset,
+            OfferType::Buy => &self.to_asset,
+        }
+    }
+
+    /// Asset the base asset is paid for in; `price` is quoted in it
+    pub fn quote_asset(&self) -> &str {
+        match self.offer_type {
+            OfferType::Sell => &self.to_asset,
+            OfferType::Buy => &self.from_asset,
+        }
+    }
+}
+
 /// P2P exchange record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct P2PExchange {
@@ -84,26 +112,50 @@ pub struct P2PExchange {
 }
 
 /// P2P exchange service
-/// 
-/// NOTE: This is a minimal stub implementation. Full implementation would include:
-/// - Escrow wallet management
-/// - Atomic swap logic for supported chains
-/// - Background worker for offer matching
-/// - Price-time priority matching algorithm
-/// - Automatic escrow release on failures
+///
+/// With an `EscrowService` attached, the seller's side of every trade is held
+/// in escrow: sell offers are locked when created, matched exchanges settle
+/// once both sides confirm, and cancelled or expired offers are refunded.
+/// With a `MatchingEngine` attached, new offers are filled against the order
+/// book as they are created, and the expiry worker sweeps the book for
+/// crossing offers.
+///
+/// NOTE: Atomic swap logic for supported chains is still missing.
 pub struct P2PService {
     db: DbPool,
+    escrow: Option<Arc<EscrowService>>,
+    matching: Option<Arc<MatchingEngine>>,
 }
 
 impl P2PService {
     pub fn new(db: DbPool) -> Self {
-        info!("Initializing P2P exchange service (stub implementation)");
-        Self { db }
+        info!("Initializing P2P exchange service");
+        Self {
+            db,
+            escrow: None,
+            matching: None,
+        }
+    }
+
+    pub fn with_escrow(mut self, escrow: Arc<EscrowService>) -> Self {
+        self.escrow = Some(escrow);
+        self
+    }
+
+    pub fn with_matching_engine(mut self, matching: Arc<MatchingEngine>) -> Self {
+        self.matching = Some(matching);
+        self
+    }
+
+    /// Escrow service, or a validation error if escrow is not enabled
+    pub fn escrow(&self) -> Result<&Arc<EscrowService>> {
+        self.escrow
+            .as_ref()
+            .ok_or_else(|| Error::Validation("P2P escrow is not enabled".to_string()))
     }
 
     /// Create a new P2P offer
-    /// 
-    /// NOTE: Full implementation would lock assets in escrow
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_offer(
         &self,
         user_id: Uuid,
@@ -114,6 +166,37 @@ impl P2PService {
         to_amount: Decimal,
         price: Decimal,
         is_proximity_offer: bool,
+    ) -> Result<P2POffer> {
+        self.create_offer_with_deposit(
+            user_id,
+            offer_type,
+            from_asset,
+            to_asset,
+            from_amount,
+            to_amount,
+            price,
+            is_proximity_offer,
+            None,
+        )
+        .await
+    }
+
+    /// Create a new P2P offer, locking `escrow_deposit` in escrow
+    ///
+    /// When escrow is enabled, sell offers must be funded: the deposit is
+    /// checked on-chain and the offer and escrow are created together.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_offer_with_deposit(
+        &self,
+        user_id: Uuid,
+        offer_type: OfferType,
+        from_asset: String,
+        to_asset: String,
+        from_amount: Decimal,
+        to_amount: Decimal,
+        price: Decimal,
+        is_proximity_offer: bool,
+        escrow_deposit: Option<OfferDeposit>,
     ) -> Result<P2POffer> {
         info!(
             "Creating P2P {} offer for user {}: {} {} -> {} {} (proximity: {})",
@@ -126,24 +209,54 @@ impl P2PService {
             is_proximity_offer
         );
 
+        let deposit = match (&self.escrow, &offer_type, escrow_deposit) {
+            (Some(escrow), OfferType::Sell, Some(funding)) => {
+                let deposit = escrow
+                    .verify_deposit(user_id, funding.escrow_id, &funding.tx_hash, &from_asset, from_amount)
+                    .await?;
+                Some((funding.escrow_id, deposit))
+            }
+            (Some(_), OfferType::Sell, None) => {
+                return Err(Error::Validation(
+                    "Sell offers must be funded with an escrow deposit".to_string(),
+                ));
+            }
+            (_, _, Some(_)) => {
+                return Err(Error::Validation(
+                    "Only sell offers can be funded at creation".to_string(),
+                ));
+            }
+            (_, _, None) => None,
+        };
+        let escrow_tx_hash = deposit.as_ref().map(|(_, d)| d.tx_hash.clone());
+        let remaining_amount = match offer_type {
+            OfferType::Sell => from_amount,
+            OfferType::Buy => to_amount,
+        };
+
         let id = Uuid::new_v4();
 
-        let client = self.db.get().await.map_err(|e| {
+        let mut client = self.db.get().await.map_err(|e| {
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
 
-        let row = client
+        let row = transaction
             .query_one(
                 r#"
                 INSERT INTO p2p_offers (
-                    id, user_id, offer_type, from_asset, to_asset,
-                    from_amount, to_amount, price, status, is_proximity_offer, created_at, expires_at
+                    id, user_id, offer_type, from_asset, to_asset, from_amount, to_amount,
+                    price, status, escrow_tx_hash, is_proximity_offer, remaining_amount,
+                    created_at, expires_at
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'ACTIVE', $9, NOW(), NOW() + INTERVAL '24 hours')
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'ACTIVE', $9, $10, $11, NOW(), NOW() + INTERVAL '24 hours')
                 RETURNING id, user_id, offer_type, from_asset, to_asset,
                           from_amount, to_amount, price, status, escrow_tx_hash,
                           matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                          conversation_id, created_at, expires_at
+                          conversation_id, remaining_amount, created_at, expires_at
                 "#,
                 &[
                     &id,
@@ -154,13 +267,43 @@ impl P2PService {
                     &from_amount,
                     &to_amount,
                     &price,
+                    &escrow_tx_hash,
                     &is_proximity_offer,
+                    &remaining_amount,
                 ],
             )
             .await
             .map_err(|e| Error::Database(format!("Failed to create offer: {}", e)))?;
+        let offer = row_to_offer(&row)?;
+
+        let locked = match (&self.escrow, &deposit) {
+            (Some(escrow), Some((escrow_id, deposit))) => {
+                Some(escrow.lock_for_offer(&transaction, *escrow_id, &offer, deposit).await?)
+            }
+            _ => None,
+        };
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        if let (Some(escrow), Some((locked, transition_id))) = (&self.escrow, locked) {
+            info!("Offer {} locked in escrow {}", offer.id, locked.id);
+            escrow.issue_deposit_receipt(&locked, transition_id).await;
+        }
 
-        self.row_to_offer(&row)
+        // Fill the new offer against the book; it stays listed for whatever is left
+        if let Some(matching) = &self.matching {
+            let nearby_wallets = matching.nearby_wallets(offer.user_id).await;
+            match matching.match_offer(offer.id, &nearby_wallets).await {
+                Ok(fills) if !fills.is_empty() => return self.get_offer(offer.id).await,
+                Ok(_) => {}
+                Err(e) => error!("Failed to match new offer {}: {}", offer.id, e),
+            }
+        }
+
+        Ok(offer)
     }
 
     /// Get active offers
@@ -180,7 +323,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE status = 'ACTIVE'
                   AND expires_at > NOW()
@@ -194,10 +337,10 @@ impl P2PService {
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch offers: {}", e)))?;
 
-        rows.iter().map(|row| self.row_to_offer(row)).collect()
+        rows.iter().map(row_to_offer).collect()
     }
     /// Get all active offers excluding the specified user's offers
-    /// Used for marketplace view
+    /// Used for marketplace view; each offer carries its creator's reputation
     pub async fn get_marketplace_offers(
         &self,
         exclude_user_id: Uuid,
@@ -215,7 +358,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE status = 'ACTIVE'
                   AND expires_at > NOW()
@@ -229,13 +372,16 @@ impl P2PService {
             )
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch marketplace offers: {}", e)))?;
+        drop(client);
 
-        rows.iter().map(|row| self.row_to_offer(row)).collect()
+        let offers = rows.iter().map(row_to_offer).collect::<Result<Vec<_>>>()?;
+        self.with_creator_reputation(offers).await
     }
 
 
 
-    /// Get active offers for discovered proximity peers
+    /// Get active offers for discovered proximity peers, with each creator's
+    /// reputation
     pub async fn get_proximity_offers(
         &self,
         discovered_peer_ids: Vec<Uuid>,
@@ -253,7 +399,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE status = 'ACTIVE'
                   AND expires_at > NOW()
@@ -268,8 +414,21 @@ impl P2PService {
             )
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch proximity offers: {}", e)))?;
+        drop(client);
 
-        rows.iter().map(|row| self.row_to_offer(row)).collect()
+        let offers = rows.iter().map(row_to_offer).collect::<Result<Vec<_>>>()?;
+        self.with_creator_reputation(offers).await
+    }
+
+    /// Attach each creator's reputation so buyers can judge counterparties
+    async fn with_creator_reputation(&self, mut offers: Vec<P2POffer>) -> Result<Vec<P2POffer>> {
+        let user_ids: Vec<Uuid> = offers.iter().map(|o| o.user_id).collect();
+        let reputations = fetch_reputations(&self.db, &user_ids).await?;
+
+        for offer in &mut offers {
+            offer.creator_reputation = reputations.get(&offer.user_id).cloned();
+        }
+        Ok(offers)
     }
 
     /// Get active offers with proximity priority
@@ -291,7 +450,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE status = 'ACTIVE'
                   AND expires_at > NOW()
@@ -310,10 +469,10 @@ impl P2PService {
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch offers with proximity priority: {}", e)))?;
 
-        rows.iter().map(|row| self.row_to_offer(row)).collect()
+        rows.iter().map(row_to_offer).collect()
     }
 
-    /// Cancel an offer
+    /// Cancel an offer, refunding its escrow
     pub async fn cancel_offer(&self, offer_id: Uuid, user_id: Uuid) -> Result<()> {
         info!("Cancelling offer {} for user {}", offer_id, user_id);
 
@@ -339,7 +498,7 @@ impl P2PService {
             ));
         }
 
-        // NOTE: Full implementation would release escrow here
+        self.refund_escrow(offer_id, TransitionReason::OfferCancelled).await;
 
         Ok(())
     }
@@ -405,7 +564,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE id = $1
                 FOR UPDATE
@@ -433,7 +592,7 @@ impl P2PService {
         })?;
 
         // Parse the offer to validate
-        let offer = self.row_to_offer(&offer_row)?;
+        let offer = row_to_offer(&offer_row)?;
 
         // Validate acceptor is not the creator
         if offer.user_id == acceptor_id {
@@ -480,12 +639,13 @@ impl P2PService {
                 UPDATE p2p_offers
                 SET status = 'MATCHED',
                     acceptor_id = $2,
-                    accepted_at = NOW()
+                    accepted_at = NOW(),
+                    remaining_amount = 0
                 WHERE id = $1
                 RETURNING id, user_id, offer_type, from_asset, to_asset,
                           from_amount, to_amount, price, status, escrow_tx_hash,
                           matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                          conversation_id, created_at, expires_at
+                          conversation_id, remaining_amount, created_at, expires_at
                 "#,
                 &[&offer_id, &acceptor_id],
             )
@@ -506,7 +666,7 @@ impl P2PService {
             OfferType::Sell => (acceptor_id, offer.user_id),
         };
 
-        // Create exchange record
+        // Create exchange record for whatever partial fills left unfilled
         let exchange_id = Uuid::new_v4();
         transaction
             .execute(
@@ -522,8 +682,8 @@ impl P2PService {
                     &offer_id,
                     &buyer_user_id,
                     &seller_user_id,
-                    &offer.to_asset,
-                    &offer.to_amount,
+                    &offer.base_asset(),
+                    &offer.remaining_amount,
                     &offer.price,
                 ],
             )
@@ -541,6 +701,27 @@ impl P2PService {
                 Error::Database(format!("Failed to create exchange record: {}", e))
             })?;
 
+        // Hold the seller's side in escrow until both parties confirm
+        if let Some(escrow) = &self.escrow {
+            let escrow = escrow
+                .open_for_exchange(
+                    &transaction,
+                    &offer,
+                    exchange_id,
+                    seller_user_id,
+                    buyer_user_id,
+                    offer.remaining_amount,
+                )
+                .await?;
+            tracing::info!(
+                offer_id = %offer_id,
+                exchange_id = %exchange_id,
+                escrow_id = %escrow.id,
+                escrow_status = %escrow.status,
+                "Opened escrow for exchange"
+            );
+        }
+
         // Commit transaction
         transaction.commit().await.map_err(|e| {
             tracing::error!(
@@ -581,7 +762,7 @@ impl P2PService {
                         offer.user_id,
                         acceptor_id,
                         offer_id,
-                        format!("Your offer has been accepted!"),
+                        "Your offer has been accepted!".to_string(),
                     )
                     .await
                 {
@@ -608,7 +789,7 @@ impl P2PService {
         }
 
         // Return updated offer
-        self.row_to_offer(&updated_row)
+        row_to_offer(&updated_row)
     }
 
 
@@ -624,7 +805,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE user_id = $1
                 ORDER BY created_at DESC
@@ -635,7 +816,7 @@ impl P2PService {
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch user offers: {}", e)))?;
 
-        rows.iter().map(|row| self.row_to_offer(row)).collect()
+        rows.iter().map(row_to_offer).collect()
     }
     /// Get a specific offer by ID
     pub async fn get_offer(&self, offer_id: Uuid) -> Result<P2POffer> {
@@ -649,7 +830,7 @@ impl P2PService {
                 SELECT id, user_id, offer_type, from_asset, to_asset,
                        from_amount, to_amount, price, status, escrow_tx_hash,
                        matched_with_offer_id, is_proximity_offer, acceptor_id, accepted_at,
-                       conversation_id, created_at, expires_at
+                       conversation_id, remaining_amount, created_at, expires_at
                 FROM p2p_offers
                 WHERE id = $1
                 "#,
@@ -659,98 +840,130 @@ impl P2PService {
             .map_err(|e| Error::Database(format!("Failed to fetch offer: {}", e)))?;
 
         match row {
-            Some(r) => self.row_to_offer(&r),
+            Some(r) => row_to_offer(&r),
             None => Err(Error::Internal(format!("Offer {} not found", offer_id))),
         }
     }
 
 
 
-    /// Expire old offers (background job)
+    /// Expire old offers and refund their escrow (background job)
     pub async fn expire_old_offers(&self) -> Result<u64> {
         let client = self.db.get().await.map_err(|e| {
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
 
-        let rows_updated = client
-            .execute(
+        let rows = client
+            .query(
                 r#"
                 UPDATE p2p_offers
                 SET status = 'EXPIRED'
                 WHERE status = 'ACTIVE' AND expires_at < NOW()
+                RETURNING id
                 "#,
                 &[],
             )
             .await
             .map_err(|e| Error::Database(format!("Failed to expire offers: {}", e)))?;
+        drop(client);
+
+        for row in &rows {
+            self.refund_escrow(row.get("id"), TransitionReason::OfferExpired)
+                .await;
+        }
 
-        if rows_updated > 0 {
-            info!("Expired {} old P2P offers", rows_updated);
+        if !rows.is_empty() {
+            info!("Expired {} old P2P offers", rows.len());
         }
 
-        Ok(rows_updated)
+        Ok(rows.len() as u64)
     }
 
-    /// Convert database row to P2POffer
-    fn row_to_offer(&self, row: &tokio_postgres::Row) -> Result<P2POffer> {
-        let offer_type_str: String = row
-            .try_get("offer_type")
-            .map_err(|e| Error::Database(format!("Failed to get offer_type: {}", e)))?;
-        let offer_type = match offer_type_str.as_str() {
-            "BUY" => OfferType::Buy,
-            "SELL" => OfferType::Sell,
-            _ => return Err(Error::Internal(format!("Invalid offer type: {}", offer_type_str))),
-        };
+    /// Fill `user_id`'s offer against the order book
+    ///
+    /// Proximity offers may only trade with the peers `user_id`'s own
+    /// discovery sessions found.
+    pub async fn match_offer(&self, offer_id: Uuid, user_id: Uuid) -> Result<Vec<P2PFill>> {
+        let matching = self
+            .matching
+            .as_ref()
+            .ok_or_else(|| Error::Validation("P2P order matching is not enabled".to_string()))?;
+
+        let offer = self.get_offer(offer_id).await?;
+        if offer.user_id != user_id {
+            return Err(Error::Unauthorized);
+        }
 
-        let status_str: String = row
-            .try_get("status")
-            .map_err(|e| Error::Database(format!("Failed to get status: {}", e)))?;
-        let status = match status_str.as_str() {
-            "ACTIVE" => OfferStatus::Active,
-            "MATCHED" => OfferStatus::Matched,
-            "EXECUTED" => OfferStatus::Executed,
-            "CANCELLED" => OfferStatus::Cancelled,
-            "EXPIRED" => OfferStatus::Expired,
-            _ => return Err(Error::Internal(format!("Invalid status: {}", status_str))),
-        };
+        let nearby_wallets = matching.nearby_wallets(user_id).await;
+        matching.match_offer(offer_id, &nearby_wallets).await
+    }
 
-        let created_at_systime: std::time::SystemTime = row
-            .try_get("created_at")
-            .map_err(|e| Error::Database(format!("Failed to get created_at: {}", e)))?;
-        let expires_at_systime: std::time::SystemTime = row
-            .try_get("expires_at")
-            .map_err(|e| Error::Database(format!("Failed to get expires_at: {}", e)))?;
-
-        // Handle new optional fields
-        let acceptor_id: Option<Uuid> = row.try_get("acceptor_id").ok();
-        let accepted_at: Option<DateTime<Utc>> = row
-            .try_get::<_, Option<std::time::SystemTime>>("accepted_at")
-            .ok()
-            .flatten()
-            .map(DateTime::<Utc>::from);
-        let conversation_id: Option<Uuid> = row.try_get("conversation_id").ok();
-
-        Ok(P2POffer {
-            id: row.try_get("id").map_err(|e| Error::Database(format!("Failed to get id: {}", e)))?,
-            user_id: row.try_get("user_id").map_err(|e| Error::Database(format!("Failed to get user_id: {}", e)))?,
-            offer_type,
-            from_asset: row.try_get("from_asset").map_err(|e| Error::Database(format!("Failed to get from_asset: {}", e)))?,
-            to_asset: row.try_get("to_asset").map_err(|e| Error::Database(format!("Failed to get to_asset: {}", e)))?,
-            from_amount: row.try_get("from_amount").map_err(|e| Error::Database(format!("Failed to get from_amount: {}", e)))?,
-            to_amount: row.try_get("to_amount").map_err(|e| Error::Database(format!("Failed to get to_amount: {}", e)))?,
-            price: row.try_get("price").map_err(|e| Error::Database(format!("Failed to get price: {}", e)))?,
-            status,
-            escrow_tx_hash: row.try_get("escrow_tx_hash").ok(),
-            matched_with_offer_id: row.try_get("matched_with_offer_id").ok(),
-            is_proximity_offer: row.try_get("is_proximity_offer").unwrap_or(false),
-            acceptor_id,
-            accepted_at,
-            conversation_id,
-            created_at: DateTime::<Utc>::from(created_at_systime),
-            expires_at: DateTime::<Utc>::from(expires_at_systime),
+    /// Record `user_id`'s confirmation that an exchange took place
+    ///
+    /// The escrow is released to the buyer once both sides have confirmed.
+    pub async fn confirm_exchange(&self, exchange_id: Uuid, user_id: Uuid) -> Result<P2PEscrow> {
+        self.escrow()?.confirm(exchange_id, user_id).await
+    }
+
+    /// Lock the seller's deposit for a matched buy offer
+    pub async fn deposit_escrow(&self, exchange_id: Uuid, user_id: Uuid, tx_hash: &str) -> Result<P2PEscrow> {
+        self.escrow()?.deposit(exchange_id, user_id, tx_hash).await
+    }
+
+    /// Spawn a background task expiring offers, matching crossing offers
+    /// and settling timed out escrows every `interval`
+    pub fn start_expiry_worker(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        info!("Starting P2P expiry worker with interval: {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                if let Err(e) = self.expire_old_offers().await {
+                    error!("Error expiring P2P offers: {}", e);
+                }
+
+                if let Some(matching) = &self.matching {
+                    match matching.run_matching(500).await {
+                        Ok(fills) if !fills.is_empty() => info!("Made {} P2P fills", fills.len()),
+                        Ok(_) => {}
+                        Err(e) => error!("Error matching P2P offers: {}", e),
+                    }
+                }
+
+                if let Some(escrow) = &self.escrow {
+                    match escrow.reconcile_payouts(100).await {
+                        Ok(0) => {}
+                        Ok(reconciled) => info!("Reconciled {} in-flight P2P escrow payouts", reconciled),
+                        Err(e) => error!("Error reconciling P2P escrow payouts: {}", e),
+                    }
+                    match escrow.process_timeouts(100).await {
+                        Ok(0) => {}
+                        Ok(settled) => info!("Settled {} timed out P2P escrows", settled),
+                        Err(e) => error!("Error processing P2P escrow timeouts: {}", e),
+                    }
+                }
+            }
         })
     }
 
+    /// Refund an unmatched offer's escrow; failures are left to the timeout
+    /// worker to retry
+    async fn refund_escrow(&self, offer_id: Uuid, reason: TransitionReason) {
+        let Some(escrow) = &self.escrow else {
+            return;
+        };
+
+        match escrow.refund_offer(offer_id, reason).await {
+            Ok(Some(refunded)) => info!("Refunded escrow {} for offer {}", refunded.id, offer_id),
+            Ok(None) => {}
+            Err(e) => error!("Failed to refund escrow for offer {}: {}", offer_id, e),
+        }
+    }
+
+
     /// Get user's exchange history
     /// 
     /// NOTE: Stub implementation
@@ -796,6 +1009,68 @@ impl P2PService {
     }
 }
 
+/// Convert database row to P2POffer
+pub(crate) fn row_to_offer(row: &tokio_postgres::Row) -> Result<P2POffer> {
+    let offer_type_str: String = row
+        .try_get("offer_type")
+        .map_err(|e| Error::Database(format!("Failed to get offer_type: {}", e)))?;
+    let offer_type = match offer_type_str.as_str() {
+        "BUY" => OfferType::Buy,
+        "SELL" => OfferType::Sell,
+        _ => return Err(Error::Internal(format!("Invalid offer type: {}", offer_type_str))),
+    };
+
+    let status_str: String = row
+        .try_get("status")
+        .map_err(|e| Error::Database(format!("Failed to get status: {}", e)))?;
+    let status = match status_str.as_str() {
+        "ACTIVE" => OfferStatus::Active,
+        "MATCHED" => OfferStatus::Matched,
+        "EXECUTED" => OfferStatus::Executed,
+        "CANCELLED" => OfferStatus::Cancelled,
+        "EXPIRED" => OfferStatus::Expired,
+        _ => return Err(Error::Internal(format!("Invalid status: {}", status_str))),
+    };
+
+    let created_at_systime: std::time::SystemTime = row
+        .try_get("created_at")
+        .map_err(|e| Error::Database(format!("Failed to get created_at: {}", e)))?;
+    let expires_at_systime: std::time::SystemTime = row
+        .try_get("expires_at")
+        .map_err(|e| Error::Database(format!("Failed to get expires_at: {}", e)))?;
+
+    // Handle new optional fields
+    let acceptor_id: Option<Uuid> = row.try_get("acceptor_id").ok();
+    let accepted_at: Option<DateTime<Utc>> = row
+        .try_get::<_, Option<std::time::SystemTime>>("accepted_at")
+        .ok()
+        .flatten()
+        .map(DateTime::<Utc>::from);
+    let conversation_id: Option<Uuid> = row.try_get("conversation_id").ok();
+
+    Ok(P2POffer {
+        id: row.try_get("id").map_err(|e| Error::Database(format!("Failed to get id: {}", e)))?,
+        user_id: row.try_get("user_id").map_err(|e| Error::Database(format!("Failed to get user_id: {}", e)))?,
+        offer_type,
+        from_asset: row.try_get("from_asset").map_err(|e| Error::Database(format!("Failed to get from_asset: {}", e)))?,
+        to_asset: row.try_get("to_asset").map_err(|e| Error::Database(format!("Failed to get to_asset: {}", e)))?,
+        from_amount: row.try_get("from_amount").map_err(|e| Error::Database(format!("Failed to get from_amount: {}", e)))?,
+        to_amount: row.try_get("to_amount").map_err(|e| Error::Database(format!("Failed to get to_amount: {}", e)))?,
+        price: row.try_get("price").map_err(|e| Error::Database(format!("Failed to get price: {}", e)))?,
+        status,
+        escrow_tx_hash: row.try_get("escrow_tx_hash").ok(),
+        matched_with_offer_id: row.try_get("matched_with_offer_id").ok(),
+        is_proximity_offer: row.try_get("is_proximity_offer").unwrap_or(false),
+        acceptor_id,
+        accepted_at,
+        conversation_id,
+        remaining_amount: row.try_get("remaining_amount").map_err(|e| Error::Database(format!("Failed to get remaining_amount: {}", e)))?,
+        creator_reputation: None,
+        created_at: DateTime::<Utc>::from(created_at_systime),
+        expires_at: DateTime::<Utc>::from(expires_at_systime),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
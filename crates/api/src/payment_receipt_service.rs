@@ -1,4 +1,6 @@
-use crate::receipt_service::{ReceiptData, ReceiptService};
+use crate::receipt_format::SignedReceipt;
+use crate::receipt_service::{InclusionProof, Receipt, ReceiptData, ReceiptService, VerificationStatus};
+use crate::tax_lots::{RealizedGain, TaxLotService};
 use blockchain::Blockchain;
 use chrono::{DateTime, Utc};
 use database::DbPool;
@@ -8,6 +10,20 @@ use std::sync::Arc;
 use tracing::{debug, error, info};
 use uuid::Uuid;
 
+/// Receipts a user is a party to: the sender or recipient is the user's
+/// connected wallet or one of their verified wallets. `{user}` is replaced
+/// with the placeholder of the user id parameter.
+const RECEIPT_OWNER_CLAUSE: &str = "(EXISTS (SELECT 1 FROM wallets w \
+      WHERE w.user_id = {user} AND w.address IN (blockchain_receipts.sender, blockchain_receipts.recipient)) \
+     OR EXISTS (SELECT 1 FROM wallet_verifications v \
+      WHERE v.user_id = {user} AND v.verified = TRUE \
+        AND v.wallet_address IN (blockchain_receipts.sender, blockchain_receipts.recipient)))";
+
+/// `RECEIPT_OWNER_CLAUSE` for the user id bound to `$param`
+fn receipt_owner_clause(param: usize) -> String {
+    RECEIPT_OWNER_CLAUSE.replace("{user}", &format!("${}", param))
+}
+
 /// User-facing payment receipt with all required fields
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct PaymentReceipt {
@@ -33,6 +49,8 @@ pub struct PaymentReceipt {
     pub sender: String,
     /// Recipient address
     pub recipient: String,
+    /// Gain or loss realized by the transaction, if it disposed of an asset
+    pub realized_gain: Option<RealizedGain>,
 }
 
 /// Transaction type enum
@@ -71,16 +89,40 @@ pub struct TransactionFees {
 pub struct BlockchainConfirmation {
     /// Blockchain where transaction was confirmed
     pub blockchain: Blockchain,
-    /// Transaction hash on blockchain
-    pub transaction_hash: String,
+    /// Display name of the chain, e.g. `"Binance Smart Chain"`
+    pub chain_name: String,
+    /// Transaction hash on blockchain, `None` until the receipt's batch is anchored
+    pub transaction_hash: Option<String>,
+    /// SHA-256 of the receipt, the value anchored on-chain
+    pub receipt_hash: Option<String>,
+    /// Proof that the receipt hash is part of the anchored Merkle root
+    pub inclusion_proof: Option<InclusionProof>,
+    /// Server-signed receipt file that verifies offline, `None` for receipts
+    /// issued before receipts were signed
+    pub signed_receipt: Option<SignedReceipt>,
     /// Verification status
     pub verified: bool,
     /// Verification timestamp
     pub verified_at: Option<DateTime<Utc>>,
 }
 
+impl From<&Receipt> for BlockchainConfirmation {
+    fn from(receipt: &Receipt) -> Self {
+        Self {
+            blockchain: receipt.blockchain.clone(),
+            chain_name: receipt.chain_name.clone(),
+            transaction_hash: receipt.transaction_hash.clone(),
+            receipt_hash: receipt.receipt_hash.clone(),
+            inclusion_proof: receipt.inclusion_proof.clone(),
+            signed_receipt: receipt.signed(),
+            verified: receipt.verification_status == VerificationStatus::Confirmed,
+            verified_at: receipt.verified_at,
+        }
+    }
+}
+
 /// Search filters for receipts
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ReceiptSearchFilters {
     /// Filter by transaction type
     pub transaction_type: Option<TransactionType>,
@@ -133,6 +175,7 @@ pub struct ReceiptSearchResults {
 pub struct PaymentReceiptService {
     db: DbPool,
     receipt_service: Arc<ReceiptService>,
+    tax_lots: Option<Arc<TaxLotService>>,
 }
 
 impl PaymentReceiptService {
@@ -142,9 +185,16 @@ impl PaymentReceiptService {
         Self {
             db,
             receipt_service,
+            tax_lots: None,
         }
     }
 
+    /// Report the gain or loss each receipted transaction realized
+    pub fn with_tax_lots(mut self, tax_lots: Arc<TaxLotService>) -> Self {
+        self.tax_lots = Some(tax_lots);
+        self
+    }
+
     /// Generate receipt for a payment transaction
     /// 
     /// Creates a blockchain receipt and returns a user-facing payment receipt
@@ -180,6 +230,8 @@ impl PaymentReceiptService {
         // Calculate total fees
         let total_fees = self.calculate_total_fees(network_fee, platform_fee, None);
 
+        let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
+
         // Build payment receipt
         let payment_receipt = PaymentReceipt {
             id: blockchain_receipt.id,
@@ -195,15 +247,10 @@ impl PaymentReceiptService {
                 total: total_fees,
             },
             exchange_rate: None,
-            confirmation: BlockchainConfirmation {
-                blockchain,
-                transaction_hash: blockchain_receipt.transaction_hash,
-                verified: blockchain_receipt.verification_status
-                    == crate::receipt_service::VerificationStatus::Confirmed,
-                verified_at: blockchain_receipt.verified_at,
-            },
+            confirmation,
             sender,
             recipient,
+            realized_gain: None,
         };
 
         debug!("Payment receipt generated: {:?}", payment_receipt.id);
@@ -253,6 +300,8 @@ impl PaymentReceiptService {
         // Calculate total fees
         let total_fees = self.calculate_total_fees(network_fee, platform_fee, None);
 
+        let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
+
         // Build payment receipt
         let payment_receipt = PaymentReceipt {
             id: blockchain_receipt.id,
@@ -268,15 +317,10 @@ impl PaymentReceiptService {
                 total: total_fees,
             },
             exchange_rate: price_usd,
-            confirmation: BlockchainConfirmation {
-                blockchain,
-                transaction_hash: blockchain_receipt.transaction_hash,
-                verified: blockchain_receipt.verification_status
-                    == crate::receipt_service::VerificationStatus::Confirmed,
-                verified_at: blockchain_receipt.verified_at,
-            },
+            confirmation,
             sender,
             recipient,
+            realized_gain: None,
         };
 
         debug!("Trade receipt generated: {:?}", payment_receipt.id);
@@ -325,6 +369,8 @@ impl PaymentReceiptService {
         // Calculate total fees
         let total_fees = self.calculate_total_fees(network_fee, platform_fee, provider_fee);
 
+        let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
+
         // Build payment receipt
         let payment_receipt = PaymentReceipt {
             id: blockchain_receipt.id,
@@ -340,15 +386,10 @@ impl PaymentReceiptService {
                 total: total_fees,
             },
             exchange_rate: Some(exchange_rate),
-            confirmation: BlockchainConfirmation {
-                blockchain,
-                transaction_hash: blockchain_receipt.transaction_hash,
-                verified: blockchain_receipt.verification_status
-                    == crate::receipt_service::VerificationStatus::Confirmed,
-                verified_at: blockchain_receipt.verified_at,
-            },
+            confirmation,
             sender,
             recipient,
+            realized_gain: None,
         };
 
         debug!("Conversion receipt generated: {:?}", payment_receipt.id);
@@ -383,8 +424,10 @@ impl PaymentReceiptService {
             .fetch_transaction_details(transaction_type.clone(), transaction_id)
             .await?;
 
+        let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
+
         // Build payment receipt
-        let payment_receipt = PaymentReceipt {
+        let mut payment_receipt = PaymentReceipt {
             id: blockchain_receipt.id,
             transaction_id,
             transaction_type,
@@ -393,16 +436,12 @@ impl PaymentReceiptService {
             currency: blockchain_receipt.currency,
             fees,
             exchange_rate,
-            confirmation: BlockchainConfirmation {
-                blockchain: blockchain_receipt.blockchain,
-                transaction_hash: blockchain_receipt.transaction_hash,
-                verified: blockchain_receipt.verification_status
-                    == crate::receipt_service::VerificationStatus::Confirmed,
-                verified_at: blockchain_receipt.verified_at,
-            },
+            confirmation,
             sender: blockchain_receipt.sender,
             recipient: blockchain_receipt.recipient,
+            realized_gain: None,
         };
+        self.attach_realized_gains(std::slice::from_mut(&mut payment_receipt)).await;
 
         Ok(payment_receipt)
     }
@@ -441,6 +480,8 @@ impl PaymentReceiptService {
                     .fetch_transaction_details(transaction_type.clone(), transaction_id)
                     .await?;
 
+                let confirmation = BlockchainConfirmation::from(&receipt);
+
                 // Build payment receipt
                 let payment_receipt = PaymentReceipt {
                     id: receipt.id,
@@ -451,15 +492,10 @@ impl PaymentReceiptService {
                     currency: receipt.currency,
                     fees,
                     exchange_rate,
-                    confirmation: BlockchainConfirmation {
-                        blockchain: receipt.blockchain,
-                        transaction_hash: receipt.transaction_hash,
-                        verified: receipt.verification_status
-                            == crate::receipt_service::VerificationStatus::Confirmed,
-                        verified_at: receipt.verified_at,
-                    },
+                    confirmation,
                     sender: receipt.sender,
                     recipient: receipt.recipient,
+                    realized_gain: None,
                 };
 
                 Ok(Some(payment_receipt))
@@ -470,8 +506,10 @@ impl PaymentReceiptService {
 
     /// Verify receipt and update verification status
     /// 
-    /// Verifies the receipt on the blockchain and returns the updated receipt
-    /// with current verification status.
+    /// Recomputes the receipt's Merkle root from its inclusion proof and
+    /// checks it against the anchor transaction on the blockchain, returning
+    /// the updated receipt with current verification status. Receipts still
+    /// waiting for their batch to be anchored stay unverified.
     pub async fn verify_receipt(&self, receipt_id: Uuid) -> Result<PaymentReceipt> {
         info!("Verifying payment receipt: {}", receipt_id);
 
@@ -496,6 +534,8 @@ impl PaymentReceiptService {
             .fetch_transaction_details(transaction_type.clone(), transaction_id)
             .await?;
 
+        let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
+
         // Build payment receipt
         let payment_receipt = PaymentReceipt {
             id: blockchain_receipt.id,
@@ -506,15 +546,10 @@ impl PaymentReceiptService {
             currency: blockchain_receipt.currency,
             fees,
             exchange_rate,
-            confirmation: BlockchainConfirmation {
-                blockchain: blockchain_receipt.blockchain,
-                transaction_hash: blockchain_receipt.transaction_hash,
-                verified: blockchain_receipt.verification_status
-                    == crate::receipt_service::VerificationStatus::Confirmed,
-                verified_at: blockchain_receipt.verified_at,
-            },
+            confirmation,
             sender: blockchain_receipt.sender,
             recipient: blockchain_receipt.recipient,
+            realized_gain: None,
         };
 
         info!(
@@ -530,17 +565,46 @@ impl PaymentReceiptService {
         Ok(payment_receipt)
     }
 
-    /// Search receipts with filters and pagination
+    /// Whether `user_id` is a party to the receipt
+    ///
+    /// Errors with `Unauthorized` when the receipt does not exist or belongs
+    /// to someone else, so callers cannot probe for other users' receipts.
+    pub async fn ensure_receipt_owner(&self, user_id: Uuid, receipt_id: Uuid) -> Result<()> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let owned = client
+            .query_opt(
+                &format!(
+                    "SELECT 1 FROM blockchain_receipts WHERE id = $1 AND {}",
+                    receipt_owner_clause(2)
+                ),
+                &[&receipt_id, &user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to check receipt owner: {}", e)))?;
+
+        match owned {
+            Some(_) => Ok(()),
+            None => Err(Error::Unauthorized),
+        }
+    }
+
+    /// Search `user_id`'s receipts with filters and pagination
     /// 
     /// Searches for receipts matching the provided filters with pagination support.
     /// Supports filtering by transaction type, asset/currency, and date range.
+    /// Only receipts the user is a party to are returned.
     pub async fn search_receipts(
         &self,
+        user_id: Uuid,
         filters: ReceiptSearchFilters,
         pagination: Pagination,
     ) -> Result<ReceiptSearchResults> {
         info!(
-            "Searching receipts with filters: type={:?}, asset={:?}, date_range={:?} to {:?}, page={}, page_size={}",
+            "Searching receipts of user {} with filters: type={:?}, asset={:?}, date_range={:?} to {:?}, page={}, page_size={}",
+            user_id,
             filters.transaction_type,
             filters.asset,
             filters.start_date,
@@ -554,9 +618,9 @@ impl PaymentReceiptService {
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
 
-        // Build WHERE clause dynamically based on filters
-        let mut where_clauses = Vec::new();
-        let mut param_index = 1;
+        // Build WHERE clause dynamically based on filters, always scoped to the user
+        let mut where_clauses = vec![receipt_owner_clause(1)];
+        let mut param_index = 2;
 
         // Store owned values for parameters
         let asset_param = filters.asset.clone();
@@ -592,14 +656,10 @@ impl PaymentReceiptService {
             param_index += 1;
         }
 
-        let where_clause = if where_clauses.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_clauses.join(" AND "))
-        };
+        let where_clause = format!("WHERE {}", where_clauses.join(" AND "));
 
         // Build parameter vector for count query
-        let mut count_params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = Vec::new();
+        let mut count_params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = vec![Box::new(user_id)];
         if let Some(ref asset) = asset_param {
             count_params.push(Box::new(asset.clone()));
         }
@@ -643,7 +703,7 @@ impl PaymentReceiptService {
         let limit_param = limit as i64;
         
         // Create search params vector with pagination parameters
-        let mut search_params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = Vec::new();
+        let mut search_params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = vec![Box::new(user_id)];
         if let Some(ref asset) = asset_param {
             search_params.push(Box::new(asset.clone()));
         }
@@ -658,11 +718,7 @@ impl PaymentReceiptService {
 
         let search_query = format!(
             r#"
-            SELECT 
-                id, payment_id, trade_id, conversion_id,
-                amount, currency, sender, recipient,
-                blockchain, transaction_hash, verification_status,
-                created_at, verified_at
+            SELECT id
             FROM blockchain_receipts
             {}
             ORDER BY created_at DESC
@@ -684,20 +740,19 @@ impl PaymentReceiptService {
                 Error::Database(format!("Failed to search receipts: {}", e))
             })?;
 
+        // Load the full receipts, including proofs and signatures
+        let receipt_ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let blockchain_receipts = self.receipt_service.get_receipts(&receipt_ids).await?;
+
         // Convert rows to PaymentReceipts
         let mut receipts = Vec::new();
-        for row in rows {
-            let receipt_id: Uuid = row.get("id");
-            let payment_id: Option<Uuid> = row.get("payment_id");
-            let trade_id: Option<Uuid> = row.get("trade_id");
-            let conversion_id: Option<Uuid> = row.get("conversion_id");
-
+        for blockchain_receipt in blockchain_receipts {
             // Determine transaction type and ID
-            let (transaction_type, transaction_id) = if let Some(pid) = payment_id {
+            let (transaction_type, transaction_id) = if let Some(pid) = blockchain_receipt.payment_id {
                 (TransactionType::Payment, pid)
-            } else if let Some(tid) = trade_id {
+            } else if let Some(tid) = blockchain_receipt.trade_id {
                 (TransactionType::Trade, tid)
-            } else if let Some(cid) = conversion_id {
+            } else if let Some(cid) = blockchain_receipt.conversion_id {
                 (TransactionType::Conversion, cid)
             } else {
                 continue; // Skip invalid receipts
@@ -719,58 +774,28 @@ impl PaymentReceiptService {
                     )
                 });
 
-            let amount: Decimal = row.get("amount");
-            let currency: String = row.get("currency");
-            let sender: String = row.get("sender");
-            let recipient: String = row.get("recipient");
-            let blockchain_str: String = row.get("blockchain");
-            let blockchain = Blockchain::from_str(&blockchain_str);
-            let transaction_hash: String = row.get("transaction_hash");
-            let verification_status: String = row.get("verification_status");
-            
-            // Handle timestamp - database might return NaiveDateTime
-            let created_at: DateTime<Utc> = match row.try_get::<_, DateTime<Utc>>("created_at") {
-                Ok(dt) => dt,
-                Err(_) => {
-                    // Try as NaiveDateTime and convert to UTC
-                    let naive: chrono::NaiveDateTime = row.get("created_at");
-                    DateTime::from_naive_utc_and_offset(naive, chrono::Utc)
-                }
-            };
-            
-            let verified_at: Option<DateTime<Utc>> = match row.try_get::<_, Option<DateTime<Utc>>>("verified_at") {
-                Ok(dt) => dt,
-                Err(_) => {
-                    // Try as NaiveDateTime and convert to UTC
-                    row.try_get::<_, Option<chrono::NaiveDateTime>>("verified_at")
-                        .ok()
-                        .flatten()
-                        .map(|naive| DateTime::from_naive_utc_and_offset(naive, chrono::Utc))
-                }
-            };
+            let confirmation = BlockchainConfirmation::from(&blockchain_receipt);
 
             let payment_receipt = PaymentReceipt {
-                id: receipt_id,
+                id: blockchain_receipt.id,
                 transaction_id,
                 transaction_type,
-                timestamp: created_at,
-                amount,
-                currency,
+                timestamp: blockchain_receipt.created_at,
+                amount: blockchain_receipt.amount,
+                currency: blockchain_receipt.currency,
                 fees,
                 exchange_rate,
-                confirmation: BlockchainConfirmation {
-                    blockchain,
-                    transaction_hash,
-                    verified: verification_status == "CONFIRMED",
-                    verified_at,
-                },
-                sender,
-                recipient,
+                confirmation,
+                sender: blockchain_receipt.sender,
+                recipient: blockchain_receipt.recipient,
+                realized_gain: None,
             };
 
             receipts.push(payment_receipt);
         }
 
+        self.attach_realized_gains(&mut receipts).await;
+
         info!(
             "Found {} receipts (total: {}, page: {}/{})",
             receipts.len(),
@@ -788,6 +813,25 @@ impl PaymentReceiptService {
         })
     }
 
+    /// Fill in the realized gain of receipts whose transaction disposed of an asset
+    ///
+    /// Receipts are still returned without gains if the tax lots cannot be built.
+    async fn attach_realized_gains(&self, receipts: &mut [PaymentReceipt]) {
+        let Some(tax_lots) = &self.tax_lots else {
+            return;
+        };
+
+        let transaction_ids: Vec<Uuid> = receipts.iter().map(|r| r.transaction_id).collect();
+        match tax_lots.realized_gains(&transaction_ids).await {
+            Ok(mut gains) => {
+                for receipt in receipts {
+                    receipt.realized_gain = gains.remove(&receipt.transaction_id);
+                }
+            }
+            Err(e) => error!("Failed to compute realized gains for receipts: {}", e),
+        }
+    }
+
     /// Calculate total fees from individual fee components
     fn calculate_total_fees(
         &self,
@@ -933,13 +977,14 @@ impl PaymentReceiptService {
         }
     }
 
-    /// Export a single receipt as PDF
+    /// Export one of `user_id`'s receipts as PDF
     /// 
     /// Generates a PDF document for a single receipt with all details.
-    pub async fn export_receipt_pdf(&self, receipt_id: Uuid) -> Result<Vec<u8>> {
+    pub async fn export_receipt_pdf(&self, user_id: Uuid, receipt_id: Uuid) -> Result<Vec<u8>> {
         use printpdf::*;
 
         info!("Exporting receipt {} as PDF", receipt_id);
+        self.ensure_receipt_owner(user_id, receipt_id).await?;
 
         // Get the receipt
         let receipt = self.get_receipt(receipt_id).await?;
@@ -966,7 +1011,7 @@ impl PaymentReceiptService {
         let mut y_pos = 250.0;
         let line_height = 7.0;
 
-        let details = vec![
+        let mut details = vec![
             format!("Receipt ID: {}", receipt.id),
             format!("Transaction ID: {}", receipt.transaction_id),
             format!("Type: {}", receipt.transaction_type.as_str()),
@@ -983,18 +1028,77 @@ impl PaymentReceiptService {
             String::new(),
             format!("Exchange Rate: {}", receipt.exchange_rate.map(|r| r.to_string()).unwrap_or("N/A".to_string())),
             String::new(),
+        ];
+
+        if let Some(gain) = &receipt.realized_gain {
+            details.extend([
+                "Realized Gain/Loss:".to_string(),
+                format!("  Cost Basis: ${}", gain.cost_basis_usd.round_dp(2)),
+                format!("  Proceeds: ${}", gain.proceeds_usd.round_dp(2)),
+                format!("  Gain/Loss: ${}", gain.gain_usd.round_dp(2)),
+                format!(
+                    "  Holding Period: {}",
+                    gain.holding_period.map(|p| p.as_str()).unwrap_or("MIXED")
+                ),
+                String::new(),
+            ]);
+        }
+
+        details.extend([
             "Blockchain Confirmation:".to_string(),
-            format!("  Blockchain: {}", receipt.confirmation.blockchain.name()),
-            format!("  Transaction Hash: {}", receipt.confirmation.transaction_hash),
+            format!("  Blockchain: {}", receipt.confirmation.chain_name),
+            format!(
+                "  Transaction Hash: {}",
+                receipt.confirmation.transaction_hash.as_deref().unwrap_or("Pending anchoring")
+            ),
+            format!("  Receipt Hash: {}", receipt.confirmation.receipt_hash.as_deref().unwrap_or("N/A")),
             format!("  Verified: {}", if receipt.confirmation.verified { "Yes" } else { "No" }),
             format!("  Verified At: {}", receipt.confirmation.verified_at.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()).unwrap_or("N/A".to_string())),
-        ];
+        ]);
 
         for detail in details {
             current_layer.use_text(&detail, 12.0, Mm(20.0), Mm(y_pos), &font);
             y_pos -= line_height;
         }
 
+        // Embed the signed receipt so the printout can be verified offline
+        if let Some(signed) = &receipt.confirmation.signed_receipt {
+            let mono = doc.add_builtin_font(BuiltinFont::Courier).map_err(|e| {
+                error!("Failed to add font: {}", e);
+                shared::Error::Internal(format!("Failed to add font: {}", e))
+            })?;
+
+            let mut layer = current_layer;
+            let mut pages = 1;
+            y_pos -= line_height;
+            for detail in [
+                "Signed Receipt:".to_string(),
+                format!("  Format: {}", signed.receipt.version),
+                format!("  Signer: {}", signed.signer),
+                format!("  Signature: {}", signed.signature),
+            ] {
+                layer.use_text(&detail, 12.0, Mm(20.0), Mm(y_pos), &font);
+                y_pos -= line_height;
+            }
+
+            // The compact receipt file, wrapped to the page width
+            let file = serde_json::to_string(signed).map_err(|e| {
+                shared::Error::Internal(format!("Failed to serialize signed receipt: {}", e))
+            })?;
+            let chars: Vec<char> = file.chars().collect();
+            for chunk in chars.chunks(95) {
+                if y_pos < 15.0 {
+                    pages += 1;
+                    let (page, page_layer) = doc.add_page(Mm(210.0), Mm(297.0), format!("Layer {}", pages));
+                    layer = doc.get_page(page).get_layer(page_layer);
+                    y_pos = 280.0;
+                }
+                let line: String = chunk.iter().collect();
+                layer.use_text(&line, 7.0, Mm(20.0), Mm(y_pos), &mono);
+                y_pos -= 3.5;
+            }
+        }
+
         // Save to bytes
         let pdf_bytes = doc.save_to_bytes().map_err(|e| {
             error!("Failed to save PDF: {}", e);
@@ -1006,11 +1110,13 @@ impl PaymentReceiptService {
         Ok(pdf_bytes)
     }
 
-    /// Export receipt history as CSV
+    /// Export `user_id`'s receipt history as CSV
     /// 
-    /// Generates a CSV file containing all receipts matching the search filters.
+    /// Generates a CSV file containing all of the user's receipts matching
+    /// the search filters.
     pub async fn export_receipts_csv(
         &self,
+        user_id: Uuid,
         filters: ReceiptSearchFilters,
     ) -> Result<Vec<u8>> {
         info!("Exporting receipts as CSV with filters: type={:?}, asset={:?}", 
@@ -1022,7 +1128,7 @@ impl PaymentReceiptService {
             page_size: 10000, // Large limit for export
         };
 
-        let results = self.search_receipts(filters, pagination).await?;
+        let results = self.search_receipts(user_id, filters, pagination).await?;
 
         // Create CSV writer
         let mut wtr = csv::Writer::from_writer(vec![]);
@@ -1046,6 +1152,12 @@ impl PaymentReceiptService {
             "Transaction Hash",
             "Verified",
             "Verified At",
+            "Receipt Hash",
+            "Signed Receipt",
+            "Cost Basis (USD)",
+            "Proceeds (USD)",
+            "Realized Gain/Loss (USD)",
+            "Holding Period",
         ]).map_err(|e| {
             error!("Failed to write CSV header: {}", e);
             Error::Internal(format!("Failed to write CSV header: {}", e))
@@ -1054,6 +1166,7 @@ impl PaymentReceiptService {
         // Write data rows
         let receipts_len = results.receipts.len();
         for receipt in results.receipts {
+            let gain = receipt.realized_gain.as_ref();
             wtr.write_record(&[
                 receipt.id.to_string(),
                 receipt.transaction_id.to_string(),
@@ -1068,10 +1181,21 @@ impl PaymentReceiptService {
                 receipt.fees.provider_fee.map(|f| f.to_string()).unwrap_or_default(),
                 receipt.fees.total.to_string(),
                 receipt.exchange_rate.map(|r| r.to_string()).unwrap_or_default(),
-                receipt.confirmation.blockchain.name().to_string(),
-                receipt.confirmation.transaction_hash,
+                receipt.confirmation.chain_name,
+                receipt.confirmation.transaction_hash.unwrap_or_default(),
                 if receipt.confirmation.verified { "Yes" } else { "No" }.to_string(),
                 receipt.confirmation.verified_at.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()).unwrap_or_default(),
+                receipt.confirmation.receipt_hash.unwrap_or_default(),
+                receipt
+                    .confirmation
+                    .signed_receipt
+                    .and_then(|signed| serde_json::to_string(&signed).ok())
+                    .unwrap_or_default(),
+                gain.map(|g| g.cost_basis_usd.round_dp(2).to_string()).unwrap_or_default(),
+                gain.map(|g| g.proceeds_usd.round_dp(2).to_string()).unwrap_or_default(),
+                gain.map(|g| g.gain_usd.round_dp(2).to_string()).unwrap_or_default(),
+                gain.map(|g| g.holding_period.map(|p| p.as_str()).unwrap_or("MIXED").to_string())
+                    .unwrap_or_default(),
             ]).map_err(|e| {
                 error!("Failed to write CSV row: {}", e);
                 Error::Internal(format!("Failed to write CSV row: {}", e))
@@ -1089,6 +1213,21 @@ impl PaymentReceiptService {
         Ok(csv_bytes)
     }
 
+    /// Export one of `user_id`'s receipts as a signed receipt file
+    ///
+    /// The file verifies with `verify_signed_receipt` against
+    /// [`Self::signing_key`], without access to this service.
+    pub async fn export_signed_receipt(&self, user_id: Uuid, receipt_id: Uuid) -> Result<SignedReceipt> {
+        info!("Exporting receipt {} as signed receipt file", receipt_id);
+        self.ensure_receipt_owner(user_id, receipt_id).await?;
+        self.receipt_service.get_signed_receipt(receipt_id).await
+    }
+
+    /// Public key receipts are signed with, base58-encoded
+    pub fn signing_key(&self) -> String {
+        self.receipt_service.signing_key().to_string()
+    }
+
     /// Archive receipts older than 7 years
     /// 
     /// Marks receipts older than 7 years as archived. This is a background job
@@ -11,6 +11,7 @@ use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::tax_lots::TaxLotService;
 use crate::trim_config_service::TrimConfigService;
 
 /// Service for evaluating positions and generating trim recommendations
@@ -20,6 +21,7 @@ pub struct PositionEvaluator {
     db_pool: DbPool,
     trim_config_service: Arc<TrimConfigService>,
     claude_client: ClaudeClient,
+    tax_lots: Option<Arc<TaxLotService>>,
 }
 
 /// Represents a user's position in an asset with profit calculation
@@ -54,9 +56,16 @@ impl PositionEvaluator {
             db_pool,
             trim_config_service,
             claude_client: ClaudeClient::new(claude_api_key),
+            tax_lots: None,
         }
     }
 
+    /// Take entry prices from the fee-adjusted cost basis of the user's open tax lots
+    pub fn with_tax_lots(mut self, tax_lots: Arc<TaxLotService>) -> Self {
+        self.tax_lots = Some(tax_lots);
+        self
+    }
+
     /// Start the background worker that evaluates positions every 5 minutes
     pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
@@ -192,6 +201,11 @@ impl PositionEvaluator {
             .await
             .map_err(|e| Error::Database(format!("Failed to query wallets: {}", e)))?;
 
+        let ledger = match &self.tax_lots {
+            Some(tax_lots) => Some(tax_lots.build_ledger(user_id).await?),
+            None => None,
+        };
+
         let mut positions = Vec::new();
 
         for wallet_row in wallet_rows {
@@ -225,8 +239,11 @@ impl PositionEvaluator {
                     }
                 });
 
-                // Get entry price from trade history (if available)
-                let entry_price_usd = self.get_entry_price(user_id, &token_mint).await?;
+                // Average cost of the open lots, or the first buy without tax lots
+                let entry_price_usd = match ledger.as_ref().and_then(|l| l.average_cost(&token_symbol)) {
+                    Some(average_cost) => Some(average_cost),
+                    None => self.get_entry_price(user_id, &token_mint).await?,
+                };
 
                 let (profit_percent, profit_usd) = if let (Some(entry), Some(current)) =
                     (entry_price_usd, current_price_usd)
@@ -275,7 +275,7 @@ impl PositionManagementService {
     }
 
     /// Validate user has sufficient balance for a sell order
-    async fn validate_balance(
+    pub(crate) async fn validate_balance(
         &self,
         user_id: Uuid,
         asset: &str,
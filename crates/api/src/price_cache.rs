@@ -1,24 +1,60 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use redis::AsyncCommands;
-use std::collections::HashMap;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
-use crate::mesh_types::{CachedPriceData, DataFreshness};
+use crate::mesh_types::{CachedPriceData, ConsensusPriceData, DataFreshness};
 use crate::mesh_metrics::MeshMetricsCollector;
 use database::DbPool;
 
+/// Default deviation from the consensus beyond which a quote is an outlier
+pub const DEFAULT_DISCREPANCY_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// Default age after which a quote no longer counts toward the consensus
+pub const DEFAULT_CONSENSUS_WINDOW_SECS: u64 = 120;
+
+/// How far ahead of the local clock a quote's timestamp may be
+const MAX_QUOTE_CLOCK_SKEW_SECS: i64 = 30;
+
+/// Default number of distinct providers that must agree before a price is
+/// accepted, enough for a majority to out-vote one bad provider
+pub const DEFAULT_MIN_CONSENSUS_PROVIDERS: usize = 3;
+
+/// Fewest quotes needed before a single quote can be blamed as the outlier
+const MIN_QUOTES_FOR_OUTLIER_REJECTION: usize = 3;
+
+/// Number of agreeing providers at which the consensus reaches full confidence
+const FULL_CONFIDENCE_PROVIDERS: usize = 3;
+
 /// Manages local caching of price data with freshness tracking
+/// 
+/// Besides the most recent accepted quote per asset, the cache keeps the
+/// latest quote from each provider so reads can be served as a median
+/// consensus that a single bad provider cannot move. No price is accepted
+/// until a quorum of distinct providers agrees on it.
 pub struct PriceCache {
     /// In-memory cache for fast lookups
     cache: Arc<RwLock<HashMap<String, CachedPriceData>>>,
+    /// Latest quote from each provider, per asset
+    quotes: Arc<RwLock<HashMap<String, HashMap<Uuid, CachedPriceData>>>>,
     /// Redis connection for distributed caching
     redis: redis::aio::ConnectionManager,
     /// Database pool for persistent storage
     db: DbPool,
     /// Metrics collector for tracking cache operations
     metrics: Arc<MeshMetricsCollector>,
+    /// Maximum deviation from the consensus, in percent
+    discrepancy_threshold_percent: f64,
+    /// Quotes older than the newest quote by more than this are dropped
+    consensus_window: Duration,
+    /// Distinct agreeing providers needed for a consensus
+    min_providers: usize,
 }
 
 impl PriceCache {
@@ -26,19 +62,99 @@ impl PriceCache {
     pub fn new(redis: redis::aio::ConnectionManager, db: DbPool, metrics: Arc<MeshMetricsCollector>) -> Self {
         Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
+            quotes: Arc::new(RwLock::new(HashMap::new())),
             redis,
             db,
             metrics,
+            discrepancy_threshold_percent: DEFAULT_DISCREPANCY_THRESHOLD_PERCENT,
+            consensus_window: Duration::seconds(DEFAULT_CONSENSUS_WINDOW_SECS as i64),
+            min_providers: DEFAULT_MIN_CONSENSUS_PROVIDERS,
         }
     }
     
+    /// Set the outlier threshold, the window of quotes used for consensus and
+    /// the number of distinct providers that must agree
+    pub fn with_consensus_settings(
+        mut self,
+        discrepancy_threshold_percent: f64,
+        consensus_window_secs: u64,
+        min_providers: usize,
+    ) -> Self {
+        self.discrepancy_threshold_percent = discrepancy_threshold_percent;
+        self.consensus_window = Duration::seconds(consensus_window_secs as i64);
+        self.min_providers = min_providers.max(1);
+        self
+    }
+    
     /// Store price data in cache
     /// 
-    /// Compares timestamps and only stores if the new data is fresher than existing data
-    /// Stores in memory, Redis, and database for durability
-    /// Detects price discrepancies >5% between providers and logs them
+    /// Records the quote as the provider's latest for the asset, then checks it
+    /// against the consensus of all recent quotes. Outliers are recorded as
+    /// validation failures and never become the cached price, and neither do
+    /// quotes while fewer providers than the quorum agree. Quotes dated
+    /// further ahead than the allowed clock skew are rejected outright.
+    /// Otherwise the quote is stored in memory and Redis if it is fresher
+    /// than the existing data.
     pub async fn store(&self, asset: String, data: CachedPriceData) -> Result<()> {
-        // Check if we should replace existing data and detect price discrepancies
+        let now = Utc::now();
+        if is_future_dated(&data, now) {
+            tracing::warn!(
+                "Rejecting {} quote from provider {} dated {} in the future",
+                asset,
+                data.source_node_id,
+                data.timestamp.to_rfc3339()
+            );
+            self.metrics
+                .record_validation_failure(data.source_node_id, "future_timestamp")
+                .await;
+            return Ok(());
+        }
+        
+        // Record the quote and check it against the other providers (Requirement 8.2, 8.4)
+        let consensus = {
+            let mut quotes = self.quotes.write().await;
+            let book = quotes.entry(asset.clone()).or_default();
+            let is_newer = book
+                .get(&data.source_node_id)
+                .is_none_or(|existing| self.should_replace(existing, &data));
+            if is_newer {
+                book.insert(data.source_node_id, data.clone());
+            }
+            prune_quotes(book, self.consensus_window, now);
+            
+            let recent: Vec<CachedPriceData> = book.values().cloned().collect();
+            compute_consensus(&asset, &recent, self.discrepancy_threshold_percent, self.min_providers)
+        };
+        
+        match &consensus {
+            None => {
+                tracing::debug!(
+                    "Not caching {} quote from provider {}: fewer than {} providers agree",
+                    asset,
+                    data.source_node_id,
+                    self.min_providers
+                );
+                return Ok(());
+            }
+            Some(consensus) if consensus.outliers.contains(&data.source_node_id) => {
+                tracing::warn!(
+                    "Price outlier for asset {}: provider {} quoted {} against consensus {} of {} providers (threshold {:.2}%)",
+                    asset,
+                    data.source_node_id,
+                    data.price,
+                    consensus.price,
+                    consensus.providers.len(),
+                    self.discrepancy_threshold_percent
+                );
+                self.metrics
+                    .record_validation_failure(data.source_node_id, "price_outlier")
+                    .await;
+                return Ok(());
+            }
+            Some(_) => {}
+        }
+        
+        // Check if we should replace existing data
         let (should_store, existing_data) = {
             let cache = self.cache.read().await;
             match cache.get(&asset) {
@@ -50,26 +166,6 @@ impl PriceCache {
             }
         };
         
-        // Detect price discrepancies between providers (Requirement 8.2, 8.4)
-        if let Some(existing) = &existing_data {
-            // Only check discrepancies if data is from different providers
-            if existing.source_node_id != data.source_node_id {
-                if let Some(discrepancy_pct) = self.calculate_price_discrepancy(existing, &data) {
-                    if discrepancy_pct > 5.0 {
-                        tracing::warn!(
-                            "Price discrepancy detected for asset {}: {:.2}% difference between provider {} (price: {}) and provider {} (price: {})",
-                            asset,
-                            discrepancy_pct,
-                            existing.source_node_id,
-                            existing.price,
-                            data.source_node_id,
-                            data.price
-                        );
-                    }
-                }
-            }
-        }
-        
         if !should_store {
             tracing::debug!(
                 "Skipping store for asset {} - existing data is newer (existing: {}, new: {})",
@@ -102,6 +198,70 @@ impl PriceCache {
         Ok(())
     }
     
+    /// Get the consensus price for a specific asset
+    /// 
+    /// Uses the quotes from all providers within the consensus window, `None`
+    /// while fewer than the quorum agree. When there are none, because none
+    /// have been received since startup or providers stopped sending, the
+    /// cached price, which a quorum agreed on, is returned with low confidence.
+    pub async fn consensus(&self, asset: &str) -> Result<Option<ConsensusPriceData>> {
+        let now = Utc::now();
+        let recent: Vec<CachedPriceData> = {
+            let quotes = self.quotes.read().await;
+            quotes
+                .get(asset)
+                .map(|book| recent_quotes(book, self.consensus_window, now))
+                .unwrap_or_default()
+        };
+        
+        if !recent.is_empty() {
+            self.metrics.record_cache_hit(asset).await;
+            return Ok(compute_consensus(
+                asset,
+                &recent,
+                self.discrepancy_threshold_percent,
+                self.min_providers,
+            ));
+        }
+        
+        let cached = self.get(asset).await?;
+        Ok(cached.and_then(|data| {
+            compute_consensus(asset, &[data], self.discrepancy_threshold_percent, 1)
+        }))
+    }
+    
+    /// Get the consensus price for every cached asset
+    pub async fn get_all_consensus(&self) -> Result<HashMap<String, ConsensusPriceData>> {
+        // Recent quotes need a quorum; a cached price already had one
+        let now = Utc::now();
+        let mut recent: HashMap<String, (Vec<CachedPriceData>, usize)> = {
+            let quotes = self.quotes.read().await;
+            quotes
+                .iter()
+                .map(|(asset, book)| (asset, recent_quotes(book, self.consensus_window, now)))
+                .filter(|(_, recent)| !recent.is_empty())
+                .map(|(asset, recent)| (asset.clone(), (recent, self.min_providers)))
+                .collect()
+        };
+        
+        {
+            let cache = self.cache.read().await;
+            for (asset, data) in cache.iter() {
+                recent
+                    .entry(asset.clone())
+                    .or_insert_with(|| (vec![data.clone()], 1));
+            }
+        }
+        
+        Ok(recent
+            .into_iter()
+            .filter_map(|(asset, (quotes, min_providers))| {
+                compute_consensus(&asset, &quotes, self.discrepancy_threshold_percent, min_providers)
+                    .map(|consensus| (asset, consensus))
+            })
+            .collect())
+    }
+    
     /// Get price data from cache for a specific asset
     /// 
     /// Checks memory first, then Redis if not found in memory
@@ -191,6 +351,7 @@ impl PriceCache {
                 source_node_id,
                 blockchain,
                 change_24h,
+                signer: None,
             };
             
             cache.insert(asset, data);
@@ -255,29 +416,296 @@ impl PriceCache {
     pub fn should_replace(&self, existing: &CachedPriceData, new: &CachedPriceData) -> bool {
         new.timestamp > existing.timestamp
     }
+}
+
+/// Whether `quote` is dated further ahead of `now` than clocks may drift
+fn is_future_dated(quote: &CachedPriceData, now: DateTime<Utc>) -> bool {
+    quote.timestamp - now > Duration::seconds(MAX_QUOTE_CLOCK_SKEW_SECS)
+}
+
+/// Whether `quote` counts toward the consensus at `now`: no older than
+/// `window` and not dated too far ahead
+fn is_recent(quote: &CachedPriceData, window: Duration, now: DateTime<Utc>) -> bool {
+    now - quote.timestamp <= window && !is_future_dated(quote, now)
+}
+
+/// Quotes in `book` that count toward the consensus at `now`
+fn recent_quotes(book: &HashMap<Uuid, CachedPriceData>, window: Duration, now: DateTime<Utc>) -> Vec<CachedPriceData> {
+    book.values().filter(|quote| is_recent(quote, window, now)).cloned().collect()
+}
+
+/// Drop quotes in `book` that no longer count toward the consensus at `now`
+fn prune_quotes(book: &mut HashMap<Uuid, CachedPriceData>, window: Duration, now: DateTime<Utc>) {
+    book.retain(|_, quote| is_recent(quote, window, now));
+}
+
+/// Compute the median consensus of recent provider quotes
+/// 
+/// Quotes signed by the same key count once, as the newest of them. With at
+/// least three quotes, those deviating from the median by more than
+/// `threshold_percent` are rejected as outliers and the consensus is the
+/// median of the rest. With fewer quotes no single provider can be blamed,
+/// so all are used and any disagreement drops the confidence to zero.
+/// Quotes whose price does not parse as a positive number are outliers.
+/// There is no consensus while fewer than `min_providers` quotes agree.
+pub fn compute_consensus(
+    asset: &str,
+    quotes: &[CachedPriceData],
+    threshold_percent: f64,
+    min_providers: usize,
+) -> Option<ConsensusPriceData> {
+    let quotes = newest_per_signer(quotes);
+    let mut priced: Vec<(Decimal, &CachedPriceData)> = Vec::with_capacity(quotes.len());
+    let mut outliers = Vec::new();
+    for quote in quotes.iter().copied() {
+        match Decimal::from_str(quote.price.trim()) {
+            Ok(price) if price > Decimal::ZERO => priced.push((price, quote)),
+            _ => outliers.push(quote.source_node_id),
+        }
+    }
+    if priced.is_empty() {
+        return None;
+    }
+    priced.sort_by_key(|(price, _)| *price);
     
-    /// Calculate price discrepancy percentage between two price data points
-    /// 
-    /// Returns None if prices cannot be parsed as numbers
-    /// Returns Some(percentage) representing the absolute percentage difference
-    fn calculate_price_discrepancy(
-        &self,
-        existing: &CachedPriceData,
-        new: &CachedPriceData,
-    ) -> Option<f64> {
-        // Parse prices as f64
-        let existing_price = existing.price.parse::<f64>().ok()?;
-        let new_price = new.price.parse::<f64>().ok()?;
-        
-        // Avoid division by zero
-        if existing_price == 0.0 {
-            return None;
+    let reference = median(&priced);
+    let mut agreeing: Vec<(Decimal, &CachedPriceData)> = priced.clone();
+    if priced.len() >= MIN_QUOTES_FOR_OUTLIER_REJECTION {
+        let (within, beyond): (Vec<_>, Vec<_>) = priced
+            .iter()
+            .partition(|(price, _)| deviation_percent(reference, *price) <= threshold_percent);
+        // With no quote near the median there is nothing to reject against
+        if !within.is_empty() {
+            outliers.extend(beyond.iter().map(|(_, quote)| quote.source_node_id));
+            agreeing = within;
         }
-        
-        // Calculate absolute percentage difference
-        let difference = (new_price - existing_price).abs();
-        let percentage = (difference / existing_price) * 100.0;
-        
-        Some(percentage)
+    }
+    
+    if agreeing.len() < min_providers {
+        return None;
+    }
+    
+    let lowest = agreeing[0].0;
+    let highest = agreeing[agreeing.len() - 1].0;
+    let conflicting = deviation_percent(lowest, highest) > threshold_percent;
+    let confidence = if conflicting {
+        0.0
+    } else {
+        let agreement = agreeing.len() as f64 / quotes.len() as f64;
+        let coverage = agreeing.len().min(FULL_CONFIDENCE_PROVIDERS) as f64
+            / FULL_CONFIDENCE_PROVIDERS as f64;
+        agreement * coverage
+    };
+    
+    let newest = agreeing
+        .iter()
+        .map(|(_, quote)| *quote)
+        .max_by_key(|quote| quote.timestamp)?;
+    
+    Some(ConsensusPriceData {
+        asset: asset.to_string(),
+        price: format_median(&agreeing),
+        timestamp: newest.timestamp,
+        source_node_id: newest.source_node_id,
+        blockchain: newest.blockchain.clone(),
+        change_24h: newest.change_24h.clone(),
+        confidence,
+        providers: agreeing.iter().map(|(_, quote)| quote.source_node_id).collect(),
+        outliers,
+    })
+}
+
+/// The newest quote of each signing key; unsigned quotes stand for their
+/// own node
+fn newest_per_signer(quotes: &[CachedPriceData]) -> Vec<&CachedPriceData> {
+    let mut by_newest: Vec<&CachedPriceData> = quotes.iter().collect();
+    by_newest.sort_by_key(|quote| std::cmp::Reverse(quote.timestamp));
+    
+    let mut seen = HashSet::new();
+    by_newest
+        .into_iter()
+        .filter(|quote| match &quote.signer {
+            Some(signer) => seen.insert(signer.clone()),
+            None => true,
+        })
+        .collect()
+}
+
+/// Median of quotes sorted by price
+fn median(sorted: &[(Decimal, &CachedPriceData)]) -> Decimal {
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        sorted[mid].0
+    } else {
+        (sorted[mid - 1].0 + sorted[mid].0) / Decimal::TWO
+    }
+}
+
+/// Median price as quoted, or the normalized midpoint for an even count
+fn format_median(sorted: &[(Decimal, &CachedPriceData)]) -> String {
+    if sorted.len() % 2 == 1 {
+        sorted[sorted.len() / 2].1.price.trim().to_string()
+    } else {
+        median(sorted).normalize().to_string()
+    }
+}
+
+/// Absolute deviation of `price` from `reference`, in percent
+fn deviation_percent(reference: Decimal, price: Decimal) -> f64 {
+    if reference.is_zero() {
+        return 0.0;
+    }
+    ((price - reference).abs() / reference * Decimal::ONE_HUNDRED)
+        .to_f64()
+        .unwrap_or(f64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(price: &str, seconds_ago: i64) -> CachedPriceData {
+        CachedPriceData {
+            asset: "SOL".to_string(),
+            price: price.to_string(),
+            timestamp: Utc::now() - Duration::seconds(seconds_ago),
+            source_node_id: Uuid::new_v4(),
+            blockchain: "solana".to_string(),
+            change_24h: None,
+            signer: None,
+        }
+    }
+
+    #[test]
+    fn test_single_bad_provider_is_rejected() {
+        let quotes = vec![
+            quote("100.00", 3),
+            quote("101.00", 2),
+            quote("99.50", 1),
+            quote("250.00", 0),
+        ];
+
+        let consensus = compute_consensus("SOL", &quotes, 5.0, 1).unwrap();
+
+        assert_eq!(consensus.price, "100.00");
+        assert_eq!(consensus.outliers, vec![quotes[3].source_node_id]);
+        assert_eq!(consensus.providers.len(), 3);
+        // Newest agreeing quote, not the rejected one
+        assert_eq!(consensus.source_node_id, quotes[2].source_node_id);
+        assert!((consensus.confidence - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_agreeing_providers_reach_full_confidence() {
+        let quotes = vec![quote("100.00", 2), quote("100.40", 1), quote("100.20", 0)];
+
+        let consensus = compute_consensus("SOL", &quotes, 5.0, 1).unwrap();
+
+        assert_eq!(consensus.price, "100.20");
+        assert!(consensus.outliers.is_empty());
+        assert_eq!(consensus.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_single_quote_has_low_confidence() {
+        let quotes = vec![quote("105.75", 0)];
+
+        let consensus = compute_consensus("SOL", &quotes, 5.0, 1).unwrap();
+
+        assert_eq!(consensus.price, "105.75");
+        assert_eq!(consensus.source_node_id, quotes[0].source_node_id);
+        assert!((consensus.confidence - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_two_conflicting_quotes_have_no_confidence() {
+        let quotes = vec![quote("100.00", 1), quote("110.00", 0)];
+
+        let consensus = compute_consensus("SOL", &quotes, 5.0, 1).unwrap();
+
+        assert_eq!(consensus.price, "105");
+        assert!(consensus.outliers.is_empty());
+        assert_eq!(consensus.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_unparseable_quote_is_outlier() {
+        let quotes = vec![quote("100.00", 1), quote("not-a-price", 0)];
+
+        let consensus = compute_consensus("SOL", &quotes, 5.0, 1).unwrap();
+
+        assert_eq!(consensus.price, "100.00");
+        assert_eq!(consensus.outliers, vec![quotes[1].source_node_id]);
+        assert!(compute_consensus("SOL", &quotes[1..], 5.0, 1).is_none());
+    }
+
+    #[test]
+    fn test_consensus_needs_a_quorum_of_distinct_signers() {
+        let signed = |price: &str, seconds_ago: i64, signer: &str| CachedPriceData {
+            signer: Some(signer.to_string()),
+            ..quote(price, seconds_ago)
+        };
+        let quotes = vec![signed("100.00", 2, "a"), signed("100.10", 1, "b")];
+        assert!(compute_consensus("SOL", &quotes, 5.0, 3).is_none());
+
+        // One key publishing under several node IDs is still one provider
+        let mut sybil = quotes.clone();
+        sybil.push(signed("150.00", 0, "b"));
+        sybil.push(signed("150.00", 0, "b"));
+        assert!(compute_consensus("SOL", &sybil, 5.0, 3).is_none());
+
+        let mut quorum = quotes.clone();
+        quorum.push(signed("100.20", 0, "c"));
+        let consensus = compute_consensus("SOL", &quorum, 5.0, 3).unwrap();
+        assert_eq!(consensus.price, "100.10");
+        assert_eq!(consensus.providers.len(), 3);
+    }
+
+    #[test]
+    fn test_prune_drops_quotes_outside_window() {
+        let fresh = quote("100.00", 0);
+        let stale = quote("90.00", 600);
+        let mut book: HashMap<Uuid, CachedPriceData> = [fresh.clone(), stale.clone()]
+            .into_iter()
+            .map(|quote| (quote.source_node_id, quote))
+            .collect();
+
+        prune_quotes(&mut book, Duration::seconds(DEFAULT_CONSENSUS_WINDOW_SECS as i64), Utc::now());
+
+        assert!(book.contains_key(&fresh.source_node_id));
+        assert!(!book.contains_key(&stale.source_node_id));
+    }
+
+    #[test]
+    fn test_aged_quotes_give_no_consensus() {
+        let book: HashMap<Uuid, CachedPriceData> = [quote("100.00", 600), quote("100.10", 610), quote("99.90", 620)]
+            .into_iter()
+            .map(|quote| (quote.source_node_id, quote))
+            .collect();
+        let window = Duration::seconds(DEFAULT_CONSENSUS_WINDOW_SECS as i64);
+
+        // A quorum once, but every quote has aged past the window
+        let all: Vec<CachedPriceData> = book.values().cloned().collect();
+        assert!(compute_consensus("SOL", &all, 5.0, 3).is_some());
+        let recent = recent_quotes(&book, window, Utc::now());
+        assert!(recent.is_empty());
+        assert!(compute_consensus("SOL", &recent, 5.0, 3).is_none());
+    }
+
+    #[test]
+    fn test_future_dated_quote_does_not_evict_others() {
+        let honest = quote("100.00", 60);
+        let future = quote("500.00", -3600);
+        let mut book: HashMap<Uuid, CachedPriceData> = [honest.clone(), future.clone()]
+            .into_iter()
+            .map(|quote| (quote.source_node_id, quote))
+            .collect();
+
+        prune_quotes(&mut book, Duration::seconds(DEFAULT_CONSENSUS_WINDOW_SECS as i64), Utc::now());
+
+        assert!(book.contains_key(&honest.source_node_id));
+        assert!(!book.contains_key(&future.source_node_id));
+        // Small clock drift is tolerated
+        assert!(!is_future_dated(&quote("100.00", -5), Utc::now()));
     }
 }
@@ -291,28 +291,8 @@ impl PriceMonitor {
         };
         
         // Store the notification
-        // For now, we'll store it directly in the database since NotificationService
-        // uses in-memory storage. In production, this would use the service's storage.
-        let client = self.db_pool.get().await.map_err(|e| {
-            Error::Database(format!("Failed to get database connection: {}", e))
-        })?;
-        
-        client
-            .execute(
-                "INSERT INTO notifications (id, user_id, type, title, message, data, priority, read, created_at)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-                &[
-                    &notification.id,
-                    &notification.user_id,
-                    &notification.notification_type,
-                    &notification.title,
-                    &notification.message,
-                    &notification.data,
-                    &notification.priority,
-                    &notification.read,
-                    &notification.created_at,
-                ],
-            )
+        self.notification_service
+            .create_notification(notification)
             .await
             .map_err(|e| Error::Database(format!("Failed to create notification: {}", e)))?;
         
@@ -378,26 +358,8 @@ impl PriceMonitor {
                 created_at: chrono::Utc::now(),
             };
             
-            let client = self.db_pool.get().await.map_err(|e| {
-                Error::Database(format!("Failed to get database connection: {}", e))
-            })?;
-            
-            client
-                .execute(
-                    "INSERT INTO notifications (id, user_id, type, title, message, data, priority, read, created_at)
-                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-                    &[
-                        &notification.id,
-                        &notification.user_id,
-                        &notification.notification_type,
-                        &notification.title,
-                        &notification.message,
-                        &notification.data,
-                        &notification.priority,
-                        &notification.read,
-                        &notification.created_at,
-                    ],
-                )
+            self.notification_service
+                .create_notification(notification)
                 .await
                 .map_err(|e| Error::Database(format!("Failed to create notification: {}", e)))?;
             
@@ -496,26 +458,8 @@ impl PriceMonitor {
         };
         
         // Store the notification
-        let client = self.db_pool.get().await.map_err(|e| {
-            Error::Database(format!("Failed to get database connection: {}", e))
-        })?;
-        
-        client
-            .execute(
-                "INSERT INTO notifications (id, user_id, type, title, message, data, priority, read, created_at)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-                &[
-                    &notification.id,
-                    &notification.user_id,
-                    &notification.notification_type,
-                    &notification.title,
-                    &notification.message,
-                    &notification.data,
-                    &notification.priority,
-                    &notification.read,
-                    &notification.created_at,
-                ],
-            )
+        self.notification_service
+            .create_notification(notification)
             .await
             .map_err(|e| Error::Database(format!("Failed to create notification: {}", e)))?;
         
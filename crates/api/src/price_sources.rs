@@ -0,0 +1,224 @@
+// Price feed adapters for the price data the API already pulls
+//
+// Each adapter implements `shared::price_feed::PriceSource`, so the token
+// valuations in `PriceFeedService` can fall back from one upstream to the next.
+
+use async_trait::async_trait;
+use blockchain::Blockchain;
+use rust_decimal::prelude::ToPrimitive;
+use shared::config::PriceFeedConfig;
+use shared::price_feed::{known_symbol, PriceFeedService, PriceSource, TokenPrice};
+use shared::{Error, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::birdeye_service::BirdeyeService;
+use crate::coinmarketcap_service::CoinMarketCapService;
+use crate::price_cache::PriceCache;
+
+/// Lowest mesh consensus confidence accepted by default
+///
+/// Recent quotes only reach a consensus once a quorum of providers agrees, so
+/// this mainly admits the low-confidence cached price, which a quorum agreed
+/// on before a restart; conflicting providers have no confidence and fail it.
+pub const DEFAULT_MESH_MIN_CONFIDENCE: f64 = 0.3;
+
+/// Extra mint to symbol mappings on top of `known_symbol`
+#[derive(Debug, Clone, Default)]
+struct SymbolMap {
+    overrides: HashMap<String, String>,
+}
+
+impl SymbolMap {
+    fn symbol_for(&self, token_mint: &str) -> Option<String> {
+        self.overrides
+            .get(token_mint)
+            .cloned()
+            .or_else(|| known_symbol(token_mint).map(str::to_string))
+    }
+}
+
+/// Solana token prices from the Birdeye `defi/price` endpoint
+pub struct BirdeyePriceSource {
+    service: Arc<BirdeyeService>,
+}
+
+impl BirdeyePriceSource {
+    pub fn new(service: Arc<BirdeyeService>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl PriceSource for BirdeyePriceSource {
+    fn name(&self) -> &str {
+        "birdeye"
+    }
+
+    async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>> {
+        let price = self
+            .service
+            .get_asset_price(&Blockchain::SOLANA, token_mint)
+            .await
+            .map_err(|e| Error::ExternalService(e.to_string()))?;
+
+        Ok(price.price_usd.to_f64().map(|price_usd| TokenPrice {
+            token_mint: token_mint.to_string(),
+            symbol: known_symbol(token_mint).unwrap_or_default().to_string(),
+            price_usd,
+            last_updated: price.last_updated,
+            source: String::new(),
+        }))
+    }
+}
+
+/// Token prices from CoinMarketCap, which quotes by ticker symbol
+///
+/// Only mints with a known symbol are priced.
+pub struct CoinMarketCapPriceSource {
+    service: Arc<CoinMarketCapService>,
+    symbols: SymbolMap,
+}
+
+impl CoinMarketCapPriceSource {
+    pub fn new(service: Arc<CoinMarketCapService>) -> Self {
+        Self {
+            service,
+            symbols: SymbolMap::default(),
+        }
+    }
+
+    /// Price `token_mint` as the CoinMarketCap ticker `symbol`
+    pub fn with_symbol(mut self, token_mint: impl Into<String>, symbol: impl Into<String>) -> Self {
+        self.symbols.overrides.insert(token_mint.into(), symbol.into());
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinMarketCapPriceSource {
+    fn name(&self) -> &str {
+        "coinmarketcap"
+    }
+
+    async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>> {
+        let Some(symbol) = self.symbols.symbol_for(token_mint) else {
+            return Ok(None);
+        };
+
+        let price = self
+            .service
+            .get_price_by_symbol(&symbol)
+            .await
+            .map_err(|e| Error::ExternalService(e.to_string()))?;
+
+        Ok(price.price_usd.to_f64().map(|price_usd| TokenPrice {
+            token_mint: token_mint.to_string(),
+            symbol: price.symbol,
+            price_usd,
+            last_updated: price.last_updated,
+            source: String::new(),
+        }))
+    }
+}
+
+/// Consensus prices received over the P2P mesh network
+///
+/// Only mints with a known symbol are priced, and consensus below the
+/// minimum confidence is treated as unavailable.
+pub struct MeshPriceSource {
+    price_cache: Arc<PriceCache>,
+    symbols: SymbolMap,
+    min_confidence: f64,
+}
+
+impl MeshPriceSource {
+    pub fn new(price_cache: Arc<PriceCache>) -> Self {
+        Self {
+            price_cache,
+            symbols: SymbolMap::default(),
+            min_confidence: DEFAULT_MESH_MIN_CONFIDENCE,
+        }
+    }
+
+    /// Price `token_mint` from the mesh asset `symbol`
+    pub fn with_symbol(mut self, token_mint: impl Into<String>, symbol: impl Into<String>) -> Self {
+        self.symbols.overrides.insert(token_mint.into(), symbol.into());
+        self
+    }
+
+    /// Ignore consensus prices with a confidence below `min_confidence`
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for MeshPriceSource {
+    fn name(&self) -> &str {
+        "mesh"
+    }
+
+    async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>> {
+        let Some(symbol) = self.symbols.symbol_for(token_mint) else {
+            return Ok(None);
+        };
+
+        let consensus = self
+            .price_cache
+            .consensus(&symbol)
+            .await
+            .map_err(|e| Error::Internal(format!("Mesh price cache error: {}", e)))?;
+
+        Ok(consensus
+            .filter(|consensus| consensus.confidence >= self.min_confidence)
+            .and_then(|consensus| {
+                let price_usd = consensus.price.parse::<f64>().ok()?;
+                Some(TokenPrice {
+                    token_mint: token_mint.to_string(),
+                    symbol,
+                    price_usd,
+                    last_updated: consensus.timestamp,
+                    source: String::new(),
+                })
+            }))
+    }
+}
+
+/// Build the token price feed described by `config`
+///
+/// Sources appear in the configured order; names without a matching
+/// adapter are rejected.
+pub fn price_feed_from_config(
+    config: &PriceFeedConfig,
+    birdeye: Arc<BirdeyeService>,
+    coinmarketcap: Arc<CoinMarketCapService>,
+    mesh_price_cache: Arc<PriceCache>,
+    redis: redis::aio::ConnectionManager,
+) -> Result<PriceFeedService> {
+    let mut price_feed = PriceFeedService::new().with_redis_cache(redis, config.cache_ttl_secs);
+
+    for name in &config.sources {
+        price_feed = match name.as_str() {
+            "birdeye" => price_feed.with_source(
+                Arc::new(BirdeyePriceSource::new(Arc::clone(&birdeye))),
+                Duration::from_secs(config.birdeye_max_age_secs),
+            ),
+            "coinmarketcap" => price_feed.with_source(
+                Arc::new(CoinMarketCapPriceSource::new(Arc::clone(&coinmarketcap))),
+                Duration::from_secs(config.coinmarketcap_max_age_secs),
+            ),
+            "mesh" => price_feed.with_source(
+                Arc::new(MeshPriceSource::new(Arc::clone(&mesh_price_cache))),
+                Duration::from_secs(config.mesh_max_age_secs),
+            ),
+            other => {
+                return Err(Error::Validation(format!("Unknown price feed source: {}", other)))
+            }
+        };
+    }
+
+    Ok(price_feed)
+}
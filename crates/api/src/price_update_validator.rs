@@ -211,6 +211,8 @@ mod tests {
             timestamp: Utc::now(),
             prices,
             ttl: 10,
+            signer: None,
+            signature: None,
         }
     }
 
@@ -307,12 +307,7 @@ impl PrivacyService {
         blockchain: blockchain::Blockchain,
         expires_at: Option<DateTime<Utc>>,
     ) -> Result<TemporaryWallet> {
-        let blockchain_str = match blockchain {
-            blockchain::Blockchain::Solana => "Solana",
-            blockchain::Blockchain::Ethereum => "Ethereum",
-            blockchain::Blockchain::BinanceSmartChain => "BinanceSmartChain",
-            blockchain::Blockchain::Polygon => "Polygon",
-        };
+        let blockchain_str = blockchain.id();
 
         let expires_in_hours = if let Some(exp) = expires_at {
             let duration = exp.signed_duration_since(Utc::now());
@@ -0,0 +1,292 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::mesh_types::PriceUpdate;
+
+/// Ed25519 identity used by a provider node to sign the price updates it originates
+pub struct ProviderIdentity {
+    keypair: Keypair,
+}
+
+impl ProviderIdentity {
+    /// Create an identity with a freshly generated keypair
+    pub fn generate() -> Self {
+        Self::from_keypair(Keypair::new())
+    }
+
+    /// Create an identity from an existing keypair
+    pub fn from_keypair(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Load an identity from a Solana CLI keypair file
+    pub fn from_file(path: &str) -> Result<Self, ApiError> {
+        solana_sdk::signature::read_keypair_file(path)
+            .map(Self::from_keypair)
+            .map_err(|e| {
+                ApiError::ConfigurationError(format!(
+                    "Failed to read provider keypair {}: {}",
+                    path, e
+                ))
+            })
+    }
+
+    /// Public key other nodes use to verify this provider's updates
+    pub fn public_key(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// Stamp the signer and sign the canonical bytes of `update`
+    pub fn sign(&self, update: &mut PriceUpdate) {
+        update.signer = Some(self.public_key().to_string());
+        let signature = self.keypair.sign_message(&update.signing_bytes());
+        update.signature = Some(signature.to_string());
+    }
+}
+
+/// How a receiving node decides which provider keys to accept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderTrustPolicy {
+    /// Only updates signed by one of these keys are accepted
+    TrustedKeys(HashSet<Pubkey>),
+    /// The first valid key seen for a provider node is pinned for that node
+    TrustOnFirstUse,
+}
+
+/// Verifies price update signatures and tracks the key pinned to each provider node
+///
+/// Under either policy, once a `source_node_id` has been seen with a valid
+/// signature its key is pinned, so one provider cannot publish under another
+/// provider's node ID.
+pub struct ProviderKeyStore {
+    policy: ProviderTrustPolicy,
+    pinned: RwLock<HashMap<Uuid, Pubkey>>,
+}
+
+impl ProviderKeyStore {
+    /// Accept only updates signed by one of `keys`
+    pub fn trusted(keys: impl IntoIterator<Item = Pubkey>) -> Self {
+        Self {
+            policy: ProviderTrustPolicy::TrustedKeys(keys.into_iter().collect()),
+            pinned: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Pin each provider node to the first key it signs with
+    pub fn trust_on_first_use() -> Self {
+        Self {
+            policy: ProviderTrustPolicy::TrustOnFirstUse,
+            pinned: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Build a store trusting the given base58 public keys, or pinning keys
+    /// on first use when explicitly enabled
+    ///
+    /// Without either, no received update is accepted: trust on first use
+    /// lets anyone join as a provider, so it is never the default.
+    pub fn from_config(trusted_keys: &[String], trust_on_first_use: bool) -> Result<Self, ApiError> {
+        if trust_on_first_use {
+            if !trusted_keys.is_empty() {
+                return Err(ApiError::ConfigurationError(
+                    "Trusted provider keys and trust on first use are mutually exclusive".to_string(),
+                ));
+            }
+            return Ok(Self::trust_on_first_use());
+        }
+
+        let keys = trusted_keys
+            .iter()
+            .map(|key| {
+                Pubkey::from_str(key.trim()).map_err(|_| {
+                    ApiError::ConfigurationError(format!("Invalid trusted provider key: {}", key))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::trusted(keys))
+    }
+
+    pub fn policy(&self) -> &ProviderTrustPolicy {
+        &self.policy
+    }
+
+    /// Key currently pinned for a provider node, if any
+    pub async fn pinned_key(&self, node_id: &Uuid) -> Option<Pubkey> {
+        self.pinned.read().await.get(node_id).copied()
+    }
+
+    /// Verify that `update` is signed by a key acceptable for its source node
+    pub async fn verify(&self, update: &PriceUpdate) -> Result<(), ApiError> {
+        let (signer, signature) = match (&update.signer, &update.signature) {
+            (Some(signer), Some(signature)) => (signer, signature),
+            _ => {
+                return Err(ApiError::UntrustedPriceSource(format!(
+                    "Update {} from node {} is not signed",
+                    update.message_id, update.source_node_id
+                )))
+            }
+        };
+
+        let signer = Pubkey::from_str(signer).map_err(|_| {
+            ApiError::UntrustedPriceSource(format!("Malformed signer key: {}", signer))
+        })?;
+        let signature = Signature::from_str(signature).map_err(|_| {
+            ApiError::UntrustedPriceSource("Malformed update signature".to_string())
+        })?;
+
+        if !signature.verify(signer.as_ref(), &update.signing_bytes()) {
+            return Err(ApiError::UntrustedPriceSource(format!(
+                "Invalid signature on update {} from node {}",
+                update.message_id, update.source_node_id
+            )));
+        }
+
+        if let ProviderTrustPolicy::TrustedKeys(keys) = &self.policy {
+            if !keys.contains(&signer) {
+                return Err(ApiError::UntrustedPriceSource(format!(
+                    "Signer {} is not a trusted provider",
+                    signer
+                )));
+            }
+        }
+
+        let mut pinned = self.pinned.write().await;
+        match pinned.get(&update.source_node_id) {
+            Some(pinned_key) if *pinned_key != signer => Err(ApiError::UntrustedPriceSource(format!(
+                "Node {} is pinned to key {}, update signed by {}",
+                update.source_node_id, pinned_key, signer
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                tracing::info!(
+                    source_node_id = %update.source_node_id,
+                    signer = %signer,
+                    "Pinned provider key"
+                );
+                pinned.insert(update.source_node_id, signer);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_types::PriceData;
+    use chrono::Utc;
+
+    fn signed_update(identity: &ProviderIdentity, node_id: Uuid) -> PriceUpdate {
+        let mut prices = HashMap::new();
+        for (asset, price) in [("SOL", "100.50"), ("BTC", "45000.00"), ("ETH", "3000.25")] {
+            prices.insert(
+                asset.to_string(),
+                PriceData {
+                    asset: asset.to_string(),
+                    price: price.to_string(),
+                    blockchain: "multi".to_string(),
+                    change_24h: None,
+                },
+            );
+        }
+
+        let mut update = PriceUpdate {
+            message_id: Uuid::new_v4(),
+            source_node_id: node_id,
+            timestamp: Utc::now(),
+            prices,
+            ttl: 10,
+            signer: None,
+            signature: None,
+        };
+        identity.sign(&mut update);
+        update
+    }
+
+    #[tokio::test]
+    async fn test_signature_survives_ttl_decrement_and_wire_round_trip() {
+        let identity = ProviderIdentity::generate();
+        let store = ProviderKeyStore::trusted([identity.public_key()]);
+        let mut update = signed_update(&identity, Uuid::new_v4());
+
+        update.ttl -= 1;
+        let relayed: PriceUpdate =
+            serde_json::from_str(&serde_json::to_string(&update).unwrap()).unwrap();
+
+        assert!(store.verify(&relayed).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_tampered_and_unsigned_updates() {
+        let identity = ProviderIdentity::generate();
+        let store = ProviderKeyStore::trust_on_first_use();
+
+        let mut tampered = signed_update(&identity, Uuid::new_v4());
+        tampered.prices.get_mut("SOL").unwrap().price = "1.00".to_string();
+        assert!(matches!(
+            store.verify(&tampered).await,
+            Err(ApiError::UntrustedPriceSource(_))
+        ));
+
+        let mut unsigned = signed_update(&identity, Uuid::new_v4());
+        unsigned.signature = None;
+        assert!(matches!(
+            store.verify(&unsigned).await,
+            Err(ApiError::UntrustedPriceSource(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trusted_keys_reject_unknown_signer() {
+        let trusted = ProviderIdentity::generate();
+        let rogue = ProviderIdentity::generate();
+        let store = ProviderKeyStore::trusted([trusted.public_key()]);
+
+        assert!(store.verify(&signed_update(&trusted, Uuid::new_v4())).await.is_ok());
+        assert!(store.verify(&signed_update(&rogue, Uuid::new_v4())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trust_on_first_use_pins_node_key() {
+        let original = ProviderIdentity::generate();
+        let impostor = ProviderIdentity::generate();
+        let store = ProviderKeyStore::trust_on_first_use();
+        let node_id = Uuid::new_v4();
+
+        assert!(store.verify(&signed_update(&original, node_id)).await.is_ok());
+        assert_eq!(store.pinned_key(&node_id).await, Some(original.public_key()));
+
+        assert!(store.verify(&signed_update(&impostor, node_id)).await.is_err());
+        assert!(store.verify(&signed_update(&original, node_id)).await.is_ok());
+
+        // The impostor can still publish under its own node ID
+        assert!(store.verify(&signed_update(&impostor, Uuid::new_v4())).await.is_ok());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let key = ProviderIdentity::generate().public_key();
+
+        // Nothing is trusted unless configured
+        assert!(matches!(
+            ProviderKeyStore::from_config(&[], false).unwrap().policy(),
+            ProviderTrustPolicy::TrustedKeys(keys) if keys.is_empty()
+        ));
+        assert!(matches!(
+            ProviderKeyStore::from_config(&[key.to_string()], false).unwrap().policy(),
+            ProviderTrustPolicy::TrustedKeys(keys) if keys.contains(&key)
+        ));
+        assert_eq!(
+            ProviderKeyStore::from_config(&[], true).unwrap().policy(),
+            &ProviderTrustPolicy::TrustOnFirstUse
+        );
+        assert!(ProviderKeyStore::from_config(&[key.to_string()], true).is_err());
+        assert!(ProviderKeyStore::from_config(&["not-a-key".to_string()], false).is_err());
+    }
+}
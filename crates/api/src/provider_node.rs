@@ -13,6 +13,7 @@ use crate::coinmarketcap_service::CoinMarketCapService;
 use crate::coordination_service::CoordinationService;
 use crate::mesh_metrics::MeshMetricsCollector;
 use crate::mesh_types::{PriceData, PriceUpdate};
+use crate::provider_identity::ProviderIdentity;
 
 /// Provider node that fetches price data from CoinMarketCap API and broadcasts to the network
 /// 
@@ -37,6 +38,8 @@ pub struct ProviderNode {
     is_active: Arc<AtomicBool>,
     /// Unique node identifier
     node_id: Uuid,
+    /// Signing identity for originated price updates
+    identity: Arc<ProviderIdentity>,
 }
 
 impl ProviderNode {
@@ -48,12 +51,14 @@ impl ProviderNode {
     /// * `coordination_service` - Service for coordinating fetch timing with other providers
     /// * `metrics` - Metrics collector for tracking provider operations
     /// * `node_id` - Unique identifier for this provider node
+    /// * `identity` - Ed25519 identity used to sign broadcast price updates
     pub fn new(
         coinmarketcap_service: Arc<CoinMarketCapService>,
         peer_manager: Arc<PeerConnectionManager>,
         coordination_service: Arc<CoordinationService>,
         metrics: Arc<MeshMetricsCollector>,
         node_id: Uuid,
+        identity: Arc<ProviderIdentity>,
     ) -> Self {
         Self {
             coinmarketcap_service,
@@ -63,6 +68,7 @@ impl ProviderNode {
             fetch_interval: Duration::from_secs(30),
             is_active: Arc::new(AtomicBool::new(false)),
             node_id,
+            identity,
         }
     }
 
@@ -119,6 +125,7 @@ impl ProviderNode {
         let is_active = Arc::clone(&self.is_active);
         let fetch_interval = self.fetch_interval;
         let node_id = self.node_id;
+        let identity = Arc::clone(&self.identity);
         
         // Spawn background task for fetch loop
         tokio::spawn(async move {
@@ -143,6 +150,7 @@ impl ProviderNode {
                             &peer_manager,
                             &metrics,
                             node_id,
+                            &identity,
                         ).await {
                             error!("Failed to fetch and broadcast: {}", e);
                             
@@ -162,6 +170,7 @@ impl ProviderNode {
                             &peer_manager,
                             &metrics,
                             node_id,
+                            &identity,
                         ).await {
                             error!("Failed to fetch and broadcast: {}", e);
                             
@@ -192,6 +201,7 @@ impl ProviderNode {
         peer_manager: &Arc<PeerConnectionManager>,
         metrics: &Arc<MeshMetricsCollector>,
         node_id: Uuid,
+        identity: &ProviderIdentity,
     ) -> Result<()> {
         debug!("Fetching price data from CoinMarketCap API");
         
@@ -218,8 +228,8 @@ impl ProviderNode {
         
         info!("Fetched {} price data points in {}ms", asset_count, duration_ms);
         
-        // Create price update message
-        let update = Self::create_price_update_static(node_id, prices);
+        // Create signed price update message
+        let update = Self::create_price_update_static(identity, node_id, prices);
         
         // Broadcast to all connected peers
         Self::broadcast_update_static(peer_manager, update).await?;
@@ -246,6 +256,7 @@ impl ProviderNode {
             &self.peer_manager,
             &self.metrics,
             self.node_id,
+            &self.identity,
         ).await
     }
 
@@ -337,19 +348,28 @@ impl ProviderNode {
     /// - Current timestamp
     /// - Price data
     /// - TTL of 10
+    /// - Signature by this provider's identity
     fn create_price_update(&self, prices: HashMap<String, PriceData>) -> PriceUpdate {
-        Self::create_price_update_static(self.node_id, prices)
+        Self::create_price_update_static(&self.identity, self.node_id, prices)
     }
 
-    /// Create a price update message (static version)
-    fn create_price_update_static(node_id: Uuid, prices: HashMap<String, PriceData>) -> PriceUpdate {
-        PriceUpdate {
+    /// Create a signed price update message (static version)
+    fn create_price_update_static(
+        identity: &ProviderIdentity,
+        node_id: Uuid,
+        prices: HashMap<String, PriceData>,
+    ) -> PriceUpdate {
+        let mut update = PriceUpdate {
             message_id: Uuid::new_v4(),
             source_node_id: node_id,
             timestamp: Utc::now(),
             prices,
             ttl: 10,
-        }
+            signer: None,
+            signature: None,
+        };
+        identity.sign(&mut update);
+        update
     }
 
     /// Broadcast price update to all connected peers
@@ -389,6 +409,8 @@ impl ProviderNode {
             timestamp: update.timestamp,
             prices: serde_json::to_value(&update.prices)?,
             ttl: update.ttl,
+            signer: update.signer,
+            signature: update.signature,
         };
         
         let mut success_count = 0;
@@ -434,12 +456,15 @@ mod tests {
             },
         );
         
-        let update = ProviderNode::create_price_update_static(node_id, prices.clone());
+        let identity = ProviderIdentity::generate();
+        let update = ProviderNode::create_price_update_static(&identity, node_id, prices.clone());
         
         assert_eq!(update.source_node_id, node_id);
         assert_eq!(update.ttl, 10);
         assert_eq!(update.prices.len(), 1);
         assert!(update.prices.contains_key("SOL"));
+        assert_eq!(update.signer, Some(identity.public_key().to_string()));
+        assert!(update.signature.is_some());
     }
     
     #[test]
@@ -447,8 +472,10 @@ mod tests {
         let node_id = Uuid::new_v4();
         let prices = HashMap::new();
         
-        let update1 = ProviderNode::create_price_update_static(node_id, prices.clone());
-        let update2 = ProviderNode::create_price_update_static(node_id, prices);
+        let identity = ProviderIdentity::generate();
+        
+        let update1 = ProviderNode::create_price_update_static(&identity, node_id, prices.clone());
+        let update2 = ProviderNode::create_price_update_static(&identity, node_id, prices);
         
         assert_ne!(update1.message_id, update2.message_id);
     }
@@ -1,11 +1,16 @@
 // Proximity API Handlers
 
+use crate::auth::Claims;
 use crate::{ApiError, ApiResult, AppState};
 use axum::{
     extract::{Path, State},
-    Json,
+    http::Method,
+    Extension, Json,
+};
+use proximity::{
+    DiscoveryMethod, DiscoveredPeer, DiscoverySession, ProximityError, TransferRequest,
+    TransferStatus,
 };
-use proximity::{DiscoveryMethod, DiscoveredPeer, DiscoverySession, TransferRequest, TransferStatus};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -131,6 +136,35 @@ pub struct GetTransferHistoryResponse {
     pub total: i64,
 }
 
+// ============================================================================
+// Authorization
+// ============================================================================
+
+/// Reject callers that may not act on behalf of `user_id`
+fn authorize_user(claims: &Claims, user_id: Uuid, method: &Method) -> ApiResult<()> {
+    if claims.can_access_user(user_id, method) {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(
+            "Not authorized to access this user's resources".to_string(),
+        ))
+    }
+}
+
+/// Look up an active transfer request, mapping a missing transfer to 404
+async fn find_transfer(state: &AppState, transfer_id: Uuid) -> ApiResult<TransferRequest> {
+    state
+        .proximity_transfer_service
+        .get_transfer_request(transfer_id)
+        .await
+        .map_err(|e| match e {
+            ProximityError::TransferNotFound(_) => {
+                ApiError::NotFound(format!("Transfer {} not found", transfer_id))
+            }
+            e => ApiError::InternalError(format!("Failed to get transfer: {}", e)),
+        })
+}
+
 // ============================================================================
 // Discovery Endpoints
 // ============================================================================
@@ -140,8 +174,11 @@ pub struct GetTransferHistoryResponse {
 /// **Validates: Requirements 1.1, 1.3**
 pub async fn start_discovery(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<StartDiscoveryRequest>,
 ) -> ApiResult<Json<StartDiscoveryResponse>> {
+    authorize_user(&claims, req.user_id, &Method::POST)?;
+
     let duration = if req.duration_minutes == 0 { 30 } else { req.duration_minutes };
     
     let session = state.proximity_session_manager
@@ -162,8 +199,15 @@ pub async fn start_discovery(
 /// **Validates: Requirements 1.3**
 pub async fn stop_discovery(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<StopDiscoveryRequest>,
 ) -> ApiResult<Json<StopDiscoveryResponse>> {
+    let session = state.proximity_session_manager
+        .get_session(req.session_id)
+        .await
+        .map_err(|_| ApiError::NotFound(format!("Session {} not found", req.session_id)))?;
+    authorize_user(&claims, session.user_id, &Method::POST)?;
+
     state.proximity_session_manager
         .end_session(req.session_id)
         .await
@@ -199,9 +243,12 @@ pub async fn get_discovered_peers(
 /// **Validates: Requirements 17.4**
 pub async fn block_peer(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(_peer_id): Path<String>,
     Json(req): Json<BlockPeerRequest>,
 ) -> ApiResult<Json<BlockPeerResponse>> {
+    authorize_user(&claims, req.user_id, &Method::POST)?;
+
     database::proximity::add_blocked_peer(
         &state.db_pool,
         req.user_id,
@@ -225,8 +272,11 @@ pub async fn block_peer(
 /// **Validates: Requirements 5.3, 5.5**
 pub async fn create_transfer(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<CreateTransferRequest>,
 ) -> ApiResult<Json<CreateTransferResponse>> {
+    authorize_user(&claims, req.sender_user_id, &Method::POST)?;
+
     let transfer = state.proximity_transfer_service
         .create_transfer_request(
             req.sender_user_id,
@@ -247,8 +297,12 @@ pub async fn create_transfer(
 /// **Validates: Requirements 6.4, 7.1**
 pub async fn accept_transfer(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(transfer_id): Path<Uuid>,
 ) -> ApiResult<Json<AcceptTransferResponse>> {
+    let transfer = find_transfer(&state, transfer_id).await?;
+    authorize_user(&claims, transfer.recipient_user_id, &Method::POST)?;
+
     state.proximity_transfer_service
         .accept_transfer(transfer_id)
         .await
@@ -266,9 +320,13 @@ pub async fn accept_transfer(
 /// **Validates: Requirements 6.5**
 pub async fn reject_transfer(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(transfer_id): Path<Uuid>,
     Json(req): Json<RejectTransferRequest>,
 ) -> ApiResult<Json<RejectTransferResponse>> {
+    let transfer = find_transfer(&state, transfer_id).await?;
+    authorize_user(&claims, transfer.recipient_user_id, &Method::POST)?;
+
     state.proximity_transfer_service
         .reject_transfer(transfer_id, req.reason)
         .await
@@ -285,8 +343,14 @@ pub async fn reject_transfer(
 /// **Validates: Requirements 6.3**
 pub async fn get_transfer_status(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     Path(transfer_id): Path<Uuid>,
 ) -> ApiResult<Json<GetTransferStatusResponse>> {
+    let transfer = find_transfer(&state, transfer_id).await?;
+    if !claims.can_access_user(transfer.sender_user_id, &Method::GET) {
+        authorize_user(&claims, transfer.recipient_user_id, &Method::GET)?;
+    }
+
     let status = state.proximity_transfer_service
         .get_transfer_status(transfer_id)
         .await
@@ -304,8 +368,11 @@ pub async fn get_transfer_status(
 /// **Validates: Requirements 10.5, 10.6**
 pub async fn get_transfer_history(
     State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
     axum::extract::Query(query): axum::extract::Query<GetTransferHistoryQuery>,
 ) -> ApiResult<Json<GetTransferHistoryResponse>> {
+    authorize_user(&claims, query.user_id, &Method::GET)?;
+
     let filter = database::proximity::TransferFilter {
         user_id: Some(query.user_id),
         status: None,
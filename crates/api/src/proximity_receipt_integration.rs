@@ -33,7 +33,7 @@ pub async fn create_proximity_receipt(
         currency: receipt_data.currency,
         sender: receipt_data.sender,
         recipient: receipt_data.recipient,
-        blockchain: Blockchain::Solana, // Proximity transfers currently only support Solana
+        blockchain: Blockchain::SOLANA, // Proximity transfers currently only support Solana
     };
 
     // Create the receipt
@@ -81,12 +81,12 @@ mod tests {
             currency: proximity_data.currency.clone(),
             sender: proximity_data.sender.clone(),
             recipient: proximity_data.recipient.clone(),
-            blockchain: Blockchain::Solana,
+            blockchain: Blockchain::SOLANA,
         };
 
         assert_eq!(receipt_data.proximity_transfer_id, Some(proximity_data.proximity_transfer_id));
         assert_eq!(receipt_data.amount, proximity_data.amount);
         assert_eq!(receipt_data.currency, "SOL");
-        assert_eq!(receipt_data.blockchain, Blockchain::Solana);
+        assert_eq!(receipt_data.blockchain, Blockchain::SOLANA);
     }
 }
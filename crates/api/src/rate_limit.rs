@@ -8,7 +8,7 @@ use redis::AsyncCommands;
 use std::sync::Arc;
 use tracing::warn;
 
-use crate::AppState;
+use crate::{auth::Claims, AppState};
 
 /// Rate limit configuration
 #[derive(Clone)]
@@ -32,16 +32,17 @@ pub async fn rate_limit_middleware(
     req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Extract user ID from request (simplified - in production, extract from JWT)
+    // Key on the authenticated subject set by auth_middleware; client-supplied
+    // headers can't be trusted to identify the caller
     let user_id = req
-        .headers()
-        .get("X-User-ID")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("anonymous");
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.sub.clone())
+        .unwrap_or_else(|| "anonymous".to_string());
 
     // Get endpoint path for per-endpoint rate limiting
-    let path = req.uri().path();
-    let rate_limit_key = format!("ratelimit:{}:{}", user_id, path);
+    let path = req.uri().path().to_string();
+    let rate_limit_key = rate_limit_key(&user_id, &path);
 
     // Check rate limit
     let mut redis_conn = state.redis_pool.clone();
@@ -63,6 +64,11 @@ pub async fn rate_limit_middleware(
     }
 }
 
+/// Redis key for a caller's request count on one endpoint
+fn rate_limit_key(user_id: &str, path: &str) -> String {
+    format!("ratelimit:{}:{}", user_id, path)
+}
+
 /// Check rate limit using Redis
 async fn check_rate_limit(
     redis_conn: &mut redis::aio::ConnectionManager,
@@ -104,9 +110,7 @@ mod tests {
 
     #[test]
     fn test_rate_limit_key_format() {
-        let user_id = "user123";
-        let path = "/api/wallets";
-        let key = format!("ratelimit:{}:{}", user_id, path);
+        let key = rate_limit_key("user123", "/api/wallets");
         assert_eq!(key, "ratelimit:user123:/api/wallets");
     }
 }
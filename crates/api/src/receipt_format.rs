@@ -0,0 +1,301 @@
+//! Canonical, signed receipt format that can be verified offline
+//!
+//! A receipt is serialized as compact JSON with its fields in declaration
+//! order, so the same receipt always produces the same bytes. The receipt
+//! hash (the Merkle leaf that is anchored on-chain) is the SHA-256 of those
+//! bytes, and the server signs the same bytes with its Ed25519 receipt key.
+//! [`verify_signed_receipt`] checks a receipt file against the published key
+//! without access to the database.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared::{Error, Result};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::merkle;
+use crate::receipt_service::{InclusionProof, Receipt, ReceiptData};
+
+/// Version tag of the canonical receipt serialization
+pub const RECEIPT_FORMAT_VERSION: &str = "blockchain-receipt/v1";
+
+/// Canonical content of a receipt, the bytes that are hashed and signed
+///
+/// Field order is part of the format; new fields require a new version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptPayload {
+    pub version: String,
+    pub id: Uuid,
+    pub payment_id: Option<Uuid>,
+    pub trade_id: Option<Uuid>,
+    pub conversion_id: Option<Uuid>,
+    pub proximity_transfer_id: Option<Uuid>,
+    /// Amount without trailing zeros, e.g. `"100.5"`
+    pub amount: String,
+    pub currency: String,
+    pub sender: String,
+    pub recipient: String,
+    /// Display name of the chain in the registry, e.g. `"Binance Smart Chain"`
+    pub blockchain: String,
+    /// RFC 3339 in UTC with microseconds, the precision receipts are stored at
+    pub created_at: String,
+}
+
+impl ReceiptPayload {
+    /// Payload of a new receipt on the chain named `chain_name`
+    pub fn new(id: Uuid, data: &ReceiptData, chain_name: &str, created_at: DateTime<Utc>) -> Self {
+        Self {
+            version: RECEIPT_FORMAT_VERSION.to_string(),
+            id,
+            payment_id: data.payment_id,
+            trade_id: data.trade_id,
+            conversion_id: data.conversion_id,
+            proximity_transfer_id: data.proximity_transfer_id,
+            amount: data.amount.normalize().to_string(),
+            currency: data.currency.clone(),
+            sender: data.sender.clone(),
+            recipient: data.recipient.clone(),
+            blockchain: chain_name.to_string(),
+            created_at: created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+
+    /// Deterministic bytes covered by the receipt hash and signature
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("receipt payload serializes")
+    }
+
+    /// `0x`-prefixed SHA-256 of the canonical bytes
+    pub fn hash(&self) -> String {
+        merkle::to_hex(&Sha256::digest(self.canonical_bytes()).into())
+    }
+}
+
+impl From<&Receipt> for ReceiptPayload {
+    /// Payload of a stored receipt, identical to the one it was created with
+    fn from(receipt: &Receipt) -> Self {
+        Self {
+            version: RECEIPT_FORMAT_VERSION.to_string(),
+            id: receipt.id,
+            payment_id: receipt.payment_id,
+            trade_id: receipt.trade_id,
+            conversion_id: receipt.conversion_id,
+            proximity_transfer_id: receipt.proximity_transfer_id,
+            amount: receipt.amount.normalize().to_string(),
+            currency: receipt.currency.clone(),
+            sender: receipt.sender.clone(),
+            recipient: receipt.recipient.clone(),
+            blockchain: receipt.chain_name.clone(),
+            created_at: receipt.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+}
+
+/// A receipt as handed to users and auditors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    pub receipt: ReceiptPayload,
+    pub receipt_hash: String,
+    /// Base58 Ed25519 public key of the server that issued the receipt
+    pub signer: String,
+    /// Base58 Ed25519 signature over the payload's canonical bytes
+    pub signature: String,
+    /// Transaction anchoring the Merkle root, once the receipt's batch is anchored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_transaction: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inclusion_proof: Option<InclusionProof>,
+}
+
+impl SignedReceipt {
+    /// Parse a receipt file
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::Validation(format!("Malformed receipt file: {}", e)))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("signed receipt serializes")
+    }
+}
+
+/// Ed25519 key the server signs receipts with
+pub struct ReceiptSigner {
+    keypair: Keypair,
+}
+
+impl ReceiptSigner {
+    /// Create a signer with a freshly generated keypair
+    pub fn generate() -> Self {
+        Self::from_keypair(Keypair::new())
+    }
+
+    pub fn from_keypair(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Load the signing key from a Solana CLI keypair file
+    pub fn from_file(path: &str) -> Result<Self> {
+        solana_sdk::signature::read_keypair_file(path)
+            .map(Self::from_keypair)
+            .map_err(|e| Error::Internal(format!("Failed to read receipt signing keypair {}: {}", path, e)))
+    }
+
+    /// Key auditors verify receipts against
+    pub fn public_key(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// Base58 signature over the payload's canonical bytes
+    pub fn sign(&self, payload: &ReceiptPayload) -> String {
+        self.keypair.sign_message(&payload.canonical_bytes()).to_string()
+    }
+}
+
+/// Verify a receipt file issued by the holder of `public_key`
+///
+/// Checks the format version, that the receipt hash matches the content,
+/// that the signature is `public_key`'s and, if the receipt carries an
+/// inclusion proof, that the hash leads to the proof's Merkle root. Whether
+/// that root is recorded by `anchor_transaction` has to be checked against
+/// the chain itself.
+pub fn verify_signed_receipt(receipt: &SignedReceipt, public_key: &str) -> Result<()> {
+    if receipt.receipt.version != RECEIPT_FORMAT_VERSION {
+        return Err(Error::Validation(format!(
+            "Unsupported receipt format: {}",
+            receipt.receipt.version
+        )));
+    }
+
+    if receipt.receipt.hash() != receipt.receipt_hash {
+        return Err(Error::Validation("Receipt hash does not match its content".to_string()));
+    }
+
+    if receipt.signer != public_key {
+        return Err(Error::Validation(format!(
+            "Receipt is signed by {}, not {}",
+            receipt.signer, public_key
+        )));
+    }
+    let signer = Pubkey::from_str(public_key)
+        .map_err(|_| Error::Validation(format!("Malformed receipt signing key: {}", public_key)))?;
+    let signature = Signature::from_str(&receipt.signature)
+        .map_err(|e| Error::Validation(format!("Malformed receipt signature: {}", e)))?;
+    if !signature.verify(signer.as_ref(), &receipt.receipt.canonical_bytes()) {
+        return Err(Error::Validation("Invalid receipt signature".to_string()));
+    }
+
+    if let Some(proof) = &receipt.inclusion_proof {
+        if !proof.verifies(&receipt.receipt_hash) {
+            return Err(Error::Validation(
+                "Inclusion proof does not lead to its Merkle root".to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::MerkleTree;
+    use blockchain::Blockchain;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+
+    fn payload() -> ReceiptPayload {
+        let data = ReceiptData {
+            payment_id: None,
+            trade_id: Some(Uuid::from_u128(7)),
+            conversion_id: None,
+            proximity_transfer_id: None,
+            amount: Decimal::new(100500, 3),
+            currency: "SOL".to_string(),
+            sender: "sender".to_string(),
+            recipient: "recipient".to_string(),
+            blockchain: Blockchain::SOLANA,
+        };
+        ReceiptPayload::new(Uuid::nil(), &data, "Solana", Utc.timestamp_opt(1_700_000_000, 123_456_000).unwrap())
+    }
+
+    fn signed(signer: &ReceiptSigner, payload: ReceiptPayload) -> SignedReceipt {
+        SignedReceipt {
+            receipt_hash: payload.hash(),
+            signer: signer.public_key().to_string(),
+            signature: signer.sign(&payload),
+            receipt: payload,
+            anchor_transaction: None,
+            inclusion_proof: None,
+        }
+    }
+
+    #[test]
+    fn test_canonical_bytes_are_stable() {
+        assert_eq!(
+            String::from_utf8(payload().canonical_bytes()).unwrap(),
+            concat!(
+                r#"{"version":"blockchain-receipt/v1","id":"00000000-0000-0000-0000-000000000000","#,
+                r#""payment_id":null,"trade_id":"00000000-0000-0000-0000-000000000007","#,
+                r#""conversion_id":null,"proximity_transfer_id":null,"amount":"100.5","#,
+                r#""currency":"SOL","sender":"sender","recipient":"recipient","blockchain":"Solana","#,
+                r#""created_at":"2023-11-14T22:13:20.123456Z"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_receipt_file_round_trip_verifies() {
+        let signer = ReceiptSigner::generate();
+        let mut receipt = signed(&signer, payload());
+
+        let leaves = [merkle::from_hex(&receipt.receipt_hash).unwrap(), [7u8; 32]];
+        let tree = MerkleTree::new(&leaves).unwrap();
+        receipt.inclusion_proof = Some(InclusionProof {
+            anchor_id: Uuid::nil(),
+            merkle_root: merkle::to_hex(&tree.root()),
+            leaf_index: 0,
+            leaf_count: 2,
+            siblings: tree.proof(0).unwrap().iter().map(merkle::to_hex).collect(),
+        });
+        receipt.anchor_transaction = Some("anchor-tx".to_string());
+
+        let file = SignedReceipt::from_json(&receipt.to_json()).unwrap();
+        assert_eq!(file, receipt);
+        assert!(verify_signed_receipt(&file, &signer.public_key().to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_tampered_or_foreign_receipts() {
+        let signer = ReceiptSigner::generate();
+        let key = signer.public_key().to_string();
+        let receipt = signed(&signer, payload());
+
+        let mut tampered = receipt.clone();
+        tampered.receipt.amount = "1000.5".to_string();
+        assert!(verify_signed_receipt(&tampered, &key).is_err());
+
+        // Rehashing the tampered content does not help without the key
+        tampered.receipt_hash = tampered.receipt.hash();
+        assert!(verify_signed_receipt(&tampered, &key).is_err());
+
+        let foreign = signed(&ReceiptSigner::generate(), payload());
+        assert!(verify_signed_receipt(&foreign, &key).is_err());
+
+        let mut bad_proof = receipt.clone();
+        bad_proof.inclusion_proof = Some(InclusionProof {
+            anchor_id: Uuid::nil(),
+            merkle_root: payload().hash(),
+            leaf_index: 0,
+            leaf_count: 1,
+            siblings: Vec::new(),
+        });
+        assert!(verify_signed_receipt(&bad_proof, &key).is_err());
+
+        let mut unknown_version = receipt;
+        unknown_version.receipt.version = "blockchain-receipt/v0".to_string();
+        assert!(verify_signed_receipt(&unknown_version, &key).is_err());
+    }
+}
@@ -1,12 +1,37 @@
-use blockchain::{Blockchain, MultiChainClient};
-use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use blockchain::{
+    Blockchain, ChainRegistry, EvmSigner, EvmTransactionBuilder, MultiChainClient, NonceManager, SolanaClient,
+    TransactionStatus,
+};
+use chrono::{DateTime, SubsecRound, Utc};
 use database::DbPool;
+use serde::{Deserialize, Serialize};
 use shared::{Error, Result};
+use solana_sdk::signature::{Keypair, Signature};
+use std::str::FromStr;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tokio_postgres::Row;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::merkle::{self, MerkleTree};
+use crate::receipt_format::{ReceiptPayload, ReceiptSigner, SignedReceipt, RECEIPT_FORMAT_VERSION};
+
+/// Most receipts anchored under one Merkle root
+pub const DEFAULT_MAX_BATCH_SIZE: i64 = 1024;
+
+/// Pending anchors older than this are assumed abandoned and resubmitted
+const STALE_ANCHOR_MINUTES: i64 = 10;
+
+const RECEIPT_SELECT: &str = "SELECT r.id, r.payment_id, r.trade_id, r.conversion_id, r.proximity_transfer_id, \
+     r.amount, r.currency, r.sender, r.recipient, r.blockchain, r.transaction_hash, r.verification_status, \
+     r.created_at, r.verified_at, r.receipt_hash, r.format_version, r.signer, r.signature, \
+     r.leaf_index, r.merkle_proof, \
+     a.id AS anchor_id, a.merkle_root, a.leaf_count \
+     FROM blockchain_receipts r \
+     LEFT JOIN receipt_anchors a ON a.id = r.anchor_id AND a.status = 'ANCHORED'";
+
 /// Verification status for blockchain receipts
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VerificationStatus {
@@ -25,6 +50,62 @@ impl VerificationStatus {
     }
 }
 
+/// How receipt hashes are put on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchoringMode {
+    /// Every receipt is anchored in its own transaction as it is created
+    Immediate,
+    /// Receipts are queued and anchored periodically, one Merkle root per
+    /// batch and chain
+    Batched,
+}
+
+impl FromStr for AnchoringMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "immediate" => Ok(AnchoringMode::Immediate),
+            "batched" | "batch" => Ok(AnchoringMode::Batched),
+            other => Err(Error::Validation(format!("Unknown receipt anchoring mode: {}", other))),
+        }
+    }
+}
+
+/// Proof that a receipt hash is a leaf of an anchored Merkle root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub anchor_id: Uuid,
+    pub merkle_root: String,
+    pub leaf_index: i32,
+    pub leaf_count: i32,
+    /// Sibling hashes from the leaf up to the root
+    pub siblings: Vec<String>,
+}
+
+impl InclusionProof {
+    /// Recompute the root from `receipt_hash` and check it is this proof's root
+    pub fn verifies(&self, receipt_hash: &str) -> bool {
+        let Some(leaf) = merkle::from_hex(receipt_hash) else {
+            return false;
+        };
+        let Some(siblings) = self
+            .siblings
+            .iter()
+            .map(|s| merkle::from_hex(s))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+        let (Ok(index), Ok(count)) = (usize::try_from(self.leaf_index), usize::try_from(self.leaf_count)) else {
+            return false;
+        };
+
+        merkle::root_from_proof(&leaf, index, count, &siblings)
+            .is_some_and(|root| merkle::to_hex(&root) == self.merkle_root)
+    }
+}
+
 /// Blockchain receipt data structure
 #[derive(Debug, Clone)]
 pub struct Receipt {
@@ -38,10 +119,43 @@ pub struct Receipt {
     pub sender: String,
     pub recipient: String,
     pub blockchain: Blockchain,
-    pub transaction_hash: String,
+    /// Chain name the receipt was stored and signed under
+    pub chain_name: String,
+    /// Anchor transaction; `None` while the receipt is queued for a batch
+    pub transaction_hash: Option<String>,
     pub verification_status: VerificationStatus,
     pub created_at: DateTime<Utc>,
     pub verified_at: Option<DateTime<Utc>>,
+    /// SHA-256 of the receipt fields, the leaf that gets anchored; `None` for
+    /// receipts created before batching
+    pub receipt_hash: Option<String>,
+    /// Canonical format the receipt hash and signature cover; `None` for
+    /// receipts created before the signed format
+    pub format_version: Option<String>,
+    /// Base58 key and signature of the server that issued the receipt
+    pub signer: Option<String>,
+    pub signature: Option<String>,
+    /// Set once the receipt's batch is anchored
+    pub inclusion_proof: Option<InclusionProof>,
+}
+
+impl Receipt {
+    /// The receipt file handed to users and auditors, `None` for receipts
+    /// created before the signed format
+    pub fn signed(&self) -> Option<SignedReceipt> {
+        if self.format_version.as_deref() != Some(RECEIPT_FORMAT_VERSION) {
+            return None;
+        }
+
+        Some(SignedReceipt {
+            receipt: ReceiptPayload::from(self),
+            receipt_hash: self.receipt_hash.clone()?,
+            signer: self.signer.clone()?,
+            signature: self.signature.clone()?,
+            anchor_transaction: self.inclusion_proof.as_ref().and(self.transaction_hash.clone()),
+            inclusion_proof: self.inclusion_proof.clone(),
+        })
+    }
 }
 
 /// Data for creating a new receipt
@@ -58,112 +172,426 @@ pub struct ReceiptData {
     pub blockchain: Blockchain,
 }
 
+/// Puts Merkle roots on-chain and reads them back
+#[async_trait]
+pub trait ReceiptAnchor: Send + Sync {
+    /// Record `merkle_root` on `blockchain`, returning the transaction hash
+    async fn anchor(&self, blockchain: &Blockchain, merkle_root: &str) -> Result<String>;
+
+    /// The root recorded by an anchor transaction, or `None` if there is no
+    /// such transaction
+    async fn anchored_root(&self, blockchain: &Blockchain, transaction_hash: &str) -> Result<Option<String>>;
+}
+
+/// Memo prefix Solana anchor transactions record before the Merkle root
+pub const ANCHOR_MEMO_PREFIX: &str = "receipt-root:";
+
+/// Anchor that records each Merkle root in a transaction from a service
+/// wallet: an SPL Memo on Solana, or the calldata of a zero-value
+/// self-transfer on EVM chains
+///
+/// Reading a root back fetches the transaction from the chain and decodes
+/// the root from it. Chains without a configured wallet cannot be anchored
+/// on, but their existing anchors can still be verified.
+pub struct OnChainReceiptAnchor {
+    blockchain_client: Arc<MultiChainClient>,
+    solana_payer: Option<Arc<Keypair>>,
+    evm_signer: Option<Arc<EvmSigner>>,
+    evm_nonces: Arc<NonceManager>,
+}
+
+impl OnChainReceiptAnchor {
+    pub fn new(blockchain_client: Arc<MultiChainClient>) -> Self {
+        Self {
+            blockchain_client,
+            solana_payer: None,
+            evm_signer: None,
+            evm_nonces: Arc::new(NonceManager::new()),
+        }
+    }
+
+    /// Pay for Solana anchor transactions from `payer`
+    pub fn with_solana_payer(mut self, payer: Keypair) -> Self {
+        self.solana_payer = Some(Arc::new(payer));
+        self
+    }
+
+    /// Send EVM anchor transactions from `signer`'s address
+    pub fn with_evm_signer(mut self, signer: EvmSigner) -> Self {
+        self.evm_signer = Some(Arc::new(signer));
+        self
+    }
+
+    fn not_configured(blockchain: &Blockchain) -> Error {
+        Error::Internal(format!("Blockchain client not configured for {}", blockchain))
+    }
+
+    fn no_wallet(blockchain: &Blockchain) -> Error {
+        Error::Internal(format!("No receipt anchor wallet configured for {}", blockchain))
+    }
+
+    async fn anchor_on_solana(&self, blockchain: &Blockchain, merkle_root: &str) -> Result<String> {
+        let client = self
+            .blockchain_client
+            .solana()
+            .ok_or_else(|| Self::not_configured(blockchain))?;
+        let payer = self.solana_payer.as_ref().ok_or_else(|| Self::no_wallet(blockchain))?;
+
+        let blockhash = client.get_latest_blockhash().await?;
+        let memo = format!("{}{}", ANCHOR_MEMO_PREFIX, merkle_root.to_lowercase());
+        let transaction = SolanaClient::sign_memo(payer, &memo, blockhash);
+        let signature = client.send_transaction(&transaction).await?;
+        Ok(signature.to_string())
+    }
+
+    async fn anchor_on_evm(&self, blockchain: &Blockchain, merkle_root: &str) -> Result<String> {
+        let client = self
+            .blockchain_client
+            .evm_shared(blockchain)
+            .ok_or_else(|| Self::not_configured(blockchain))?;
+        let signer = self.evm_signer.as_ref().ok_or_else(|| Self::no_wallet(blockchain))?;
+        let root = merkle::from_hex(merkle_root)
+            .ok_or_else(|| Error::Validation(format!("Invalid Merkle root: {}", merkle_root)))?;
+
+        let builder = EvmTransactionBuilder::new(client.clone())
+            .with_nonce_manager(self.evm_nonces.clone());
+        let transaction = builder
+            .build(signer.address(), Some(signer.address()), 0, merkle::to_hex(&root))
+            .await?;
+        Ok(builder.sign_and_send(&transaction, signer).await?.hash)
+    }
+
+    /// Outcome of an anchor transaction, or an error until it has the
+    /// confirmations its chain requires
+    async fn require_final(&self, blockchain: &Blockchain, transaction_hash: &str) -> Result<TransactionStatus> {
+        self.blockchain_client
+            .get_final_transaction_status(blockchain, transaction_hash)
+            .await?
+            .ok_or_else(|| {
+                Error::Internal(format!(
+                    "Anchor transaction {} is not final yet",
+                    transaction_hash
+                ))
+            })
+    }
+
+    async fn solana_root(&self, blockchain: &Blockchain, transaction_hash: &str) -> Result<Option<String>> {
+        let client = self
+            .blockchain_client
+            .solana()
+            .ok_or_else(|| Self::not_configured(blockchain))?;
+        let Ok(signature) = Signature::from_str(transaction_hash) else {
+            return Ok(None);
+        };
+        self.require_final(blockchain, transaction_hash).await?;
+
+        match client.get_transaction_memos(&signature).await? {
+            None => Err(Error::Internal(format!(
+                "Anchor transaction {} is not confirmed yet",
+                transaction_hash
+            ))),
+            Some(Err(_)) => Ok(None),
+            Some(Ok(memos)) => Ok(memos.iter().find_map(|memo| {
+                let root = memo.strip_prefix(ANCHOR_MEMO_PREFIX)?;
+                merkle::from_hex(root).map(|root| merkle::to_hex(&root))
+            })),
+        }
+    }
+
+    async fn evm_root(&self, blockchain: &Blockchain, transaction_hash: &str) -> Result<Option<String>> {
+        let client = self
+            .blockchain_client
+            .evm(blockchain)
+            .ok_or_else(|| Self::not_configured(blockchain))?;
+        if client.validate_transaction_hash(transaction_hash).is_err() {
+            return Ok(None);
+        }
+
+        if self.require_final(blockchain, transaction_hash).await? != TransactionStatus::Success {
+            return Ok(None);
+        }
+        let Some(receipt) = client.get_transaction_receipt(transaction_hash).await? else {
+            return Err(Error::Internal(format!(
+                "Anchor transaction {} is not mined yet",
+                transaction_hash
+            )));
+        };
+        // Only transactions from the anchor wallet count as anchors
+        if let Some(signer) = &self.evm_signer {
+            if !receipt.from.eq_ignore_ascii_case(signer.address()) {
+                return Ok(None);
+            }
+        }
+
+        let input = client.get_transaction_input(transaction_hash).await?;
+        Ok(input
+            .as_deref()
+            .and_then(merkle::from_hex)
+            .map(|root| merkle::to_hex(&root)))
+    }
+}
+
+#[async_trait]
+impl ReceiptAnchor for OnChainReceiptAnchor {
+    async fn anchor(&self, blockchain: &Blockchain, merkle_root: &str) -> Result<String> {
+        if blockchain.is_solana() {
+            self.anchor_on_solana(blockchain, merkle_root).await
+        } else {
+            self.anchor_on_evm(blockchain, merkle_root).await
+        }
+    }
+
+    /// Roots of transactions still in flight are an error, so the receipts
+    /// stay pending rather than failing verification
+    async fn anchored_root(&self, blockchain: &Blockchain, transaction_hash: &str) -> Result<Option<String>> {
+        if blockchain.is_solana() {
+            self.solana_root(blockchain, transaction_hash).await
+        } else {
+            self.evm_root(blockchain, transaction_hash).await
+        }
+    }
+}
+
+/// Hash of a receipt stored before the versioned format
+///
+/// Hash format: SHA-256(amount|currency|timestamp|sender|recipient|id)
+fn legacy_receipt_hash(receipt: &Receipt) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let source_id = receipt
+        .payment_id
+        .or(receipt.trade_id)
+        .or(receipt.conversion_id)
+        .or(receipt.proximity_transfer_id)?;
+    let receipt_data = format!(
+        "{}|{}|{}|{}|{}|{}",
+        receipt.amount.normalize(),
+        receipt.currency,
+        receipt.created_at.timestamp(),
+        receipt.sender,
+        receipt.recipient,
+        source_id
+    );
+    Some(merkle::to_hex(&Sha256::digest(receipt_data.as_bytes()).into()))
+}
+
 /// Blockchain receipt service
 /// 
 /// Handles creation and verification of blockchain-based receipts for payments,
 /// trades, and conversions. Generates SHA-256 hashes of transaction data and
-/// submits them to the blockchain for immutable proof.
+/// anchors them on the blockchain for immutable proof, either one transaction
+/// per receipt or one Merkle root per batch of receipts.
 pub struct ReceiptService {
     db: DbPool,
-    blockchain_client: Arc<MultiChainClient>,
+    signer: Arc<ReceiptSigner>,
+    anchor: Arc<dyn ReceiptAnchor>,
+    chains: ChainRegistry,
+    mode: AnchoringMode,
+    max_batch_size: i64,
 }
 
 impl ReceiptService {
-    /// Create a new receipt service
+    /// Create a new receipt service that anchors each receipt immediately
+    ///
+    /// Receipts are signed with a key generated for this instance; use
+    /// [`Self::with_signer`] to sign with a key that can be published.
+    /// Anchors are read from the chain, but submitting them needs an
+    /// [`OnChainReceiptAnchor`] with wallets passed to [`Self::with_anchor`].
     pub fn new(db: DbPool, blockchain_client: Arc<MultiChainClient>) -> Self {
         info!("Initializing blockchain receipt service");
         Self {
             db,
-            blockchain_client,
+            signer: Arc::new(ReceiptSigner::generate()),
+            chains: blockchain_client.registry().clone(),
+            anchor: Arc::new(OnChainReceiptAnchor::new(blockchain_client)),
+            mode: AnchoringMode::Immediate,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         }
     }
 
+    /// Sign receipts with `signer`
+    pub fn with_signer(mut self, signer: Arc<ReceiptSigner>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Use `anchor` to put receipt hashes on-chain
+    pub fn with_anchor(mut self, anchor: Arc<dyn ReceiptAnchor>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Anchor receipts as they are created or in periodic batches; batched
+    /// receipts are only anchored by [`Self::anchor_pending_receipts`]
+    pub fn with_anchoring_mode(mut self, mode: AnchoringMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Anchor at most `max_batch_size` receipts under one root
+    pub fn with_max_batch_size(mut self, max_batch_size: i64) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    pub fn anchoring_mode(&self) -> AnchoringMode {
+        self.mode
+    }
+
+    /// Key receipts are signed with, for auditors to verify receipt files
+    pub fn signing_key(&self) -> solana_sdk::pubkey::Pubkey {
+        self.signer.public_key()
+    }
+
     /// Create a blockchain receipt for a payment, trade, or conversion
     /// 
     /// This method:
-    /// 1. Generates a SHA-256 hash of the transaction data
-    /// 2. Submits the hash to the specified blockchain
-    /// 3. Stores the receipt with transaction hash in the database
+    /// 1. Hashes and signs the canonical serialization of the receipt
+    /// 2. In immediate mode, submits the hash to the specified blockchain
+    /// 3. Stores the receipt in the database, queued for the next batch in
+    ///    batched mode
     /// 4. Links the receipt to the source transaction
     pub async fn create_receipt(&self, data: ReceiptData) -> Result<Receipt> {
+        let chain_name = self.chains.require(&data.blockchain)?.name.clone();
         info!(
             "Creating blockchain receipt on {} for amount: {} {}",
-            data.blockchain.name(),
+            chain_name,
             data.amount,
             data.currency
         );
 
-        // Generate SHA-256 hash of receipt data
-        let receipt_hash = self.generate_receipt_hash(&data)?;
+        if data.payment_id.is_none()
+            && data.trade_id.is_none()
+            && data.conversion_id.is_none()
+            && data.proximity_transfer_id.is_none()
+        {
+            return Err(Error::Internal("Receipt must have at least one source ID".to_string()));
+        }
+
+        // Round to the stored precision so the payload can be rebuilt from the row
+        let data = ReceiptData {
+            amount: data
+                .amount
+                .round_dp_with_strategy(18, rust_decimal::RoundingStrategy::MidpointAwayFromZero),
+            ..data
+        };
+        let id = Uuid::new_v4();
+        let created_at = Utc::now().trunc_subsecs(6);
+
+        let payload = ReceiptPayload::new(id, &data, &chain_name, created_at);
+        let receipt_hash = payload.hash();
+        let signature = self.signer.sign(&payload);
         debug!("Generated receipt hash: {}", receipt_hash);
 
-        // Submit hash to blockchain with retry logic
-        let transaction_hash = self.submit_to_blockchain(&data.blockchain, &receipt_hash).await?;
-        info!(
-            "Receipt hash submitted to {} blockchain: {}",
-            data.blockchain.name(),
-            transaction_hash
-        );
+        let anchored = match self.mode {
+            AnchoringMode::Immediate => {
+                let leaf = merkle::from_hex(&receipt_hash)
+                    .ok_or_else(|| Error::Internal("Malformed receipt hash".to_string()))?;
+                let tree = MerkleTree::new(&[leaf])
+                    .ok_or_else(|| Error::Internal("Empty receipt batch".to_string()))?;
+                let merkle_root = merkle::to_hex(&tree.root());
 
-        // Store receipt in database
-        let receipt = self.store_receipt(data, transaction_hash).await?;
-        info!("Receipt stored in database with ID: {}", receipt.id);
+                // Submit hash to blockchain with retry logic
+                let transaction_hash = self.submit_to_blockchain(&data.blockchain, &merkle_root).await?;
+                info!(
+                    "Receipt hash submitted to {} blockchain: {}",
+                    chain_name,
+                    transaction_hash
+                );
+                Some((merkle_root, transaction_hash))
+            }
+            AnchoringMode::Batched => None,
+        };
 
-        Ok(receipt)
-    }
+        let mut client = self.db.get().await.map_err(|e| {
+            error!("Failed to get database connection: {}", e);
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
 
-    /// Generate SHA-256 hash of receipt data
-    /// 
-    /// Hash format: SHA-256(amount|currency|timestamp|sender|recipient|id)
-    fn generate_receipt_hash(&self, data: &ReceiptData) -> Result<String> {
-        use sha2::{Digest, Sha256};
-
-        let timestamp = Utc::now().timestamp();
-        
-        // Determine which ID to use (payment, trade, conversion, or proximity transfer)
-        let id = data
-            .payment_id
-            .or(data.trade_id)
-            .or(data.conversion_id)
-            .or(data.proximity_transfer_id)
-            .ok_or_else(|| {
-                Error::Internal("Receipt must have at least one source ID".to_string())
-            })?;
+        let (anchor_id, leaf_index, merkle_proof, transaction_hash) = match anchored {
+            Some((merkle_root, transaction_hash)) => {
+                let anchor_id = Uuid::new_v4();
+                transaction
+                    .execute(
+                        "INSERT INTO receipt_anchors
+                             (id, blockchain, merkle_root, leaf_count, transaction_hash, status, anchored_at)
+                         VALUES ($1, $2, $3, 1, $4, 'ANCHORED', NOW())",
+                        &[&anchor_id, &chain_name, &merkle_root, &transaction_hash],
+                    )
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to store receipt anchor: {}", e)))?;
+                (Some(anchor_id), Some(0i32), Some(Vec::<String>::new()), Some(transaction_hash))
+            }
+            None => (None, None, None, None),
+        };
 
-        // Concatenate fields with pipe separator
-        let receipt_data = format!(
-            "{}|{}|{}|{}|{}|{}",
-            data.amount, data.currency, timestamp, data.sender, data.recipient, id
-        );
+        transaction
+            .execute(
+                r#"
+                INSERT INTO blockchain_receipts (
+                    id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
+                    sender, recipient, blockchain, transaction_hash,
+                    verification_status, created_at, receipt_hash, format_version, signer, signature,
+                    anchor_id, leaf_index, merkle_proof
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'PENDING', $12, $13, $14, $15, $16,
+                        $17, $18, $19)
+                "#,
+                &[
+                    &id,
+                    &data.payment_id,
+                    &data.trade_id,
+                    &data.conversion_id,
+                    &data.proximity_transfer_id,
+                    &data.amount,
+                    &data.currency,
+                    &data.sender,
+                    &data.recipient,
+                    &chain_name,
+                    &transaction_hash,
+                    &std::time::SystemTime::from(created_at),
+                    &receipt_hash,
+                    &RECEIPT_FORMAT_VERSION,
+                    &self.signer.public_key().to_string(),
+                    &signature,
+                    &anchor_id,
+                    &leaf_index,
+                    &merkle_proof,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to store receipt in database: {:?}", e);
+                Error::Database(format!("Failed to store receipt: {:?}", e))
+            })?;
 
-        // Generate SHA-256 hash
-        let mut hasher = Sha256::new();
-        hasher.update(receipt_data.as_bytes());
-        let hash_bytes = hasher.finalize();
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+        info!("Receipt stored in database with ID: {}", id);
 
-        // Convert to hex string
-        let hash_hex = format!("0x{}", hex::encode(hash_bytes));
-        Ok(hash_hex)
+        self.get_receipt(id).await
     }
 
-    /// Submit receipt hash to blockchain with retry logic
+    /// Submit a Merkle root to blockchain with retry logic
     /// 
-    /// Attempts to submit the hash up to 3 times with exponential backoff
-    async fn submit_to_blockchain(
-        &self,
-        blockchain: &Blockchain,
-        receipt_hash: &str,
-    ) -> Result<String> {
+    /// Attempts to submit the root up to 3 times with exponential backoff
+    async fn submit_to_blockchain(&self, blockchain: &Blockchain, merkle_root: &str) -> Result<String> {
         const MAX_RETRIES: u32 = 3;
         let mut retry_count = 0;
 
         loop {
-            match self.try_submit_to_blockchain(blockchain, receipt_hash).await {
+            match self.anchor.anchor(blockchain, merkle_root).await {
                 Ok(tx_hash) => {
                     if retry_count > 0 {
                         info!(
-                            "Successfully submitted receipt to {} after {} retries",
-                            blockchain.name(),
+                            "Successfully submitted receipt root to {} after {} retries",
+                            blockchain,
                             retry_count
                         );
                     }
@@ -173,8 +601,8 @@ impl ReceiptService {
                     retry_count += 1;
                     if retry_count >= MAX_RETRIES {
                         error!(
-                            "Failed to submit receipt to {} after {} attempts: {}",
-                            blockchain.name(),
+                            "Failed to submit receipt root to {} after {} attempts: {}",
+                            blockchain,
                             MAX_RETRIES,
                             e
                         );
@@ -187,7 +615,7 @@ impl ReceiptService {
                     warn!(
                         "Blockchain submission attempt {} failed for {}: {}. Retrying...",
                         retry_count,
-                        blockchain.name(),
+                        blockchain,
                         e
                     );
 
@@ -199,100 +627,265 @@ impl ReceiptService {
         }
     }
 
-    /// Attempt to submit receipt hash to blockchain (single attempt)
-    async fn try_submit_to_blockchain(
-        &self,
-        blockchain: &Blockchain,
-        receipt_hash: &str,
-    ) -> Result<String> {
-        // Get the appropriate blockchain client
-        let _client = self
-            .blockchain_client
-            .get_client(*blockchain)
-            .ok_or_else(|| {
-                Error::Internal(format!(
-                    "Blockchain client not configured for {}",
-                    blockchain.name()
-                ))
-            })?;
+    /// Anchor every queued receipt, one Merkle root per batch and chain
+    ///
+    /// Anchors left pending by an interrupted run are resubmitted first.
+    /// A batch whose submission fails is marked failed and its receipts are
+    /// queued again. Returns the number of receipts anchored.
+    pub async fn anchor_pending_receipts(&self) -> Result<usize> {
+        let mut anchored = self.resubmit_stale_anchors().await?;
 
-        // For this implementation, we'll create a simple data transaction
-        // In a real implementation, this would create a proper transaction
-        // with the receipt hash embedded in the transaction data
-        
-        // Note: This is a simplified implementation
-        // In production, you would:
-        // 1. Create a proper transaction with the hash in the data field
-        // 2. Sign the transaction with a service wallet
-        // 3. Submit the signed transaction
-        
-        // For now, we'll simulate by validating the blockchain is available
-        // and return a mock transaction hash
-        // The actual implementation would use client.submit_transaction()
-        
-        match blockchain {
-            Blockchain::Solana => {
-                // Solana implementation would go here
-                // For now, return a simulated transaction hash
-                Ok(format!("solana_tx_{}", &receipt_hash[2..18]))
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let chains: Vec<String> = client
+            .query(
+                "SELECT DISTINCT blockchain FROM blockchain_receipts
+                 WHERE anchor_id IS NULL AND receipt_hash IS NOT NULL",
+                &[],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch queued receipts: {}", e)))?
+            .iter()
+            .map(|row| row.get("blockchain"))
+            .collect();
+        drop(client);
+
+        for chain in chains {
+            anchored += self.anchor_chain_receipts(&self.chains.resolve(&chain)?).await?;
+        }
+
+        Ok(anchored)
+    }
+
+    /// Anchor the receipts queued on one chain, in batches of at most
+    /// `max_batch_size`
+    pub async fn anchor_chain_receipts(&self, blockchain: &Blockchain) -> Result<usize> {
+        let chain = self.chains.require(blockchain)?.name.as_str();
+        let mut anchored = 0;
+
+        while let Some((anchor_id, merkle_root, leaf_count)) = self.build_batch(chain).await? {
+            match self.submit_to_blockchain(blockchain, &merkle_root).await {
+                Ok(transaction_hash) => {
+                    self.mark_anchored(anchor_id, &transaction_hash).await?;
+                    info!(
+                        "Anchored {} receipts on {} under root {} in {}",
+                        leaf_count, chain, merkle_root, transaction_hash
+                    );
+                    anchored += leaf_count as usize;
+                }
+                Err(e) => {
+                    error!("Failed to anchor receipt batch {}: {}", anchor_id, e);
+                    self.mark_failed(anchor_id).await?;
+                    break;
+                }
             }
-            Blockchain::Ethereum | Blockchain::BinanceSmartChain | Blockchain::Polygon => {
-                // EVM chains implementation
-                // In production, this would create and submit a real transaction
-                // For now, return a simulated transaction hash
-                Ok(format!("evm_tx_{}", &receipt_hash[2..18]))
+
+            if i64::from(leaf_count) < self.max_batch_size {
+                break;
             }
         }
+
+        Ok(anchored)
     }
 
-    /// Store receipt in database
-    async fn store_receipt(
-        &self,
-        data: ReceiptData,
-        transaction_hash: String,
-    ) -> Result<Receipt> {
-        let id = Uuid::new_v4();
-        let blockchain_str = data.blockchain.name().to_string();
+    /// Claim up to `max_batch_size` queued receipts on `chain`, assigning
+    /// each its leaf index and proof under a new pending anchor
+    async fn build_batch(&self, chain: &str) -> Result<Option<(Uuid, String, i32)>> {
+        let mut client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
 
+        let rows = transaction
+            .query(
+                "SELECT id, receipt_hash FROM blockchain_receipts
+                 WHERE blockchain = $1 AND anchor_id IS NULL AND receipt_hash IS NOT NULL
+                 ORDER BY created_at, id
+                 LIMIT $2
+                 FOR UPDATE SKIP LOCKED",
+                &[&chain, &self.max_batch_size],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch queued receipts: {}", e)))?;
+
+        let mut leaves = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let receipt_hash: String = row.get("receipt_hash");
+            leaves.push(merkle::from_hex(&receipt_hash).ok_or_else(|| {
+                Error::Internal(format!("Malformed receipt hash: {}", receipt_hash))
+            })?);
+        }
+        let Some(tree) = MerkleTree::new(&leaves) else {
+            return Ok(None);
+        };
+
+        let anchor_id = Uuid::new_v4();
+        let merkle_root = merkle::to_hex(&tree.root());
+        let leaf_count = tree.leaf_count() as i32;
+        transaction
+            .execute(
+                "INSERT INTO receipt_anchors (id, blockchain, merkle_root, leaf_count, status)
+                 VALUES ($1, $2, $3, $4, 'PENDING')",
+                &[&anchor_id, &chain, &merkle_root, &leaf_count],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to store receipt anchor: {}", e)))?;
+
+        for (index, row) in rows.iter().enumerate() {
+            let receipt_id: Uuid = row.get("id");
+            let proof: Vec<String> = tree
+                .proof(index)
+                .unwrap_or_default()
+                .iter()
+                .map(merkle::to_hex)
+                .collect();
+            transaction
+                .execute(
+                    "UPDATE blockchain_receipts SET anchor_id = $2, leaf_index = $3, merkle_proof = $4
+                     WHERE id = $1",
+                    &[&receipt_id, &anchor_id, &(index as i32), &proof],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to store inclusion proof: {}", e)))?;
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        Ok(Some((anchor_id, merkle_root, leaf_count)))
+    }
+
+    /// Resubmit anchors that have been pending for too long, e.g. because
+    /// the process stopped between building a batch and recording its
+    /// transaction
+    async fn resubmit_stale_anchors(&self) -> Result<usize> {
         let client = self.db.get().await.map_err(|e| {
-            error!("Failed to get database connection: {}", e);
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
 
-        let row = client
-            .query_one(
-                r#"
-                INSERT INTO blockchain_receipts (
-                    id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
-                    sender, recipient, blockchain, transaction_hash,
-                    verification_status, created_at
-                )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'PENDING', NOW())
-                RETURNING id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
-                          sender, recipient, blockchain, transaction_hash,
-                          verification_status, created_at, verified_at
-                "#,
-                &[
-                    &id,
-                    &data.payment_id,
-                    &data.trade_id,
-                    &data.conversion_id,
-                    &data.proximity_transfer_id,
-                    &data.amount,
-                    &data.currency,
-                    &data.sender,
-                    &data.recipient,
-                    &blockchain_str,
-                    &transaction_hash,
-                ],
+        // Claim the anchors by pushing back their attempt time so concurrent
+        // workers skip them
+        let rows = client
+            .query(
+                "UPDATE receipt_anchors SET attempted_at = NOW()
+                 WHERE status = 'PENDING' AND attempted_at < NOW() - make_interval(mins => $1)
+                 RETURNING id, blockchain, merkle_root, leaf_count",
+                &[&(STALE_ANCHOR_MINUTES as i32)],
             )
             .await
-            .map_err(|e| {
-                error!("Failed to store receipt in database: {:?}", e);
-                Error::Database(format!("Failed to store receipt: {:?}", e))
-            })?;
+            .map_err(|e| Error::Database(format!("Failed to claim stale anchors: {}", e)))?;
+        drop(client);
+
+        let mut anchored = 0;
+        for row in rows {
+            let anchor_id: Uuid = row.get("id");
+            let merkle_root: String = row.get("merkle_root");
+            let leaf_count: i32 = row.get("leaf_count");
+            let blockchain = self.chains.resolve(&row.get::<_, String>("blockchain"))?;
+
+            warn!("Resubmitting stale receipt anchor {}", anchor_id);
+            match self.submit_to_blockchain(&blockchain, &merkle_root).await {
+                Ok(transaction_hash) => {
+                    self.mark_anchored(anchor_id, &transaction_hash).await?;
+                    anchored += leaf_count as usize;
+                }
+                Err(e) => {
+                    error!("Failed to resubmit receipt anchor {}: {}", anchor_id, e);
+                    self.mark_failed(anchor_id).await?;
+                }
+            }
+        }
+
+        Ok(anchored)
+    }
+
+    async fn mark_anchored(&self, anchor_id: Uuid, transaction_hash: &str) -> Result<()> {
+        let mut client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        transaction
+            .execute(
+                "UPDATE receipt_anchors
+                 SET status = 'ANCHORED', transaction_hash = $2, anchored_at = NOW()
+                 WHERE id = $1",
+                &[&anchor_id, &transaction_hash],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update receipt anchor: {}", e)))?;
+        transaction
+            .execute(
+                "UPDATE blockchain_receipts SET transaction_hash = $2 WHERE anchor_id = $1",
+                &[&anchor_id, &transaction_hash],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update anchored receipts: {}", e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+    }
+
+    /// Mark an anchor failed and queue its receipts for the next batch
+    async fn mark_failed(&self, anchor_id: Uuid) -> Result<()> {
+        let mut client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        transaction
+            .execute(
+                "UPDATE receipt_anchors SET status = 'FAILED' WHERE id = $1 AND status = 'PENDING'",
+                &[&anchor_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update receipt anchor: {}", e)))?;
+        transaction
+            .execute(
+                "UPDATE blockchain_receipts SET anchor_id = NULL, leaf_index = NULL, merkle_proof = NULL
+                 WHERE anchor_id = $1",
+                &[&anchor_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to release receipts: {}", e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))
+    }
+
+    /// Spawn a background task anchoring queued receipts every `interval`
+    pub fn start_anchoring_worker(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        info!("Starting receipt anchoring worker with interval: {:?}", interval);
 
-        self.row_to_receipt(&row)
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                match self.anchor_pending_receipts().await {
+                    Ok(0) => {}
+                    Ok(anchored) => info!("Anchored {} receipts", anchored),
+                    Err(e) => error!("Error anchoring receipts: {}", e),
+                }
+            }
+        })
     }
 
     /// Get receipt by ID
@@ -302,16 +895,7 @@ impl ReceiptService {
         })?;
 
         let rows = client
-            .query(
-                r#"
-                SELECT id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
-                       sender, recipient, blockchain, transaction_hash,
-                       verification_status, created_at, verified_at
-                FROM blockchain_receipts
-                WHERE id = $1
-                "#,
-                &[&receipt_id],
-            )
+            .query(&format!("{} WHERE r.id = $1", RECEIPT_SELECT), &[&receipt_id])
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch receipt: {}", e)))?;
 
@@ -322,6 +906,34 @@ impl ReceiptService {
         self.row_to_receipt(&rows[0])
     }
 
+    /// Receipts by ID, in the order given; unknown IDs are skipped
+    pub async fn get_receipts(&self, receipt_ids: &[Uuid]) -> Result<Vec<Receipt>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                &format!(
+                    "{} JOIN UNNEST($1::UUID[]) WITH ORDINALITY AS ids(id, position) ON ids.id = r.id \
+                     ORDER BY ids.position",
+                    RECEIPT_SELECT
+                ),
+                &[&receipt_ids],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch receipts: {}", e)))?;
+
+        rows.iter().map(|row| self.row_to_receipt(row)).collect()
+    }
+
+    /// The signed receipt file for a receipt
+    pub async fn get_signed_receipt(&self, receipt_id: Uuid) -> Result<SignedReceipt> {
+        self.get_receipt(receipt_id).await?.signed().ok_or_else(|| {
+            Error::Validation(format!("Receipt {} predates signed receipts", receipt_id))
+        })
+    }
+
     /// Get receipt by source transaction ID
     pub async fn get_receipt_by_source(
         &self,
@@ -336,17 +948,16 @@ impl ReceiptService {
 
         let row = client
             .query_opt(
-                r#"
-                SELECT id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
-                       sender, recipient, blockchain, transaction_hash,
-                       verification_status, created_at, verified_at
-                FROM blockchain_receipts
-                WHERE ($1::UUID IS NULL OR payment_id = $1)
-                  AND ($2::UUID IS NULL OR trade_id = $2)
-                  AND ($3::UUID IS NULL OR conversion_id = $3)
-                  AND ($4::UUID IS NULL OR proximity_transfer_id = $4)
-                LIMIT 1
-                "#,
+                &format!(
+                    r#"{}
+                    WHERE ($1::UUID IS NULL OR r.payment_id = $1)
+                      AND ($2::UUID IS NULL OR r.trade_id = $2)
+                      AND ($3::UUID IS NULL OR r.conversion_id = $3)
+                      AND ($4::UUID IS NULL OR r.proximity_transfer_id = $4)
+                    LIMIT 1
+                    "#,
+                    RECEIPT_SELECT
+                ),
                 &[&payment_id, &trade_id, &conversion_id, &proximity_transfer_id],
             )
             .await
@@ -362,10 +973,13 @@ impl ReceiptService {
     /// 
     /// This method:
     /// 1. Fetches the receipt from the database
-    /// 2. Queries the blockchain for the transaction
-    /// 3. Verifies the transaction exists and is confirmed
-    /// 4. Updates the verification status in the database
-    /// 5. Returns the updated receipt with verification status
+    /// 2. Recomputes the receipt hash from the stored fields
+    /// 3. Recomputes the Merkle root from the hash and inclusion proof
+    /// 4. Checks the anchor transaction on the blockchain records that root
+    /// 5. Updates the verification status in the database
+    /// 6. Returns the updated receipt with verification status
+    ///
+    /// Receipts still queued for a batch stay pending.
     pub async fn verify_receipt(&self, receipt_id: Uuid) -> Result<Receipt> {
         info!("Verifying receipt: {}", receipt_id);
 
@@ -378,10 +992,20 @@ impl ReceiptService {
             return Ok(receipt);
         }
 
+        let Some(transaction_hash) = receipt.transaction_hash.clone() else {
+            debug!("Receipt {} has not been anchored yet", receipt_id);
+            return Ok(receipt);
+        };
+
         // Verify transaction on blockchain
         let verification_result = self
-            .verify_transaction_on_blockchain(&receipt.blockchain, &receipt.transaction_hash)
-            .await;
+            .anchor
+            .anchored_root(&receipt.blockchain, &transaction_hash)
+            .await
+            .map(|root| match root {
+                Some(root) => self.proves_receipt(&receipt, &root),
+                None => false,
+            });
 
         // Update verification status based on result
         let new_status = match verification_result {
@@ -389,15 +1013,16 @@ impl ReceiptService {
                 info!(
                     "Receipt {} verified successfully on {}",
                     receipt_id,
-                    receipt.blockchain.name()
+                    receipt.chain_name
                 );
                 VerificationStatus::Confirmed
             }
             Ok(false) => {
                 warn!(
-                    "Receipt {} verification failed: transaction not found on {}",
+                    "Receipt {} verification failed: anchor {} on {} does not prove it",
                     receipt_id,
-                    receipt.blockchain.name()
+                    transaction_hash,
+                    receipt.chain_name
                 );
                 VerificationStatus::Failed
             }
@@ -405,7 +1030,7 @@ impl ReceiptService {
                 error!(
                     "Error verifying receipt {} on {}: {}",
                     receipt_id,
-                    receipt.blockchain.name(),
+                    receipt.chain_name,
                     e
                 );
                 // Keep as pending if there's an error (might be temporary)
@@ -423,64 +1048,28 @@ impl ReceiptService {
         Ok(receipt)
     }
 
-    /// Verify transaction exists on blockchain
-    /// 
-    /// Returns Ok(true) if transaction is confirmed, Ok(false) if not found,
-    /// or Err if there's an error checking
-    async fn verify_transaction_on_blockchain(
-        &self,
-        blockchain: &Blockchain,
-        transaction_hash: &str,
-    ) -> Result<bool> {
-        debug!(
-            "Checking transaction {} on {}",
-            transaction_hash,
-            blockchain.name()
-        );
+    /// Whether the receipt's fields and inclusion proof lead to
+    /// `anchored_root`, the root recorded on-chain
+    ///
+    /// Receipts created before batching carry no hash or proof; for those
+    /// the anchor transaction existing is all that can be checked.
+    fn proves_receipt(&self, receipt: &Receipt, anchored_root: &str) -> bool {
+        let (Some(receipt_hash), Some(proof)) = (&receipt.receipt_hash, &receipt.inclusion_proof) else {
+            return receipt.receipt_hash.is_none();
+        };
 
-        // Get the appropriate blockchain client
-        let _client = self
-            .blockchain_client
-            .get_client(*blockchain)
-            .ok_or_else(|| {
-                Error::Internal(format!(
-                    "Blockchain client not configured for {}",
-                    blockchain.name()
-                ))
-            })?;
+        let recomputed = match receipt.format_version.as_deref() {
+            Some(RECEIPT_FORMAT_VERSION) => ReceiptPayload::from(receipt).hash(),
+            Some(_) => return false,
+            None => match legacy_receipt_hash(receipt) {
+                Some(hash) => hash,
+                None => return false,
+            },
+        };
 
-        // For this implementation, we'll simulate verification
-        // In a real implementation, this would:
-        // 1. Query the blockchain for the transaction by hash
-        // 2. Check if the transaction exists and is confirmed
-        // 3. Optionally verify the transaction data matches the receipt hash
-        
-        // Note: This is a simplified implementation
-        // In production, you would:
-        // - For Solana: Use getTransaction RPC method
-        // - For EVM chains: Use eth_getTransactionByHash and eth_getTransactionReceipt
-        // - Check confirmation count (e.g., 6+ confirmations for Ethereum)
-        
-        match blockchain {
-            Blockchain::Solana => {
-                // Solana verification would use getTransaction
-                // For now, simulate by checking hash format
-                if transaction_hash.starts_with("solana_tx_") {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Blockchain::Ethereum | Blockchain::BinanceSmartChain | Blockchain::Polygon => {
-                // EVM verification would use eth_getTransactionByHash
-                // For now, simulate by checking hash format
-                if transaction_hash.starts_with("evm_tx_") {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-        }
+        recomputed == *receipt_hash
+            && proof.verifies(&recomputed)
+            && proof.merkle_root.eq_ignore_ascii_case(anchored_root)
     }
 
     /// Update verification status in database
@@ -500,16 +1089,13 @@ impl ReceiptService {
             None
         };
 
-        let row = client
-            .query_one(
+        client
+            .execute(
                 r#"
                 UPDATE blockchain_receipts
                 SET verification_status = $1,
                     verified_at = $2
                 WHERE id = $3
-                RETURNING id, payment_id, trade_id, conversion_id, proximity_transfer_id, amount, currency,
-                          sender, recipient, blockchain, transaction_hash,
-                          verification_status, created_at, verified_at
                 "#,
                 &[&status_str, &verified_at, &receipt_id],
             )
@@ -518,8 +1104,9 @@ impl ReceiptService {
                 error!("Failed to update receipt verification status: {}", e);
                 Error::Database(format!("Failed to update verification status: {}", e))
             })?;
+        drop(client);
 
-        self.row_to_receipt(&row)
+        self.get_receipt(receipt_id).await
     }
 
     /// Convert database row to Receipt struct
@@ -527,19 +1114,7 @@ impl ReceiptService {
         let blockchain_str: String = row.try_get("blockchain").map_err(|e| {
             Error::Database(format!("Failed to get blockchain field: {}", e))
         })?;
-
-        let blockchain = match blockchain_str.as_str() {
-            "Solana" => Blockchain::Solana,
-            "Ethereum" => Blockchain::Ethereum,
-            "Binance Smart Chain" => Blockchain::BinanceSmartChain,
-            "Polygon" => Blockchain::Polygon,
-            _ => {
-                return Err(Error::Internal(format!(
-                    "Unknown blockchain: {}",
-                    blockchain_str
-                )))
-            }
-        };
+        let blockchain = self.chains.resolve(&blockchain_str)?;
 
         let status_str: String = row.try_get("verification_status").map_err(|e| {
             Error::Database(format!("Failed to get verification_status field: {}", e))
@@ -565,6 +1140,29 @@ impl ReceiptService {
             .flatten()
             .map(DateTime::<Utc>::from);
 
+        // Only receipts whose anchor has been confirmed carry a proof
+        let inclusion_proof = match (
+            row.try_get::<_, Option<Uuid>>("anchor_id").ok().flatten(),
+            row.try_get::<_, Option<i32>>("leaf_index").ok().flatten(),
+        ) {
+            (Some(anchor_id), Some(leaf_index)) => Some(InclusionProof {
+                anchor_id,
+                merkle_root: row.try_get("merkle_root").map_err(|e| {
+                    Error::Database(format!("Failed to get merkle_root field: {}", e))
+                })?,
+                leaf_index,
+                leaf_count: row.try_get("leaf_count").map_err(|e| {
+                    Error::Database(format!("Failed to get leaf_count field: {}", e))
+                })?,
+                siblings: row
+                    .try_get::<_, Option<Vec<String>>>("merkle_proof")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default(),
+            }),
+            _ => None,
+        };
+
         Ok(Receipt {
             id: row.try_get("id").map_err(|e| {
                 Error::Database(format!("Failed to get id field: {}", e))
@@ -586,12 +1184,26 @@ impl ReceiptService {
                 Error::Database(format!("Failed to get recipient field: {}", e))
             })?,
             blockchain,
+            chain_name: blockchain_str,
             transaction_hash: row.try_get("transaction_hash").map_err(|e| {
                 Error::Database(format!("Failed to get transaction_hash field: {}", e))
             })?,
             verification_status,
             created_at,
             verified_at,
+            receipt_hash: row.try_get("receipt_hash").map_err(|e| {
+                Error::Database(format!("Failed to get receipt_hash field: {}", e))
+            })?,
+            format_version: row.try_get("format_version").map_err(|e| {
+                Error::Database(format!("Failed to get format_version field: {}", e))
+            })?,
+            signer: row.try_get("signer").map_err(|e| {
+                Error::Database(format!("Failed to get signer field: {}", e))
+            })?,
+            signature: row.try_get("signature").map_err(|e| {
+                Error::Database(format!("Failed to get signature field: {}", e))
+            })?,
+            inclusion_proof,
         })
     }
 }
@@ -619,11 +1231,104 @@ mod tests {
             currency: "USD".to_string(),
             sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
             recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-            blockchain: Blockchain::Ethereum,
+            blockchain: Blockchain::ETHEREUM,
         };
 
         assert!(data.payment_id.is_some());
         assert_eq!(data.currency, "USD");
-        assert_eq!(data.blockchain, Blockchain::Ethereum);
+        assert_eq!(data.blockchain, Blockchain::ETHEREUM);
+    }
+
+    #[test]
+    fn test_anchoring_mode_from_str() {
+        assert_eq!("immediate".parse::<AnchoringMode>().unwrap(), AnchoringMode::Immediate);
+        assert_eq!("Batched".parse::<AnchoringMode>().unwrap(), AnchoringMode::Batched);
+        assert!("hourly".parse::<AnchoringMode>().is_err());
+    }
+
+    #[test]
+    fn test_stored_receipt_rebuilds_signed_payload() {
+        let signer = ReceiptSigner::generate();
+        let data = ReceiptData {
+            payment_id: None,
+            trade_id: Some(Uuid::new_v4()),
+            conversion_id: None,
+            proximity_transfer_id: None,
+            amount: Decimal::new(10050, 2),
+            currency: "USDC".to_string(),
+            sender: "sender".to_string(),
+            recipient: "recipient".to_string(),
+            blockchain: Blockchain::POLYGON,
+        };
+        let id = Uuid::new_v4();
+        let created_at = Utc::now().trunc_subsecs(6);
+        let payload = ReceiptPayload::new(id, &data, "Polygon", created_at);
+
+        // As read back from the database, at the column's scale
+        let receipt = Receipt {
+            id,
+            payment_id: None,
+            trade_id: data.trade_id,
+            conversion_id: None,
+            proximity_transfer_id: None,
+            amount: Decimal::from_i128_with_scale(100_500_000_000_000_000_000, 18),
+            currency: data.currency.clone(),
+            sender: data.sender.clone(),
+            recipient: data.recipient.clone(),
+            blockchain: data.blockchain.clone(),
+            chain_name: "Polygon".to_string(),
+            transaction_hash: None,
+            verification_status: VerificationStatus::Pending,
+            created_at,
+            verified_at: None,
+            receipt_hash: Some(payload.hash()),
+            format_version: Some(RECEIPT_FORMAT_VERSION.to_string()),
+            signer: Some(signer.public_key().to_string()),
+            signature: Some(signer.sign(&payload)),
+            inclusion_proof: None,
+        };
+
+        let signed = receipt.signed().unwrap();
+        assert_eq!(signed.receipt, payload);
+        assert!(crate::receipt_format::verify_signed_receipt(&signed, &signer.public_key().to_string()).is_ok());
+
+        let legacy = Receipt { format_version: None, ..receipt };
+        assert!(legacy.signed().is_none());
+    }
+
+    #[test]
+    fn test_inclusion_proof_verifies_receipt_hash() {
+        let hashes: Vec<String> = (0..3u8).map(|i| merkle::to_hex(&[i; 32])).collect();
+        let leaves: Vec<_> = hashes.iter().map(|h| merkle::from_hex(h).unwrap()).collect();
+        let tree = MerkleTree::new(&leaves).unwrap();
+
+        let proof = InclusionProof {
+            anchor_id: Uuid::nil(),
+            merkle_root: merkle::to_hex(&tree.root()),
+            leaf_index: 2,
+            leaf_count: 3,
+            siblings: tree.proof(2).unwrap().iter().map(merkle::to_hex).collect(),
+        };
+
+        assert!(proof.verifies(&hashes[2]));
+        assert!(!proof.verifies(&hashes[1]));
+        assert!(!InclusionProof { leaf_index: 1, ..proof.clone() }.verifies(&hashes[2]));
+        assert!(!InclusionProof { leaf_count: 4, ..proof.clone() }.verifies(&hashes[2]));
+        assert!(!InclusionProof { leaf_index: -1, ..proof }.verifies(&hashes[2]));
+    }
+
+    #[tokio::test]
+    async fn test_anchor_needs_a_wallet_and_ignores_foreign_hashes() {
+        let client = Arc::new(MultiChainClient::new().with_solana("http://127.0.0.1:1".to_string(), None));
+        let anchor = OnChainReceiptAnchor::new(client);
+        let root = merkle::to_hex(&[7; 32]);
+
+        // Without a wallet nothing is sent
+        assert!(anchor.anchor(&Blockchain::SOLANA, &root).await.is_err());
+        assert!(anchor.anchor(&Blockchain::POLYGON, &root).await.is_err());
+
+        // Hashes that cannot be transactions on the chain anchor nothing
+        let simulated = format!("solana_tx_{}", &root[2..]);
+        assert_eq!(anchor.anchored_root(&Blockchain::SOLANA, &simulated).await.unwrap(), None);
     }
 }
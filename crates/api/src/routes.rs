@@ -1,13 +1,23 @@
 use axum::{
+    middleware,
     routing::{get, post, put},
     Router,
 };
 use std::sync::Arc;
 use tower_http::services::ServeDir;
 
-use crate::{handlers, proximity_handlers, proximity_websocket, AppState};
+use crate::{auth, handlers, proximity_handlers, proximity_websocket, rate_limit, AppState};
 
 pub fn create_router(state: Arc<AppState>) -> Router {
+    public_routes()
+        .merge(protected_routes(state.clone()))
+        .with_state(state)
+        // Serve static frontend files
+        .nest_service("/", ServeDir::new("frontend"))
+}
+
+/// Routes reachable without a bearer token
+fn public_routes() -> Router<Arc<AppState>> {
     Router::new()
         // Health check and monitoring
         .route("/health", get(handlers::health_check))
@@ -25,6 +35,29 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         // User & Wallet Management
         .route("/api/users/register", post(handlers::register_user))
         .route("/api/users/login", post(handlers::login_user))
+        
+        // Mesh Price Service - Price Data Access
+        .route("/api/mesh/prices", get(handlers::get_mesh_prices))
+        .route("/api/mesh/prices/:asset", get(handlers::get_mesh_price_by_asset))
+        .route("/api/mesh/network/status", get(handlers::get_mesh_network_status))
+        
+        // CoinMarketCap Price Data
+        .route("/api/cmc/price", get(handlers::get_crypto_price))
+        .route("/api/cmc/prices", get(handlers::get_crypto_prices))
+        .route("/api/cmc/convert", get(handlers::convert_crypto))
+        
+        // Receipt signing key, for verifying receipt files offline
+        .route("/api/receipts/signing-key", get(handlers::get_receipt_signing_key))
+}
+
+/// Routes that require a valid JWT
+///
+/// Layers run bottom-up: the token is verified first, then the caller is
+/// rate limited by subject, and finally any `:user_id` path segment must
+/// match the caller unless the token carries an admin scope.
+fn protected_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        // User & Wallet Management
         .route("/api/users/2fa/enable", post(handlers::enable_2fa))
         .route("/api/users/2fa/verify", post(handlers::verify_2fa))
         .route("/api/users/2fa/disable", post(handlers::disable_2fa))
@@ -89,6 +122,14 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/trim/:user_id/config", put(handlers::update_trim_config))
         .route("/api/trim/:user_id/executions", get(handlers::get_trim_executions))
         
+        // Tax Lots
+        .route("/api/tax/:user_id/lots", get(handlers::get_tax_lots))
+        .route("/api/tax/:user_id/settings", get(handlers::get_tax_settings))
+        .route("/api/tax/:user_id/settings", put(handlers::update_tax_settings))
+        .route("/api/tax/:user_id/disposals/:source/:disposal_id/lots", post(handlers::select_tax_lots))
+        .route("/api/tax/:user_id/reports/:tax_year", get(handlers::get_tax_report))
+        .route("/api/tax/:user_id/reports/:tax_year/:kind", get(handlers::export_tax_report))
+        
         // Position Management
         .route("/api/positions/:user_id/modes", get(handlers::get_user_position_modes))
         .route("/api/positions/:user_id/mode", post(handlers::set_position_mode))
@@ -97,10 +138,26 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/positions/:user_id/orders/manual", get(handlers::get_user_manual_orders))
         .route("/api/positions/:user_id/orders/:order_id", get(handlers::get_manual_order))
         .route("/api/positions/:user_id/orders/:order_id/cancel", post(handlers::cancel_manual_order))
+        .route("/api/positions/:user_id/orders/conditional", post(handlers::create_conditional_order))
+        .route("/api/positions/:user_id/orders/conditional", get(handlers::get_user_conditional_orders))
+        .route("/api/positions/:user_id/orders/bracket", post(handlers::create_bracket_order))
+        .route("/api/positions/:user_id/orders/conditional/:order_id", get(handlers::get_conditional_order))
+        .route("/api/positions/:user_id/orders/conditional/:order_id/transitions", get(handlers::get_conditional_order_transitions))
+        .route("/api/positions/:user_id/orders/conditional/:order_id/cancel", post(handlers::cancel_conditional_order))
+        
+        // DCA Schedules
+        .route("/api/dca/:user_id/schedules", post(handlers::create_dca_schedule))
+        .route("/api/dca/:user_id/schedules", get(handlers::get_user_dca_schedules))
+        .route("/api/dca/:user_id/schedules/:schedule_id", get(handlers::get_dca_schedule))
+        .route("/api/dca/:user_id/schedules/:schedule_id/runs", get(handlers::get_dca_runs))
+        .route("/api/dca/:user_id/schedules/:schedule_id/pause", post(handlers::pause_dca_schedule))
+        .route("/api/dca/:user_id/schedules/:schedule_id/resume", post(handlers::resume_dca_schedule))
+        .route("/api/dca/:user_id/schedules/:schedule_id/cancel", post(handlers::cancel_dca_schedule))
         
         // Payment Receipts
         .route("/api/receipts/search", post(handlers::search_receipts))
         .route("/api/receipts/:receipt_id/pdf", get(handlers::export_receipt_pdf))
+        .route("/api/receipts/:receipt_id/signed", get(handlers::export_signed_receipt))
         .route("/api/receipts/export/csv", post(handlers::export_receipts_csv))
         
         // Chat
@@ -119,13 +176,26 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/p2p/offers/:offer_id", get(handlers::get_offer))
         .route("/api/p2p/:user_id/offers/:offer_id/cancel", post(handlers::cancel_p2p_offer))
         .route("/api/p2p/:user_id/offers/:offer_id/accept", post(handlers::accept_p2p_offer))
+        .route("/api/p2p/:user_id/offers/:offer_id/match", post(handlers::match_p2p_offer))
         .route("/api/p2p/:user_id/offers/:offer_id/status", get(handlers::get_p2p_offer_status))
         .route("/api/p2p/:user_id/marketplace", get(handlers::get_marketplace_offers))
         .route("/api/p2p/:user_id/exchanges", get(handlers::get_user_exchanges))
+        .route("/api/p2p/:user_id/exchanges/:exchange_id/confirm", post(handlers::confirm_p2p_exchange))
+        .route("/api/p2p/:user_id/exchanges/:exchange_id/escrow", get(handlers::get_p2p_exchange_escrow))
+        .route("/api/p2p/:user_id/exchanges/:exchange_id/escrow/deposit", post(handlers::deposit_p2p_escrow))
+        .route("/api/p2p/escrow", get(handlers::get_p2p_escrow_address))
+        .route("/api/p2p/:user_id/exchanges/:exchange_id/rating", post(handlers::rate_p2p_exchange))
+        .route("/api/p2p/:user_id/exchanges/:exchange_id/dispute", post(handlers::open_p2p_dispute))
+        .route("/api/p2p/:user_id/disputes/:dispute_id", get(handlers::get_p2p_dispute))
+        .route("/api/p2p/:user_id/disputes/:dispute_id/evidence", post(handlers::add_p2p_dispute_evidence))
+        .route("/api/p2p/disputes", get(handlers::list_p2p_disputes))
+        .route("/api/p2p/disputes/:dispute_id/resolve", post(handlers::resolve_p2p_dispute))
+        .route("/api/p2p/traders/:trader_id/reputation", get(handlers::get_p2p_trader_reputation))
         
         // Verification
         .route("/api/verification/:user_id/status", get(handlers::get_verification_status))
         .route("/api/verification/:user_id/identity", post(handlers::submit_identity_verification))
+        .route("/api/verification/:user_id/wallet/challenge", post(handlers::create_wallet_challenge))
         .route("/api/verification/:user_id/wallet", post(handlers::verify_wallet_ownership))
         .route("/api/verification/:user_id/wallets", get(handlers::get_verified_wallets))
         
@@ -168,18 +238,14 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/api/mesh/provider/enable", post(handlers::enable_mesh_provider))
         .route("/api/mesh/provider/disable", post(handlers::disable_mesh_provider))
         .route("/api/mesh/provider/status", get(handlers::get_mesh_provider_status))
-        
-        // Mesh Price Service - Price Data Access
-        .route("/api/mesh/prices", get(handlers::get_mesh_prices))
-        .route("/api/mesh/prices/:asset", get(handlers::get_mesh_price_by_asset))
-        .route("/api/mesh/network/status", get(handlers::get_mesh_network_status))
-        
-        // CoinMarketCap Price Data
-        .route("/api/cmc/price", get(handlers::get_crypto_price))
-        .route("/api/cmc/prices", get(handlers::get_crypto_prices))
-        .route("/api/cmc/convert", get(handlers::convert_crypto))
-        
-        .with_state(state)
-        // Serve static frontend files
-        .nest_service("/", ServeDir::new("frontend"))
+
+        .route_layer(middleware::from_fn(auth::user_path_authorization))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::rate_limit_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.jwt_config.clone(),
+            auth::auth_middleware,
+        ))
 }
@@ -236,6 +236,25 @@ impl StakingService {
         })
     }
 
+    /// Get the user who owns a staking approval request, if it exists
+    pub async fn get_approval_request_owner(&self, request_id: Uuid) -> Result<Option<Uuid>> {
+        let client = self
+            .db_pool
+            .get()
+            .await
+            .context("Failed to get database connection")?;
+
+        let row = client
+            .query_opt(
+                "SELECT user_id FROM staking_approval_requests WHERE id = $1",
+                &[&request_id],
+            )
+            .await
+            .context("Failed to query staking approval request")?;
+
+        Ok(row.map(|r| r.get(0)))
+    }
+
     /// Initiate staking after user approval
     /// 
     /// **Validates: Requirement 3.3**
@@ -456,14 +475,23 @@ impl StakingService {
             .await
             .context("Failed to get database connection")?;
 
-        let now = Utc::now();
-
+        // Each increase is recorded as a reward event so it can be taxed as
+        // income and opens a tax lot at the time it was received
         client
             .execute(
-                "UPDATE staking_positions 
-                 SET rewards_earned = $1, last_reward_at = $2
-                 WHERE id = $3",
-                &[&rewards_earned, &now, &position_id],
+                "WITH previous AS (
+                     SELECT id, user_id, asset, rewards_earned
+                     FROM staking_positions WHERE id = $2 FOR UPDATE
+                 ), updated AS (
+                     UPDATE staking_positions
+                     SET rewards_earned = $1, last_reward_at = NOW()
+                     WHERE id = $2
+                 )
+                 INSERT INTO staking_rewards (position_id, user_id, asset, amount, rewarded_at)
+                 SELECT id, user_id, asset, $1 - rewards_earned, NOW()
+                 FROM previous
+                 WHERE $1 > rewards_earned AND user_id IS NOT NULL",
+                &[&rewards_earned, &position_id],
             )
             .await
             .context("Failed to update staking rewards")?;
@@ -0,0 +1,1349 @@
+//! Tax lots and realized/unrealized gain and loss
+//!
+//! Every acquisition of an asset opens a lot: a buy, the incoming side of a
+//! conversion or P2P exchange, a received proximity transfer or a staking
+//! reward. A lot's cost basis is its USD value when acquired plus fees.
+//! Every disposal consumes open lots in the order of the user's
+//! [`DisposalMethod`] and realizes the difference between its fee-adjusted
+//! proceeds and the basis it consumed.
+//!
+//! Lots are not stored. [`LotLedger::build_with_changes`] replays a user's
+//! history, matching each disposal with the method in effect when it
+//! happened. A method change applies from its effective date, which cannot
+//! precede a recorded disposal, and a disposal's lot selection cannot be
+//! changed once made, so reported gains stay as reported.
+
+use chrono::{DateTime, Months, Utc};
+use database::DbPool;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::price_cache::PriceCache;
+
+/// Assets valued at one US dollar when no price is recorded for them
+const USD_STABLECOINS: &[&str] = &["USD", "USDC", "USDT", "DAI", "BUSD", "PYUSD"];
+
+/// Order in which a disposal consumes open lots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DisposalMethod {
+    /// Oldest lots first
+    Fifo,
+    /// Newest lots first
+    Lifo,
+    /// Highest unit cost first, minimizing realized gains
+    Hifo,
+    /// Lots the user selected for the disposal, then oldest first
+    SpecificId,
+}
+
+impl DisposalMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisposalMethod::Fifo => "FIFO",
+            DisposalMethod::Lifo => "LIFO",
+            DisposalMethod::Hifo => "HIFO",
+            DisposalMethod::SpecificId => "SPECIFIC_ID",
+        }
+    }
+}
+
+impl FromStr for DisposalMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "FIFO" => Ok(DisposalMethod::Fifo),
+            "LIFO" => Ok(DisposalMethod::Lifo),
+            "HIFO" => Ok(DisposalMethod::Hifo),
+            "SPECIFIC_ID" => Ok(DisposalMethod::SpecificId),
+            other => Err(Error::Validation(format!("Invalid disposal method: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HoldingPeriod {
+    /// Held one year or less
+    ShortTerm,
+    /// Held more than one year
+    LongTerm,
+}
+
+impl HoldingPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HoldingPeriod::ShortTerm => "SHORT_TERM",
+            HoldingPeriod::LongTerm => "LONG_TERM",
+        }
+    }
+
+    /// Holding period of an asset acquired at `acquired_at` and disposed of at `disposed_at`
+    pub fn between(acquired_at: DateTime<Utc>, disposed_at: DateTime<Utc>) -> Self {
+        match acquired_at.checked_add_months(Months::new(12)) {
+            Some(one_year) if disposed_at > one_year => HoldingPeriod::LongTerm,
+            _ => HoldingPeriod::ShortTerm,
+        }
+    }
+}
+
+/// Transaction table a tax event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaxEventSource {
+    Trade,
+    Conversion,
+    P2pExchange,
+    ProximityTransfer,
+    StakingReward,
+}
+
+impl TaxEventSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxEventSource::Trade => "TRADE",
+            TaxEventSource::Conversion => "CONVERSION",
+            TaxEventSource::P2pExchange => "P2P_EXCHANGE",
+            TaxEventSource::ProximityTransfer => "PROXIMITY_TRANSFER",
+            TaxEventSource::StakingReward => "STAKING_REWARD",
+        }
+    }
+}
+
+impl FromStr for TaxEventSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "TRADE" => Ok(TaxEventSource::Trade),
+            "CONVERSION" => Ok(TaxEventSource::Conversion),
+            "P2P_EXCHANGE" => Ok(TaxEventSource::P2pExchange),
+            "PROXIMITY_TRANSFER" => Ok(TaxEventSource::ProximityTransfer),
+            "STAKING_REWARD" => Ok(TaxEventSource::StakingReward),
+            other => Err(Error::Validation(format!("Invalid tax event source: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaxEventKind {
+    Acquisition,
+    Disposal,
+}
+
+/// One side of a transaction that changes a user's holdings of an asset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxEvent {
+    pub source: TaxEventSource,
+    /// Id of the transaction in its source table, and of the lot an acquisition opens
+    pub source_id: Uuid,
+    pub kind: TaxEventKind,
+    /// Upper-case asset symbol
+    pub asset: String,
+    pub amount: Decimal,
+    /// USD value of `amount` at the time, `None` if no price was recorded
+    pub value_usd: Option<Decimal>,
+    /// Fees in USD, added to an acquisition's basis or deducted from a disposal's proceeds
+    pub fee_usd: Decimal,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Disposal method a user chose, applying to disposals from `effective_from` on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisposalMethodChange {
+    pub disposal_method: DisposalMethod,
+    pub effective_from: DateTime<Utc>,
+}
+
+/// Method in effect at `at` under `changes`, sorted by effective date; FIFO
+/// before the first change
+pub fn method_at(changes: &[DisposalMethodChange], at: DateTime<Utc>) -> DisposalMethod {
+    changes
+        .iter()
+        .take_while(|change| change.effective_from <= at)
+        .last()
+        .map(|change| change.disposal_method)
+        .unwrap_or(DisposalMethod::Fifo)
+}
+
+/// Lot a specific-ID disposal should consume, and how much of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotSelection {
+    pub disposal_source: TaxEventSource,
+    pub disposal_id: Uuid,
+    pub lot_id: Uuid,
+    pub amount: Decimal,
+}
+
+/// An acquired quantity of an asset and what it cost
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxLot {
+    /// Id of the acquiring transaction
+    pub id: Uuid,
+    pub source: TaxEventSource,
+    pub asset: String,
+    pub acquired_at: DateTime<Utc>,
+    pub amount: Decimal,
+    pub remaining: Decimal,
+    /// Fee-inclusive USD cost of `amount`
+    pub cost_basis_usd: Decimal,
+    /// Part of the cost basis not yet consumed by disposals
+    pub remaining_cost_basis_usd: Decimal,
+    /// No price was recorded at acquisition, so the basis is fees only
+    pub missing_price: bool,
+}
+
+impl TaxLot {
+    fn open(event: &TaxEvent) -> Self {
+        let cost_basis_usd = event.value_usd.unwrap_or(Decimal::ZERO) + event.fee_usd;
+        Self {
+            id: event.source_id,
+            source: event.source,
+            asset: event.asset.clone(),
+            acquired_at: event.occurred_at,
+            amount: event.amount,
+            remaining: event.amount,
+            cost_basis_usd,
+            remaining_cost_basis_usd: cost_basis_usd,
+            missing_price: event.value_usd.is_none(),
+        }
+    }
+
+    /// Cost basis per unit
+    pub fn unit_cost(&self) -> Decimal {
+        if self.amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.cost_basis_usd / self.amount
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.remaining > Decimal::ZERO
+    }
+}
+
+/// The part of a disposal matched against one lot
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Disposal {
+    pub source: TaxEventSource,
+    pub source_id: Uuid,
+    pub asset: String,
+    /// Lot consumed, `None` for the part of a disposal exceeding the user's
+    /// known holdings, which is reported with a zero basis
+    pub lot_id: Option<Uuid>,
+    pub acquired_at: Option<DateTime<Utc>>,
+    pub disposed_at: DateTime<Utc>,
+    pub amount: Decimal,
+    /// Fee-adjusted USD proceeds
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub gain_usd: Decimal,
+    /// Short-term when the acquisition date is unknown
+    pub holding_period: HoldingPeriod,
+    /// No price was recorded for the proceeds or the consumed lot
+    pub missing_price: bool,
+}
+
+/// A user's lots and the disposals matched against them
+#[derive(Debug, Clone, Default)]
+pub struct LotLedger {
+    pub lots: Vec<TaxLot>,
+    pub disposals: Vec<Disposal>,
+}
+
+impl LotLedger {
+    /// Replay `events` in time order, matching every disposal with `method`
+    ///
+    /// `selections` are only used with [`DisposalMethod::SpecificId`].
+    pub fn build(events: &[TaxEvent], method: DisposalMethod, selections: &[LotSelection]) -> Self {
+        Self::replay(events, |_| method, selections)
+    }
+
+    /// Replay `events` in time order, matching each disposal with the method
+    /// `changes` put in effect at the time
+    pub fn build_with_changes(
+        events: &[TaxEvent],
+        changes: &[DisposalMethodChange],
+        selections: &[LotSelection],
+    ) -> Self {
+        Self::replay(events, |at| method_at(changes, at), selections)
+    }
+
+    fn replay(
+        events: &[TaxEvent],
+        method_at: impl Fn(DateTime<Utc>) -> DisposalMethod,
+        selections: &[LotSelection],
+    ) -> Self {
+        let mut ordered: Vec<&TaxEvent> = events.iter().filter(|e| e.amount > Decimal::ZERO).collect();
+        // Acquisitions before disposals at the same instant
+        ordered.sort_by_key(|e| (e.occurred_at, e.kind == TaxEventKind::Disposal));
+
+        let mut ledger = Self::default();
+        for event in ordered {
+            match event.kind {
+                TaxEventKind::Acquisition => ledger.lots.push(TaxLot::open(event)),
+                TaxEventKind::Disposal => {
+                    let method = method_at(event.occurred_at);
+                    let selected: Vec<&LotSelection> = if method == DisposalMethod::SpecificId {
+                        selections
+                            .iter()
+                            .filter(|s| s.disposal_source == event.source && s.disposal_id == event.source_id)
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    let disposals = ledger.dispose(event, method, &selected);
+                    ledger.disposals.extend(disposals);
+                }
+            }
+        }
+        ledger
+    }
+
+    /// Consume lots for one disposal event
+    fn dispose(&mut self, event: &TaxEvent, method: DisposalMethod, selected: &[&LotSelection]) -> Vec<Disposal> {
+        let mut takes: Vec<(Option<usize>, Decimal)> = Vec::new();
+        let mut remaining = event.amount;
+
+        for selection in selected {
+            let index = self.lots.iter().position(|lot| {
+                lot.id == selection.lot_id
+                    && lot.asset == event.asset
+                    && lot.is_open()
+                    && lot.acquired_at <= event.occurred_at
+            });
+            if let Some(index) = index {
+                let take = selection.amount.min(self.lots[index].remaining).min(remaining);
+                if take > Decimal::ZERO {
+                    takes.push((Some(index), take));
+                    remaining -= take;
+                    self.lots[index].remaining -= take;
+                }
+            }
+        }
+
+        for index in self.lot_order(&event.asset, event.occurred_at, method) {
+            if remaining.is_zero() {
+                break;
+            }
+            let take = self.lots[index].remaining.min(remaining);
+            takes.push((Some(index), take));
+            remaining -= take;
+            self.lots[index].remaining -= take;
+        }
+
+        if remaining > Decimal::ZERO {
+            takes.push((None, remaining));
+        }
+
+        // Remaining amounts were reduced above; restore them so basis can be
+        // taken proportionally to what each lot held before this disposal
+        for (index, take) in &takes {
+            if let Some(index) = index {
+                self.lots[*index].remaining += *take;
+            }
+        }
+
+        let net_proceeds = event.value_usd.unwrap_or(Decimal::ZERO) - event.fee_usd;
+        let mut allocated = Decimal::ZERO;
+        let count = takes.len();
+        takes
+            .into_iter()
+            .enumerate()
+            .map(|(i, (index, take))| {
+                let proceeds_usd = if i + 1 == count {
+                    net_proceeds - allocated
+                } else {
+                    net_proceeds * take / event.amount
+                };
+                allocated += proceeds_usd;
+
+                let (lot_id, acquired_at, cost_basis_usd, lot_missing_price) = match index {
+                    Some(index) => {
+                        let lot = &mut self.lots[index];
+                        let basis = if take == lot.remaining {
+                            lot.remaining_cost_basis_usd
+                        } else {
+                            lot.remaining_cost_basis_usd * take / lot.remaining
+                        };
+                        lot.remaining -= take;
+                        lot.remaining_cost_basis_usd -= basis;
+                        (Some(lot.id), Some(lot.acquired_at), basis, lot.missing_price)
+                    }
+                    None => (None, None, Decimal::ZERO, false),
+                };
+
+                Disposal {
+                    source: event.source,
+                    source_id: event.source_id,
+                    asset: event.asset.clone(),
+                    lot_id,
+                    acquired_at,
+                    disposed_at: event.occurred_at,
+                    amount: take,
+                    proceeds_usd,
+                    cost_basis_usd,
+                    gain_usd: proceeds_usd - cost_basis_usd,
+                    holding_period: acquired_at
+                        .map(|at| HoldingPeriod::between(at, event.occurred_at))
+                        .unwrap_or(HoldingPeriod::ShortTerm),
+                    missing_price: event.value_usd.is_none() || lot_missing_price,
+                }
+            })
+            .collect()
+    }
+
+    /// Indices of the open lots of `asset` held at `at`, in consumption order
+    fn lot_order(&self, asset: &str, at: DateTime<Utc>, method: DisposalMethod) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.lots.len())
+            .filter(|&i| {
+                let lot = &self.lots[i];
+                lot.asset == asset && lot.is_open() && lot.acquired_at <= at
+            })
+            .collect();
+
+        match method {
+            DisposalMethod::Fifo | DisposalMethod::SpecificId => {
+                indices.sort_by_key(|&i| self.lots[i].acquired_at)
+            }
+            DisposalMethod::Lifo => indices.sort_by_key(|&i| std::cmp::Reverse(self.lots[i].acquired_at)),
+            DisposalMethod::Hifo => indices.sort_by(|&a, &b| {
+                self.lots[b]
+                    .unit_cost()
+                    .cmp(&self.lots[a].unit_cost())
+                    .then(self.lots[a].acquired_at.cmp(&self.lots[b].acquired_at))
+            }),
+        }
+        indices
+    }
+
+    pub fn open_lots(&self) -> impl Iterator<Item = &TaxLot> {
+        self.lots.iter().filter(|lot| lot.is_open())
+    }
+
+    pub fn realized_gain_usd(&self) -> Decimal {
+        self.disposals.iter().map(|d| d.gain_usd).sum()
+    }
+
+    /// Average remaining cost per unit of the open lots of `asset`
+    pub fn average_cost(&self, asset: &str) -> Option<Decimal> {
+        let asset = asset.to_uppercase();
+        let (amount, basis) = self
+            .open_lots()
+            .filter(|lot| lot.asset == asset)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(amount, basis), lot| {
+                (amount + lot.remaining, basis + lot.remaining_cost_basis_usd)
+            });
+        (amount > Decimal::ZERO).then(|| basis / amount)
+    }
+
+    /// Disposals that selling `amount` of `asset` for `proceeds_usd` at `at`
+    /// would produce, without changing the ledger
+    pub fn preview_disposal(
+        &self,
+        asset: &str,
+        amount: Decimal,
+        proceeds_usd: Decimal,
+        at: DateTime<Utc>,
+        method: DisposalMethod,
+    ) -> Vec<Disposal> {
+        let event = TaxEvent {
+            source: TaxEventSource::Trade,
+            source_id: Uuid::nil(),
+            kind: TaxEventKind::Disposal,
+            asset: asset.to_uppercase(),
+            amount,
+            value_usd: Some(proceeds_usd),
+            fee_usd: Decimal::ZERO,
+            occurred_at: at,
+        };
+        self.clone().dispose(&event, method, &[])
+    }
+}
+
+/// Realized result of one transaction, summed over the lots it consumed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RealizedGain {
+    pub amount: Decimal,
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub gain_usd: Decimal,
+    /// `None` when the transaction consumed both short- and long-term lots
+    pub holding_period: Option<HoldingPeriod>,
+}
+
+impl RealizedGain {
+    pub fn from_disposals(disposals: &[Disposal]) -> Option<Self> {
+        let first = disposals.first()?;
+        let mixed = disposals.iter().any(|d| d.holding_period != first.holding_period);
+        Some(Self {
+            amount: disposals.iter().map(|d| d.amount).sum(),
+            proceeds_usd: disposals.iter().map(|d| d.proceeds_usd).sum(),
+            cost_basis_usd: disposals.iter().map(|d| d.cost_basis_usd).sum(),
+            gain_usd: disposals.iter().map(|d| d.gain_usd).sum(),
+            holding_period: (!mixed).then_some(first.holding_period),
+        })
+    }
+}
+
+/// An open lot at current prices
+#[derive(Debug, Clone, Serialize)]
+pub struct LotValuation {
+    #[serde(flatten)]
+    pub lot: TaxLot,
+    pub price_usd: Option<Decimal>,
+    pub market_value_usd: Option<Decimal>,
+    pub unrealized_gain_usd: Option<Decimal>,
+}
+
+/// A user's open lots, disposals and total P&L
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxLotReport {
+    pub user_id: Uuid,
+    pub disposal_method: DisposalMethod,
+    pub lots: Vec<LotValuation>,
+    pub disposals: Vec<Disposal>,
+    pub realized_gain_usd: Decimal,
+    /// Over the open lots with a current price
+    pub unrealized_gain_usd: Decimal,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// USD value of `amount` of `asset` at `price`, with stablecoins at par
+pub(crate) fn usd_value(asset: &str, amount: Decimal, price: Option<Decimal>) -> Option<Decimal> {
+    price
+        .or_else(|| USD_STABLECOINS.contains(&asset).then_some(Decimal::ONE))
+        .map(|price| price * amount)
+}
+
+/// Latest recorded USD price of the upper-case asset `asset` at or before
+/// `at`, as a scalar subquery over SQL expressions
+fn price_at(asset: &str, at: &str) -> String {
+    format!(
+        "(SELECT h.price_usd FROM asset_price_history h \
+          WHERE h.asset = {} AND h.recorded_at <= {} \
+          ORDER BY h.recorded_at DESC LIMIT 1)",
+        asset, at
+    )
+}
+
+/// Builds tax lots from a user's transactions
+pub struct TaxLotService {
+    db: DbPool,
+}
+
+impl TaxLotService {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+
+    /// The user's disposal method in effect now, FIFO unless they chose another
+    pub async fn get_disposal_method(&self, user_id: Uuid) -> Result<DisposalMethod> {
+        let changes = self.get_method_changes(user_id).await?;
+        Ok(method_at(&changes, Utc::now()))
+    }
+
+    /// The user's disposal method changes, oldest first
+    pub async fn get_method_changes(&self, user_id: Uuid) -> Result<Vec<DisposalMethodChange>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                "SELECT disposal_method, effective_from FROM tax_method_changes
+                 WHERE user_id = $1 ORDER BY effective_from",
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch disposal method changes: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DisposalMethodChange {
+                    disposal_method: row.get::<_, String>("disposal_method").parse()?,
+                    effective_from: row.get("effective_from"),
+                })
+            })
+            .collect()
+    }
+
+    /// Dispose of lots by `method` from `effective_from` on
+    ///
+    /// The change cannot apply to disposals already recorded, so
+    /// `effective_from` must be after the user's latest disposal. It
+    /// replaces any change scheduled at or after `effective_from`.
+    pub async fn set_disposal_method(
+        &self,
+        user_id: Uuid,
+        method: DisposalMethod,
+        effective_from: DateTime<Utc>,
+    ) -> Result<()> {
+        let events = self.load_events(user_id).await?;
+        let last_disposal = events
+            .iter()
+            .filter(|e| e.kind == TaxEventKind::Disposal)
+            .map(|e| e.occurred_at)
+            .max();
+        if let Some(last_disposal) = last_disposal.filter(|at| *at >= effective_from) {
+            return Err(Error::Validation(format!(
+                "A disposal method change must take effect after the last recorded disposal at {}",
+                last_disposal
+            )));
+        }
+
+        let mut client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        transaction
+            .execute(
+                "DELETE FROM tax_method_changes WHERE user_id = $1 AND effective_from >= $2",
+                &[&user_id, &effective_from],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to replace disposal method changes: {}", e)))?;
+        transaction
+            .execute(
+                "INSERT INTO tax_method_changes (user_id, disposal_method, effective_from)
+                 VALUES ($1, $2, $3)",
+                &[&user_id, &method.as_str(), &effective_from],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to store disposal method change: {}", e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        info!(
+            "User {} disposes of lots by {} from {}",
+            user_id,
+            method.as_str(),
+            effective_from
+        );
+        Ok(())
+    }
+
+    /// Choose the lots a disposal consumes under [`DisposalMethod::SpecificId`]
+    ///
+    /// A disposal's lots are selected once, while SPECIFIC_ID was in effect
+    /// at the disposal and before a later disposal of the same asset is
+    /// recorded. Lots must be of the disposed asset and acquired before it.
+    pub async fn select_lots(
+        &self,
+        user_id: Uuid,
+        disposal_source: TaxEventSource,
+        disposal_id: Uuid,
+        lots: &[(Uuid, Decimal)],
+    ) -> Result<Vec<LotSelection>> {
+        if lots.is_empty() {
+            return Err(Error::Validation("Select at least one lot".to_string()));
+        }
+        if lots.iter().any(|(_, amount)| *amount <= Decimal::ZERO) {
+            return Err(Error::Validation("Selected amounts must be positive".to_string()));
+        }
+
+        let events = self.load_events(user_id).await?;
+        let disposal = events
+            .iter()
+            .find(|e| {
+                e.kind == TaxEventKind::Disposal && e.source == disposal_source && e.source_id == disposal_id
+            })
+            .ok_or_else(|| Error::Validation(format!("Disposal {} not found", disposal_id)))?;
+
+        let changes = self.get_method_changes(user_id).await?;
+        if method_at(&changes, disposal.occurred_at) != DisposalMethod::SpecificId {
+            return Err(Error::Validation(format!(
+                "Disposal {} was not made under the SPECIFIC_ID method",
+                disposal_id
+            )));
+        }
+        let disposed_later = events.iter().any(|e| {
+            e.kind == TaxEventKind::Disposal
+                && e.asset == disposal.asset
+                && e.occurred_at > disposal.occurred_at
+        });
+        if disposed_later {
+            return Err(Error::Validation(format!(
+                "Lots of disposal {} are locked by a later disposal of {}",
+                disposal_id, disposal.asset
+            )));
+        }
+
+        for (lot_id, _) in lots {
+            let is_lot = events.iter().any(|e| {
+                e.kind == TaxEventKind::Acquisition
+                    && e.source_id == *lot_id
+                    && e.asset == disposal.asset
+                    && e.occurred_at <= disposal.occurred_at
+            });
+            if !is_lot {
+                return Err(Error::Validation(format!(
+                    "Lot {} not found for {}",
+                    lot_id, disposal.asset
+                )));
+            }
+        }
+
+        let mut client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to start transaction: {}", e)))?;
+
+        // Serializes the user's selections so a disposal cannot be selected twice
+        transaction
+            .execute("SELECT id FROM users WHERE id = $1 FOR UPDATE", &[&user_id])
+            .await
+            .map_err(|e| Error::Database(format!("Failed to lock user: {}", e)))?;
+        let selected = transaction
+            .query_opt(
+                "SELECT 1 FROM tax_lot_selections
+                 WHERE user_id = $1 AND disposal_source = $2 AND disposal_id = $3
+                 LIMIT 1",
+                &[&user_id, &disposal_source.as_str(), &disposal_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch lot selection: {}", e)))?;
+        if selected.is_some() {
+            return Err(Error::Validation(format!(
+                "Lots were already selected for disposal {}",
+                disposal_id
+            )));
+        }
+
+        let mut selections = Vec::new();
+        for (lot_id, amount) in lots {
+            transaction
+                .execute(
+                    "INSERT INTO tax_lot_selections (user_id, disposal_source, disposal_id, lot_id, amount)
+                     VALUES ($1, $2, $3, $4, $5)
+                     ON CONFLICT (user_id, disposal_source, disposal_id, lot_id)
+                     DO UPDATE SET amount = tax_lot_selections.amount + EXCLUDED.amount",
+                    &[&user_id, &disposal_source.as_str(), &disposal_id, lot_id, amount],
+                )
+                .await
+                .map_err(|e| Error::Database(format!("Failed to store lot selection: {}", e)))?;
+            selections.push(LotSelection {
+                disposal_source,
+                disposal_id,
+                lot_id: *lot_id,
+                amount: *amount,
+            });
+        }
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| Error::Database(format!("Failed to commit transaction: {}", e)))?;
+
+        Ok(selections)
+    }
+
+    async fn get_lot_selections(&self, user_id: Uuid) -> Result<Vec<LotSelection>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                "SELECT disposal_source, disposal_id, lot_id, amount
+                 FROM tax_lot_selections WHERE user_id = $1
+                 ORDER BY created_at, id",
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch lot selections: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(LotSelection {
+                    disposal_source: row.get::<_, String>("disposal_source").parse()?,
+                    disposal_id: row.get("disposal_id"),
+                    lot_id: row.get("lot_id"),
+                    amount: row.get("amount"),
+                })
+            })
+            .collect()
+    }
+
+    /// Every acquisition and disposal of the user, valued at the time
+    pub async fn load_events(&self, user_id: Uuid) -> Result<Vec<TaxEvent>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let mut events = Vec::new();
+
+        // Trades record their own price and fee; mints are reported by symbol where known
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT t.*, {} AS history_price FROM (
+                        SELECT id, action, amount, price_usd, total_value_usd, fee_usd,
+                               COALESCE(executed_at, NOW()) AS occurred_at,
+                               UPPER(COALESCE(
+                                   (SELECT pa.token_symbol FROM portfolio_assets pa
+                                    WHERE pa.token_mint = trade_executions.token_mint LIMIT 1),
+                                   token_mint)) AS asset
+                        FROM trade_executions
+                        WHERE user_id = $1 AND status = 'CONFIRMED'
+                     ) t",
+                    price_at("t.asset", "t.occurred_at")
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load trades: {}", e)))?;
+        for row in rows {
+            let asset: String = row.get("asset");
+            let amount: Decimal = row.get("amount");
+            let price: Option<Decimal> = row.get("price_usd");
+            let total: Option<Decimal> = row.get("total_value_usd");
+            let kind = match row.get::<_, String>("action").to_uppercase().as_str() {
+                "BUY" => TaxEventKind::Acquisition,
+                "SELL" => TaxEventKind::Disposal,
+                _ => continue,
+            };
+            events.push(TaxEvent {
+                source: TaxEventSource::Trade,
+                source_id: row.get("id"),
+                kind,
+                value_usd: total
+                    .or_else(|| price.map(|p| p * amount))
+                    .or_else(|| usd_value(&asset, amount, row.get("history_price"))),
+                asset,
+                amount,
+                fee_usd: row.get("fee_usd"),
+                occurred_at: row.get("occurred_at"),
+            });
+        }
+
+        // Conversions dispose of one asset for another; fees are charged in the source asset
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT c.*, {} AS from_price, {} AS to_price FROM (
+                        SELECT id, UPPER(from_asset) AS from_asset, UPPER(to_asset) AS to_asset,
+                               from_amount, to_amount,
+                               COALESCE(network_fee, 0) + COALESCE(platform_fee, 0)
+                                   + COALESCE(provider_fee, 0) AS fees,
+                               COALESCE(COALESCE(completed_at, created_at) AT TIME ZONE 'UTC', NOW()) AS occurred_at
+                        FROM conversions
+                        WHERE user_id = $1 AND LOWER(status) = 'completed'
+                     ) c",
+                    price_at("c.from_asset", "c.occurred_at"),
+                    price_at("c.to_asset", "c.occurred_at")
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load conversions: {}", e)))?;
+        for row in rows {
+            let id: Uuid = row.get("id");
+            let occurred_at: DateTime<Utc> = row.get("occurred_at");
+            let from_asset: String = row.get("from_asset");
+            let to_asset: String = row.get("to_asset");
+            let from_amount: Decimal = row.get("from_amount");
+            let to_amount: Decimal = row.get("to_amount");
+            let fees: Decimal = row.get("fees");
+
+            // What was received is the better measure of the exchange's value
+            let value_usd = usd_value(&to_asset, to_amount, row.get("to_price"))
+                .or_else(|| usd_value(&from_asset, from_amount, row.get("from_price")));
+            let fee_usd = match value_usd {
+                Some(value) if from_amount > Decimal::ZERO => fees * value / from_amount,
+                _ => Decimal::ZERO,
+            };
+
+            events.push(TaxEvent {
+                source: TaxEventSource::Conversion,
+                source_id: id,
+                kind: TaxEventKind::Disposal,
+                asset: from_asset,
+                amount: from_amount,
+                value_usd,
+                fee_usd,
+                occurred_at,
+            });
+            events.push(TaxEvent {
+                source: TaxEventSource::Conversion,
+                source_id: id,
+                kind: TaxEventKind::Acquisition,
+                asset: to_asset,
+                amount: to_amount,
+                value_usd,
+                fee_usd: Decimal::ZERO,
+                occurred_at,
+            });
+        }
+
+        // P2P exchanges are priced in the offer's quote asset; the seller pays the platform fee
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT e.*, {} AS asset_price, {} AS quote_price FROM (
+                        SELECT e.id, e.seller_user_id, UPPER(e.asset) AS asset, e.amount, e.price,
+                               e.platform_fee,
+                               UPPER(CASE WHEN o.offer_type = 'SELL' THEN o.to_asset ELSE o.from_asset END)
+                                   AS quote_asset,
+                               COALESCE(e.executed_at AT TIME ZONE 'UTC', NOW()) AS occurred_at
+                        FROM p2p_exchanges e
+                        JOIN p2p_offers o ON o.id = e.seller_offer_id
+                        WHERE (e.buyer_user_id = $1 OR e.seller_user_id = $1) AND e.status = 'COMPLETED'
+                     ) e",
+                    price_at("e.asset", "e.occurred_at"),
+                    price_at("e.quote_asset", "e.occurred_at")
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load P2P exchanges: {}", e)))?;
+        for row in rows {
+            let asset: String = row.get("asset");
+            let quote_asset: String = row.get("quote_asset");
+            let amount: Decimal = row.get("amount");
+            let price: Decimal = row.get("price");
+            let platform_fee: Decimal = row.get("platform_fee");
+            let quote_usd = usd_value(&quote_asset, Decimal::ONE, row.get("quote_price"));
+            let is_seller = row.get::<_, Option<Uuid>>("seller_user_id") == Some(user_id);
+
+            events.push(TaxEvent {
+                source: TaxEventSource::P2pExchange,
+                source_id: row.get("id"),
+                kind: if is_seller { TaxEventKind::Disposal } else { TaxEventKind::Acquisition },
+                value_usd: quote_usd
+                    .map(|q| amount * price * q)
+                    .or_else(|| usd_value(&asset, amount, row.get("asset_price"))),
+                fee_usd: if is_seller {
+                    quote_usd.map(|q| platform_fee * q).unwrap_or(Decimal::ZERO)
+                } else {
+                    Decimal::ZERO
+                },
+                asset,
+                amount,
+                occurred_at: row.get("occurred_at"),
+            });
+        }
+
+        // Proximity transfers move an asset at its market value
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT p.*, {} AS price FROM (
+                        SELECT id, sender_user_id, UPPER(asset) AS asset, amount,
+                               COALESCE(completed_at, created_at) AT TIME ZONE 'UTC' AS occurred_at
+                        FROM proximity_transfers
+                        WHERE (sender_user_id = $1 OR recipient_user_id = $1) AND status = 'Completed'
+                     ) p",
+                    price_at("p.asset", "p.occurred_at")
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load proximity transfers: {}", e)))?;
+        for row in rows {
+            let asset: String = row.get("asset");
+            let amount: Decimal = row.get("amount");
+            let sent = row.get::<_, Uuid>("sender_user_id") == user_id;
+            events.push(TaxEvent {
+                source: TaxEventSource::ProximityTransfer,
+                source_id: row.get("id"),
+                kind: if sent { TaxEventKind::Disposal } else { TaxEventKind::Acquisition },
+                value_usd: usd_value(&asset, amount, row.get("price")),
+                asset,
+                amount,
+                fee_usd: Decimal::ZERO,
+                occurred_at: row.get("occurred_at"),
+            });
+        }
+
+        // Staking rewards are acquired at their market value when received
+        let rows = client
+            .query(
+                &format!(
+                    "SELECT r.*, {} AS price FROM (
+                        SELECT id, UPPER(asset) AS asset, amount, rewarded_at AS occurred_at
+                        FROM staking_rewards WHERE user_id = $1
+                     ) r",
+                    price_at("r.asset", "r.occurred_at")
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load staking rewards: {}", e)))?;
+        for row in rows {
+            let asset: String = row.get("asset");
+            let amount: Decimal = row.get("amount");
+            events.push(TaxEvent {
+                source: TaxEventSource::StakingReward,
+                source_id: row.get("id"),
+                kind: TaxEventKind::Acquisition,
+                value_usd: usd_value(&asset, amount, row.get("price")),
+                asset,
+                amount,
+                fee_usd: Decimal::ZERO,
+                occurred_at: row.get("occurred_at"),
+            });
+        }
+
+        debug!("Loaded {} tax events for user {}", events.len(), user_id);
+        Ok(events)
+    }
+
+    /// Replay the user's history with their disposal method
+    pub async fn build_ledger(&self, user_id: Uuid) -> Result<LotLedger> {
+        let events = self.load_events(user_id).await?;
+        self.replay(user_id, &events).await
+    }
+
+    /// Replay `events`, e.g. a subset of [`Self::load_events`], with the
+    /// user's disposal method changes and lot selections
+    pub async fn replay(&self, user_id: Uuid, events: &[TaxEvent]) -> Result<LotLedger> {
+        let changes = self.get_method_changes(user_id).await?;
+        let selections = if changes.iter().any(|c| c.disposal_method == DisposalMethod::SpecificId) {
+            self.get_lot_selections(user_id).await?
+        } else {
+            Vec::new()
+        };
+        Ok(LotLedger::build_with_changes(events, &changes, &selections))
+    }
+
+    /// Open lots with unrealized P&L at the latest recorded prices, and all disposals
+    pub async fn get_lot_report(&self, user_id: Uuid) -> Result<TaxLotReport> {
+        let disposal_method = self.get_disposal_method(user_id).await?;
+        let ledger = self.build_ledger(user_id).await?;
+
+        let assets: Vec<String> = ledger.open_lots().map(|lot| lot.asset.clone()).collect();
+        let prices = self.latest_prices(&assets).await?;
+
+        let lots: Vec<LotValuation> = ledger
+            .open_lots()
+            .map(|lot| {
+                let price_usd = prices.get(&lot.asset).copied();
+                let market_value_usd = usd_value(&lot.asset, lot.remaining, price_usd);
+                LotValuation {
+                    price_usd: price_usd.or_else(|| market_value_usd.map(|_| Decimal::ONE)),
+                    unrealized_gain_usd: market_value_usd.map(|value| value - lot.remaining_cost_basis_usd),
+                    market_value_usd,
+                    lot: lot.clone(),
+                }
+            })
+            .collect();
+
+        Ok(TaxLotReport {
+            user_id,
+            disposal_method,
+            unrealized_gain_usd: lots.iter().filter_map(|l| l.unrealized_gain_usd).sum(),
+            realized_gain_usd: ledger.realized_gain_usd(),
+            disposals: ledger.disposals,
+            lots,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Realized gain of each of `transaction_ids` that disposed of an asset,
+    /// from the ledger of the user who disposed of it
+    pub async fn realized_gains(&self, transaction_ids: &[Uuid]) -> Result<HashMap<Uuid, RealizedGain>> {
+        if transaction_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+        let rows = client
+            .query(
+                "SELECT DISTINCT user_id FROM (
+                    SELECT user_id FROM trade_executions WHERE id = ANY($1)
+                    UNION ALL SELECT user_id FROM conversions WHERE id = ANY($1)
+                    UNION ALL SELECT seller_user_id FROM p2p_exchanges WHERE id = ANY($1)
+                    UNION ALL SELECT sender_user_id FROM proximity_transfers WHERE id = ANY($1)
+                 ) owners WHERE user_id IS NOT NULL",
+                &[&transaction_ids],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to resolve transaction owners: {}", e)))?;
+        drop(client);
+
+        let mut gains = HashMap::new();
+        for row in rows {
+            let ledger = self.build_ledger(row.get("user_id")).await?;
+            for id in transaction_ids {
+                let disposals: Vec<Disposal> =
+                    ledger.disposals.iter().filter(|d| d.source_id == *id).cloned().collect();
+                if let Some(gain) = RealizedGain::from_disposals(&disposals) {
+                    gains.insert(*id, gain);
+                }
+            }
+        }
+        Ok(gains)
+    }
+
+    /// Record a USD price for valuing later transactions
+    pub async fn record_price(
+        &self,
+        asset: &str,
+        price_usd: Decimal,
+        recorded_at: DateTime<Utc>,
+        source: &str,
+    ) -> Result<()> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        client
+            .execute(
+                "INSERT INTO asset_price_history (asset, price_usd, recorded_at, source)
+                 VALUES (UPPER($1), $2, $3, $4)
+                 ON CONFLICT (asset, recorded_at) DO NOTHING",
+                &[&asset, &price_usd, &recorded_at, &source],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to record price: {}", e)))?;
+        Ok(())
+    }
+
+    /// Latest recorded USD price of each of `assets`
+    pub async fn latest_prices(&self, assets: &[String]) -> Result<HashMap<String, Decimal>> {
+        self.prices_at(assets, Utc::now()).await
+    }
+
+    /// Latest USD price of each of `assets` recorded at or before `at`
+    pub async fn prices_at(&self, assets: &[String], at: DateTime<Utc>) -> Result<HashMap<String, Decimal>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                "SELECT DISTINCT ON (asset) asset, price_usd
+                 FROM asset_price_history
+                 WHERE asset = ANY($1) AND recorded_at <= $2
+                 ORDER BY asset, recorded_at DESC",
+                &[&assets, &at],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch prices: {}", e)))?;
+
+        Ok(rows.iter().map(|row| (row.get("asset"), row.get("price_usd"))).collect())
+    }
+
+    /// Every USD price of `asset` recorded before `until`, oldest first
+    pub async fn price_history(&self, asset: &str, until: DateTime<Utc>) -> Result<Vec<(DateTime<Utc>, Decimal)>> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let rows = client
+            .query(
+                "SELECT recorded_at, price_usd
+                 FROM asset_price_history
+                 WHERE asset = UPPER($1) AND recorded_at < $2
+                 ORDER BY recorded_at",
+                &[&asset, &until],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch price history: {}", e)))?;
+
+        Ok(rows.iter().map(|row| (row.get("recorded_at"), row.get("price_usd"))).collect())
+    }
+
+    /// Start a background worker that records the mesh consensus prices
+    /// into the price history every `interval`
+    pub fn start_price_recorder(
+        self: Arc<Self>,
+        price_cache: Arc<PriceCache>,
+        interval: std::time::Duration,
+    ) -> JoinHandle<()> {
+        info!("Starting price history recorder with interval: {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let prices = match price_cache.get_all_consensus().await {
+                    Ok(prices) => prices,
+                    Err(e) => {
+                        error!("Error reading consensus prices: {}", e);
+                        continue;
+                    }
+                };
+                for (asset, data) in prices {
+                    let Ok(price) = Decimal::from_str(&data.price) else {
+                        continue;
+                    };
+                    if let Err(e) = self.record_price(&asset, price, data.timestamp, "mesh").await {
+                        error!("Error recording price of {}: {}", asset, e);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+    }
+
+    fn event(kind: TaxEventKind, id: u128, amount: i64, value: i64, when: DateTime<Utc>) -> TaxEvent {
+        TaxEvent {
+            source: TaxEventSource::Trade,
+            source_id: Uuid::from_u128(id),
+            kind,
+            asset: "SOL".to_string(),
+            amount: Decimal::from(amount),
+            value_usd: Some(Decimal::from(value)),
+            fee_usd: Decimal::ZERO,
+            occurred_at: when,
+        }
+    }
+
+    /// Two buys at $10 and $30, then a sale of 15 for $600
+    fn history() -> Vec<TaxEvent> {
+        vec![
+            event(TaxEventKind::Acquisition, 1, 10, 100, at(2022, 1, 1)),
+            event(TaxEventKind::Acquisition, 2, 10, 300, at(2023, 6, 1)),
+            event(TaxEventKind::Disposal, 3, 15, 600, at(2023, 9, 1)),
+        ]
+    }
+
+    #[test]
+    fn test_disposal_methods_choose_lots() {
+        let fifo = LotLedger::build(&history(), DisposalMethod::Fifo, &[]);
+        assert_eq!(fifo.disposals.len(), 2);
+        assert_eq!(fifo.disposals[0].lot_id, Some(Uuid::from_u128(1)));
+        assert_eq!(fifo.disposals[0].holding_period, HoldingPeriod::LongTerm);
+        assert_eq!(fifo.disposals[1].holding_period, HoldingPeriod::ShortTerm);
+        // 10 @ $10 + 5 @ $30 against $600
+        assert_eq!(fifo.realized_gain_usd(), Decimal::from(350));
+        assert_eq!(fifo.average_cost("sol"), Some(Decimal::from(30)));
+
+        let lifo = LotLedger::build(&history(), DisposalMethod::Lifo, &[]);
+        assert_eq!(lifo.disposals[0].lot_id, Some(Uuid::from_u128(2)));
+        // 10 @ $30 + 5 @ $10
+        assert_eq!(lifo.realized_gain_usd(), Decimal::from(250));
+
+        // Same order as LIFO here, since the newer lot is the more expensive
+        let hifo = LotLedger::build(&history(), DisposalMethod::Hifo, &[]);
+        assert_eq!(hifo.realized_gain_usd(), Decimal::from(250));
+
+        let proceeds: Decimal = fifo.disposals.iter().map(|d| d.proceeds_usd).sum();
+        assert_eq!(proceeds, Decimal::from(600));
+    }
+
+    #[test]
+    fn test_specific_id_selection_then_fifo() {
+        let selection = LotSelection {
+            disposal_source: TaxEventSource::Trade,
+            disposal_id: Uuid::from_u128(3),
+            lot_id: Uuid::from_u128(2),
+            amount: Decimal::from(4),
+        };
+        let ledger = LotLedger::build(&history(), DisposalMethod::SpecificId, std::slice::from_ref(&selection));
+
+        assert_eq!(ledger.disposals[0].lot_id, Some(Uuid::from_u128(2)));
+        assert_eq!(ledger.disposals[0].amount, Decimal::from(4));
+        assert_eq!(ledger.disposals[1].lot_id, Some(Uuid::from_u128(1)));
+        assert_eq!(ledger.disposals[1].amount, Decimal::from(10));
+        assert_eq!(ledger.disposals[2].lot_id, Some(Uuid::from_u128(2)));
+        assert_eq!(ledger.disposals[2].amount, Decimal::from(1));
+
+        // Selections are ignored under other methods
+        let fifo = LotLedger::build(&history(), DisposalMethod::Fifo, &[selection]);
+        assert_eq!(fifo.disposals[0].lot_id, Some(Uuid::from_u128(1)));
+    }
+
+    #[test]
+    fn test_method_changes_apply_from_their_date() {
+        let events = vec![
+            event(TaxEventKind::Acquisition, 1, 10, 100, at(2022, 1, 1)),
+            event(TaxEventKind::Acquisition, 2, 10, 300, at(2023, 6, 1)),
+            event(TaxEventKind::Disposal, 3, 5, 250, at(2023, 9, 1)),
+            event(TaxEventKind::Disposal, 4, 5, 250, at(2024, 1, 1)),
+        ];
+        let changes = vec![DisposalMethodChange {
+            disposal_method: DisposalMethod::Lifo,
+            effective_from: at(2023, 10, 1),
+        }];
+        assert_eq!(method_at(&changes, at(2023, 9, 1)), DisposalMethod::Fifo);
+        assert_eq!(method_at(&changes, at(2023, 10, 1)), DisposalMethod::Lifo);
+
+        // The earlier sale keeps its FIFO lot, the later one is matched LIFO
+        let ledger = LotLedger::build_with_changes(&events, &changes, &[]);
+        assert_eq!(ledger.disposals[0].lot_id, Some(Uuid::from_u128(1)));
+        assert_eq!(ledger.disposals[0].gain_usd, Decimal::from(200));
+        assert_eq!(ledger.disposals[1].lot_id, Some(Uuid::from_u128(2)));
+        assert_eq!(ledger.disposals[1].gain_usd, Decimal::from(100));
+    }
+
+    #[test]
+    fn test_fees_adjust_basis_and_proceeds() {
+        let mut buy = event(TaxEventKind::Acquisition, 1, 2, 100, at(2024, 1, 1));
+        buy.fee_usd = Decimal::from(4);
+        let mut sell = event(TaxEventKind::Disposal, 2, 1, 80, at(2024, 2, 1));
+        sell.fee_usd = Decimal::from(2);
+
+        let ledger = LotLedger::build(&[sell, buy], DisposalMethod::Fifo, &[]);
+        let disposal = &ledger.disposals[0];
+        assert_eq!(disposal.cost_basis_usd, Decimal::from(52));
+        assert_eq!(disposal.proceeds_usd, Decimal::from(78));
+        assert_eq!(disposal.gain_usd, Decimal::from(26));
+        assert_eq!(ledger.lots[0].remaining_cost_basis_usd, Decimal::from(52));
+    }
+
+    #[test]
+    fn test_disposal_beyond_holdings_has_zero_basis() {
+        let events = vec![
+            event(TaxEventKind::Acquisition, 1, 1, 10, at(2024, 1, 1)),
+            event(TaxEventKind::Disposal, 2, 3, 90, at(2024, 1, 2)),
+        ];
+        let ledger = LotLedger::build(&events, DisposalMethod::Fifo, &[]);
+
+        assert_eq!(ledger.disposals.len(), 2);
+        assert_eq!(ledger.disposals[1].lot_id, None);
+        assert_eq!(ledger.disposals[1].cost_basis_usd, Decimal::ZERO);
+        assert_eq!(ledger.disposals[1].proceeds_usd, Decimal::from(60));
+        assert_eq!(ledger.open_lots().count(), 0);
+    }
+
+    #[test]
+    fn test_holding_period_boundary() {
+        let bought = at(2023, 3, 15);
+        assert_eq!(HoldingPeriod::between(bought, at(2024, 3, 15)), HoldingPeriod::ShortTerm);
+        assert_eq!(HoldingPeriod::between(bought, at(2024, 3, 16)), HoldingPeriod::LongTerm);
+    }
+
+    #[test]
+    fn test_preview_leaves_ledger_unchanged() {
+        let ledger = LotLedger::build(&history(), DisposalMethod::Fifo, &[]);
+        let preview = ledger.preview_disposal("SOL", Decimal::from(5), Decimal::from(250), at(2024, 1, 1), DisposalMethod::Fifo);
+
+        assert_eq!(RealizedGain::from_disposals(&preview).unwrap().gain_usd, Decimal::from(100));
+        assert_eq!(ledger.open_lots().map(|lot| lot.remaining).sum::<Decimal>(), Decimal::from(5));
+    }
+}
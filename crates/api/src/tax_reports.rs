@@ -0,0 +1,974 @@
+//! Tax-year reports built from a user's tax lots
+//!
+//! A [`TaxReport`] covers one tax year of one jurisdiction and contains a
+//! capital gains report with the columns of IRS Form 8949, an income report
+//! of staking rewards and P2P platform fees, and the user's holdings at the
+//! end of the year. Each part can be exported as CSV or PDF.
+//!
+//! The jurisdiction's profile sets the tax-year boundaries, the holding
+//! period after which a disposal is long-term and the reporting currency.
+//! Values are converted from USD at the time of each transaction, using the
+//! asset price history for assets and the USD price of one unit of the
+//! currency, recorded under its code (e.g. `GBP`), for the exchange rate.
+//! Trade and P2P fees are costs: buying fees are part of a lot's basis and
+//! selling fees are deducted from proceeds, so P2P fees are listed in the
+//! income report for reference but not counted as income.
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::tax_lots::{method_at, usd_value, HoldingPeriod, TaxEventKind, TaxEventSource, TaxLotService};
+
+/// Tax rules a report is prepared under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaxJurisdiction {
+    /// United States: calendar year, long-term after one year, USD
+    Us,
+    /// United Kingdom: 6 April to 5 April, no holding period distinction, GBP
+    Uk,
+    /// Canada: calendar year, no holding period distinction, CAD
+    Ca,
+    /// Australia: 1 July to 30 June, named by the year it ends, CGT discount
+    /// after one year, AUD
+    Au,
+    /// Germany: calendar year, private disposals tax-free after one year, EUR
+    De,
+}
+
+impl TaxJurisdiction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxJurisdiction::Us => "US",
+            TaxJurisdiction::Uk => "UK",
+            TaxJurisdiction::Ca => "CA",
+            TaxJurisdiction::Au => "AU",
+            TaxJurisdiction::De => "DE",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaxJurisdiction::Us => "United States",
+            TaxJurisdiction::Uk => "United Kingdom",
+            TaxJurisdiction::Ca => "Canada",
+            TaxJurisdiction::Au => "Australia",
+            TaxJurisdiction::De => "Germany",
+        }
+    }
+
+    /// Currency the jurisdiction's reports are valued in
+    pub fn currency(&self) -> &'static str {
+        match self {
+            TaxJurisdiction::Us => "USD",
+            TaxJurisdiction::Uk => "GBP",
+            TaxJurisdiction::Ca => "CAD",
+            TaxJurisdiction::Au => "AUD",
+            TaxJurisdiction::De => "EUR",
+        }
+    }
+
+    /// Months an asset must be held for a disposal to be long-term, `None`
+    /// if the jurisdiction taxes all disposals alike
+    pub fn long_term_after_months(&self) -> Option<u32> {
+        match self {
+            TaxJurisdiction::Us | TaxJurisdiction::Au | TaxJurisdiction::De => Some(12),
+            TaxJurisdiction::Uk | TaxJurisdiction::Ca => None,
+        }
+    }
+
+    /// Holding period of a disposal under this jurisdiction
+    ///
+    /// Disposals of unknown acquisition date are never long-term.
+    pub fn holding_period(
+        &self,
+        acquired_at: Option<DateTime<Utc>>,
+        disposed_at: DateTime<Utc>,
+    ) -> Option<HoldingPeriod> {
+        let months = self.long_term_after_months()?;
+        let long_term = acquired_at
+            .and_then(|at| at.checked_add_months(Months::new(months)))
+            .is_some_and(|threshold| disposed_at > threshold);
+        Some(if long_term { HoldingPeriod::LongTerm } else { HoldingPeriod::ShortTerm })
+    }
+
+    /// First instant of tax year `year` and of the year after it
+    pub fn tax_year(&self, year: i32) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let (start_year, month, day) = match self {
+            TaxJurisdiction::Us | TaxJurisdiction::Ca | TaxJurisdiction::De => (year, 1, 1),
+            TaxJurisdiction::Uk => (year, 4, 6),
+            TaxJurisdiction::Au => (year - 1, 7, 1),
+        };
+        let start = NaiveDate::from_ymd_opt(start_year, month, day)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|start| start.and_utc())
+            .ok_or_else(|| Error::Validation(format!("Invalid tax year: {}", year)))?;
+        let end = start
+            .checked_add_months(Months::new(12))
+            .ok_or_else(|| Error::Validation(format!("Invalid tax year: {}", year)))?;
+        Ok((start, end))
+    }
+}
+
+impl FromStr for TaxJurisdiction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "US" => Ok(TaxJurisdiction::Us),
+            "UK" | "GB" => Ok(TaxJurisdiction::Uk),
+            "CA" => Ok(TaxJurisdiction::Ca),
+            "AU" => Ok(TaxJurisdiction::Au),
+            "DE" => Ok(TaxJurisdiction::De),
+            other => Err(Error::Validation(format!("Unsupported tax jurisdiction: {}", other))),
+        }
+    }
+}
+
+/// Converts USD values into a report's currency at the rate of the time
+struct ExchangeRates {
+    currency: &'static str,
+    /// USD price of one unit of the currency, oldest first; unused for USD
+    history: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+impl ExchangeRates {
+    /// Value of `usd` in the currency at the latest rate recorded at or before `at`
+    fn convert(&self, usd: Decimal, at: DateTime<Utc>) -> Result<Decimal> {
+        if self.currency == "USD" {
+            return Ok(usd);
+        }
+        let recorded = self.history.partition_point(|(recorded_at, _)| *recorded_at <= at);
+        match recorded.checked_sub(1).map(|i| self.history[i].1) {
+            Some(rate) if rate > Decimal::ZERO => Ok(usd / rate),
+            _ => Err(Error::Internal(format!(
+                "No USD price of {} recorded at or before {}",
+                self.currency,
+                at.to_rfc3339()
+            ))),
+        }
+    }
+}
+
+/// Part of a tax report that can be exported on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaxReportKind {
+    CapitalGains,
+    Income,
+    Holdings,
+}
+
+impl TaxReportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxReportKind::CapitalGains => "capital-gains",
+            TaxReportKind::Income => "income",
+            TaxReportKind::Holdings => "holdings",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            TaxReportKind::CapitalGains => "Capital Gains and Losses",
+            TaxReportKind::Income => "Income",
+            TaxReportKind::Holdings => "Year-End Holdings",
+        }
+    }
+}
+
+impl FromStr for TaxReportKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "capital-gains" => Ok(TaxReportKind::CapitalGains),
+            "income" => Ok(TaxReportKind::Income),
+            "holdings" => Ok(TaxReportKind::Holdings),
+            other => Err(Error::Validation(format!("Unknown tax report: {}", other))),
+        }
+    }
+}
+
+/// One Form 8949 line: the part of a disposal matched against one lot
+///
+/// Values are in the report's currency.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapitalGainLine {
+    /// (a) Description of property, e.g. `"1.5 SOL"`
+    pub description: String,
+    pub asset: String,
+    pub amount: Decimal,
+    /// (b) Date acquired, `None` when the disposal exceeded known holdings
+    pub acquired_at: Option<DateTime<Utc>>,
+    /// (c) Date sold or disposed of
+    pub disposed_at: DateTime<Utc>,
+    /// (d) Proceeds, net of selling fees, at the disposal's exchange rate
+    pub proceeds: Decimal,
+    /// (e) Cost basis, including acquisition fees, at the acquisition's exchange rate
+    pub cost_basis: Decimal,
+    /// (h) Gain or loss
+    pub gain: Decimal,
+    /// Part I (short-term) or Part II (long-term); `None` if the jurisdiction
+    /// does not distinguish
+    pub holding_period: Option<HoldingPeriod>,
+    pub source: TaxEventSource,
+    pub transaction_id: Uuid,
+    /// No price was recorded for the proceeds or the acquisition
+    pub missing_price: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IncomeCategory {
+    /// Reward received from a staking position, income at its market value
+    StakingReward,
+    /// Platform fee paid on a P2P sale, already deducted from its proceeds
+    /// and so not counted as income
+    P2pFee,
+}
+
+impl IncomeCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncomeCategory::StakingReward => "STAKING_REWARD",
+            IncomeCategory::P2pFee => "P2P_FEE",
+        }
+    }
+}
+
+/// One line of the income report
+#[derive(Debug, Clone, Serialize)]
+pub struct IncomeLine {
+    pub category: IncomeCategory,
+    pub transaction_id: Uuid,
+    pub date: DateTime<Utc>,
+    pub asset: String,
+    /// Amount received, or for fees the amount sold
+    pub amount: Decimal,
+    /// Market value of a reward, or the fee paid
+    pub value: Option<Decimal>,
+}
+
+/// An asset held at the end of the tax year
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingLine {
+    pub asset: String,
+    pub amount: Decimal,
+    pub lot_count: usize,
+    /// Sum of the lots' bases at their acquisitions' exchange rates
+    pub cost_basis: Decimal,
+    /// Price at the end of the tax year
+    pub price: Option<Decimal>,
+    pub market_value: Option<Decimal>,
+    pub unrealized_gain: Option<Decimal>,
+}
+
+/// Totals of a report, in its currency
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaxReportSummary {
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub short_term_gain: Decimal,
+    pub long_term_gain: Decimal,
+    /// All gains and losses, including those of jurisdictions without holding periods
+    pub total_gain: Decimal,
+    /// Trade and P2P fees of the year's disposals, deducted from their proceeds
+    pub selling_fees: Decimal,
+    pub staking_income: Decimal,
+    /// Part of `selling_fees` paid to the P2P platform
+    pub p2p_fees: Decimal,
+    pub year_end_value: Decimal,
+    /// Lines valued without a recorded price, which need review
+    pub lines_missing_price: usize,
+}
+
+/// A user's tax report for one tax year
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxReport {
+    pub user_id: Uuid,
+    pub tax_year: i32,
+    pub jurisdiction: TaxJurisdiction,
+    pub period_start: DateTime<Utc>,
+    /// Exclusive
+    pub period_end: DateTime<Utc>,
+    /// Method in effect at the end of the period, or now for the current one
+    pub disposal_method: crate::tax_lots::DisposalMethod,
+    pub currency: String,
+    pub capital_gains: Vec<CapitalGainLine>,
+    pub income: Vec<IncomeLine>,
+    pub holdings: Vec<HoldingLine>,
+    pub summary: TaxReportSummary,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Generates tax reports from the user's tax lots
+pub struct TaxReportService {
+    tax_lots: Arc<TaxLotService>,
+}
+
+impl TaxReportService {
+    pub fn new(tax_lots: Arc<TaxLotService>) -> Self {
+        Self { tax_lots }
+    }
+
+    /// Build the report for `tax_year` under `jurisdiction`
+    ///
+    /// Fails if the price history has no exchange rate for the
+    /// jurisdiction's currency at the time of a transaction in the report.
+    pub async fn generate_report(
+        &self,
+        user_id: Uuid,
+        tax_year: i32,
+        jurisdiction: TaxJurisdiction,
+    ) -> Result<TaxReport> {
+        let (period_start, period_end) = jurisdiction.tax_year(tax_year)?;
+        info!(
+            "Generating {} tax report {} for user {}",
+            jurisdiction.as_str(),
+            tax_year,
+            user_id
+        );
+
+        let currency = jurisdiction.currency();
+        let rates = ExchangeRates {
+            currency,
+            history: if currency == "USD" {
+                Vec::new()
+            } else {
+                self.tax_lots.price_history(currency, period_end).await?
+            },
+        };
+
+        let in_period = |at: &DateTime<Utc>| *at >= period_start && *at < period_end;
+        let events = self.tax_lots.load_events(user_id).await?;
+        let method_changes = self.tax_lots.get_method_changes(user_id).await?;
+        let disposal_method = method_at(
+            &method_changes,
+            (period_end - chrono::Duration::seconds(1)).min(Utc::now()),
+        );
+
+        // Disposals are matched against the whole history, so lots consumed
+        // in earlier years are not sold again
+        let ledger = self.tax_lots.replay(user_id, &events).await?;
+        let capital_gains = ledger
+            .disposals
+            .iter()
+            .filter(|d| in_period(&d.disposed_at))
+            .map(|d| {
+                let proceeds = rates.convert(d.proceeds_usd, d.disposed_at)?;
+                let cost_basis = rates.convert(d.cost_basis_usd, d.acquired_at.unwrap_or(d.disposed_at))?;
+                Ok(CapitalGainLine {
+                    description: format!("{} {}", d.amount.normalize(), d.asset),
+                    asset: d.asset.clone(),
+                    amount: d.amount,
+                    acquired_at: d.acquired_at,
+                    disposed_at: d.disposed_at,
+                    proceeds,
+                    cost_basis,
+                    gain: proceeds - cost_basis,
+                    holding_period: jurisdiction.holding_period(d.acquired_at, d.disposed_at),
+                    source: d.source,
+                    transaction_id: d.source_id,
+                    missing_price: d.missing_price,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut income = Vec::new();
+        let mut selling_fees = Decimal::ZERO;
+        for e in events.iter().filter(|e| in_period(&e.occurred_at)) {
+            match (e.source, e.kind) {
+                (TaxEventSource::StakingReward, TaxEventKind::Acquisition) => income.push(IncomeLine {
+                    category: IncomeCategory::StakingReward,
+                    transaction_id: e.source_id,
+                    date: e.occurred_at,
+                    asset: e.asset.clone(),
+                    amount: e.amount,
+                    value: e.value_usd.map(|value| rates.convert(value, e.occurred_at)).transpose()?,
+                }),
+                (source, TaxEventKind::Disposal) if e.fee_usd > Decimal::ZERO => {
+                    let fee = rates.convert(e.fee_usd, e.occurred_at)?;
+                    selling_fees += fee;
+                    if source == TaxEventSource::P2pExchange {
+                        income.push(IncomeLine {
+                            category: IncomeCategory::P2pFee,
+                            transaction_id: e.source_id,
+                            date: e.occurred_at,
+                            asset: e.asset.clone(),
+                            amount: e.amount,
+                            value: Some(fee),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        income.sort_by_key(|line| line.date);
+
+        // Holdings are what the history up to the end of the year leaves open
+        let year_end_events: Vec<_> = events.into_iter().filter(|e| e.occurred_at < period_end).collect();
+        let year_end = self.tax_lots.replay(user_id, &year_end_events).await?;
+        let mut by_asset: BTreeMap<String, (Decimal, usize, Decimal)> = BTreeMap::new();
+        for lot in year_end.open_lots() {
+            let cost_basis = rates.convert(lot.remaining_cost_basis_usd, lot.acquired_at)?;
+            let entry = by_asset.entry(lot.asset.clone()).or_default();
+            entry.0 += lot.remaining;
+            entry.1 += 1;
+            entry.2 += cost_basis;
+        }
+        let year_end_at = period_end - chrono::Duration::microseconds(1);
+        let assets: Vec<String> = by_asset.keys().cloned().collect();
+        let prices = self.tax_lots.prices_at(&assets, year_end_at).await?;
+        let holdings = by_asset
+            .into_iter()
+            .map(|(asset, (amount, lot_count, cost_basis))| {
+                let market_value = usd_value(&asset, amount, prices.get(&asset).copied())
+                    .map(|value| rates.convert(value, year_end_at))
+                    .transpose()?;
+                Ok(HoldingLine {
+                    price: market_value.map(|value| value / amount),
+                    unrealized_gain: market_value.map(|value| value - cost_basis),
+                    market_value,
+                    asset,
+                    amount,
+                    lot_count,
+                    cost_basis,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let summary = TaxReportSummary {
+            selling_fees,
+            ..Self::summarize(&capital_gains, &income, &holdings)
+        };
+
+        Ok(TaxReport {
+            user_id,
+            tax_year,
+            jurisdiction,
+            period_start,
+            period_end,
+            disposal_method,
+            currency: currency.to_string(),
+            capital_gains,
+            income,
+            holdings,
+            summary,
+            generated_at: Utc::now(),
+        })
+    }
+
+    fn summarize(capital_gains: &[CapitalGainLine], income: &[IncomeLine], holdings: &[HoldingLine]) -> TaxReportSummary {
+        let mut summary = TaxReportSummary::default();
+        for line in capital_gains {
+            summary.proceeds += line.proceeds;
+            summary.cost_basis += line.cost_basis;
+            summary.total_gain += line.gain;
+            match line.holding_period {
+                Some(HoldingPeriod::ShortTerm) => summary.short_term_gain += line.gain,
+                Some(HoldingPeriod::LongTerm) => summary.long_term_gain += line.gain,
+                None => {}
+            }
+            if line.missing_price {
+                summary.lines_missing_price += 1;
+            }
+        }
+        for line in income {
+            let value = line.value.unwrap_or(Decimal::ZERO);
+            match line.category {
+                IncomeCategory::StakingReward => summary.staking_income += value,
+                IncomeCategory::P2pFee => summary.p2p_fees += value,
+            }
+            if line.value.is_none() {
+                summary.lines_missing_price += 1;
+            }
+        }
+        for line in holdings {
+            match line.market_value {
+                Some(value) => summary.year_end_value += value,
+                None => summary.lines_missing_price += 1,
+            }
+        }
+        summary
+    }
+}
+
+impl TaxReport {
+    /// Column headers and rows of one part of the report
+    fn table(&self, kind: TaxReportKind) -> (Vec<String>, Vec<Vec<String>>) {
+        let in_currency = |header: &str| format!("{} ({})", header, self.currency);
+        match kind {
+            TaxReportKind::CapitalGains => (
+                vec![
+                    "Description".to_string(),
+                    "Date Acquired".to_string(),
+                    "Date Sold".to_string(),
+                    in_currency("Proceeds"),
+                    in_currency("Cost Basis"),
+                    in_currency("Gain/Loss"),
+                    "Term".to_string(),
+                    "Source".to_string(),
+                    "Transaction ID".to_string(),
+                    "Missing Price".to_string(),
+                ],
+                self
+                    .capital_gains
+                    .iter()
+                    .map(|line| {
+                        vec![
+                            line.description.clone(),
+                            line.acquired_at.map(format_date).unwrap_or_else(|| "VARIOUS".to_string()),
+                            format_date(line.disposed_at),
+                            format_money(line.proceeds),
+                            format_money(line.cost_basis),
+                            format_money(line.gain),
+                            line.holding_period.map(|p| p.as_str()).unwrap_or("N/A").to_string(),
+                            line.source.as_str().to_string(),
+                            line.transaction_id.to_string(),
+                            yes_no(line.missing_price),
+                        ]
+                    })
+                    .collect(),
+            ),
+            TaxReportKind::Income => (
+                vec![
+                    "Date".to_string(),
+                    "Category".to_string(),
+                    "Asset".to_string(),
+                    "Amount".to_string(),
+                    in_currency("Value"),
+                    "Transaction ID".to_string(),
+                ],
+                self
+                    .income
+                    .iter()
+                    .map(|line| {
+                        vec![
+                            format_date(line.date),
+                            line.category.as_str().to_string(),
+                            line.asset.clone(),
+                            line.amount.normalize().to_string(),
+                            line.value.map(format_money).unwrap_or_default(),
+                            line.transaction_id.to_string(),
+                        ]
+                    })
+                    .collect(),
+            ),
+            TaxReportKind::Holdings => (
+                vec![
+                    "Asset".to_string(),
+                    "Amount".to_string(),
+                    "Lots".to_string(),
+                    in_currency("Cost Basis"),
+                    in_currency("Price"),
+                    in_currency("Market Value"),
+                    in_currency("Unrealized Gain/Loss"),
+                ],
+                self
+                    .holdings
+                    .iter()
+                    .map(|line| {
+                        vec![
+                            line.asset.clone(),
+                            line.amount.normalize().to_string(),
+                            line.lot_count.to_string(),
+                            format_money(line.cost_basis),
+                            line.price.map(|p| p.round_dp(6).normalize().to_string()).unwrap_or_default(),
+                            line.market_value.map(format_money).unwrap_or_default(),
+                            line.unrealized_gain.map(format_money).unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Totals printed under a part of the report
+    fn totals(&self, kind: TaxReportKind) -> Vec<String> {
+        let summary = &self.summary;
+        let money = |value: Decimal| format!("{} {}", format_money(value), self.currency);
+        let mut totals = match kind {
+            TaxReportKind::CapitalGains => {
+                let mut totals = vec![
+                    format!("Total proceeds: {}", money(summary.proceeds)),
+                    format!("Total cost basis: {}", money(summary.cost_basis)),
+                ];
+                if self.jurisdiction.long_term_after_months().is_some() {
+                    totals.push(format!("Short-term gain/loss: {}", money(summary.short_term_gain)));
+                    totals.push(format!("Long-term gain/loss: {}", money(summary.long_term_gain)));
+                }
+                totals.push(format!("Net gain/loss: {}", money(summary.total_gain)));
+                totals.push(format!(
+                    "Selling fees: {} (already deducted from proceeds)",
+                    money(summary.selling_fees)
+                ));
+                totals
+            }
+            TaxReportKind::Income => vec![
+                format!("Staking income: {}", money(summary.staking_income)),
+                format!(
+                    "P2P platform fees paid: {} (already deducted from proceeds, not income)",
+                    money(summary.p2p_fees)
+                ),
+            ],
+            TaxReportKind::Holdings => vec![format!(
+                "Value at {}: {}",
+                format_date(self.period_end - chrono::Duration::days(1)),
+                money(summary.year_end_value)
+            )],
+        };
+        if summary.lines_missing_price > 0 {
+            totals.push(format!(
+                "{} line(s) valued without a recorded price need review",
+                summary.lines_missing_price
+            ));
+        }
+        totals
+    }
+
+    /// Export one part of the report as CSV
+    pub fn to_csv(&self, kind: TaxReportKind) -> Result<Vec<u8>> {
+        let (headers, rows) = self.table(kind);
+
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.write_record(&headers).map_err(|e| {
+            error!("Failed to write CSV header: {}", e);
+            Error::Internal(format!("Failed to write CSV header: {}", e))
+        })?;
+        for row in &rows {
+            wtr.write_record(row).map_err(|e| {
+                error!("Failed to write CSV row: {}", e);
+                Error::Internal(format!("Failed to write CSV row: {}", e))
+            })?;
+        }
+
+        let csv_bytes = wtr.into_inner().map_err(|e| {
+            error!("Failed to finalize CSV: {}", e);
+            Error::Internal(format!("Failed to finalize CSV: {}", e))
+        })?;
+
+        info!(
+            "Exported {} report of user {} as CSV ({} rows)",
+            kind.as_str(),
+            self.user_id,
+            rows.len()
+        );
+        Ok(csv_bytes)
+    }
+
+    /// Export one part of the report as a landscape PDF table
+    pub fn to_pdf(&self, kind: TaxReportKind) -> Result<Vec<u8>> {
+        use printpdf::*;
+
+        const PAGE_WIDTH: f32 = 297.0;
+        const PAGE_HEIGHT: f32 = 210.0;
+        const LINE_HEIGHT: f32 = 4.5;
+        // Widest cell that fits on a line with every other column, in characters
+        const MAX_COLUMN_WIDTH: usize = 36;
+
+        let (headers, rows) = self.table(kind);
+        let title = format!("{} - Tax Year {}", kind.title(), self.tax_year);
+
+        let (doc, page1, layer1) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| {
+            error!("Failed to add font: {}", e);
+            shared::Error::Internal(format!("Failed to add font: {}", e))
+        })?;
+        let mono = doc.add_builtin_font(BuiltinFont::Courier).map_err(|e| {
+            error!("Failed to add font: {}", e);
+            shared::Error::Internal(format!("Failed to add font: {}", e))
+        })?;
+
+        let mut layer = doc.get_page(page1).get_layer(layer1);
+        layer.use_text(&title, 18.0, Mm(15.0), Mm(PAGE_HEIGHT - 20.0), &font);
+
+        let mut y_pos = PAGE_HEIGHT - 30.0;
+        for detail in [
+            format!("User: {}", self.user_id),
+            format!(
+                "Jurisdiction: {} | Period: {} to {} | Disposal method: {}",
+                self.jurisdiction.name(),
+                format_date(self.period_start),
+                format_date(self.period_end - chrono::Duration::days(1)),
+                self.disposal_method.as_str()
+            ),
+            format!(
+                "Values in {} at the time of each transaction. Generated {}",
+                self.currency,
+                self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+        ] {
+            layer.use_text(&detail, 10.0, Mm(15.0), Mm(y_pos), &font);
+            y_pos -= 6.0;
+        }
+        y_pos -= 4.0;
+
+        // Fixed-width columns, sized to the widest cell
+        let widths: Vec<usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(header.chars().count()))
+                    .max()
+                    .unwrap_or(0)
+                    .min(MAX_COLUMN_WIDTH)
+            })
+            .collect();
+        let line_chars: usize = widths.iter().map(|w| w + 2).sum();
+        // Courier is 0.6 em wide; fit the table to the printable width
+        let font_size = ((PAGE_WIDTH - 30.0) / (line_chars as f32 * 0.6 * 0.3528)).min(8.0);
+        let format_row = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let cell: String = cell.chars().take(*width).collect();
+                    format!("{:<width$}  ", cell, width = *width)
+                })
+                .collect::<String>()
+        };
+
+        let header_line = format_row(&headers);
+        let mut pages = 1;
+        layer.use_text(&header_line, font_size, Mm(15.0), Mm(y_pos), &mono);
+        y_pos -= LINE_HEIGHT;
+
+        for row in &rows {
+            if y_pos < 15.0 {
+                pages += 1;
+                let (page, page_layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), format!("Layer {}", pages));
+                layer = doc.get_page(page).get_layer(page_layer);
+                y_pos = PAGE_HEIGHT - 15.0;
+                layer.use_text(&header_line, font_size, Mm(15.0), Mm(y_pos), &mono);
+                y_pos -= LINE_HEIGHT;
+            }
+            layer.use_text(format_row(row), font_size, Mm(15.0), Mm(y_pos), &mono);
+            y_pos -= LINE_HEIGHT;
+        }
+        if rows.is_empty() {
+            layer.use_text("No entries for this tax year", 10.0, Mm(15.0), Mm(y_pos), &font);
+            y_pos -= LINE_HEIGHT;
+        }
+
+        y_pos -= LINE_HEIGHT;
+        for total in self.totals(kind) {
+            if y_pos < 15.0 {
+                pages += 1;
+                let (page, page_layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), format!("Layer {}", pages));
+                layer = doc.get_page(page).get_layer(page_layer);
+                y_pos = PAGE_HEIGHT - 15.0;
+            }
+            layer.use_text(&total, 10.0, Mm(15.0), Mm(y_pos), &font);
+            y_pos -= 6.0;
+        }
+
+        let pdf_bytes = doc.save_to_bytes().map_err(|e| {
+            error!("Failed to save PDF: {}", e);
+            shared::Error::Internal(format!("Failed to save PDF: {}", e))
+        })?;
+
+        info!(
+            "Exported {} report of user {} as PDF ({} bytes)",
+            kind.as_str(),
+            self.user_id,
+            pdf_bytes.len()
+        );
+        Ok(pdf_bytes)
+    }
+}
+
+fn format_date(at: DateTime<Utc>) -> String {
+    at.format("%m/%d/%Y").to_string()
+}
+
+fn format_money(value: Decimal) -> String {
+    format!("{:.2}", value.round_dp(2))
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes" } else { "No" }.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+    }
+
+    fn report() -> TaxReport {
+        let capital_gains = vec![
+            CapitalGainLine {
+                description: "1.5 SOL".to_string(),
+                asset: "SOL".to_string(),
+                amount: Decimal::new(15, 1),
+                acquired_at: Some(at(2022, 3, 1)),
+                disposed_at: at(2024, 2, 1),
+                proceeds: Decimal::new(15075, 2),
+                cost_basis: Decimal::from(60),
+                gain: Decimal::new(9075, 2),
+                holding_period: Some(HoldingPeriod::LongTerm),
+                source: TaxEventSource::Trade,
+                transaction_id: Uuid::from_u128(1),
+                missing_price: false,
+            },
+            CapitalGainLine {
+                description: "2 SOL".to_string(),
+                asset: "SOL".to_string(),
+                amount: Decimal::from(2),
+                acquired_at: None,
+                disposed_at: at(2024, 5, 1),
+                proceeds: Decimal::from(300),
+                cost_basis: Decimal::ZERO,
+                gain: Decimal::from(300),
+                holding_period: Some(HoldingPeriod::ShortTerm),
+                source: TaxEventSource::P2pExchange,
+                transaction_id: Uuid::from_u128(2),
+                missing_price: false,
+            },
+        ];
+        let income = vec![
+            IncomeLine {
+                category: IncomeCategory::StakingReward,
+                transaction_id: Uuid::from_u128(3),
+                date: at(2024, 6, 1),
+                asset: "SOL".to_string(),
+                amount: Decimal::new(5, 1),
+                value: None,
+            },
+            IncomeLine {
+                category: IncomeCategory::P2pFee,
+                transaction_id: Uuid::from_u128(2),
+                date: at(2024, 5, 1),
+                asset: "SOL".to_string(),
+                amount: Decimal::from(2),
+                value: Some(Decimal::new(150, 2)),
+            },
+        ];
+        let holdings = vec![HoldingLine {
+            asset: "SOL".to_string(),
+            amount: Decimal::from(3),
+            lot_count: 2,
+            cost_basis: Decimal::from(150),
+            price: Some(Decimal::from(100)),
+            market_value: Some(Decimal::from(300)),
+            unrealized_gain: Some(Decimal::from(150)),
+        }];
+        let summary = TaxReportService::summarize(&capital_gains, &income, &holdings);
+
+        TaxReport {
+            user_id: Uuid::nil(),
+            tax_year: 2024,
+            jurisdiction: TaxJurisdiction::Us,
+            period_start: at(2024, 1, 1),
+            period_end: at(2025, 1, 1),
+            disposal_method: crate::tax_lots::DisposalMethod::Fifo,
+            currency: "USD".to_string(),
+            capital_gains,
+            income,
+            holdings,
+            summary,
+            generated_at: at(2025, 1, 15),
+        }
+    }
+
+    #[test]
+    fn test_tax_year_boundaries() {
+        let (start, end) = TaxJurisdiction::Us.tax_year(2024).unwrap();
+        assert_eq!((start.date_naive(), end.date_naive()), (
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        ));
+
+        let (start, _) = TaxJurisdiction::Uk.tax_year(2024).unwrap();
+        assert_eq!(start.date_naive(), NaiveDate::from_ymd_opt(2024, 4, 6).unwrap());
+
+        // Australian financial years are named by the year they end in
+        let (start, end) = TaxJurisdiction::Au.tax_year(2024).unwrap();
+        assert_eq!(start.date_naive(), NaiveDate::from_ymd_opt(2023, 7, 1).unwrap());
+        assert_eq!(end.date_naive(), NaiveDate::from_ymd_opt(2024, 7, 1).unwrap());
+    }
+
+    #[test]
+    fn test_holding_period_by_jurisdiction() {
+        let bought = Some(at(2023, 1, 1));
+        assert_eq!(
+            TaxJurisdiction::Us.holding_period(bought, at(2024, 6, 1)),
+            Some(HoldingPeriod::LongTerm)
+        );
+        assert_eq!(TaxJurisdiction::Uk.holding_period(bought, at(2024, 6, 1)), None);
+        // Unknown acquisition dates are never long-term
+        assert_eq!(
+            TaxJurisdiction::De.holding_period(None, at(2024, 6, 1)),
+            Some(HoldingPeriod::ShortTerm)
+        );
+    }
+
+    #[test]
+    fn test_jurisdiction_profiles() {
+        assert_eq!("gb".parse::<TaxJurisdiction>().unwrap(), TaxJurisdiction::Uk);
+        assert!("FR".parse::<TaxJurisdiction>().is_err());
+        assert_eq!(TaxJurisdiction::Us.currency(), "USD");
+        assert_eq!(TaxJurisdiction::Uk.currency(), "GBP");
+        assert_eq!(TaxJurisdiction::De.currency(), "EUR");
+    }
+
+    #[test]
+    fn test_exchange_rate_at_transaction_time() {
+        let rates = ExchangeRates {
+            currency: "GBP",
+            history: vec![(at(2024, 1, 1), Decimal::new(125, 2)), (at(2024, 7, 1), Decimal::from(2))],
+        };
+
+        assert_eq!(rates.convert(Decimal::from(250), at(2024, 3, 1)).unwrap(), Decimal::from(200));
+        assert_eq!(rates.convert(Decimal::from(250), at(2024, 7, 1)).unwrap(), Decimal::from(125));
+        // No rate recorded yet
+        assert!(rates.convert(Decimal::from(250), at(2023, 12, 31)).is_err());
+
+        let usd = ExchangeRates { currency: "USD", history: Vec::new() };
+        assert_eq!(usd.convert(Decimal::from(250), at(2020, 1, 1)).unwrap(), Decimal::from(250));
+    }
+
+    #[test]
+    fn test_summary_splits_terms_and_counts_missing_prices() {
+        let summary = report().summary;
+        assert_eq!(summary.long_term_gain, Decimal::new(9075, 2));
+        assert_eq!(summary.short_term_gain, Decimal::from(300));
+        assert_eq!(summary.total_gain, Decimal::new(39075, 2));
+        assert_eq!(summary.year_end_value, Decimal::from(300));
+        // Fees are listed but not income
+        assert_eq!(summary.staking_income, Decimal::ZERO);
+        assert_eq!(summary.p2p_fees, Decimal::new(150, 2));
+        assert_eq!(summary.lines_missing_price, 1);
+    }
+
+    #[test]
+    fn test_capital_gains_csv_has_8949_columns() {
+        let csv = String::from_utf8(report().to_csv(TaxReportKind::CapitalGains).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert!(lines[0].starts_with("Description,Date Acquired,Date Sold,Proceeds (USD),Cost Basis (USD),Gain/Loss (USD),Term"));
+        assert!(lines[1].starts_with("1.5 SOL,03/01/2022,02/01/2024,150.75,60.00,90.75,LONG_TERM,TRADE"));
+        assert!(lines[2].starts_with("2 SOL,VARIOUS,05/01/2024,300.00,0.00,300.00,SHORT_TERM,P2P_EXCHANGE"));
+    }
+
+    #[test]
+    fn test_every_report_exports_as_pdf() {
+        for kind in [TaxReportKind::CapitalGains, TaxReportKind::Income, TaxReportKind::Holdings] {
+            let pdf = report().to_pdf(kind).unwrap();
+            assert!(pdf.starts_with(b"%PDF"), "{}", kind.as_str());
+        }
+    }
+}
@@ -13,6 +13,7 @@ use trading::TradingService;
 use uuid::Uuid;
 
 use crate::position_management_service::{PositionManagementService, PositionMode};
+use crate::tax_lots::TaxLotService;
 use crate::trim_config_service::TrimConfigService;
 
 /// Service for executing trim recommendations
@@ -28,6 +29,7 @@ pub struct TrimExecutor {
     trading_service: Arc<TradingService>,
     #[allow(dead_code)]
     notification_service: Arc<NotificationService>,
+    tax_lots: Option<Arc<TaxLotService>>,
 }
 
 /// Represents a pending trim recommendation
@@ -76,9 +78,17 @@ impl TrimExecutor {
             position_management_service,
             trading_service,
             notification_service,
+            tax_lots: None,
         }
     }
 
+    /// Report realized profit against the lots the sale consumes under the
+    /// user's disposal method
+    pub fn with_tax_lots(mut self, tax_lots: Arc<TaxLotService>) -> Self {
+        self.tax_lots = Some(tax_lots);
+        self
+    }
+
     /// Start the background worker that processes pending trims every 1 minute
     pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
@@ -254,15 +264,10 @@ impl TrimExecutor {
         // Get current price for profit calculation
         let current_price = self.get_current_price(&trim.token_mint).await?;
 
-        // Get entry price for profit calculation
-        let entry_price = self.get_entry_price(trim.user_id, &trim.token_mint).await?;
-
-        // Calculate profit realized
-        let profit_realized = if let Some(entry) = entry_price {
-            (current_price - entry) * trim_amount
-        } else {
-            Decimal::ZERO
-        };
+        // Calculate profit realized, before the sale changes the user's lots
+        let profit_realized = self
+            .calculate_profit_realized(trim, trim_amount, current_price)
+            .await?;
 
         // Create a recommendation for the trading service
         let recommendation = Recommendation {
@@ -315,6 +320,38 @@ impl TrimExecutor {
         Ok(trim_execution)
     }
 
+    /// Profit of selling `trim_amount` at `current_price`
+    ///
+    /// Uses the user's tax lots when available, otherwise the price of the
+    /// first buy.
+    async fn calculate_profit_realized(
+        &self,
+        trim: &PendingTrim,
+        trim_amount: Decimal,
+        current_price: Decimal,
+    ) -> Result<Decimal> {
+        if let Some(tax_lots) = &self.tax_lots {
+            let method = tax_lots.get_disposal_method(trim.user_id).await?;
+            let ledger = tax_lots.build_ledger(trim.user_id).await?;
+            if ledger.average_cost(&trim.token_symbol).is_some() {
+                let disposals = ledger.preview_disposal(
+                    &trim.token_symbol,
+                    trim_amount,
+                    current_price * trim_amount,
+                    Utc::now(),
+                    method,
+                );
+                return Ok(disposals.iter().map(|d| d.gain_usd).sum());
+            }
+        }
+
+        let entry_price = self.get_entry_price(trim.user_id, &trim.token_mint).await?;
+        Ok(match entry_price {
+            Some(entry) => (current_price - entry) * trim_amount,
+            None => Decimal::ZERO,
+        })
+    }
+
     /// Get user settings
     async fn get_user_settings(&self, user_id: Uuid) -> Result<UserSettings> {
         let client = self
@@ -1,10 +1,17 @@
-use chrono::{DateTime, Utc};
+use crate::wallet_signature;
+use blockchain::{ChainKind, ChainRegistry};
+use chrono::{DateTime, Duration, Utc};
 use database::DbPool;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use shared::{Error, Result};
-use tracing::info;
+use std::sync::Arc;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// How long a wallet ownership challenge can be signed
+const WALLET_CHALLENGE_TTL_MINUTES: i64 = 10;
+
 /// Verification level
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VerificationLevel {
@@ -42,86 +49,358 @@ pub struct WalletVerification {
     pub verified_at: DateTime<Utc>,
 }
 
+/// Server-issued message a wallet must sign to prove ownership
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletChallenge {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub blockchain: String,
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Reasons a wallet ownership proof is rejected
+#[derive(Debug, thiserror::Error)]
+pub enum WalletVerificationError {
+    #[error("Invalid wallet address: {0}")]
+    InvalidWalletAddress(String),
+    #[error("Unsupported blockchain: {0}")]
+    UnsupportedBlockchain(String),
+    #[error("Challenge not found")]
+    ChallengeNotFound,
+    #[error("Challenge was issued for a different wallet")]
+    ChallengeMismatch,
+    #[error("Challenge expired")]
+    ChallengeExpired,
+    #[error("Challenge has already been used")]
+    ChallengeReplayed,
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+/// Signature scheme used by a blockchain's wallets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignatureScheme {
+    /// Raw Ed25519 signature over the message (Solana)
+    Ed25519,
+    /// EIP-191 `personal_sign` (every EVM chain)
+    Eip191,
+}
+
+impl SignatureScheme {
+    /// Scheme of a registered chain, with the chain's registry id
+    fn for_blockchain(
+        chains: &ChainRegistry,
+        blockchain: &str,
+    ) -> std::result::Result<(Self, String), WalletVerificationError> {
+        let chain = chains
+            .resolve(blockchain)
+            .ok()
+            .and_then(|chain| chains.get(&chain))
+            .ok_or_else(|| WalletVerificationError::UnsupportedBlockchain(blockchain.to_string()))?;
+
+        let scheme = match chain.kind {
+            ChainKind::Solana => SignatureScheme::Ed25519,
+            ChainKind::Evm => SignatureScheme::Eip191,
+        };
+        Ok((scheme, chain.id.clone()))
+    }
+
+    /// Validate an address and return the canonical form stored in the database
+    fn normalize_address(&self, address: &str) -> std::result::Result<String, WalletVerificationError> {
+        match self {
+            SignatureScheme::Ed25519 => address
+                .parse::<solana_sdk::pubkey::Pubkey>()
+                .map(|pubkey| pubkey.to_string())
+                .map_err(|_| WalletVerificationError::InvalidWalletAddress(address.to_string())),
+            SignatureScheme::Eip191 => wallet_signature::normalize_evm_address(address)
+                .map_err(|_| WalletVerificationError::InvalidWalletAddress(address.to_string())),
+        }
+    }
+
+    fn verify(
+        &self,
+        address: &str,
+        message: &str,
+        signature: &str,
+    ) -> std::result::Result<(), WalletVerificationError> {
+        let valid = match self {
+            SignatureScheme::Ed25519 => wallet_signature::verify_solana_signature(address, message, signature),
+            SignatureScheme::Eip191 => wallet_signature::verify_eip191_signature(address, message, signature),
+        }
+        .map_err(|e| WalletVerificationError::InvalidSignature(e.to_string()))?;
+
+        if valid {
+            Ok(())
+        } else {
+            Err(WalletVerificationError::InvalidSignature(
+                "Signature was not produced by this wallet".to_string(),
+            ))
+        }
+    }
+}
+
+/// Build the human-readable message a wallet is asked to sign
+fn build_challenge_message(
+    user_id: Uuid,
+    wallet_address: &str,
+    blockchain: &str,
+    nonce: &str,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> String {
+    format!(
+        "Sign this message to prove you own this wallet.\n\n\
+         Wallet: {}\n\
+         Blockchain: {}\n\
+         User: {}\n\
+         Nonce: {}\n\
+         Issued at: {}\n\
+         Expires at: {}",
+        wallet_address,
+        blockchain,
+        user_id,
+        nonce,
+        issued_at.to_rfc3339(),
+        expires_at.to_rfc3339()
+    )
+}
+
 /// Verification service
 /// 
-/// NOTE: This is a minimal stub implementation. Full implementation would include:
+/// Wallet ownership is proven by signing a server-issued, single-use challenge.
+/// 
+/// NOTE: Identity verification is still a stub. Full implementation would include:
 /// - KYC provider integration (e.g., Onfido)
 /// - Document upload to secure storage
-/// - Signature verification for wallet ownership
 /// - Verification-based feature access control middleware
 pub struct VerificationService {
     db: DbPool,
+    chains: Arc<ChainRegistry>,
 }
 
 impl VerificationService {
     pub fn new(db: DbPool) -> Self {
         info!("Initializing verification service (stub implementation)");
-        Self { db }
+        Self {
+            db,
+            chains: Arc::new(ChainRegistry::default()),
+        }
+    }
+
+    /// Accept wallets on the chains in `chains` instead of the built-in ones
+    pub fn with_chain_registry(mut self, chains: Arc<ChainRegistry>) -> Self {
+        self.chains = chains;
+        self
     }
 
-    /// Verify wallet ownership via signature
+    /// Issue a challenge for a wallet ownership proof
     /// 
-    /// NOTE: Full implementation would verify the signature cryptographically
+    /// The returned message must be signed by the wallet and submitted to
+    /// `verify_wallet` before `expires_at`. Each challenge can be used once.
+    pub async fn create_wallet_challenge(
+        &self,
+        user_id: Uuid,
+        wallet_address: String,
+        blockchain: String,
+    ) -> std::result::Result<WalletChallenge, WalletVerificationError> {
+        let (scheme, blockchain) = SignatureScheme::for_blockchain(&self.chains, &blockchain)?;
+        let wallet_address = scheme.normalize_address(&wallet_address)?;
+
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let nonce = hex::encode(nonce);
+
+        let id = Uuid::new_v4();
+        let issued_at = Utc::now();
+        let expires_at = issued_at + Duration::minutes(WALLET_CHALLENGE_TTL_MINUTES);
+        let message = build_challenge_message(
+            user_id,
+            &wallet_address,
+            &blockchain,
+            &nonce,
+            issued_at,
+            expires_at,
+        );
+
+        let client = self.db.get().await.map_err(|e| {
+            WalletVerificationError::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO wallet_verification_challenges (
+                    id, user_id, wallet_address, blockchain, message, created_at, expires_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                &[
+                    &id,
+                    &user_id,
+                    &wallet_address,
+                    &blockchain,
+                    &message,
+                    &issued_at,
+                    &expires_at,
+                ],
+            )
+            .await
+            .map_err(|e| WalletVerificationError::Database(format!("Failed to store challenge: {}", e)))?;
+
+        info!(
+            "Issued wallet challenge {} for {} on {} (user {})",
+            id, wallet_address, blockchain, user_id
+        );
+
+        Ok(WalletChallenge {
+            id,
+            wallet_address,
+            blockchain,
+            message,
+            expires_at,
+        })
+    }
+
+    /// Verify wallet ownership via a signed challenge
+    /// 
+    /// Solana signatures are checked with Ed25519; signatures on EVM chains
+    /// are checked by EIP-191 signer recovery. The challenge is
+    /// consumed in the same transaction that records the verification.
     pub async fn verify_wallet(
         &self,
         user_id: Uuid,
         wallet_address: String,
         blockchain: String,
-        challenge_message: String,
+        challenge_id: Uuid,
         signature: String,
-    ) -> Result<WalletVerification> {
+    ) -> std::result::Result<WalletVerification, WalletVerificationError> {
         info!(
             "Verifying wallet {} on {} for user {}",
             wallet_address, blockchain, user_id
         );
 
-        let id = Uuid::new_v4();
+        let (scheme, blockchain) = SignatureScheme::for_blockchain(&self.chains, &blockchain)?;
+        let wallet_address = scheme.normalize_address(&wallet_address)?;
 
-        let client = self.db.get().await.map_err(|e| {
-            Error::Database(format!("Failed to get database connection: {}", e))
+        let mut client = self.db.get().await.map_err(|e| {
+            WalletVerificationError::Database(format!("Failed to get database connection: {}", e))
         })?;
 
-        // NOTE: Full implementation would verify signature here
-        // For now, we just store the verification
+        let transaction = client.transaction().await.map_err(|e| {
+            WalletVerificationError::Database(format!("Failed to start transaction: {}", e))
+        })?;
 
-        let row = client
+        // Lock the challenge so concurrent submissions cannot both consume it
+        let challenge = transaction
+            .query_opt(
+                r#"
+                SELECT wallet_address, blockchain, message, expires_at, consumed_at
+                FROM wallet_verification_challenges
+                WHERE id = $1 AND user_id = $2
+                FOR UPDATE
+                "#,
+                &[&challenge_id, &user_id],
+            )
+            .await
+            .map_err(|e| WalletVerificationError::Database(format!("Failed to fetch challenge: {}", e)))?
+            .ok_or(WalletVerificationError::ChallengeNotFound)?;
+
+        let challenge_wallet: String = challenge.get("wallet_address");
+        let challenge_blockchain: String = challenge.get("blockchain");
+        let challenge_message: String = challenge.get("message");
+        let expires_at: DateTime<Utc> = challenge.get("expires_at");
+        let consumed_at: Option<DateTime<Utc>> = challenge.get("consumed_at");
+
+        if challenge_wallet != wallet_address || challenge_blockchain != blockchain {
+            return Err(WalletVerificationError::ChallengeMismatch);
+        }
+        if consumed_at.is_some() {
+            warn!("Replay of wallet challenge {} for user {}", challenge_id, user_id);
+            return Err(WalletVerificationError::ChallengeReplayed);
+        }
+        if Utc::now() > expires_at {
+            return Err(WalletVerificationError::ChallengeExpired);
+        }
+
+        scheme.verify(&wallet_address, &challenge_message, &signature)?;
+
+        transaction
+            .execute(
+                "UPDATE wallet_verification_challenges SET consumed_at = NOW() WHERE id = $1",
+                &[&challenge_id],
+            )
+            .await
+            .map_err(|e| WalletVerificationError::Database(format!("Failed to consume challenge: {}", e)))?;
+
+        let row = transaction
             .query_one(
                 r#"
                 INSERT INTO wallet_verifications (
                     id, user_id, wallet_address, blockchain,
-                    challenge_message, signature, verified, verified_at
+                    challenge_message, signature, challenge_id, verified, verified_at
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, TRUE, NOW())
+                VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE, NOW())
                 ON CONFLICT (user_id, wallet_address, blockchain)
-                DO UPDATE SET verified = TRUE, verified_at = NOW()
+                DO UPDATE SET
+                    challenge_message = EXCLUDED.challenge_message,
+                    signature = EXCLUDED.signature,
+                    challenge_id = EXCLUDED.challenge_id,
+                    verified = TRUE,
+                    verified_at = NOW()
                 RETURNING id, user_id, wallet_address, blockchain, verified, verified_at
                 "#,
                 &[
-                    &id,
+                    &Uuid::new_v4(),
                     &user_id,
                     &wallet_address,
                     &blockchain,
                     &challenge_message,
                     &signature,
+                    &challenge_id,
                 ],
             )
             .await
-            .map_err(|e| Error::Database(format!("Failed to verify wallet: {}", e)))?;
+            .map_err(|e| WalletVerificationError::Database(format!("Failed to verify wallet: {}", e)))?;
+
+        transaction.commit().await.map_err(|e| {
+            WalletVerificationError::Database(format!("Failed to commit verification: {}", e))
+        })?;
 
-        let verified_at_systime: std::time::SystemTime = row
-            .try_get("verified_at")
-            .map_err(|e| Error::Database(format!("Failed to get verified_at: {}", e)))?;
+        let column_error = |e: tokio_postgres::Error| WalletVerificationError::Database(e.to_string());
+        let verified_at_systime: std::time::SystemTime = row.try_get("verified_at").map_err(column_error)?;
+
+        info!("Verified wallet {} on {} for user {}", wallet_address, blockchain, user_id);
 
         Ok(WalletVerification {
-            id: row.try_get("id").map_err(|e| Error::Database(format!("Failed to get id: {}", e)))?,
-            user_id: row.try_get("user_id").map_err(|e| Error::Database(format!("Failed to get user_id: {}", e)))?,
-            wallet_address: row.try_get("wallet_address").map_err(|e| Error::Database(format!("Failed to get wallet_address: {}", e)))?,
-            blockchain: row.try_get("blockchain").map_err(|e| Error::Database(format!("Failed to get blockchain: {}", e)))?,
-            verified: row.try_get("verified").map_err(|e| Error::Database(format!("Failed to get verified: {}", e)))?,
+            id: row.try_get("id").map_err(column_error)?,
+            user_id: row.try_get("user_id").map_err(column_error)?,
+            wallet_address: row.try_get("wallet_address").map_err(column_error)?,
+            blockchain: row.try_get("blockchain").map_err(column_error)?,
+            verified: row.try_get("verified").map_err(column_error)?,
             verified_at: DateTime::<Utc>::from(verified_at_systime),
         })
     }
 
+    /// Delete challenges that expired without being used
+    /// 
+    /// Consumed challenges are kept because verifications reference them.
+    pub async fn cleanup_expired_challenges(&self) -> Result<u64> {
+        let client = self.db.get().await.map_err(|e| {
+            Error::Database(format!("Failed to get database connection: {}", e))
+        })?;
+
+        client
+            .execute(
+                "DELETE FROM wallet_verification_challenges WHERE consumed_at IS NULL AND expires_at < NOW()",
+                &[],
+            )
+            .await
+            .map_err(|e| Error::Database(format!("Failed to clean up challenges: {}", e)))
+    }
+
     /// Check if user has verified wallet
     pub async fn is_wallet_verified(
         &self,
@@ -129,6 +408,17 @@ impl VerificationService {
         wallet_address: &str,
         blockchain: &str,
     ) -> Result<bool> {
+        // Stored addresses and chains are canonical, so normalize the lookup the same way
+        let (wallet_address, blockchain) = match SignatureScheme::for_blockchain(&self.chains, blockchain) {
+            Ok((scheme, blockchain)) => (
+                scheme
+                    .normalize_address(wallet_address)
+                    .unwrap_or_else(|_| wallet_address.to_string()),
+                blockchain,
+            ),
+            Err(_) => (wallet_address.to_string(), blockchain.to_string()),
+        };
+
         let client = self.db.get().await.map_err(|e| {
             Error::Database(format!("Failed to get database connection: {}", e))
         })?;
@@ -206,28 +496,31 @@ impl VerificationService {
         Ok(Uuid::new_v4())
     }
 
-    /// Verify wallet ownership
-    /// 
-    /// NOTE: Stub implementation wrapping verify_wallet
+    /// Issue a wallet ownership challenge for a typed blockchain
+    pub async fn create_wallet_ownership_challenge(
+        &self,
+        user_id: Uuid,
+        wallet_address: String,
+        blockchain: blockchain::Blockchain,
+    ) -> std::result::Result<WalletChallenge, WalletVerificationError> {
+        self.create_wallet_challenge(user_id, wallet_address, blockchain.id().to_string())
+            .await
+    }
+
+    /// Verify wallet ownership for a typed blockchain
     pub async fn verify_wallet_ownership(
         &self,
         user_id: Uuid,
         wallet_address: String,
         blockchain: blockchain::Blockchain,
+        challenge_id: Uuid,
         signature: String,
-    ) -> Result<WalletVerification> {
-        let blockchain_str = match blockchain {
-            blockchain::Blockchain::Solana => "Solana",
-            blockchain::Blockchain::Ethereum => "Ethereum",
-            blockchain::Blockchain::BinanceSmartChain => "BinanceSmartChain",
-            blockchain::Blockchain::Polygon => "Polygon",
-        };
-
+    ) -> std::result::Result<WalletVerification, WalletVerificationError> {
         self.verify_wallet(
             user_id,
             wallet_address,
-            blockchain_str.to_string(),
-            "challenge".to_string(),
+            blockchain.id().to_string(),
+            challenge_id,
             signature,
         )
         .await
@@ -287,6 +580,72 @@ mod tests {
         assert_eq!(VerificationStatus::Rejected.as_str(), "REJECTED");
     }
 
+    #[test]
+    fn test_signature_scheme_for_blockchain() {
+        let chains = ChainRegistry::default();
+        assert_eq!(
+            SignatureScheme::for_blockchain(&chains, "Solana").unwrap(),
+            (SignatureScheme::Ed25519, "Solana".to_string())
+        );
+        for chain in ["Ethereum", "BinanceSmartChain", "Polygon"] {
+            assert_eq!(
+                SignatureScheme::for_blockchain(&chains, chain).unwrap(),
+                (SignatureScheme::Eip191, chain.to_string())
+            );
+        }
+        // Aliases resolve to the id challenges are stored under
+        assert_eq!(
+            SignatureScheme::for_blockchain(&chains, "bsc").unwrap().1,
+            "BinanceSmartChain"
+        );
+        assert!(matches!(
+            SignatureScheme::for_blockchain(&chains, "Dogecoin"),
+            Err(WalletVerificationError::UnsupportedBlockchain(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(
+            SignatureScheme::Eip191
+                .normalize_address("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23")
+                .unwrap(),
+            "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        assert!(matches!(
+            SignatureScheme::Ed25519.normalize_address("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"),
+            Err(WalletVerificationError::InvalidWalletAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_challenge_message_signed_by_wallet() {
+        use solana_sdk::signature::{Keypair, Signer};
+
+        let keypair = Keypair::new();
+        let address = keypair.pubkey().to_string();
+        let now = Utc::now();
+        let message = build_challenge_message(
+            Uuid::new_v4(),
+            &address,
+            "Solana",
+            "abc123",
+            now,
+            now + Duration::minutes(WALLET_CHALLENGE_TTL_MINUTES),
+        );
+        assert!(message.contains(&address));
+        assert!(message.contains("Nonce: abc123"));
+
+        let signature = keypair.sign_message(message.as_bytes()).to_string();
+        assert!(SignatureScheme::Ed25519.verify(&address, &message, &signature).is_ok());
+
+        let forged = Keypair::new().sign_message(message.as_bytes()).to_string();
+        assert!(matches!(
+            SignatureScheme::Ed25519.verify(&address, &message, &forged),
+            Err(WalletVerificationError::InvalidSignature(_))
+        ));
+    }
+
     #[test]
     fn test_verification_level_values() {
         assert_eq!(VerificationLevel::None as i32, 0);
@@ -74,6 +74,12 @@ impl WalletService {
         }
     }
 
+    /// Value assets with `price_feed` instead of the default empty feed
+    pub fn with_price_feed(mut self, price_feed: PriceFeedService) -> Self {
+        self.price_feed = price_feed;
+        self
+    }
+
     /// Connect a user's Solana wallet and retrieve portfolio
     /// 
     /// This validates the wallet address, retrieves the portfolio from Tantum API (if available)
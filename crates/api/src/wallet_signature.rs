@@ -0,0 +1,170 @@
+// Wallet signature verification for ownership proofs
+//
+// Solana wallets sign the raw message bytes with Ed25519. EVM wallets sign via
+// `personal_sign` (EIP-191), so the signer address is recovered from the
+// prefixed Keccak-256 digest and compared with the claimed address.
+
+use shared::{Error, Result};
+use solana_sdk::keccak;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::secp256k1_recover::secp256k1_recover;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+
+/// Verify a base58 Ed25519 signature over `message` by a Solana wallet
+///
+/// Returns `Ok(false)` when the signature is well-formed but was not made by
+/// `wallet_address`.
+pub fn verify_solana_signature(wallet_address: &str, message: &str, signature: &str) -> Result<bool> {
+    let pubkey = Pubkey::from_str(wallet_address)
+        .map_err(|_| Error::InvalidWalletAddress(wallet_address.to_string()))?;
+    let signature = Signature::from_str(signature)
+        .map_err(|e| Error::Validation(format!("Malformed Ed25519 signature: {}", e)))?;
+
+    Ok(signature.verify(pubkey.as_ref(), message.as_bytes()))
+}
+
+/// Verify an EIP-191 `personal_sign` signature over `message` by an EVM wallet
+///
+/// The signature is the 65-byte `r || s || v` hex string returned by wallets;
+/// `v` may be 0/1 or 27/28. Returns `Ok(false)` when the recovered signer is
+/// not `wallet_address`.
+pub fn verify_eip191_signature(wallet_address: &str, message: &str, signature: &str) -> Result<bool> {
+    let expected = normalize_evm_address(wallet_address)?;
+    let recovered = recover_eip191_address(message, signature)?;
+
+    Ok(recovered == expected)
+}
+
+/// Recover the lowercase `0x` address that produced an EIP-191 signature
+pub fn recover_eip191_address(message: &str, signature: &str) -> Result<String> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|e| Error::Validation(format!("Malformed EIP-191 signature: {}", e)))?;
+    if bytes.len() != 65 {
+        return Err(Error::Validation(format!(
+            "EIP-191 signature must be 65 bytes, got {}",
+            bytes.len()
+        )));
+    }
+
+    let recovery_id = match bytes[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        v => {
+            return Err(Error::Validation(format!(
+                "Invalid EIP-191 recovery id: {}",
+                v
+            )))
+        }
+    };
+
+    let digest = eip191_digest(message);
+    let public_key = secp256k1_recover(&digest, recovery_id, &bytes[..64])
+        .map_err(|e| Error::Validation(format!("Failed to recover signer: {}", e)))?;
+
+    let address_hash = keccak::hash(&public_key.to_bytes());
+    Ok(format!("0x{}", hex::encode(&address_hash.to_bytes()[12..])))
+}
+
+/// Validate an EVM address and return it in lowercase `0x` form
+pub fn normalize_evm_address(address: &str) -> Result<String> {
+    let hex_part = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .ok_or_else(|| Error::InvalidWalletAddress(address.to_string()))?;
+
+    if hex_part.len() != 40 || !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::InvalidWalletAddress(address.to_string()));
+    }
+
+    Ok(format!("0x{}", hex_part.to_lowercase()))
+}
+
+/// Keccak-256 of `"\x19Ethereum Signed Message:\n" || len(message) || message`
+fn eip191_digest(message: &str) -> [u8; 32] {
+    let prefixed = format!("\x19Ethereum Signed Message:\n{}{}", message.len(), message);
+    keccak::hash(prefixed.as_bytes()).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    // Example account and signature from the web3.js `accounts.sign` documentation
+    const WEB3_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const WEB3_MESSAGE: &str = "Some data";
+    const WEB3_SIGNATURE: &str = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+
+    #[test]
+    fn test_solana_signature_round_trip() {
+        let keypair = Keypair::new();
+        let message = "Verify wallet ownership";
+        let signature = keypair.sign_message(message.as_bytes()).to_string();
+        let address = keypair.pubkey().to_string();
+
+        assert!(verify_solana_signature(&address, message, &signature).unwrap());
+        assert!(!verify_solana_signature(&address, "different message", &signature).unwrap());
+
+        let other = Keypair::new().pubkey().to_string();
+        assert!(!verify_solana_signature(&other, message, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_solana_signature_rejects_malformed_input() {
+        let address = Keypair::new().pubkey().to_string();
+
+        assert!(matches!(
+            verify_solana_signature("not-an-address", "msg", "sig"),
+            Err(Error::InvalidWalletAddress(_))
+        ));
+        assert!(matches!(
+            verify_solana_signature(&address, "msg", "not-a-signature"),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_eip191_recovers_known_signer() {
+        let recovered = recover_eip191_address(WEB3_MESSAGE, WEB3_SIGNATURE).unwrap();
+        assert_eq!(recovered, WEB3_ADDRESS.to_lowercase());
+
+        assert!(verify_eip191_signature(WEB3_ADDRESS, WEB3_MESSAGE, WEB3_SIGNATURE).unwrap());
+        assert!(!verify_eip191_signature(WEB3_ADDRESS, "Other data", WEB3_SIGNATURE).unwrap());
+    }
+
+    #[test]
+    fn test_eip191_accepts_zero_based_recovery_id() {
+        let mut bytes = hex::decode(WEB3_SIGNATURE.trim_start_matches("0x")).unwrap();
+        bytes[64] -= 27;
+        let signature = hex::encode(bytes);
+
+        assert!(verify_eip191_signature(WEB3_ADDRESS, WEB3_MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_eip191_rejects_malformed_signature() {
+        assert!(matches!(
+            verify_eip191_signature(WEB3_ADDRESS, WEB3_MESSAGE, "0x1234"),
+            Err(Error::Validation(_))
+        ));
+
+        let mut bytes = hex::decode(WEB3_SIGNATURE.trim_start_matches("0x")).unwrap();
+        bytes[64] = 5;
+        assert!(matches!(
+            verify_eip191_signature(WEB3_ADDRESS, WEB3_MESSAGE, &hex::encode(bytes)),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_evm_address() {
+        assert_eq!(
+            normalize_evm_address(WEB3_ADDRESS).unwrap(),
+            "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        assert!(normalize_evm_address("2c7536E3605D9C16a7a3D7b1898e529396a65c23").is_err());
+        assert!(normalize_evm_address("0x1234").is_err());
+        assert!(normalize_evm_address("0xZZ7536E3605D9C16a7a3D7b1898e529396a65c23").is_err());
+    }
+}
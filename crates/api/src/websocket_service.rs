@@ -1,22 +1,37 @@
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ws::{close_code, CloseFrame, Message, WebSocket},
+        Query, State, WebSocketUpgrade,
     },
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
     response::Response,
 };
-use futures::{sink::SinkExt, stream::StreamExt};
+use async_trait::async_trait;
+use futures::{
+    sink::SinkExt,
+    stream::{SplitSink, StreamExt},
+};
+use notification::{ChannelKind, NotificationChannel};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::broadcast;
-use tracing::{error, info, warn};
+use shared::models::Notification;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::mesh_types::{ConsensusPriceData, DataFreshness, NetworkStatus};
 use crate::AppState;
 
 /// Maximum number of messages to buffer in the broadcast channel
 const CHANNEL_CAPACITY: usize = 100;
 
+/// Maximum number of private messages to buffer per connected user
+const USER_CHANNEL_CAPACITY: usize = 32;
+
 /// WebSocket update types that can be pushed to clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -56,6 +71,15 @@ pub enum DashboardUpdate {
         action: String,
         timestamp: i64,
     },
+    /// Stop-loss, take-profit or trailing stop order changed status
+    ConditionalOrderUpdated {
+        order_id: String,
+        asset: String,
+        order_type: String,
+        status: String,
+        price: Option<String>,
+        timestamp: i64,
+    },
     /// Portfolio value update
     PortfolioUpdate {
         total_value_usd: String,
@@ -116,27 +140,204 @@ pub enum DashboardUpdate {
         retry_count: u32,
         timestamp: i64,
     },
+    /// In-app push of a user's notification
+    Notification {
+        notification_id: String,
+        user_id: String,
+        notification_type: String,
+        title: String,
+        message: String,
+        priority: String,
+        timestamp: i64,
+    },
+    /// Mesh network health
+    MeshStatus {
+        active_providers: usize,
+        connected_peers: usize,
+        total_network_size: usize,
+        data_freshness: String,
+        extended_offline: bool,
+        offline_duration_minutes: Option<i64>,
+        timestamp: i64,
+    },
+    /// Topics the client is subscribed to, sent after every change
+    Subscriptions {
+        topics: Vec<String>,
+    },
+    /// A client message could not be handled
+    Error {
+        message: String,
+    },
+}
+
+/// Public stream a dashboard client can subscribe to
+///
+/// Private events (trades, payments, notifications) are not topics: they are
+/// always delivered, and only to the socket's own user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Topic {
+    /// Price updates for every asset (`prices`)
+    Prices,
+    /// Price updates for one asset (`prices:SOL`)
+    AssetPrices(String),
+    /// Mesh network status (`mesh_status`)
+    MeshStatus,
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topic::Prices => f.write_str("prices"),
+            Topic::AssetPrices(asset) => write!(f, "prices:{}", asset),
+            Topic::MeshStatus => f.write_str("mesh_status"),
+        }
+    }
+}
+
+impl FromStr for Topic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prices" => Ok(Topic::Prices),
+            "mesh_status" => Ok(Topic::MeshStatus),
+            _ => match s.strip_prefix("prices:") {
+                Some(asset) if !asset.is_empty() => Ok(Topic::AssetPrices(asset.to_uppercase())),
+                _ => Err(format!("Unknown topic: {}", s)),
+            },
+        }
+    }
+}
+
+/// Topics one dashboard connection is subscribed to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Subscriptions {
+    topics: HashSet<Topic>,
+}
+
+impl Subscriptions {
+    /// What a new connection receives until it asks otherwise
+    pub fn initial() -> Self {
+        Self {
+            topics: HashSet::from([Topic::Prices]),
+        }
+    }
+
+    pub fn subscribe(&mut self, topics: impl IntoIterator<Item = Topic>) {
+        self.topics.extend(topics);
+    }
+
+    pub fn unsubscribe(&mut self, topics: &[Topic]) {
+        self.topics.retain(|topic| !topics.contains(topic));
+    }
+
+    pub fn contains(&self, topic: &Topic) -> bool {
+        self.topics.contains(topic)
+    }
+
+    /// Whether price updates for `asset` should be sent
+    pub fn wants_asset(&self, asset: &str) -> bool {
+        self.topics.contains(&Topic::Prices)
+            || self.topics.contains(&Topic::AssetPrices(asset.to_uppercase()))
+    }
+
+    /// Whether a public update should be sent to this connection
+    pub fn wants(&self, update: &DashboardUpdate) -> bool {
+        match update {
+            DashboardUpdate::PriceUpdate { asset, .. }
+            | DashboardUpdate::PriceMeshUpdate { asset, .. } => self.wants_asset(asset),
+            DashboardUpdate::MeshStatus { .. } => self.topics.contains(&Topic::MeshStatus),
+            _ => true,
+        }
+    }
+
+    /// Subscribed topics in a stable order
+    pub fn topic_names(&self) -> Vec<String> {
+        let mut topics: Vec<&Topic> = self.topics.iter().collect();
+        topics.sort();
+        topics.into_iter().map(Topic::to_string).collect()
+    }
+}
+
+/// Messages a dashboard client sends over the socket
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe { topics: Vec<String> },
+    Unsubscribe { topics: Vec<String> },
 }
 
 /// WebSocket service for managing real-time dashboard updates
+///
+/// Public updates (prices, mesh status) go out on one broadcast channel that
+/// every connection filters by its subscriptions. Private updates go out on a
+/// per-user channel that only that user's authenticated connections receive.
 #[derive(Clone)]
 pub struct WebSocketService {
     tx: broadcast::Sender<DashboardUpdate>,
+    user_channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<DashboardUpdate>>>>,
 }
 
 impl WebSocketService {
     /// Create a new WebSocket service
     pub fn new() -> Self {
         let (tx, _rx) = broadcast::channel(CHANNEL_CAPACITY);
-        Self { tx }
+        Self {
+            tx,
+            user_channels: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
-    /// Get a receiver for dashboard updates
+    /// Get a receiver for public dashboard updates
     pub fn subscribe(&self) -> broadcast::Receiver<DashboardUpdate> {
         self.tx.subscribe()
     }
 
-    /// Broadcast a price update to all connected clients
+    /// Get a receiver for `user_id`'s private dashboard updates
+    pub fn subscribe_user(&self, user_id: Uuid) -> broadcast::Receiver<DashboardUpdate> {
+        let mut channels = self.user_channels.write().unwrap_or_else(|e| e.into_inner());
+        channels
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(USER_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Number of open receivers for `user_id`'s private updates
+    pub fn user_connection_count(&self, user_id: Uuid) -> usize {
+        let channels = self.user_channels.read().unwrap_or_else(|e| e.into_inner());
+        channels.get(&user_id).map_or(0, |tx| tx.receiver_count())
+    }
+
+    /// Drop `user_id`'s channel once none of their connections remain
+    pub fn release_user(&self, user_id: Uuid) {
+        let mut channels = self.user_channels.write().unwrap_or_else(|e| e.into_inner());
+        if channels.get(&user_id).is_some_and(|tx| tx.receiver_count() == 0) {
+            channels.remove(&user_id);
+        }
+    }
+
+    /// Send a private update to `user_id`'s connections, if they have any
+    fn send_to_user(&self, user_id: Uuid, update: DashboardUpdate, what: &str) -> bool {
+        let channels = self.user_channels.read().unwrap_or_else(|e| e.into_inner());
+        match channels.get(&user_id) {
+            Some(tx) if tx.send(update).is_ok() => true,
+            _ => {
+                debug!("No dashboard connected for user {}, dropping {}", user_id, what);
+                false
+            }
+        }
+    }
+
+    /// Broadcast mesh network health to clients subscribed to `mesh_status`
+    pub fn broadcast_mesh_status(&self, status: &NetworkStatus) {
+        let update = mesh_status_update(status);
+
+        if let Err(e) = self.tx.send(update) {
+            debug!("No dashboard subscribed to mesh status: {}", e);
+        }
+    }
+
+    /// Broadcast a price update to clients subscribed to the asset
     pub fn broadcast_price_update(
         &self,
         asset: String,
@@ -157,9 +358,10 @@ impl WebSocketService {
         }
     }
 
-    /// Broadcast a trade execution to all connected clients
+    /// Push a trade execution to `user_id`'s dashboards
     pub fn broadcast_trade_executed(
         &self,
+        user_id: Uuid,
         trade_id: Uuid,
         asset: String,
         action: String,
@@ -175,14 +377,13 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast trade execution: {}", e);
-        }
+        self.send_to_user(user_id, update, "trade execution");
     }
 
-    /// Broadcast a trim execution to all connected clients
+    /// Push a trim execution to `user_id`'s dashboards
     pub fn broadcast_trim_executed(
         &self,
+        user_id: Uuid,
         trim_id: Uuid,
         asset: String,
         amount_sold: String,
@@ -198,14 +399,13 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast trim execution: {}", e);
-        }
+        self.send_to_user(user_id, update, "trim execution");
     }
 
-    /// Broadcast a benchmark trigger to all connected clients
+    /// Push a benchmark trigger to `user_id`'s dashboards
     pub fn broadcast_benchmark_triggered(
         &self,
+        user_id: Uuid,
         benchmark_id: Uuid,
         asset: String,
         target_price: String,
@@ -221,27 +421,51 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast benchmark trigger: {}", e);
-        }
+        self.send_to_user(user_id, update, "benchmark trigger");
+    }
+
+    /// Push a conditional order status change to `user_id`'s dashboards
+    pub fn broadcast_conditional_order_updated(
+        &self,
+        user_id: Uuid,
+        order_id: Uuid,
+        asset: String,
+        order_type: String,
+        status: String,
+        price: Option<String>,
+    ) {
+        let update = DashboardUpdate::ConditionalOrderUpdated {
+            order_id: order_id.to_string(),
+            asset,
+            order_type,
+            status,
+            price,
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        self.send_to_user(user_id, update, "conditional order update");
     }
 
-    /// Broadcast a portfolio value update to all connected clients
-    pub fn broadcast_portfolio_update(&self, total_value_usd: String, change_24h: String) {
+    /// Push a portfolio value update to `user_id`'s dashboards
+    pub fn broadcast_portfolio_update(
+        &self,
+        user_id: Uuid,
+        total_value_usd: String,
+        change_24h: String,
+    ) {
         let update = DashboardUpdate::PortfolioUpdate {
             total_value_usd,
             change_24h,
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast portfolio update: {}", e);
-        }
+        self.send_to_user(user_id, update, "portfolio update");
     }
 
-    /// Broadcast a conversion completion to all connected clients
+    /// Push a conversion completion to `user_id`'s dashboards
     pub fn broadcast_conversion_completed(
         &self,
+        user_id: Uuid,
         conversion_id: Uuid,
         from_asset: String,
         to_asset: String,
@@ -257,17 +481,16 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast conversion completion: {}", e);
-        }
+        self.send_to_user(user_id, update, "conversion completion");
     }
 
-    /// Broadcast a mesh network price update to all connected clients
+    /// Broadcast a mesh network price update to clients subscribed to the asset
     /// 
     /// This method is used by the gossip protocol to push price updates
     /// received from the mesh network to WebSocket clients.
     /// 
     /// Requirements: 12.1
+    #[allow(clippy::too_many_arguments)]
     pub fn broadcast_mesh_price_update(
         &self,
         asset: String,
@@ -293,14 +516,16 @@ impl WebSocketService {
         }
     }
 
-    /// Broadcast a stealth payment detection to all connected clients
+    /// Push a stealth payment detection to the recipient's dashboards
     /// 
     /// This method is called when the stealth scanner detects an incoming
     /// payment during blockchain scanning.
     /// 
     /// Requirements: 10.4, 10.7
+    #[allow(clippy::too_many_arguments)]
     pub fn broadcast_stealth_payment_detected(
         &self,
+        user_id: Uuid,
         payment_id: Uuid,
         stealth_address: String,
         amount: u64,
@@ -320,12 +545,10 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast stealth payment detection: {}", e);
-        }
+        self.send_to_user(user_id, update, "stealth payment detection");
     }
 
-    /// Broadcast a payment queued event to all connected clients
+    /// Push a payment queued event to the sender's dashboards
     /// 
     /// This method is called when a stealth payment is added to the offline
     /// payment queue.
@@ -333,6 +556,7 @@ impl WebSocketService {
     /// Requirements: 10.4, 10.7
     pub fn broadcast_payment_queued(
         &self,
+        user_id: Uuid,
         payment_id: Uuid,
         stealth_address: String,
         amount: u64,
@@ -344,12 +568,10 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast payment queued: {}", e);
-        }
+        self.send_to_user(user_id, update, "payment queued");
     }
 
-    /// Broadcast a payment settled event to all connected clients
+    /// Push a payment settled event to the sender's dashboards
     /// 
     /// This method is called when a queued payment successfully settles
     /// on the blockchain.
@@ -357,6 +579,7 @@ impl WebSocketService {
     /// Requirements: 10.4, 10.7
     pub fn broadcast_payment_settled(
         &self,
+        user_id: Uuid,
         payment_id: Uuid,
         stealth_address: String,
         amount: u64,
@@ -370,12 +593,10 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast payment settled: {}", e);
-        }
+        self.send_to_user(user_id, update, "payment settled");
     }
 
-    /// Broadcast a payment failed event to all connected clients
+    /// Push a payment failed event to the sender's dashboards
     /// 
     /// This method is called when a queued payment fails after maximum
     /// retry attempts.
@@ -383,6 +604,7 @@ impl WebSocketService {
     /// Requirements: 10.4, 10.7
     pub fn broadcast_payment_failed(
         &self,
+        user_id: Uuid,
         payment_id: Uuid,
         stealth_address: String,
         amount: u64,
@@ -398,9 +620,7 @@ impl WebSocketService {
             timestamp: chrono::Utc::now().timestamp(),
         };
         
-        if let Err(e) = self.tx.send(update) {
-            warn!("Failed to broadcast payment failed: {}", e);
-        }
+        self.send_to_user(user_id, update, "payment failed");
     }
 }
 
@@ -410,123 +630,300 @@ impl Default for WebSocketService {
     }
 }
 
+/// In-app push notification channel backed by the dashboard WebSocket
+///
+/// Delivery is best effort: a user with no open dashboard still finds the
+/// notification in their feed, so a push with no listeners is not retried.
+pub struct WebSocketPushChannel {
+    websocket_service: Arc<WebSocketService>,
+}
+
+impl WebSocketPushChannel {
+    pub fn new(websocket_service: Arc<WebSocketService>) -> Self {
+        Self { websocket_service }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebSocketPushChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Push
+    }
+
+    async fn deliver(&self, notification: &Notification, _target: &str) -> notification::Result<()> {
+        let update = DashboardUpdate::Notification {
+            notification_id: notification.id.to_string(),
+            user_id: notification.user_id.to_string(),
+            notification_type: notification.notification_type.clone(),
+            title: notification.title.clone(),
+            message: notification.message.clone(),
+            priority: notification.priority.clone(),
+            timestamp: notification.created_at.timestamp(),
+        };
+
+        self.websocket_service
+            .send_to_user(notification.user_id, update, "notification");
+        Ok(())
+    }
+}
+
+
+/// Query parameters accepted on the dashboard WebSocket upgrade
+#[derive(Debug, Deserialize)]
+pub struct WebSocketAuthQuery {
+    /// JWT for clients that cannot set headers, such as browsers
+    token: Option<String>,
+}
+
 /// WebSocket handler for dashboard updates
+///
+/// The upgrade must carry a valid JWT, either as an `Authorization: Bearer`
+/// header or as a `token` query parameter, since browsers cannot set headers
+/// on WebSocket requests. The connection is bound to the token's user and
+/// closed when the token expires.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+    Query(query): Query<WebSocketAuthQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .or(query.token.as_deref())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let claims = state.jwt_config.verify_token(token).map_err(|e| {
+        debug!("Rejected dashboard WebSocket token: {}", e);
+        StatusCode::UNAUTHORIZED
+    })?;
+    let user_id = claims.user_id().ok_or(StatusCode::UNAUTHORIZED)?;
+    let expires_at = claims.exp as i64;
+
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, user_id, expires_at)))
 }
 
-/// Handle an individual WebSocket connection
+/// Handle an individual WebSocket connection for `user_id`
 /// 
 /// Requirements: 12.2 - Send initial cached data on WebSocket connection
 /// Requirements: 12.4 - Send only changed assets in updates (delta updates)
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
+async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: Uuid, expires_at: i64) {
     let (mut sender, mut receiver) = socket.split();
     
-    // Subscribe to dashboard updates
-    let mut rx = state.websocket_service.subscribe();
+    let mut public_rx = state.websocket_service.subscribe();
+    let mut user_rx = state.websocket_service.subscribe_user(user_id);
     
-    info!("New WebSocket connection established");
+    info!("New WebSocket connection established for user {}", user_id);
     
+    let mut subscriptions = Subscriptions::initial();
     // Track last sent prices for delta updates (Requirement 12.4)
-    let mut last_sent_prices: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let mut last_sent_prices: HashMap<String, String> = HashMap::new();
     
-    // Send initial cached mesh price data if available
-    // Note: This will be fully integrated when mesh_price_service is added to AppState
-    // For now, we prepare the infrastructure for sending initial data
+    let token_expiry = tokio::time::sleep(Duration::from_secs(
+        (expires_at - chrono::Utc::now().timestamp()).max(0) as u64,
+    ));
+    tokio::pin!(token_expiry);
     
-    // Spawn a task to send updates to the client
-    let mut send_task = tokio::spawn(async move {
-        // TODO: When mesh_price_service is added to AppState, send initial cached data here:
-        // if let Ok(cached_prices) = state.mesh_price_service.get_all_price_data().await {
-        //     for (asset, data) in cached_prices {
-        //         let freshness = DataFreshness::from_timestamp(data.timestamp);
-        //         let freshness_str = match freshness { ... };
-        //         let initial_update = DashboardUpdate::PriceMeshUpdate { ... };
-        //         if let Ok(json) = serde_json::to_string(&initial_update) {
-        //             let _ = sender.send(Message::Text(json)).await;
-        //         }
-        //         // Track initial prices for delta updates
-        //         last_sent_prices.insert(asset, data.price);
-        //     }
-        // }
-        
-        while let Ok(update) = rx.recv().await {
-            // For mesh price updates, check if the price has changed (delta update)
-            let should_send = match &update {
-                DashboardUpdate::PriceMeshUpdate { asset, price, .. } => {
-                    // Check if price has changed since last send
-                    let has_changed = last_sent_prices.get(asset)
-                        .map(|last_price| last_price != price)
-                        .unwrap_or(true); // Send if we haven't sent this asset before
-                    
-                    if has_changed {
-                        // Update tracking
-                        last_sent_prices.insert(asset.clone(), price.clone());
-                        true
-                    } else {
-                        // Price hasn't changed, skip sending
-                        false
+    if send_price_snapshot(&mut sender, &state, &subscriptions, &mut last_sent_prices).await {
+        loop {
+            let update = tokio::select! {
+                update = public_rx.recv() => match update {
+                    Ok(update) if subscriptions.wants(&update) => update,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client for user {} skipped {} updates", user_id, skipped);
+                        continue;
                     }
-                }
-                // For non-mesh updates, always send
-                _ => true,
-            };
-            
-            if should_send {
-                // Serialize the update to JSON
-                match serde_json::to_string(&update) {
-                    Ok(json) => {
-                        if sender.send(Message::Text(json)).await.is_err() {
-                            // Client disconnected
+                    Err(RecvError::Closed) => break,
+                },
+                update = user_rx.recv() => match update {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("WebSocket client for user {} skipped {} private updates", user_id, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                message = receiver.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let connected = handle_client_message(
+                            &text,
+                            &mut sender,
+                            &state,
+                            &mut subscriptions,
+                            &mut last_sent_prices,
+                        )
+                        .await;
+                        if !connected {
                             break;
                         }
+                        continue;
                     }
-                    Err(e) => {
-                        error!("Failed to serialize dashboard update: {}", e);
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!("WebSocket client requested close");
+                        break;
                     }
-                }
-            }
-        }
-    });
-    
-    // Spawn a task to receive messages from the client (for ping/pong)
-    let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
-                Message::Close(_) => {
-                    info!("WebSocket client requested close");
+                    // Pings are answered by axum
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        debug!("WebSocket receive error: {}", e);
+                        break;
+                    }
+                },
+                _ = &mut token_expiry => {
+                    info!("Closing WebSocket for user {}: token expired", user_id);
+                    let _ = sender
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::POLICY,
+                            reason: "token expired".into(),
+                        })))
+                        .await;
                     break;
                 }
-                Message::Ping(_data) => {
-                    // Echo back pong (axum handles this automatically)
-                    info!("Received ping from client");
-                }
-                Message::Pong(_) => {
-                    // Client responded to our ping
-                }
-                Message::Text(text) => {
-                    // Could handle client commands here if needed
-                    info!("Received text message from client: {}", text);
+            };
+            
+            // For mesh price updates, skip prices the client already has
+            if let DashboardUpdate::PriceMeshUpdate { asset, price, .. } = &update {
+                if last_sent_prices.get(asset) == Some(price) {
+                    continue;
                 }
-                _ => {}
+                last_sent_prices.insert(asset.clone(), price.clone());
+            }
+            
+            if !send_update(&mut sender, &update).await {
+                // Client disconnected
+                break;
             }
         }
-    });
+    }
     
-    // Wait for either task to finish
-    tokio::select! {
-        _ = (&mut send_task) => {
-            recv_task.abort();
-            info!("WebSocket send task completed");
+    drop(user_rx);
+    state.websocket_service.release_user(user_id);
+    info!("WebSocket connection closed for user {}", user_id);
+}
+
+/// Apply a subscribe or unsubscribe request from the client
+///
+/// Returns false once the client has disconnected.
+async fn handle_client_message(
+    text: &str,
+    sender: &mut SplitSink<WebSocket, Message>,
+    state: &AppState,
+    subscriptions: &mut Subscriptions,
+    last_sent_prices: &mut HashMap<String, String>,
+) -> bool {
+    let (subscribe, names) = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::Subscribe { topics }) => (true, topics),
+        Ok(ClientMessage::Unsubscribe { topics }) => (false, topics),
+        Err(e) => {
+            let message = format!("Invalid message: {}", e);
+            return send_update(sender, &DashboardUpdate::Error { message }).await;
         }
-        _ = (&mut recv_task) => {
-            send_task.abort();
-            info!("WebSocket receive task completed");
+    };
+    let topics = match names.iter().map(|name| name.parse::<Topic>()).collect::<Result<Vec<_>, _>>() {
+        Ok(topics) => topics,
+        Err(message) => return send_update(sender, &DashboardUpdate::Error { message }).await,
+    };
+    
+    let wants_mesh_status = subscribe
+        && topics.contains(&Topic::MeshStatus)
+        && !subscriptions.contains(&Topic::MeshStatus);
+    if subscribe {
+        subscriptions.subscribe(topics);
+    } else {
+        subscriptions.unsubscribe(&topics);
+        // Forget prices no longer followed so resubscribing sends them again
+        last_sent_prices.retain(|asset, _| subscriptions.wants_asset(asset));
+    }
+    
+    let ack = DashboardUpdate::Subscriptions {
+        topics: subscriptions.topic_names(),
+    };
+    if !send_update(sender, &ack).await {
+        return false;
+    }
+    if !subscribe {
+        return true;
+    }
+    
+    if !send_price_snapshot(sender, state, subscriptions, last_sent_prices).await {
+        return false;
+    }
+    if wants_mesh_status {
+        match state.mesh_price_service.get_network_status().await {
+            Ok(status) => return send_update(sender, &mesh_status_update(&status)).await,
+            Err(e) => warn!("Failed to load mesh status for WebSocket client: {}", e),
         }
     }
+    true
+}
+
+/// Send the cached mesh prices the client is subscribed to but has not seen
+///
+/// Returns false once the client has disconnected.
+///
+/// Requirements: 12.2
+async fn send_price_snapshot(
+    sender: &mut SplitSink<WebSocket, Message>,
+    state: &AppState,
+    subscriptions: &Subscriptions,
+    last_sent_prices: &mut HashMap<String, String>,
+) -> bool {
+    let cached_prices = match state.mesh_price_service.get_all_price_data().await {
+        Ok(cached_prices) => cached_prices,
+        Err(e) => {
+            warn!("Failed to load cached prices for WebSocket client: {}", e);
+            return true;
+        }
+    };
     
-    info!("WebSocket connection closed");
+    for data in cached_prices.into_values() {
+        if !subscriptions.wants_asset(&data.asset)
+            || last_sent_prices.get(&data.asset) == Some(&data.price)
+        {
+            continue;
+        }
+        last_sent_prices.insert(data.asset.clone(), data.price.clone());
+        
+        if !send_update(sender, &mesh_price_update(data)).await {
+            return false;
+        }
+    }
+    true
+}
+
+/// Serialize and send one update; returns false once the client is gone
+async fn send_update(sender: &mut SplitSink<WebSocket, Message>, update: &DashboardUpdate) -> bool {
+    match serde_json::to_string(update) {
+        Ok(json) => sender.send(Message::Text(json)).await.is_ok(),
+        Err(e) => {
+            error!("Failed to serialize dashboard update: {}", e);
+            true
+        }
+    }
+}
+
+fn mesh_price_update(data: ConsensusPriceData) -> DashboardUpdate {
+    DashboardUpdate::PriceMeshUpdate {
+        freshness: DataFreshness::from_timestamp(data.timestamp).label(),
+        asset: data.asset,
+        blockchain: data.blockchain,
+        price: data.price,
+        change_24h: data.change_24h,
+        timestamp: data.timestamp.timestamp(),
+        source_node_id: data.source_node_id.to_string(),
+    }
+}
+
+fn mesh_status_update(status: &NetworkStatus) -> DashboardUpdate {
+    DashboardUpdate::MeshStatus {
+        active_providers: status.active_providers.len(),
+        connected_peers: status.connected_peers,
+        total_network_size: status.total_network_size,
+        data_freshness: status.data_freshness.label(),
+        extended_offline: status.extended_offline,
+        offline_duration_minutes: status.offline_duration_minutes,
+        timestamp: chrono::Utc::now().timestamp(),
+    }
 }
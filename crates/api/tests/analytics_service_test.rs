@@ -347,6 +347,8 @@ fn test_performance_metrics_structure() {
         change_7d_percent: 10.0,
         all_time_profit_loss_usd: 2000.0,
         all_time_profit_loss_percent: 20.0,
+        realized_profit_loss_usd: 300.0,
+        unrealized_profit_loss_usd: 1200.0,
         positions: vec![],
         timestamp: Utc::now(),
     };
@@ -356,6 +358,7 @@ fn test_performance_metrics_structure() {
     assert!(json.contains("change_24h_usd"));
     assert!(json.contains("change_7d_usd"));
     assert!(json.contains("all_time_profit_loss_usd"));
+    assert!(json.contains("realized_profit_loss_usd"));
 }
 
 #[test]
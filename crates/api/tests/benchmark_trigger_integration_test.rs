@@ -80,7 +80,7 @@ async fn test_benchmark_trigger_creates_alert_notification() {
     let birdeye_api_key = std::env::var("BIRDEYE_API_KEY")
         .unwrap_or_else(|_| "test_key".to_string());
     let birdeye_service = Arc::new(BirdeyeService::new(birdeye_api_key, redis_pool.clone()));
-    let notification_service = Arc::new(NotificationService::new());
+    let notification_service = Arc::new(NotificationService::new(pool.clone()));
     let position_management_service = Arc::new(PositionManagementService::new(pool.clone()));
     
     // Create price monitor (wiring benchmark triggers to notification service)
@@ -131,7 +131,7 @@ async fn test_benchmark_trigger_creates_execute_notification() {
     let birdeye_api_key = std::env::var("BIRDEYE_API_KEY")
         .unwrap_or_else(|_| "test_key".to_string());
     let birdeye_service = Arc::new(BirdeyeService::new(birdeye_api_key, redis_pool.clone()));
-    let notification_service = Arc::new(NotificationService::new());
+    let notification_service = Arc::new(NotificationService::new(pool.clone()));
     let position_management_service = Arc::new(PositionManagementService::new(pool.clone()));
     
     // Create price monitor (wiring benchmark triggers to trading service)
@@ -178,54 +178,43 @@ async fn test_notification_service_integration() {
     let pool = setup_test_db().await;
     let user_id = create_test_user(&pool).await;
     
-    // Create a notification directly in the database (simulating what price_monitor does)
-    let client = pool.get().await.expect("Failed to get client");
-    
-    let notification_id = Uuid::new_v4();
-    client
-        .execute(
-            "INSERT INTO notifications (id, user_id, type, title, message, data, priority, read, created_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())",
-            &[
-                &notification_id,
-                &user_id,
-                &"BENCHMARK_ALERT",
-                &"Price Alert: SOL",
-                &"Price alert: SOL has crossed above your target price of 100. Current price: 105",
-                &serde_json::json!({
-                    "asset": "SOL",
-                    "target_price": "100",
-                    "current_price": "105",
-                }),
-                &"HIGH",
-                &false,
-            ],
-        )
+    // Create a notification the way price_monitor does
+    let notification_service = NotificationService::new(pool.clone());
+
+    let notification = notification_service
+        .create_notification(shared::models::Notification {
+            id: Uuid::new_v4(),
+            user_id,
+            notification_type: "BENCHMARK_ALERT".to_string(),
+            title: "Price Alert: SOL".to_string(),
+            message: "Price alert: SOL has crossed above your target price of 100. Current price: 105"
+                .to_string(),
+            data: Some(serde_json::json!({
+                "asset": "SOL",
+                "target_price": "100",
+                "current_price": "105",
+            })),
+            priority: "HIGH".to_string(),
+            read: false,
+            created_at: chrono::Utc::now(),
+        })
         .await
         .expect("Failed to create notification");
-    
-    // Verify notification was created
-    let row = client
-        .query_one(
-            "SELECT id, user_id, type, title, priority, read FROM notifications WHERE id = $1",
-            &[&notification_id],
-        )
+
+    // Verify notification was stored
+    let unread = notification_service
+        .get_unread_notifications(user_id, None, notification::DEFAULT_PAGE_SIZE)
         .await
-        .expect("Failed to query notification");
-    
-    let retrieved_id: Uuid = row.get(0);
-    let retrieved_user_id: Uuid = row.get(1);
-    let notification_type: String = row.get(2);
-    let title: String = row.get(3);
-    let priority: String = row.get(4);
-    let read: bool = row.get(5);
-    
-    assert_eq!(retrieved_id, notification_id);
-    assert_eq!(retrieved_user_id, user_id);
-    assert_eq!(notification_type, "BENCHMARK_ALERT");
-    assert_eq!(title, "Price Alert: SOL");
-    assert_eq!(priority, "HIGH");
-    assert!(!read);
+        .expect("Failed to query notifications");
+
+    assert_eq!(unread.notifications.len(), 1);
+    let retrieved = &unread.notifications[0];
+    assert_eq!(retrieved.id, notification.id);
+    assert_eq!(retrieved.user_id, user_id);
+    assert_eq!(retrieved.notification_type, "BENCHMARK_ALERT");
+    assert_eq!(retrieved.title, "Price Alert: SOL");
+    assert_eq!(retrieved.priority, "HIGH");
+    assert!(!retrieved.read);
     
     cleanup_test_data(&pool, user_id).await;
 }
@@ -1,4 +1,5 @@
-use api::birdeye_service::{Blockchain, BirdeyeService, WalletAddress};
+use api::birdeye_service::{BirdeyeService, WalletAddress};
+use blockchain::{Blockchain, ChainRegistry};
 use redis::aio::ConnectionManager;
 use rust_decimal::Decimal;
 
@@ -35,7 +36,7 @@ async fn test_get_multi_chain_portfolio_with_valid_addresses() {
     // Test with a known Solana wallet (replace with a test wallet)
     let wallets = vec![
         WalletAddress {
-            blockchain: Blockchain::Solana,
+            blockchain: Blockchain::SOLANA,
             address: "So11111111111111111111111111111111111111112".to_string(),
         },
     ];
@@ -66,7 +67,7 @@ async fn test_get_asset_price_for_solana_token() {
     // Test with SOL token address
     let sol_address = "So11111111111111111111111111111111111111112";
     
-    let result = service.get_asset_price(&Blockchain::Solana, sol_address).await;
+    let result = service.get_asset_price(&Blockchain::SOLANA, sol_address).await;
     
     match result {
         Ok(price_data) => {
@@ -80,16 +81,19 @@ async fn test_get_asset_price_for_solana_token() {
 
 #[test]
 fn test_blockchain_to_birdeye_chain_conversion() {
-    assert_eq!(Blockchain::Solana.to_birdeye_chain(), "solana");
-    assert_eq!(Blockchain::Ethereum.to_birdeye_chain(), "ethereum");
-    assert_eq!(Blockchain::BinanceSmartChain.to_birdeye_chain(), "bsc");
-    assert_eq!(Blockchain::Polygon.to_birdeye_chain(), "polygon");
+    // Birdeye's x-chain names are the registry slugs
+    let chains = ChainRegistry::default();
+    let slug = |blockchain| chains.get(&blockchain).unwrap().slug.clone();
+    assert_eq!(slug(Blockchain::SOLANA), "solana");
+    assert_eq!(slug(Blockchain::ETHEREUM), "ethereum");
+    assert_eq!(slug(Blockchain::BINANCE_SMART_CHAIN), "bsc");
+    assert_eq!(slug(Blockchain::POLYGON), "polygon");
 }
 
 #[test]
 fn test_wallet_address_serialization() {
     let wallet = WalletAddress {
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
         address: "0x1234567890abcdef".to_string(),
     };
     
@@ -136,7 +140,7 @@ fn test_multi_chain_portfolio_structure() {
             symbol: "SOL".to_string(),
             name: "Solana".to_string(),
             address: "So11111111111111111111111111111111111111112".to_string(),
-            blockchain: Blockchain::Solana,
+            blockchain: Blockchain::SOLANA,
             balance: Decimal::from(100),
             price_usd: Decimal::from(50),
             value_usd: Decimal::from(5000),
@@ -179,7 +183,7 @@ fn test_asset_value_calculation() {
         symbol: "ETH".to_string(),
         name: "Ethereum".to_string(),
         address: "0x0000000000000000000000000000000000000000".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
         balance: Decimal::from(10),
         price_usd: Decimal::from(2000),
         value_usd: Decimal::from(20000),
@@ -198,7 +202,7 @@ async fn test_retry_logic_on_api_failure() {
     let service = BirdeyeService::new(api_key, redis);
     
     let wallets = vec![WalletAddress {
-        blockchain: Blockchain::Solana,
+        blockchain: Blockchain::SOLANA,
         address: "invalid_address".to_string(),
     }];
     
@@ -239,7 +243,7 @@ fn test_multiple_chains_in_portfolio() {
             symbol: "SOL".to_string(),
             name: "Solana".to_string(),
             address: "So11111111111111111111111111111111111111112".to_string(),
-            blockchain: Blockchain::Solana,
+            blockchain: Blockchain::SOLANA,
             balance: Decimal::from(100),
             price_usd: Decimal::from(50),
             value_usd: Decimal::from(5000),
@@ -253,7 +257,7 @@ fn test_multiple_chains_in_portfolio() {
             symbol: "ETH".to_string(),
             name: "Ethereum".to_string(),
             address: "0x0000000000000000000000000000000000000000".to_string(),
-            blockchain: Blockchain::Ethereum,
+            blockchain: Blockchain::ETHEREUM,
             balance: Decimal::from(10),
             price_usd: Decimal::from(2000),
             value_usd: Decimal::from(20000),
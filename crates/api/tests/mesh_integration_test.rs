@@ -6,6 +6,17 @@ use proximity::PeerConnectionManager;
 use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
+use api::{ProviderIdentity, ProviderKeyStore};
+use solana_sdk::signer::keypair::keypair_from_seed;
+
+/// Sign an update as its source node, with a key derived from the node ID so
+/// repeated updates from one provider match its pinned key
+fn sign_update(update: &mut PriceUpdate) {
+    let node_id = update.source_node_id.as_bytes();
+    let seed = [node_id.as_slice(), node_id.as_slice()].concat();
+    let keypair = keypair_from_seed(&seed).expect("32-byte seed");
+    ProviderIdentity::from_keypair(keypair).sign(update);
+}
 
 /// Helper to create a test Redis connection
 async fn create_test_redis() -> redis::aio::ConnectionManager {
@@ -51,7 +62,7 @@ async fn test_price_cache_storage() {
     let redis = create_test_redis().await;
     let db = create_test_db().await;
     let metrics = Arc::new(MeshMetricsCollector::new());
-    let cache = PriceCache::new(redis, db, metrics);
+    let cache = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 120, 1);
     
     let asset = "SOL".to_string();
     let data = CachedPriceData {
@@ -61,6 +72,7 @@ async fn test_price_cache_storage() {
         source_node_id: Uuid::new_v4(),
         blockchain: "solana".to_string(),
         change_24h: Some("5.2".to_string()),
+        signer: None,
     };
     
     // Store data
@@ -82,7 +94,7 @@ async fn test_price_cache_timestamp_comparison() {
     let redis = create_test_redis().await;
     let db = create_test_db().await;
     let metrics = Arc::new(MeshMetricsCollector::new());
-    let cache = PriceCache::new(redis, db, metrics);
+    let cache = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 120, 1);
     
     let asset = "SOL".to_string();
     let now = Utc::now();
@@ -91,10 +103,12 @@ async fn test_price_cache_timestamp_comparison() {
     let old_data = CachedPriceData {
         asset: asset.clone(),
         price: "100.00".to_string(),
-        timestamp: now - chrono::Duration::minutes(5),
+        // Within the consensus window, so it is cached
+        timestamp: now - chrono::Duration::seconds(90),
         source_node_id: Uuid::new_v4(),
         blockchain: "solana".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), old_data.clone()).await.unwrap();
     
@@ -106,6 +120,7 @@ async fn test_price_cache_timestamp_comparison() {
         source_node_id: Uuid::new_v4(),
         blockchain: "solana".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), older_data).await.unwrap();
     
@@ -121,6 +136,7 @@ async fn test_price_cache_timestamp_comparison() {
         source_node_id: Uuid::new_v4(),
         blockchain: "solana".to_string(),
         change_24h: Some("5.0".to_string()),
+        signer: None,
     };
     cache.store(asset.clone(), new_data.clone()).await.unwrap();
     
@@ -183,7 +199,9 @@ async fn test_full_message_flow() {
     
     // Create components
     let message_tracker = Arc::new(MessageTracker::new(redis.clone()));
-    let price_cache = Arc::new(PriceCache::new(redis.clone(), db, metrics.clone()));
+    let price_cache = Arc::new(
+        PriceCache::new(redis.clone(), db, metrics.clone()).with_consensus_settings(5.0, 120, 1),
+    );
     let peer_manager = Arc::new(PeerConnectionManager::new());
     let websocket_service = Arc::new(WebSocketService::new());
     
@@ -193,6 +211,7 @@ async fn test_full_message_flow() {
         price_cache.clone(),
         websocket_service,
         metrics,
+        Arc::new(ProviderKeyStore::trust_on_first_use()),
     );
     
     // Create a price update
@@ -204,13 +223,16 @@ async fn test_full_message_flow() {
         change_24h: Some("5.2".to_string()),
     });
     
-    let update = PriceUpdate {
+    let mut update = PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id: Uuid::new_v4(),
         timestamp: Utc::now(),
         prices,
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut update);
     
     let from_peer = "test-peer".to_string();
     
@@ -231,6 +253,58 @@ async fn test_full_message_flow() {
     assert!(result.is_ok(), "Duplicate message should be handled gracefully");
 }
 
+/// Integration test: unsigned or forged updates never reach the cache
+#[tokio::test]
+#[ignore] // Requires Redis and database connection
+async fn test_gossip_rejects_unauthenticated_updates() {
+    let redis = create_test_redis().await;
+    let db = create_test_db().await;
+    let metrics = Arc::new(MeshMetricsCollector::new());
+    
+    let message_tracker = Arc::new(MessageTracker::new(redis.clone()));
+    let price_cache = Arc::new(
+        PriceCache::new(redis.clone(), db, metrics.clone()).with_consensus_settings(5.0, 120, 1),
+    );
+    let gossip = GossipProtocol::new(
+        Arc::new(PeerConnectionManager::new()),
+        message_tracker.clone(),
+        price_cache.clone(),
+        Arc::new(WebSocketService::new()),
+        metrics,
+        Arc::new(ProviderKeyStore::trust_on_first_use()),
+    );
+    
+    let asset = format!("FORGED-{}", Uuid::new_v4());
+    let mut prices = HashMap::new();
+    prices.insert(asset.clone(), PriceData {
+        asset: asset.clone(),
+        price: "1.00".to_string(),
+        blockchain: "solana".to_string(),
+        change_24h: None,
+    });
+    
+    let unsigned = PriceUpdate {
+        message_id: Uuid::new_v4(),
+        source_node_id: Uuid::new_v4(),
+        timestamp: Utc::now(),
+        prices,
+        ttl: 10,
+        signer: None,
+        signature: None,
+    };
+    assert!(gossip.process_update(unsigned.clone(), "test-peer".to_string()).await.is_err());
+    
+    // A valid signature no longer matches once a relay tampers with the prices
+    let mut tampered = unsigned.clone();
+    tampered.message_id = Uuid::new_v4();
+    sign_update(&mut tampered);
+    tampered.prices.get_mut(&asset).unwrap().price = "2.00".to_string();
+    assert!(gossip.process_update(tampered.clone(), "test-peer".to_string()).await.is_err());
+    
+    assert!(!message_tracker.has_seen(&tampered.message_id).await);
+    assert!(price_cache.get(&asset).await.unwrap().is_none());
+}
+
 /// Test that components can be loaded from storage on startup
 #[tokio::test]
 #[ignore] // Requires Redis and database connection
@@ -240,7 +314,7 @@ async fn test_component_persistence() {
     let metrics = Arc::new(MeshMetricsCollector::new());
     
     // Create and populate a price cache
-    let cache1 = PriceCache::new(redis.clone(), db.clone(), metrics.clone());
+    let cache1 = PriceCache::new(redis.clone(), db.clone(), metrics.clone()).with_consensus_settings(5.0, 120, 1);
     let asset = "SOL".to_string();
     let data = CachedPriceData {
         asset: asset.clone(),
@@ -249,13 +323,14 @@ async fn test_component_persistence() {
         source_node_id: Uuid::new_v4(),
         blockchain: "solana".to_string(),
         change_24h: Some("5.2".to_string()),
+        signer: None,
     };
     
     cache1.store(asset.clone(), data.clone()).await.unwrap();
     cache1.persist_to_storage().await.unwrap();
     
     // Create a new cache instance and load from storage
-    let cache2 = PriceCache::new(redis, db, metrics);
+    let cache2 = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 120, 1);
     cache2.load_from_storage().await.unwrap();
     
     // Verify data was loaded
@@ -293,7 +368,7 @@ async fn test_price_cache_discrepancy_detection() {
     let redis = create_test_redis().await;
     let db = create_test_db().await;
     let metrics = Arc::new(MeshMetricsCollector::new());
-    let cache = PriceCache::new(redis, db, metrics);
+    let cache = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 120, 1);
     
     let asset = "SOL".to_string();
     let now = Utc::now();
@@ -308,6 +383,7 @@ async fn test_price_cache_discrepancy_detection() {
         source_node_id: provider1,
         blockchain: "solana".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data1).await.unwrap();
     
@@ -320,6 +396,7 @@ async fn test_price_cache_discrepancy_detection() {
         source_node_id: provider2,
         blockchain: "solana".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data2.clone()).await.unwrap();
     
@@ -337,6 +414,7 @@ async fn test_price_cache_discrepancy_detection() {
         source_node_id: provider1,
         blockchain: "solana".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data3.clone()).await.unwrap();
     
@@ -352,7 +430,7 @@ async fn test_price_cache_multi_provider_freshness() {
     let redis = create_test_redis().await;
     let db = create_test_db().await;
     let metrics = Arc::new(MeshMetricsCollector::new());
-    let cache = PriceCache::new(redis, db, metrics);
+    let cache = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 120, 1);
     
     let asset = "ETH".to_string();
     let now = Utc::now();
@@ -368,6 +446,7 @@ async fn test_price_cache_multi_provider_freshness() {
         source_node_id: provider1,
         blockchain: "ethereum".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data1).await.unwrap();
     
@@ -379,6 +458,7 @@ async fn test_price_cache_multi_provider_freshness() {
         source_node_id: provider2,
         blockchain: "ethereum".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data2.clone()).await.unwrap();
     
@@ -395,6 +475,7 @@ async fn test_price_cache_multi_provider_freshness() {
         source_node_id: provider3,
         blockchain: "ethereum".to_string(),
         change_24h: None,
+        signer: None,
     };
     cache.store(asset.clone(), data3).await.unwrap();
     
@@ -403,3 +484,96 @@ async fn test_price_cache_multi_provider_freshness() {
     assert_eq!(retrieved.price, "2010.00", "Freshest data should be kept");
     assert_eq!(retrieved.source_node_id, provider2, "Provider 2 has freshest data");
 }
+
+/// Test that one provider quoting far from the others cannot move the consensus
+#[tokio::test]
+#[ignore] // Requires Redis and database connection
+async fn test_price_cache_consensus_rejects_outlier() {
+    let redis = create_test_redis().await;
+    let db = create_test_db().await;
+    let metrics = Arc::new(MeshMetricsCollector::new());
+    let cache = PriceCache::new(redis, db, metrics.clone()).with_consensus_settings(5.0, 120, 3);
+    
+    let asset = "BTC".to_string();
+    let now = Utc::now();
+    let honest = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+    let rogue = Uuid::new_v4();
+    
+    for (i, (provider, price)) in honest
+        .iter()
+        .zip(["45000.00", "45100.00", "44950.00"])
+        .enumerate()
+    {
+        let data = CachedPriceData {
+            asset: asset.clone(),
+            price: price.to_string(),
+            timestamp: now + chrono::Duration::seconds(i as i64),
+            source_node_id: *provider,
+            blockchain: "bitcoin".to_string(),
+            change_24h: None,
+            signer: None,
+        };
+        cache.store(asset.clone(), data).await.unwrap();
+    }
+    
+    // The rogue provider publishes the newest, wildly wrong quote
+    let spoofed = CachedPriceData {
+        asset: asset.clone(),
+        price: "90000.00".to_string(),
+        timestamp: now + chrono::Duration::seconds(10),
+        source_node_id: rogue,
+        blockchain: "bitcoin".to_string(),
+        change_24h: None,
+        signer: None,
+    };
+    cache.store(asset.clone(), spoofed).await.unwrap();
+    
+    let consensus = cache.consensus(&asset).await.unwrap().unwrap();
+    assert_eq!(consensus.price, "45000.00", "Outlier should not move the consensus");
+    assert_eq!(consensus.outliers, vec![rogue]);
+    assert!(!consensus.providers.contains(&rogue));
+    assert!(consensus.confidence > 0.5 && consensus.confidence < 1.0);
+    
+    // The outlier never becomes the cached price and is recorded against its provider
+    let cached = cache.get(&asset).await.unwrap().unwrap();
+    assert_ne!(cached.source_node_id, rogue);
+    
+    let stats = metrics.get_metrics().await.validation_stats;
+    assert_eq!(stats.failure_reasons.get("price_outlier"), Some(&1));
+    assert_eq!(stats.failures_by_node.get(&rogue), Some(&1));
+}
+
+/// Test that quotes aging past the consensus window stop producing a quorum
+#[tokio::test]
+#[ignore] // Requires Redis and database connection
+async fn test_price_cache_consensus_expires_with_quotes() {
+    let redis = create_test_redis().await;
+    let db = create_test_db().await;
+    let metrics = Arc::new(MeshMetricsCollector::new());
+    let cache = PriceCache::new(redis, db, metrics).with_consensus_settings(5.0, 2, 3);
+    
+    let asset = format!("EXP{}", &Uuid::new_v4().simple().to_string()[..6]).to_uppercase();
+    let now = Utc::now();
+    for price in ["10.00", "10.01", "9.99"] {
+        let data = CachedPriceData {
+            asset: asset.clone(),
+            price: price.to_string(),
+            timestamp: now,
+            source_node_id: Uuid::new_v4(),
+            blockchain: "solana".to_string(),
+            change_24h: None,
+            signer: None,
+        };
+        cache.store(asset.clone(), data).await.unwrap();
+    }
+    
+    let consensus = cache.consensus(&asset).await.unwrap().unwrap();
+    assert_eq!(consensus.confidence, 1.0);
+    
+    // Providers stop sending; only the low-confidence cached price is left
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    let consensus = cache.consensus(&asset).await.unwrap().unwrap();
+    assert!(consensus.confidence < 0.5);
+    assert_eq!(consensus.providers.len(), 1);
+    assert!(cache.get_all_consensus().await.unwrap()[&asset].confidence < 0.5);
+}
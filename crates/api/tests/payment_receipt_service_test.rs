@@ -35,9 +35,9 @@ async fn create_test_service() -> Result<(PaymentReceiptService, database::DbPoo
     let blockchain_client = Arc::new(
         MultiChainClient::new()
             .with_solana("https://api.devnet.solana.com".to_string(), None)
-            .with_ethereum("https://eth-sepolia.public.blastapi.io".to_string(), None)
-            .with_bsc("https://bsc-testnet.public.blastapi.io".to_string(), None)
-            .with_polygon("https://polygon-mumbai.public.blastapi.io".to_string(), None)
+            .with_evm(&Blockchain::ETHEREUM, "https://eth-sepolia.public.blastapi.io".to_string(), None).unwrap()
+            .with_evm(&Blockchain::BINANCE_SMART_CHAIN, "https://bsc-testnet.public.blastapi.io".to_string(), None).unwrap()
+            .with_evm(&Blockchain::POLYGON, "https://polygon-mumbai.public.blastapi.io".to_string(), None).unwrap()
     );
     
     let receipt_service = Arc::new(ReceiptService::new(db_pool.clone(), blockchain_client));
@@ -70,6 +70,24 @@ async fn create_test_user(db: &database::DbPool) -> Uuid {
     user_id
 }
 
+/// Helper to create a test user with a connected wallet, returning the
+/// user_id and the wallet address
+async fn create_test_user_with_wallet(db: &database::DbPool) -> (Uuid, String) {
+    let user_id = create_test_user(db).await;
+    let wallet = format!("wallet-{}", &user_id.simple().to_string()[..20]);
+    let client = db.get().await.expect("Failed to get db connection");
+
+    client
+        .execute(
+            "INSERT INTO wallets (user_id, address) VALUES ($1, $2)",
+            &[&user_id, &wallet],
+        )
+        .await
+        .expect("Failed to insert test wallet");
+
+    (user_id, wallet)
+}
+
 #[tokio::test]
 async fn test_generate_payment_receipt() {
     let (service, _db) = match create_test_service().await {
@@ -85,7 +103,7 @@ async fn test_generate_payment_receipt() {
     let currency = "USD".to_string();
     let sender = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string();
     let recipient = "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string();
-    let blockchain = Blockchain::Ethereum;
+    let blockchain = Blockchain::ETHEREUM;
     let network_fee = Some(Decimal::new(21, 2)); // 0.21
     let platform_fee = Some(Decimal::new(50, 2)); // 0.50
 
@@ -96,7 +114,7 @@ async fn test_generate_payment_receipt() {
             currency.clone(),
             sender.clone(),
             recipient.clone(),
-            blockchain,
+            blockchain.clone(),
             network_fee,
             platform_fee,
         )
@@ -124,7 +142,7 @@ async fn test_generate_payment_receipt() {
 
     // Verify blockchain confirmation
     assert_eq!(receipt.confirmation.blockchain, blockchain);
-    assert!(!receipt.confirmation.transaction_hash.is_empty());
+    assert!(receipt.confirmation.transaction_hash.is_some());
 }
 
 #[tokio::test]
@@ -143,7 +161,7 @@ async fn test_generate_trade_receipt() {
     let price_usd = Some(Decimal::new(10050, 2)); // 100.50 USD per token
     let sender = "wallet1".to_string();
     let recipient = "wallet2".to_string();
-    let blockchain = Blockchain::Solana;
+    let blockchain = Blockchain::SOLANA;
     let network_fee = Some(Decimal::new(5000, 6)); // 0.005 SOL
     let platform_fee = Some(Decimal::new(1, 2)); // 0.01 SOL
 
@@ -155,7 +173,7 @@ async fn test_generate_trade_receipt() {
             price_usd,
             sender.clone(),
             recipient.clone(),
-            blockchain,
+            blockchain.clone(),
             network_fee,
             platform_fee,
         )
@@ -182,7 +200,7 @@ async fn test_generate_trade_receipt() {
 
     // Verify blockchain confirmation
     assert_eq!(receipt.confirmation.blockchain, blockchain);
-    assert!(!receipt.confirmation.transaction_hash.is_empty());
+    assert!(receipt.confirmation.transaction_hash.is_some());
 }
 
 #[tokio::test]
@@ -204,7 +222,7 @@ async fn test_generate_conversion_receipt() {
     let exchange_rate = Decimal::new(100, 0); // 100 USDC per SOL
     let sender = "wallet1".to_string();
     let recipient = "wallet2".to_string();
-    let blockchain = Blockchain::Solana;
+    let blockchain = Blockchain::SOLANA;
     let network_fee = Some(Decimal::new(5000, 6)); // 0.005 SOL
     let platform_fee = Some(Decimal::new(50, 2)); // 0.50 USDC
     let provider_fee = Some(Decimal::new(25, 2)); // 0.25 USDC
@@ -244,7 +262,7 @@ async fn test_generate_conversion_receipt() {
             exchange_rate,
             sender.clone(),
             recipient.clone(),
-            blockchain,
+            blockchain.clone(),
             network_fee,
             platform_fee,
             provider_fee,
@@ -274,7 +292,7 @@ async fn test_generate_conversion_receipt() {
 
     // Verify blockchain confirmation
     assert_eq!(receipt.confirmation.blockchain, blockchain);
-    assert!(!receipt.confirmation.transaction_hash.is_empty());
+    assert!(receipt.confirmation.transaction_hash.is_some());
 }
 
 #[tokio::test]
@@ -295,7 +313,7 @@ async fn test_receipt_has_all_required_fields() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -322,8 +340,8 @@ async fn test_receipt_has_all_required_fields() {
     assert_eq!(receipt.exchange_rate, None);
 
     // Blockchain confirmation
-    assert!(!receipt.confirmation.transaction_hash.is_empty());
-    assert_eq!(receipt.confirmation.blockchain, Blockchain::Ethereum);
+    assert!(receipt.confirmation.transaction_hash.is_some());
+    assert_eq!(receipt.confirmation.blockchain, Blockchain::ETHEREUM);
 }
 
 #[tokio::test]
@@ -345,7 +363,7 @@ async fn test_get_receipt_by_id() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -384,7 +402,7 @@ async fn test_get_receipt_by_transaction() {
             Some(Decimal::new(100, 0)),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
         )
@@ -425,7 +443,7 @@ async fn test_verify_receipt() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -493,7 +511,7 @@ async fn test_fees_calculation() {
             Decimal::new(100, 0),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             Some(Decimal::new(10, 2)),  // 0.10
             Some(Decimal::new(20, 2)),  // 0.20
             Some(Decimal::new(30, 2)),  // 0.30
@@ -513,7 +531,7 @@ async fn test_fees_calculation() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             Some(Decimal::new(15, 2)), // 0.15
             None,
         )
@@ -532,7 +550,7 @@ async fn test_fees_calculation() {
             None,
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
         )
@@ -553,10 +571,10 @@ async fn test_multiple_blockchains() {
     };
 
     let blockchains = vec![
-        Blockchain::Solana,
-        Blockchain::Ethereum,
-        Blockchain::BinanceSmartChain,
-        Blockchain::Polygon,
+        Blockchain::SOLANA,
+        Blockchain::ETHEREUM,
+        Blockchain::BINANCE_SMART_CHAIN,
+        Blockchain::POLYGON,
     ];
 
     for blockchain in blockchains {
@@ -568,7 +586,7 @@ async fn test_multiple_blockchains() {
                 "USD".to_string(),
                 "sender".to_string(),
                 "recipient".to_string(),
-                blockchain,
+                blockchain.clone(),
                 None,
                 None,
             )
@@ -611,7 +629,7 @@ async fn test_receipt_timestamp_accuracy() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -674,7 +692,7 @@ async fn test_conversion_receipt_exchange_rate() {
             exchange_rate,
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
             None,
@@ -689,13 +707,14 @@ async fn test_conversion_receipt_exchange_rate() {
 
 #[tokio::test]
 async fn test_search_receipts_no_filters() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create multiple receipts
     for i in 0..5 {
@@ -705,9 +724,9 @@ async fn test_search_receipts_no_filters() {
                 payment_id,
                 Decimal::new(100 + i, 0),
                 "USD".to_string(),
-                "sender".to_string(),
+                wallet.clone(),
                 "recipient".to_string(),
-                Blockchain::Ethereum,
+                Blockchain::ETHEREUM,
                 None,
                 None,
             )
@@ -728,7 +747,7 @@ async fn test_search_receipts_no_filters() {
         page_size: 10,
     };
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     if let Err(ref e) = result {
         eprintln!("Search error: {:?}", e);
@@ -745,13 +764,14 @@ async fn test_search_receipts_no_filters() {
 
 #[tokio::test]
 async fn test_search_receipts_by_type() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create different types of receipts
     let payment_id = Uuid::new_v4();
@@ -760,9 +780,9 @@ async fn test_search_receipts_by_type() {
             payment_id,
             Decimal::new(100, 0),
             "USD".to_string(),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -776,9 +796,9 @@ async fn test_search_receipts_by_type() {
             Decimal::new(10, 0),
             "SOL".to_string(),
             Some(Decimal::new(100, 0)),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
         )
@@ -795,7 +815,7 @@ async fn test_search_receipts_by_type() {
 
     let pagination = api::Pagination::default();
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts");
     let results = result.unwrap();
@@ -808,13 +828,14 @@ async fn test_search_receipts_by_type() {
 
 #[tokio::test]
 async fn test_search_receipts_by_asset() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create receipts with different assets
     let payment_id = Uuid::new_v4();
@@ -823,9 +844,9 @@ async fn test_search_receipts_by_asset() {
             payment_id,
             Decimal::new(100, 0),
             "USD".to_string(),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -839,9 +860,9 @@ async fn test_search_receipts_by_asset() {
             Decimal::new(10, 0),
             "SOL".to_string(),
             Some(Decimal::new(100, 0)),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
         )
@@ -858,7 +879,7 @@ async fn test_search_receipts_by_asset() {
 
     let pagination = api::Pagination::default();
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts");
     let results = result.unwrap();
@@ -872,13 +893,14 @@ async fn test_search_receipts_by_asset() {
 #[tokio::test]
 #[ignore] // TODO: Fix DateTime serialization for date range filters
 async fn test_search_receipts_by_date_range() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     let now = chrono::Utc::now();
     let one_hour_ago = now - chrono::Duration::hours(1);
@@ -891,9 +913,9 @@ async fn test_search_receipts_by_date_range() {
             payment_id,
             Decimal::new(100, 0),
             "USD".to_string(),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -910,7 +932,7 @@ async fn test_search_receipts_by_date_range() {
 
     let pagination = api::Pagination::default();
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     if let Err(ref e) = result {
         eprintln!("Search error in date range test: {:?}", e);
@@ -929,7 +951,7 @@ async fn test_search_receipts_by_date_range() {
         end_date: Some(two_hours_ago),
     };
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts");
     // This might have old receipts from other tests, so we just verify it doesn't error
@@ -937,13 +959,14 @@ async fn test_search_receipts_by_date_range() {
 
 #[tokio::test]
 async fn test_search_receipts_pagination() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create 15 receipts
     for i in 0..15 {
@@ -953,9 +976,9 @@ async fn test_search_receipts_pagination() {
                 payment_id,
                 Decimal::new(100 + i, 0),
                 "USD".to_string(),
-                "sender".to_string(),
+                wallet.clone(),
                 "recipient".to_string(),
-                Blockchain::Ethereum,
+                Blockchain::ETHEREUM,
                 None,
                 None,
             )
@@ -976,7 +999,7 @@ async fn test_search_receipts_pagination() {
         page_size: 5,
     };
 
-    let result = service.search_receipts(filters.clone(), pagination).await;
+    let result = service.search_receipts(user_id, filters.clone(), pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts page 0");
     let page0 = result.unwrap();
@@ -993,7 +1016,7 @@ async fn test_search_receipts_pagination() {
         page_size: 5,
     };
 
-    let result = service.search_receipts(filters.clone(), pagination).await;
+    let result = service.search_receipts(user_id, filters.clone(), pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts page 1");
     let page1 = result.unwrap();
@@ -1013,13 +1036,14 @@ async fn test_search_receipts_pagination() {
 #[tokio::test]
 #[ignore] // TODO: Fix DateTime serialization for date range filters
 async fn test_search_receipts_combined_filters() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     let now = chrono::Utc::now();
     let one_hour_ago = now - chrono::Duration::hours(1);
@@ -1034,9 +1058,9 @@ async fn test_search_receipts_combined_filters() {
             Decimal::new(1, 0),
             "SOL".to_string(),
             Decimal::new(100, 0),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
             None,
@@ -1054,7 +1078,7 @@ async fn test_search_receipts_combined_filters() {
 
     let pagination = api::Pagination::default();
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     assert!(result.is_ok(), "Failed to search receipts with combined filters");
     let results = result.unwrap();
@@ -1073,13 +1097,14 @@ async fn test_search_receipts_combined_filters() {
 
 #[tokio::test]
 async fn test_search_receipts_empty_results() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, _) = create_test_user_with_wallet(&db).await;
 
     // Search for a non-existent asset
     let filters = api::ReceiptSearchFilters {
@@ -1091,7 +1116,7 @@ async fn test_search_receipts_empty_results() {
 
     let pagination = api::Pagination::default();
 
-    let result = service.search_receipts(filters, pagination).await;
+    let result = service.search_receipts(user_id, filters, pagination).await;
 
     assert!(result.is_ok(), "Search should succeed even with no results");
     let results = result.unwrap();
@@ -1104,13 +1129,14 @@ async fn test_search_receipts_empty_results() {
 
 #[tokio::test]
 async fn test_export_receipt_pdf() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create a receipt
     let payment_id = Uuid::new_v4();
@@ -1119,9 +1145,9 @@ async fn test_export_receipt_pdf() {
             payment_id,
             Decimal::new(100, 0),
             "USD".to_string(),
-            "sender_address".to_string(),
+            wallet.clone(),
             "recipient_address".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             Some(Decimal::new(5, 2)),
             Some(Decimal::new(10, 2)),
         )
@@ -1129,7 +1155,7 @@ async fn test_export_receipt_pdf() {
         .expect("Failed to create receipt");
 
     // Export as PDF
-    let result = service.export_receipt_pdf(receipt.id).await;
+    let result = service.export_receipt_pdf(user_id, receipt.id).await;
 
     assert!(result.is_ok(), "Failed to export PDF: {:?}", result.err());
     let pdf_bytes = result.unwrap();
@@ -1143,13 +1169,14 @@ async fn test_export_receipt_pdf() {
 
 #[tokio::test]
 async fn test_export_receipts_csv() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create multiple receipts
     for i in 0..3 {
@@ -1159,9 +1186,9 @@ async fn test_export_receipts_csv() {
                 payment_id,
                 Decimal::new(100 + i, 0),
                 "USD".to_string(),
-                "sender".to_string(),
+                wallet.clone(),
                 "recipient".to_string(),
-                Blockchain::Ethereum,
+                Blockchain::ETHEREUM,
                 None,
                 None,
             )
@@ -1177,7 +1204,7 @@ async fn test_export_receipts_csv() {
         end_date: None,
     };
 
-    let result = service.export_receipts_csv(filters).await;
+    let result = service.export_receipts_csv(user_id, filters).await;
 
     assert!(result.is_ok(), "Failed to export CSV: {:?}", result.err());
     let csv_bytes = result.unwrap();
@@ -1199,13 +1226,14 @@ async fn test_export_receipts_csv() {
 
 #[tokio::test]
 async fn test_export_csv_with_filters() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, wallet) = create_test_user_with_wallet(&db).await;
 
     // Create receipts of different types
     let payment_id = Uuid::new_v4();
@@ -1214,9 +1242,9 @@ async fn test_export_csv_with_filters() {
             payment_id,
             Decimal::new(100, 0),
             "USD".to_string(),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -1230,9 +1258,9 @@ async fn test_export_csv_with_filters() {
             Decimal::new(10, 0),
             "SOL".to_string(),
             Some(Decimal::new(100, 0)),
-            "sender".to_string(),
+            wallet.clone(),
             "recipient".to_string(),
-            Blockchain::Solana,
+            Blockchain::SOLANA,
             None,
             None,
         )
@@ -1247,7 +1275,7 @@ async fn test_export_csv_with_filters() {
         end_date: None,
     };
 
-    let result = service.export_receipts_csv(filters).await;
+    let result = service.export_receipts_csv(user_id, filters).await;
 
     assert!(result.is_ok(), "Failed to export filtered CSV");
     let csv_bytes = result.unwrap();
@@ -1263,29 +1291,81 @@ async fn test_export_csv_with_filters() {
 
 #[tokio::test]
 async fn test_export_pdf_nonexistent_receipt() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, _) = create_test_user_with_wallet(&db).await;
 
     let nonexistent_id = Uuid::new_v4();
-    let result = service.export_receipt_pdf(nonexistent_id).await;
+    let result = service.export_receipt_pdf(user_id, nonexistent_id).await;
 
     assert!(result.is_err(), "Should fail for nonexistent receipt");
 }
 
+#[tokio::test]
+async fn test_receipts_are_scoped_to_their_parties() {
+    let (service, db) = match create_test_service().await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Skipping test - database not available: {}", e);
+            return;
+        }
+    };
+    let (owner_id, wallet) = create_test_user_with_wallet(&db).await;
+    let (other_id, _) = create_test_user_with_wallet(&db).await;
+
+    let receipt = service
+        .generate_payment_receipt(
+            Uuid::new_v4(),
+            Decimal::new(100, 0),
+            "USD".to_string(),
+            "someone_else".to_string(),
+            wallet,
+            Blockchain::ETHEREUM,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create receipt");
+
+    // The recipient sees the receipt
+    let owned = service
+        .search_receipts(owner_id, api::ReceiptSearchFilters::default(), api::Pagination::default())
+        .await
+        .expect("Failed to search receipts");
+    assert!(owned.receipts.iter().any(|r| r.id == receipt.id));
+    assert!(service.export_receipt_pdf(owner_id, receipt.id).await.is_ok());
+
+    // Nobody else does
+    let others = service
+        .search_receipts(other_id, api::ReceiptSearchFilters::default(), api::Pagination::default())
+        .await
+        .expect("Failed to search receipts");
+    assert!(others.receipts.iter().all(|r| r.id != receipt.id));
+    assert!(matches!(
+        service.export_receipt_pdf(other_id, receipt.id).await,
+        Err(shared::Error::Unauthorized)
+    ));
+    assert!(matches!(
+        service.export_signed_receipt(other_id, receipt.id).await,
+        Err(shared::Error::Unauthorized)
+    ));
+}
+
 #[tokio::test]
 async fn test_export_csv_empty_results() {
-    let (service, _db) = match create_test_service().await {
+    let (service, db) = match create_test_service().await {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Skipping test - database not available: {}", e);
             return;
         }
     };
+    let (user_id, _) = create_test_user_with_wallet(&db).await;
 
     // Export with filter that matches nothing
     let filters = api::ReceiptSearchFilters {
@@ -1295,7 +1375,7 @@ async fn test_export_csv_empty_results() {
         end_date: None,
     };
 
-    let result = service.export_receipts_csv(filters).await;
+    let result = service.export_receipts_csv(user_id, filters).await;
 
     assert!(result.is_ok(), "Should succeed even with no results");
     let csv_bytes = result.unwrap();
@@ -1327,7 +1407,7 @@ async fn test_archive_old_receipts() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -1464,7 +1544,7 @@ async fn test_cannot_delete_recent_receipt() {
             "USD".to_string(),
             "sender".to_string(),
             "recipient".to_string(),
-            Blockchain::Ethereum,
+            Blockchain::ETHEREUM,
             None,
             None,
         )
@@ -12,6 +12,17 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::Utc;
+use api::ProviderIdentity;
+use solana_sdk::signer::keypair::keypair_from_seed;
+
+/// Sign an update as its source node, with a key derived from the node ID so
+/// repeated updates from one provider match its pinned key
+fn sign_update(update: &mut api::mesh_types::PriceUpdate) {
+    let node_id = update.source_node_id.as_bytes();
+    let seed = [node_id.as_slice(), node_id.as_slice()].concat();
+    let keypair = keypair_from_seed(&seed).expect("32-byte seed");
+    ProviderIdentity::from_keypair(keypair).sign(update);
+}
 
 /// Helper to create a test Redis connection
 async fn create_test_redis() -> redis::aio::ConnectionManager {
@@ -65,13 +76,17 @@ fn create_test_price_update(source_node_id: Uuid) -> api::mesh_types::PriceUpdat
         },
     );
     
-    api::mesh_types::PriceUpdate {
+    let mut update = api::mesh_types::PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id,
         timestamp: Utc::now(),
         prices,
         ttl: 10,
-    }
+        signer: None,
+        signature: None,
+    };
+    sign_update(&mut update);
+    update
 }
 
 // Feature: p2p-mesh-price-distribution, Property 29: Data Persistence After Provider Disconnect
@@ -198,13 +213,16 @@ async fn prop_provider_reconnection_recovery() {
         },
     );
     
-    let reconnect_update = api::mesh_types::PriceUpdate {
+    let mut reconnect_update = api::mesh_types::PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id: provider_id,
         timestamp: Utc::now(),
         prices: reconnect_prices,
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut reconnect_update);
     
     // This should succeed - provider reconnection recovery
     // The system should accept and process updates from the provider that was previously offline
@@ -437,29 +455,38 @@ async fn prop_multiple_providers_disconnect_preserves_all_data() {
     );
     
     // Send updates from all providers
-    let update1 = api::mesh_types::PriceUpdate {
+    let mut update1 = api::mesh_types::PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id: provider1_id,
         timestamp: Utc::now(),
         prices: prices1,
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut update1);
     
-    let update2 = api::mesh_types::PriceUpdate {
+    let mut update2 = api::mesh_types::PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id: provider2_id,
         timestamp: Utc::now(),
         prices: prices2,
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut update2);
     
-    let update3 = api::mesh_types::PriceUpdate {
+    let mut update3 = api::mesh_types::PriceUpdate {
         message_id: Uuid::new_v4(),
         source_node_id: provider3_id,
         timestamp: Utc::now(),
         prices: prices3,
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut update3);
     
     mesh_service
         .handle_price_update(update1, provider1_id.to_string())
@@ -9,6 +9,17 @@ use database::create_pool;
 use proximity::PeerConnectionManager;
 use std::collections::HashMap;
 use std::sync::Arc;
+use api::ProviderIdentity;
+use solana_sdk::signer::keypair::keypair_from_seed;
+
+/// Sign an update as its source node, with a key derived from the node ID so
+/// repeated updates from one provider match its pinned key
+fn sign_update(update: &mut PriceUpdate) {
+    let node_id = update.source_node_id.as_bytes();
+    let seed = [node_id.as_slice(), node_id.as_slice()].concat();
+    let keypair = keypair_from_seed(&seed).expect("32-byte seed");
+    ProviderIdentity::from_keypair(keypair).sign(update);
+}
 
 /// Helper to create a test Redis connection
 async fn create_test_redis() -> redis::aio::ConnectionManager {
@@ -167,7 +178,7 @@ async fn test_provider_reconnection_clears_offline_status() {
     
     // Simulate a provider reconnecting by sending a price update
     let provider_id = uuid::Uuid::new_v4();
-    let price_update = PriceUpdate {
+    let mut price_update = PriceUpdate {
         message_id: uuid::Uuid::new_v4(),
         source_node_id: provider_id,
         timestamp: chrono::Utc::now(),
@@ -185,7 +196,10 @@ async fn test_provider_reconnection_clears_offline_status() {
             map
         },
         ttl: 10,
+        signer: None,
+        signature: None,
     };
+    sign_update(&mut price_update);
     
     // Process the update - this verifies the service accepts updates from reconnected providers
     mesh_service
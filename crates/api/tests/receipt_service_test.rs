@@ -26,8 +26,8 @@ async fn create_test_service() -> ReceiptService {
     let blockchain_client = Arc::new(
         MultiChainClient::new()
             .with_solana("https://api.mainnet-beta.solana.com".to_string(), None)
-            .with_ethereum("https://eth.llamarpc.com".to_string(), None)
-            .with_polygon("https://polygon-rpc.com".to_string(), None),
+            .with_evm(&Blockchain::ETHEREUM, "https://eth.llamarpc.com".to_string(), None).unwrap()
+            .with_evm(&Blockchain::POLYGON, "https://polygon-rpc.com".to_string(), None).unwrap(),
     );
 
     ReceiptService::new(db, blockchain_client)
@@ -47,7 +47,7 @@ async fn test_create_receipt_for_payment() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let result = service.create_receipt(data.clone()).await;
@@ -59,8 +59,8 @@ async fn test_create_receipt_for_payment() {
     assert_eq!(receipt.currency, data.currency);
     assert_eq!(receipt.sender, data.sender);
     assert_eq!(receipt.recipient, data.recipient);
-    assert_eq!(receipt.blockchain, Blockchain::Ethereum);
-    assert!(!receipt.transaction_hash.is_empty());
+    assert_eq!(receipt.blockchain, Blockchain::ETHEREUM);
+    assert!(receipt.transaction_hash.as_ref().is_some_and(|h| !h.is_empty()));
     assert_eq!(receipt.verification_status, VerificationStatus::Pending);
 }
 
@@ -78,7 +78,7 @@ async fn test_create_receipt_for_trade() {
         currency: "SOL".to_string(),
         sender: "DYw8jCTfwHNRJhhmFcbXvVDTqWMEVFBX6ZKUmG5CNSKK".to_string(),
         recipient: "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
-        blockchain: Blockchain::Solana,
+        blockchain: Blockchain::SOLANA,
     };
 
     let result = service.create_receipt(data.clone()).await;
@@ -87,7 +87,7 @@ async fn test_create_receipt_for_trade() {
     let receipt = result.unwrap();
     assert_eq!(receipt.trade_id, Some(trade_id));
     assert_eq!(receipt.amount, data.amount);
-    assert_eq!(receipt.blockchain, Blockchain::Solana);
+    assert_eq!(receipt.blockchain, Blockchain::SOLANA);
 }
 
 #[tokio::test]
@@ -130,7 +130,7 @@ async fn test_create_receipt_for_conversion() {
         currency: "USDC".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Polygon,
+        blockchain: Blockchain::POLYGON,
     };
 
     let result = service.create_receipt(data.clone()).await;
@@ -138,7 +138,7 @@ async fn test_create_receipt_for_conversion() {
 
     let receipt = result.unwrap();
     assert_eq!(receipt.conversion_id, Some(conversion_id));
-    assert_eq!(receipt.blockchain, Blockchain::Polygon);
+    assert_eq!(receipt.blockchain, Blockchain::POLYGON);
 }
 
 #[tokio::test]
@@ -156,7 +156,7 @@ async fn test_get_receipt_by_id() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -186,7 +186,7 @@ async fn test_get_receipt_by_payment_id() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -218,7 +218,7 @@ async fn test_get_receipt_by_trade_id() {
         currency: "ETH".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -276,7 +276,7 @@ async fn test_get_receipt_by_conversion_id() {
         currency: "USDC".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Polygon,
+        blockchain: Blockchain::POLYGON,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -316,15 +316,15 @@ async fn test_receipt_has_transaction_hash() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let receipt = service.create_receipt(data).await.unwrap();
 
     // Verify transaction hash is not empty
-    assert!(!receipt.transaction_hash.is_empty());
+    assert!(receipt.transaction_hash.as_ref().is_some_and(|h| !h.is_empty()));
     // Verify it has some reasonable format
-    assert!(receipt.transaction_hash.len() > 10);
+    assert!(receipt.transaction_hash.as_ref().unwrap().len() > 10);
 }
 
 #[tokio::test]
@@ -341,7 +341,7 @@ async fn test_receipt_links_to_source_transaction() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let receipt = service.create_receipt(data).await.unwrap();
@@ -366,7 +366,7 @@ async fn test_receipt_stored_in_database() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -399,7 +399,7 @@ async fn test_multiple_receipts_for_different_blockchains() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let eth_receipt = service.create_receipt(eth_data).await.unwrap();
@@ -414,7 +414,7 @@ async fn test_multiple_receipts_for_different_blockchains() {
         currency: "SOL".to_string(),
         sender: "DYw8jCTfwHNRJhhmFcbXvVDTqWMEVFBX6ZKUmG5CNSKK".to_string(),
         recipient: "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
-        blockchain: Blockchain::Solana,
+        blockchain: Blockchain::SOLANA,
     };
 
     let sol_receipt = service.create_receipt(sol_data).await.unwrap();
@@ -429,7 +429,7 @@ async fn test_multiple_receipts_for_different_blockchains() {
         currency: "MATIC".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Polygon,
+        blockchain: Blockchain::POLYGON,
     };
 
     let poly_receipt = service.create_receipt(poly_data).await.unwrap();
@@ -439,9 +439,9 @@ async fn test_multiple_receipts_for_different_blockchains() {
     assert_ne!(eth_receipt.id, poly_receipt.id);
     assert_ne!(sol_receipt.id, poly_receipt.id);
 
-    assert_eq!(eth_receipt.blockchain, Blockchain::Ethereum);
-    assert_eq!(sol_receipt.blockchain, Blockchain::Solana);
-    assert_eq!(poly_receipt.blockchain, Blockchain::Polygon);
+    assert_eq!(eth_receipt.blockchain, Blockchain::ETHEREUM);
+    assert_eq!(sol_receipt.blockchain, Blockchain::SOLANA);
+    assert_eq!(poly_receipt.blockchain, Blockchain::POLYGON);
 }
 
 #[tokio::test]
@@ -458,7 +458,7 @@ async fn test_verify_receipt_success() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -488,7 +488,7 @@ async fn test_verify_receipt_already_confirmed() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -518,11 +518,11 @@ async fn test_verify_receipt_for_solana() {
         currency: "SOL".to_string(),
         sender: "DYw8jCTfwHNRJhhmFcbXvVDTqWMEVFBX6ZKUmG5CNSKK".to_string(),
         recipient: "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
-        blockchain: Blockchain::Solana,
+        blockchain: Blockchain::SOLANA,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
-    assert_eq!(created_receipt.blockchain, Blockchain::Solana);
+    assert_eq!(created_receipt.blockchain, Blockchain::SOLANA);
 
     // Verify the receipt
     let result = service.verify_receipt(created_receipt.id).await;
@@ -546,11 +546,11 @@ async fn test_verify_receipt_for_polygon() {
         currency: "MATIC".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Polygon,
+        blockchain: Blockchain::POLYGON,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
-    assert_eq!(created_receipt.blockchain, Blockchain::Polygon);
+    assert_eq!(created_receipt.blockchain, Blockchain::POLYGON);
 
     // Verify the receipt
     let result = service.verify_receipt(created_receipt.id).await;
@@ -583,7 +583,7 @@ async fn test_verify_receipt_updates_database() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
@@ -611,7 +611,7 @@ async fn test_verify_receipt_returns_verification_status() {
         currency: "USD".to_string(),
         sender: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
         recipient: "0x8ba1f109551bD432803012645Ac136ddd64DBA72".to_string(),
-        blockchain: Blockchain::Ethereum,
+        blockchain: Blockchain::ETHEREUM,
     };
 
     let created_receipt = service.create_receipt(data).await.unwrap();
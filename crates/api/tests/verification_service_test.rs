@@ -1,4 +1,5 @@
-use api::{VerificationService, VerificationLevel};
+use api::{VerificationService, VerificationLevel, WalletVerificationError};
+use solana_sdk::signature::{Keypair, Signer};
 use database::{create_pool, run_migrations};
 use uuid::Uuid;
 
@@ -35,20 +36,37 @@ async fn create_test_user(db: &database::DbPool) -> Uuid {
     user_id
 }
 
+async fn issue_signed_challenge(
+    service: &VerificationService,
+    user_id: Uuid,
+    keypair: &Keypair,
+) -> (Uuid, String) {
+    let challenge = service
+        .create_wallet_challenge(user_id, keypair.pubkey().to_string(), "Solana".to_string())
+        .await
+        .expect("Failed to create challenge");
+    let signature = keypair.sign_message(challenge.message.as_bytes()).to_string();
+
+    (challenge.id, signature)
+}
+
 #[tokio::test]
 #[ignore] // Requires database
 async fn test_verify_wallet() {
     let db = setup_test_db().await;
     let service = VerificationService::new(db.clone());
     let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
     
+    let (challenge_id, signature) = issue_signed_challenge(&service, user_id, &keypair).await;
+
     let result = service
         .verify_wallet(
             user_id,
-            "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string(),
-            "Ethereum".to_string(),
-            "Sign this message to verify ownership".to_string(),
-            "0xabcdef...".to_string(),
+            keypair.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge_id,
+            signature,
         )
         .await;
     
@@ -65,38 +83,164 @@ async fn test_is_wallet_verified() {
     let db = setup_test_db().await;
     let service = VerificationService::new(db.clone());
     let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
     
-    let wallet_address = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+    let wallet_address = keypair.pubkey().to_string();
     
     // Initially not verified
     let is_verified = service
-        .is_wallet_verified(user_id, wallet_address, "Ethereum")
+        .is_wallet_verified(user_id, &wallet_address, "Solana")
         .await
         .expect("Failed to check verification");
     
     assert!(!is_verified);
     
     // Verify the wallet
+    let (challenge_id, signature) = issue_signed_challenge(&service, user_id, &keypair).await;
     service
         .verify_wallet(
             user_id,
-            wallet_address.to_string(),
-            "Ethereum".to_string(),
-            "Challenge".to_string(),
-            "Signature".to_string(),
+            wallet_address.clone(),
+            "Solana".to_string(),
+            challenge_id,
+            signature,
         )
         .await
         .expect("Failed to verify wallet");
     
     // Now should be verified
     let is_verified = service
-        .is_wallet_verified(user_id, wallet_address, "Ethereum")
+        .is_wallet_verified(user_id, &wallet_address, "Solana")
         .await
         .expect("Failed to check verification");
     
     assert!(is_verified);
 }
 
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_verify_wallet_rejects_forged_signature() {
+    let db = setup_test_db().await;
+    let service = VerificationService::new(db.clone());
+    let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
+
+    let challenge = service
+        .create_wallet_challenge(user_id, keypair.pubkey().to_string(), "Solana".to_string())
+        .await
+        .expect("Failed to create challenge");
+    let forged = Keypair::new()
+        .sign_message(challenge.message.as_bytes())
+        .to_string();
+
+    let result = service
+        .verify_wallet(
+            user_id,
+            keypair.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge.id,
+            forged,
+        )
+        .await;
+
+    assert!(matches!(result, Err(WalletVerificationError::InvalidSignature(_))));
+    assert!(!service
+        .is_wallet_verified(user_id, &keypair.pubkey().to_string(), "Solana")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_verify_wallet_rejects_replayed_challenge() {
+    let db = setup_test_db().await;
+    let service = VerificationService::new(db.clone());
+    let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
+
+    let (challenge_id, signature) = issue_signed_challenge(&service, user_id, &keypair).await;
+
+    service
+        .verify_wallet(
+            user_id,
+            keypair.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge_id,
+            signature.clone(),
+        )
+        .await
+        .expect("First verification should succeed");
+
+    let replay = service
+        .verify_wallet(
+            user_id,
+            keypair.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge_id,
+            signature,
+        )
+        .await;
+
+    assert!(matches!(replay, Err(WalletVerificationError::ChallengeReplayed)));
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_verify_wallet_rejects_expired_challenge() {
+    let db = setup_test_db().await;
+    let service = VerificationService::new(db.clone());
+    let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
+
+    let (challenge_id, signature) = issue_signed_challenge(&service, user_id, &keypair).await;
+
+    db.get()
+        .await
+        .unwrap()
+        .execute(
+            "UPDATE wallet_verification_challenges SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1",
+            &[&challenge_id],
+        )
+        .await
+        .unwrap();
+
+    let result = service
+        .verify_wallet(
+            user_id,
+            keypair.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge_id,
+            signature,
+        )
+        .await;
+
+    assert!(matches!(result, Err(WalletVerificationError::ChallengeExpired)));
+}
+
+#[tokio::test]
+#[ignore] // Requires database
+async fn test_verify_wallet_rejects_challenge_for_other_wallet() {
+    let db = setup_test_db().await;
+    let service = VerificationService::new(db.clone());
+    let user_id = create_test_user(&db).await;
+    let keypair = Keypair::new();
+    let other = Keypair::new();
+
+    let (challenge_id, _) = issue_signed_challenge(&service, user_id, &keypair).await;
+
+    let result = service
+        .verify_wallet(
+            user_id,
+            other.pubkey().to_string(),
+            "Solana".to_string(),
+            challenge_id,
+            other.sign_message(b"anything").to_string(),
+        )
+        .await;
+
+    assert!(matches!(result, Err(WalletVerificationError::ChallengeMismatch)));
+}
+
 #[tokio::test]
 #[ignore] // Requires database
 async fn test_get_verification_level() {
@@ -38,12 +38,14 @@ async fn test_broadcast_price_update() {
 #[tokio::test]
 async fn test_broadcast_trade_executed() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let trade_id = uuid::Uuid::new_v4();
     
     // Broadcast a trade execution
     ws_service.broadcast_trade_executed(
+        user_id,
         trade_id,
         "ETH".to_string(),
         "BUY".to_string(),
@@ -69,12 +71,14 @@ async fn test_broadcast_trade_executed() {
 #[tokio::test]
 async fn test_broadcast_trim_executed() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let trim_id = uuid::Uuid::new_v4();
     
     // Broadcast a trim execution
     ws_service.broadcast_trim_executed(
+        user_id,
         trim_id,
         "BTC".to_string(),
         "0.25".to_string(),
@@ -100,12 +104,14 @@ async fn test_broadcast_trim_executed() {
 #[tokio::test]
 async fn test_broadcast_benchmark_triggered() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let benchmark_id = uuid::Uuid::new_v4();
     
     // Broadcast a benchmark trigger
     ws_service.broadcast_benchmark_triggered(
+        user_id,
         benchmark_id,
         "SOL".to_string(),
         "150.00".to_string(),
@@ -131,10 +137,12 @@ async fn test_broadcast_benchmark_triggered() {
 #[tokio::test]
 async fn test_broadcast_portfolio_update() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     // Broadcast a portfolio update
     ws_service.broadcast_portfolio_update(
+        user_id,
         "50000.00".to_string(),
         "3.5".to_string(),
     );
@@ -154,12 +162,14 @@ async fn test_broadcast_portfolio_update() {
 #[tokio::test]
 async fn test_broadcast_conversion_completed() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let conversion_id = uuid::Uuid::new_v4();
     
     // Broadcast a conversion completion
     ws_service.broadcast_conversion_completed(
+        user_id,
         conversion_id,
         "USDC".to_string(),
         "SOL".to_string(),
@@ -249,6 +259,7 @@ async fn test_late_subscriber_misses_old_updates() {
 #[tokio::test]
 async fn test_broadcast_without_subscribers() {
     let ws_service = WebSocketService::new();
+    let user_id = uuid::Uuid::new_v4();
     
     // Should not panic when broadcasting without subscribers
     ws_service.broadcast_price_update(
@@ -259,6 +270,7 @@ async fn test_broadcast_without_subscribers() {
     );
     
     ws_service.broadcast_trade_executed(
+        user_id,
         uuid::Uuid::new_v4(),
         "ETH".to_string(),
         "BUY".to_string(),
@@ -299,7 +311,8 @@ async fn test_update_serialization() {
 #[tokio::test]
 async fn test_broadcast_stealth_payment_detected() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let stealth_address = "StealthAddr123456789".to_string();
@@ -309,6 +322,7 @@ async fn test_broadcast_stealth_payment_detected() {
     
     // Broadcast a stealth payment detection
     ws_service.broadcast_stealth_payment_detected(
+        user_id,
         payment_id,
         stealth_address.clone(),
         1_000_000,
@@ -347,13 +361,15 @@ async fn test_broadcast_stealth_payment_detected() {
 #[tokio::test]
 async fn test_broadcast_payment_queued() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let stealth_address = "StealthAddr123456789".to_string();
     
     // Broadcast a payment queued event
     ws_service.broadcast_payment_queued(
+        user_id,
         payment_id,
         stealth_address.clone(),
         500_000,
@@ -380,7 +396,8 @@ async fn test_broadcast_payment_queued() {
 #[tokio::test]
 async fn test_broadcast_payment_settled() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let stealth_address = "StealthAddr123456789".to_string();
@@ -388,6 +405,7 @@ async fn test_broadcast_payment_settled() {
     
     // Broadcast a payment settled event
     ws_service.broadcast_payment_settled(
+        user_id,
         payment_id,
         stealth_address.clone(),
         750_000,
@@ -417,7 +435,8 @@ async fn test_broadcast_payment_settled() {
 #[tokio::test]
 async fn test_broadcast_payment_failed() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let stealth_address = "StealthAddr123456789".to_string();
@@ -425,6 +444,7 @@ async fn test_broadcast_payment_failed() {
     
     // Broadcast a payment failed event
     ws_service.broadcast_payment_failed(
+        user_id,
         payment_id,
         stealth_address.clone(),
         250_000,
@@ -459,12 +479,14 @@ async fn test_stealth_payment_event_serialization() {
     use serde_json;
     
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     
     // Broadcast a payment queued event
     ws_service.broadcast_payment_queued(
+        user_id,
         payment_id,
         "StealthAddr123".to_string(),
         1_000_000,
@@ -484,19 +506,22 @@ async fn test_stealth_payment_event_serialization() {
 #[tokio::test]
 async fn test_multiple_stealth_events_in_sequence() {
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let stealth_address = "StealthAddr123".to_string();
     
     // Simulate payment lifecycle: queued -> settled
     ws_service.broadcast_payment_queued(
+        user_id,
         payment_id,
         stealth_address.clone(),
         1_000_000,
     );
     
     ws_service.broadcast_payment_settled(
+        user_id,
         payment_id,
         stealth_address.clone(),
         1_000_000,
@@ -523,13 +548,15 @@ async fn test_stealth_payment_detected_payload_format() {
     use serde_json;
     
     let ws_service = WebSocketService::new();
-    let mut rx = ws_service.subscribe();
+    let user_id = uuid::Uuid::new_v4();
+    let mut rx = ws_service.subscribe_user(user_id);
     
     let payment_id = uuid::Uuid::new_v4();
     let viewing_tag = [0x12, 0x34, 0x56, 0x78];
     
     // Broadcast stealth payment detection
     ws_service.broadcast_stealth_payment_detected(
+        user_id,
         payment_id,
         "StealthAddr".to_string(),
         2_000_000,
@@ -550,3 +577,112 @@ async fn test_stealth_payment_detected_payload_format() {
     assert!(json.contains("\"amount\":2000000"));
 }
 
+
+// Per-user routing and topic subscription tests
+
+#[tokio::test]
+async fn test_private_updates_reach_only_their_user() {
+    let ws_service = WebSocketService::new();
+    let owner = uuid::Uuid::new_v4();
+    let other = uuid::Uuid::new_v4();
+    let mut public_rx = ws_service.subscribe();
+    let mut owner_rx = ws_service.subscribe_user(owner);
+    let mut other_rx = ws_service.subscribe_user(other);
+    
+    ws_service.broadcast_payment_settled(
+        owner,
+        uuid::Uuid::new_v4(),
+        "stealth_addr".to_string(),
+        1_000_000,
+        "sig".to_string(),
+    );
+    
+    match owner_rx.recv().await.unwrap() {
+        api::DashboardUpdate::PaymentSettled { amount, .. } => assert_eq!(amount, 1_000_000),
+        _ => panic!("Expected PaymentSettled"),
+    }
+    assert!(other_rx.try_recv().is_err());
+    assert!(public_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_user_channel_released_after_last_connection() {
+    let ws_service = WebSocketService::new();
+    let user_id = uuid::Uuid::new_v4();
+    
+    let rx1 = ws_service.subscribe_user(user_id);
+    let rx2 = ws_service.subscribe_user(user_id);
+    assert_eq!(ws_service.user_connection_count(user_id), 2);
+    
+    drop(rx1);
+    ws_service.release_user(user_id);
+    assert_eq!(ws_service.user_connection_count(user_id), 1);
+    
+    drop(rx2);
+    ws_service.release_user(user_id);
+    assert_eq!(ws_service.user_connection_count(user_id), 0);
+    
+    // Updates for a user without connections are dropped quietly
+    ws_service.broadcast_portfolio_update(user_id, "1.00".to_string(), "0.0".to_string());
+}
+
+#[test]
+fn test_topic_round_trip() {
+    use api::Topic;
+    
+    assert_eq!("prices".parse::<Topic>().unwrap(), Topic::Prices);
+    assert_eq!("mesh_status".parse::<Topic>().unwrap(), Topic::MeshStatus);
+    assert_eq!("prices:sol".parse::<Topic>().unwrap(), Topic::AssetPrices("SOL".to_string()));
+    assert_eq!(Topic::AssetPrices("SOL".to_string()).to_string(), "prices:SOL");
+    
+    assert!("prices:".parse::<Topic>().is_err());
+    assert!("trades".parse::<Topic>().is_err());
+}
+
+#[test]
+fn test_subscriptions_filter_public_updates() {
+    use api::{DashboardUpdate, Subscriptions, Topic};
+    
+    let price = |asset: &str| DashboardUpdate::PriceUpdate {
+        asset: asset.to_string(),
+        blockchain: "Solana".to_string(),
+        price: "100.00".to_string(),
+        change_24h: "1.0".to_string(),
+        timestamp: 0,
+    };
+    let status = DashboardUpdate::MeshStatus {
+        active_providers: 1,
+        connected_peers: 3,
+        total_network_size: 4,
+        data_freshness: "Just now".to_string(),
+        extended_offline: false,
+        offline_duration_minutes: None,
+        timestamp: 0,
+    };
+    
+    let mut subscriptions = Subscriptions::initial();
+    assert!(subscriptions.wants(&price("ETH")));
+    assert!(!subscriptions.wants(&status));
+    
+    subscriptions.unsubscribe(&[Topic::Prices]);
+    subscriptions.subscribe([Topic::AssetPrices("SOL".to_string()), Topic::MeshStatus]);
+    assert!(subscriptions.wants(&price("SOL")));
+    assert!(!subscriptions.wants(&price("ETH")));
+    assert!(subscriptions.wants(&status));
+    assert_eq!(subscriptions.topic_names(), vec!["prices:SOL", "mesh_status"]);
+}
+
+#[test]
+fn test_client_message_parsing() {
+    use api::ClientMessage;
+    
+    let message: ClientMessage =
+        serde_json::from_str(r#"{"action":"subscribe","topics":["prices:SOL","mesh_status"]}"#).unwrap();
+    assert!(matches!(message, ClientMessage::Subscribe { topics } if topics.len() == 2));
+    
+    let message: ClientMessage =
+        serde_json::from_str(r#"{"action":"unsubscribe","topics":["prices"]}"#).unwrap();
+    assert!(matches!(message, ClientMessage::Unsubscribe { .. }));
+    
+    assert!(serde_json::from_str::<ClientMessage>(r#"{"type":"subscribe"}"#).is_err());
+}
@@ -0,0 +1,412 @@
+//! Registry of the blockchains the platform works with
+//!
+//! Chains are described by configuration rather than code. Each entry gives
+//! the chain's identifier, RPC endpoints, native token, explorer link and the
+//! confirmation depth after which its transactions are final. Solana,
+//! Ethereum, BSC and Polygon are built in; a JSON file can override them or
+//! add further EVM chains such as Arbitrum, Base, Optimism or Avalanche.
+
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+
+use crate::evm_client::EvmChain;
+
+/// Identifier of a chain in the [`ChainRegistry`]
+///
+/// Serializes as the chain id, e.g. `"Ethereum"` or `"BinanceSmartChain"`.
+/// Parse user input with [`ChainRegistry::resolve`] so unknown chains are
+/// rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Blockchain(Cow<'static, str>);
+
+impl Blockchain {
+    pub const SOLANA: Blockchain = Blockchain(Cow::Borrowed("Solana"));
+    pub const ETHEREUM: Blockchain = Blockchain(Cow::Borrowed("Ethereum"));
+    pub const BINANCE_SMART_CHAIN: Blockchain = Blockchain(Cow::Borrowed("BinanceSmartChain"));
+    pub const POLYGON: Blockchain = Blockchain(Cow::Borrowed("Polygon"));
+
+    /// Identifier for the chain with registry id `id`
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(Cow::Owned(id.into()))
+    }
+
+    /// Registry id of the chain
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_solana(&self) -> bool {
+        *self == Self::SOLANA
+    }
+}
+
+impl fmt::Display for Blockchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Virtual machine a chain runs, which decides the client and address format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChainKind {
+    Solana,
+    Evm,
+}
+
+impl ChainKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainKind::Solana => "SOLANA",
+            ChainKind::Evm => "EVM",
+        }
+    }
+}
+
+/// Token fees and native balances are paid in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeToken {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Configuration of a single chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Stable identifier, stored in the database and accepted by the API
+    pub id: String,
+    /// Human-readable name, e.g. `"Binance Smart Chain"`
+    pub name: String,
+    /// Lowercase short name used by market data APIs, e.g. `"bsc"`
+    pub slug: String,
+    pub kind: ChainKind,
+    /// EIP-155 chain id, required for EVM chains
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    /// RPC endpoints in order of preference: primary, then fallback
+    /// (empty: no client is configured for the chain)
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    pub native_token: NativeToken,
+    /// Transaction page with `{tx}` in place of the signature or hash
+    pub explorer_tx_url: String,
+    /// Blocks (slots on Solana) after which a transaction is treated as final
+    pub confirmations: u64,
+}
+
+impl ChainConfig {
+    pub fn blockchain(&self) -> Blockchain {
+        Blockchain::new(self.id.clone())
+    }
+
+    /// Chain parameters an EVM client needs; `None` for non-EVM chains
+    pub fn evm_chain(&self) -> Option<EvmChain> {
+        match (self.kind, self.chain_id) {
+            (ChainKind::Evm, Some(chain_id)) => Some(EvmChain::new(self.blockchain(), chain_id)),
+            _ => None,
+        }
+    }
+
+    /// Explorer link for a transaction
+    pub fn explorer_link(&self, tx: &str) -> String {
+        self.explorer_tx_url.replace("{tx}", tx)
+    }
+
+    /// Whether `name` is this chain's id, name or slug, ignoring case
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim();
+        [&self.id, &self.name, &self.slug]
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() || self.name.trim().is_empty() || self.slug.trim().is_empty() {
+            return Err(Error::Validation(
+                "Chain id, name and slug must not be empty".to_string(),
+            ));
+        }
+        if self.kind == ChainKind::Evm && self.chain_id.is_none() {
+            return Err(Error::Validation(format!(
+                "EVM chain {} is missing its chain_id",
+                self.id
+            )));
+        }
+        if !self.explorer_tx_url.contains("{tx}") {
+            return Err(Error::Validation(format!(
+                "Explorer URL of {} must contain {{tx}}",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The chains known to the platform, shared by every crate
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: Vec<ChainConfig>,
+}
+
+impl ChainRegistry {
+    /// Registry of exactly `chains`
+    ///
+    /// Ids, names, slugs and EVM chain ids must be unique across chains.
+    pub fn new(chains: Vec<ChainConfig>) -> Result<Self> {
+        for (index, chain) in chains.iter().enumerate() {
+            chain.validate()?;
+
+            for other in &chains[..index] {
+                if [&chain.id, &chain.name, &chain.slug]
+                    .iter()
+                    .any(|name| other.matches(name))
+                {
+                    return Err(Error::Validation(format!(
+                        "Chains {} and {} share a name",
+                        other.id, chain.id
+                    )));
+                }
+                if chain.chain_id.is_some() && chain.chain_id == other.chain_id {
+                    return Err(Error::Validation(format!(
+                        "Chains {} and {} share chain id {}",
+                        other.id,
+                        chain.id,
+                        chain.chain_id.unwrap_or_default()
+                    )));
+                }
+            }
+        }
+
+        Ok(Self { chains })
+    }
+
+    /// Built-in chains, overridden or extended by a JSON list of chains
+    ///
+    /// An entry whose id matches a built-in chain replaces it.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let configured: Vec<ChainConfig> = serde_json::from_str(json)
+            .map_err(|e| Error::Validation(format!("Invalid chain configuration: {}", e)))?;
+
+        let mut chains = builtin_chains();
+        for chain in configured {
+            match chains.iter_mut().find(|c| c.id.eq_ignore_ascii_case(&chain.id)) {
+                Some(existing) => *existing = chain,
+                None => chains.push(chain),
+            }
+        }
+
+        Self::new(chains)
+    }
+
+    /// Read the chain configuration file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            Error::Internal(format!(
+                "Failed to read chain configuration {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json(&json)
+    }
+
+    pub fn chains(&self) -> &[ChainConfig] {
+        &self.chains
+    }
+
+    pub fn get(&self, blockchain: &Blockchain) -> Option<&ChainConfig> {
+        self.chains.iter().find(|chain| chain.id == blockchain.id())
+    }
+
+    /// Configuration of `blockchain`, or an error if it is not registered
+    pub fn require(&self, blockchain: &Blockchain) -> Result<&ChainConfig> {
+        self.get(blockchain)
+            .ok_or_else(|| Error::Validation(format!("Unsupported blockchain: {}", blockchain)))
+    }
+
+    /// Find the chain whose id, name or slug is `name`, ignoring case
+    pub fn resolve(&self, name: &str) -> Result<Blockchain> {
+        self.chains
+            .iter()
+            .find(|chain| chain.matches(name))
+            .map(ChainConfig::blockchain)
+            .ok_or_else(|| Error::Validation(format!("Unsupported blockchain: {}", name)))
+    }
+
+    /// Find an EVM chain by its EIP-155 chain id
+    pub fn by_chain_id(&self, chain_id: u64) -> Option<&ChainConfig> {
+        self.chains
+            .iter()
+            .find(|chain| chain.chain_id == Some(chain_id))
+    }
+
+    /// Human-readable name of `blockchain`, or its id if it is not registered
+    pub fn display_name<'a>(&'a self, blockchain: &'a Blockchain) -> &'a str {
+        self.get(blockchain)
+            .map(|chain| chain.name.as_str())
+            .unwrap_or_else(|| blockchain.id())
+    }
+}
+
+impl Default for ChainRegistry {
+    /// The built-in chains, without RPC endpoints
+    fn default() -> Self {
+        Self::new(builtin_chains()).expect("built-in chains are valid")
+    }
+}
+
+fn builtin_chains() -> Vec<ChainConfig> {
+    fn chain(
+        blockchain: Blockchain,
+        name: &str,
+        slug: &str,
+        chain_id: Option<u64>,
+        native_token: (&str, u8),
+        explorer_tx_url: &str,
+        confirmations: u64,
+    ) -> ChainConfig {
+        ChainConfig {
+            id: blockchain.id().to_string(),
+            name: name.to_string(),
+            slug: slug.to_string(),
+            kind: if chain_id.is_some() {
+                ChainKind::Evm
+            } else {
+                ChainKind::Solana
+            },
+            chain_id,
+            rpc_urls: Vec::new(),
+            native_token: NativeToken {
+                symbol: native_token.0.to_string(),
+                decimals: native_token.1,
+            },
+            explorer_tx_url: explorer_tx_url.to_string(),
+            confirmations,
+        }
+    }
+
+    vec![
+        chain(Blockchain::SOLANA, "Solana", "solana", None, ("SOL", 9), "https://solscan.io/tx/{tx}", 32),
+        chain(Blockchain::ETHEREUM, "Ethereum", "ethereum", Some(1), ("ETH", 18), "https://etherscan.io/tx/{tx}", 12),
+        chain(
+            Blockchain::BINANCE_SMART_CHAIN,
+            "Binance Smart Chain",
+            "bsc",
+            Some(56),
+            ("BNB", 18),
+            "https://bscscan.com/tx/{tx}",
+            15,
+        ),
+        chain(Blockchain::POLYGON, "Polygon", "polygon", Some(137), ("POL", 18), "https://polygonscan.com/tx/{tx}", 128),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARBITRUM: &str = r#"[
+        {
+            "id": "Arbitrum",
+            "name": "Arbitrum One",
+            "slug": "arbitrum",
+            "kind": "EVM",
+            "chain_id": 42161,
+            "rpc_urls": ["https://arb1.arbitrum.io/rpc"],
+            "native_token": { "symbol": "ETH", "decimals": 18 },
+            "explorer_tx_url": "https://arbiscan.io/tx/{tx}",
+            "confirmations": 20
+        },
+        {
+            "id": "Ethereum",
+            "name": "Ethereum",
+            "slug": "ethereum",
+            "kind": "EVM",
+            "chain_id": 1,
+            "rpc_urls": ["https://eth.llamarpc.com", "https://rpc.ankr.com/eth"],
+            "native_token": { "symbol": "ETH", "decimals": 18 },
+            "explorer_tx_url": "https://etherscan.io/tx/{tx}",
+            "confirmations": 64
+        }
+    ]"#;
+
+    #[test]
+    fn test_builtin_chains() {
+        let registry = ChainRegistry::default();
+        assert_eq!(registry.chains().len(), 4);
+
+        let bsc = registry.get(&Blockchain::BINANCE_SMART_CHAIN).unwrap();
+        assert_eq!(bsc.name, "Binance Smart Chain");
+        assert_eq!(bsc.evm_chain().unwrap().chain_id(), 56);
+        assert!(registry.get(&Blockchain::SOLANA).unwrap().evm_chain().is_none());
+        assert_eq!(
+            registry.get(&Blockchain::SOLANA).unwrap().explorer_link("abc"),
+            "https://solscan.io/tx/abc"
+        );
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_chains() {
+        let registry = ChainRegistry::default();
+        for name in ["bsc", "BSC", "BinanceSmartChain", "binance smart chain"] {
+            assert_eq!(registry.resolve(name).unwrap(), Blockchain::BINANCE_SMART_CHAIN);
+        }
+        assert_eq!(registry.resolve("solana").unwrap(), Blockchain::SOLANA);
+        assert!(matches!(registry.resolve("dogecoin"), Err(Error::Validation(_))));
+        assert_eq!(registry.by_chain_id(137).unwrap().id, "Polygon");
+    }
+
+    #[test]
+    fn test_configured_chains_extend_and_override_builtins() {
+        let registry = ChainRegistry::from_json(ARBITRUM).unwrap();
+        assert_eq!(registry.chains().len(), 5);
+
+        let arbitrum = registry.resolve("arbitrum one").unwrap();
+        assert_eq!(arbitrum, Blockchain::new("Arbitrum"));
+        assert_eq!(registry.display_name(&arbitrum), "Arbitrum One");
+        assert_eq!(registry.by_chain_id(42161).unwrap().id, "Arbitrum");
+
+        let ethereum = registry.get(&Blockchain::ETHEREUM).unwrap();
+        assert_eq!(ethereum.rpc_urls.len(), 2);
+        assert_eq!(ethereum.confirmations, 64);
+    }
+
+    #[test]
+    fn test_example_configuration_loads() {
+        let registry =
+            ChainRegistry::load(concat!(env!("CARGO_MANIFEST_DIR"), "/chains.example.json")).unwrap();
+        assert_eq!(registry.chains().len(), 8);
+        assert_eq!(registry.resolve("base").unwrap(), Blockchain::new("Base"));
+    }
+
+    #[test]
+    fn test_invalid_configuration_rejected() {
+        // EVM chain without a chain id
+        let missing_id = ARBITRUM.replace("\"chain_id\": 42161,", "");
+        assert!(ChainRegistry::from_json(&missing_id).is_err());
+
+        // Reuses Polygon's chain id
+        let duplicate_id = ARBITRUM.replace("42161", "137");
+        assert!(ChainRegistry::from_json(&duplicate_id).is_err());
+
+        // Slug taken by a built-in chain
+        let duplicate_slug = ARBITRUM.replace("\"arbitrum\"", "\"bsc\"");
+        assert!(ChainRegistry::from_json(&duplicate_slug).is_err());
+
+        let no_placeholder = ARBITRUM.replace("arbiscan.io/tx/{tx}", "arbiscan.io");
+        assert!(ChainRegistry::from_json(&no_placeholder).is_err());
+    }
+
+    #[test]
+    fn test_blockchain_serializes_as_id() {
+        let json = serde_json::to_string(&Blockchain::BINANCE_SMART_CHAIN).unwrap();
+        assert_eq!(json, "\"BinanceSmartChain\"");
+        let parsed: Blockchain = serde_json::from_str("\"Polygon\"").unwrap();
+        assert_eq!(parsed, Blockchain::POLYGON);
+    }
+}
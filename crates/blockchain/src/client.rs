@@ -3,9 +3,11 @@ use shared::{Error, Result};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    hash::Hash,
     instruction::Instruction,
     pubkey::Pubkey,
     signature::Signature,
+    transaction::{Transaction, TransactionError},
 };
 use std::str::FromStr;
 use std::sync::Arc;
@@ -13,7 +15,10 @@ use tracing::{debug, error, info, warn};
 
 use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::retry::{retry_with_backoff, RetryConfig};
-use crate::types::{TokenAccount, WalletBalance};
+use crate::types::{ReceivedTransfer, TokenAccount, WalletBalance};
+
+/// SPL Memo program
+pub const MEMO_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 
 /// Stealth payment metadata found on-chain
 #[derive(Debug, Clone)]
@@ -54,16 +59,25 @@ fn create_stealth_metadata_instruction(
     metadata.extend_from_slice(&ephemeral_public_key.to_bytes());
     
     // Use SPL Memo program to store metadata on-chain
-    // Program ID for SPL Memo: MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr
-    let memo_program_id = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
-    
     Instruction {
-        program_id: memo_program_id,
+        program_id: MEMO_PROGRAM_ID,
         accounts: vec![],
         data: metadata,
     }
 }
 
+/// Text of every SPL Memo instruction in `transaction`
+fn memo_texts(transaction: &solana_sdk::transaction::VersionedTransaction) -> Vec<String> {
+    let keys = transaction.message.static_account_keys();
+    transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| keys.get(instruction.program_id_index as usize) == Some(&MEMO_PROGRAM_ID))
+        .map(|instruction| String::from_utf8_lossy(&instruction.data).into_owned())
+        .collect()
+}
+
 /// Parse stealth metadata from instruction data
 /// 
 /// Returns Some(ParsedStealthMetadata) if the data contains valid stealth metadata,
@@ -181,6 +195,25 @@ impl SolanaClient {
         }
     }
 
+    /// Wrap an existing RPC client without a fallback endpoint
+    ///
+    /// Useful for pointing the client at a custom sender such as
+    /// `RpcClient::new_mock` in tests.
+    pub fn from_rpc_client(primary_client: RpcClient) -> Self {
+        let primary_circuit_breaker = Arc::new(CircuitBreaker::new(
+            format!("primary-rpc-{}", primary_client.url()),
+            CircuitBreakerConfig::default(),
+        ));
+
+        Self {
+            primary_client,
+            fallback_client: None,
+            primary_circuit_breaker,
+            fallback_circuit_breaker: None,
+            retry_config: RetryConfig::default(),
+        }
+    }
+
     /// Validate a Solana wallet address format
     pub fn validate_address(&self, address: &str) -> Result<Pubkey> {
         Pubkey::from_str(address).map_err(|e| {
@@ -439,6 +472,271 @@ impl SolanaClient {
         Ok(())
     }
 
+    /// Fetch the latest blockhash for signing new transactions
+    pub async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.execute_with_fallback("get_latest_blockhash", |client| {
+            client
+                .get_latest_blockhash()
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get blockhash: {}", e)))
+        })
+        .await
+    }
+
+    /// Get the number of decimals configured on an SPL token mint
+    ///
+    /// Works for both SPL Token and Token-2022 mints, since the base mint
+    /// layout is shared and extensions are appended after it.
+    pub async fn get_mint_decimals(&self, mint: &Pubkey) -> Result<u8> {
+        use solana_sdk::program_pack::Pack;
+        use spl_token::state::Mint;
+
+        let mint = *mint;
+        let data = self
+            .execute_with_fallback("get_mint_decimals", |client| {
+                client
+                    .get_account_data(&mint)
+                    .map_err(|e| Error::SolanaRpc(format!("Failed to get mint account: {}", e)))
+            })
+            .await?;
+
+        if data.len() < Mint::LEN {
+            return Err(Error::Validation(format!("Account {} is not a token mint", mint)));
+        }
+
+        Mint::unpack(&data[..Mint::LEN])
+            .map(|m| m.decimals)
+            .map_err(|e| Error::Validation(format!("Invalid mint account {}: {}", mint, e)))
+    }
+
+    /// Send a signed transaction without waiting for confirmation
+    ///
+    /// Resending the same signed transaction is idempotent on Solana, so this
+    /// is safe to retry; use `get_signature_status` to poll for the outcome.
+    pub async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        info!("Sending transaction with {} instructions", transaction.message.instructions.len());
+
+        self.execute_with_fallback("send_transaction", |client| {
+            client
+                .send_transaction(transaction)
+                .map_err(|e| Error::SolanaRpc(format!("Failed to send transaction: {}", e)))
+        })
+        .await
+    }
+
+    /// Get the status of a transaction signature at the client's commitment level
+    ///
+    /// Returns `None` while the transaction is not yet known to the cluster,
+    /// `Some(Ok(()))` once it has landed and `Some(Err(..))` if it failed.
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<std::result::Result<(), TransactionError>>> {
+        let signature = *signature;
+
+        self.execute_with_fallback("get_signature_status", |client| {
+            client
+                .get_signature_status(&signature)
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get signature status: {}", e)))
+        })
+        .await
+    }
+
+    /// Lamports `recipient` received in a confirmed transaction
+    ///
+    /// Returns `None` while the transaction is not yet known to the cluster
+    /// and an error if it failed. The fee payer is reported as the sender.
+    pub async fn get_received_transfer(
+        &self,
+        signature: &Signature,
+        recipient: &Pubkey,
+    ) -> Result<Option<ReceivedTransfer>> {
+        use solana_client::rpc_config::RpcTransactionConfig;
+        use solana_transaction_status::UiTransactionEncoding;
+
+        let signature = *signature;
+        let recipient = *recipient;
+
+        self.execute_with_fallback("get_received_transfer", |client| {
+            match client
+                .get_signature_status(&signature)
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get signature status: {}", e)))?
+            {
+                None => return Ok(None),
+                Some(Err(e)) => {
+                    return Err(Error::Validation(format!("Transaction {} failed: {}", signature, e)))
+                }
+                Some(Ok(())) => {}
+            }
+
+            let confirmed = client
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(client.commitment()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get transaction: {}", e)))?;
+
+            let meta = confirmed.transaction.meta.ok_or_else(|| {
+                Error::SolanaRpc(format!("Transaction {} has no status metadata", signature))
+            })?;
+            let transaction = confirmed.transaction.transaction.decode().ok_or_else(|| {
+                Error::SolanaRpc(format!("Failed to decode transaction {}", signature))
+            })?;
+
+            let keys = transaction.message.static_account_keys();
+            let sender = *keys.first().ok_or_else(|| {
+                Error::SolanaRpc(format!("Transaction {} has no accounts", signature))
+            })?;
+            let lamports = keys
+                .iter()
+                .position(|key| *key == recipient)
+                .and_then(|i| Some(meta.post_balances.get(i)?.saturating_sub(*meta.pre_balances.get(i)?)))
+                .unwrap_or(0);
+            let memos = memo_texts(&transaction);
+
+            Ok(Some(ReceivedTransfer { sender, lamports, memos }))
+        })
+        .await
+    }
+
+    /// Sign a transaction from `payer` that records `memo` with the SPL Memo
+    /// program, without sending it
+    pub fn sign_memo(
+        payer: &solana_sdk::signature::Keypair,
+        memo: &str,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        use solana_sdk::{instruction::AccountMeta, signature::Signer};
+
+        let instruction = Instruction {
+            program_id: MEMO_PROGRAM_ID,
+            accounts: vec![AccountMeta::new_readonly(payer.pubkey(), true)],
+            data: memo.as_bytes().to_vec(),
+        };
+        Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer], recent_blockhash)
+    }
+
+    /// Memos recorded by a confirmed transaction
+    ///
+    /// Returns `None` while the transaction is not yet known to the cluster
+    /// and `Some(Err(..))` if it failed.
+    pub async fn get_transaction_memos(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<std::result::Result<Vec<String>, TransactionError>>> {
+        use solana_client::rpc_config::RpcTransactionConfig;
+        use solana_transaction_status::UiTransactionEncoding;
+
+        let signature = *signature;
+
+        self.execute_with_fallback("get_transaction_memos", |client| {
+            match client
+                .get_signature_status(&signature)
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get signature status: {}", e)))?
+            {
+                None => return Ok(None),
+                Some(Err(e)) => return Ok(Some(Err(e))),
+                Some(Ok(())) => {}
+            }
+
+            let confirmed = client
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(client.commitment()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get transaction: {}", e)))?;
+            let transaction = confirmed.transaction.transaction.decode().ok_or_else(|| {
+                Error::SolanaRpc(format!("Failed to decode transaction {}", signature))
+            })?;
+
+            Ok(Some(Ok(memo_texts(&transaction))))
+        })
+        .await
+    }
+
+    /// Sign a transfer of `lamports` from `payer` to `recipient` against
+    /// `recent_blockhash`, without sending it
+    ///
+    /// Ed25519 signing is deterministic, so the same arguments always give
+    /// the same transaction and signature: a caller that persists the
+    /// blockhash can rebuild and resend the transaction without ever
+    /// creating a second transfer.
+    pub fn sign_transfer(
+        payer: &solana_sdk::signature::Keypair,
+        recipient: &Pubkey,
+        lamports: u64,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        use solana_sdk::{signature::Signer, system_instruction};
+
+        Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), recipient, lamports)],
+            Some(&payer.pubkey()),
+            &[payer],
+            recent_blockhash,
+        )
+    }
+
+    /// Whether transactions signed against `blockhash` can still land
+    ///
+    /// Once this returns `false`, a transaction using the blockhash that is
+    /// not on-chain yet never will be.
+    pub async fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        let blockhash = *blockhash;
+
+        self.execute_with_fallback("is_blockhash_valid", |client| {
+            client
+                .is_blockhash_valid(&blockhash, client.commitment())
+                .map_err(|e| Error::SolanaRpc(format!("Failed to check blockhash: {}", e)))
+        })
+        .await
+    }
+
+    /// Get the status of a transaction signature, searching the ledger
+    /// history as well as recent statuses
+    ///
+    /// Unlike `get_signature_status` this still finds transactions whose
+    /// blockhash has expired.
+    pub async fn get_signature_status_with_history(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<std::result::Result<(), TransactionError>>> {
+        let signature = *signature;
+
+        self.execute_with_fallback("get_signature_status_with_history", |client| {
+            client
+                .get_signature_status_with_commitment_and_history(&signature, client.commitment(), true)
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get signature status: {}", e)))
+        })
+        .await
+    }
+
+    /// Get the status of a transaction signature with its confirmation
+    /// count, searching the ledger history as well as recent statuses
+    ///
+    /// The count is `None` once the transaction is finalized.
+    pub async fn get_signature_confirmations(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<solana_transaction_status::TransactionStatus>> {
+        let signature = *signature;
+
+        self.execute_with_fallback("get_signature_confirmations", |client| {
+            client
+                .get_signature_statuses_with_history(&[signature])
+                .map(|response| response.value.into_iter().next().flatten())
+                .map_err(|e| Error::SolanaRpc(format!("Failed to get signature status: {}", e)))
+        })
+        .await
+    }
+
     /// Submit a stealth payment transaction to the blockchain
     /// 
     /// This method submits a transaction that transfers funds to a stealth address
@@ -466,7 +764,7 @@ impl SolanaClient {
         viewing_tag: &[u8; 4],
         version: u8,
     ) -> Result<solana_sdk::signature::Signature> {
-        use solana_sdk::{system_instruction, transaction::Transaction, signature::Signer};
+        use solana_sdk::{system_instruction, signature::Signer};
         
         info!(
             "Submitting stealth payment: {} lamports to {}",
@@ -600,6 +898,47 @@ impl SolanaClient {
         .await
     }
 
+    /// Execute an operation against the primary RPC, falling back to the
+    /// secondary endpoint if the primary fails or its circuit is open
+    async fn execute_with_fallback<F, T>(&self, operation_name: &str, operation: F) -> Result<T>
+    where
+        F: Fn(&RpcClient) -> Result<T>,
+    {
+        let primary_result = self
+            .execute_with_circuit_breaker(
+                &self.primary_circuit_breaker,
+                &format!("{}_primary", operation_name),
+                || async { operation(&self.primary_client) },
+            )
+            .await;
+
+        match primary_result {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                warn!("Primary RPC failed for {}: {}", operation_name, e);
+
+                if let (Some(fallback), Some(fallback_cb)) =
+                    (&self.fallback_client, &self.fallback_circuit_breaker)
+                {
+                    debug!("Attempting fallback RPC for {}", operation_name);
+
+                    self.execute_with_circuit_breaker(
+                        fallback_cb,
+                        &format!("{}_fallback", operation_name),
+                        || async { operation(fallback) },
+                    )
+                    .await
+                    .map_err(|fallback_err| {
+                        error!("Both primary and fallback RPC failed: {}", fallback_err);
+                        fallback_err
+                    })
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
     /// Execute an operation with circuit breaker and retry logic
     async fn execute_with_circuit_breaker<F, Fut, T>(
         &self,
@@ -691,6 +1030,31 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_sign_transfer_is_deterministic() {
+        let payer = solana_sdk::signature::Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+
+        let first = SolanaClient::sign_transfer(&payer, &recipient, 1_000, blockhash);
+        let second = SolanaClient::sign_transfer(&payer, &recipient, 1_000, blockhash);
+        assert_eq!(first.signatures, second.signatures);
+        assert!(first.verify().is_ok());
+
+        let other = SolanaClient::sign_transfer(&payer, &recipient, 1_000, Hash::new_unique());
+        assert_ne!(first.signatures, other.signatures);
+    }
+
+    #[test]
+    fn test_sign_memo_records_text() {
+        let payer = solana_sdk::signature::Keypair::new();
+        let transaction = SolanaClient::sign_memo(&payer, "receipt-root:0xabc", Hash::new_unique());
+        assert!(transaction.verify().is_ok());
+
+        let versioned = solana_sdk::transaction::VersionedTransaction::from(transaction);
+        assert_eq!(memo_texts(&versioned), vec!["receipt-root:0xabc".to_string()]);
+    }
+
     // Task 21.3: Unit tests for blockchain integration
 
     #[test]
@@ -704,7 +1068,7 @@ mod tests {
         // Verify instruction structure
         assert_eq!(
             instruction.program_id,
-            solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+            MEMO_PROGRAM_ID,
             "Should use SPL Memo program"
         );
         assert_eq!(instruction.accounts.len(), 0, "Memo instruction should have no accounts");
@@ -1,37 +1,128 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use shared::{Error, Result};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
+use crate::chain_registry::Blockchain;
 use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::retry::{retry_with_backoff, RetryConfig};
+use crate::types::{TokenBalance, TransactionStatus};
 
-/// Supported EVM-compatible blockchains
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum EvmChain {
-    Ethereum,
-    BinanceSmartChain,
-    Polygon,
+/// An EVM-compatible chain an [`EvmClient`] talks to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EvmChain {
+    blockchain: Blockchain,
+    chain_id: u64,
 }
 
 impl EvmChain {
-    pub fn name(&self) -> &'static str {
-        match self {
-            EvmChain::Ethereum => "Ethereum",
-            EvmChain::BinanceSmartChain => "Binance Smart Chain",
-            EvmChain::Polygon => "Polygon",
+    pub fn new(blockchain: Blockchain, chain_id: u64) -> Self {
+        Self {
+            blockchain,
+            chain_id,
         }
     }
 
+    pub fn blockchain(&self) -> &Blockchain {
+        &self.blockchain
+    }
+
+    pub fn name(&self) -> &str {
+        self.blockchain.id()
+    }
+
+    /// EIP-155 chain id transactions are signed for
     pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+}
+
+/// ERC-20 `balanceOf(address)` selector
+const BALANCE_OF_SELECTOR: &str = "70a08231";
+/// ERC-20 `decimals()` selector
+const DECIMALS_SELECTOR: &str = "313ce567";
+/// ERC-20 `symbol()` selector
+const SYMBOL_SELECTOR: &str = "95d89b41";
+/// Decimals assumed for a token without a working `decimals()`, which is
+/// optional in ERC-20
+const DEFAULT_ERC20_DECIMALS: u8 = 18;
+
+/// Block a state query is evaluated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    /// Latest block plus the node's pending transactions
+    Pending,
+}
+
+impl BlockTag {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            EvmChain::Ethereum => 1,
-            EvmChain::BinanceSmartChain => 56,
-            EvmChain::Polygon => 137,
+            BlockTag::Latest => "latest",
+            BlockTag::Pending => "pending",
         }
     }
 }
 
-/// EVM client for Ethereum, BSC, and Polygon
+/// Call or transaction to simulate with `eth_estimateGas`
+#[derive(Debug, Clone, Default)]
+pub struct EvmCallRequest {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Native value in wei
+    pub value: Option<u128>,
+    /// 0x-prefixed calldata
+    pub data: Option<String>,
+}
+
+/// Receipt of a mined EVM transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmTransactionReceipt {
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub block_hash: String,
+    pub from: String,
+    /// `None` for contract creations
+    pub to: Option<String>,
+    pub contract_address: Option<String>,
+    pub gas_used: u64,
+    /// Price per gas actually paid, in wei
+    pub effective_gas_price: Option<u128>,
+    pub status: TransactionStatus,
+    pub log_count: usize,
+}
+
+impl EvmTransactionReceipt {
+    /// Blocks that include the transaction, counting its own, when
+    /// `latest_block` is the chain head
+    pub fn confirmations(&self, latest_block: u64) -> u64 {
+        latest_block
+            .checked_sub(self.block_number)
+            .map_or(0, |depth| depth + 1)
+    }
+}
+
+/// EIP-1559 fee history over a range of recent blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeHistory {
+    pub oldest_block: u64,
+    /// Base fee per block in wei, plus the base fee of the next block
+    pub base_fee_per_gas: Vec<u128>,
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fees in wei at the requested percentiles, per block
+    pub reward: Vec<Vec<u128>>,
+}
+
+impl FeeHistory {
+    /// Base fee of the block after the newest one in the range
+    pub fn next_base_fee(&self) -> Option<u128> {
+        self.base_fee_per_gas.last().copied()
+    }
+}
+
+/// JSON-RPC client for an EVM-compatible chain
 pub struct EvmClient {
     chain: EvmChain,
     primary_rpc_url: String,
@@ -114,8 +205,8 @@ impl EvmClient {
     }
 
     /// Get the chain this client is configured for
-    pub fn chain(&self) -> EvmChain {
-        self.chain
+    pub fn chain(&self) -> &EvmChain {
+        &self.chain
     }
 
     /// Validate an Ethereum-compatible address format (0x + 40 hex chars)
@@ -328,6 +419,421 @@ impl EvmClient {
         Ok(())
     }
 
+    /// Validate a transaction hash format (0x + 64 hex chars)
+    pub fn validate_transaction_hash(&self, tx_hash: &str) -> Result<String> {
+        let valid = tx_hash.len() == 66
+            && tx_hash.starts_with("0x")
+            && tx_hash[2..].chars().all(|c| c.is_ascii_hexdigit());
+        if !valid {
+            return Err(Error::Validation(format!(
+                "Invalid transaction hash: {}",
+                tx_hash
+            )));
+        }
+
+        Ok(tx_hash.to_lowercase())
+    }
+
+    /// Get the native token balance of an address, in wei
+    pub async fn get_native_balance(&self, address: &str) -> Result<u128> {
+        let address = self.validate_address(address)?;
+
+        debug!("Fetching {} native balance for {}", self.chain.name(), address);
+
+        let result = self
+            .rpc_call(
+                "get_native_balance",
+                "eth_getBalance",
+                serde_json::json!([address, BlockTag::Latest.as_str()]),
+            )
+            .await?;
+
+        parse_quantity(&result, "balance")
+    }
+
+    /// Get ERC-20 balances, decimals and symbols of `owner` for each contract
+    ///
+    /// All `eth_call`s go out in a single JSON-RPC batch. Balances are
+    /// returned in the order of `contracts`. Only `balanceOf()` must succeed:
+    /// a token whose `decimals()` fails gets [`DEFAULT_ERC20_DECIMALS`] and
+    /// one whose `symbol()` fails has no symbol.
+    pub async fn get_erc20_balances(
+        &self,
+        owner: &str,
+        contracts: &[String],
+    ) -> Result<Vec<TokenBalance>> {
+        let owner = self.validate_address(owner)?;
+        let contracts = contracts
+            .iter()
+            .map(|contract| self.validate_address(contract))
+            .collect::<Result<Vec<_>>>()?;
+
+        if contracts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        debug!(
+            "Fetching {} ERC-20 balances for {} on {}",
+            contracts.len(),
+            owner,
+            self.chain.name()
+        );
+
+        let calldata = [
+            format!("0x{}{}", BALANCE_OF_SELECTOR, encode_address_arg(&owner)),
+            format!("0x{}", DECIMALS_SELECTOR),
+            format!("0x{}", SYMBOL_SELECTOR),
+        ];
+        let calls = contracts
+            .iter()
+            .flat_map(|contract| {
+                calldata.iter().map(move |data| {
+                    (
+                        "eth_call",
+                        serde_json::json!([
+                            { "to": contract, "data": data },
+                            BlockTag::Latest.as_str()
+                        ]),
+                    )
+                })
+            })
+            .collect();
+
+        let mut results = self.rpc_batch("get_erc20_balances", calls).await?.into_iter();
+
+        contracts
+            .into_iter()
+            .map(|contract| {
+                let mut next = |what: &str| -> Result<String> {
+                    let value = results
+                        .next()
+                        .ok_or_else(|| Error::EvmRpc("Missing batch response".to_string()))?
+                        .map_err(|e| Error::EvmRpc(format!("{}() failed on {}: {}", what, contract, e)))?;
+                    value.as_str().map(str::to_string).ok_or_else(|| {
+                        Error::EvmRpc(format!("Invalid {}() result from {}", what, contract))
+                    })
+                };
+
+                // Every call is taken from the batch before any is checked,
+                // so the next token's results stay aligned
+                let (balance, decimals, symbol) = (next("balanceOf"), next("decimals"), next("symbol"));
+                let amount = decode_uint(&balance?)?;
+                let decimals = decimals
+                    .and_then(|decimals| {
+                        u8::try_from(decode_uint(&decimals)?).map_err(|_| {
+                            Error::EvmRpc(format!("Decimals of {} out of range", contract))
+                        })
+                    })
+                    .unwrap_or_else(|e| {
+                        warn!("Assuming {} decimals for {}: {}", DEFAULT_ERC20_DECIMALS, contract, e);
+                        DEFAULT_ERC20_DECIMALS
+                    });
+                let symbol = symbol
+                    .and_then(|symbol| decode_abi_string(&symbol))
+                    .map_err(|e| debug!("No symbol for {}: {}", contract, e))
+                    .ok();
+
+                Ok(TokenBalance {
+                    token: contract,
+                    amount,
+                    decimals,
+                    symbol,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the receipt of a transaction
+    ///
+    /// Returns `None` while the transaction is pending or unknown to the node.
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<EvmTransactionReceipt>> {
+        let tx_hash = self.validate_transaction_hash(tx_hash)?;
+
+        let result = self
+            .rpc_call(
+                "get_transaction_receipt",
+                "eth_getTransactionReceipt",
+                serde_json::json!([tx_hash]),
+            )
+            .await?;
+
+        if result.is_null() {
+            return Ok(None);
+        }
+
+        parse_receipt(&result).map(Some)
+    }
+
+    /// Get the input data of a transaction, `None` if the node does not
+    /// know the transaction
+    pub async fn get_transaction_input(&self, tx_hash: &str) -> Result<Option<String>> {
+        let tx_hash = self.validate_transaction_hash(tx_hash)?;
+
+        let result = self
+            .rpc_call(
+                "get_transaction_by_hash",
+                "eth_getTransactionByHash",
+                serde_json::json!([tx_hash]),
+            )
+            .await?;
+
+        if result.is_null() {
+            return Ok(None);
+        }
+
+        result
+            .get("input")
+            .and_then(Value::as_str)
+            .map(|input| Some(input.to_lowercase()))
+            .ok_or_else(|| Error::EvmRpc("Transaction is missing input".to_string()))
+    }
+
+    /// Get the number of the latest block
+    pub async fn get_block_number(&self) -> Result<u64> {
+        let result = self
+            .rpc_call("get_block_number", "eth_blockNumber", serde_json::json!([]))
+            .await?;
+
+        parse_u64(&result, "block number")
+    }
+
+    /// Get the number of transactions sent from an address, i.e. its next nonce
+    pub async fn get_transaction_count(&self, address: &str, block: BlockTag) -> Result<u64> {
+        let address = self.validate_address(address)?;
+
+        let result = self
+            .rpc_call(
+                "get_transaction_count",
+                "eth_getTransactionCount",
+                serde_json::json!([address, block.as_str()]),
+            )
+            .await?;
+
+        parse_u64(&result, "transaction count")
+    }
+
+    /// Estimate the gas a call or transaction would use
+    pub async fn estimate_gas(&self, request: &EvmCallRequest) -> Result<u64> {
+        let mut call = serde_json::Map::new();
+        if let Some(from) = &request.from {
+            call.insert("from".to_string(), self.validate_address(from)?.into());
+        }
+        if let Some(to) = &request.to {
+            call.insert("to".to_string(), self.validate_address(to)?.into());
+        }
+        if let Some(value) = request.value {
+            call.insert("value".to_string(), format!("{:#x}", value).into());
+        }
+        if let Some(data) = &request.data {
+            call.insert("data".to_string(), data.clone().into());
+        }
+
+        let result = self
+            .rpc_call("estimate_gas", "eth_estimateGas", serde_json::json!([call]))
+            .await?;
+
+        parse_u64(&result, "gas estimate")
+    }
+
+    /// Get EIP-1559 fee history for the latest `block_count` blocks
+    ///
+    /// `reward_percentiles` are monotonically increasing values in 0..=100
+    /// selecting which priority fees to sample from each block.
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory> {
+        if block_count == 0 || block_count > 1024 {
+            return Err(Error::Validation(
+                "Fee history block count must be between 1 and 1024".to_string(),
+            ));
+        }
+        if reward_percentiles.windows(2).any(|w| w[0] > w[1])
+            || reward_percentiles.iter().any(|p| !(0.0..=100.0).contains(p))
+        {
+            return Err(Error::Validation(
+                "Reward percentiles must be increasing values between 0 and 100".to_string(),
+            ));
+        }
+
+        let result = self
+            .rpc_call(
+                "get_fee_history",
+                "eth_feeHistory",
+                serde_json::json!([
+                    format!("{:#x}", block_count),
+                    BlockTag::Latest.as_str(),
+                    reward_percentiles
+                ]),
+            )
+            .await?;
+
+        parse_fee_history(&result)
+    }
+
+    /// Make a JSON-RPC call, failing over to the fallback endpoint
+    async fn rpc_call(
+        &self,
+        operation_name: &str,
+        method: &'static str,
+        params: Value,
+    ) -> Result<Value> {
+        self.execute_with_fallback(operation_name, |url| {
+            let params = params.clone();
+            async move { Self::send_rpc_request(&url, method, params).await }
+        })
+        .await
+    }
+
+    /// Make a batch of JSON-RPC calls in one request, failing over to the
+    /// fallback endpoint
+    ///
+    /// Only transport failures count against the circuit breaker; an error
+    /// returned for an individual call is handed back in its slot.
+    async fn rpc_batch(
+        &self,
+        operation_name: &str,
+        calls: Vec<(&'static str, Value)>,
+    ) -> Result<Vec<Result<Value>>> {
+        let body: Vec<Value> = calls
+            .into_iter()
+            .enumerate()
+            .map(|(id, (method, params))| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": method,
+                    "params": params,
+                    "id": id
+                })
+            })
+            .collect();
+        let body = Value::Array(body);
+
+        let responses = self
+            .execute_with_fallback(operation_name, |url| {
+                let body = body.clone();
+                async move {
+                    match Self::post_json(&url, &body).await? {
+                        Value::Array(responses) => Ok(responses),
+                        other => Err(Error::EvmRpc(format!(
+                            "Expected a batch response, got: {}",
+                            rpc_error_message(&other)
+                        ))),
+                    }
+                }
+            })
+            .await?;
+
+        // Batch responses may come back in any order
+        let mut by_id: HashMap<u64, Value> = responses
+            .into_iter()
+            .filter_map(|response| Some((response.get("id")?.as_u64()?, response)))
+            .collect();
+
+        Ok((0..body.as_array().map_or(0, Vec::len) as u64)
+            .map(|id| {
+                let response = by_id
+                    .remove(&id)
+                    .ok_or_else(|| Error::EvmRpc(format!("Missing response for batch call {}", id)))?;
+                rpc_result(response)
+            })
+            .collect())
+    }
+
+    /// Send a single JSON-RPC request and extract its result
+    async fn send_rpc_request(rpc_url: &str, method: &str, params: Value) -> Result<Value> {
+        let request_body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+
+        rpc_result(Self::post_json(rpc_url, &request_body).await?)
+    }
+
+    /// POST a JSON-RPC payload and parse the response body
+    async fn post_json(rpc_url: &str, body: &Value) -> Result<Value> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(rpc_url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| Error::EvmRpc(format!("Failed to send RPC request: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::EvmRpc(format!(
+                "RPC request failed with status: {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| Error::EvmRpc(format!("Failed to parse RPC response: {}", e)))
+    }
+
+    /// Run an operation against the primary RPC and, if that fails, the fallback
+    async fn execute_with_fallback<F, Fut, T>(&self, operation_name: &str, operation: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let primary_result = self
+            .execute_with_circuit_breaker(
+                &self.primary_circuit_breaker,
+                &format!("{}_primary", operation_name),
+                || operation(self.primary_rpc_url.clone()),
+            )
+            .await;
+
+        match primary_result {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                warn!(
+                    "Primary RPC failed for {} on {}: {}",
+                    operation_name,
+                    self.chain.name(),
+                    e
+                );
+
+                if let (Some(fallback_url), Some(fallback_cb)) =
+                    (&self.fallback_rpc_url, &self.fallback_circuit_breaker)
+                {
+                    debug!(
+                        "Attempting fallback RPC for {} on {}",
+                        operation_name,
+                        self.chain.name()
+                    );
+
+                    self.execute_with_circuit_breaker(
+                        fallback_cb,
+                        &format!("{}_fallback", operation_name),
+                        || operation(fallback_url.clone()),
+                    )
+                    .await
+                    .map_err(|fallback_err| {
+                        error!(
+                            "Both primary and fallback RPC failed for {}: {}",
+                            self.chain.name(),
+                            fallback_err
+                        );
+                        fallback_err
+                    })
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
     /// Execute an operation with circuit breaker and retry logic
     async fn execute_with_circuit_breaker<F, Fut, T>(
         &self,
@@ -372,6 +878,200 @@ impl EvmClient {
     }
 }
 
+/// Whether `error` is the node refusing a request, rather than the request
+/// or its response getting lost on the way
+///
+/// Only then is it certain the node did not act on the request.
+pub fn is_rejection(error: &Error) -> bool {
+    match error {
+        Error::EvmRpc(message) => message.starts_with("RPC error: "),
+        Error::CircuitBreakerOpen(_) => true,
+        _ => false,
+    }
+}
+
+/// Extract the result of a JSON-RPC response, surfacing its error if any
+fn rpc_result(mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        return Err(Error::EvmRpc(format!("RPC error: {}", rpc_error_message(error))));
+    }
+
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| Error::EvmRpc("Missing result in RPC response".to_string()))
+}
+
+fn rpc_error_message(error: &Value) -> String {
+    error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| error.to_string())
+}
+
+/// Parse a hex-encoded JSON-RPC quantity
+fn parse_quantity(value: &Value, field: &str) -> Result<u128> {
+    let hex = value
+        .as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .ok_or_else(|| Error::EvmRpc(format!("Invalid {}: {}", field, value)))?;
+    if hex.is_empty() {
+        return Ok(0);
+    }
+
+    u128::from_str_radix(hex, 16)
+        .map_err(|e| Error::EvmRpc(format!("Invalid {} {}: {}", field, value, e)))
+}
+
+fn parse_u64(value: &Value, field: &str) -> Result<u64> {
+    u64::try_from(parse_quantity(value, field)?)
+        .map_err(|_| Error::EvmRpc(format!("{} out of range: {}", field, value)))
+}
+
+fn parse_address(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_lowercase)
+}
+
+/// Left-pad an address to a 32-byte ABI argument
+fn encode_address_arg(address: &str) -> String {
+    format!("{:0>64}", address.trim_start_matches("0x"))
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>> {
+    hex::decode(data.trim_start_matches("0x"))
+        .map_err(|e| Error::EvmRpc(format!("Invalid hex data {}: {}", data, e)))
+}
+
+/// Decode an ABI-encoded `uint256` return value
+///
+/// Values above `u128::MAX` are rejected rather than truncated.
+fn decode_uint(data: &str) -> Result<u128> {
+    let bytes = decode_hex(data)?;
+    if bytes.len() < 32 {
+        return Err(Error::EvmRpc(format!(
+            "Expected a 32-byte word, got {} bytes (is this a contract?)",
+            bytes.len()
+        )));
+    }
+    if bytes[..16].iter().any(|b| *b != 0) {
+        return Err(Error::EvmRpc(format!("Value {} exceeds 128 bits", data)));
+    }
+
+    Ok(bytes[16..32]
+        .iter()
+        .fold(0u128, |acc, b| (acc << 8) | u128::from(*b)))
+}
+
+/// Decode an ABI-encoded `string` return value
+///
+/// Some older tokens (e.g. MKR) return `bytes32` instead, which is decoded
+/// as a NUL-padded string.
+fn decode_abi_string(data: &str) -> Result<String> {
+    let bytes = decode_hex(data)?;
+
+    if bytes.len() == 32 {
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(32);
+        return Ok(String::from_utf8_lossy(&bytes[..end]).into_owned());
+    }
+
+    let invalid = || Error::EvmRpc(format!("Invalid ABI string: {}", data));
+    let word = |offset: usize| -> Result<usize> {
+        let end = offset.checked_add(32).ok_or_else(invalid)?;
+        let word = bytes.get(offset..end).ok_or_else(invalid)?;
+        if word[..24].iter().any(|b| *b != 0) {
+            return Err(invalid());
+        }
+        Ok(word[24..].iter().fold(0usize, |acc, b| (acc << 8) | usize::from(*b)))
+    };
+
+    let offset = word(0)?;
+    let length = word(offset)?;
+    let start = offset.checked_add(32).ok_or_else(invalid)?;
+    let end = start.checked_add(length).ok_or_else(invalid)?;
+    let string = bytes.get(start..end).ok_or_else(invalid)?;
+
+    Ok(String::from_utf8_lossy(string).into_owned())
+}
+
+fn parse_receipt(receipt: &Value) -> Result<EvmTransactionReceipt> {
+    let field = |name: &str| receipt.get(name).unwrap_or(&Value::Null);
+    let string = |name: &str| {
+        field(name)
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::EvmRpc(format!("Receipt is missing {}", name)))
+    };
+
+    // Receipts from before Byzantium carry a state root instead of a status
+    let status = match field("status").as_str() {
+        Some("0x1") => TransactionStatus::Success,
+        Some("0x0") => TransactionStatus::Failed,
+        _ => {
+            return Err(Error::EvmRpc(format!(
+                "Receipt has no decodable status: {}",
+                field("status")
+            )))
+        }
+    };
+
+    Ok(EvmTransactionReceipt {
+        transaction_hash: string("transactionHash")?,
+        block_number: parse_u64(field("blockNumber"), "block number")?,
+        block_hash: string("blockHash")?,
+        from: parse_address(field("from"))
+            .ok_or_else(|| Error::EvmRpc("Receipt is missing from".to_string()))?,
+        to: parse_address(field("to")),
+        contract_address: parse_address(field("contractAddress")),
+        gas_used: parse_u64(field("gasUsed"), "gas used")?,
+        effective_gas_price: match field("effectiveGasPrice") {
+            Value::Null => None,
+            price => Some(parse_quantity(price, "effective gas price")?),
+        },
+        status,
+        log_count: field("logs").as_array().map_or(0, Vec::len),
+    })
+}
+
+fn parse_fee_history(history: &Value) -> Result<FeeHistory> {
+    let array = |name: &str| -> &[Value] {
+        history
+            .get(name)
+            .and_then(Value::as_array)
+            .map_or(&[], Vec::as_slice)
+    };
+
+    Ok(FeeHistory {
+        oldest_block: parse_u64(
+            history.get("oldestBlock").unwrap_or(&Value::Null),
+            "oldest block",
+        )?,
+        base_fee_per_gas: array("baseFeePerGas")
+            .iter()
+            .map(|fee| parse_quantity(fee, "base fee"))
+            .collect::<Result<_>>()?,
+        gas_used_ratio: array("gasUsedRatio")
+            .iter()
+            .map(|ratio| {
+                ratio
+                    .as_f64()
+                    .ok_or_else(|| Error::EvmRpc(format!("Invalid gas used ratio: {}", ratio)))
+            })
+            .collect::<Result<_>>()?,
+        reward: array("reward")
+            .iter()
+            .map(|rewards| {
+                rewards
+                    .as_array()
+                    .map_or(&[][..], Vec::as_slice)
+                    .iter()
+                    .map(|reward| parse_quantity(reward, "reward"))
+                    .collect::<Result<_>>()
+            })
+            .collect::<Result<_>>()?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -379,7 +1079,7 @@ mod tests {
     #[test]
     fn test_validate_address_valid() {
         let client = EvmClient::new(
-            EvmChain::Ethereum,
+            EvmChain::new(Blockchain::ETHEREUM, 1),
             "https://eth.llamarpc.com".to_string(),
             None,
         );
@@ -392,7 +1092,7 @@ mod tests {
     #[test]
     fn test_validate_address_lowercase() {
         let client = EvmClient::new(
-            EvmChain::Ethereum,
+            EvmChain::new(Blockchain::ETHEREUM, 1),
             "https://eth.llamarpc.com".to_string(),
             None,
         );
@@ -408,7 +1108,7 @@ mod tests {
     #[test]
     fn test_validate_address_invalid_prefix() {
         let client = EvmClient::new(
-            EvmChain::Ethereum,
+            EvmChain::new(Blockchain::ETHEREUM, 1),
             "https://eth.llamarpc.com".to_string(),
             None,
         );
@@ -420,7 +1120,7 @@ mod tests {
     #[test]
     fn test_validate_address_invalid_length() {
         let client = EvmClient::new(
-            EvmChain::Ethereum,
+            EvmChain::new(Blockchain::ETHEREUM, 1),
             "https://eth.llamarpc.com".to_string(),
             None,
         );
@@ -432,7 +1132,7 @@ mod tests {
     #[test]
     fn test_validate_address_invalid_chars() {
         let client = EvmClient::new(
-            EvmChain::Ethereum,
+            EvmChain::new(Blockchain::ETHEREUM, 1),
             "https://eth.llamarpc.com".to_string(),
             None,
         );
@@ -442,16 +1142,132 @@ mod tests {
     }
 
     #[test]
-    fn test_chain_ids() {
-        assert_eq!(EvmChain::Ethereum.chain_id(), 1);
-        assert_eq!(EvmChain::BinanceSmartChain.chain_id(), 56);
-        assert_eq!(EvmChain::Polygon.chain_id(), 137);
+    fn test_chain_from_registry() {
+        let registry = crate::ChainRegistry::default();
+        let chain = |blockchain| registry.get(&blockchain).unwrap().evm_chain().unwrap();
+
+        assert_eq!(chain(Blockchain::ETHEREUM).chain_id(), 1);
+        assert_eq!(chain(Blockchain::BINANCE_SMART_CHAIN).chain_id(), 56);
+        assert_eq!(chain(Blockchain::POLYGON).chain_id(), 137);
+        assert_eq!(chain(Blockchain::BINANCE_SMART_CHAIN).name(), "BinanceSmartChain");
+    }
+
+    #[test]
+    fn test_validate_transaction_hash() {
+        let client = EvmClient::new(
+            EvmChain::new(Blockchain::ETHEREUM, 1),
+            "https://eth.llamarpc.com".to_string(),
+            None,
+        );
+
+        let hash = format!("0x{}", "AB".repeat(32));
+        assert_eq!(client.validate_transaction_hash(&hash).unwrap(), hash.to_lowercase());
+        assert!(client.validate_transaction_hash("0x1234").is_err());
+        assert!(client.validate_transaction_hash(&"ab".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn test_encode_address_arg() {
+        assert_eq!(
+            encode_address_arg("0x742d35cc6634c0532925a3b844bc9e7595f0beb0"),
+            "000000000000000000000000742d35cc6634c0532925a3b844bc9e7595f0beb0"
+        );
+    }
+
+    #[test]
+    fn test_decode_uint() {
+        let word = format!("0x{:064x}", 1_500_000u64);
+        assert_eq!(decode_uint(&word).unwrap(), 1_500_000);
+        assert_eq!(decode_uint(&format!("0x{:064x}", u128::MAX)).unwrap(), u128::MAX);
+
+        // Calling an address without code returns empty data
+        assert!(decode_uint("0x").is_err());
+        assert!(decode_uint(&format!("0x1{}", "0".repeat(63))).is_err());
+    }
+
+    #[test]
+    fn test_decode_abi_string() {
+        // symbol() -> "USDC", ABI-encoded as a dynamic string
+        let encoded = format!(
+            "0x{:064x}{:064x}{:0<64}",
+            32,
+            4,
+            hex::encode("USDC")
+        );
+        assert_eq!(decode_abi_string(&encoded).unwrap(), "USDC");
+
+        // symbol() -> bytes32("MKR")
+        let bytes32 = format!("0x{:0<64}", hex::encode("MKR"));
+        assert_eq!(decode_abi_string(&bytes32).unwrap(), "MKR");
+
+        // Length runs past the end of the data
+        let truncated = format!("0x{:064x}{:064x}{:0<64}", 32, 40, hex::encode("USDC"));
+        assert!(decode_abi_string(&truncated).is_err());
+
+        // Offsets and lengths that overflow are rejected, not wrapped
+        let huge_offset = format!("0x{:064x}{:064x}", u64::MAX, 4);
+        assert!(decode_abi_string(&huge_offset).is_err());
+        let huge_length = format!("0x{:064x}{:064x}{:0<64}", 32, u64::MAX, hex::encode("USDC"));
+        assert!(decode_abi_string(&huge_length).is_err());
+    }
+
+    #[test]
+    fn test_parse_receipt_status() {
+        let mut receipt = serde_json::json!({
+            "transactionHash": format!("0x{}", "ab".repeat(32)),
+            "blockNumber": "0x10",
+            "blockHash": format!("0x{}", "cd".repeat(32)),
+            "from": "0x742D35CC6634C0532925A3B844BC9E7595F0BEB0",
+            "to": null,
+            "contractAddress": "0x0000000000000000000000000000000000000001",
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0x3b9aca00",
+            "status": "0x1",
+            "logs": [{}, {}]
+        });
+
+        let parsed = parse_receipt(&receipt).unwrap();
+        assert_eq!(parsed.status, TransactionStatus::Success);
+        assert_eq!(parsed.block_number, 16);
+        assert_eq!(parsed.gas_used, 21_000);
+        assert_eq!(parsed.effective_gas_price, Some(1_000_000_000));
+        assert_eq!(parsed.from, "0x742d35cc6634c0532925a3b844bc9e7595f0beb0");
+        assert_eq!(parsed.to, None);
+        assert_eq!(parsed.log_count, 2);
+        assert_eq!(parsed.confirmations(15), 0);
+        assert_eq!(parsed.confirmations(16), 1);
+        assert_eq!(parsed.confirmations(27), 12);
+
+        receipt["status"] = "0x0".into();
+        assert_eq!(parse_receipt(&receipt).unwrap().status, TransactionStatus::Failed);
+
+        // Pre-Byzantium receipts only carry a state root
+        receipt.as_object_mut().unwrap().remove("status");
+        assert!(parse_receipt(&receipt).is_err());
+    }
+
+    #[test]
+    fn test_parse_fee_history() {
+        let history = parse_fee_history(&serde_json::json!({
+            "oldestBlock": "0x100",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca01", "0x77359400"],
+            "gasUsedRatio": [0.5, 0.75],
+            "reward": [["0x1", "0x2"], ["0x3", "0x4"]]
+        }))
+        .unwrap();
+
+        assert_eq!(history.oldest_block, 256);
+        assert_eq!(history.gas_used_ratio, vec![0.5, 0.75]);
+        assert_eq!(history.reward, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(history.next_base_fee(), Some(2_000_000_000));
     }
 
     #[test]
-    fn test_chain_names() {
-        assert_eq!(EvmChain::Ethereum.name(), "Ethereum");
-        assert_eq!(EvmChain::BinanceSmartChain.name(), "Binance Smart Chain");
-        assert_eq!(EvmChain::Polygon.name(), "Polygon");
+    fn test_is_rejection() {
+        assert!(is_rejection(&Error::EvmRpc("RPC error: nonce too low".to_string())));
+        assert!(is_rejection(&Error::CircuitBreakerOpen("open".to_string())));
+        // The request or its response may have been lost
+        assert!(!is_rejection(&Error::EvmRpc("Failed to send RPC request: timed out".to_string())));
+        assert!(!is_rejection(&Error::EvmRpc("RPC request failed with status: 502".to_string())));
     }
 }
@@ -0,0 +1,287 @@
+//! Local encrypted keystore for EVM signing keys
+//!
+//! Keys are stored as JSON with the secp256k1 secret encrypted by AES-256-GCM
+//! under a key derived from a passphrase with Argon2id. The address is kept in
+//! the clear so a keystore can be found without unlocking it.
+//!
+//! This is this crate's own format, not the Web3 Secret Storage (v3) format
+//! of geth and most wallets: keystores exported from them cannot be loaded,
+//! and these cannot be imported into them. Files are readable by their owner
+//! only.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use solana_sdk::keccak;
+use std::io::Write;
+use std::path::Path;
+
+/// Current keystore format version
+const KEYSTORE_VERSION: u8 = 1;
+
+/// A secp256k1 key that signs EVM transactions
+pub struct EvmSigner {
+    secret_key: libsecp256k1::SecretKey,
+    address: String,
+}
+
+impl EvmSigner {
+    /// Create a signer from a 32-byte secret key
+    pub fn from_secret_key(secret: &[u8]) -> Result<Self> {
+        let secret_key = libsecp256k1::SecretKey::parse_slice(secret)
+            .map_err(|e| Error::Validation(format!("Invalid secp256k1 secret key: {:?}", e)))?;
+        let public_key = libsecp256k1::PublicKey::from_secret_key(&secret_key);
+
+        // The address is the last 20 bytes of the Keccak-256 of the uncompressed key
+        let hash = keccak::hash(&public_key.serialize()[1..]);
+        let address = format!("0x{}", hex::encode(&hash.to_bytes()[12..]));
+
+        Ok(Self {
+            secret_key,
+            address,
+        })
+    }
+
+    /// Create a signer from a hex-encoded secret key
+    pub fn from_hex(secret: &str) -> Result<Self> {
+        let bytes = hex::decode(secret.trim_start_matches("0x"))
+            .map_err(|e| Error::Validation(format!("Invalid secret key hex: {}", e)))?;
+        Self::from_secret_key(&bytes)
+    }
+
+    /// Generate a new random signer
+    pub fn generate() -> Self {
+        loop {
+            let mut secret = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut secret);
+            // Fails only for zero or values above the curve order
+            if let Ok(signer) = Self::from_secret_key(&secret) {
+                return signer;
+            }
+        }
+    }
+
+    /// Lowercase `0x` address of this key
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Sign a 32-byte digest, returning `(r, s, y_parity)`
+    pub fn sign_hash(&self, hash: &[u8; 32]) -> ([u8; 32], [u8; 32], u8) {
+        let message = libsecp256k1::Message::parse(hash);
+        let (signature, recovery_id) = libsecp256k1::sign(&message, &self.secret_key);
+        let bytes = signature.serialize();
+
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+
+        (r, s, recovery_id.serialize())
+    }
+}
+
+impl std::fmt::Debug for EvmSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret key
+        f.debug_struct("EvmSigner")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Argon2id parameters used to derive the encryption key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreKdf {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Hex-encoded salt
+    pub salt: String,
+}
+
+/// An EVM secret key encrypted at rest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmKeystore {
+    pub version: u8,
+    pub address: String,
+    pub kdf: KeystoreKdf,
+    /// Hex-encoded AES-256-GCM nonce
+    pub nonce: String,
+    /// Hex-encoded encrypted secret key and authentication tag
+    pub ciphertext: String,
+}
+
+impl EvmKeystore {
+    /// Encrypt a signer's secret key under `passphrase`
+    pub fn encrypt(signer: &EvmSigner, passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let default_params = Params::default();
+        let kdf = KeystoreKdf {
+            memory_kib: default_params.m_cost(),
+            iterations: default_params.t_cost(),
+            parallelism: default_params.p_cost(),
+            salt: hex::encode(salt),
+        };
+
+        let cipher = Self::cipher(&kdf, passphrase)?;
+        let ciphertext = cipher
+            .encrypt(&Nonce::from(nonce), signer.secret_key.serialize().as_ref())
+            .map_err(|e| Error::Internal(format!("Failed to encrypt keystore: {}", e)))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address: signer.address.clone(),
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the secret key with `passphrase`
+    pub fn decrypt(&self, passphrase: &str) -> Result<EvmSigner> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(Error::Validation(format!(
+                "Unsupported keystore version: {}",
+                self.version
+            )));
+        }
+
+        let nonce: [u8; 12] = hex::decode(&self.nonce)
+            .ok()
+            .and_then(|nonce| nonce.try_into().ok())
+            .ok_or_else(|| Error::Validation("Invalid keystore nonce".to_string()))?;
+        let ciphertext = hex::decode(&self.ciphertext)
+            .map_err(|e| Error::Validation(format!("Invalid keystore ciphertext: {}", e)))?;
+
+        let cipher = Self::cipher(&self.kdf, passphrase)?;
+        let secret = cipher
+            .decrypt(&Nonce::from(nonce), ciphertext.as_ref())
+            .map_err(|_| Error::Unauthorized)?;
+
+        let signer = EvmSigner::from_secret_key(&secret)?;
+        if signer.address != self.address.to_lowercase() {
+            return Err(Error::Validation(format!(
+                "Keystore key does not match address {}",
+                self.address
+            )));
+        }
+
+        Ok(signer)
+    }
+
+    /// Read a keystore from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            Error::Internal(format!("Failed to read keystore {}: {}", path.display(), e))
+        })?;
+
+        serde_json::from_str(&json)
+            .map_err(|e| Error::Validation(format!("Invalid keystore {}: {}", path.display(), e)))
+    }
+
+    /// Write the keystore to a JSON file only its owner can read
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Internal(format!("Failed to serialize keystore: {}", e)))?;
+        let write_error =
+            |e: std::io::Error| Error::Internal(format!("Failed to write keystore {}: {}", path.display(), e));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path).map_err(write_error)?;
+
+        // The mode only applies to new files; tighten an existing one too
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))
+                .map_err(write_error)?;
+        }
+
+        file.write_all(json.as_bytes()).map_err(write_error)
+    }
+
+    /// AES-256-GCM cipher keyed by Argon2id over the passphrase
+    fn cipher(kdf: &KeystoreKdf, passphrase: &str) -> Result<Aes256Gcm> {
+        let salt = hex::decode(&kdf.salt)
+            .map_err(|e| Error::Validation(format!("Invalid keystore salt: {}", e)))?;
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+            .map_err(|e| Error::Validation(format!("Invalid keystore KDF parameters: {}", e)))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| Error::Internal(format!("Failed to derive keystore key: {}", e)))?;
+
+        Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| Error::Internal(format!("Failed to create cipher: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example account from the web3.js `accounts` documentation
+    const WEB3_SECRET: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const WEB3_ADDRESS: &str = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+
+    #[test]
+    fn test_signer_address() {
+        let signer = EvmSigner::from_hex(WEB3_SECRET).unwrap();
+        assert_eq!(signer.address(), WEB3_ADDRESS);
+        assert!(!format!("{:?}", signer).contains(&WEB3_SECRET[2..]));
+
+        assert!(EvmSigner::from_secret_key(&[0u8; 32]).is_err());
+        assert!(EvmSigner::from_hex("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let signer = EvmSigner::generate();
+        let keystore = EvmKeystore::encrypt(&signer, "correct horse").unwrap();
+        assert_eq!(keystore.address, signer.address());
+
+        let path = std::env::temp_dir().join(format!("keystore-{}.json", &signer.address()[2..]));
+        keystore.save(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let loaded = EvmKeystore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, keystore);
+
+        let unlocked = loaded.decrypt("correct horse").unwrap();
+        assert_eq!(unlocked.address(), signer.address());
+        assert!(matches!(loaded.decrypt("wrong"), Err(Error::Unauthorized)));
+    }
+
+    #[test]
+    fn test_keystore_rejects_mismatched_address() {
+        let signer = EvmSigner::from_hex(WEB3_SECRET).unwrap();
+        let mut keystore = EvmKeystore::encrypt(&signer, "passphrase").unwrap();
+        keystore.address = "0x0000000000000000000000000000000000000001".to_string();
+
+        assert!(keystore.decrypt("passphrase").is_err());
+    }
+}
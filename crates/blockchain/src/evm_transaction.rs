@@ -0,0 +1,785 @@
+//! EIP-1559 transaction building and signing for EVM chains
+//!
+//! `EvmTransactionBuilder` fills in the nonce, gas limit and fees of native
+//! transfers and ERC-20 `transfer`/`approve` calls, signs them with an
+//! `EvmSigner` for the client's chain id and submits them. Pending
+//! transactions can be replaced with a faster or cancelling one.
+
+use serde::{Deserialize, Serialize};
+use shared::{Error, Result};
+use solana_sdk::keccak;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::evm_client::{is_rejection, BlockTag, EvmCallRequest, EvmClient};
+use crate::evm_keystore::EvmSigner;
+
+/// EIP-2718 type of EIP-1559 transactions
+const EIP1559_TX_TYPE: u8 = 0x02;
+/// ERC-20 `transfer(address,uint256)` selector
+const TRANSFER_SELECTOR: &str = "a9059cbb";
+/// ERC-20 `approve(address,uint256)` selector
+const APPROVE_SELECTOR: &str = "095ea7b3";
+/// Gas used by a plain value transfer
+const NATIVE_TRANSFER_GAS: u64 = 21_000;
+/// Minimum fee increase nodes accept for a replacement transaction
+const REPLACEMENT_BUMP_PERCENT: u128 = 10;
+/// Blocks of fee history sampled when suggesting fees
+const FEE_HISTORY_BLOCKS: u64 = 10;
+/// How long the node's pending count may stay below the local counter
+/// without moving before the local counter is taken to be ahead of a gap
+const NONCE_RESYNC_AFTER: Duration = Duration::from_secs(120);
+
+/// How aggressively to price a transaction's fees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeeStrategy {
+    /// 10th percentile priority fee of recent blocks
+    Slow,
+    /// Median priority fee of recent blocks
+    #[default]
+    Standard,
+    /// 90th percentile priority fee of recent blocks
+    Fast,
+    /// Fixed fees in wei
+    Fixed {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+/// EIP-1559 fee caps in wei
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip1559Fees {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl Eip1559Fees {
+    /// Smallest fees a node accepts to replace a transaction paying `self`
+    pub fn bumped(&self) -> Self {
+        let bump = |fee: u128| fee + (fee * REPLACEMENT_BUMP_PERCENT).div_ceil(100).max(1);
+        Self {
+            max_fee_per_gas: bump(self.max_fee_per_gas),
+            max_priority_fee_per_gas: bump(self.max_priority_fee_per_gas),
+        }
+    }
+
+    /// The higher of each fee cap
+    fn max(self, other: Self) -> Self {
+        Self {
+            max_fee_per_gas: self.max_fee_per_gas.max(other.max_fee_per_gas),
+            max_priority_fee_per_gas: self
+                .max_priority_fee_per_gas
+                .max(other.max_priority_fee_per_gas),
+        }
+    }
+}
+
+/// Unsigned EIP-1559 (type 2) transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    /// Lowercase `0x` address of the signer
+    pub from: String,
+    pub nonce: u64,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub gas_limit: u64,
+    /// `None` for contract creations
+    pub to: Option<String>,
+    /// Native value in wei
+    pub value: u128,
+    /// 0x-prefixed calldata
+    pub data: String,
+}
+
+impl Eip1559Transaction {
+    pub fn fees(&self) -> Eip1559Fees {
+        Eip1559Fees {
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+        }
+    }
+
+    /// Keccak-256 of the type-prefixed unsigned payload, which is what gets signed
+    pub fn signing_hash(&self) -> Result<[u8; 32]> {
+        let payload = rlp_list(&self.rlp_fields()?);
+        Ok(keccak::hashv(&[&[EIP1559_TX_TYPE], &payload]).to_bytes())
+    }
+
+    /// Sign the transaction
+    ///
+    /// The signer must be the transaction's `from` address; the chain id is
+    /// part of the signed payload, so the result cannot be replayed elsewhere.
+    pub fn sign(&self, signer: &EvmSigner) -> Result<SignedEvmTransaction> {
+        if signer.address() != self.from {
+            return Err(Error::Validation(format!(
+                "Transaction from {} cannot be signed by {}",
+                self.from,
+                signer.address()
+            )));
+        }
+
+        let (r, s, y_parity) = signer.sign_hash(&self.signing_hash()?);
+
+        let mut fields = self.rlp_fields()?;
+        fields.push(rlp_uint(u128::from(y_parity)));
+        fields.push(rlp_bytes(trim_leading_zeros(&r)));
+        fields.push(rlp_bytes(trim_leading_zeros(&s)));
+
+        let mut raw = vec![EIP1559_TX_TYPE];
+        raw.extend(rlp_list(&fields));
+
+        Ok(SignedEvmTransaction {
+            hash: format!("0x{}", hex::encode(keccak::hash(&raw).to_bytes())),
+            raw_transaction: format!("0x{}", hex::encode(raw)),
+            transaction: self.clone(),
+        })
+    }
+
+    /// RLP items of the unsigned payload, in EIP-1559 order
+    fn rlp_fields(&self) -> Result<Vec<Vec<u8>>> {
+        let to = match &self.to {
+            Some(to) => decode_address(to)?.to_vec(),
+            None => Vec::new(),
+        };
+        let data = hex::decode(self.data.trim_start_matches("0x"))
+            .map_err(|e| Error::Validation(format!("Invalid calldata: {}", e)))?;
+
+        Ok(vec![
+            rlp_uint(u128::from(self.chain_id)),
+            rlp_uint(u128::from(self.nonce)),
+            rlp_uint(self.max_priority_fee_per_gas),
+            rlp_uint(self.max_fee_per_gas),
+            rlp_uint(u128::from(self.gas_limit)),
+            rlp_bytes(&to),
+            rlp_uint(self.value),
+            rlp_bytes(&data),
+            // Empty access list
+            rlp_list(&[]),
+        ])
+    }
+}
+
+/// A signed transaction ready for `eth_sendRawTransaction`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedEvmTransaction {
+    /// 0x-prefixed transaction hash
+    pub hash: String,
+    /// 0x-prefixed EIP-2718 envelope
+    pub raw_transaction: String,
+    pub transaction: Eip1559Transaction,
+}
+
+/// Calldata for ERC-20 `transfer(to, amount)`
+pub fn erc20_transfer_calldata(to: &str, amount: u128) -> Result<String> {
+    Ok(format!(
+        "0x{}{}{:064x}",
+        TRANSFER_SELECTOR,
+        hex::encode(encode_address_word(to)?),
+        amount
+    ))
+}
+
+/// Calldata for ERC-20 `approve(spender, amount)`
+pub fn erc20_approve_calldata(spender: &str, amount: u128) -> Result<String> {
+    Ok(format!(
+        "0x{}{}{:064x}",
+        APPROVE_SELECTOR,
+        hex::encode(encode_address_word(spender)?),
+        amount
+    ))
+}
+
+/// Hands out nonces per sending address, counting transactions this process
+/// has sent that the node may not have seen yet
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next_nonces: Mutex<HashMap<String, LocalNonce>>,
+}
+
+/// Local counter for one sending address
+#[derive(Debug, Clone, Copy)]
+struct LocalNonce {
+    next: u64,
+    /// Last pending count seen from the node and when it last moved
+    on_chain: u64,
+    on_chain_since: Instant,
+}
+
+impl LocalNonce {
+    fn new(on_chain: u64, now: Instant) -> Self {
+        Self {
+            next: on_chain,
+            on_chain,
+            on_chain_since: now,
+        }
+    }
+
+    /// Reserve a nonce given the node's current pending count
+    ///
+    /// The local counter runs ahead of the node while transactions are in
+    /// flight. If the node's count sits below it without moving for
+    /// `NONCE_RESYNC_AFTER`, the missing transactions were dropped or never
+    /// sent, and the node's count is used again so the gap gets filled.
+    fn reserve(&mut self, on_chain: u64, now: Instant) -> u64 {
+        if on_chain != self.on_chain {
+            self.on_chain = on_chain;
+            self.on_chain_since = now;
+        }
+
+        let stalled = now.duration_since(self.on_chain_since) >= NONCE_RESYNC_AFTER;
+        let nonce = if on_chain >= self.next || stalled {
+            on_chain
+        } else {
+            self.next
+        };
+        if stalled && nonce < self.next {
+            warn!(
+                "Pending count {} has not reached local nonce {} in {:?}, re-syncing",
+                on_chain, self.next, NONCE_RESYNC_AFTER
+            );
+            self.on_chain_since = now;
+        }
+
+        self.next = nonce + 1;
+        nonce
+    }
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve the next nonce for `address`
+    ///
+    /// The node's pending transaction count is the floor, so transactions
+    /// sent from elsewhere are accounted for.
+    pub async fn next_nonce(&self, client: &EvmClient, address: &str) -> Result<u64> {
+        let address = client.validate_address(address)?;
+        let mut next_nonces = self.next_nonces.lock().await;
+
+        let on_chain = client
+            .get_transaction_count(&address, BlockTag::Pending)
+            .await?;
+        let now = Instant::now();
+        let nonce = next_nonces
+            .entry(address)
+            .or_insert_with(|| LocalNonce::new(on_chain, now))
+            .reserve(on_chain, now);
+
+        Ok(nonce)
+    }
+
+    /// Give back a nonce whose transaction was never broadcast
+    ///
+    /// Only the most recently reserved nonce can be given back. Any other
+    /// leaves a gap that is closed once the node's pending count has stayed
+    /// below the local counter for `NONCE_RESYNC_AFTER`.
+    pub async fn release(&self, address: &str, nonce: u64) {
+        let mut next_nonces = self.next_nonces.lock().await;
+        if let Some(local) = next_nonces.get_mut(&address.to_lowercase()) {
+            if local.next == nonce + 1 {
+                local.next = nonce;
+            }
+        }
+    }
+
+    /// Forget the local counter for `address`, deferring to the node again
+    pub async fn reset(&self, address: &str) {
+        self.next_nonces.lock().await.remove(&address.to_lowercase());
+    }
+}
+
+/// Whether the node refused a transaction because of its nonce, meaning the
+/// local counter no longer matches the node
+fn is_nonce_mismatch(error: &Error) -> bool {
+    match error {
+        Error::EvmRpc(message) => {
+            let message = message.to_lowercase();
+            message.contains("nonce too high") || message.contains("nonce too low")
+        }
+        _ => false,
+    }
+}
+
+/// Builds, signs and sends EIP-1559 transactions on one EVM chain
+pub struct EvmTransactionBuilder {
+    client: Arc<EvmClient>,
+    nonces: Arc<NonceManager>,
+    fee_strategy: FeeStrategy,
+    gas_buffer_percent: u64,
+}
+
+impl EvmTransactionBuilder {
+    pub fn new(client: Arc<EvmClient>) -> Self {
+        Self {
+            client,
+            nonces: Arc::new(NonceManager::new()),
+            fee_strategy: FeeStrategy::default(),
+            gas_buffer_percent: 20,
+        }
+    }
+
+    /// Share a nonce manager with other builders sending from the same addresses
+    pub fn with_nonce_manager(mut self, nonces: Arc<NonceManager>) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    pub fn with_fee_strategy(mut self, fee_strategy: FeeStrategy) -> Self {
+        self.fee_strategy = fee_strategy;
+        self
+    }
+
+    /// Headroom added on top of `eth_estimateGas`, in percent
+    pub fn with_gas_buffer_percent(mut self, gas_buffer_percent: u64) -> Self {
+        self.gas_buffer_percent = gas_buffer_percent;
+        self
+    }
+
+    pub fn client(&self) -> &EvmClient {
+        &self.client
+    }
+
+    /// Build a native token transfer
+    pub async fn native_transfer(&self, from: &str, to: &str, value: u128) -> Result<Eip1559Transaction> {
+        self.build(from, Some(to), value, "0x".to_string()).await
+    }
+
+    /// Build an ERC-20 `transfer` of `amount` base units of `token`
+    pub async fn erc20_transfer(
+        &self,
+        from: &str,
+        token: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<Eip1559Transaction> {
+        let data = erc20_transfer_calldata(to, amount)?;
+        self.build(from, Some(token), 0, data).await
+    }
+
+    /// Build an ERC-20 `approve` letting `spender` move `amount` of `token`
+    pub async fn erc20_approve(
+        &self,
+        from: &str,
+        token: &str,
+        spender: &str,
+        amount: u128,
+    ) -> Result<Eip1559Transaction> {
+        let data = erc20_approve_calldata(spender, amount)?;
+        self.build(from, Some(token), 0, data).await
+    }
+
+    /// Build a transaction with the next nonce, an estimated gas limit and
+    /// fees from the builder's strategy
+    pub async fn build(
+        &self,
+        from: &str,
+        to: Option<&str>,
+        value: u128,
+        data: String,
+    ) -> Result<Eip1559Transaction> {
+        let from = self.client.validate_address(from)?;
+        let to = to.map(|to| self.client.validate_address(to)).transpose()?;
+
+        let gas_limit = self
+            .estimate_gas_limit(&EvmCallRequest {
+                from: Some(from.clone()),
+                to: to.clone(),
+                value: Some(value),
+                data: Some(data.clone()),
+            })
+            .await?;
+        let fees = self.suggest_fees().await?;
+        let nonce = self.nonces.next_nonce(&self.client, &from).await?;
+
+        debug!(
+            "Built {} transaction from {} with nonce {} and gas limit {}",
+            self.client.chain().name(),
+            from,
+            nonce,
+            gas_limit
+        );
+
+        Ok(Eip1559Transaction {
+            chain_id: self.client.chain().chain_id(),
+            from,
+            nonce,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            gas_limit,
+            to,
+            value,
+            data,
+        })
+    }
+
+    /// Replace a pending transaction with the same one at higher fees
+    pub async fn speed_up(&self, pending: &Eip1559Transaction) -> Result<Eip1559Transaction> {
+        let fees = self.replacement_fees(pending).await?;
+
+        Ok(Eip1559Transaction {
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            ..pending.clone()
+        })
+    }
+
+    /// Replace a pending transaction with an empty self-transfer at higher fees
+    pub async fn cancel(&self, pending: &Eip1559Transaction) -> Result<Eip1559Transaction> {
+        let fees = self.replacement_fees(pending).await?;
+
+        Ok(Eip1559Transaction {
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            gas_limit: NATIVE_TRANSFER_GAS,
+            to: Some(pending.from.clone()),
+            value: 0,
+            data: "0x".to_string(),
+            ..pending.clone()
+        })
+    }
+
+    /// Sign a transaction and submit it to the chain
+    ///
+    /// A submission that fails is checked against the node's pending
+    /// transactions, as it may have been broadcast before the response was
+    /// lost. The nonce is only handed back when the node rejected the
+    /// transaction; otherwise it stays reserved so it is never reused for a
+    /// different transaction. A nonce the node refuses as too high or too low
+    /// drops the local counter so the next build starts from the node's count.
+    pub async fn sign_and_send(
+        &self,
+        transaction: &Eip1559Transaction,
+        signer: &EvmSigner,
+    ) -> Result<SignedEvmTransaction> {
+        let chain = self.client.chain();
+        if transaction.chain_id != chain.chain_id() {
+            return Err(Error::Validation(format!(
+                "Transaction for chain id {} cannot be sent to {} (chain id {})",
+                transaction.chain_id,
+                chain.name(),
+                chain.chain_id()
+            )));
+        }
+
+        let signed = transaction.sign(signer)?;
+
+        match self.client.submit_transaction(&signed.raw_transaction).await {
+            Ok(hash) => {
+                if hash.to_lowercase() != signed.hash {
+                    warn!("Node reported hash {} for transaction {}", hash, signed.hash);
+                }
+                info!(
+                    "Sent {} transaction {} from {} with nonce {}",
+                    chain.name(),
+                    signed.hash,
+                    transaction.from,
+                    transaction.nonce
+                );
+                Ok(signed)
+            }
+            Err(e) => match self.client.get_transaction_input(&signed.hash).await {
+                Ok(Some(_)) => {
+                    warn!(
+                        "Submitting {} transaction {} failed but the node has it: {}",
+                        chain.name(),
+                        signed.hash,
+                        e
+                    );
+                    Ok(signed)
+                }
+                Ok(None) if is_nonce_mismatch(&e) => {
+                    warn!(
+                        "Node refused nonce {} of {}, re-syncing with its pending count: {}",
+                        transaction.nonce, transaction.from, e
+                    );
+                    self.nonces.reset(&transaction.from).await;
+                    Err(e)
+                }
+                Ok(None) if is_rejection(&e) => {
+                    self.nonces.release(&transaction.from, transaction.nonce).await;
+                    Err(e)
+                }
+                _ => {
+                    warn!(
+                        "Keeping nonce {} of {} reserved: transaction {} may have been broadcast",
+                        transaction.nonce, transaction.from, signed.hash
+                    );
+                    Err(e)
+                }
+            },
+        }
+    }
+
+    /// Fee caps for the configured strategy
+    ///
+    /// The max fee leaves room for the base fee to double before the
+    /// transaction stops being includable.
+    pub async fn suggest_fees(&self) -> Result<Eip1559Fees> {
+        let percentile = match self.fee_strategy {
+            FeeStrategy::Slow => 10.0,
+            FeeStrategy::Standard => 50.0,
+            FeeStrategy::Fast => 90.0,
+            FeeStrategy::Fixed {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                return Ok(Eip1559Fees {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                })
+            }
+        };
+
+        let history = self
+            .client
+            .get_fee_history(FEE_HISTORY_BLOCKS, &[percentile])
+            .await?;
+        let base_fee = history
+            .next_base_fee()
+            .ok_or_else(|| Error::EvmRpc("Fee history has no base fee".to_string()))?;
+
+        // Median across blocks of the sampled percentile
+        let mut rewards: Vec<u128> = history
+            .reward
+            .iter()
+            .filter_map(|rewards| rewards.first().copied())
+            .collect();
+        rewards.sort_unstable();
+        let priority_fee = rewards.get(rewards.len() / 2).copied().unwrap_or(0);
+
+        Ok(Eip1559Fees {
+            max_fee_per_gas: base_fee.saturating_mul(2).saturating_add(priority_fee),
+            max_priority_fee_per_gas: priority_fee,
+        })
+    }
+
+    /// Current fees, raised to at least the minimum bump over `pending`
+    async fn replacement_fees(&self, pending: &Eip1559Transaction) -> Result<Eip1559Fees> {
+        if pending.chain_id != self.client.chain().chain_id() {
+            return Err(Error::Validation(format!(
+                "Transaction is for chain id {}, not {}",
+                pending.chain_id,
+                self.client.chain().chain_id()
+            )));
+        }
+
+        Ok(self.suggest_fees().await?.max(pending.fees().bumped()))
+    }
+
+    /// `eth_estimateGas` plus the configured buffer
+    async fn estimate_gas_limit(&self, request: &EvmCallRequest) -> Result<u64> {
+        let estimate = self.client.estimate_gas(request).await?;
+        Ok(estimate.saturating_add(estimate.saturating_mul(self.gas_buffer_percent) / 100))
+    }
+}
+
+fn decode_address(address: &str) -> Result<[u8; 20]> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .ok()
+        .filter(|bytes| bytes.len() == 20)
+        .ok_or_else(|| Error::InvalidWalletAddress(address.to_string()))?;
+
+    let mut decoded = [0u8; 20];
+    decoded.copy_from_slice(&bytes);
+    Ok(decoded)
+}
+
+/// Left-pad an address to a 32-byte ABI word
+fn encode_address_word(address: &str) -> Result<[u8; 32]> {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&decode_address(address)?);
+    Ok(word)
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// RLP length prefix for a string (`offset` 0x80) or list (`offset` 0xc0)
+fn rlp_length_prefix(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+
+    let length_bytes = length.to_be_bytes();
+    let length_bytes = trim_leading_zeros(&length_bytes);
+    let mut prefix = vec![offset + 55 + length_bytes.len() as u8];
+    prefix.extend_from_slice(length_bytes);
+    prefix
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+
+    let mut encoded = rlp_length_prefix(bytes.len(), 0x80);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+/// Integers are encoded as big-endian bytes without leading zeros
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_bytes(trim_leading_zeros(&value.to_be_bytes()))
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = rlp_length_prefix(payload.len(), 0xc0);
+    encoded.extend(payload);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::secp256k1_recover::secp256k1_recover;
+
+    const SECRET: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const RECIPIENT: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+
+    fn transfer(signer: &EvmSigner) -> Eip1559Transaction {
+        Eip1559Transaction {
+            chain_id: 137,
+            from: signer.address().to_string(),
+            nonce: 9,
+            max_priority_fee_per_gas: 30_000_000_000,
+            max_fee_per_gas: 90_000_000_000,
+            gas_limit: 21_000,
+            to: Some(RECIPIENT.to_string()),
+            value: 1_000_000_000_000_000_000,
+            data: "0x".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rlp_encoding() {
+        // Examples from the Ethereum RLP specification
+        assert_eq!(rlp_bytes(b"dog"), hex::decode("83646f67").unwrap());
+        assert_eq!(rlp_bytes(b""), vec![0x80]);
+        assert_eq!(rlp_uint(0), vec![0x80]);
+        assert_eq!(rlp_uint(15), vec![0x0f]);
+        assert_eq!(rlp_uint(1024), vec![0x82, 0x04, 0x00]);
+        assert_eq!(rlp_list(&[]), vec![0xc0]);
+        assert_eq!(
+            rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]),
+            hex::decode("c88363617483646f67").unwrap()
+        );
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let encoded = rlp_bytes(lorem);
+        assert_eq!(&encoded[..2], &[0xb8, 0x38]);
+        assert_eq!(&encoded[2..], lorem);
+    }
+
+    #[test]
+    fn test_erc20_calldata() {
+        assert_eq!(
+            erc20_transfer_calldata(RECIPIENT, 1_000_000).unwrap(),
+            format!(
+                "0xa9059cbb000000000000000000000000{}{:064x}",
+                &RECIPIENT[2..],
+                1_000_000
+            )
+        );
+        assert!(erc20_approve_calldata(RECIPIENT, u128::MAX)
+            .unwrap()
+            .starts_with("0x095ea7b3"));
+        assert!(erc20_transfer_calldata("0x1234", 1).is_err());
+    }
+
+    #[test]
+    fn test_signature_recovers_sender() {
+        let signer = EvmSigner::from_hex(SECRET).unwrap();
+        let transaction = transfer(&signer);
+        let signed = transaction.sign(&signer).unwrap();
+
+        let raw = hex::decode(&signed.raw_transaction[2..]).unwrap();
+        assert_eq!(raw[0], EIP1559_TX_TYPE);
+        assert_eq!(signed.hash, format!("0x{}", hex::encode(keccak::hash(&raw).to_bytes())));
+
+        // Signing is deterministic (RFC 6979)
+        assert_eq!(transaction.sign(&signer).unwrap(), signed);
+
+        let (r, s, y_parity) = signer.sign_hash(&transaction.signing_hash().unwrap());
+        let signature = [r, s].concat();
+        let public_key = secp256k1_recover(&transaction.signing_hash().unwrap(), y_parity, &signature).unwrap();
+        let address = keccak::hash(&public_key.to_bytes()).to_bytes();
+        assert_eq!(format!("0x{}", hex::encode(&address[12..])), signer.address());
+    }
+
+    #[test]
+    fn test_chain_id_is_signed() {
+        let signer = EvmSigner::from_hex(SECRET).unwrap();
+        let polygon = transfer(&signer);
+        let ethereum = Eip1559Transaction {
+            chain_id: 1,
+            ..polygon.clone()
+        };
+
+        assert_ne!(polygon.signing_hash().unwrap(), ethereum.signing_hash().unwrap());
+        assert_ne!(
+            polygon.sign(&signer).unwrap().hash,
+            ethereum.sign(&signer).unwrap().hash
+        );
+    }
+
+    #[test]
+    fn test_sign_rejects_other_sender() {
+        let signer = EvmSigner::from_hex(SECRET).unwrap();
+        let transaction = Eip1559Transaction {
+            from: RECIPIENT.to_string(),
+            ..transfer(&signer)
+        };
+
+        assert!(transaction.sign(&signer).is_err());
+    }
+
+    #[test]
+    fn test_replacement_fee_bump() {
+        let fees = Eip1559Fees {
+            max_fee_per_gas: 100,
+            max_priority_fee_per_gas: 1,
+        };
+        let bumped = fees.bumped();
+
+        assert_eq!(bumped.max_fee_per_gas, 110);
+        // Never less than one wei more
+        assert_eq!(bumped.max_priority_fee_per_gas, 2);
+    }
+
+    #[test]
+    fn test_local_nonce_resyncs_after_gap() {
+        let start = Instant::now();
+        let mut local = LocalNonce::new(5, start);
+
+        // Counter runs ahead of the node while transactions are in flight
+        assert_eq!(local.reserve(5, start), 5);
+        assert_eq!(local.reserve(5, start), 6);
+        assert_eq!(local.reserve(6, start + Duration::from_secs(10)), 7);
+
+        // Nonce 6 was dropped, so the node stays at 6 below the counter
+        let later = start + Duration::from_secs(10) + NONCE_RESYNC_AFTER;
+        assert_eq!(local.reserve(6, later - Duration::from_secs(1)), 8);
+        assert_eq!(local.reserve(6, later), 6);
+        assert_eq!(local.reserve(7, later), 7);
+    }
+
+    #[test]
+    fn test_nonce_mismatch_detection() {
+        assert!(is_nonce_mismatch(&Error::EvmRpc("RPC error: nonce too high".to_string())));
+        assert!(is_nonce_mismatch(&Error::EvmRpc("RPC error: Nonce too low".to_string())));
+        assert!(!is_nonce_mismatch(&Error::EvmRpc(
+            "RPC error: insufficient funds for gas".to_string()
+        )));
+    }
+}
@@ -1,13 +1,22 @@
+pub mod chain_registry;
 pub mod circuit_breaker;
 pub mod client;
 pub mod evm_client;
+pub mod evm_keystore;
+pub mod evm_transaction;
 pub mod multi_chain;
 pub mod retry;
 pub mod types;
 
+pub use chain_registry::{Blockchain, ChainConfig, ChainKind, ChainRegistry, NativeToken};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
 pub use client::SolanaClient;
-pub use evm_client::{EvmClient, EvmChain};
-pub use multi_chain::{Blockchain, BlockchainClientRef, BlockchainConfig, MultiChainClient};
+pub use evm_client::{BlockTag, EvmCallRequest, EvmChain, EvmClient, EvmTransactionReceipt, FeeHistory};
+pub use evm_keystore::{EvmKeystore, EvmSigner};
+pub use evm_transaction::{
+    Eip1559Fees, Eip1559Transaction, EvmTransactionBuilder, FeeStrategy, NonceManager,
+    SignedEvmTransaction,
+};
+pub use multi_chain::{BlockchainClientRef, MultiChainClient};
 pub use retry::{retry_with_backoff, RetryConfig};
 pub use types::*;
@@ -1,72 +1,36 @@
-use shared::Result;
+use shared::{Error, Result};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::info;
 
+use crate::chain_registry::{Blockchain, ChainRegistry};
 use crate::circuit_breaker::CircuitBreakerConfig;
-use crate::evm_client::{EvmChain, EvmClient};
+use crate::evm_client::{BlockTag, EvmCallRequest, EvmChain, EvmClient, EvmTransactionReceipt, FeeHistory};
 use crate::retry::RetryConfig;
+use crate::types::{TokenBalance, TransactionStatus};
 use crate::SolanaClient;
 
-/// Blockchain type identifier
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
-pub enum Blockchain {
-    Solana,
-    Ethereum,
-    BinanceSmartChain,
-    Polygon,
-}
-
-impl Blockchain {
-    pub fn name(&self) -> &'static str {
-        match self {
-            Blockchain::Solana => "Solana",
-            Blockchain::Ethereum => "Ethereum",
-            Blockchain::BinanceSmartChain => "Binance Smart Chain",
-            Blockchain::Polygon => "Polygon",
-        }
-    }
-
-    pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "solana" => Blockchain::Solana,
-            "ethereum" => Blockchain::Ethereum,
-            "binancesmartchain" | "bsc" | "binance smart chain" => Blockchain::BinanceSmartChain,
-            "polygon" => Blockchain::Polygon,
-            _ => Blockchain::Solana, // Default fallback
-        }
-    }
-}
-
-/// Configuration for a blockchain RPC endpoint
-#[derive(Debug, Clone)]
-pub struct BlockchainConfig {
-    pub blockchain: Blockchain,
-    pub primary_rpc_url: String,
-    pub fallback_rpc_url: Option<String>,
-}
-
 /// Multi-chain blockchain client manager
 /// 
-/// Provides unified access to multiple blockchain clients (Solana, Ethereum, BSC, Polygon)
-/// with automatic failover, circuit breakers, and retry logic.
+/// Provides unified access to a Solana client and a client per configured EVM
+/// chain, with automatic failover, circuit breakers, and retry logic.
 pub struct MultiChainClient {
+    registry: ChainRegistry,
     solana_client: Option<SolanaClient>,
-    ethereum_client: Option<EvmClient>,
-    bsc_client: Option<EvmClient>,
-    polygon_client: Option<EvmClient>,
+    evm_clients: Vec<Arc<EvmClient>>,
     retry_config: RetryConfig,
     circuit_breaker_config: CircuitBreakerConfig,
 }
 
 impl MultiChainClient {
-    /// Create a new multi-chain client with default configurations
+    /// Create a new multi-chain client over the built-in chains, with no
+    /// clients configured
     pub fn new() -> Self {
         info!("Initializing multi-chain blockchain client");
         Self {
+            registry: ChainRegistry::default(),
             solana_client: None,
-            ethereum_client: None,
-            bsc_client: None,
-            polygon_client: None,
+            evm_clients: Vec::new(),
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
         }
@@ -79,15 +43,30 @@ impl MultiChainClient {
     ) -> Self {
         info!("Initializing multi-chain blockchain client with custom config");
         Self {
-            solana_client: None,
-            ethereum_client: None,
-            bsc_client: None,
-            polygon_client: None,
             retry_config,
             circuit_breaker_config,
+            ..Self::new()
         }
     }
 
+    /// Use `registry` and configure a client for each chain in it that has
+    /// RPC endpoints
+    pub fn with_registry(mut self, registry: ChainRegistry) -> Self {
+        for chain in registry.chains() {
+            let Some(primary_rpc) = chain.rpc_urls.first().cloned() else {
+                continue;
+            };
+            let fallback_rpc = chain.rpc_urls.get(1).cloned();
+
+            self = match chain.evm_chain() {
+                Some(evm_chain) => self.with_evm_chain(evm_chain, primary_rpc, fallback_rpc),
+                None => self.with_solana(primary_rpc, fallback_rpc),
+            };
+        }
+        self.registry = registry;
+        self
+    }
+
     /// Configure Solana client
     pub fn with_solana(mut self, primary_rpc: String, fallback_rpc: Option<String>) -> Self {
         info!("Configuring Solana client");
@@ -100,64 +79,36 @@ impl MultiChainClient {
         self
     }
 
-    /// Configure Ethereum client
-    pub fn with_ethereum(mut self, primary_rpc: String, fallback_rpc: Option<String>) -> Self {
-        info!("Configuring Ethereum client");
-        self.ethereum_client = Some(EvmClient::new_with_config(
-            EvmChain::Ethereum,
-            primary_rpc,
-            fallback_rpc,
-            self.retry_config.clone(),
-            self.circuit_breaker_config.clone(),
-        ));
-        self
-    }
-
-    /// Configure Binance Smart Chain client
-    pub fn with_bsc(mut self, primary_rpc: String, fallback_rpc: Option<String>) -> Self {
-        info!("Configuring Binance Smart Chain client");
-        self.bsc_client = Some(EvmClient::new_with_config(
-            EvmChain::BinanceSmartChain,
-            primary_rpc,
-            fallback_rpc,
-            self.retry_config.clone(),
-            self.circuit_breaker_config.clone(),
-        ));
-        self
+    /// Configure the client of a registered EVM chain
+    pub fn with_evm(
+        self,
+        blockchain: &Blockchain,
+        primary_rpc: String,
+        fallback_rpc: Option<String>,
+    ) -> Result<Self> {
+        let evm_chain = self.registry.require(blockchain)?.evm_chain().ok_or_else(|| {
+            Error::Validation(format!("{} is not an EVM chain", blockchain))
+        })?;
+        Ok(self.with_evm_chain(evm_chain, primary_rpc, fallback_rpc))
     }
 
-    /// Configure Polygon client
-    pub fn with_polygon(mut self, primary_rpc: String, fallback_rpc: Option<String>) -> Self {
-        info!("Configuring Polygon client");
-        self.polygon_client = Some(EvmClient::new_with_config(
-            EvmChain::Polygon,
+    fn with_evm_chain(mut self, chain: EvmChain, primary_rpc: String, fallback_rpc: Option<String>) -> Self {
+        info!("Configuring {} client", chain.name());
+        self.evm_clients
+            .retain(|client| client.chain().blockchain() != chain.blockchain());
+        self.evm_clients.push(Arc::new(EvmClient::new_with_config(
+            chain,
             primary_rpc,
             fallback_rpc,
             self.retry_config.clone(),
             self.circuit_breaker_config.clone(),
-        ));
+        )));
         self
     }
 
-    /// Configure multiple blockchains from a list of configs
-    pub fn with_configs(mut self, configs: Vec<BlockchainConfig>) -> Self {
-        for config in configs {
-            match config.blockchain {
-                Blockchain::Solana => {
-                    self = self.with_solana(config.primary_rpc_url, config.fallback_rpc_url);
-                }
-                Blockchain::Ethereum => {
-                    self = self.with_ethereum(config.primary_rpc_url, config.fallback_rpc_url);
-                }
-                Blockchain::BinanceSmartChain => {
-                    self = self.with_bsc(config.primary_rpc_url, config.fallback_rpc_url);
-                }
-                Blockchain::Polygon => {
-                    self = self.with_polygon(config.primary_rpc_url, config.fallback_rpc_url);
-                }
-            }
-        }
-        self
+    /// The chains this client knows about
+    pub fn registry(&self) -> &ChainRegistry {
+        &self.registry
     }
 
     /// Get the Solana client
@@ -165,58 +116,55 @@ impl MultiChainClient {
         self.solana_client.as_ref()
     }
 
-    /// Get the Ethereum client
-    pub fn ethereum(&self) -> Option<&EvmClient> {
-        self.ethereum_client.as_ref()
-    }
-
-    /// Get the Binance Smart Chain client
-    pub fn bsc(&self) -> Option<&EvmClient> {
-        self.bsc_client.as_ref()
+    /// Get the client of an EVM chain
+    pub fn evm(&self, blockchain: &Blockchain) -> Option<&EvmClient> {
+        self.evm_shared(blockchain).map(Arc::as_ref)
     }
 
-    /// Get the Polygon client
-    pub fn polygon(&self) -> Option<&EvmClient> {
-        self.polygon_client.as_ref()
+    /// Get a shared handle to the client of an EVM chain, e.g. for an
+    /// `EvmTransactionBuilder`
+    pub fn evm_shared(&self, blockchain: &Blockchain) -> Option<&Arc<EvmClient>> {
+        self.evm_clients
+            .iter()
+            .find(|client| client.chain().blockchain() == blockchain)
     }
 
     /// Get a client for a specific blockchain
-    pub fn get_client(&self, blockchain: Blockchain) -> Option<BlockchainClientRef<'_>> {
-        match blockchain {
-            Blockchain::Solana => self
-                .solana_client
-                .as_ref()
-                .map(BlockchainClientRef::Solana),
-            Blockchain::Ethereum => self
-                .ethereum_client
-                .as_ref()
-                .map(BlockchainClientRef::Evm),
-            Blockchain::BinanceSmartChain => {
-                self.bsc_client.as_ref().map(BlockchainClientRef::Evm)
-            }
-            Blockchain::Polygon => self
-                .polygon_client
-                .as_ref()
-                .map(BlockchainClientRef::Evm),
+    pub fn get_client(&self, blockchain: &Blockchain) -> Option<BlockchainClientRef<'_>> {
+        if blockchain.is_solana() {
+            self.solana().map(BlockchainClientRef::Solana)
+        } else {
+            self.evm(blockchain).map(BlockchainClientRef::Evm)
         }
     }
 
+    /// Get the outcome of a transaction once it has the confirmations the
+    /// chain's registry entry requires
+    ///
+    /// Returns `None` while the transaction has not landed or is not final.
+    pub async fn get_final_transaction_status(
+        &self,
+        blockchain: &Blockchain,
+        tx_id: &str,
+    ) -> Result<Option<TransactionStatus>> {
+        let confirmations = self.registry.require(blockchain)?.confirmations;
+        let client = self.get_client(blockchain).ok_or_else(|| {
+            Error::Internal(format!("Blockchain client not configured for {}", blockchain))
+        })?;
+        client.get_final_transaction_status(tx_id, confirmations).await
+    }
+
     /// Get list of configured blockchains
     pub fn configured_blockchains(&self) -> Vec<Blockchain> {
-        let mut blockchains = Vec::new();
-        if self.solana_client.is_some() {
-            blockchains.push(Blockchain::Solana);
-        }
-        if self.ethereum_client.is_some() {
-            blockchains.push(Blockchain::Ethereum);
-        }
-        if self.bsc_client.is_some() {
-            blockchains.push(Blockchain::BinanceSmartChain);
-        }
-        if self.polygon_client.is_some() {
-            blockchains.push(Blockchain::Polygon);
-        }
-        blockchains
+        self.solana_client
+            .iter()
+            .map(|_| Blockchain::SOLANA)
+            .chain(
+                self.evm_clients
+                    .iter()
+                    .map(|client| client.chain().blockchain().clone()),
+            )
+            .collect()
     }
 
     /// Health check for all configured blockchain clients
@@ -224,19 +172,11 @@ impl MultiChainClient {
         let mut results = HashMap::new();
 
         if let Some(client) = &self.solana_client {
-            results.insert(Blockchain::Solana, client.health_check().await);
+            results.insert(Blockchain::SOLANA, client.health_check().await);
         }
 
-        if let Some(client) = &self.ethereum_client {
-            results.insert(Blockchain::Ethereum, client.health_check().await);
-        }
-
-        if let Some(client) = &self.bsc_client {
-            results.insert(Blockchain::BinanceSmartChain, client.health_check().await);
-        }
-
-        if let Some(client) = &self.polygon_client {
-            results.insert(Blockchain::Polygon, client.health_check().await);
+        for client in &self.evm_clients {
+            results.insert(client.chain().blockchain().clone(), client.health_check().await);
         }
 
         results
@@ -280,27 +220,172 @@ impl<'a> BlockchainClientRef<'a> {
             BlockchainClientRef::Evm(client) => client.validate_address(address),
         }
     }
+
+    /// Get the native token balance of an address (lamports on Solana, wei on EVM chains)
+    pub async fn get_native_balance(&self, address: &str) -> Result<u128> {
+        match self {
+            BlockchainClientRef::Solana(client) => {
+                client.get_sol_balance(address).await.map(u128::from)
+            }
+            BlockchainClientRef::Evm(client) => client.get_native_balance(address).await,
+        }
+    }
+
+    /// Get the balances `owner` holds of each token (mint or contract address)
+    ///
+    /// On Solana an empty `tokens` list returns every SPL token held; on EVM
+    /// chains holdings cannot be enumerated, so it returns nothing.
+    pub async fn get_token_balances(
+        &self,
+        owner: &str,
+        tokens: &[String],
+    ) -> Result<Vec<TokenBalance>> {
+        match self {
+            BlockchainClientRef::Solana(client) => {
+                let accounts = client.get_token_accounts(owner).await?;
+
+                // An owner can hold several accounts for the same mint
+                let mut balances: Vec<TokenBalance> = Vec::new();
+                for account in accounts {
+                    if !tokens.is_empty() && !tokens.contains(&account.mint) {
+                        continue;
+                    }
+                    match balances.iter_mut().find(|b| b.token == account.mint) {
+                        Some(balance) => balance.amount += u128::from(account.amount),
+                        None => balances.push(TokenBalance {
+                            token: account.mint,
+                            amount: u128::from(account.amount),
+                            decimals: account.decimals,
+                            symbol: None,
+                        }),
+                    }
+                }
+                Ok(balances)
+            }
+            BlockchainClientRef::Evm(client) => client.get_erc20_balances(owner, tokens).await,
+        }
+    }
+
+    /// Get the outcome of a transaction by signature or hash
+    ///
+    /// Returns `None` while the transaction has not landed.
+    pub async fn get_transaction_status(&self, tx_id: &str) -> Result<Option<TransactionStatus>> {
+        match self {
+            BlockchainClientRef::Solana(client) => {
+                let signature = parse_signature(tx_id)?;
+                Ok(client.get_signature_status(&signature).await?.map(|status| {
+                    match status {
+                        Ok(()) => TransactionStatus::Success,
+                        Err(_) => TransactionStatus::Failed,
+                    }
+                }))
+            }
+            BlockchainClientRef::Evm(client) => Ok(client
+                .get_transaction_receipt(tx_id)
+                .await?
+                .map(|receipt| receipt.status)),
+        }
+    }
+
+    /// Get the outcome of a transaction once it is `confirmations` blocks
+    /// (slots on Solana) deep, counting the block that includes it
+    ///
+    /// Returns `None` while the transaction has not landed or is not yet
+    /// deep enough to be final.
+    pub async fn get_final_transaction_status(
+        &self,
+        tx_id: &str,
+        confirmations: u64,
+    ) -> Result<Option<TransactionStatus>> {
+        match self {
+            BlockchainClientRef::Solana(client) => {
+                let signature = parse_signature(tx_id)?;
+                Ok(client
+                    .get_signature_confirmations(&signature)
+                    .await?
+                    // Finalized transactions no longer report a count
+                    .filter(|status| {
+                        status
+                            .confirmations
+                            .is_none_or(|count| count as u64 >= confirmations)
+                    })
+                    .map(|status| match status.err {
+                        None => TransactionStatus::Success,
+                        Some(_) => TransactionStatus::Failed,
+                    }))
+            }
+            BlockchainClientRef::Evm(client) => {
+                let Some(receipt) = client.get_transaction_receipt(tx_id).await? else {
+                    return Ok(None);
+                };
+                let latest_block = client.get_block_number().await?;
+                Ok((receipt.confirmations(latest_block) >= confirmations).then_some(receipt.status))
+            }
+        }
+    }
+
+    /// Get the receipt of an EVM transaction
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: &str,
+    ) -> Result<Option<EvmTransactionReceipt>> {
+        self.evm("get_transaction_receipt")?
+            .get_transaction_receipt(tx_hash)
+            .await
+    }
+
+    /// Get the next nonce of an EVM address
+    pub async fn get_transaction_count(&self, address: &str, block: BlockTag) -> Result<u64> {
+        self.evm("get_transaction_count")?
+            .get_transaction_count(address, block)
+            .await
+    }
+
+    /// Estimate the gas an EVM call or transaction would use
+    pub async fn estimate_gas(&self, request: &EvmCallRequest) -> Result<u64> {
+        self.evm("estimate_gas")?.estimate_gas(request).await
+    }
+
+    /// Get EIP-1559 fee history of an EVM chain
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory> {
+        self.evm("get_fee_history")?
+            .get_fee_history(block_count, reward_percentiles)
+            .await
+    }
+
+    /// The EVM client, or an error naming the EVM-only operation requested
+    fn evm(&self, operation: &str) -> Result<&'a EvmClient> {
+        match self {
+            BlockchainClientRef::Solana(_) => Err(shared::Error::Validation(format!(
+                "{} is only supported on EVM chains",
+                operation
+            ))),
+            BlockchainClientRef::Evm(client) => Ok(client),
+        }
+    }
+}
+
+fn parse_signature(tx_id: &str) -> Result<solana_sdk::signature::Signature> {
+    tx_id
+        .parse()
+        .map_err(|e| shared::Error::Validation(format!("Invalid transaction signature: {}", e)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_blockchain_names() {
-        assert_eq!(Blockchain::Solana.name(), "Solana");
-        assert_eq!(Blockchain::Ethereum.name(), "Ethereum");
-        assert_eq!(Blockchain::BinanceSmartChain.name(), "Binance Smart Chain");
-        assert_eq!(Blockchain::Polygon.name(), "Polygon");
-    }
-
     #[test]
     fn test_multi_chain_client_creation() {
         let client = MultiChainClient::new();
         assert!(client.solana().is_none());
-        assert!(client.ethereum().is_none());
-        assert!(client.bsc().is_none());
-        assert!(client.polygon().is_none());
+        assert!(client.evm(&Blockchain::ETHEREUM).is_none());
+        assert!(client.configured_blockchains().is_empty());
+        assert_eq!(client.registry().chains().len(), 4);
     }
 
     #[test]
@@ -309,73 +394,88 @@ mod tests {
             .with_solana("https://api.mainnet-beta.solana.com".to_string(), None);
         
         assert!(client.solana().is_some());
-        assert!(client.ethereum().is_none());
+        assert!(client.evm(&Blockchain::ETHEREUM).is_none());
     }
 
     #[test]
     fn test_multi_chain_client_with_ethereum() {
         let client = MultiChainClient::new()
-            .with_ethereum("https://eth.llamarpc.com".to_string(), None);
+            .with_evm(&Blockchain::ETHEREUM, "https://eth.llamarpc.com".to_string(), None)
+            .unwrap();
         
         assert!(client.solana().is_none());
-        assert!(client.ethereum().is_some());
+        assert_eq!(client.evm(&Blockchain::ETHEREUM).unwrap().chain().chain_id(), 1);
     }
 
     #[test]
-    fn test_multi_chain_client_with_all_chains() {
-        let client = MultiChainClient::new()
-            .with_solana("https://api.mainnet-beta.solana.com".to_string(), None)
-            .with_ethereum("https://eth.llamarpc.com".to_string(), None)
-            .with_bsc("https://bsc-dataseed.binance.org".to_string(), None)
-            .with_polygon("https://polygon-rpc.com".to_string(), None);
-        
-        assert!(client.solana().is_some());
-        assert!(client.ethereum().is_some());
-        assert!(client.bsc().is_some());
-        assert!(client.polygon().is_some());
+    fn test_with_evm_rejects_unknown_and_non_evm_chains() {
+        let url = "https://example.com".to_string();
+        assert!(MultiChainClient::new()
+            .with_evm(&Blockchain::new("Dogecoin"), url.clone(), None)
+            .is_err());
+        assert!(MultiChainClient::new()
+            .with_evm(&Blockchain::SOLANA, url, None)
+            .is_err());
     }
 
     #[test]
     fn test_configured_blockchains() {
         let client = MultiChainClient::new()
             .with_solana("https://api.mainnet-beta.solana.com".to_string(), None)
-            .with_ethereum("https://eth.llamarpc.com".to_string(), None);
+            .with_evm(&Blockchain::ETHEREUM, "https://eth.llamarpc.com".to_string(), None)
+            .unwrap();
         
         let configured = client.configured_blockchains();
-        assert_eq!(configured.len(), 2);
-        assert!(configured.contains(&Blockchain::Solana));
-        assert!(configured.contains(&Blockchain::Ethereum));
+        assert_eq!(configured, vec![Blockchain::SOLANA, Blockchain::ETHEREUM]);
     }
 
     #[test]
     fn test_get_client() {
         let client = MultiChainClient::new()
-            .with_ethereum("https://eth.llamarpc.com".to_string(), None);
+            .with_evm(&Blockchain::ETHEREUM, "https://eth.llamarpc.com".to_string(), None)
+            .unwrap();
         
-        assert!(client.get_client(Blockchain::Ethereum).is_some());
-        assert!(client.get_client(Blockchain::Solana).is_none());
+        assert!(client.get_client(&Blockchain::ETHEREUM).is_some());
+        assert!(client.get_client(&Blockchain::SOLANA).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_evm_only_operations_rejected_on_solana() {
+        let client = MultiChainClient::new()
+            .with_solana("https://api.mainnet-beta.solana.com".to_string(), None);
+        let solana = client.get_client(&Blockchain::SOLANA).unwrap();
+
+        let result = solana
+            .get_transaction_count("11111111111111111111111111111111", BlockTag::Pending)
+            .await;
+        assert!(matches!(result, Err(shared::Error::Validation(_))));
+        assert!(solana.get_fee_history(10, &[50.0]).await.is_err());
     }
 
     #[test]
-    fn test_with_configs() {
-        let configs = vec![
-            BlockchainConfig {
-                blockchain: Blockchain::Ethereum,
-                primary_rpc_url: "https://eth.llamarpc.com".to_string(),
-                fallback_rpc_url: None,
-            },
-            BlockchainConfig {
-                blockchain: Blockchain::Polygon,
-                primary_rpc_url: "https://polygon-rpc.com".to_string(),
-                fallback_rpc_url: Some("https://polygon-backup.com".to_string()),
-            },
-        ];
-
-        let client = MultiChainClient::new().with_configs(configs);
+    fn test_with_registry() {
+        let registry = ChainRegistry::from_json(
+            r#"[
+                {
+                    "id": "Base",
+                    "name": "Base",
+                    "slug": "base",
+                    "kind": "EVM",
+                    "chain_id": 8453,
+                    "rpc_urls": ["https://mainnet.base.org", "https://base.llamarpc.com"],
+                    "native_token": { "symbol": "ETH", "decimals": 18 },
+                    "explorer_tx_url": "https://basescan.org/tx/{tx}",
+                    "confirmations": 20
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let client = MultiChainClient::new().with_registry(registry);
         
-        assert!(client.ethereum().is_some());
-        assert!(client.polygon().is_some());
-        assert!(client.solana().is_none());
-        assert!(client.bsc().is_none());
+        // Only chains with RPC endpoints get a client
+        assert_eq!(client.configured_blockchains(), vec![Blockchain::new("Base")]);
+        assert_eq!(client.evm(&Blockchain::new("Base")).unwrap().chain().chain_id(), 8453);
+        assert!(client.registry().resolve("base").is_ok());
     }
 }
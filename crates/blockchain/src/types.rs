@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 
 /// Represents a token account with balance information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,3 +17,32 @@ pub struct WalletBalance {
     pub sol_balance: u64, // in lamports
     pub token_accounts: Vec<TokenAccount>,
 }
+
+/// SOL received by an account in a confirmed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedTransfer {
+    /// Fee payer of the transaction, taken to be the sender
+    pub sender: Pubkey,
+    /// Net lamports the account gained
+    pub lamports: u64,
+    /// Text of the transaction's SPL Memo instructions
+    pub memos: Vec<String>,
+}
+
+/// Balance of a fungible token (SPL or ERC-20) held by an address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBalance {
+    /// Mint address on Solana, contract address on EVM chains
+    pub token: String,
+    /// Raw amount in the token's smallest unit
+    pub amount: u128,
+    pub decimals: u8,
+    pub symbol: Option<String>,
+}
+
+/// Outcome of a transaction that has landed on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    Success,
+    Failed,
+}
@@ -0,0 +1,275 @@
+use blockchain::{
+    BlockTag, Blockchain, BlockchainClientRef, CircuitBreakerConfig, EvmCallRequest, EvmChain, EvmClient,
+    RetryConfig, TransactionStatus,
+};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const OWNER: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const MKR: &str = "0x9f8f72aa9304c8b593d555f12ef6589cc3a579a2";
+/// Token implementing only `balanceOf()`
+const BARE_TOKEN: &str = "0x00000000000000000000000000000000000000b0";
+const MINED_TX: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+const REVERTED_TX: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+const PENDING_TX: &str = "0x3333333333333333333333333333333333333333333333333333333333333333";
+
+/// Answer a single JSON-RPC call the way a node would
+fn respond(call: &Value) -> Value {
+    let params = &call["params"];
+    let result = match call["method"].as_str().unwrap() {
+        "eth_getBalance" => json!("0xde0b6b3a7640000"),
+        // Twelve blocks including the one the mined transactions landed in
+        "eth_blockNumber" => json!("0x100c"),
+        "eth_getTransactionCount" => match params[1].as_str() {
+            Some("pending") => json!("0x8"),
+            _ => json!("0x7"),
+        },
+        "eth_estimateGas" => json!("0xb411"),
+        "eth_feeHistory" => json!({
+            "oldestBlock": "0x1000",
+            "baseFeePerGas": ["0x3b9aca00", "0x4a817c80"],
+            "gasUsedRatio": [0.9],
+            "reward": [["0x59682f00"]]
+        }),
+        "eth_call" => {
+            let to = params[0]["to"].as_str().unwrap();
+            let data = params[0]["data"].as_str().unwrap();
+            match (&data[..10], to) {
+                ("0x70a08231", USDC) => json!(format!("0x{:064x}", 2_500_000u64)),
+                ("0x70a08231", MKR) => json!(format!("0x{:064x}", 0)),
+                ("0x70a08231", BARE_TOKEN) => json!(format!("0x{:064x}", 5)),
+                ("0x313ce567", USDC) => json!(format!("0x{:064x}", 6)),
+                ("0x313ce567", MKR) => json!(format!("0x{:064x}", 18)),
+                ("0x95d89b41", USDC) => json!(format!(
+                    "0x{:064x}{:064x}{:0<64}",
+                    32,
+                    4,
+                    hex::encode("USDC")
+                )),
+                // MKR returns its symbol as bytes32
+                ("0x95d89b41", MKR) => json!(format!("0x{:0<64}", hex::encode("MKR"))),
+                _ => {
+                    return json!({
+                        "jsonrpc": "2.0",
+                        "id": call["id"],
+                        "error": { "code": -32000, "message": "execution reverted" }
+                    })
+                }
+            }
+        }
+        "eth_getTransactionReceipt" => match params[0].as_str().unwrap() {
+            hash @ (MINED_TX | REVERTED_TX) => json!({
+                "transactionHash": hash,
+                "blockNumber": "0x1001",
+                "blockHash": format!("0x{}", "ab".repeat(32)),
+                "from": OWNER,
+                "to": USDC,
+                "contractAddress": null,
+                "gasUsed": "0xb411",
+                "effectiveGasPrice": "0x4a817c80",
+                "status": if hash == MINED_TX { "0x1" } else { "0x0" },
+                "logs": []
+            }),
+            _ => Value::Null,
+        },
+        "eth_getTransactionByHash" => match params[0].as_str().unwrap() {
+            hash @ MINED_TX => json!({
+                "hash": hash,
+                "from": OWNER,
+                "to": OWNER,
+                "input": format!("0x{}", "AB".repeat(32)),
+            }),
+            _ => Value::Null,
+        },
+        method => panic!("Unexpected RPC method {}", method),
+    };
+
+    json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+}
+
+/// Start a stub JSON-RPC node on an ephemeral port, returning its URL and a
+/// counter of HTTP requests served
+async fn start_stub_node() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let served = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            served.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length: usize = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse().ok())?
+                            })
+                            .unwrap_or(0);
+                        if buf.len() >= end + 4 + length {
+                            break buf[end + 4..end + 4 + length].to_vec();
+                        }
+                    }
+                };
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let response = match &request {
+                    Value::Array(calls) => Value::Array(calls.iter().rev().map(respond).collect()),
+                    call => respond(call),
+                }
+                .to_string();
+
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    (format!("http://{}", addr), requests)
+}
+
+fn client(primary_url: String, fallback_url: Option<String>) -> EvmClient {
+    EvmClient::new_with_config(
+        EvmChain::new(Blockchain::ETHEREUM, 1),
+        primary_url,
+        fallback_url,
+        RetryConfig {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            backoff_multiplier: 1.0,
+        },
+        CircuitBreakerConfig::default(),
+    )
+}
+
+#[tokio::test]
+async fn test_native_balance_and_nonce() {
+    let (url, _) = start_stub_node().await;
+    let client = client(url, None);
+
+    assert_eq!(client.get_native_balance(OWNER).await.unwrap(), 1_000_000_000_000_000_000);
+    assert_eq!(client.get_transaction_count(OWNER, BlockTag::Latest).await.unwrap(), 7);
+    assert_eq!(client.get_transaction_count(OWNER, BlockTag::Pending).await.unwrap(), 8);
+    assert!(client.get_native_balance("0x1234").await.is_err());
+}
+
+#[tokio::test]
+async fn test_falls_back_when_primary_is_down() {
+    let (url, _) = start_stub_node().await;
+    let client = client("http://127.0.0.1:1".to_string(), Some(url));
+
+    assert_eq!(client.get_native_balance(OWNER).await.unwrap(), 1_000_000_000_000_000_000);
+}
+
+#[tokio::test]
+async fn test_erc20_balances_are_batched() {
+    let (url, requests) = start_stub_node().await;
+    let client = client(url, None);
+
+    let balances = client
+        .get_erc20_balances(OWNER, &[USDC.to_string(), MKR.to_string()])
+        .await
+        .unwrap();
+
+    // Six eth_calls in one HTTP request, answered out of order
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[0].token, USDC);
+    assert_eq!(balances[0].amount, 2_500_000);
+    assert_eq!(balances[0].decimals, 6);
+    assert_eq!(balances[0].symbol.as_deref(), Some("USDC"));
+    assert_eq!(balances[1].amount, 0);
+    assert_eq!(balances[1].decimals, 18);
+    assert_eq!(balances[1].symbol.as_deref(), Some("MKR"));
+
+    // Missing metadata falls back per token instead of failing the lookup
+    let balances = client
+        .get_erc20_balances(OWNER, &[BARE_TOKEN.to_string(), USDC.to_string()])
+        .await
+        .unwrap();
+    assert_eq!(balances[0].amount, 5);
+    assert_eq!(balances[0].decimals, 18);
+    assert_eq!(balances[0].symbol, None);
+    assert_eq!(balances[1].symbol.as_deref(), Some("USDC"));
+
+    // A contract whose balanceOf() reverts fails the lookup
+    let not_a_token = "0x0000000000000000000000000000000000000001".to_string();
+    assert!(client.get_erc20_balances(OWNER, &[not_a_token]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_transaction_receipts() {
+    let (url, _) = start_stub_node().await;
+    let client = client(url, None);
+
+    let receipt = client.get_transaction_receipt(MINED_TX).await.unwrap().unwrap();
+    assert_eq!(receipt.status, TransactionStatus::Success);
+    assert_eq!(receipt.block_number, 0x1001);
+    assert_eq!(receipt.gas_used, 0xb411);
+    assert_eq!(receipt.to.as_deref(), Some(USDC));
+
+    assert!(client.get_transaction_receipt(PENDING_TX).await.unwrap().is_none());
+
+    assert_eq!(
+        client.get_transaction_input(MINED_TX).await.unwrap(),
+        Some(format!("0x{}", "ab".repeat(32)))
+    );
+    assert!(client.get_transaction_input(PENDING_TX).await.unwrap().is_none());
+
+    // Callers can stay chain-agnostic
+    let client = BlockchainClientRef::Evm(&client);
+    assert_eq!(
+        client.get_transaction_status(REVERTED_TX).await.unwrap(),
+        Some(TransactionStatus::Failed)
+    );
+    assert_eq!(client.get_transaction_status(PENDING_TX).await.unwrap(), None);
+
+    // Transactions are final once buried deep enough
+    assert_eq!(
+        client.get_final_transaction_status(MINED_TX, 12).await.unwrap(),
+        Some(TransactionStatus::Success)
+    );
+    assert_eq!(client.get_final_transaction_status(MINED_TX, 13).await.unwrap(), None);
+    assert_eq!(client.get_final_transaction_status(PENDING_TX, 1).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_gas_estimate_and_fee_history() {
+    let (url, _) = start_stub_node().await;
+    let client = client(url, None);
+
+    let request = EvmCallRequest {
+        from: Some(OWNER.to_string()),
+        to: Some(USDC.to_string()),
+        value: None,
+        data: Some(format!("0xa9059cbb{:0>64}{:064x}", &OWNER[2..], 1_000_000u64)),
+    };
+    assert_eq!(client.estimate_gas(&request).await.unwrap(), 0xb411);
+
+    let history = client.get_fee_history(1, &[50.0]).await.unwrap();
+    assert_eq!(history.oldest_block, 0x1000);
+    assert_eq!(history.reward, vec![vec![1_500_000_000]]);
+    assert_eq!(history.next_base_fee(), Some(1_250_000_000));
+
+    assert!(client.get_fee_history(0, &[50.0]).await.is_err());
+    assert!(client.get_fee_history(5, &[90.0, 10.0]).await.is_err());
+}
@@ -0,0 +1,255 @@
+use blockchain::{
+    Blockchain, CircuitBreakerConfig, EvmChain, EvmClient, EvmSigner, EvmTransactionBuilder, FeeStrategy,
+    NonceManager, RetryConfig,
+};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const SECRET: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const RECIPIENT: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+const USDC: &str = "0x2791bca1f2de4661ed88a30c99a7a9449aa84174";
+
+/// Raw transactions the stub node has been sent
+type Sent = Arc<Mutex<Vec<String>>>;
+
+/// Answer a JSON-RPC call the way a Polygon node with a quiet mempool would
+fn respond(call: &Value, sent: &Sent) -> Value {
+    let params = &call["params"];
+    let result = match call["method"].as_str().unwrap() {
+        // Three transactions already pending for the sender
+        "eth_getTransactionCount" => json!("0x3"),
+        "eth_estimateGas" => match params[0]["data"].as_str() {
+            Some("0x") | None => json!("0x5208"),
+            Some(_) => json!("0xc350"),
+        },
+        "eth_feeHistory" => json!({
+            "oldestBlock": "0x100",
+            "baseFeePerGas": ["0x5", "0x6", "0xa"],
+            "gasUsedRatio": [0.5, 0.5],
+            "reward": [["0x2"], ["0x4"]]
+        }),
+        "eth_sendRawTransaction" => {
+            let raw = params[0].as_str().unwrap().to_string();
+            // Reject anything carrying a marker value so failures can be tested
+            if raw.contains("deadbeef") {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": call["id"],
+                    "error": { "code": -32000, "message": "insufficient funds" }
+                });
+            }
+            // Lose the response of anything carrying this one
+            if raw.contains("feedface") {
+                return json!({ "jsonrpc": "2.0", "id": call["id"] });
+            }
+            sent.lock().unwrap().push(raw.clone());
+            json!(format!("0x{}", "00".repeat(32)))
+        }
+        // Nothing failed to send is known to the node
+        "eth_getTransactionByHash" => Value::Null,
+        method => panic!("Unexpected RPC method {}", method),
+    };
+
+    json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })
+}
+
+/// Start a stub JSON-RPC node on an ephemeral port
+async fn start_stub_node(sent: Sent) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let sent = sent.clone();
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length: usize = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse().ok())?
+                            })
+                            .unwrap_or(0);
+                        if buf.len() >= end + 4 + length {
+                            break buf[end + 4..end + 4 + length].to_vec();
+                        }
+                    }
+                };
+
+                let call: Value = serde_json::from_slice(&body).unwrap();
+                let response = respond(&call, &sent).to_string();
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+async fn builder() -> (EvmTransactionBuilder, Sent) {
+    let sent = Sent::default();
+    let url = start_stub_node(sent.clone()).await;
+    let client = EvmClient::new_with_config(
+        EvmChain::new(Blockchain::POLYGON, 137),
+        url,
+        None,
+        RetryConfig {
+            max_attempts: 1,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+            backoff_multiplier: 1.0,
+        },
+        CircuitBreakerConfig::default(),
+    );
+
+    (EvmTransactionBuilder::new(Arc::new(client)), sent)
+}
+
+#[tokio::test]
+async fn test_native_transfer_is_built_signed_and_sent() {
+    let (builder, sent) = builder().await;
+    let signer = EvmSigner::from_hex(SECRET).unwrap();
+
+    let transaction = builder
+        .native_transfer(signer.address(), RECIPIENT, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(transaction.chain_id, 137);
+    assert_eq!(transaction.nonce, 3);
+    // 21000 plus the 20% buffer
+    assert_eq!(transaction.gas_limit, 25_200);
+    // Median reward, and twice the next base fee on top
+    assert_eq!(transaction.max_priority_fee_per_gas, 4);
+    assert_eq!(transaction.max_fee_per_gas, 24);
+
+    let signed = builder.sign_and_send(&transaction, &signer).await.unwrap();
+    assert_eq!(*sent.lock().unwrap(), vec![signed.raw_transaction.clone()]);
+    assert!(signed.raw_transaction.starts_with("0x02"));
+
+    // The local counter runs ahead of the node's pending count
+    let next = builder
+        .erc20_transfer(signer.address(), USDC, RECIPIENT, 5_000_000)
+        .await
+        .unwrap();
+    assert_eq!(next.nonce, 4);
+    assert_eq!(next.to.as_deref(), Some(USDC));
+    assert_eq!(next.value, 0);
+    assert!(next.data.starts_with("0xa9059cbb"));
+    assert_eq!(next.gas_limit, 60_000);
+}
+
+#[tokio::test]
+async fn test_rejected_transaction_releases_its_nonce() {
+    let (builder, sent) = builder().await;
+    let signer = EvmSigner::from_hex(SECRET).unwrap();
+
+    let rejected = builder
+        .native_transfer(signer.address(), RECIPIENT, 0xdeadbeef)
+        .await
+        .unwrap();
+    assert_eq!(rejected.nonce, 3);
+    assert!(builder.sign_and_send(&rejected, &signer).await.is_err());
+    assert!(sent.lock().unwrap().is_empty());
+
+    let retry = builder
+        .native_transfer(signer.address(), RECIPIENT, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(retry.nonce, 3);
+}
+
+#[tokio::test]
+async fn test_lost_submission_keeps_its_nonce() {
+    let (builder, _) = builder().await;
+    let signer = EvmSigner::from_hex(SECRET).unwrap();
+
+    let lost = builder
+        .native_transfer(signer.address(), RECIPIENT, 0xfeedface)
+        .await
+        .unwrap();
+    assert_eq!(lost.nonce, 3);
+    assert!(builder.sign_and_send(&lost, &signer).await.is_err());
+
+    // It may still have been broadcast, so its nonce is not reused
+    let next = builder
+        .native_transfer(signer.address(), RECIPIENT, 1_000)
+        .await
+        .unwrap();
+    assert_eq!(next.nonce, 4);
+}
+
+#[tokio::test]
+async fn test_speed_up_and_cancel_reuse_the_nonce() {
+    let (builder, _) = builder().await;
+    let signer = EvmSigner::from_hex(SECRET).unwrap();
+
+    let pending = builder
+        .erc20_approve(signer.address(), USDC, RECIPIENT, u128::MAX)
+        .await
+        .unwrap();
+
+    // Fees have not moved, so the minimum replacement bump applies
+    let faster = builder.speed_up(&pending).await.unwrap();
+    assert_eq!(faster.nonce, pending.nonce);
+    assert_eq!(faster.data, pending.data);
+    assert_eq!(faster.max_fee_per_gas, 27);
+    assert_eq!(faster.max_priority_fee_per_gas, 5);
+
+    let cancel = builder.cancel(&faster).await.unwrap();
+    assert_eq!(cancel.nonce, pending.nonce);
+    assert_eq!(cancel.to.as_deref(), Some(signer.address()));
+    assert_eq!(cancel.value, 0);
+    assert_eq!(cancel.data, "0x");
+    assert_eq!(cancel.gas_limit, 21_000);
+    assert!(cancel.max_fee_per_gas > faster.max_fee_per_gas);
+
+    builder.sign_and_send(&cancel, &signer).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fixed_fees_and_chain_id_checks() {
+    let (builder, _) = builder().await;
+    let builder = builder
+        .with_nonce_manager(Arc::new(NonceManager::new()))
+        .with_fee_strategy(FeeStrategy::Fixed {
+            max_fee_per_gas: 50_000_000_000,
+            max_priority_fee_per_gas: 30_000_000_000,
+        })
+        .with_gas_buffer_percent(0);
+    let signer = EvmSigner::from_hex(SECRET).unwrap();
+
+    let transaction = builder
+        .native_transfer(signer.address(), RECIPIENT, 1)
+        .await
+        .unwrap();
+    assert_eq!(transaction.max_fee_per_gas, 50_000_000_000);
+    assert_eq!(transaction.gas_limit, 21_000);
+
+    // Signed for another chain, so the node would reject it
+    let mainnet = blockchain::Eip1559Transaction {
+        chain_id: 1,
+        ..transaction.clone()
+    };
+    assert!(builder.sign_and_send(&mainnet, &signer).await.is_err());
+    assert!(builder.speed_up(&mainnet).await.is_err());
+
+    // Only the sender's key can sign
+    let stranger = EvmSigner::generate();
+    assert!(builder.sign_and_send(&transaction, &stranger).await.is_err());
+}
@@ -0,0 +1,21 @@
+-- Server-issued challenges for wallet ownership verification
+CREATE TABLE IF NOT EXISTS wallet_verification_challenges (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet_address VARCHAR(255) NOT NULL,
+    blockchain VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_verification_challenges_user ON wallet_verification_challenges(user_id);
+CREATE INDEX IF NOT EXISTS idx_wallet_verification_challenges_expires ON wallet_verification_challenges(expires_at);
+
+-- Link each verification to the challenge that proved it
+ALTER TABLE wallet_verifications
+ADD COLUMN IF NOT EXISTS challenge_id UUID REFERENCES wallet_verification_challenges(id) ON DELETE SET NULL;
+
+-- Verifications recorded before signatures were checked cannot be trusted
+UPDATE wallet_verifications SET verified = FALSE WHERE challenge_id IS NULL AND verified = TRUE;
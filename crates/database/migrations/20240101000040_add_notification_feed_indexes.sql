@@ -0,0 +1,14 @@
+-- Notification reads filter on read and order by created_at, so neither may be NULL
+UPDATE notifications SET read = FALSE WHERE read IS NULL;
+UPDATE notifications SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE notifications ALTER COLUMN read SET NOT NULL;
+ALTER TABLE notifications ALTER COLUMN created_at SET NOT NULL;
+
+-- Keyset pagination over a user's feed and cursor polling of unread notifications
+CREATE INDEX IF NOT EXISTS idx_notifications_user_feed
+    ON notifications(user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_user_unread_feed
+    ON notifications(user_id, created_at, id) WHERE read = FALSE;
+
+-- Superseded by idx_notifications_user_unread_feed
+DROP INDEX IF EXISTS idx_notifications_user_unread;
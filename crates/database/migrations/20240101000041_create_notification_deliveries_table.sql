@@ -0,0 +1,27 @@
+-- Per-user delivery addresses for webhook and chat bot notifications
+ALTER TABLE notification_preferences ADD COLUMN IF NOT EXISTS webhook_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE notification_preferences ADD COLUMN IF NOT EXISTS webhook_url TEXT;
+ALTER TABLE notification_preferences ADD COLUMN IF NOT EXISTS bot_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE notification_preferences ADD COLUMN IF NOT EXISTS bot_destination TEXT;
+
+-- Delivery of each notification on each channel it was routed to
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+    id UUID PRIMARY KEY,
+    notification_id UUID NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('EMAIL', 'WEBHOOK', 'PUSH', 'BOT')),
+    target TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('PENDING', 'DELIVERED', 'RETRYING', 'DEAD_LETTER')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_notification ON notification_deliveries(notification_id);
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_retry
+    ON notification_deliveries(next_attempt_at) WHERE status = 'RETRYING';
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_dead_letter
+    ON notification_deliveries(updated_at DESC) WHERE status = 'DEAD_LETTER';
@@ -0,0 +1,45 @@
+-- Escrow holding the seller's side of a P2P offer until it is released or refunded
+CREATE TABLE IF NOT EXISTS p2p_escrows (
+    id UUID PRIMARY KEY,
+    offer_id UUID NOT NULL REFERENCES p2p_offers(id) ON DELETE CASCADE,
+    exchange_id UUID REFERENCES p2p_exchanges(id) ON DELETE CASCADE,
+    seller_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    buyer_user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL CHECK (status IN (
+        'AWAITING_DEPOSIT', 'LOCKED', 'RELEASING', 'RELEASED', 'REFUNDING', 'REFUNDED', 'CANCELLED'
+    )),
+    depositor_address VARCHAR(255),
+    lock_tx_hash VARCHAR(255),
+    settlement_tx_hash VARCHAR(255),
+    seller_confirmed_at TIMESTAMPTZ,
+    buyer_confirmed_at TIMESTAMPTZ,
+    timeout_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one open escrow per offer
+CREATE UNIQUE INDEX IF NOT EXISTS idx_p2p_escrows_open_offer
+    ON p2p_escrows(offer_id) WHERE status IN ('AWAITING_DEPOSIT', 'LOCKED', 'RELEASING', 'REFUNDING');
+CREATE INDEX IF NOT EXISTS idx_p2p_escrows_exchange ON p2p_escrows(exchange_id);
+CREATE INDEX IF NOT EXISTS idx_p2p_escrows_timeout
+    ON p2p_escrows(timeout_at) WHERE status IN ('AWAITING_DEPOSIT', 'LOCKED');
+
+-- Every status change of an escrow, with the transaction that caused it
+CREATE TABLE IF NOT EXISTS p2p_escrow_transitions (
+    id UUID PRIMARY KEY,
+    escrow_id UUID NOT NULL REFERENCES p2p_escrows(id) ON DELETE CASCADE,
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL,
+    reason VARCHAR(30) NOT NULL,
+    tx_hash VARCHAR(255),
+    receipt_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_escrow_transitions_escrow ON p2p_escrow_transitions(escrow_id, created_at);
+-- A deposit transaction can lock only one escrow
+CREATE UNIQUE INDEX IF NOT EXISTS idx_p2p_escrow_transitions_lock_tx
+    ON p2p_escrow_transitions(tx_hash) WHERE to_status = 'LOCKED' AND reason = 'DEPOSIT';
@@ -0,0 +1,16 @@
+-- Unfilled amount of the traded asset (from_amount for SELL, to_amount for BUY), reduced by partial fills
+ALTER TABLE p2p_offers ADD COLUMN IF NOT EXISTS remaining_amount DECIMAL(36, 18);
+UPDATE p2p_offers
+SET remaining_amount = CASE WHEN offer_type = 'SELL' THEN from_amount ELSE to_amount END
+WHERE remaining_amount IS NULL;
+ALTER TABLE p2p_offers ALTER COLUMN remaining_amount SET NOT NULL;
+
+-- Order book lookups by pair, side and price-time priority
+CREATE INDEX IF NOT EXISTS idx_p2p_offers_book
+    ON p2p_offers(from_asset, to_asset, offer_type, price, created_at) WHERE status = 'ACTIVE';
+
+-- A partially filled offer has one escrow per fill plus one for its unfilled remainder
+DROP INDEX IF EXISTS idx_p2p_escrows_open_offer;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_p2p_escrows_unmatched_offer
+    ON p2p_escrows(offer_id)
+    WHERE exchange_id IS NULL AND status IN ('AWAITING_DEPOSIT', 'LOCKED', 'RELEASING', 'REFUNDING');
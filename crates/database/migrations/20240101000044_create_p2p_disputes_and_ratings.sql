@@ -0,0 +1,50 @@
+-- Escrows are held while their exchange is disputed
+ALTER TABLE p2p_escrows DROP CONSTRAINT IF EXISTS p2p_escrows_status_check;
+ALTER TABLE p2p_escrows ADD CONSTRAINT p2p_escrows_status_check CHECK (status IN (
+    'AWAITING_DEPOSIT', 'LOCKED', 'DISPUTED', 'RELEASING', 'RELEASED', 'REFUNDING', 'REFUNDED', 'CANCELLED'
+));
+
+-- A dispute over one P2P exchange, decided by an arbitrator
+CREATE TABLE IF NOT EXISTS p2p_disputes (
+    id UUID PRIMARY KEY,
+    exchange_id UUID NOT NULL UNIQUE REFERENCES p2p_exchanges(id) ON DELETE CASCADE,
+    opened_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('OPEN', 'RESOLVED')),
+    outcome VARCHAR(20) CHECK (outcome IN ('RELEASE_TO_BUYER', 'REFUND_TO_SELLER')),
+    arbitrator_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    CHECK ((status = 'RESOLVED') = (outcome IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_disputes_open ON p2p_disputes(created_at) WHERE status = 'OPEN';
+
+-- Evidence submitted by either party or the arbitrator
+CREATE TABLE IF NOT EXISTS p2p_dispute_evidence (
+    id UUID PRIMARY KEY,
+    dispute_id UUID NOT NULL REFERENCES p2p_disputes(id) ON DELETE CASCADE,
+    submitted_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('CHAT_MESSAGE', 'TX_HASH', 'NOTE')),
+    reference VARCHAR(255),
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_dispute_evidence_dispute ON p2p_dispute_evidence(dispute_id, created_at);
+
+-- Post-trade rating of the counterparty, one per participant and exchange
+CREATE TABLE IF NOT EXISTS p2p_ratings (
+    id UUID PRIMARY KEY,
+    exchange_id UUID NOT NULL REFERENCES p2p_exchanges(id) ON DELETE CASCADE,
+    rater_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    ratee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    score SMALLINT NOT NULL CHECK (score BETWEEN 1 AND 5),
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_p2p_ratings_exchange_rater UNIQUE (exchange_id, rater_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_ratings_ratee ON p2p_ratings(ratee_id);
+CREATE INDEX IF NOT EXISTS idx_p2p_exchanges_seller ON p2p_exchanges(seller_user_id);
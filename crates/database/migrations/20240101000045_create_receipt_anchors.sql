@@ -0,0 +1,34 @@
+-- One on-chain transaction anchoring the Merkle root of a batch of receipt hashes
+CREATE TABLE IF NOT EXISTS receipt_anchors (
+    id UUID PRIMARY KEY,
+    blockchain VARCHAR(50) NOT NULL,
+    merkle_root VARCHAR(66) NOT NULL,
+    leaf_count INTEGER NOT NULL CHECK (leaf_count > 0),
+    transaction_hash VARCHAR(255),
+    status VARCHAR(20) NOT NULL CHECK (status IN ('PENDING', 'ANCHORED', 'FAILED')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- When the root was last submitted, used to find abandoned pending anchors
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    anchored_at TIMESTAMPTZ,
+    CHECK ((status = 'ANCHORED') = (transaction_hash IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_receipt_anchors_pending ON receipt_anchors(attempted_at) WHERE status = 'PENDING';
+
+-- Receipts are queued until their batch is anchored
+ALTER TABLE blockchain_receipts ALTER COLUMN transaction_hash DROP NOT NULL;
+
+-- Keep the full precision of crypto amounts so receipt hashes can be recomputed
+ALTER TABLE blockchain_receipts ALTER COLUMN amount TYPE DECIMAL(36, 18);
+
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS receipt_hash VARCHAR(66);
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS anchor_id UUID REFERENCES receipt_anchors(id);
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS leaf_index INTEGER CHECK (leaf_index >= 0);
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS merkle_proof TEXT[];
+
+CREATE INDEX IF NOT EXISTS idx_blockchain_receipts_unanchored ON blockchain_receipts(blockchain, created_at)
+    WHERE anchor_id IS NULL AND receipt_hash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_blockchain_receipts_anchor ON blockchain_receipts(anchor_id);
+
+COMMENT ON COLUMN blockchain_receipts.receipt_hash IS 'SHA-256 of the receipt fields, the Merkle leaf that is anchored';
+COMMENT ON COLUMN blockchain_receipts.merkle_proof IS 'Sibling hashes from the receipt leaf up to its anchor''s root';
@@ -0,0 +1,7 @@
+-- Receipts are hashed and signed over a canonical, versioned serialization
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS format_version VARCHAR(40);
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS signer VARCHAR(64);
+ALTER TABLE blockchain_receipts ADD COLUMN IF NOT EXISTS signature VARCHAR(128);
+
+COMMENT ON COLUMN blockchain_receipts.format_version IS 'Canonical serialization the receipt hash and signature cover; NULL for receipts created before signing';
+COMMENT ON COLUMN blockchain_receipts.signer IS 'Base58 Ed25519 public key of the server that signed the receipt';
@@ -0,0 +1,44 @@
+-- Tax-lot accounting: USD price history for valuing transactions at the
+-- time they happened, each user's disposal method, specific-ID lot
+-- selections and individual staking reward events
+
+CREATE TABLE IF NOT EXISTS asset_price_history (
+    asset VARCHAR(50) NOT NULL,
+    price_usd DECIMAL(36, 18) NOT NULL CHECK (price_usd >= 0),
+    recorded_at TIMESTAMPTZ NOT NULL,
+    source VARCHAR(50) NOT NULL,
+    PRIMARY KEY (asset, recorded_at)
+);
+
+COMMENT ON TABLE asset_price_history IS 'USD prices by upper-case asset symbol; a transaction is valued at the latest price recorded at or before it';
+
+CREATE TABLE IF NOT EXISTS tax_settings (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    disposal_method VARCHAR(20) NOT NULL DEFAULT 'FIFO'
+        CHECK (disposal_method IN ('FIFO', 'LIFO', 'HIFO', 'SPECIFIC_ID')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tax_lot_selections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    disposal_source VARCHAR(30) NOT NULL,
+    disposal_id UUID NOT NULL,
+    lot_id UUID NOT NULL,
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, disposal_source, disposal_id, lot_id)
+);
+
+COMMENT ON TABLE tax_lot_selections IS 'Lots a disposal consumes first under the SPECIFIC_ID method; lot_id is the id of the acquiring transaction';
+
+CREATE TABLE IF NOT EXISTS staking_rewards (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    position_id UUID NOT NULL REFERENCES staking_positions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    rewarded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_staking_rewards_user ON staking_rewards(user_id, rewarded_at);
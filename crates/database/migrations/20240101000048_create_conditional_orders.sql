@@ -0,0 +1,55 @@
+-- Conditional exit orders attached to a position: stop-loss, take-profit and
+-- trailing stops, optionally paired one-cancels-other, and every status
+-- change they go through
+
+CREATE TABLE IF NOT EXISTS conditional_orders (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    blockchain VARCHAR(50) NOT NULL DEFAULT 'Solana',
+    order_type VARCHAR(20) NOT NULL
+        CHECK (order_type IN ('STOP_LOSS', 'TAKE_PROFIT', 'TRAILING_STOP')),
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    trigger_price DECIMAL(36, 18) CHECK (trigger_price > 0),
+    trail_percent DECIMAL(9, 4) CHECK (trail_percent > 0 AND trail_percent < 100),
+    trail_amount DECIMAL(36, 18) CHECK (trail_amount > 0),
+    peak_price DECIMAL(36, 18),
+    oco_group_id UUID,
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE'
+        CHECK (status IN ('ACTIVE', 'TRIGGERED', 'EXECUTED', 'FAILED', 'CANCELLED')),
+    manual_order_id UUID,
+    triggered_price DECIMAL(36, 18),
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    triggered_at TIMESTAMPTZ,
+    CHECK (
+        (order_type = 'TRAILING_STOP' AND trigger_price IS NULL
+            AND (trail_percent IS NULL) <> (trail_amount IS NULL))
+        OR (order_type <> 'TRAILING_STOP' AND trigger_price IS NOT NULL
+            AND trail_percent IS NULL AND trail_amount IS NULL)
+    )
+);
+
+COMMENT ON COLUMN conditional_orders.peak_price IS 'Highest price a trailing stop has seen since it was placed; the stop trails below it';
+COMMENT ON COLUMN conditional_orders.oco_group_id IS 'Orders sharing a group are one-cancels-other: the first to trigger cancels the rest';
+COMMENT ON COLUMN conditional_orders.manual_order_id IS 'Manual order the triggered sell was executed through';
+
+CREATE INDEX IF NOT EXISTS idx_conditional_orders_user ON conditional_orders(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_conditional_orders_active_asset
+    ON conditional_orders(asset) WHERE status = 'ACTIVE';
+CREATE INDEX IF NOT EXISTS idx_conditional_orders_oco_group
+    ON conditional_orders(oco_group_id) WHERE oco_group_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS conditional_order_transitions (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES conditional_orders(id) ON DELETE CASCADE,
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL,
+    price DECIMAL(36, 18),
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_conditional_order_transitions_order
+    ON conditional_order_transitions(order_id, created_at);
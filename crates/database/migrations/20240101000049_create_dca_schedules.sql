@@ -0,0 +1,61 @@
+-- Recurring dollar-cost-averaging plans and every run they make
+
+CREATE TABLE IF NOT EXISTS dca_schedules (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    quote_asset VARCHAR(50) NOT NULL DEFAULT 'USDC',
+    blockchain VARCHAR(50) NOT NULL DEFAULT 'Solana',
+    action VARCHAR(10) NOT NULL CHECK (action IN ('BUY', 'SELL')),
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    venue VARCHAR(20) NOT NULL CHECK (venue IN ('TRADE', 'CONVERSION')),
+    settle_address VARCHAR(255),
+    interval_secs BIGINT CHECK (interval_secs >= 60),
+    cron_expression VARCHAR(100),
+    price_ceiling DECIMAL(36, 18) CHECK (price_ceiling > 0),
+    price_floor DECIMAL(36, 18) CHECK (price_floor > 0),
+    end_at TIMESTAMPTZ,
+    total_budget DECIMAL(36, 18) CHECK (total_budget > 0),
+    total_spent DECIMAL(36, 18) NOT NULL DEFAULT 0,
+    run_count INTEGER NOT NULL DEFAULT 0,
+    catch_up_policy VARCHAR(20) NOT NULL DEFAULT 'RUN_ONCE'
+        CHECK (catch_up_policy IN ('SKIP', 'RUN_ONCE', 'RUN_ALL')),
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE'
+        CHECK (status IN ('ACTIVE', 'PAUSED', 'COMPLETED', 'CANCELLED')),
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((interval_secs IS NULL) <> (cron_expression IS NULL)),
+    CHECK (venue <> 'CONVERSION' OR settle_address IS NOT NULL)
+);
+
+COMMENT ON COLUMN dca_schedules.amount IS 'Amount of the asset bought or sold by each run';
+COMMENT ON COLUMN dca_schedules.cron_expression IS 'Five-field cron expression evaluated in UTC; exclusive with interval_secs';
+COMMENT ON COLUMN dca_schedules.total_budget IS 'Quote-currency value the plan may trade in total before it completes';
+COMMENT ON COLUMN dca_schedules.catch_up_policy IS 'What happens to runs missed while the scheduler was down: SKIP them, RUN_ONCE for the latest, or RUN_ALL';
+
+CREATE INDEX IF NOT EXISTS idx_dca_schedules_user ON dca_schedules(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_dca_schedules_due
+    ON dca_schedules(next_run_at) WHERE status = 'ACTIVE';
+
+CREATE TABLE IF NOT EXISTS dca_runs (
+    id UUID PRIMARY KEY,
+    schedule_id UUID NOT NULL REFERENCES dca_schedules(id) ON DELETE CASCADE,
+    scheduled_for TIMESTAMPTZ NOT NULL,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('EXECUTED', 'FAILED', 'SKIPPED')),
+    amount DECIMAL(36, 18),
+    price DECIMAL(36, 18),
+    value DECIMAL(36, 18),
+    manual_order_id UUID,
+    transaction_hash VARCHAR(255),
+    receipt_id UUID,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (schedule_id, scheduled_for)
+);
+
+COMMENT ON COLUMN dca_runs.value IS 'Quote-currency value traded by the run, counted against the plan budget';
+COMMENT ON COLUMN dca_runs.receipt_id IS 'Blockchain receipt of the trade or conversion';
+
+CREATE INDEX IF NOT EXISTS idx_dca_runs_schedule ON dca_runs(schedule_id, scheduled_for DESC);
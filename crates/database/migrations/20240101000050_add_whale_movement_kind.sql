@@ -0,0 +1,7 @@
+-- Structured classification of each whale movement: swap, transfer, stake or
+-- liquidity change, with tokens in and out, amounts and counterparties
+ALTER TABLE whale_movements ADD COLUMN IF NOT EXISTS movement_kind JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_whale_movements_kind ON whale_movements((movement_kind->>'kind'));
+
+COMMENT ON COLUMN whale_movements.movement_kind IS 'MovementKind JSON from the monitoring classifier; NULL for movements recorded before classification';
@@ -0,0 +1,18 @@
+-- Deposit transactions consumed by escrows; a transaction can fund only one escrow
+CREATE TABLE IF NOT EXISTS p2p_escrow_deposits (
+    tx_hash VARCHAR(255) PRIMARY KEY,
+    escrow_id UUID NOT NULL REFERENCES p2p_escrows(id) ON DELETE CASCADE,
+    depositor_address VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_p2p_escrow_deposits_escrow ON p2p_escrow_deposits(escrow_id);
+
+-- Deposits locked before this table existed
+INSERT INTO p2p_escrow_deposits (tx_hash, escrow_id, depositor_address, created_at)
+SELECT DISTINCT ON (t.tx_hash) t.tx_hash, t.escrow_id, COALESCE(e.depositor_address, ''), t.created_at
+FROM p2p_escrow_transitions t
+JOIN p2p_escrows e ON e.id = t.escrow_id
+WHERE t.to_status = 'LOCKED' AND t.reason = 'DEPOSIT' AND t.tx_hash IS NOT NULL
+ORDER BY t.tx_hash, t.created_at
+ON CONFLICT (tx_hash) DO NOTHING;
@@ -0,0 +1,21 @@
+-- Escrow payouts, stored once signed and before they are sent, so a payout
+-- whose outcome is unknown is looked up on-chain instead of being retried
+CREATE TABLE IF NOT EXISTS p2p_escrow_payouts (
+    tx_hash VARCHAR(255) PRIMARY KEY,
+    escrow_id UUID NOT NULL REFERENCES p2p_escrows(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    amount DECIMAL(36, 18) NOT NULL CHECK (amount > 0),
+    recipient VARCHAR(255) NOT NULL,
+    -- Chain-specific data needed to rebuild the signed transaction (the recent blockhash on Solana)
+    nonce VARCHAR(255) NOT NULL,
+    -- Escrow status to return to if the payout fails, and why it was paid out
+    from_status VARCHAR(20) NOT NULL,
+    reason VARCHAR(30) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING' CHECK (status IN ('PENDING', 'CONFIRMED', 'FAILED')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one payout in flight per escrow
+CREATE UNIQUE INDEX IF NOT EXISTS idx_p2p_escrow_payouts_pending
+    ON p2p_escrow_payouts(escrow_id) WHERE status = 'PENDING';
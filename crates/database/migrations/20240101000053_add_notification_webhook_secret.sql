@@ -0,0 +1,7 @@
+-- Each webhook is signed with its own secret instead of one shared key
+ALTER TABLE notification_preferences ADD COLUMN IF NOT EXISTS webhook_secret TEXT;
+
+-- Webhooks registered before this change get a fresh secret
+UPDATE notification_preferences
+SET webhook_secret = replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', '')
+WHERE webhook_url IS NOT NULL AND webhook_secret IS NULL;
@@ -0,0 +1,25 @@
+-- Trades record their fee, and a disposal method applies only to disposals
+-- from the date it was chosen, so already reported gains never change
+
+ALTER TABLE trade_executions ADD COLUMN IF NOT EXISTS fee_usd DECIMAL(20, 8) NOT NULL DEFAULT 0
+    CHECK (fee_usd >= 0);
+
+CREATE TABLE IF NOT EXISTS tax_method_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    disposal_method VARCHAR(20) NOT NULL
+        CHECK (disposal_method IN ('FIFO', 'LIFO', 'HIFO', 'SPECIFIC_ID')),
+    effective_from TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, effective_from)
+);
+
+COMMENT ON TABLE tax_method_changes IS 'Disposal method of each user from effective_from on; FIFO before the first change';
+
+-- Methods chosen before this change were applied to the whole history
+INSERT INTO tax_method_changes (user_id, disposal_method, effective_from, created_at)
+SELECT user_id, disposal_method, TIMESTAMPTZ '1970-01-01 00:00:00+00', updated_at
+FROM tax_settings
+ON CONFLICT (user_id, effective_from) DO NOTHING;
+
+DROP TABLE IF EXISTS tax_settings;
@@ -0,0 +1,6 @@
+-- DCA budgets, prices and run values are all in USD, whatever the quote asset
+COMMENT ON COLUMN dca_schedules.total_budget IS 'USD value the plan may trade in total before it completes';
+COMMENT ON COLUMN dca_schedules.price_ceiling IS 'USD price above which runs are skipped';
+COMMENT ON COLUMN dca_schedules.price_floor IS 'USD price below which runs are skipped';
+COMMENT ON COLUMN dca_runs.price IS 'USD price of the asset the run was made at';
+COMMENT ON COLUMN dca_runs.value IS 'USD value traded by the run, counted against the plan budget';
@@ -0,0 +1,71 @@
+-- Position management tables from 20240101000028, which was never part of
+-- run_migrations; created here so that migration stays as shipped
+CREATE TABLE IF NOT EXISTS position_modes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    blockchain VARCHAR(50) NOT NULL DEFAULT 'Solana',
+    mode VARCHAR(20) NOT NULL DEFAULT 'manual', -- 'manual' or 'automatic'
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(user_id, asset, blockchain)
+);
+
+CREATE INDEX IF NOT EXISTS idx_position_modes_user ON position_modes(user_id);
+CREATE INDEX IF NOT EXISTS idx_position_modes_user_asset ON position_modes(user_id, asset);
+
+CREATE TABLE IF NOT EXISTS manual_orders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    blockchain VARCHAR(50) NOT NULL DEFAULT 'Solana',
+    action VARCHAR(10) NOT NULL, -- 'BUY' or 'SELL'
+    amount DECIMAL(36, 18) NOT NULL,
+    price_usd DECIMAL(18, 8),
+    total_value_usd DECIMAL(18, 2),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- 'pending', 'executed', 'failed', 'cancelled'
+    transaction_hash VARCHAR(255),
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    executed_at TIMESTAMP WITH TIME ZONE,
+    cancelled_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_manual_orders_user ON manual_orders(user_id);
+CREATE INDEX IF NOT EXISTS idx_manual_orders_status ON manual_orders(status);
+CREATE INDEX IF NOT EXISTS idx_manual_orders_created ON manual_orders(created_at DESC);
+
+CREATE TABLE IF NOT EXISTS pending_automatic_orders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    asset VARCHAR(50) NOT NULL,
+    blockchain VARCHAR(50) NOT NULL DEFAULT 'Solana',
+    order_type VARCHAR(50) NOT NULL, -- 'benchmark', 'trim', 'ai_recommendation'
+    order_reference_id UUID, -- ID of the benchmark, trim, or recommendation
+    action VARCHAR(10) NOT NULL, -- 'BUY' or 'SELL'
+    amount DECIMAL(36, 18) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- 'pending', 'executed', 'cancelled'
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    executed_at TIMESTAMP WITH TIME ZONE,
+    cancelled_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_automatic_orders_user ON pending_automatic_orders(user_id);
+CREATE INDEX IF NOT EXISTS idx_pending_automatic_orders_asset ON pending_automatic_orders(user_id, asset);
+CREATE INDEX IF NOT EXISTS idx_pending_automatic_orders_status ON pending_automatic_orders(status);
+
+-- Conditional orders and DCA runs link the manual order that placed their trade
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'conditional_orders_manual_order_id_fkey') THEN
+        ALTER TABLE conditional_orders
+            ADD CONSTRAINT conditional_orders_manual_order_id_fkey
+            FOREIGN KEY (manual_order_id) REFERENCES manual_orders(id) ON DELETE SET NULL;
+    END IF;
+
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'dca_runs_manual_order_id_fkey') THEN
+        ALTER TABLE dca_runs
+            ADD CONSTRAINT dca_runs_manual_order_id_fkey
+            FOREIGN KEY (manual_order_id) REFERENCES manual_orders(id) ON DELETE SET NULL;
+    END IF;
+END $$;
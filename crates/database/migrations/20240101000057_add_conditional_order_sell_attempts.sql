@@ -0,0 +1,2 @@
+-- Failed sells are counted so an order that keeps failing stops retrying
+ALTER TABLE conditional_orders ADD COLUMN IF NOT EXISTS failed_attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,37 @@
+-- Notification cursors page by a database-assigned sequence. created_at comes
+-- from the writer's clock, so a row inserted late (clock skew, a slow insert)
+-- could land behind a cursor built from it and never be polled.
+CREATE SEQUENCE IF NOT EXISTS notifications_seq_seq;
+
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS seq BIGINT;
+
+-- Existing rows keep their feed order
+UPDATE notifications n
+SET seq = numbered.seq
+FROM (
+    SELECT id,
+           (SELECT COALESCE(MAX(seq), 0) FROM notifications)
+               + ROW_NUMBER() OVER (ORDER BY created_at, id) AS seq
+    FROM notifications
+    WHERE seq IS NULL
+) numbered
+WHERE n.id = numbered.id;
+
+-- Only ever moves the sequence forward, so re-running never reissues values
+SELECT setval(
+    'notifications_seq_seq',
+    GREATEST(
+        (SELECT COALESCE(MAX(seq), 0) FROM notifications),
+        (SELECT last_value FROM notifications_seq_seq),
+        1
+    )
+);
+
+ALTER TABLE notifications ALTER COLUMN seq SET DEFAULT nextval('notifications_seq_seq');
+ALTER TABLE notifications ALTER COLUMN seq SET NOT NULL;
+ALTER SEQUENCE notifications_seq_seq OWNED BY notifications.seq;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_seq
+    ON notifications(user_id, seq);
+CREATE INDEX IF NOT EXISTS idx_notifications_user_unread_seq
+    ON notifications(user_id, seq) WHERE read = FALSE;
@@ -65,6 +65,26 @@ pub async fn run_migrations(pool: &DbPool) -> anyhow::Result<()> {
         include_str!("../migrations/20240101000032_add_proximity_transfer_to_receipts.sql"),
         include_str!("../migrations/20240101000037_create_mesh_price_cache_table.sql"),
         include_str!("../migrations/20240101000038_create_mesh_seen_messages_table.sql"),
+        include_str!("../migrations/20240101000039_create_wallet_verification_challenges_table.sql"),
+        include_str!("../migrations/20240101000040_add_notification_feed_indexes.sql"),
+        include_str!("../migrations/20240101000041_create_notification_deliveries_table.sql"),
+        include_str!("../migrations/20240101000042_create_p2p_escrows_table.sql"),
+        include_str!("../migrations/20240101000043_add_p2p_offer_remaining_amount.sql"),
+        include_str!("../migrations/20240101000044_create_p2p_disputes_and_ratings.sql"),
+        include_str!("../migrations/20240101000045_create_receipt_anchors.sql"),
+        include_str!("../migrations/20240101000046_add_signed_receipt_format.sql"),
+        include_str!("../migrations/20240101000047_create_tax_lot_tables.sql"),
+        include_str!("../migrations/20240101000048_create_conditional_orders.sql"),
+        include_str!("../migrations/20240101000049_create_dca_schedules.sql"),
+        include_str!("../migrations/20240101000050_add_whale_movement_kind.sql"),
+        include_str!("../migrations/20240101000051_create_p2p_escrow_deposits.sql"),
+        include_str!("../migrations/20240101000052_create_p2p_escrow_payouts.sql"),
+        include_str!("../migrations/20240101000053_add_notification_webhook_secret.sql"),
+        include_str!("../migrations/20240101000054_add_trade_fees_and_tax_method_changes.sql"),
+        include_str!("../migrations/20240101000055_dca_budgets_in_usd.sql"),
+        include_str!("../migrations/20240101000056_create_position_management_tables.sql"),
+        include_str!("../migrations/20240101000057_add_conditional_order_sell_attempts.sql"),
+        include_str!("../migrations/20240101000058_add_notification_sequence.sql"),
     ];
     
     for (idx, migration) in migrations.iter().enumerate() {
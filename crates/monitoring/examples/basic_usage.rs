@@ -8,7 +8,7 @@
 ///
 /// Note: This requires Redis to be running on localhost:6379
 
-use monitoring::{MonitoringEngine, WorkerPoolConfig};
+use monitoring::{MonitoringEngine, MonitoringMode, WorkerPoolConfig};
 use uuid::Uuid;
 
 #[tokio::main]
@@ -26,6 +26,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         worker_count: 10,
         whales_per_worker: 100,
         check_interval_seconds: 30,
+        mode: MonitoringMode::Polling,
+        solana_ws_url: None,
     };
 
     // Create the monitoring engine
@@ -0,0 +1,1048 @@
+//! Classification of whale transactions by the programs they call
+//!
+//! Each program id maps to an [`InstructionDecoder`]. The registry runs the
+//! matching decoder over every instruction of a parsed transaction,
+//! including inner ones, and keeps the most specific movement found, so a
+//! swap routed through Jupiter is not reported as the token transfers it
+//! is made of.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiMessage,
+    UiParsedInstruction, UiTransactionTokenBalance,
+};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+
+/// Wrapped SOL mint; native SOL movements are reported under it as well
+pub const NATIVE_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+const SOL_DECIMALS: u8 = 9;
+
+/// Native SOL changes smaller than this are fees and account rent
+const SOL_DUST_LAMPORTS: i128 = 10_000_000;
+
+/// Swaps are priced in these; paying with one of them is a buy
+const QUOTE_MINTS: &[&str] = &[NATIVE_SOL_MINT, USDC_MINT, USDT_MINT];
+
+/// Aggregators only route swaps through other venues
+const AGGREGATORS: &[(&str, &str)] = &[
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter"),
+    ("JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB", "Jupiter v4"),
+];
+
+/// Pools that both swap and take liquidity
+const AMMS: &[(&str, &str)] = &[
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium AMM"),
+    ("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", "Raydium CLMM"),
+    ("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", "Raydium CPMM"),
+    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "Orca Whirlpool"),
+    ("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP", "Orca"),
+    ("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", "Meteora DLMM"),
+    ("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB", "Meteora"),
+    ("2wT8Yq49kHgDzXuPxZSaeLaH1qbmGXtEyPy64bL7aD3c", "Lifinity"),
+];
+
+/// An amount of one token, in base units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenAmount {
+    pub mint: String,
+    /// Base units, as a string to preserve precision
+    pub amount: String,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    fn new(mint: &str, amount: u128, decimals: u8) -> Self {
+        Self {
+            mint: mint.to_string(),
+            amount: amount.to_string(),
+            decimals,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeAction {
+    Delegate,
+    Deactivate,
+    Withdraw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityAction {
+    Deposit,
+    Withdraw,
+}
+
+/// What a whale transaction did, from the whale's point of view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MovementKind {
+    /// SOL or tokens sent to or received from another wallet
+    Transfer {
+        direction: TransferDirection,
+        token: TokenAmount,
+        /// Owner of the other side, or the raw account when unknown
+        counterparty: Option<String>,
+    },
+    /// One token exchanged for another; `token_in` is what the whale paid
+    Swap {
+        venue: String,
+        program_id: String,
+        token_in: TokenAmount,
+        token_out: TokenAmount,
+    },
+    Stake {
+        action: StakeAction,
+        stake_account: String,
+        /// Validator vote account, for delegations
+        vote_account: Option<String>,
+        lamports: Option<u64>,
+    },
+    /// Tokens added to or taken out of a pool
+    Liquidity {
+        action: LiquidityAction,
+        venue: String,
+        program_id: String,
+        tokens: Vec<TokenAmount>,
+        /// Pool share minted or burned, if the pool issues one
+        lp_token: Option<TokenAmount>,
+    },
+}
+
+impl MovementKind {
+    /// Short label stored as the movement type, e.g. `BUY` or `STAKE`
+    pub fn movement_type(&self) -> &'static str {
+        match self {
+            MovementKind::Transfer { direction: TransferDirection::Outgoing, .. } => "SEND",
+            MovementKind::Transfer { direction: TransferDirection::Incoming, .. } => "RECEIVE",
+            MovementKind::Swap { .. } if self.is_buy() => "BUY",
+            MovementKind::Swap { .. } => "SELL",
+            MovementKind::Stake { action: StakeAction::Delegate, .. } => "STAKE",
+            MovementKind::Stake { .. } => "UNSTAKE",
+            MovementKind::Liquidity { action: LiquidityAction::Deposit, .. } => "LP_ADD",
+            MovementKind::Liquidity { action: LiquidityAction::Withdraw, .. } => "LP_REMOVE",
+        }
+    }
+
+    /// The token whose position the movement changes: the one bought or
+    /// sold, sent or received, staked or added to a pool
+    pub fn primary_token(&self) -> TokenAmount {
+        match self {
+            MovementKind::Transfer { token, .. } => token.clone(),
+            MovementKind::Swap { token_out, .. } if self.is_buy() => token_out.clone(),
+            MovementKind::Swap { token_in, .. } => token_in.clone(),
+            MovementKind::Stake { lamports, .. } => {
+                TokenAmount::new(NATIVE_SOL_MINT, lamports.unwrap_or(0) as u128, SOL_DECIMALS)
+            }
+            MovementKind::Liquidity { tokens, .. } => tokens[0].clone(),
+        }
+    }
+
+    /// Whether the primary token leaves the whale's position
+    fn is_outflow(&self) -> bool {
+        matches!(self.movement_type(), "SEND" | "SELL" | "STAKE" | "LP_ADD")
+    }
+
+    fn is_buy(&self) -> bool {
+        match self {
+            MovementKind::Swap { token_in, token_out, .. } => {
+                QUOTE_MINTS.contains(&token_in.mint.as_str())
+                    && !QUOTE_MINTS.contains(&token_out.mint.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// Movements describing more of the transaction win over the transfers
+    /// they are made of
+    fn specificity(&self) -> u8 {
+        match self {
+            MovementKind::Transfer { .. } => 1,
+            MovementKind::Stake { .. } => 2,
+            MovementKind::Swap { .. } | MovementKind::Liquidity { .. } => 3,
+        }
+    }
+}
+
+/// A classified whale transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub kind: MovementKind,
+    /// Primary token amount as a share of the whale's holding of it, before
+    /// the movement for outflows and after it for inflows
+    pub percent_of_position: Option<f64>,
+}
+
+/// One instruction of a transaction, top-level or inner
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub program_id: String,
+    /// `type` of instructions the RPC node could parse, e.g. `transfer`
+    pub instruction_type: Option<String>,
+    /// `info` of parsed instructions, `Null` otherwise
+    pub info: Value,
+    pub accounts: Vec<String>,
+}
+
+impl Instruction {
+    fn from_ui(instruction: &UiInstruction, account_keys: &[String]) -> Option<Self> {
+        match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => Some(Self {
+                program_id: parsed.program_id.clone(),
+                instruction_type: parsed.parsed["type"].as_str().map(str::to_string),
+                info: parsed.parsed["info"].clone(),
+                accounts: Vec::new(),
+            }),
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => Some(Self {
+                program_id: decoded.program_id.clone(),
+                instruction_type: None,
+                info: Value::Null,
+                accounts: decoded.accounts.clone(),
+            }),
+            UiInstruction::Compiled(compiled) => Some(Self {
+                program_id: account_keys.get(compiled.program_id_index as usize)?.clone(),
+                instruction_type: None,
+                info: Value::Null,
+                accounts: compiled
+                    .accounts
+                    .iter()
+                    .filter_map(|index| account_keys.get(*index as usize).cloned())
+                    .collect(),
+            }),
+        }
+    }
+
+    /// String field of `info`
+    pub fn info_str(&self, field: &str) -> Option<&str> {
+        self.info[field].as_str()
+    }
+}
+
+/// A token account's balance around the transaction
+#[derive(Debug, Clone, Default)]
+struct TokenBalance {
+    mint: String,
+    owner: Option<String>,
+    decimals: u8,
+    pre: u128,
+    post: u128,
+}
+
+/// Net change of one token in the whale's wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub mint: String,
+    pub decimals: u8,
+    pub delta: i128,
+}
+
+/// A successful transaction seen from one whale's wallet
+pub struct ParsedTransaction {
+    whale: String,
+    account_keys: Vec<String>,
+    pre_lamports: Vec<u64>,
+    post_lamports: Vec<u64>,
+    /// The whale's native SOL before and after, not counting the fee
+    whale_lamports: Option<(u64, u64)>,
+    token_balances: HashMap<String, TokenBalance>,
+    instructions: Vec<Instruction>,
+}
+
+impl ParsedTransaction {
+    /// Read a JSON or JSON-parsed transaction; `None` for failed
+    /// transactions and other encodings
+    pub fn new(whale_address: &str, transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Option<Self> {
+        let meta = transaction.transaction.meta.as_ref()?;
+        if meta.err.is_some() {
+            return None;
+        }
+        let EncodedTransaction::Json(ui_transaction) = &transaction.transaction.transaction else {
+            return None;
+        };
+
+        let (account_keys, outer) = match &ui_transaction.message {
+            UiMessage::Parsed(message) => (
+                message.account_keys.iter().map(|key| key.pubkey.clone()).collect::<Vec<_>>(),
+                message.instructions.clone(),
+            ),
+            UiMessage::Raw(message) => {
+                let mut keys = message.account_keys.clone();
+                // Versioned transactions load further accounts from lookup tables
+                if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+                    keys.extend(loaded.writable.iter().cloned());
+                    keys.extend(loaded.readonly.iter().cloned());
+                }
+                let instructions = message.instructions.iter().cloned().map(UiInstruction::Compiled).collect();
+                (keys, instructions)
+            }
+        };
+
+        let inner = match &meta.inner_instructions {
+            OptionSerializer::Some(inner) => inner.iter().flat_map(|set| set.instructions.clone()).collect(),
+            _ => Vec::new(),
+        };
+        let instructions = outer
+            .iter()
+            .chain(inner.iter())
+            .filter_map(|instruction| Instruction::from_ui(instruction, &account_keys))
+            .collect();
+
+        let whale_lamports = account_keys.iter().position(|key| key == whale_address).and_then(|index| {
+            let pre = *meta.pre_balances.get(index)?;
+            let mut post = *meta.post_balances.get(index)?;
+            // The first account pays the fee
+            if index == 0 {
+                post += meta.fee;
+            }
+            Some((pre, post))
+        });
+
+        let mut token_balances: HashMap<String, TokenBalance> = HashMap::new();
+        let mut record = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, is_pre: bool| {
+            let OptionSerializer::Some(balances) = balances else {
+                return;
+            };
+            for balance in balances {
+                let Some(account) = account_keys.get(balance.account_index as usize) else {
+                    continue;
+                };
+                let amount = balance.ui_token_amount.amount.parse::<u128>().unwrap_or(0);
+                let entry = token_balances.entry(account.clone()).or_default();
+                entry.mint = balance.mint.clone();
+                entry.decimals = balance.ui_token_amount.decimals;
+                if let OptionSerializer::Some(owner) = &balance.owner {
+                    entry.owner = Some(owner.clone());
+                }
+                if is_pre {
+                    entry.pre = amount;
+                } else {
+                    entry.post = amount;
+                }
+            }
+        };
+        record(&meta.pre_token_balances, true);
+        record(&meta.post_token_balances, false);
+
+        Some(Self {
+            whale: whale_address.to_string(),
+            account_keys,
+            pre_lamports: meta.pre_balances.clone(),
+            post_lamports: meta.post_balances.clone(),
+            whale_lamports,
+            token_balances,
+            instructions,
+        })
+    }
+
+    pub fn whale(&self) -> &str {
+        &self.whale
+    }
+
+    /// Top-level instructions followed by inner ones
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Wallet owning a token account touched by the transaction
+    pub fn token_account_owner(&self, account: &str) -> Option<&str> {
+        self.token_balances.get(account)?.owner.as_deref()
+    }
+
+    /// Mint and decimals of a token account touched by the transaction
+    pub fn token_account_mint(&self, account: &str) -> Option<(&str, u8)> {
+        let balance = self.token_balances.get(account)?;
+        Some((balance.mint.as_str(), balance.decimals))
+    }
+
+    /// Lamports an account held before the transaction
+    pub fn lamports_before(&self, account: &str) -> Option<u64> {
+        let index = self.account_keys.iter().position(|key| key == account)?;
+        self.pre_lamports.get(index).copied()
+    }
+
+    /// Lamports an account holds after the transaction
+    pub fn lamports_after(&self, account: &str) -> Option<u64> {
+        let index = self.account_keys.iter().position(|key| key == account)?;
+        self.post_lamports.get(index).copied()
+    }
+
+    /// Net change of every token in the whale's wallet, with native and
+    /// wrapped SOL combined and fee or rent sized SOL changes left out
+    pub fn whale_changes(&self) -> Vec<BalanceChange> {
+        let mut changes: BTreeMap<&str, (u8, i128)> = BTreeMap::new();
+        for balance in self.whale_token_balances() {
+            let change = changes.entry(balance.mint.as_str()).or_insert((balance.decimals, 0));
+            change.1 += balance.post as i128 - balance.pre as i128;
+        }
+        if let Some((pre, post)) = self.whale_lamports {
+            changes.entry(NATIVE_SOL_MINT).or_insert((SOL_DECIMALS, 0)).1 += post as i128 - pre as i128;
+        }
+
+        changes
+            .into_iter()
+            .filter(|(mint, (_, delta))| {
+                *delta != 0 && (*mint != NATIVE_SOL_MINT || delta.abs() >= SOL_DUST_LAMPORTS)
+            })
+            .map(|(mint, (decimals, delta))| BalanceChange {
+                mint: mint.to_string(),
+                decimals,
+                delta,
+            })
+            .collect()
+    }
+
+    /// The whale's holding of `mint` before and after the transaction
+    fn whale_position(&self, mint: &str) -> (u128, u128) {
+        let (mut before, mut after) = self
+            .whale_token_balances()
+            .filter(|balance| balance.mint == mint)
+            .fold((0, 0), |(before, after), balance| (before + balance.pre, after + balance.post));
+        if mint == NATIVE_SOL_MINT {
+            if let Some((pre, post)) = self.whale_lamports {
+                before += pre as u128;
+                after += post as u128;
+            }
+        }
+        (before, after)
+    }
+
+    fn whale_token_balances(&self) -> impl Iterator<Item = &TokenBalance> {
+        self.token_balances
+            .values()
+            .filter(|balance| balance.owner.as_deref() == Some(self.whale.as_str()))
+    }
+
+    fn percent_of_position(&self, kind: &MovementKind) -> Option<f64> {
+        let token = kind.primary_token();
+        let amount = token.amount.parse::<f64>().ok()?;
+        let (before, after) = self.whale_position(&token.mint);
+        let position = if kind.is_outflow() { before } else { after };
+        if position == 0 {
+            return None;
+        }
+        Some((amount / position as f64 * 100.0).min(100.0))
+    }
+}
+
+/// Turns instructions of one or more programs into movements
+pub trait InstructionDecoder: Send + Sync {
+    /// Programs this decoder handles
+    fn program_ids(&self) -> Vec<String>;
+
+    /// The whale movement `instruction` makes, if any
+    fn decode(&self, instruction: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind>;
+}
+
+/// SOL transfers through the System program
+pub struct SystemDecoder;
+
+impl InstructionDecoder for SystemDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![SYSTEM_PROGRAM_ID.to_string()]
+    }
+
+    fn decode(&self, instruction: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind> {
+        if !matches!(instruction.instruction_type.as_deref(), Some("transfer" | "transferWithSeed")) {
+            return None;
+        }
+        let source = instruction.info_str("source")?;
+        let destination = instruction.info_str("destination")?;
+        let lamports = instruction.info["lamports"].as_u64()?;
+        let whale = transaction.whale();
+
+        // Funding the whale's own wrapped SOL account is not a transfer
+        let owner = |account: &str| transaction.token_account_owner(account).unwrap_or(account).to_string();
+        let (source_owner, destination_owner) = (owner(source), owner(destination));
+        let (direction, counterparty) = if source_owner == whale && destination_owner != whale {
+            (TransferDirection::Outgoing, destination_owner)
+        } else if destination_owner == whale && source_owner != whale {
+            (TransferDirection::Incoming, source_owner)
+        } else {
+            return None;
+        };
+
+        Some(MovementKind::Transfer {
+            direction,
+            token: TokenAmount::new(NATIVE_SOL_MINT, lamports as u128, SOL_DECIMALS),
+            counterparty: Some(counterparty),
+        })
+    }
+}
+
+/// Token transfers through SPL Token and Token-2022
+pub struct TokenDecoder;
+
+impl InstructionDecoder for TokenDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![TOKEN_PROGRAM_ID.to_string(), TOKEN_2022_PROGRAM_ID.to_string()]
+    }
+
+    fn decode(&self, instruction: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind> {
+        let (amount, mint, decimals) = match instruction.instruction_type.as_deref()? {
+            "transfer" => {
+                let source = instruction.info_str("source")?;
+                let destination = instruction.info_str("destination")?;
+                let (mint, decimals) = transaction
+                    .token_account_mint(source)
+                    .or_else(|| transaction.token_account_mint(destination))?;
+                (instruction.info_str("amount")?, mint.to_string(), decimals)
+            }
+            "transferChecked" => {
+                let token_amount = &instruction.info["tokenAmount"];
+                (
+                    token_amount["amount"].as_str()?,
+                    instruction.info_str("mint")?.to_string(),
+                    token_amount["decimals"].as_u64()? as u8,
+                )
+            }
+            _ => return None,
+        };
+        let amount = amount.parse::<u128>().ok()?;
+        let source = instruction.info_str("source")?;
+        let destination = instruction.info_str("destination")?;
+        let whale = transaction.whale();
+
+        let source_owner = transaction
+            .token_account_owner(source)
+            .or_else(|| instruction.info_str("authority"))
+            .or_else(|| instruction.info_str("multisigAuthority"));
+        let destination_owner = transaction.token_account_owner(destination);
+        let (direction, counterparty) = if source_owner == Some(whale) && destination_owner != Some(whale) {
+            (TransferDirection::Outgoing, destination_owner.unwrap_or(destination))
+        } else if destination_owner == Some(whale) && source_owner != Some(whale) {
+            (TransferDirection::Incoming, source_owner.unwrap_or(source))
+        } else {
+            return None;
+        };
+
+        Some(MovementKind::Transfer {
+            direction,
+            token: TokenAmount::new(&mint, amount, decimals),
+            counterparty: Some(counterparty.to_string()),
+        })
+    }
+}
+
+/// Delegations, deactivations and withdrawals through the Stake program
+pub struct StakeDecoder;
+
+impl InstructionDecoder for StakeDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        vec![STAKE_PROGRAM_ID.to_string()]
+    }
+
+    fn decode(&self, instruction: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind> {
+        let stake_account = instruction.info_str("stakeAccount")?;
+        let whale = Some(transaction.whale());
+        let (action, lamports) = match instruction.instruction_type.as_deref()? {
+            "delegate" if instruction.info_str("stakeAuthority") == whale => {
+                (StakeAction::Delegate, transaction.lamports_after(stake_account))
+            }
+            // Deactivating leaves the lamports in place; report what was staked
+            "deactivate" if instruction.info_str("stakeAuthority") == whale => {
+                (StakeAction::Deactivate, transaction.lamports_before(stake_account))
+            }
+            "withdraw"
+                if instruction.info_str("withdrawAuthority") == whale
+                    || instruction.info_str("destination") == whale =>
+            {
+                (StakeAction::Withdraw, instruction.info["lamports"].as_u64())
+            }
+            _ => return None,
+        };
+
+        Some(MovementKind::Stake {
+            action,
+            stake_account: stake_account.to_string(),
+            vote_account: instruction.info_str("voteAccount").map(str::to_string),
+            lamports,
+        })
+    }
+}
+
+/// Swaps and liquidity changes through DEX programs
+///
+/// DEX instructions are not parsed by the RPC node and differ per program,
+/// so the movement is read from the whale's balance changes instead: one
+/// token out and one in is a swap; several tokens out is a deposit and
+/// several in a withdrawal, with the pool share on the other side.
+pub struct DexDecoder {
+    venues: HashMap<String, String>,
+    liquidity: bool,
+}
+
+impl DexDecoder {
+    /// Decoder for aggregators, which only swap
+    pub fn aggregators(venues: &[(&str, &str)]) -> Self {
+        Self::with_venues(venues, false)
+    }
+
+    /// Decoder for AMMs, which swap and take liquidity
+    pub fn amms(venues: &[(&str, &str)]) -> Self {
+        Self::with_venues(venues, true)
+    }
+
+    fn with_venues(venues: &[(&str, &str)], liquidity: bool) -> Self {
+        Self {
+            venues: venues
+                .iter()
+                .map(|(program_id, name)| (program_id.to_string(), name.to_string()))
+                .collect(),
+            liquidity,
+        }
+    }
+}
+
+impl InstructionDecoder for DexDecoder {
+    fn program_ids(&self) -> Vec<String> {
+        self.venues.keys().cloned().collect()
+    }
+
+    fn decode(&self, instruction: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind> {
+        let venue = self.venues.get(&instruction.program_id)?.clone();
+        let program_id = instruction.program_id.clone();
+        let amount = |change: &BalanceChange| {
+            TokenAmount::new(&change.mint, change.delta.unsigned_abs(), change.decimals)
+        };
+
+        let (outgoing, incoming): (Vec<_>, Vec<_>) =
+            transaction.whale_changes().into_iter().partition(|change| change.delta < 0);
+        match (outgoing.as_slice(), incoming.as_slice()) {
+            ([paid], [received]) => Some(MovementKind::Swap {
+                venue,
+                program_id,
+                token_in: amount(paid),
+                token_out: amount(received),
+            }),
+            (deposited, share) if self.liquidity && deposited.len() >= 2 && share.len() <= 1 => {
+                Some(MovementKind::Liquidity {
+                    action: LiquidityAction::Deposit,
+                    venue,
+                    program_id,
+                    tokens: deposited.iter().map(amount).collect(),
+                    lp_token: share.first().map(amount),
+                })
+            }
+            (share, withdrawn) if self.liquidity && withdrawn.len() >= 2 && share.len() <= 1 => {
+                Some(MovementKind::Liquidity {
+                    action: LiquidityAction::Withdraw,
+                    venue,
+                    program_id,
+                    tokens: withdrawn.iter().map(amount).collect(),
+                    lp_token: share.first().map(amount),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Instruction decoders by program id
+pub struct DecoderRegistry {
+    decoders: HashMap<String, Arc<dyn InstructionDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// Registry with decoders for System, SPL Token, Token-2022, Stake and
+    /// the known DEX aggregators and AMMs
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Arc::new(SystemDecoder));
+        registry.register(Arc::new(TokenDecoder));
+        registry.register(Arc::new(StakeDecoder));
+        registry.register(Arc::new(DexDecoder::aggregators(AGGREGATORS)));
+        registry.register(Arc::new(DexDecoder::amms(AMMS)));
+        registry
+    }
+
+    /// Registry without any decoders
+    pub fn empty() -> Self {
+        Self {
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Use `decoder` for its programs, replacing any decoder registered
+    /// for them before
+    pub fn register(&mut self, decoder: Arc<dyn InstructionDecoder>) {
+        for program_id in decoder.program_ids() {
+            self.decoders.insert(program_id, decoder.clone());
+        }
+    }
+
+    /// Classify a transaction from `whale_address`'s point of view; `None`
+    /// when it failed or no instruction moved the whale's funds
+    pub fn classify(
+        &self,
+        whale_address: &str,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Option<Classification> {
+        let transaction = ParsedTransaction::new(whale_address, transaction)?;
+        let kind = transaction
+            .instructions()
+            .iter()
+            .filter_map(|instruction| {
+                self.decoders
+                    .get(&instruction.program_id)?
+                    .decode(instruction, &transaction)
+            })
+            // The first of the most specific movements
+            .min_by_key(|kind| Reverse(kind.specificity()))?;
+
+        Some(Classification {
+            percent_of_position: transaction.percent_of_position(&kind),
+            kind,
+        })
+    }
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const WHALE: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const OTHER: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+    const BONK_MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const JUPITER: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+    const RAYDIUM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+    fn parsed(program_id: &str, instruction_type: &str, info: Value) -> Value {
+        json!({
+            "program": "test",
+            "programId": program_id,
+            "parsed": { "type": instruction_type, "info": info }
+        })
+    }
+
+    fn decoded(program_id: &str) -> Value {
+        json!({ "programId": program_id, "accounts": [], "data": "" })
+    }
+
+    fn token_balance(account_index: usize, mint: &str, owner: &str, amount: u128, decimals: u8) -> Value {
+        json!({
+            "accountIndex": account_index,
+            "mint": mint,
+            "owner": owner,
+            "programId": TOKEN_PROGRAM_ID,
+            "uiTokenAmount": {
+                "uiAmount": null,
+                "decimals": decimals,
+                "amount": amount.to_string(),
+                "uiAmountString": ""
+            }
+        })
+    }
+
+    /// A JSON-parsed transaction paid for by the first account
+    fn transaction(
+        account_keys: &[&str],
+        instructions: Vec<Value>,
+        lamports: &[(u64, u64)],
+        pre_token_balances: Vec<Value>,
+        post_token_balances: Vec<Value>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let keys: Vec<Value> = account_keys
+            .iter()
+            .map(|key| json!({ "pubkey": key, "writable": true, "signer": false, "source": "transaction" }))
+            .collect();
+        serde_json::from_value(json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": keys,
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": instructions
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": lamports.iter().map(|(pre, _)| *pre).collect::<Vec<_>>(),
+                "postBalances": lamports.iter().map(|(_, post)| *post).collect::<Vec<_>>(),
+                "innerInstructions": [],
+                "preTokenBalances": pre_token_balances,
+                "postTokenBalances": post_token_balances
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sol_transfer() {
+        let tx = transaction(
+            &[WHALE, OTHER, SYSTEM_PROGRAM_ID],
+            vec![parsed(
+                SYSTEM_PROGRAM_ID,
+                "transfer",
+                json!({ "source": WHALE, "destination": OTHER, "lamports": 25_000_000_000u64 }),
+            )],
+            &[(100_000_005_000, 75_000_000_000), (0, 25_000_000_000), (1, 1)],
+            vec![],
+            vec![],
+        );
+
+        let classification = DecoderRegistry::new().classify(WHALE, &tx).unwrap();
+        assert_eq!(
+            classification.kind,
+            MovementKind::Transfer {
+                direction: TransferDirection::Outgoing,
+                token: TokenAmount::new(NATIVE_SOL_MINT, 25_000_000_000, 9),
+                counterparty: Some(OTHER.to_string()),
+            }
+        );
+        assert_eq!(classification.kind.movement_type(), "SEND");
+        assert!((classification.percent_of_position.unwrap() - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_token_2022_transfer_received() {
+        let (source, destination) = ("SrcTokenAccount1111111111111111111111111111", "DstTokenAccount1111111111111111111111111111");
+        let tx = transaction(
+            &[OTHER, source, destination, TOKEN_2022_PROGRAM_ID],
+            vec![parsed(
+                TOKEN_2022_PROGRAM_ID,
+                "transferChecked",
+                json!({
+                    "source": source,
+                    "destination": destination,
+                    "mint": BONK_MINT,
+                    "authority": OTHER,
+                    "tokenAmount": { "amount": "400000", "decimals": 5, "uiAmount": 4.0, "uiAmountString": "4" }
+                }),
+            )],
+            &[(1_000_000_000, 999_995_000), (0, 0), (0, 0), (1, 1)],
+            vec![token_balance(1, BONK_MINT, OTHER, 400_000, 5), token_balance(2, BONK_MINT, WHALE, 600_000, 5)],
+            vec![token_balance(1, BONK_MINT, OTHER, 0, 5), token_balance(2, BONK_MINT, WHALE, 1_000_000, 5)],
+        );
+
+        let classification = DecoderRegistry::new().classify(WHALE, &tx).unwrap();
+        assert_eq!(
+            classification.kind,
+            MovementKind::Transfer {
+                direction: TransferDirection::Incoming,
+                token: TokenAmount::new(BONK_MINT, 400_000, 5),
+                counterparty: Some(OTHER.to_string()),
+            }
+        );
+        assert_eq!(classification.kind.movement_type(), "RECEIVE");
+        assert!((classification.percent_of_position.unwrap() - 40.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_aggregator_swap_is_a_buy() {
+        let whale_bonk = "WhaleBonkAccount11111111111111111111111111111";
+        let tx = transaction(
+            &[WHALE, whale_bonk, JUPITER],
+            // The swap's own token transfers are outranked by the swap
+            vec![
+                decoded(JUPITER),
+                parsed(
+                    SYSTEM_PROGRAM_ID,
+                    "transfer",
+                    json!({ "source": WHALE, "destination": OTHER, "lamports": 2_000_000_000u64 }),
+                ),
+            ],
+            // Paid 2 SOL, plus the fee and rent for the new token account
+            &[(10_000_000_000, 7_997_955_000), (0, 2_039_280), (1, 1)],
+            vec![],
+            vec![token_balance(1, BONK_MINT, WHALE, 5_000_000_000, 5)],
+        );
+
+        let classification = DecoderRegistry::new().classify(WHALE, &tx).unwrap();
+        assert_eq!(
+            classification.kind,
+            MovementKind::Swap {
+                venue: "Jupiter".to_string(),
+                program_id: JUPITER.to_string(),
+                token_in: TokenAmount::new(NATIVE_SOL_MINT, 2_002_040_000, 9),
+                token_out: TokenAmount::new(BONK_MINT, 5_000_000_000, 5),
+            }
+        );
+        assert_eq!(classification.kind.movement_type(), "BUY");
+        assert_eq!(classification.kind.primary_token().mint, BONK_MINT);
+        assert_eq!(classification.percent_of_position, Some(100.0));
+    }
+
+    #[test]
+    fn test_amm_liquidity_deposit() {
+        let (whale_usdc, whale_bonk, whale_lp) = (
+            "WhaleUsdcAccount11111111111111111111111111111",
+            "WhaleBonkAccount11111111111111111111111111111",
+            "WhaleLpAccount1111111111111111111111111111111",
+        );
+        let lp_mint = "LpMint1111111111111111111111111111111111111";
+        let tx = transaction(
+            &[WHALE, whale_usdc, whale_bonk, whale_lp, RAYDIUM],
+            vec![decoded(RAYDIUM)],
+            &[(1_000_000_000, 999_995_000), (0, 0), (0, 0), (0, 0), (1, 1)],
+            vec![
+                token_balance(1, USDC_MINT, WHALE, 500_000_000, 6),
+                token_balance(2, BONK_MINT, WHALE, 9_000_000, 5),
+                token_balance(3, lp_mint, WHALE, 0, 9),
+            ],
+            vec![
+                token_balance(1, USDC_MINT, WHALE, 400_000_000, 6),
+                token_balance(2, BONK_MINT, WHALE, 8_000_000, 5),
+                token_balance(3, lp_mint, WHALE, 77, 9),
+            ],
+        );
+
+        let kind = DecoderRegistry::new().classify(WHALE, &tx).unwrap().kind;
+        assert_eq!(
+            kind,
+            MovementKind::Liquidity {
+                action: LiquidityAction::Deposit,
+                venue: "Raydium AMM".to_string(),
+                program_id: RAYDIUM.to_string(),
+                tokens: vec![
+                    TokenAmount::new(BONK_MINT, 1_000_000, 5),
+                    TokenAmount::new(USDC_MINT, 100_000_000, 6),
+                ],
+                lp_token: Some(TokenAmount::new(lp_mint, 77, 9)),
+            }
+        );
+        assert_eq!(kind.movement_type(), "LP_ADD");
+    }
+
+    #[test]
+    fn test_stake_delegation() {
+        let (stake_account, vote_account) = (
+            "StakeAccount11111111111111111111111111111111",
+            "VoteAccount111111111111111111111111111111111",
+        );
+        let tx = transaction(
+            &[WHALE, stake_account, vote_account, STAKE_PROGRAM_ID],
+            vec![
+                parsed(
+                    SYSTEM_PROGRAM_ID,
+                    "createAccount",
+                    json!({ "source": WHALE, "newAccount": stake_account, "lamports": 50_000_000_000u64 }),
+                ),
+                parsed(
+                    STAKE_PROGRAM_ID,
+                    "delegate",
+                    json!({ "stakeAccount": stake_account, "voteAccount": vote_account, "stakeAuthority": WHALE }),
+                ),
+            ],
+            &[(200_000_005_000, 150_000_000_000), (0, 50_000_000_000), (1, 1), (1, 1)],
+            vec![],
+            vec![],
+        );
+
+        let classification = DecoderRegistry::new().classify(WHALE, &tx).unwrap();
+        assert_eq!(
+            classification.kind,
+            MovementKind::Stake {
+                action: StakeAction::Delegate,
+                stake_account: stake_account.to_string(),
+                vote_account: Some(vote_account.to_string()),
+                lamports: Some(50_000_000_000),
+            }
+        );
+        assert_eq!(classification.kind.movement_type(), "STAKE");
+        assert!((classification.percent_of_position.unwrap() - 25.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_failed_and_unrelated_transactions_are_not_classified() {
+        let transfer = parsed(
+            SYSTEM_PROGRAM_ID,
+            "transfer",
+            json!({ "source": WHALE, "destination": OTHER, "lamports": 1_000_000_000u64 }),
+        );
+        let mut failed = transaction(&[WHALE, OTHER], vec![transfer], &[(2_000_000_000, 1_999_995_000), (0, 0)], vec![], vec![]);
+        failed.transaction.meta.as_mut().unwrap().err =
+            Some(solana_sdk::transaction::TransactionError::AccountNotFound);
+        assert!(DecoderRegistry::new().classify(WHALE, &failed).is_none());
+
+        let unknown = transaction(
+            &[WHALE, OTHER],
+            vec![decoded("ComputeBudget111111111111111111111111111111")],
+            &[(2_000_000_000, 1_999_995_000), (0, 0)],
+            vec![],
+            vec![],
+        );
+        assert!(DecoderRegistry::new().classify(WHALE, &unknown).is_none());
+    }
+
+    struct InboundDecoder;
+
+    impl InstructionDecoder for InboundDecoder {
+        fn program_ids(&self) -> Vec<String> {
+            vec![SYSTEM_PROGRAM_ID.to_string()]
+        }
+
+        fn decode(&self, _: &Instruction, transaction: &ParsedTransaction) -> Option<MovementKind> {
+            Some(MovementKind::Transfer {
+                direction: TransferDirection::Incoming,
+                token: TokenAmount::new(NATIVE_SOL_MINT, 1, 9),
+                counterparty: Some(transaction.whale().to_string()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_registered_decoder_replaces_builtin() {
+        let tx = transaction(
+            &[WHALE, OTHER],
+            vec![parsed(
+                SYSTEM_PROGRAM_ID,
+                "transfer",
+                json!({ "source": WHALE, "destination": OTHER, "lamports": 1_000_000_000u64 }),
+            )],
+            &[(2_000_000_000, 999_995_000), (0, 1_000_000_000)],
+            vec![],
+            vec![],
+        );
+
+        let mut registry = DecoderRegistry::new();
+        registry.register(Arc::new(InboundDecoder));
+        let kind = registry.classify(WHALE, &tx).unwrap().kind;
+        assert_eq!(kind.movement_type(), "RECEIVE");
+        assert!(DecoderRegistry::empty().classify(WHALE, &tx).is_none());
+    }
+
+    #[test]
+    fn test_movement_kind_serialization() {
+        let kind = MovementKind::Stake {
+            action: StakeAction::Deactivate,
+            stake_account: "stake".to_string(),
+            vote_account: None,
+            lamports: Some(1),
+        };
+        let value = serde_json::to_value(&kind).unwrap();
+        assert_eq!(value["kind"], "stake");
+        assert_eq!(value["action"], "deactivate");
+        assert_eq!(serde_json::from_value::<MovementKind>(value).unwrap(), kind);
+    }
+}
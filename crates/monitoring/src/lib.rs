@@ -2,14 +2,23 @@ mod worker_pool;
 mod worker;
 mod redis_store;
 mod message_queue;
+mod subscription;
+mod classifier;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod mock_pubsub;
 
 pub use worker_pool::{WorkerPool, WorkerPoolConfig};
 pub use worker::Worker;
 pub use redis_store::RedisStore;
 pub use message_queue::{MessageQueueClient, WhaleMovementEvent};
+pub use classifier::{
+    Classification, DecoderRegistry, DexDecoder, Instruction, InstructionDecoder, LiquidityAction, MovementKind,
+    ParsedTransaction, StakeAction, StakeDecoder, SystemDecoder, TokenAmount, TokenDecoder, TransferDirection,
+};
+pub use subscription::{websocket_url, MonitoringMode, SubscriptionConfig, WhaleNotification, WhaleSubscriber};
 
 use shared::Result;
 
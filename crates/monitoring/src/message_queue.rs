@@ -1,3 +1,4 @@
+use crate::classifier::MovementKind;
 use aws_sdk_sqs::Client as SqsClient;
 use serde::{Deserialize, Serialize};
 use shared::{Error, Result};
@@ -16,6 +17,7 @@ pub struct MessageQueueClient {
 /// 
 /// This event includes all information needed for downstream processing:
 /// - Whale movement details (address, transaction, type, amount)
+/// - What the transaction did: swap, transfer, stake or liquidity change
 /// - Affected user IDs who are tracking this whale
 /// 
 /// **Validates: Requirements 3.2**
@@ -27,7 +29,8 @@ pub struct WhaleMovementEvent {
     pub whale_address: String,
     /// Solana transaction signature
     pub transaction_signature: String,
-    /// Movement type: BUY or SELL
+    /// Movement type: BUY, SELL, SEND, RECEIVE, STAKE, UNSTAKE, LP_ADD or
+    /// LP_REMOVE
     pub movement_type: String,
     /// Token mint address
     pub token_mint: String,
@@ -35,6 +38,10 @@ pub struct WhaleMovementEvent {
     pub amount: String,
     /// Percentage of whale's total position
     pub percent_of_position: f64,
+    /// Tokens in and out, amounts and counterparties of the movement;
+    /// absent in events published before classification
+    #[serde(default)]
+    pub movement_kind: Option<MovementKind>,
     /// Timestamp when movement was detected
     pub detected_at: chrono::DateTime<chrono::Utc>,
     /// List of user IDs who are tracking this whale
@@ -264,6 +271,7 @@ impl MessageQueueClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::classifier::TokenAmount;
 
     #[test]
     fn test_whale_movement_event_serialization() {
@@ -275,6 +283,20 @@ mod tests {
             token_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
             amount: "1000000".to_string(),
             percent_of_position: 10.5,
+            movement_kind: Some(MovementKind::Swap {
+                venue: "Jupiter".to_string(),
+                program_id: "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string(),
+                token_in: TokenAmount {
+                    mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                    amount: "1000000".to_string(),
+                    decimals: 6,
+                },
+                token_out: TokenAmount {
+                    mint: "So11111111111111111111111111111111111111112".to_string(),
+                    amount: "7000000".to_string(),
+                    decimals: 9,
+                },
+            }),
             detected_at: chrono::Utc::now(),
             affected_user_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
         };
@@ -283,11 +305,13 @@ mod tests {
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("whale_address"));
         assert!(json.contains("affected_user_ids"));
+        assert!(json.contains(r#""kind":"swap""#));
 
         // Test deserialization
         let deserialized: WhaleMovementEvent = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.whale_address, event.whale_address);
         assert_eq!(deserialized.affected_user_ids.len(), 2);
+        assert_eq!(deserialized.movement_kind, event.movement_kind);
     }
 
     #[test]
@@ -303,6 +327,7 @@ mod tests {
             token_mint: "test_mint".to_string(),
             amount: "5000000".to_string(),
             percent_of_position: 7.5,
+            movement_kind: None,
             detected_at: chrono::Utc::now(),
             affected_user_ids: vec![user_id_1, user_id_2],
         };
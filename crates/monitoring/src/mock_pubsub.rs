@@ -0,0 +1,245 @@
+//! Local stand-in for a Solana RPC PubSub endpoint, for testing
+//! subscriptions offline
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+enum Outbound {
+    Text(String),
+    Close,
+}
+
+/// A subscription open on the current connection
+struct Subscription {
+    id: u64,
+    method: String,
+    address: String,
+}
+
+#[derive(Default)]
+struct State {
+    connections: usize,
+    next_subscription_id: u64,
+    subscriptions: Vec<Subscription>,
+    /// Methods of every request received, in order
+    requests: Vec<String>,
+    outbound: Option<mpsc::UnboundedSender<Outbound>>,
+}
+
+/// Answers subscribe and unsubscribe requests and pushes notifications the
+/// test asks for; serves one connection at a time
+pub struct MockPubsubServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockPubsubServer {
+    pub async fn start() -> Self {
+        Self::start_on("127.0.0.1:0".parse().unwrap()).await
+    }
+
+    pub async fn start_on(addr: SocketAddr) -> Self {
+        let listener = TcpListener::bind(addr).await.expect("bind mock pubsub server");
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let accept_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(ws) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                tokio::spawn(serve(ws, accept_state.clone()));
+            }
+        });
+
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Connections accepted so far
+    pub async fn connections(&self) -> usize {
+        self.state.lock().await.connections
+    }
+
+    /// Addresses with an open subscription of `method`, e.g. `logsSubscribe`
+    pub async fn subscribed_addresses(&self, method: &str) -> Vec<String> {
+        let state = self.state.lock().await;
+        let mut addresses: Vec<String> = state
+            .subscriptions
+            .iter()
+            .filter(|s| s.method == method)
+            .map(|s| s.address.clone())
+            .collect();
+        addresses.sort();
+        addresses
+    }
+
+    /// Wait until a request for `method` has been received
+    pub async fn wait_for_request(&self, method: &str) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !self.state.lock().await.requests.iter().any(|m| m == method) {
+            assert!(Instant::now() < deadline, "no {} request received", method);
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Notify the logs subscription for `address` of a transaction
+    pub async fn send_logs(&self, address: &str, signature: &str, succeeded: bool) {
+        let id = self.subscription_id("logsSubscribe", address).await;
+        let err = if succeeded { Value::Null } else { json!({ "InstructionError": [0, "InvalidArgument"] }) };
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 1 },
+                    "value": { "signature": signature, "err": err, "logs": [] }
+                },
+                "subscription": id
+            }
+        }))
+        .await;
+    }
+
+    /// Notify the account subscription for `address` of a balance change
+    pub async fn send_account_update(&self, address: &str) {
+        let id = self.subscription_id("accountSubscribe", address).await;
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "accountNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 1 },
+                    "value": {
+                        "lamports": 1_000_000_000u64,
+                        "data": ["", "base64"],
+                        "owner": "11111111111111111111111111111111",
+                        "executable": false,
+                        "rentEpoch": 0,
+                        "space": 0
+                    }
+                },
+                "subscription": id
+            }
+        }))
+        .await;
+    }
+
+    /// Close the current connection
+    pub async fn disconnect(&self) {
+        let mut state = self.state.lock().await;
+        if let Some(outbound) = state.outbound.take() {
+            let _ = outbound.send(Outbound::Close);
+        }
+        state.subscriptions.clear();
+    }
+
+    /// Id of the open `method` subscription for `address`, waiting for the
+    /// client to subscribe
+    async fn subscription_id(&self, method: &str, address: &str) -> u64 {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let id = self
+                .state
+                .lock()
+                .await
+                .subscriptions
+                .iter()
+                .find(|s| s.method == method && s.address == address)
+                .map(|s| s.id);
+            if let Some(id) = id {
+                return id;
+            }
+            assert!(Instant::now() < deadline, "no {} subscription for {}", method, address);
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    async fn send(&self, message: Value) {
+        let state = self.state.lock().await;
+        let outbound = state.outbound.as_ref().expect("client connected");
+        outbound.send(Outbound::Text(message.to_string())).expect("connection open");
+    }
+}
+
+async fn serve(
+    ws: tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    state: Arc<Mutex<State>>,
+) {
+    let (mut sink, mut stream) = ws.split();
+    let (outbound, mut outbound_receiver) = mpsc::unbounded_channel();
+    {
+        let mut state = state.lock().await;
+        state.connections += 1;
+        state.subscriptions.clear();
+        state.outbound = Some(outbound);
+    }
+
+    loop {
+        tokio::select! {
+            message = outbound_receiver.recv() => match message {
+                Some(Outbound::Text(text)) => {
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Outbound::Close) | None => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    break;
+                }
+            },
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(data))) => {
+                        let _ = sink.send(Message::Pong(data)).await;
+                        continue;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let request: Value = serde_json::from_str(&text).expect("JSON-RPC request");
+                let reply = respond(&request, &state).await;
+                if sink.send(Message::Text(reply.to_string())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn respond(request: &Value, state: &Mutex<State>) -> Value {
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let params = &request["params"];
+    let mut state = state.lock().await;
+    state.requests.push(method.clone());
+
+    let result = if method.ends_with("Unsubscribe") {
+        let id = params[0].as_u64();
+        state.subscriptions.retain(|s| Some(s.id) != id);
+        json!(true)
+    } else {
+        let address = match method.as_str() {
+            "logsSubscribe" => params[0]["mentions"][0].as_str(),
+            _ => params[0].as_str(),
+        }
+        .unwrap_or_default()
+        .to_string();
+        state.next_subscription_id += 1;
+        let id = state.next_subscription_id;
+        state.subscriptions.push(Subscription { id, method, address });
+        json!(id)
+    };
+
+    json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] })
+}
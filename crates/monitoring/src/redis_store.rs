@@ -50,6 +50,45 @@ impl RedisStore {
         Ok(())
     }
 
+    /// Record a failed attempt at processing a whale transaction, returning
+    /// how many attempts have failed so far
+    pub async fn record_failed_transaction(&self, whale_address: &str, signature: &str) -> Result<u64> {
+        let key = format!("whale:{}:failed_tx", whale_address);
+        let mut conn = self.client.lock().await;
+        
+        let attempts: u64 = conn
+            .hincr(&key, signature, 1)
+            .await
+            .map_err(|e| shared::Error::Redis(format!("Failed to record failed transaction: {}", e)))?;
+        
+        Ok(attempts)
+    }
+
+    /// Get the whale transactions waiting to be retried
+    pub async fn get_failed_transactions(&self, whale_address: &str) -> Result<Vec<String>> {
+        let key = format!("whale:{}:failed_tx", whale_address);
+        let mut conn = self.client.lock().await;
+        
+        let result: Vec<String> = conn
+            .hkeys(&key)
+            .await
+            .map_err(|e| shared::Error::Redis(format!("Failed to get failed transactions: {}", e)))?;
+        
+        Ok(result)
+    }
+
+    /// Stop retrying a whale transaction
+    pub async fn clear_failed_transaction(&self, whale_address: &str, signature: &str) -> Result<()> {
+        let key = format!("whale:{}:failed_tx", whale_address);
+        let mut conn = self.client.lock().await;
+        
+        conn.hdel::<_, _, ()>(&key, signature)
+            .await
+            .map_err(|e| shared::Error::Redis(format!("Failed to clear failed transaction: {}", e)))?;
+        
+        Ok(())
+    }
+
     /// Get cached whale holdings
     pub async fn get_whale_holdings(&self, whale_address: &str) -> Result<Option<String>> {
         let key = format!("whale:{}:holdings", whale_address);
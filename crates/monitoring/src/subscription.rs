@@ -0,0 +1,430 @@
+use futures::future::{ready, BoxFuture};
+use futures::stream::{BoxStream, SelectAll, StreamExt};
+use shared::{Error, Result};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How workers find new whale transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MonitoringMode {
+    /// Poll `getSignaturesForAddress` for every whale each check interval
+    #[default]
+    Polling,
+    /// Subscribe to whale transactions over the RPC WebSocket, polling only
+    /// to backfill after a disconnect and while disconnected
+    Subscription,
+}
+
+impl MonitoringMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MonitoringMode::Polling => "polling",
+            MonitoringMode::Subscription => "subscription",
+        }
+    }
+}
+
+impl FromStr for MonitoringMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "polling" => Ok(MonitoringMode::Polling),
+            "subscription" => Ok(MonitoringMode::Subscription),
+            other => Err(Error::Validation(format!(
+                "Unknown monitoring mode '{}', expected polling or subscription",
+                other
+            ))),
+        }
+    }
+}
+
+/// WebSocket endpoint served alongside an HTTP RPC endpoint, e.g.
+/// `https://api.devnet.solana.com` becomes `wss://api.devnet.solana.com`
+pub fn websocket_url(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+/// Configuration for WebSocket subscriptions
+#[derive(Debug, Clone)]
+pub struct SubscriptionConfig {
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    /// Delay before the first reconnection attempt, doubled after each
+    /// failed attempt up to `max_reconnect_delay`
+    pub reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    /// Also subscribe to each whale's account and check it for new
+    /// signatures whenever its balance changes
+    pub account_updates: bool,
+}
+
+impl SubscriptionConfig {
+    pub fn new(ws_url: impl Into<String>) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            commitment: CommitmentConfig::confirmed(),
+            reconnect_delay: Duration::from_secs(1),
+            max_reconnect_delay: Duration::from_secs(60),
+            account_updates: false,
+        }
+    }
+
+    pub fn with_reconnect_delay(mut self, initial: Duration, max: Duration) -> Self {
+        self.reconnect_delay = initial;
+        self.max_reconnect_delay = max.max(initial);
+        self
+    }
+
+    pub fn with_account_updates(mut self, account_updates: bool) -> Self {
+        self.account_updates = account_updates;
+        self
+    }
+}
+
+/// What a [`WhaleSubscriber`] reports to its worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhaleNotification {
+    /// Subscribed to every whale on a new connection; transactions made
+    /// before this point, including any while disconnected, must be
+    /// backfilled by polling
+    Connected,
+    /// The connection dropped; reconnection is being retried
+    Disconnected,
+    /// A successful transaction mentioning the whale (`logsSubscribe`)
+    Transaction { whale_address: String, signature: String },
+    /// The whale's account changed (`accountSubscribe`)
+    AccountChanged { whale_address: String },
+}
+
+type Unsubscribe = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// Keeps a PubSub connection subscribed to a worker's whales
+///
+/// Reconnects with exponential backoff and resubscribes every whale after a
+/// disconnect. Whales added or removed through `whales` are subscribed or
+/// unsubscribed when `whales_changed` is notified.
+pub struct WhaleSubscriber {
+    config: SubscriptionConfig,
+    whales: Arc<RwLock<Vec<String>>>,
+    whales_changed: Arc<Notify>,
+}
+
+impl WhaleSubscriber {
+    pub fn new(config: SubscriptionConfig, whales: Arc<RwLock<Vec<String>>>, whales_changed: Arc<Notify>) -> Self {
+        Self {
+            config,
+            whales,
+            whales_changed,
+        }
+    }
+
+    /// Spawn the subscription task; it stops once the returned receiver is
+    /// dropped
+    pub fn spawn(self) -> (mpsc::Receiver<WhaleNotification>, JoinHandle<()>) {
+        let (sender, receiver) = mpsc::channel(1024);
+        let handle = tokio::spawn(async move { self.run(sender).await });
+        (receiver, handle)
+    }
+
+    async fn run(self, notifications: mpsc::Sender<WhaleNotification>) {
+        let mut delay = self.config.reconnect_delay;
+
+        loop {
+            match PubsubClient::new(&self.config.ws_url).await {
+                Ok(client) => {
+                    info!("Connected to {}", self.config.ws_url);
+                    delay = self.config.reconnect_delay;
+
+                    let result = self.stream(&client, &notifications).await;
+                    let _ = client.shutdown().await;
+                    match result {
+                        Ok(()) if notifications.is_closed() => return,
+                        Ok(()) => warn!("Subscription connection to {} closed", self.config.ws_url),
+                        Err(e) => warn!("Subscription connection to {} failed: {}", self.config.ws_url, e),
+                    }
+                    if notifications.send(WhaleNotification::Disconnected).await.is_err() {
+                        return;
+                    }
+                }
+                Err(e) => warn!("Failed to connect to {}: {}", self.config.ws_url, e),
+            }
+
+            debug!("Reconnecting to {} in {:?}", self.config.ws_url, delay);
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = notifications.closed() => return,
+            }
+            delay = (delay * 2).min(self.config.max_reconnect_delay);
+        }
+    }
+
+    /// Forward notifications from one connection until it drops or the
+    /// receiver goes away
+    async fn stream(
+        &self,
+        client: &PubsubClient,
+        notifications: &mpsc::Sender<WhaleNotification>,
+    ) -> Result<()> {
+        let mut streams = SelectAll::new();
+        let mut subscribed = HashMap::new();
+
+        self.sync_subscriptions(client, &mut streams, &mut subscribed).await?;
+        if notifications.send(WhaleNotification::Connected).await.is_err() {
+            return Ok(());
+        }
+
+        loop {
+            tokio::select! {
+                notification = streams.next(), if !streams.is_empty() => match notification {
+                    Some(notification) => {
+                        if notifications.send(notification).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    // Every stream ends together when the connection drops;
+                    // streams of unsubscribed whales end one by one
+                    None if subscribed.is_empty() => {}
+                    None => return Ok(()),
+                },
+                _ = self.whales_changed.notified() => {
+                    self.sync_subscriptions(client, &mut streams, &mut subscribed).await?;
+                }
+                _ = notifications.closed() => return Ok(()),
+            }
+        }
+    }
+
+    /// Subscribe whales that are not yet subscribed and unsubscribe removed
+    /// ones
+    async fn sync_subscriptions<'a>(
+        &self,
+        client: &'a PubsubClient,
+        streams: &mut SelectAll<BoxStream<'a, WhaleNotification>>,
+        subscribed: &mut HashMap<String, Vec<Unsubscribe>>,
+    ) -> Result<()> {
+        let whales = self.whales.read().await.clone();
+
+        let removed: Vec<String> = subscribed
+            .keys()
+            .filter(|address| !whales.contains(address))
+            .cloned()
+            .collect();
+        for address in removed {
+            for unsubscribe in subscribed.remove(&address).unwrap_or_default() {
+                unsubscribe().await;
+            }
+            debug!("Unsubscribed from whale {}", address);
+        }
+
+        for address in whales {
+            if subscribed.contains_key(&address) {
+                continue;
+            }
+            let pubkey = match Pubkey::from_str(&address) {
+                Ok(pubkey) => pubkey,
+                Err(e) => {
+                    warn!("Not subscribing to invalid whale address {}: {}", address, e);
+                    continue;
+                }
+            };
+
+            let mut unsubscribes = Vec::new();
+
+            let (logs, unsubscribe) = client
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![address.clone()]),
+                    RpcTransactionLogsConfig {
+                        commitment: Some(self.config.commitment),
+                    },
+                )
+                .await
+                .map_err(|e| Error::SolanaRpc(format!("logsSubscribe failed for {}: {}", address, e)))?;
+            let whale_address = address.clone();
+            // Failed transactions moved nothing
+            streams.push(
+                logs.filter_map(move |response| {
+                    ready(response.value.err.is_none().then(|| WhaleNotification::Transaction {
+                        whale_address: whale_address.clone(),
+                        signature: response.value.signature,
+                    }))
+                })
+                .boxed(),
+            );
+            unsubscribes.push(unsubscribe);
+
+            if self.config.account_updates {
+                let (accounts, unsubscribe) = client
+                    .account_subscribe(
+                        &pubkey,
+                        Some(RpcAccountInfoConfig {
+                            commitment: Some(self.config.commitment),
+                            ..Default::default()
+                        }),
+                    )
+                    .await
+                    .map_err(|e| Error::SolanaRpc(format!("accountSubscribe failed for {}: {}", address, e)))?;
+                let whale_address = address.clone();
+                streams.push(
+                    accounts
+                        .map(move |_| WhaleNotification::AccountChanged {
+                            whale_address: whale_address.clone(),
+                        })
+                        .boxed(),
+                );
+                unsubscribes.push(unsubscribe);
+            }
+
+            debug!("Subscribed to whale {}", address);
+            subscribed.insert(address, unsubscribes);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_pubsub::MockPubsubServer;
+    use tokio::time::timeout;
+
+    const WHALE_A: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const WHALE_B: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+
+    fn config(ws_url: String) -> SubscriptionConfig {
+        SubscriptionConfig::new(ws_url)
+            .with_reconnect_delay(Duration::from_millis(20), Duration::from_millis(100))
+    }
+
+    async fn next(receiver: &mut mpsc::Receiver<WhaleNotification>) -> WhaleNotification {
+        timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("notification in time")
+            .expect("subscriber running")
+    }
+
+    fn transaction(whale_address: &str, signature: &str) -> WhaleNotification {
+        WhaleNotification::Transaction {
+            whale_address: whale_address.to_string(),
+            signature: signature.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_monitoring_mode_from_str() {
+        assert_eq!("Subscription".parse::<MonitoringMode>().unwrap(), MonitoringMode::Subscription);
+        assert_eq!("polling".parse::<MonitoringMode>().unwrap(), MonitoringMode::Polling);
+        assert!("webhook".parse::<MonitoringMode>().is_err());
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(websocket_url("https://api.devnet.solana.com"), "wss://api.devnet.solana.com");
+        assert_eq!(websocket_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8899");
+        assert_eq!(websocket_url("wss://rpc.example.com"), "wss://rpc.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_forwards_successful_transactions() {
+        let server = MockPubsubServer::start().await;
+        let whales = Arc::new(RwLock::new(vec![WHALE_A.to_string()]));
+        let (mut receiver, _handle) =
+            WhaleSubscriber::new(config(server.url()), whales, Arc::new(Notify::new())).spawn();
+
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+        server.send_logs(WHALE_A, "failed-sig", false).await;
+        server.send_logs(WHALE_A, "sig-1", true).await;
+        assert_eq!(next(&mut receiver).await, transaction(WHALE_A, "sig-1"));
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_and_resubscribes() {
+        let server = MockPubsubServer::start().await;
+        let whales = Arc::new(RwLock::new(vec![WHALE_A.to_string()]));
+        let (mut receiver, _handle) =
+            WhaleSubscriber::new(config(server.url()), whales, Arc::new(Notify::new())).spawn();
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+
+        server.disconnect().await;
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Disconnected);
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+        assert_eq!(server.connections().await, 2);
+
+        server.send_logs(WHALE_A, "sig-after-reconnect", true).await;
+        assert_eq!(next(&mut receiver).await, transaction(WHALE_A, "sig-after-reconnect"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_server_is_up() {
+        // Reserve a port, then start the server on it after the subscriber
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let whales = Arc::new(RwLock::new(vec![WHALE_A.to_string()]));
+        let (mut receiver, _handle) =
+            WhaleSubscriber::new(config(format!("ws://{}", addr)), whales, Arc::new(Notify::new())).spawn();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let server = MockPubsubServer::start_on(addr).await;
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+        assert_eq!(server.subscribed_addresses("logsSubscribe").await, vec![WHALE_A.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_follows_whale_changes() {
+        let server = MockPubsubServer::start().await;
+        let whales = Arc::new(RwLock::new(vec![WHALE_A.to_string()]));
+        let changed = Arc::new(Notify::new());
+        let (mut receiver, _handle) = WhaleSubscriber::new(
+            config(server.url()).with_account_updates(true),
+            whales.clone(),
+            changed.clone(),
+        )
+        .spawn();
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+        assert_eq!(server.subscribed_addresses("accountSubscribe").await, vec![WHALE_A.to_string()]);
+
+        *whales.write().await = vec![WHALE_B.to_string()];
+        changed.notify_one();
+        server.wait_for_request("logsUnsubscribe").await;
+        server.wait_for_request("accountUnsubscribe").await;
+
+        server.send_account_update(WHALE_B).await;
+        assert_eq!(
+            next(&mut receiver).await,
+            WhaleNotification::AccountChanged { whale_address: WHALE_B.to_string() }
+        );
+        server.send_logs(WHALE_B, "sig-b", true).await;
+        assert_eq!(next(&mut receiver).await, transaction(WHALE_B, "sig-b"));
+        assert_eq!(server.subscribed_addresses("logsSubscribe").await, vec![WHALE_B.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_when_receiver_dropped() {
+        let server = MockPubsubServer::start().await;
+        let whales = Arc::new(RwLock::new(vec![WHALE_A.to_string()]));
+        let (mut receiver, handle) =
+            WhaleSubscriber::new(config(server.url()), whales, Arc::new(Notify::new())).spawn();
+        assert_eq!(next(&mut receiver).await, WhaleNotification::Connected);
+
+        drop(receiver);
+        timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{WorkerPoolConfig, MonitoringEngine};
+    use crate::{WorkerPoolConfig, MonitoringEngine, MonitoringMode};
 
     #[tokio::test]
     #[ignore] // Requires Redis to be running
@@ -13,6 +13,8 @@ mod tests {
             worker_count: 2,
             whales_per_worker: 100,
             check_interval_seconds: 30,
+            mode: MonitoringMode::Polling,
+            solana_ws_url: None,
         };
 
         let result = MonitoringEngine::new(config).await;
@@ -28,6 +30,8 @@ mod tests {
             worker_count: 5,
             whales_per_worker: 100,
             check_interval_seconds: 30,
+            mode: MonitoringMode::Polling,
+            solana_ws_url: None,
         };
 
         assert_eq!(config.worker_count, 5);
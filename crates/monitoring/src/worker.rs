@@ -1,23 +1,32 @@
+use crate::classifier::{DecoderRegistry, MovementKind};
 use crate::redis_store::RedisStore;
 use crate::message_queue::{MessageQueueClient, WhaleMovementEvent};
+use crate::subscription::{SubscriptionConfig, WhaleNotification, WhaleSubscriber};
 use blockchain::SolanaClient;
 use database::DbPool;
 use shared::{Error, Result};
+use solana_client::rpc_config::RpcTransactionConfig;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Attempts at processing a whale transaction before it is given up on
+const MAX_TRANSACTION_ATTEMPTS: u64 = 5;
+
 /// A worker that monitors a set of whale accounts
 pub struct Worker {
     id: usize,
     whale_addresses: Arc<RwLock<Vec<String>>>,
+    whales_changed: Arc<Notify>,
     solana_client: Arc<SolanaClient>,
     redis_store: RedisStore,
     db_pool: Option<DbPool>,
     message_queue: Option<Arc<MessageQueueClient>>,
+    subscription: Option<SubscriptionConfig>,
+    decoders: Arc<DecoderRegistry>,
     check_interval: Duration,
     shutdown_signal: Arc<RwLock<bool>>,
 }
@@ -33,10 +42,13 @@ impl Worker {
         Self {
             id,
             whale_addresses: Arc::new(RwLock::new(Vec::new())),
+            whales_changed: Arc::new(Notify::new()),
             solana_client,
             redis_store,
             db_pool: None,
             message_queue: None,
+            subscription: None,
+            decoders: Arc::new(DecoderRegistry::new()),
             check_interval: Duration::from_secs(check_interval_seconds),
             shutdown_signal: Arc::new(RwLock::new(false)),
         }
@@ -52,11 +64,24 @@ impl Worker {
         self.message_queue = Some(mq_client);
     }
 
+    /// Watch whales through WebSocket subscriptions instead of polling;
+    /// polling is still used to backfill after each (re)connection and
+    /// while disconnected
+    pub fn set_subscription(&mut self, config: SubscriptionConfig) {
+        self.subscription = Some(config);
+    }
+
+    /// Set the instruction decoders used to classify whale transactions
+    pub fn set_decoders(&mut self, decoders: Arc<DecoderRegistry>) {
+        self.decoders = decoders;
+    }
+
     /// Assign whale addresses to this worker
     pub async fn assign_whales(&self, addresses: Vec<String>) -> Result<()> {
         let mut whales = self.whale_addresses.write().await;
         whales.extend(addresses);
         info!("Worker {} now monitoring {} whales", self.id, whales.len());
+        self.whales_changed.notify_one();
         Ok(())
     }
 
@@ -65,6 +90,7 @@ impl Worker {
         let mut whales = self.whale_addresses.write().await;
         whales.retain(|addr| !addresses.contains(addr));
         info!("Worker {} now monitoring {} whales", self.id, whales.len());
+        self.whales_changed.notify_one();
         Ok(())
     }
 
@@ -80,6 +106,20 @@ impl Worker {
         // Register worker as active
         self.redis_store.register_worker(self.id).await?;
         
+        match &self.subscription {
+            Some(config) => self.run_subscribed(config.clone()).await,
+            None => self.run_polling().await,
+        }
+
+        // Unregister worker
+        self.redis_store.unregister_worker(self.id).await?;
+        info!("Worker {} stopped", self.id);
+        
+        Ok(())
+    }
+
+    /// Check every whale each check interval
+    async fn run_polling(&self) {
         let mut ticker = interval(self.check_interval);
         
         loop {
@@ -90,47 +130,149 @@ impl Worker {
             }
 
             ticker.tick().await;
-            
-            // Get current whale list
-            let whales = {
-                let whales_guard = self.whale_addresses.read().await;
-                whales_guard.clone()
-            };
-
-            if whales.is_empty() {
-                debug!("Worker {} has no whales to monitor", self.id);
-                continue;
-            }
+            self.check_all_whales().await;
+        }
+    }
 
-            debug!("Worker {} checking {} whales", self.id, whales.len());
+    /// Handle whale transactions as the subscription reports them
+    ///
+    /// Every (re)connection is followed by a polling pass from each whale's
+    /// last seen signature, which picks up transactions made while
+    /// disconnected. Until the first connection and after a disconnect the
+    /// worker polls each check interval; while connected it only retries
+    /// transactions that failed to process.
+    async fn run_subscribed(&self, config: SubscriptionConfig) {
+        info!("Worker {} subscribing to whales via {}", self.id, config.ws_url);
+        let (mut notifications, subscriber) =
+            WhaleSubscriber::new(config, self.whale_addresses.clone(), self.whales_changed.clone()).spawn();
+        let mut connected = false;
+        let mut ticker = interval(self.check_interval);
 
-            // Check each whale
-            for whale_address in whales {
-                if *self.shutdown_signal.read().await {
-                    break;
+        loop {
+            tokio::select! {
+                notification = notifications.recv() => match notification {
+                    Some(WhaleNotification::Connected) => {
+                        info!("Worker {} subscribed, backfilling missed transactions", self.id);
+                        connected = true;
+                        self.check_all_whales().await;
+                    }
+                    Some(WhaleNotification::Disconnected) => {
+                        warn!("Worker {} subscription lost, polling until reconnected", self.id);
+                        connected = false;
+                    }
+                    Some(WhaleNotification::Transaction { whale_address, signature }) => {
+                        if let Err(e) = self.handle_notified_transaction(&whale_address, &signature).await {
+                            error!(
+                                "Worker {} error handling transaction {} for whale {}: {}",
+                                self.id, signature, whale_address, e
+                            );
+                        }
+                    }
+                    Some(WhaleNotification::AccountChanged { whale_address }) => {
+                        if let Err(e) = self.check_whale_activity(&whale_address).await {
+                            error!(
+                                "Worker {} error checking whale {}: {}",
+                                self.id, whale_address, e
+                            );
+                        }
+                    }
+                    None => {
+                        error!("Worker {} subscription task stopped, falling back to polling", self.id);
+                        return self.run_polling().await;
+                    }
+                },
+                _ = ticker.tick() => {
+                    if *self.shutdown_signal.read().await {
+                        info!("Worker {} received shutdown signal", self.id);
+                        break;
+                    }
+                    if connected {
+                        self.retry_all_failed_transactions().await;
+                    } else {
+                        self.check_all_whales().await;
+                    }
                 }
+            }
+        }
 
-                if let Err(e) = self.check_whale_activity(&whale_address).await {
-                    // Log error but continue monitoring other whales (Requirement 3.5)
-                    error!(
-                        "Worker {} error checking whale {}: {}",
-                        self.id, whale_address, e
-                    );
-                }
+        drop(notifications);
+        if let Err(e) = subscriber.await {
+            warn!("Worker {} subscription task join error: {}", self.id, e);
+        }
+    }
+
+    /// Check every assigned whale for transactions since its last seen
+    /// signature
+    async fn check_all_whales(&self) {
+        // Get current whale list
+        let whales = {
+            let whales_guard = self.whale_addresses.read().await;
+            whales_guard.clone()
+        };
+
+        if whales.is_empty() {
+            debug!("Worker {} has no whales to monitor", self.id);
+            return;
+        }
+
+        debug!("Worker {} checking {} whales", self.id, whales.len());
+
+        // Check each whale
+        for whale_address in whales {
+            if *self.shutdown_signal.read().await {
+                break;
+            }
+
+            if let Err(e) = self.check_whale_activity(&whale_address).await {
+                // Log error but continue monitoring other whales (Requirement 3.5)
+                error!(
+                    "Worker {} error checking whale {}: {}",
+                    self.id, whale_address, e
+                );
             }
         }
+    }
 
-        // Unregister worker
-        self.redis_store.unregister_worker(self.id).await?;
-        info!("Worker {} stopped", self.id);
-        
-        Ok(())
+    /// Retry failed transactions of every assigned whale
+    async fn retry_all_failed_transactions(&self) {
+        let whales = self.whale_addresses.read().await.clone();
+
+        for whale_address in whales {
+            if let Err(e) = self.retry_failed_transactions(&whale_address).await {
+                error!(
+                    "Worker {} error retrying transactions for whale {}: {}",
+                    self.id, whale_address, e
+                );
+            }
+        }
+    }
+
+    /// Process a transaction reported by the subscription and record it as
+    /// the whale's last seen signature
+    ///
+    /// The backfill after a reconnection may already have processed it;
+    /// movements are stored once per signature, so it is not reported twice.
+    async fn handle_notified_transaction(&self, whale_address: &str, signature: &str) -> Result<()> {
+        info!(
+            "Worker {} notified of transaction {} for whale {}",
+            self.id, signature, whale_address
+        );
+
+        // A failed transaction is queued for retry, as a later one moves the
+        // last seen signature past it
+        self.process_or_queue_retry(whale_address, signature).await;
+
+        self.redis_store
+            .set_last_transaction(whale_address, signature)
+            .await
     }
 
     /// Check a single whale account for new transactions
     async fn check_whale_activity(&self, whale_address: &str) -> Result<()> {
         debug!("Worker {} checking whale {}", self.id, whale_address);
 
+        self.retry_failed_transactions(whale_address).await?;
+
         // Get the last checked transaction signature from Redis
         let last_signature = self.redis_store.get_last_transaction(whale_address).await?;
 
@@ -149,15 +291,10 @@ impl Worker {
             whale_address
         );
 
-        // Process each new transaction
+        // Process each new transaction, queueing failures for retry so the
+        // others are not held up (Requirement 3.5)
         for signature in &signatures {
-            if let Err(e) = self.process_transaction(whale_address, signature).await {
-                error!(
-                    "Worker {} error processing transaction {} for whale {}: {}",
-                    self.id, signature, whale_address, e
-                );
-                // Continue processing other transactions (Requirement 3.5)
-            }
+            self.process_or_queue_retry(whale_address, signature).await;
         }
 
         // Update the last checked signature to the most recent one
@@ -170,6 +307,67 @@ impl Worker {
         Ok(())
     }
 
+    /// Process a transaction, queueing it for retry on the whale's next check
+    /// if that fails
+    async fn process_or_queue_retry(&self, whale_address: &str, signature: &str) {
+        let Err(e) = self.process_transaction(whale_address, signature).await else {
+            return;
+        };
+
+        error!(
+            "Worker {} error processing transaction {} for whale {}: {}",
+            self.id, signature, whale_address, e
+        );
+        if let Err(e) = self
+            .redis_store
+            .record_failed_transaction(whale_address, signature)
+            .await
+        {
+            error!(
+                "Worker {} could not queue transaction {} for retry: {}",
+                self.id, signature, e
+            );
+        }
+    }
+
+    /// Retry transactions of a whale that failed to process earlier, giving
+    /// up on one after `MAX_TRANSACTION_ATTEMPTS`
+    async fn retry_failed_transactions(&self, whale_address: &str) -> Result<()> {
+        for signature in self.redis_store.get_failed_transactions(whale_address).await? {
+            match self.process_transaction(whale_address, &signature).await {
+                Ok(()) => {
+                    info!(
+                        "Worker {} processed transaction {} for whale {} on retry",
+                        self.id, signature, whale_address
+                    );
+                }
+                Err(e) => {
+                    let attempts = self
+                        .redis_store
+                        .record_failed_transaction(whale_address, &signature)
+                        .await?;
+                    if attempts < MAX_TRANSACTION_ATTEMPTS {
+                        warn!(
+                            "Worker {} retry {} of transaction {} for whale {} failed: {}",
+                            self.id, attempts, signature, whale_address, e
+                        );
+                        continue;
+                    }
+                    error!(
+                        "Worker {} giving up on transaction {} for whale {} after {} attempts: {}",
+                        self.id, signature, whale_address, attempts, e
+                    );
+                }
+            }
+
+            self.redis_store
+                .clear_failed_transaction(whale_address, &signature)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     /// Get recent transaction signatures for a whale address
     async fn get_recent_signatures(
         &self,
@@ -219,8 +417,14 @@ impl Worker {
         // Fetch transaction details
         let transaction = self.fetch_transaction_details(whale_address, signature).await?;
 
-        // Analyze the transaction to determine movement type and amount
-        let movement = self.analyze_transaction(whale_address, &transaction).await?;
+        // Classify the transaction to determine movement type and amount
+        let Some(movement) = self.analyze_transaction(whale_address, &transaction) else {
+            debug!(
+                "Worker {} found no whale movement in transaction {}",
+                self.id, signature
+            );
+            return Ok(());
+        };
 
         // Filter movements below 5% threshold (Requirement 3.4)
         if let Some(percent) = movement.percent_of_position {
@@ -257,11 +461,17 @@ impl Worker {
 
         let _pubkey = self.solana_client.validate_address(whale_address)?;
 
-        // Get transaction with full details
+        // Get the parsed transaction, including versioned ones routed
+        // through lookup tables
+        let config = RpcTransactionConfig {
+            encoding: Some(solana_transaction_status::UiTransactionEncoding::JsonParsed),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        };
         match self
             .solana_client
             .primary_client()
-            .get_transaction(&sig, solana_transaction_status::UiTransactionEncoding::Json)
+            .get_transaction_with_config(&sig, config)
         {
             Ok(tx) => {
                 // Parse the transaction to extract relevant details
@@ -284,57 +494,24 @@ impl Worker {
         }
     }
 
-    /// Analyze a transaction to determine movement type and amount
-    async fn analyze_transaction(
+    /// Classify a transaction to determine movement type and amount
+    fn analyze_transaction(
         &self,
         whale_address: &str,
         transaction: &TransactionDetails,
-    ) -> Result<WhaleMovementData> {
-        // For now, we'll implement a simplified version that detects token transfers
-        // A full implementation would parse instruction data to determine buy/sell
-        
-        // Get the whale's current token balances to calculate percentage
-        let whale_balance = self.solana_client.get_wallet_balance(whale_address).await?;
-
-        // Parse transaction to find token transfers
-        // This is a simplified implementation - a production version would need
-        // more sophisticated parsing of instruction data
-        
-        // For demonstration, we'll create a placeholder movement
-        // In a real implementation, you would:
-        // 1. Parse the transaction instructions
-        // 2. Identify token program instructions (transfer, transferChecked)
-        // 3. Determine if it's a buy (receiving tokens) or sell (sending tokens)
-        // 4. Extract the token mint and amount
-        
-        // Placeholder: assume first token account has movement
-        if let Some(token_account) = whale_balance.token_accounts.first() {
-            let movement_type = "SELL"; // Simplified - would be determined from tx analysis
-            let amount = "1000000"; // Simplified - would be extracted from tx
-            
-            // Calculate percentage of position
-            let percent_of_position = if token_account.amount > 0 {
-                let amount_value = amount.parse::<u64>().unwrap_or(0) as f64;
-                let total_value = token_account.amount as f64;
-                (amount_value / total_value) * 100.0
-            } else {
-                0.0
-            };
-
-            Ok(WhaleMovementData {
-                whale_address: whale_address.to_string(),
-                transaction_signature: transaction.signature.clone(),
-                movement_type: movement_type.to_string(),
-                token_mint: token_account.mint.clone(),
-                amount: amount.to_string(),
-                percent_of_position: Some(percent_of_position),
-            })
-        } else {
-            // No token accounts found - might be a SOL-only transaction
-            Err(Error::Internal(
-                "Unable to determine movement details from transaction".to_string(),
-            ))
-        }
+    ) -> Option<WhaleMovementData> {
+        let classification = self.decoders.classify(whale_address, &transaction.transaction)?;
+        let token = classification.kind.primary_token();
+
+        Some(WhaleMovementData {
+            whale_address: whale_address.to_string(),
+            transaction_signature: transaction.signature.clone(),
+            movement_type: classification.kind.movement_type().to_string(),
+            token_mint: token.mint,
+            amount: token.amount,
+            percent_of_position: classification.percent_of_position,
+            movement_kind: classification.kind,
+        })
     }
 
     /// Store a whale movement in PostgreSQL
@@ -362,12 +539,16 @@ impl Worker {
                 movement_type,
                 token_mint,
                 amount,
-                percent_of_position
-            ) VALUES ($1, $2, $3, $4, $5, $6)
+                percent_of_position,
+                movement_kind
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
             ON CONFLICT (transaction_signature) DO NOTHING
             RETURNING id
         "#;
 
+        let movement_kind = serde_json::to_value(&movement.movement_kind)
+            .map_err(|e| Error::Internal(format!("Failed to serialize movement kind: {}", e)))?;
+
         match client
             .query_opt(
                 query,
@@ -378,6 +559,7 @@ impl Worker {
                     &movement.token_mint,
                     &movement.amount,
                     &movement.percent_of_position,
+                    &movement_kind,
                 ],
             )
             .await
@@ -402,6 +584,7 @@ impl Worker {
                         token_mint: movement.token_mint.clone(),
                         amount: movement.amount.clone(),
                         percent_of_position: movement.percent_of_position.unwrap_or(0.0),
+                        movement_kind: Some(movement.movement_kind.clone()),
                         detected_at: chrono::Utc::now(),
                         affected_user_ids: affected_users,
                     };
@@ -525,7 +708,6 @@ struct TransactionDetails {
     signature: String,
     #[allow(dead_code)]
     whale_address: String,
-    #[allow(dead_code)]
     transaction: solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta,
 }
 
@@ -537,11 +719,13 @@ struct WhaleMovementData {
     token_mint: String,
     amount: String,
     percent_of_position: Option<f64>,
+    movement_kind: MovementKind,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::classifier::{TokenAmount, TransferDirection};
     use crate::redis_store::RedisStore;
     use blockchain::SolanaClient;
     use std::sync::Arc;
@@ -614,6 +798,15 @@ mod tests {
             token_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
             amount: "1000000".to_string(),
             percent_of_position: Some(10.5),
+            movement_kind: MovementKind::Transfer {
+                direction: TransferDirection::Outgoing,
+                token: TokenAmount {
+                    mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                    amount: "1000000".to_string(),
+                    decimals: 6,
+                },
+                counterparty: None,
+            },
         };
 
         assert_eq!(movement.movement_type, "SELL");
@@ -1,6 +1,8 @@
+use crate::classifier::DecoderRegistry;
 use crate::redis_store::RedisStore;
 use crate::worker::Worker;
 use crate::message_queue::MessageQueueClient;
+use crate::subscription::{websocket_url, MonitoringMode, SubscriptionConfig};
 use blockchain::SolanaClient;
 use database::DbPool;
 use shared::{Error, Result};
@@ -20,6 +22,11 @@ pub struct WorkerPoolConfig {
     pub worker_count: usize,
     pub whales_per_worker: usize,
     pub check_interval_seconds: u64,
+    /// Poll every whale, or subscribe to their transactions over WebSocket
+    pub mode: MonitoringMode,
+    /// PubSub endpoint for subscription mode; derived from
+    /// `solana_rpc_url` when unset
+    pub solana_ws_url: Option<String>,
 }
 
 /// Worker pool that manages multiple workers for parallel whale monitoring
@@ -44,8 +51,8 @@ impl WorkerPool {
     /// Create a new worker pool (async constructor)
     pub async fn new(config: WorkerPoolConfig) -> Result<Self> {
         info!(
-            "Creating worker pool with {} workers, {} whales per worker",
-            config.worker_count, config.whales_per_worker
+            "Creating worker pool with {} workers, {} whales per worker ({} mode)",
+            config.worker_count, config.whales_per_worker, config.mode.as_str()
         );
 
         // Create Solana client
@@ -60,12 +67,19 @@ impl WorkerPool {
         // Create workers
         let mut workers = Vec::new();
         for i in 0..config.worker_count {
-            let worker = Worker::new(
+            let mut worker = Worker::new(
                 i,
                 solana_client.clone(),
                 redis_store.clone(),
                 config.check_interval_seconds,
             );
+            if config.mode == MonitoringMode::Subscription {
+                let ws_url = config
+                    .solana_ws_url
+                    .clone()
+                    .unwrap_or_else(|| websocket_url(&config.solana_rpc_url));
+                worker.set_subscription(SubscriptionConfig::new(ws_url));
+            }
             workers.push(Arc::new(RwLock::new(worker)));
         }
 
@@ -113,6 +127,16 @@ impl WorkerPool {
         info!("Message queue configured for all workers");
     }
 
+    /// Set the instruction decoders all workers classify transactions with
+    pub async fn set_decoders(&mut self, decoders: Arc<DecoderRegistry>) {
+        for worker in &self.workers {
+            let mut worker_guard = worker.write().await;
+            worker_guard.set_decoders(decoders.clone());
+        }
+
+        info!("Instruction decoders configured for all workers");
+    }
+
     /// Assign whales to a user for monitoring
     pub async fn assign_whales(&mut self, user_id: Uuid, whale_addresses: Vec<String>) -> Result<()> {
         info!(
@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use serde_json::json;
+use shared::config::NotificationConfig;
+use shared::models::Notification;
+
+use crate::channel::{ChannelKind, NotificationChannel};
+use crate::webhook::{check_public_url, public_http_client};
+use crate::{NotificationError, Result};
+
+const TELEGRAM_API_BASE: &str = "https://api.telegram.org";
+const TELEGRAM_MAX_MESSAGE_CHARS: usize = 4096;
+const DISCORD_MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Chat service a `BotChannel` posts through
+#[derive(Debug, Clone)]
+pub enum BotProvider {
+    /// Telegram Bot API; the delivery target is the user's chat ID
+    Telegram { api_base: String, token: String },
+    /// Discord incoming webhooks; the delivery target is the webhook URL
+    Discord,
+}
+
+/// Delivers notifications as chat messages from a bot
+pub struct BotChannel {
+    client: reqwest::Client,
+    provider: BotProvider,
+}
+
+impl BotChannel {
+    pub fn telegram(token: impl Into<String>) -> Self {
+        Self::new(BotProvider::Telegram {
+            api_base: TELEGRAM_API_BASE.to_string(),
+            token: token.into(),
+        })
+    }
+
+    pub fn discord() -> Self {
+        Self::new(BotProvider::Discord)
+    }
+
+    pub fn new(provider: BotProvider) -> Self {
+        Self {
+            client: public_http_client(),
+            provider,
+        }
+    }
+
+    /// Build the channel configured by `config`, if any
+    pub fn from_config(config: &NotificationConfig) -> Result<Option<Self>> {
+        match config.bot_provider.as_deref() {
+            None | Some("") => Ok(None),
+            Some("telegram") => {
+                let token = config.bot_token.clone().ok_or_else(|| {
+                    NotificationError::ValidationError(
+                        "NOTIFICATION_BOT_TOKEN is required for the Telegram bot".to_string(),
+                    )
+                })?;
+                Ok(Some(Self::telegram(token)))
+            }
+            Some("discord") => Ok(Some(Self::discord())),
+            Some(other) => Err(NotificationError::ValidationError(format!(
+                "Unknown notification bot provider: {}",
+                other
+            ))),
+        }
+    }
+
+    pub fn provider(&self) -> &BotProvider {
+        &self.provider
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for BotChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Bot
+    }
+
+    async fn deliver(&self, notification: &Notification, target: &str) -> Result<()> {
+        let request = match &self.provider {
+            BotProvider::Telegram { api_base, token } => self
+                .client
+                .post(format!("{}/bot{}/sendMessage", api_base, token))
+                .json(&json!({
+                    "chat_id": target,
+                    "text": format_message(notification, TELEGRAM_MAX_MESSAGE_CHARS),
+                    "disable_web_page_preview": true,
+                })),
+            BotProvider::Discord => {
+                check_public_url(target).map_err(NotificationError::DeliveryError)?;
+                self.client.post(target).json(&json!({
+                    "content": format_message(notification, DISCORD_MAX_MESSAGE_CHARS),
+                }))
+            }
+        };
+
+        // Errors from reqwest can include the URL, which embeds the Telegram token
+        let response = request.send().await.map_err(|e| {
+            NotificationError::DeliveryError(format!("Bot request failed: {}", e.without_url()))
+        })?;
+
+        if !response.status().is_success() {
+            return Err(NotificationError::DeliveryError(format!(
+                "Bot API returned {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Chat message text for `notification`, cut to `max_chars`
+fn format_message(notification: &Notification, max_chars: usize) -> String {
+    let text = format!(
+        "[{}] {}\n{}",
+        notification.priority, notification.title, notification.message
+    );
+
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn notification(message: &str) -> Notification {
+        Notification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            notification_type: "WHALE_MOVEMENT".to_string(),
+            title: "Whale Movement Detected: SOL".to_string(),
+            message: message.to_string(),
+            data: None,
+            priority: "HIGH".to_string(),
+            read: false,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_format_message() {
+        let text = format_message(&notification("Recommendation: BUY"), DISCORD_MAX_MESSAGE_CHARS);
+        assert_eq!(text, "[HIGH] Whale Movement Detected: SOL\nRecommendation: BUY");
+    }
+
+    #[test]
+    fn test_format_message_truncates() {
+        let text = format_message(&notification(&"é".repeat(3000)), DISCORD_MAX_MESSAGE_CHARS);
+        assert_eq!(text.chars().count(), DISCORD_MAX_MESSAGE_CHARS);
+        assert!(text.ends_with('…'));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = NotificationConfig {
+            smtp: None,
+            bot_provider: None,
+            bot_token: None,
+            max_delivery_attempts: 5,
+            retry_backoff_secs: 30,
+            retry_interval_secs: 15,
+        };
+        assert!(BotChannel::from_config(&config).unwrap().is_none());
+
+        config.bot_provider = Some("discord".to_string());
+        assert!(matches!(
+            BotChannel::from_config(&config).unwrap().unwrap().provider(),
+            BotProvider::Discord
+        ));
+
+        config.bot_provider = Some("telegram".to_string());
+        assert!(BotChannel::from_config(&config).is_err());
+        config.bot_token = Some("123:abc".to_string());
+        assert!(matches!(
+            BotChannel::from_config(&config).unwrap().unwrap().provider(),
+            BotProvider::Telegram { token, .. } if token == "123:abc"
+        ));
+
+        config.bot_provider = Some("slack".to_string());
+        assert!(BotChannel::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use shared::models::{Notification, NotificationPreferences};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{NotificationError, Result};
+
+/// Delivery channels a notification can be routed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChannelKind {
+    /// Email to the user's account address
+    Email,
+    /// Signed HTTP POST to a URL the user registered
+    Webhook,
+    /// In-app push to the user's open dashboard sessions
+    Push,
+    /// Message from a chat bot (Telegram, Discord)
+    Bot,
+}
+
+impl ChannelKind {
+    pub const ALL: [ChannelKind; 4] = [
+        ChannelKind::Email,
+        ChannelKind::Webhook,
+        ChannelKind::Push,
+        ChannelKind::Bot,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelKind::Email => "EMAIL",
+            ChannelKind::Webhook => "WEBHOOK",
+            ChannelKind::Push => "PUSH",
+            ChannelKind::Bot => "BOT",
+        }
+    }
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChannelKind {
+    type Err = NotificationError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ChannelKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| NotificationError::ValidationError(format!("Unknown notification channel: {}", s)))
+    }
+}
+
+/// A way of getting a notification in front of a user
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Which preference switch routes notifications to this channel
+    fn kind(&self) -> ChannelKind;
+
+    /// Deliver `notification` to `target`, the recipient's address on this
+    /// channel: an email address, webhook URL, bot destination, or the user
+    /// ID for in-app push
+    ///
+    /// An error means the delivery may be retried later.
+    async fn deliver(&self, notification: &Notification, target: &str) -> Result<()>;
+}
+
+/// Channels and recipient addresses `prefs` routes a user's notifications to
+///
+/// Channels are skipped when switched off or when the user has not given an
+/// address for them.
+pub fn delivery_routes(
+    prefs: &NotificationPreferences,
+    user_email: Option<&str>,
+) -> Vec<(ChannelKind, String)> {
+    let mut routes = Vec::new();
+
+    if prefs.email_enabled {
+        if let Some(email) = user_email.filter(|email| !email.is_empty()) {
+            routes.push((ChannelKind::Email, email.to_string()));
+        }
+    }
+    if prefs.webhook_enabled {
+        if let Some(url) = prefs.webhook_url.as_ref().filter(|url| !url.is_empty()) {
+            routes.push((ChannelKind::Webhook, url.clone()));
+        }
+    }
+    if prefs.push_enabled {
+        routes.push((ChannelKind::Push, prefs.user_id.to_string()));
+    }
+    if prefs.bot_enabled {
+        if let Some(destination) = prefs.bot_destination.as_ref().filter(|d| !d.is_empty()) {
+            routes.push((ChannelKind::Bot, destination.clone()));
+        }
+    }
+
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn prefs() -> NotificationPreferences {
+        NotificationPreferences {
+            user_id: Uuid::new_v4(),
+            in_app_enabled: true,
+            email_enabled: false,
+            push_enabled: false,
+            frequency: "REALTIME".to_string(),
+            minimum_movement_percent: 5.0,
+            minimum_confidence: 70,
+            webhook_enabled: false,
+            webhook_url: None,
+            webhook_secret: None,
+            bot_enabled: false,
+            bot_destination: None,
+        }
+    }
+
+    #[test]
+    fn test_channel_kind_round_trip() {
+        for kind in ChannelKind::ALL {
+            assert_eq!(kind.as_str().parse::<ChannelKind>().unwrap(), kind);
+            assert_eq!(
+                serde_json::to_string(&kind).unwrap(),
+                format!("\"{}\"", kind.as_str())
+            );
+        }
+        assert!("SMS".parse::<ChannelKind>().is_err());
+    }
+
+    #[test]
+    fn test_routes_follow_preferences() {
+        let mut prefs = prefs();
+        assert!(delivery_routes(&prefs, Some("user@example.com")).is_empty());
+
+        prefs.email_enabled = true;
+        prefs.push_enabled = true;
+        prefs.webhook_enabled = true;
+        prefs.webhook_url = Some("https://hooks.example.com/alerts".to_string());
+        prefs.bot_enabled = true;
+        prefs.bot_destination = Some("123456".to_string());
+
+        let routes = delivery_routes(&prefs, Some("user@example.com"));
+        assert_eq!(
+            routes,
+            vec![
+                (ChannelKind::Email, "user@example.com".to_string()),
+                (ChannelKind::Webhook, "https://hooks.example.com/alerts".to_string()),
+                (ChannelKind::Push, prefs.user_id.to_string()),
+                (ChannelKind::Bot, "123456".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_routes_skip_channels_without_address() {
+        let mut prefs = prefs();
+        prefs.email_enabled = true;
+        prefs.webhook_enabled = true;
+        prefs.bot_enabled = true;
+        prefs.bot_destination = Some(String::new());
+
+        assert!(delivery_routes(&prefs, None).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use shared::models::Notification;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::NotificationError;
+
+/// Position of a notification in a user's feed
+///
+/// Notifications are ordered by `seq`, a sequence the database assigns on
+/// insert. `created_at` is set by the writer, so a row stored late with an
+/// earlier timestamp would sort behind a timestamp cursor and be skipped. The
+/// string form is the sequence number and is opaque to clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationCursor {
+    pub seq: i64,
+}
+
+impl fmt::Display for NotificationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.seq)
+    }
+}
+
+impl FromStr for NotificationCursor {
+    type Err = NotificationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let seq = s
+            .parse::<i64>()
+            .ok()
+            .filter(|seq| *seq >= 0)
+            .ok_or_else(|| NotificationError::ValidationError(format!("Invalid notification cursor: {}", s)))?;
+
+        Ok(Self { seq })
+    }
+}
+
+impl Serialize for NotificationCursor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationCursor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// One page of a user's notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPage {
+    pub notifications: Vec<Notification>,
+    /// Cursor to pass back for the next page; `None` when there is nothing
+    /// further to read
+    pub next_cursor: Option<NotificationCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = NotificationCursor { seq: 1_700_000 };
+
+        let parsed: NotificationCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, format!("\"{}\"", cursor));
+        assert_eq!(serde_json::from_str::<NotificationCursor>(&json).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_rejects_malformed_cursor() {
+        for s in ["", "abc", "-1", "1.5", "1700000000123456:00000000-0000-0000-0000-000000000000"] {
+            assert!(s.parse::<NotificationCursor>().is_err(), "accepted {:?}", s);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::config::NotificationConfig;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::channel::ChannelKind;
+use crate::{NotificationError, Result};
+
+/// Where a notification stands on one delivery channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryStatus {
+    /// Recorded, first attempt not finished yet
+    Pending,
+    Delivered,
+    /// Failed, another attempt is scheduled
+    Retrying,
+    /// Failed on every attempt; kept for inspection and manual replay
+    DeadLetter,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "PENDING",
+            DeliveryStatus::Delivered => "DELIVERED",
+            DeliveryStatus::Retrying => "RETRYING",
+            DeliveryStatus::DeadLetter => "DEAD_LETTER",
+        }
+    }
+}
+
+impl fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = NotificationError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "PENDING" => Ok(DeliveryStatus::Pending),
+            "DELIVERED" => Ok(DeliveryStatus::Delivered),
+            "RETRYING" => Ok(DeliveryStatus::Retrying),
+            "DEAD_LETTER" => Ok(DeliveryStatus::DeadLetter),
+            other => Err(NotificationError::ValidationError(format!(
+                "Unknown delivery status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Delivery record of one notification on one channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDelivery {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    pub user_id: Uuid,
+    pub channel: ChannelKind,
+    /// Recipient address on the channel
+    pub target: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    /// When the next retry is due, while `Retrying`
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How often, and how far apart, failed deliveries are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts, including the first, before dead-lettering
+    pub max_attempts: u32,
+    /// Delay after the first failure, doubled after each later one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &NotificationConfig) -> Self {
+        Self {
+            max_attempts: config.max_delivery_attempts.max(1),
+            initial_backoff: Duration::from_secs(config.retry_backoff_secs),
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the next attempt after `attempts` failed ones, or `None`
+    /// once the delivery should be dead-lettered
+    pub fn backoff_after(&self, attempts: u32) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+
+        let doublings = attempts.saturating_sub(1).min(16);
+        Some(
+            self.initial_backoff
+                .saturating_mul(1 << doublings)
+                .min(self.max_backoff),
+        )
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(3600),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_until_dead_letter() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(90),
+        };
+
+        assert_eq!(policy.backoff_after(1), Some(Duration::from_secs(30)));
+        assert_eq!(policy.backoff_after(2), Some(Duration::from_secs(60)));
+        assert_eq!(policy.backoff_after(3), Some(Duration::from_secs(90)));
+        assert_eq!(policy.backoff_after(4), None);
+    }
+
+    #[test]
+    fn test_delivery_status_round_trip() {
+        for status in [
+            DeliveryStatus::Pending,
+            DeliveryStatus::Delivered,
+            DeliveryStatus::Retrying,
+            DeliveryStatus::DeadLetter,
+        ] {
+            assert_eq!(status.as_str().parse::<DeliveryStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status.as_str())
+            );
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use crate::channel::{ChannelKind, NotificationChannel};
 use crate::Result;
 use async_trait::async_trait;
-use shared::models::{Recommendation, TradeExecution};
+use shared::models::{Notification, Recommendation, TradeExecution};
+use std::sync::Arc;
 
 /// Email notification data
 #[derive(Debug, Clone)]
@@ -32,6 +34,52 @@ impl EmailService for MockEmailService {
     }
 }
 
+/// Delivers notifications by email through an `EmailService`
+pub struct EmailChannel {
+    email_service: Arc<dyn EmailService>,
+}
+
+impl EmailChannel {
+    pub fn new(email_service: Arc<dyn EmailService>) -> Self {
+        Self { email_service }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Email
+    }
+
+    async fn deliver(&self, notification: &Notification, target: &str) -> Result<()> {
+        self.email_service
+            .send_email(build_notification_email(target, notification))
+            .await
+    }
+}
+
+/// Build email for any stored notification
+pub fn build_notification_email(user_email: &str, notification: &Notification) -> EmailNotification {
+    let body = format!(
+        r#"
+Hello,
+
+{}
+
+---
+This is an automated notification from Solana Whale Tracker.
+To manage your notification preferences, visit your dashboard.
+        "#,
+        notification.message
+    );
+
+    EmailNotification {
+        to: user_email.to_string(),
+        subject: notification.title.clone(),
+        body,
+    }
+}
+
 /// Build email for whale movement notification
 pub fn build_whale_movement_email(
     user_email: &str,
@@ -185,6 +233,27 @@ mod tests {
         assert!(email.body.contains("$500.00"));
     }
 
+    #[test]
+    fn test_build_notification_email() {
+        let notification = Notification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            notification_type: "BENCHMARK_ALERT".to_string(),
+            title: "Price Alert: SOL".to_string(),
+            message: "SOL has crossed above your target price of 100".to_string(),
+            data: None,
+            priority: "HIGH".to_string(),
+            read: false,
+            created_at: chrono::Utc::now(),
+        };
+
+        let email = build_notification_email("user@example.com", &notification);
+
+        assert_eq!(email.to, "user@example.com");
+        assert_eq!(email.subject, "Price Alert: SOL");
+        assert!(email.body.contains("crossed above your target price of 100"));
+    }
+
     #[tokio::test]
     async fn test_mock_email_service() {
         let service = MockEmailService;
@@ -4,6 +4,7 @@ use std::fmt;
 pub enum NotificationError {
     DatabaseError(String),
     ValidationError(String),
+    DeliveryError(String),
 }
 
 impl fmt::Display for NotificationError {
@@ -11,6 +12,7 @@ impl fmt::Display for NotificationError {
         match self {
             NotificationError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             NotificationError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            NotificationError::DeliveryError(msg) => write!(f, "Delivery error: {}", msg),
         }
     }
 }
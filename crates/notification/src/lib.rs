@@ -1,43 +1,113 @@
+use database::DbPool;
+use shared::config::NotificationConfig;
 use shared::models::{Notification, NotificationPreferences, Recommendation, TradeExecution};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::info;
+use std::time::Duration;
+use tokio_postgres::Row;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+pub mod bot;
+pub mod channel;
+pub mod cursor;
+pub mod delivery;
 pub mod error;
 pub mod email;
+pub mod smtp;
+pub mod webhook;
 
+pub use bot::{BotChannel, BotProvider};
+pub use channel::{delivery_routes, ChannelKind, NotificationChannel};
+pub use cursor::{NotificationCursor, NotificationPage};
+pub use delivery::{DeliveryStatus, NotificationDelivery, RetryPolicy};
 pub use error::{NotificationError, Result};
-pub use email::{EmailNotification, EmailService, MockEmailService};
+pub use email::{EmailChannel, EmailNotification, EmailService, MockEmailService};
+pub use smtp::SmtpEmailService;
+pub use webhook::WebhookChannel;
 
-/// In-memory notification storage (for MVP - would use database in production)
-type NotificationStore = Arc<RwLock<Vec<Notification>>>;
-type PreferencesStore = Arc<RwLock<std::collections::HashMap<Uuid, NotificationPreferences>>>;
+/// Page size used when a caller does not ask for one
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest page a single read returns
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+const NOTIFICATION_COLUMNS: &str =
+    "id, user_id, notification_type, title, message, data, priority, read, created_at";
+
+const PREFERENCE_COLUMNS: &str = "user_id, in_app_enabled, email_enabled, push_enabled, frequency, \
+     minimum_movement_percent::FLOAT8 AS minimum_movement_percent, minimum_confidence, \
+     webhook_enabled, webhook_url, webhook_secret, bot_enabled, bot_destination";
+
+const DELIVERY_COLUMNS: &str = "id, notification_id, user_id, channel, target, status, attempts, \
+     last_error, next_attempt_at, delivered_at, created_at, updated_at";
+
+/// How long a replica holds a claimed retry before others may take it over
+const DELIVERY_LEASE_SECS: f64 = 300.0;
 
 /// Notification service for managing user notifications
+///
+/// Notifications and preferences are stored in Postgres, so they survive
+/// restarts and every API replica sees the same data. Each notification is
+/// delivered to the channels its user has enabled; failed deliveries are
+/// retried with backoff and dead-lettered once the retry policy gives up.
 pub struct NotificationService {
-    notifications: NotificationStore,
-    preferences: PreferencesStore,
-    email_service: Option<Arc<dyn EmailService>>,
+    db_pool: DbPool,
+    channels: HashMap<ChannelKind, Arc<dyn NotificationChannel>>,
+    retry_policy: RetryPolicy,
 }
 
 impl NotificationService {
     /// Create a new notification service
-    pub fn new() -> Self {
+    pub fn new(db_pool: DbPool) -> Self {
         Self {
-            notifications: Arc::new(RwLock::new(Vec::new())),
-            preferences: Arc::new(RwLock::new(std::collections::HashMap::new())),
-            email_service: None,
+            db_pool,
+            channels: HashMap::new(),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
     /// Create a new notification service with email support
-    pub fn with_email_service(email_service: Arc<dyn EmailService>) -> Self {
-        Self {
-            notifications: Arc::new(RwLock::new(Vec::new())),
-            preferences: Arc::new(RwLock::new(std::collections::HashMap::new())),
-            email_service: Some(email_service),
+    pub fn with_email_service(db_pool: DbPool, email_service: Arc<dyn EmailService>) -> Self {
+        Self::new(db_pool).with_channel(Arc::new(EmailChannel::new(email_service)))
+    }
+
+    /// Create a notification service with the email, webhook and bot channels
+    /// and retry policy described by `config`
+    pub fn from_config(db_pool: DbPool, config: &NotificationConfig) -> Result<Self> {
+        let mut service = Self::new(db_pool.clone())
+            .with_retry_policy(RetryPolicy::from_config(config))
+            .with_channel(Arc::new(WebhookChannel::new(db_pool)));
+
+        if let Some(smtp) = &config.smtp {
+            let email_service = Arc::new(SmtpEmailService::new(smtp)?);
+            service = service.with_channel(Arc::new(EmailChannel::new(email_service)));
+        }
+        if let Some(bot) = BotChannel::from_config(config)? {
+            service = service.with_channel(Arc::new(bot));
         }
+
+        Ok(service)
+    }
+
+    /// Deliver notifications through `channel`, replacing any channel of the
+    /// same kind
+    pub fn with_channel(mut self, channel: Arc<dyn NotificationChannel>) -> Self {
+        self.channels.insert(channel.kind(), channel);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Channels notifications can currently be delivered through
+    pub fn channel_kinds(&self) -> Vec<ChannelKind> {
+        ChannelKind::ALL
+            .into_iter()
+            .filter(|kind| self.channels.contains_key(kind))
+            .collect()
     }
 
     /// Create a notification for a whale movement recommendation
@@ -78,23 +148,8 @@ impl NotificationService {
             created_at: chrono::Utc::now(),
         };
 
-        self.store_notification(&notification).await?;
-        
-        // Send email if enabled and email service is available
-        if let (Some(email), Some(email_service)) = (user_email, &self.email_service) {
-            let prefs = self.get_preferences(user_id).await?;
-            if prefs.email_enabled {
-                let email_notification = email::build_whale_movement_email(
-                    email,
-                    recommendation,
-                    whale_address,
-                    token_symbol,
-                );
-                if let Err(e) = email_service.send_email(email_notification).await {
-                    tracing::warn!("Failed to send email notification: {:?}", e);
-                }
-            }
-        }
+        let notification = self.store_notification(notification).await?;
+        self.dispatch(&notification, user_email).await;
 
         info!("Created whale movement notification for user {}", user_id);
         Ok(notification)
@@ -107,62 +162,78 @@ impl NotificationService {
         user_email: Option<&str>,
         trade: &TradeExecution,
     ) -> Result<Notification> {
-        let notification = Notification {
-            id: Uuid::new_v4(),
-            user_id,
-            notification_type: "TRADE_EXECUTED".to_string(),
-            title: format!("Trade Executed: {}", trade.action),
-            message: format!(
-                "Auto-trader executed a {} trade for {} tokens",
-                trade.action, trade.token_mint
-            ),
-            data: Some(serde_json::json!({
-                "trade_id": trade.id,
-                "action": trade.action,
-                "token_mint": trade.token_mint,
-                "amount": trade.amount,
-                "transaction_signature": trade.transaction_signature,
-            })),
-            priority: "HIGH".to_string(),
-            read: false,
-            created_at: chrono::Utc::now(),
-        };
-
-        self.store_notification(&notification).await?;
-        
-        // Send email if enabled and email service is available
-        if let (Some(email), Some(email_service)) = (user_email, &self.email_service) {
-            let prefs = self.get_preferences(user_id).await?;
-            if prefs.email_enabled {
-                let email_notification = email::build_trade_execution_email(email, trade);
-                if let Err(e) = email_service.send_email(email_notification).await {
-                    tracing::warn!("Failed to send email notification: {:?}", e);
-                }
-            }
-        }
+        let notification = self.store_notification(trade_notification(user_id, trade)).await?;
+        self.dispatch(&notification, user_email).await;
 
         info!("Created trade notification for user {}", user_id);
         Ok(notification)
     }
 
-    /// Store a notification in memory
-    async fn store_notification(&self, notification: &Notification) -> Result<()> {
-        let mut notifications = self.notifications.write().await;
-        notifications.push(notification.clone());
-        Ok(())
+    /// Store a notification built by another service and deliver it to the
+    /// user's channels, emailing the account address
+    pub async fn create_notification(&self, notification: Notification) -> Result<Notification> {
+        let notification = self.store_notification(notification).await?;
+        self.dispatch(&notification, None).await;
+
+        info!(
+            "Created {} notification for user {}",
+            notification.notification_type, notification.user_id
+        );
+        Ok(notification)
+    }
+
+    /// Insert a notification, returning it with the timestamp as stored
+    async fn store_notification(&self, notification: Notification) -> Result<Notification> {
+        let client = self.client().await?;
+
+        let row = client
+            .query_one(
+                "INSERT INTO notifications
+                    (id, user_id, notification_type, title, message, data, priority, read, created_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 RETURNING created_at",
+                &[
+                    &notification.id,
+                    &notification.user_id,
+                    &notification.notification_type,
+                    &notification.title,
+                    &notification.message,
+                    &notification.data,
+                    &notification.priority,
+                    &notification.read,
+                    &notification.created_at,
+                ],
+            )
+            .await
+            .map_err(|e| db_error("Failed to store notification", e))?;
+
+        // Postgres keeps microseconds; hand back the timestamp as stored
+        Ok(Notification {
+            created_at: row.get("created_at"),
+            ..notification
+        })
     }
 
     /// Get user's notification preferences
+    ///
+    /// Users without stored preferences get the defaults, which are persisted.
     pub async fn get_preferences(&self, user_id: Uuid) -> Result<NotificationPreferences> {
-        let preferences = self.preferences.read().await;
-        
-        match preferences.get(&user_id) {
-            Some(prefs) => Ok(prefs.clone()),
-            None => {
-                drop(preferences); // Release read lock
-                let default_prefs = self.create_default_preferences(user_id).await?;
-                Ok(default_prefs)
-            }
+        let client = self.client().await?;
+
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM notification_preferences WHERE user_id = $1",
+                    PREFERENCE_COLUMNS
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to load notification preferences", e))?;
+
+        match row {
+            Some(row) => Ok(preferences_from_row(&row)),
+            None => self.create_default_preferences(user_id).await,
         }
     }
 
@@ -171,55 +242,150 @@ impl NotificationService {
         &self,
         user_id: Uuid,
     ) -> Result<NotificationPreferences> {
-        let prefs = NotificationPreferences {
-            user_id,
-            in_app_enabled: true,
-            email_enabled: false,
-            push_enabled: false,
-            frequency: "REALTIME".to_string(),
-            minimum_movement_percent: 5.0,
-            minimum_confidence: 70,
-        };
-
-        let mut preferences = self.preferences.write().await;
-        preferences.insert(user_id, prefs.clone());
+        let client = self.client().await?;
+
+        // A concurrent request may have created them first; the no-op update
+        // makes RETURNING yield whichever row won
+        let row = client
+            .query_one(
+                &format!(
+                    "INSERT INTO notification_preferences (user_id)
+                     VALUES ($1)
+                     ON CONFLICT (user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+                     RETURNING {}",
+                    PREFERENCE_COLUMNS
+                ),
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to create notification preferences", e))?;
 
         info!("Created default notification preferences for user {}", user_id);
-        Ok(prefs)
+        Ok(preferences_from_row(&row))
     }
 
     /// Update user's notification preferences
+    ///
+    /// Registering a new webhook URL generates a new signing secret for it;
+    /// the secret is kept while the URL stays the same.
     pub async fn update_preferences(
         &self,
         prefs: &NotificationPreferences,
     ) -> Result<()> {
-        let mut preferences = self.preferences.write().await;
-        preferences.insert(prefs.user_id, prefs.clone());
+        validate_preferences(prefs)?;
+        let client = self.client().await?;
+        let new_secret = prefs.webhook_url.as_ref().map(|_| webhook::generate_secret());
+
+        // Right-hand sides of SET see the stored row, so the secret survives
+        // only when the stored URL is unchanged
+        client
+            .execute(
+                "INSERT INTO notification_preferences
+                    (user_id, in_app_enabled, email_enabled, push_enabled, frequency,
+                     minimum_movement_percent, minimum_confidence,
+                     webhook_enabled, webhook_url, webhook_secret, bot_enabled, bot_destination)
+                 VALUES ($1, $2, $3, $4, $5, $6::FLOAT8, $7, $8, $9, $10, $11, $12)
+                 ON CONFLICT (user_id) DO UPDATE SET
+                    in_app_enabled = EXCLUDED.in_app_enabled,
+                    email_enabled = EXCLUDED.email_enabled,
+                    push_enabled = EXCLUDED.push_enabled,
+                    frequency = EXCLUDED.frequency,
+                    minimum_movement_percent = EXCLUDED.minimum_movement_percent,
+                    minimum_confidence = EXCLUDED.minimum_confidence,
+                    webhook_enabled = EXCLUDED.webhook_enabled,
+                    webhook_url = EXCLUDED.webhook_url,
+                    webhook_secret = CASE
+                        WHEN notification_preferences.webhook_url = EXCLUDED.webhook_url
+                             AND notification_preferences.webhook_secret IS NOT NULL
+                        THEN notification_preferences.webhook_secret
+                        ELSE EXCLUDED.webhook_secret
+                    END,
+                    bot_enabled = EXCLUDED.bot_enabled,
+                    bot_destination = EXCLUDED.bot_destination",
+                &[
+                    &prefs.user_id,
+                    &prefs.in_app_enabled,
+                    &prefs.email_enabled,
+                    &prefs.push_enabled,
+                    &prefs.frequency,
+                    &prefs.minimum_movement_percent,
+                    &prefs.minimum_confidence,
+                    &prefs.webhook_enabled,
+                    &prefs.webhook_url,
+                    &new_secret,
+                    &prefs.bot_enabled,
+                    &prefs.bot_destination,
+                ],
+            )
+            .await
+            .map_err(|e| db_error("Failed to update notification preferences", e))?;
 
         info!("Updated notification preferences for user {}", prefs.user_id);
         Ok(())
     }
 
-    /// Get unread notifications for a user
-    pub async fn get_unread_notifications(&self, user_id: Uuid) -> Result<Vec<Notification>> {
-        let notifications = self.notifications.read().await;
-        
-        let unread: Vec<Notification> = notifications
-            .iter()
-            .filter(|n| n.user_id == user_id && !n.read)
-            .cloned()
-            .collect();
+    /// Get unread notifications for a user, oldest first
+    ///
+    /// Pass the returned `next_cursor` back in to poll for notifications that
+    /// arrived since the last call. When nothing new is unread the cursor is
+    /// handed back unchanged.
+    pub async fn get_unread_notifications(
+        &self,
+        user_id: Uuid,
+        after: Option<NotificationCursor>,
+        limit: i64,
+    ) -> Result<NotificationPage> {
+        let client = self.client().await?;
+        let limit = clamp_page_size(limit);
+
+        let rows = match after {
+            Some(cursor) => client
+                .query(
+                    &format!(
+                        "SELECT {}, seq FROM notifications
+                         WHERE user_id = $1 AND read = FALSE AND seq > $2
+                         ORDER BY seq
+                         LIMIT $3",
+                        NOTIFICATION_COLUMNS
+                    ),
+                    &[&user_id, &cursor.seq, &limit],
+                )
+                .await,
+            None => client
+                .query(
+                    &format!(
+                        "SELECT {}, seq FROM notifications
+                         WHERE user_id = $1 AND read = FALSE
+                         ORDER BY seq
+                         LIMIT $2",
+                        NOTIFICATION_COLUMNS
+                    ),
+                    &[&user_id, &limit],
+                )
+                .await,
+        }
+        .map_err(|e| db_error("Failed to load unread notifications", e))?;
+
+        let notifications = rows.iter().map(notification_from_row).collect();
+        let next_cursor = rows.last().map(cursor_from_row).or(after);
 
-        Ok(unread)
+        Ok(NotificationPage {
+            notifications,
+            next_cursor,
+        })
     }
 
     /// Mark a notification as read
     pub async fn mark_as_read(&self, notification_id: Uuid) -> Result<()> {
-        let mut notifications = self.notifications.write().await;
-        
-        if let Some(notification) = notifications.iter_mut().find(|n| n.id == notification_id) {
-            notification.read = true;
-        }
+        let client = self.client().await?;
+
+        client
+            .execute(
+                "UPDATE notifications SET read = TRUE WHERE id = $1 AND read = FALSE",
+                &[&notification_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to mark notification as read", e))?;
 
         Ok(())
     }
@@ -251,50 +417,528 @@ impl NotificationService {
         Ok(true)
     }
 
-    /// Get all notifications for a user (read and unread)
-    pub async fn get_all_notifications(&self, user_id: Uuid) -> Result<Vec<Notification>> {
-        let notifications = self.notifications.read().await;
-        
-        let user_notifications: Vec<Notification> = notifications
-            .iter()
-            .filter(|n| n.user_id == user_id)
-            .cloned()
-            .collect();
+    /// Get all notifications for a user (read and unread), newest first
+    ///
+    /// Pass the returned `next_cursor` back in to page through older
+    /// notifications; it is `None` once the last page has been read.
+    pub async fn get_all_notifications(
+        &self,
+        user_id: Uuid,
+        before: Option<NotificationCursor>,
+        limit: i64,
+    ) -> Result<NotificationPage> {
+        let client = self.client().await?;
+        let limit = clamp_page_size(limit);
+
+        let rows = match before {
+            Some(cursor) => client
+                .query(
+                    &format!(
+                        "SELECT {}, seq FROM notifications
+                         WHERE user_id = $1 AND seq < $2
+                         ORDER BY seq DESC
+                         LIMIT $3",
+                        NOTIFICATION_COLUMNS
+                    ),
+                    &[&user_id, &cursor.seq, &limit],
+                )
+                .await,
+            None => client
+                .query(
+                    &format!(
+                        "SELECT {}, seq FROM notifications
+                         WHERE user_id = $1
+                         ORDER BY seq DESC
+                         LIMIT $2",
+                        NOTIFICATION_COLUMNS
+                    ),
+                    &[&user_id, &limit],
+                )
+                .await,
+        }
+        .map_err(|e| db_error("Failed to load notifications", e))?;
 
-        Ok(user_notifications)
+        let notifications = rows.iter().map(notification_from_row).collect();
+        let next_cursor = if rows.len() as i64 == limit {
+            rows.last().map(cursor_from_row)
+        } else {
+            None
+        };
+
+        Ok(NotificationPage {
+            notifications,
+            next_cursor,
+        })
     }
 
-    /// Get notification count for a user
+    /// Get notification count for a user as `(total, unread)`
     pub async fn get_notification_count(&self, user_id: Uuid) -> Result<(usize, usize)> {
-        let notifications = self.notifications.read().await;
-        
-        let total = notifications.iter().filter(|n| n.user_id == user_id).count();
-        let unread = notifications
-            .iter()
-            .filter(|n| n.user_id == user_id && !n.read)
-            .count();
+        let client = self.client().await?;
+
+        let row = client
+            .query_one(
+                "SELECT COUNT(*) AS total, COUNT(*) FILTER (WHERE read = FALSE) AS unread
+                 FROM notifications
+                 WHERE user_id = $1",
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to count notifications", e))?;
+
+        let total: i64 = row.get("total");
+        let unread: i64 = row.get("unread");
+        Ok((total as usize, unread as usize))
+    }
+
+    /// Get the number of unread notifications for a user
+    pub async fn get_unread_count(&self, user_id: Uuid) -> Result<usize> {
+        let client = self.client().await?;
+
+        let row = client
+            .query_one(
+                "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read = FALSE",
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to count unread notifications", e))?;
+
+        let unread: i64 = row.get(0);
+        Ok(unread as usize)
+    }
+
+    /// Mark all notifications as read for a user, returning how many changed
+    pub async fn mark_all_as_read(&self, user_id: Uuid) -> Result<u64> {
+        let client = self.client().await?;
+
+        let updated = client
+            .execute(
+                "UPDATE notifications SET read = TRUE WHERE user_id = $1 AND read = FALSE",
+                &[&user_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to mark notifications as read", e))?;
+
+        info!("Marked {} notifications as read for user {}", updated, user_id);
+        Ok(updated)
+    }
+
+    /// Deliver `notification` to every channel its user's preferences route
+    /// it to, recording the outcome of each delivery
+    ///
+    /// Failed deliveries are scheduled for retry rather than returned as
+    /// errors; see `retry_due_deliveries`. Without `user_email` the user's
+    /// account email is used.
+    pub async fn deliver_notification(
+        &self,
+        notification: &Notification,
+        user_email: Option<&str>,
+    ) -> Result<Vec<NotificationDelivery>> {
+        let prefs = self.get_preferences(notification.user_id).await?;
+        let account_email = match user_email {
+            None if prefs.email_enabled => self.account_email(notification.user_id).await?,
+            _ => None,
+        };
+        let mut deliveries = Vec::new();
+
+        for (kind, target) in delivery_routes(&prefs, user_email.or(account_email.as_deref())) {
+            let Some(channel) = self.channels.get(&kind) else {
+                debug!("No {} channel configured, skipping delivery", kind);
+                continue;
+            };
+            deliveries.push(self.deliver_to(channel.as_ref(), notification, &target).await?);
+        }
+
+        Ok(deliveries)
+    }
 
-        Ok((total, unread))
+    /// Email address of the user's account, if the user exists
+    async fn account_email(&self, user_id: Uuid) -> Result<Option<String>> {
+        let client = self.client().await?;
+
+        let row = client
+            .query_opt("SELECT email FROM users WHERE id = $1", &[&user_id])
+            .await
+            .map_err(|e| db_error("Failed to load user email", e))?;
+
+        Ok(row.map(|row| row.get("email")))
     }
 
-    /// Mark all notifications as read for a user
-    pub async fn mark_all_as_read(&self, user_id: Uuid) -> Result<()> {
-        let mut notifications = self.notifications.write().await;
-        
-        for notification in notifications.iter_mut() {
-            if notification.user_id == user_id && !notification.read {
-                notification.read = true;
+    /// `deliver_notification` for callers that must not fail on delivery problems
+    async fn dispatch(&self, notification: &Notification, user_email: Option<&str>) {
+        if let Err(e) = self.deliver_notification(notification, user_email).await {
+            warn!("Failed to deliver notification {}: {}", notification.id, e);
+        }
+    }
+
+    async fn deliver_to(
+        &self,
+        channel: &dyn NotificationChannel,
+        notification: &Notification,
+        target: &str,
+    ) -> Result<NotificationDelivery> {
+        // Record the delivery before attempting it, so a crash mid-send still
+        // leaves a trace
+        let delivery_id = Uuid::new_v4();
+        self.client()
+            .await?
+            .execute(
+                "INSERT INTO notification_deliveries
+                    (id, notification_id, user_id, channel, target, status)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &delivery_id,
+                    &notification.id,
+                    &notification.user_id,
+                    &channel.kind().as_str(),
+                    &target,
+                    &DeliveryStatus::Pending.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| db_error("Failed to record notification delivery", e))?;
+
+        let outcome = channel.deliver(notification, target).await;
+        self.record_attempt(delivery_id, 0, outcome).await
+    }
+
+    /// Store the outcome of a delivery attempt, scheduling a retry or
+    /// dead-lettering the delivery if it failed
+    async fn record_attempt(
+        &self,
+        delivery_id: Uuid,
+        previous_attempts: i32,
+        outcome: Result<()>,
+    ) -> Result<NotificationDelivery> {
+        let client = self.client().await?;
+        let attempts = previous_attempts + 1;
+
+        let row = match outcome {
+            Ok(()) => {
+                client
+                    .query_one(
+                        &format!(
+                            "UPDATE notification_deliveries
+                             SET status = $2, attempts = $3, last_error = NULL,
+                                 next_attempt_at = NULL, delivered_at = NOW(), updated_at = NOW()
+                             WHERE id = $1
+                             RETURNING {}",
+                            DELIVERY_COLUMNS
+                        ),
+                        &[&delivery_id, &DeliveryStatus::Delivered.as_str(), &attempts],
+                    )
+                    .await
+            }
+            Err(e) => {
+                let (status, retry_in) = match self.retry_policy.backoff_after(attempts as u32) {
+                    Some(backoff) => (DeliveryStatus::Retrying, Some(backoff.as_secs_f64())),
+                    None => (DeliveryStatus::DeadLetter, None),
+                };
+                if status == DeliveryStatus::DeadLetter {
+                    error!(
+                        "Dead-lettered notification delivery {} after {} attempts: {}",
+                        delivery_id, attempts, e
+                    );
+                } else {
+                    warn!(
+                        "Notification delivery {} failed (attempt {}): {}",
+                        delivery_id, attempts, e
+                    );
+                }
+
+                client
+                    .query_one(
+                        &format!(
+                            "UPDATE notification_deliveries
+                             SET status = $2, attempts = $3, last_error = $4,
+                                 next_attempt_at = NOW() + $5::FLOAT8 * INTERVAL '1 second',
+                                 updated_at = NOW()
+                             WHERE id = $1
+                             RETURNING {}",
+                            DELIVERY_COLUMNS
+                        ),
+                        &[&delivery_id, &status.as_str(), &attempts, &e.to_string(), &retry_in],
+                    )
+                    .await
             }
         }
+        .map_err(|e| db_error("Failed to record delivery attempt", e))?;
 
-        info!("Marked all notifications as read for user {}", user_id);
-        Ok(())
+        delivery_from_row(&row)
+    }
+
+    /// Retry up to `limit` deliveries whose backoff has elapsed, returning
+    /// how many were attempted
+    ///
+    /// Deliveries are claimed with a lease, so several replicas can run this
+    /// concurrently without sending anything twice.
+    pub async fn retry_due_deliveries(&self, limit: i64) -> Result<usize> {
+        let client = self.client().await?;
+
+        let claimed = client
+            .query(
+                "UPDATE notification_deliveries
+                 SET next_attempt_at = NOW() + $2::FLOAT8 * INTERVAL '1 second', updated_at = NOW()
+                 WHERE id IN (
+                    SELECT id FROM notification_deliveries
+                    WHERE status = 'RETRYING' AND next_attempt_at <= NOW()
+                    ORDER BY next_attempt_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING id, notification_id, channel, target, attempts",
+                &[&limit, &DELIVERY_LEASE_SECS],
+            )
+            .await
+            .map_err(|e| db_error("Failed to claim notification retries", e))?;
+
+        if claimed.is_empty() {
+            return Ok(0);
+        }
+
+        let notification_ids: Vec<Uuid> = claimed.iter().map(|row| row.get("notification_id")).collect();
+        let notifications: HashMap<Uuid, Notification> = client
+            .query(
+                &format!(
+                    "SELECT {} FROM notifications WHERE id = ANY($1)",
+                    NOTIFICATION_COLUMNS
+                ),
+                &[&notification_ids],
+            )
+            .await
+            .map_err(|e| db_error("Failed to load notifications for retry", e))?
+            .iter()
+            .map(|row| {
+                let notification = notification_from_row(row);
+                (notification.id, notification)
+            })
+            .collect();
+        drop(client);
+
+        for row in &claimed {
+            let delivery_id: Uuid = row.get("id");
+            let notification_id: Uuid = row.get("notification_id");
+            let channel: String = row.get("channel");
+            let target: String = row.get("target");
+            let attempts: i32 = row.get("attempts");
+
+            let outcome = match (
+                notifications.get(&notification_id),
+                channel.parse::<ChannelKind>().ok().and_then(|kind| self.channels.get(&kind)),
+            ) {
+                (Some(notification), Some(channel)) => channel.deliver(notification, &target).await,
+                (None, _) => Err(NotificationError::DeliveryError(
+                    "Notification no longer exists".to_string(),
+                )),
+                (_, None) => Err(NotificationError::DeliveryError(format!(
+                    "No {} channel configured",
+                    channel
+                ))),
+            };
+
+            self.record_attempt(delivery_id, attempts, outcome).await?;
+        }
+
+        Ok(claimed.len())
+    }
+
+    /// Spawn a background task retrying due deliveries every `interval`
+    pub fn start_retry_worker(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        info!("Starting notification retry worker with interval: {:?}", interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                match self.retry_due_deliveries(DEFAULT_PAGE_SIZE).await {
+                    Ok(0) => {}
+                    Ok(retried) => debug!("Retried {} notification deliveries", retried),
+                    Err(e) => error!("Error retrying notification deliveries: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Delivery status of a notification on each channel it was routed to
+    pub async fn get_delivery_status(&self, notification_id: Uuid) -> Result<Vec<NotificationDelivery>> {
+        let client = self.client().await?;
+
+        client
+            .query(
+                &format!(
+                    "SELECT {} FROM notification_deliveries
+                     WHERE notification_id = $1
+                     ORDER BY created_at, id",
+                    DELIVERY_COLUMNS
+                ),
+                &[&notification_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to load delivery status", e))?
+            .iter()
+            .map(delivery_from_row)
+            .collect()
+    }
+
+    /// Most recently dead-lettered deliveries
+    pub async fn get_dead_letters(&self, limit: i64) -> Result<Vec<NotificationDelivery>> {
+        let client = self.client().await?;
+
+        client
+            .query(
+                &format!(
+                    "SELECT {} FROM notification_deliveries
+                     WHERE status = 'DEAD_LETTER'
+                     ORDER BY updated_at DESC
+                     LIMIT $1",
+                    DELIVERY_COLUMNS
+                ),
+                &[&clamp_page_size(limit)],
+            )
+            .await
+            .map_err(|e| db_error("Failed to load dead-lettered deliveries", e))?
+            .iter()
+            .map(delivery_from_row)
+            .collect()
+    }
+
+    /// Give a dead-lettered delivery a fresh set of retries, starting now
+    ///
+    /// Returns false if the delivery is not dead-lettered.
+    pub async fn requeue_dead_letter(&self, delivery_id: Uuid) -> Result<bool> {
+        let client = self.client().await?;
+
+        let updated = client
+            .execute(
+                "UPDATE notification_deliveries
+                 SET status = 'RETRYING', attempts = 0, next_attempt_at = NOW(), updated_at = NOW()
+                 WHERE id = $1 AND status = 'DEAD_LETTER'",
+                &[&delivery_id],
+            )
+            .await
+            .map_err(|e| db_error("Failed to requeue delivery", e))?;
+
+        Ok(updated == 1)
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Object> {
+        self.db_pool
+            .get()
+            .await
+            .map_err(|e| db_error("Failed to get database connection", e))
+    }
+}
+
+fn db_error(context: &str, e: impl std::fmt::Display) -> NotificationError {
+    NotificationError::DatabaseError(format!("{}: {}", context, e))
+}
+
+fn clamp_page_size(limit: i64) -> i64 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Cursor positioned at a row selected with its `seq`
+fn cursor_from_row(row: &Row) -> NotificationCursor {
+    NotificationCursor { seq: row.get("seq") }
+}
+
+fn notification_from_row(row: &Row) -> Notification {
+    Notification {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        notification_type: row.get("notification_type"),
+        title: row.get("title"),
+        message: row.get("message"),
+        data: row.get("data"),
+        priority: row.get("priority"),
+        read: row.get("read"),
+        created_at: row.get("created_at"),
     }
 }
 
-impl Default for NotificationService {
-    fn default() -> Self {
-        Self::new()
+fn preferences_from_row(row: &Row) -> NotificationPreferences {
+    NotificationPreferences {
+        user_id: row.get("user_id"),
+        in_app_enabled: row.get::<_, Option<bool>>("in_app_enabled").unwrap_or(true),
+        email_enabled: row.get::<_, Option<bool>>("email_enabled").unwrap_or(false),
+        push_enabled: row.get::<_, Option<bool>>("push_enabled").unwrap_or(false),
+        frequency: row
+            .get::<_, Option<String>>("frequency")
+            .unwrap_or_else(|| "REALTIME".to_string()),
+        minimum_movement_percent: row
+            .get::<_, Option<f64>>("minimum_movement_percent")
+            .unwrap_or(5.0),
+        minimum_confidence: row.get::<_, Option<i32>>("minimum_confidence").unwrap_or(70),
+        webhook_enabled: row.get("webhook_enabled"),
+        webhook_url: row.get("webhook_url"),
+        webhook_secret: row.get("webhook_secret"),
+        bot_enabled: row.get("bot_enabled"),
+        bot_destination: row.get("bot_destination"),
+    }
+}
+
+fn delivery_from_row(row: &Row) -> Result<NotificationDelivery> {
+    Ok(NotificationDelivery {
+        id: row.get("id"),
+        notification_id: row.get("notification_id"),
+        user_id: row.get("user_id"),
+        channel: row.get::<_, String>("channel").parse()?,
+        target: row.get("target"),
+        status: row.get::<_, String>("status").parse()?,
+        attempts: row.get("attempts"),
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        delivered_at: row.get("delivered_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// Reject delivery addresses the service cannot or should not send to
+fn validate_preferences(prefs: &NotificationPreferences) -> Result<()> {
+    if let Some(url) = &prefs.webhook_url {
+        validate_http_url(url, "webhook_url")?;
+    }
+    if prefs.webhook_enabled && prefs.webhook_url.is_none() {
+        return Err(NotificationError::ValidationError(
+            "webhook_url is required when webhooks are enabled".to_string(),
+        ));
+    }
+    if prefs.bot_enabled && prefs.bot_destination.as_deref().unwrap_or("").trim().is_empty() {
+        return Err(NotificationError::ValidationError(
+            "bot_destination is required when bot messages are enabled".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_http_url(url: &str, field: &str) -> Result<()> {
+    webhook::check_public_url(url)
+        .map_err(|reason| NotificationError::ValidationError(format!("{} {}", field, reason)))
+}
+
+/// Notification telling `user_id` that a trade was executed
+pub fn trade_notification(user_id: Uuid, trade: &TradeExecution) -> Notification {
+    Notification {
+        id: Uuid::new_v4(),
+        user_id,
+        notification_type: "TRADE_EXECUTED".to_string(),
+        title: format!("Trade Executed: {}", trade.action),
+        message: format!(
+            "Auto-trader executed a {} trade for {} tokens",
+            trade.action, trade.token_mint
+        ),
+        data: Some(serde_json::json!({
+            "trade_id": trade.id,
+            "action": trade.action,
+            "token_mint": trade.token_mint,
+            "amount": trade.amount,
+            "transaction_signature": trade.transaction_signature,
+        })),
+        priority: "HIGH".to_string(),
+        read: false,
+        created_at: chrono::Utc::now(),
     }
 }
 
@@ -302,6 +946,78 @@ impl Default for NotificationService {
 mod tests {
     use super::*;
 
+    /// Helper to create a test database pool
+    async fn create_test_db() -> DbPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test_db".to_string());
+
+        database::create_pool(&database_url, 2).await.unwrap()
+    }
+
+    /// Helper to create a user for notifications to belong to
+    async fn create_test_user(db_pool: &DbPool) -> Uuid {
+        let user_id = Uuid::new_v4();
+        let client = db_pool.get().await.unwrap();
+        client
+            .execute(
+                "INSERT INTO users (id, email, password_hash) VALUES ($1, $2, $3)",
+                &[&user_id, &format!("notify-{}@example.com", user_id), &"hash"],
+            )
+            .await
+            .unwrap();
+        user_id
+    }
+
+    /// Channel that records deliveries and fails on demand
+    struct StubChannel {
+        kind: ChannelKind,
+        fail: bool,
+        delivered: std::sync::Mutex<Vec<(Uuid, String)>>,
+    }
+
+    impl StubChannel {
+        fn new(kind: ChannelKind, fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                kind,
+                fail,
+                delivered: std::sync::Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NotificationChannel for StubChannel {
+        fn kind(&self) -> ChannelKind {
+            self.kind
+        }
+
+        async fn deliver(&self, notification: &Notification, target: &str) -> Result<()> {
+            if self.fail {
+                return Err(NotificationError::DeliveryError("stub failure".to_string()));
+            }
+            self.delivered
+                .lock()
+                .unwrap()
+                .push((notification.id, target.to_string()));
+            Ok(())
+        }
+    }
+
+    fn test_recommendation(user_id: Uuid, action: &str, confidence: i32) -> Recommendation {
+        Recommendation {
+            id: Uuid::new_v4(),
+            movement_id: Uuid::new_v4(),
+            user_id,
+            action: action.to_string(),
+            confidence,
+            reasoning: "Test".to_string(),
+            suggested_amount: None,
+            timeframe: None,
+            risks: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
     #[test]
     fn test_notification_priority_high_confidence() {
         let recommendation = Recommendation {
@@ -365,6 +1081,11 @@ mod tests {
             frequency: "REALTIME".to_string(),
             minimum_movement_percent: 5.0,
             minimum_confidence: 70,
+            webhook_enabled: false,
+            webhook_url: None,
+            webhook_secret: None,
+            bot_enabled: false,
+            bot_destination: None,
         };
 
         assert!(prefs.in_app_enabled);
@@ -376,22 +1097,12 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_create_and_retrieve_notification() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "BUY".to_string(),
-            confidence: 85,
-            reasoning: "Strong whale accumulation".to_string(),
-            suggested_amount: Some("100".to_string()),
-            timeframe: Some("short-term".to_string()),
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "BUY", 85);
 
         let notification = service
             .create_whale_movement_notification(user_id, None, &recommendation, "whale123", "SOL")
@@ -403,28 +1114,25 @@ mod tests {
         assert_eq!(notification.priority, "HIGH");
         assert!(!notification.read);
 
-        let unread = service.get_unread_notifications(user_id).await.unwrap();
-        assert_eq!(unread.len(), 1);
-        assert_eq!(unread[0].id, notification.id);
+        // A second service instance, as on another replica, sees the same data
+        let replica = NotificationService::new(db_pool);
+        let unread = replica
+            .get_unread_notifications(user_id, None, DEFAULT_PAGE_SIZE)
+            .await
+            .unwrap();
+        assert_eq!(unread.notifications.len(), 1);
+        assert_eq!(unread.notifications[0].id, notification.id);
+        assert_eq!(unread.notifications[0].created_at, notification.created_at);
+        assert_eq!(unread.notifications[0].data, notification.data);
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_mark_notification_as_read() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "SELL".to_string(),
-            confidence: 75,
-            reasoning: "Whale dumping".to_string(),
-            suggested_amount: None,
-            timeframe: None,
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "SELL", 75);
 
         let notification = service
             .create_whale_movement_notification(user_id, None, &recommendation, "whale456", "USDC")
@@ -433,14 +1141,100 @@ mod tests {
 
         service.mark_as_read(notification.id).await.unwrap();
 
-        let unread = service.get_unread_notifications(user_id).await.unwrap();
-        assert_eq!(unread.len(), 0);
+        let unread = service
+            .get_unread_notifications(user_id, None, DEFAULT_PAGE_SIZE)
+            .await
+            .unwrap();
+        assert!(unread.notifications.is_empty());
+        assert_eq!(service.get_unread_count(user_id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_unread_cursor_polling() {
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "BUY", 85);
+
+        let first = service
+            .create_whale_movement_notification(user_id, None, &recommendation, "whale1", "SOL")
+            .await
+            .unwrap();
+        let second = service
+            .create_whale_movement_notification(user_id, None, &recommendation, "whale2", "SOL")
+            .await
+            .unwrap();
+
+        let page = service.get_unread_notifications(user_id, None, 1).await.unwrap();
+        assert_eq!(page.notifications.len(), 1);
+        assert_eq!(page.notifications[0].id, first.id);
+
+        let page = service
+            .get_unread_notifications(user_id, page.next_cursor, 1)
+            .await
+            .unwrap();
+        assert_eq!(page.notifications.len(), 1);
+        assert_eq!(page.notifications[0].id, second.id);
+
+        // Nothing new yet: the cursor is handed back unchanged
+        let cursor = page.next_cursor;
+        let page = service.get_unread_notifications(user_id, cursor, 1).await.unwrap();
+        assert!(page.notifications.is_empty());
+        assert_eq!(page.next_cursor, cursor);
+
+        let third = service
+            .create_whale_movement_notification(user_id, None, &recommendation, "whale3", "SOL")
+            .await
+            .unwrap();
+        let page = service
+            .get_unread_notifications(user_id, cursor, DEFAULT_PAGE_SIZE)
+            .await
+            .unwrap();
+        assert_eq!(page.notifications.len(), 1);
+        assert_eq!(page.notifications[0].id, third.id);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_unread_cursor_sees_late_insert_with_earlier_timestamp() {
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "BUY", 85);
+
+        let first = service
+            .create_whale_movement_notification(user_id, None, &recommendation, "whale1", "SOL")
+            .await
+            .unwrap();
+        let page = service.get_unread_notifications(user_id, None, DEFAULT_PAGE_SIZE).await.unwrap();
+        assert_eq!(page.notifications.len(), 1);
+
+        // A writer with a lagging clock stores a row stamped before the one
+        // the cursor already passed
+        let late = service
+            .create_notification(Notification {
+                id: Uuid::new_v4(),
+                created_at: first.created_at - chrono::Duration::minutes(5),
+                ..first.clone()
+            })
+            .await
+            .unwrap();
+
+        let page = service
+            .get_unread_notifications(user_id, page.next_cursor, DEFAULT_PAGE_SIZE)
+            .await
+            .unwrap();
+        assert_eq!(page.notifications.len(), 1);
+        assert_eq!(page.notifications[0].id, late.id);
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_default_preferences_creation() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
 
         let prefs = service.get_preferences(user_id).await.unwrap();
 
@@ -449,31 +1243,73 @@ mod tests {
         assert!(!prefs.email_enabled);
         assert!(!prefs.push_enabled);
         assert_eq!(prefs.frequency, "REALTIME");
+        assert_eq!(prefs.minimum_movement_percent, 5.0);
+        assert_eq!(prefs.minimum_confidence, 70);
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_update_preferences() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
 
         let mut prefs = service.get_preferences(user_id).await.unwrap();
         prefs.email_enabled = true;
         prefs.minimum_confidence = 80;
+        prefs.minimum_movement_percent = 7.5;
 
         service.update_preferences(&prefs).await.unwrap();
 
         let updated_prefs = service.get_preferences(user_id).await.unwrap();
         assert!(updated_prefs.email_enabled);
         assert_eq!(updated_prefs.minimum_confidence, 80);
+        assert_eq!(updated_prefs.minimum_movement_percent, 7.5);
+        assert!(updated_prefs.webhook_secret.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_webhook_secret_follows_url() {
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+
+        let mut prefs = service.get_preferences(user_id).await.unwrap();
+        prefs.webhook_enabled = true;
+        prefs.webhook_url = Some("https://hooks.example.com/alerts".to_string());
+        service.update_preferences(&prefs).await.unwrap();
+        let first = service.get_preferences(user_id).await.unwrap().webhook_secret.unwrap();
+
+        // Unrelated changes keep the secret
+        prefs.minimum_confidence = 90;
+        service.update_preferences(&prefs).await.unwrap();
+        let kept = service.get_preferences(user_id).await.unwrap().webhook_secret.unwrap();
+        assert_eq!(kept, first);
+
+        // A new URL gets a new secret, and other users get their own
+        prefs.webhook_url = Some("https://hooks.example.com/other".to_string());
+        service.update_preferences(&prefs).await.unwrap();
+        let rotated = service.get_preferences(user_id).await.unwrap().webhook_secret.unwrap();
+        assert_ne!(rotated, first);
+
+        let other_user = create_test_user(&db_pool).await;
+        let mut other = service.get_preferences(other_user).await.unwrap();
+        other.webhook_enabled = true;
+        other.webhook_url = prefs.webhook_url.clone();
+        service.update_preferences(&other).await.unwrap();
+        let other_secret = service.get_preferences(other_user).await.unwrap().webhook_secret.unwrap();
+        assert_ne!(other_secret, rotated);
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_should_notify_filters() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
 
-        // Get default preferences (min movement 5%, min confidence 70)
-        let _prefs = service.get_preferences(user_id).await.unwrap();
+        // Default preferences (min movement 5%, min confidence 70)
 
         // Should notify - meets all criteria
         assert!(service.should_notify(user_id, 10.0, 75).await.unwrap());
@@ -486,54 +1322,45 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_all_notifications() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "BUY".to_string(),
-            confidence: 85,
-            reasoning: "Test".to_string(),
-            suggested_amount: None,
-            timeframe: None,
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+    #[ignore] // Requires database connection
+    async fn test_get_all_notifications_paginates() {
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "BUY", 85);
+
+        let mut created = Vec::new();
+        for whale in ["whale1", "whale2", "whale3"] {
+            created.push(
+                service
+                    .create_whale_movement_notification(user_id, None, &recommendation, whale, "SOL")
+                    .await
+                    .unwrap(),
+            );
+        }
 
-        // Create two notifications
-        service
-            .create_whale_movement_notification(user_id, None, &recommendation, "whale1", "SOL")
-            .await
-            .unwrap();
-        service
-            .create_whale_movement_notification(user_id, None, &recommendation, "whale2", "USDC")
+        let first = service.get_all_notifications(user_id, None, 2).await.unwrap();
+        assert_eq!(first.notifications.len(), 2);
+        assert_eq!(first.notifications[0].id, created[2].id);
+        assert_eq!(first.notifications[1].id, created[1].id);
+        assert!(first.next_cursor.is_some());
+
+        let second = service
+            .get_all_notifications(user_id, first.next_cursor, 2)
             .await
             .unwrap();
-
-        let all = service.get_all_notifications(user_id).await.unwrap();
-        assert_eq!(all.len(), 2);
+        assert_eq!(second.notifications.len(), 1);
+        assert_eq!(second.notifications[0].id, created[0].id);
+        assert!(second.next_cursor.is_none());
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_get_notification_count() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "SELL".to_string(),
-            confidence: 75,
-            reasoning: "Test".to_string(),
-            suggested_amount: None,
-            timeframe: None,
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "SELL", 75);
 
         // Create three notifications
         let n1 = service
@@ -555,25 +1382,16 @@ mod tests {
         let (total, unread) = service.get_notification_count(user_id).await.unwrap();
         assert_eq!(total, 3);
         assert_eq!(unread, 2);
+        assert_eq!(service.get_unread_count(user_id).await.unwrap(), 2);
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_mark_all_as_read() {
-        let service = NotificationService::new();
-        let user_id = Uuid::new_v4();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "HOLD".to_string(),
-            confidence: 70,
-            reasoning: "Test".to_string(),
-            suggested_amount: None,
-            timeframe: None,
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone());
+        let user_id = create_test_user(&db_pool).await;
+        let recommendation = test_recommendation(user_id, "HOLD", 70);
 
         // Create multiple notifications
         service
@@ -586,35 +1404,30 @@ mod tests {
             .unwrap();
 
         // Mark all as read
-        service.mark_all_as_read(user_id).await.unwrap();
+        assert_eq!(service.mark_all_as_read(user_id).await.unwrap(), 2);
+        assert_eq!(service.mark_all_as_read(user_id).await.unwrap(), 0);
 
-        let unread = service.get_unread_notifications(user_id).await.unwrap();
-        assert_eq!(unread.len(), 0);
+        let unread = service
+            .get_unread_notifications(user_id, None, DEFAULT_PAGE_SIZE)
+            .await
+            .unwrap();
+        assert!(unread.notifications.is_empty());
     }
 
     #[tokio::test]
+    #[ignore] // Requires database connection
     async fn test_email_notification_with_service() {
+        let db_pool = create_test_db().await;
         let email_service = Arc::new(MockEmailService) as Arc<dyn EmailService>;
-        let service = NotificationService::with_email_service(email_service);
-        let user_id = Uuid::new_v4();
-        
+        let service = NotificationService::with_email_service(db_pool.clone(), email_service);
+        let user_id = create_test_user(&db_pool).await;
+
         // Enable email notifications
         let mut prefs = service.get_preferences(user_id).await.unwrap();
         prefs.email_enabled = true;
         service.update_preferences(&prefs).await.unwrap();
-        
-        let recommendation = Recommendation {
-            id: Uuid::new_v4(),
-            movement_id: Uuid::new_v4(),
-            user_id,
-            action: "BUY".to_string(),
-            confidence: 85,
-            reasoning: "Test".to_string(),
-            suggested_amount: None,
-            timeframe: None,
-            risks: None,
-            created_at: chrono::Utc::now(),
-        };
+
+        let recommendation = test_recommendation(user_id, "BUY", 85);
 
         // Should send email notification
         let notification = service
@@ -630,4 +1443,177 @@ mod tests {
 
         assert_eq!(notification.user_id, user_id);
     }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_delivers_to_enabled_channels() {
+        let db_pool = create_test_db().await;
+        let push = StubChannel::new(ChannelKind::Push, false);
+        let webhook = StubChannel::new(ChannelKind::Webhook, false);
+        let service = NotificationService::new(db_pool.clone())
+            .with_channel(push.clone())
+            .with_channel(webhook.clone());
+        let user_id = create_test_user(&db_pool).await;
+
+        let mut prefs = service.get_preferences(user_id).await.unwrap();
+        prefs.push_enabled = true;
+        prefs.email_enabled = true; // no email channel configured
+        service.update_preferences(&prefs).await.unwrap();
+
+        let notification = service
+            .create_whale_movement_notification(
+                user_id,
+                Some("user@example.com"),
+                &test_recommendation(user_id, "BUY", 85),
+                "whale123",
+                "SOL",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *push.delivered.lock().unwrap(),
+            vec![(notification.id, user_id.to_string())]
+        );
+        assert!(webhook.delivered.lock().unwrap().is_empty());
+
+        let deliveries = service.get_delivery_status(notification.id).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].channel, ChannelKind::Push);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts, 1);
+        assert!(deliveries[0].delivered_at.is_some());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_created_notification_emails_account_address() {
+        let db_pool = create_test_db().await;
+        let email = StubChannel::new(ChannelKind::Email, false);
+        let service = NotificationService::new(db_pool.clone()).with_channel(email.clone());
+        let user_id = create_test_user(&db_pool).await;
+
+        let mut prefs = service.get_preferences(user_id).await.unwrap();
+        prefs.email_enabled = true;
+        service.update_preferences(&prefs).await.unwrap();
+
+        let notification = service
+            .create_notification(Notification {
+                id: Uuid::new_v4(),
+                user_id,
+                notification_type: "PRICE_ALERT".to_string(),
+                title: "SOL moved".to_string(),
+                message: "SOL crossed your alert price".to_string(),
+                data: None,
+                priority: "HIGH".to_string(),
+                read: false,
+                created_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *email.delivered.lock().unwrap(),
+            vec![(notification.id, format!("notify-{}@example.com", user_id))]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires database connection
+    async fn test_failed_delivery_retries_then_dead_letters() {
+        let db_pool = create_test_db().await;
+        let service = NotificationService::new(db_pool.clone())
+            .with_channel(StubChannel::new(ChannelKind::Webhook, true))
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                initial_backoff: Duration::ZERO,
+                max_backoff: Duration::ZERO,
+            });
+        let user_id = create_test_user(&db_pool).await;
+
+        let mut prefs = service.get_preferences(user_id).await.unwrap();
+        prefs.webhook_enabled = true;
+        prefs.webhook_url = Some("https://hooks.example.com/alerts".to_string());
+        service.update_preferences(&prefs).await.unwrap();
+
+        let notification = service
+            .create_whale_movement_notification(
+                user_id,
+                None,
+                &test_recommendation(user_id, "SELL", 75),
+                "whale456",
+                "USDC",
+            )
+            .await
+            .unwrap();
+
+        let deliveries = service.get_delivery_status(notification.id).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Retrying);
+        assert_eq!(deliveries[0].attempts, 1);
+        assert_eq!(deliveries[0].last_error.as_deref(), Some("Delivery error: stub failure"));
+
+        // Other tests may leave due retries behind; drain until ours is done
+        while service.retry_due_deliveries(MAX_PAGE_SIZE).await.unwrap() == MAX_PAGE_SIZE as usize {}
+
+        let deliveries = service.get_delivery_status(notification.id).await.unwrap();
+        assert_eq!(deliveries[0].status, DeliveryStatus::DeadLetter);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert!(deliveries[0].next_attempt_at.is_none());
+
+        let dead_letters = service.get_dead_letters(MAX_PAGE_SIZE).await.unwrap();
+        assert!(dead_letters.iter().any(|d| d.id == deliveries[0].id));
+
+        assert!(service.requeue_dead_letter(deliveries[0].id).await.unwrap());
+        assert!(!service.requeue_dead_letter(deliveries[0].id).await.unwrap());
+        let deliveries = service.get_delivery_status(notification.id).await.unwrap();
+        assert_eq!(deliveries[0].status, DeliveryStatus::Retrying);
+        assert_eq!(deliveries[0].attempts, 0);
+    }
+
+    #[test]
+    fn test_validate_preferences() {
+        let mut prefs = NotificationPreferences {
+            user_id: Uuid::new_v4(),
+            in_app_enabled: true,
+            email_enabled: false,
+            push_enabled: false,
+            frequency: "REALTIME".to_string(),
+            minimum_movement_percent: 5.0,
+            minimum_confidence: 70,
+            webhook_enabled: true,
+            webhook_url: Some("https://hooks.example.com/alerts".to_string()),
+            webhook_secret: None,
+            bot_enabled: false,
+            bot_destination: None,
+        };
+        assert!(validate_preferences(&prefs).is_ok());
+
+        prefs.webhook_url = Some("file:///etc/passwd".to_string());
+        assert!(validate_preferences(&prefs).is_err());
+
+        prefs.webhook_url = Some("http://169.254.169.254/latest/meta-data/".to_string());
+        assert!(validate_preferences(&prefs).is_err());
+
+        prefs.webhook_url = Some("http://localhost:8080/hook".to_string());
+        assert!(validate_preferences(&prefs).is_err());
+
+        prefs.webhook_url = None;
+        assert!(validate_preferences(&prefs).is_err());
+
+        prefs.webhook_enabled = false;
+        prefs.bot_enabled = true;
+        assert!(validate_preferences(&prefs).is_err());
+
+        prefs.bot_destination = Some("123456".to_string());
+        assert!(validate_preferences(&prefs).is_ok());
+    }
+
+    #[test]
+    fn test_page_size_is_clamped() {
+        assert_eq!(clamp_page_size(0), 1);
+        assert_eq!(clamp_page_size(-5), 1);
+        assert_eq!(clamp_page_size(DEFAULT_PAGE_SIZE), DEFAULT_PAGE_SIZE);
+        assert_eq!(clamp_page_size(MAX_PAGE_SIZE + 1), MAX_PAGE_SIZE);
+    }
 }
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use shared::config::SmtpConfig;
+
+use crate::email::{EmailNotification, EmailService};
+use crate::{NotificationError, Result};
+
+/// Port on which SMTP servers expect TLS from the first byte
+const IMPLICIT_TLS_PORT: u16 = 465;
+
+/// Sends email through an authenticated SMTP relay
+pub struct SmtpEmailService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpEmailService {
+    pub fn new(config: &SmtpConfig) -> Result<Self> {
+        let from = config.from_email.parse::<Mailbox>().map_err(|e| {
+            NotificationError::ValidationError(format!("Invalid SMTP from address: {}", e))
+        })?;
+
+        let builder = if config.port == IMPLICIT_TLS_PORT {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+        }
+        .map_err(|e| NotificationError::ValidationError(format!("Invalid SMTP relay: {}", e)))?;
+
+        let transport = builder
+            .port(config.port)
+            .credentials(Credentials::new(
+                config.username.clone(),
+                config.password.clone(),
+            ))
+            .build();
+
+        Ok(Self { transport, from })
+    }
+}
+
+#[async_trait]
+impl EmailService for SmtpEmailService {
+    async fn send_email(&self, notification: EmailNotification) -> Result<()> {
+        let to = notification.to.parse::<Mailbox>().map_err(|e| {
+            NotificationError::ValidationError(format!("Invalid recipient address: {}", e))
+        })?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(notification.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(notification.body)
+            .map_err(|e| NotificationError::DeliveryError(format!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| NotificationError::DeliveryError(format!("SMTP send failed: {}", e)))?;
+
+        Ok(())
+    }
+}
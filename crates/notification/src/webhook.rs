@@ -0,0 +1,315 @@
+use async_trait::async_trait;
+use database::DbPool;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use shared::models::Notification;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::channel::{ChannelKind, NotificationChannel};
+use crate::{NotificationError, Result};
+
+/// Header carrying the unix timestamp covered by the signature
+pub const TIMESTAMP_HEADER: &str = "X-Notification-Timestamp";
+
+/// Header carrying `sha256=<hex HMAC>` of `"<timestamp>.<body>"`
+pub const SIGNATURE_HEADER: &str = "X-Notification-Signature";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Body POSTed to user webhooks
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    event: &'static str,
+    notification: &'a Notification,
+}
+
+/// Delivers notifications as signed JSON POSTs to user-registered URLs
+///
+/// Each webhook is signed with its own secret, generated when the user
+/// registers the URL. Receivers recompute the HMAC with that secret (see
+/// `verify_signature`) and should reject stale timestamps to stop replays.
+/// Only public addresses are contacted and redirects are not followed.
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    db_pool: DbPool,
+}
+
+impl WebhookChannel {
+    pub fn new(db_pool: DbPool) -> Self {
+        Self {
+            client: public_http_client(),
+            db_pool,
+        }
+    }
+
+    /// Signing secret of `user_id`'s webhook, if `url` is still the one
+    /// they registered
+    async fn secret_for(&self, user_id: uuid::Uuid, url: &str) -> Result<Option<String>> {
+        let client = self.db_pool.get().await.map_err(|e| {
+            NotificationError::DatabaseError(format!("Failed to get database connection: {}", e))
+        })?;
+
+        let row = client
+            .query_opt(
+                "SELECT webhook_secret FROM notification_preferences
+                 WHERE user_id = $1 AND webhook_url = $2",
+                &[&user_id, &url],
+            )
+            .await
+            .map_err(|e| {
+                NotificationError::DatabaseError(format!("Failed to load webhook secret: {}", e))
+            })?;
+
+        Ok(row.and_then(|row| row.get("webhook_secret")))
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Webhook
+    }
+
+    async fn deliver(&self, notification: &Notification, target: &str) -> Result<()> {
+        let body = serde_json::to_vec(&WebhookPayload {
+            event: "notification",
+            notification,
+        })
+        .map_err(|e| NotificationError::DeliveryError(format!("Failed to encode webhook: {}", e)))?;
+        check_public_url(target).map_err(NotificationError::DeliveryError)?;
+        let secret = self
+            .secret_for(notification.user_id, target)
+            .await?
+            .ok_or_else(|| {
+                NotificationError::DeliveryError("Webhook URL is no longer registered".to_string())
+            })?;
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let response = self
+            .client
+            .post(target)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| NotificationError::DeliveryError(format!("Webhook request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(NotificationError::DeliveryError(format!(
+                "Webhook returned {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// HTTP client for user-supplied URLs
+///
+/// Host names are resolved to public addresses only, so a URL that resolves
+/// (or is later re-pointed) to an internal address cannot be reached.
+/// Redirects and proxies are disabled so the checked address is the one
+/// connected to.
+pub(crate) fn public_http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Resolver that drops every non-public address a name resolves to
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Reject URLs that are not http(s) or that name a non-public host directly
+///
+/// Host names are checked again when they are resolved at connect time.
+pub(crate) fn check_public_url(url: &str) -> std::result::Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| "not a valid URL".to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("must be an http(s) URL".to_string());
+    }
+
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| "must be an http(s) URL".to_string())?;
+    let public = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    };
+    if public {
+        Ok(())
+    } else {
+        Err("must not point at a private or local address".to_string())
+    }
+}
+
+/// Whether `ip` is routable on the public internet
+///
+/// Loopback, private (RFC 1918, unique local), link-local (including the
+/// 169.254.169.254 metadata service), carrier-grade NAT, multicast and
+/// reserved ranges are not.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || first == 0x2001 && ip.segments()[1] == 0x0db8)
+}
+
+/// Fresh random secret for signing one user's webhook
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Signature header value for `body` sent at `timestamp`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check a received signature header in constant time
+pub fn verify_signature(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex_digest| hex::decode(hex_digest).ok())
+    else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_round_trip() {
+        let body = br#"{"event":"notification"}"#;
+        let signature = sign("secret", 1_700_000_000, body);
+
+        assert!(signature.starts_with("sha256="));
+        assert!(verify_signature("secret", 1_700_000_000, body, &signature));
+    }
+
+    #[test]
+    fn test_signature_rejects_tampering() {
+        let body = br#"{"event":"notification"}"#;
+        let signature = sign("secret", 1_700_000_000, body);
+
+        assert!(!verify_signature("other", 1_700_000_000, body, &signature));
+        assert!(!verify_signature("secret", 1_700_000_001, body, &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, b"{}", &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, body, "sha256=zz"));
+        assert!(!verify_signature("secret", 1_700_000_000, body, &signature[7..]));
+    }
+
+    #[test]
+    fn test_non_public_addresses_are_rejected() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} should not be public", ip);
+        }
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(is_public_ip("2606:4700::1111".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_check_public_url() {
+        assert!(check_public_url("https://hooks.example.com/alerts").is_ok());
+        assert!(check_public_url("http://93.184.216.34/hook").is_ok());
+
+        assert!(check_public_url("file:///etc/passwd").is_err());
+        assert!(check_public_url("http://localhost:8080/hook").is_err());
+        assert!(check_public_url("http://127.0.0.1/hook").is_err());
+        assert!(check_public_url("http://169.254.169.254/latest/meta-data/").is_err());
+        assert!(check_public_url("http://[::1]/hook").is_err());
+    }
+
+    #[test]
+    fn test_generated_secrets_differ() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 64);
+        assert_ne!(secret, generate_secret());
+    }
+}
@@ -0,0 +1,89 @@
+//! Example exercising the native TCP transport between two processes
+//!
+//! Start a listener in one terminal:
+//!
+//!     cargo run -p proximity --example tcp_peer -- listen 127.0.0.1:7878
+//!
+//! and connect to it from another, optionally pinning the wallet printed by
+//! the listener:
+//!
+//!     cargo run -p proximity --example tcp_peer -- connect 127.0.0.1:7878 [WALLET]
+//!
+//! Both sides run the challenge-response handshake, the connector sends a
+//! Ping and the listener answers with a Pong.
+
+use futures::StreamExt;
+use proximity::platform::native::{NativeSocketConnection, NativeSocketListener};
+use proximity::{PeerMessage, PlatformConnection, ResolvedPeer, StaticPeerResolver};
+use solana_sdk::signature::{Keypair, Signer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let usage = "usage: tcp_peer (listen ADDR | connect ADDR [WALLET])";
+    let mode = args.get(1).ok_or(usage)?;
+    let addr: SocketAddr = args.get(2).ok_or(usage)?.parse()?;
+
+    let identity = Arc::new(Keypair::new());
+    println!("Local wallet: {}", identity.pubkey());
+
+    match mode.as_str() {
+        "listen" => {
+            let listener = NativeSocketListener::bind(addr, identity).await?;
+            println!("Listening on {}", listener.local_addr()?);
+
+            loop {
+                let mut connection = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        println!("Rejected peer: {}", e);
+                        continue;
+                    }
+                };
+                println!("Authenticated peer {}", connection.peer_id());
+
+                while let Some(message) = connection.next().await {
+                    let message = message?;
+                    println!("Received {:?}", message);
+                    if let PeerMessage::Ping = message {
+                        connection.send(&PeerMessage::Pong).await?;
+                    }
+                }
+                println!("Peer disconnected");
+            }
+        }
+        "connect" => {
+            let peer_id = "tcp-peer-example".to_string();
+            let resolver = Arc::new(StaticPeerResolver::new());
+            resolver
+                .insert(
+                    peer_id.clone(),
+                    ResolvedPeer {
+                        addr,
+                        wallet_address: args.get(3).cloned(),
+                    },
+                )
+                .await;
+
+            let mut connection = NativeSocketConnection::new(peer_id.clone())
+                .with_identity(identity)
+                .with_resolver(resolver);
+            connection.connect(&peer_id).await?;
+            println!(
+                "Connected to wallet {}",
+                connection.remote_wallet().unwrap_or_default()
+            );
+
+            connection.send(&PeerMessage::Ping).await?;
+            if let Some(reply) = connection.next().await {
+                println!("Received {:?}", reply?);
+            }
+            connection.close().await?;
+        }
+        _ => return Err(usage.into()),
+    }
+
+    Ok(())
+}
@@ -8,6 +8,46 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Domain separator that starts every handshake transcript
+pub const HANDSHAKE_DOMAIN: &[u8] = b"lattice-proximity-handshake/v1";
+
+/// Which end of a connection a handshake participant is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeRole {
+    /// The side that opened the connection
+    Initiator,
+    /// The side that accepted it
+    Responder,
+}
+
+/// Bytes both sides of a handshake sign
+///
+/// `HANDSHAKE_DOMAIN || initiator nonce || responder nonce || initiator key ||
+/// responder key`. Nonces and keys are 32 bytes each, so the concatenation is
+/// unambiguous, and both sides arrive at the same transcript from their own
+/// point of view. A signature over it is bound to this handshake and cannot be
+/// relayed into another one or reused as a signature over a bare nonce.
+pub fn handshake_transcript(
+    role: HandshakeRole,
+    local_nonce: &[u8],
+    peer_nonce: &[u8],
+    local_key: &[u8],
+    peer_key: &[u8],
+) -> Vec<u8> {
+    let (initiator_nonce, responder_nonce, initiator_key, responder_key) = match role {
+        HandshakeRole::Initiator => (local_nonce, peer_nonce, local_key, peer_key),
+        HandshakeRole::Responder => (peer_nonce, local_nonce, peer_key, local_key),
+    };
+
+    let mut transcript = Vec::with_capacity(HANDSHAKE_DOMAIN.len() + 4 * 32);
+    transcript.extend_from_slice(HANDSHAKE_DOMAIN);
+    transcript.extend_from_slice(initiator_nonce);
+    transcript.extend_from_slice(responder_nonce);
+    transcript.extend_from_slice(initiator_key);
+    transcript.extend_from_slice(responder_key);
+    transcript
+}
+
 #[derive(Clone)]
 pub struct Challenge {
     pub nonce: [u8; 32],
@@ -63,6 +103,42 @@ impl AuthenticationService {
     /// Verify peer signature using Ed25519
     /// Returns true if signature is valid and matches claimed wallet address
     pub async fn verify_peer(&self, peer_id: PeerId, proof: AuthenticationProof) -> Result<bool> {
+        self.verify_signed(peer_id, proof, |challenge, _| challenge.nonce.to_vec())
+            .await
+    }
+
+    /// Verify a peer's signature over the handshake transcript
+    ///
+    /// The transcript is built from the stored challenge nonce, the nonce the
+    /// peer sent, our public key and the key in `proof`, exactly as the peer
+    /// must have built it (see [`handshake_transcript`]).
+    pub async fn verify_handshake(
+        &self,
+        peer_id: PeerId,
+        proof: AuthenticationProof,
+        role: HandshakeRole,
+        peer_nonce: &[u8],
+        local_key: &[u8],
+    ) -> Result<bool> {
+        self.verify_signed(peer_id, proof, |challenge, proof| {
+            handshake_transcript(
+                role,
+                &challenge.nonce,
+                peer_nonce,
+                local_key,
+                &proof.public_key,
+            )
+        })
+        .await
+    }
+
+    /// Consume the peer's challenge and check `proof` signs the message built from it
+    async fn verify_signed(
+        &self,
+        peer_id: PeerId,
+        proof: AuthenticationProof,
+        message: impl FnOnce(&Challenge, &AuthenticationProof) -> Vec<u8>,
+    ) -> Result<bool> {
         // Check rate limiting
         if !self.check_rate_limit(&peer_id).await? {
             let err = ProximityError::RateLimitExceeded;
@@ -118,8 +194,8 @@ impl AuthenticationService {
                 err
             })?;
 
-        // Verify the signature against the challenge nonce
-        match public_key.verify(&challenge.nonce, &signature) {
+        // Verify the signature against the challenged message
+        match public_key.verify(&message(&challenge, &proof), &signature) {
             Ok(_) => {
                 // Verify that the public key matches the claimed wallet address
                 let derived_address = bs58::encode(&proof.public_key).into_string();
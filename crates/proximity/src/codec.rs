@@ -0,0 +1,140 @@
+// Framed wire codec for PeerMessage over stream transports
+//
+// Each frame is a 4-byte big-endian length prefix followed by the JSON
+// encoding of a single PeerMessage.
+
+use crate::{PeerMessage, ProximityError};
+use bytes::{Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
+
+/// Maximum accepted frame payload (1 MiB)
+pub const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+/// Length-prefixed JSON codec for `PeerMessage`
+#[derive(Debug)]
+pub struct PeerMessageCodec {
+    inner: LengthDelimitedCodec,
+}
+
+impl PeerMessageCodec {
+    /// Create a codec with the default maximum frame length
+    pub fn new() -> Self {
+        Self::with_max_frame_length(MAX_FRAME_LENGTH)
+    }
+
+    /// Create a codec that rejects frames larger than `max_frame_length` bytes
+    pub fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Self {
+            inner: LengthDelimitedCodec::builder()
+                .length_field_type::<u32>()
+                .max_frame_length(max_frame_length)
+                .new_codec(),
+        }
+    }
+}
+
+impl Default for PeerMessageCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder<PeerMessage> for PeerMessageCodec {
+    type Error = ProximityError;
+
+    fn encode(&mut self, message: PeerMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let payload = serde_json::to_vec(&message)?;
+        self.inner
+            .encode(Bytes::from(payload), dst)
+            .map_err(|e| ProximityError::SerializationError(format!("Failed to frame message: {}", e)))
+    }
+}
+
+impl Decoder for PeerMessageCodec {
+    type Item = PeerMessage;
+    type Error = ProximityError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let frame = match self.inner.decode(src) {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                return Err(ProximityError::SerializationError(format!(
+                    "Invalid frame: {}",
+                    e
+                )))
+            }
+        };
+
+        let message = serde_json::from_slice(&frame)?;
+        Ok(Some(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut codec = PeerMessageCodec::new();
+        let mut buffer = BytesMut::new();
+
+        codec.encode(PeerMessage::Ping, &mut buffer).unwrap();
+        codec
+            .encode(
+                PeerMessage::Challenge {
+                    nonce: vec![7u8; 32],
+                    public_key: vec![9u8; 32],
+                },
+                &mut buffer,
+            )
+            .unwrap();
+
+        assert!(matches!(codec.decode(&mut buffer).unwrap(), Some(PeerMessage::Ping)));
+        match codec.decode(&mut buffer).unwrap() {
+            Some(PeerMessage::Challenge { nonce, public_key }) => {
+                assert_eq!(nonce, vec![7u8; 32]);
+                assert_eq!(public_key, vec![9u8; 32]);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_partial_frame_waits_for_more_data() {
+        let mut codec = PeerMessageCodec::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(PeerMessage::Pong, &mut encoded).unwrap();
+
+        let mut buffer = BytesMut::from(&encoded[..encoded.len() - 1]);
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+
+        buffer.extend_from_slice(&encoded[encoded.len() - 1..]);
+        assert!(matches!(codec.decode(&mut buffer).unwrap(), Some(PeerMessage::Pong)));
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected() {
+        let mut codec = PeerMessageCodec::with_max_frame_length(16);
+        let mut buffer = BytesMut::new();
+        buffer.extend_from_slice(&1024u32.to_be_bytes());
+        buffer.extend_from_slice(&[0u8; 32]);
+
+        assert!(codec.decode(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_invalid_json_rejected() {
+        let mut codec = PeerMessageCodec::new();
+        let mut buffer = BytesMut::new();
+        buffer.extend_from_slice(&4u32.to_be_bytes());
+        buffer.extend_from_slice(b"nope");
+
+        assert!(matches!(
+            codec.decode(&mut buffer),
+            Err(ProximityError::SerializationError(_))
+        ));
+    }
+}
@@ -1,14 +1,25 @@
 // Peer Connection Manager - manages peer-to-peer connections
 
+use crate::platform::{PlatformConnection, PlatformConnectionFactory};
 use crate::{ConnectionQuality, ConnectionType, PeerId, PeerMessage, ProximityError, Result};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
+/// How often live transports are drained for incoming messages
+const RECEIVE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type HandlerResult = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>;
+type MessageHandler =
+    Arc<dyn Fn(PeerId, PeerMessage) -> Pin<Box<dyn Future<Output = HandlerResult> + Send>> + Send + Sync>;
+type SharedTransport = Arc<Mutex<Box<dyn PlatformConnection>>>;
+
 /// Represents an active peer connection
 #[derive(Clone)]
 pub struct PeerConnection {
@@ -75,12 +86,20 @@ impl PeerConnection {
 }
 
 /// Manages peer-to-peer connections
-/// Manages peer-to-peer connections
+///
+/// Without a connection factory, connections are bookkeeping only. With one
+/// (see [`PeerConnectionManager::with_connection_factory`]) every connection is
+/// backed by a live platform transport whose incoming messages are routed to
+/// the registered message handler.
 pub struct PeerConnectionManager {
     connections: Arc<RwLock<HashMap<PeerId, PeerConnection>>>,
     retry_config: RetryConfig,
     /// Optional message handler for routing incoming messages
-    message_handler: Arc<RwLock<Option<Arc<dyn Fn(PeerId, PeerMessage) -> std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send>> + Send + Sync>>>>,
+    message_handler: Arc<RwLock<Option<MessageHandler>>>,
+    /// Factory for live transports; `None` keeps connections simulated
+    connection_factory: Option<Arc<dyn PlatformConnectionFactory>>,
+    /// Live transports by peer
+    transports: Arc<RwLock<HashMap<PeerId, SharedTransport>>>,
     /// Peer routing preferences based on hop count to providers
     routing_preferences: Arc<RwLock<HashMap<PeerId, u32>>>,
     /// Maximum number of connections to maintain
@@ -112,6 +131,8 @@ impl PeerConnectionManager {
             connections: Arc::new(RwLock::new(HashMap::new())),
             retry_config: RetryConfig::default(),
             message_handler: Arc::new(RwLock::new(None)),
+            connection_factory: None,
+            transports: Arc::new(RwLock::new(HashMap::new())),
             routing_preferences: Arc::new(RwLock::new(HashMap::new())),
             max_connections: 10,
         }
@@ -123,11 +144,19 @@ impl PeerConnectionManager {
             connections: Arc::new(RwLock::new(HashMap::new())),
             retry_config,
             message_handler: Arc::new(RwLock::new(None)),
+            connection_factory: None,
+            transports: Arc::new(RwLock::new(HashMap::new())),
             routing_preferences: Arc::new(RwLock::new(HashMap::new())),
             max_connections: 10,
         }
     }
     
+    /// Back connections with live transports created by `factory`
+    pub fn with_connection_factory(mut self, factory: Arc<dyn PlatformConnectionFactory>) -> Self {
+        self.connection_factory = Some(factory);
+        self
+    }
+
     /// Set a message handler for routing incoming messages
     /// 
     /// The handler will be called whenever a message is received from a peer.
@@ -138,7 +167,7 @@ impl PeerConnectionManager {
     pub async fn set_message_handler<F, Fut>(&self, handler: F)
     where
         F: Fn(PeerId, PeerMessage) -> Fut + Send + Sync + 'static,
-        Fut: std::future::Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
     {
         let mut message_handler = self.message_handler.write().await;
         *message_handler = Some(Arc::new(move |peer_id, message| {
@@ -159,10 +188,18 @@ impl PeerConnectionManager {
     /// * `Ok(())` - Message handled successfully
     /// * `Err(_)` - No handler registered or handler returned error
     pub async fn handle_incoming_message(&self, peer_id: PeerId, message: PeerMessage) -> Result<()> {
+        Self::dispatch_message(&self.message_handler, peer_id, message).await
+    }
+
+    async fn dispatch_message(
+        message_handler: &RwLock<Option<MessageHandler>>,
+        peer_id: PeerId,
+        message: PeerMessage,
+    ) -> Result<()> {
         debug!("Handling incoming message from peer {}: {:?}", peer_id, message);
         
-        let handler = self.message_handler.read().await;
-        if let Some(handler) = handler.as_ref() {
+        let handler = message_handler.read().await.clone();
+        if let Some(handler) = handler {
             handler(peer_id, message).await
                 .map_err(|e| ProximityError::InternalError(format!("Message handler error: {}", e)))
         } else {
@@ -193,13 +230,16 @@ impl PeerConnectionManager {
         }
 
         // Attempt connection with retry logic
-        let connection = self.establish_connection_with_retry(&peer_id).await?;
+        let (connection, transport) = self.establish_connection_with_retry(&peer_id).await?;
 
         // Store the connection
         {
             let mut connections = self.connections.write().await;
             connections.insert(peer_id.clone(), connection.clone());
         }
+        if let Some(transport) = transport {
+            self.register_transport(peer_id.clone(), transport).await;
+        }
 
         info!("Successfully established connection to peer: {}", peer_id);
         
@@ -209,6 +249,116 @@ impl PeerConnectionManager {
         Ok(connection)
     }
     
+    /// Register an inbound connection accepted by a platform listener
+    ///
+    /// The connection must already be authenticated; it is tracked under its
+    /// own peer ID and its messages are routed like outbound connections.
+    pub async fn attach_connection(&self, transport: Box<dyn PlatformConnection>) -> Result<PeerConnection> {
+        let peer_id = transport.peer_id().clone();
+        info!("Attaching inbound connection from peer: {}", peer_id);
+
+        let connection = PeerConnection::new(peer_id.clone(), self.determine_connection_type());
+        {
+            let mut connections = self.connections.write().await;
+            connections.insert(peer_id.clone(), connection.clone());
+        }
+        self.register_transport(peer_id.clone(), transport).await;
+
+        self.on_connection_established(&peer_id).await;
+
+        Ok(connection)
+    }
+
+    /// Store a live transport and start routing its incoming messages
+    async fn register_transport(&self, peer_id: PeerId, transport: Box<dyn PlatformConnection>) {
+        let transport: SharedTransport = Arc::new(Mutex::new(transport));
+
+        let previous = self
+            .transports
+            .write()
+            .await
+            .insert(peer_id.clone(), Arc::clone(&transport));
+        if let Some(previous) = previous {
+            if let Err(e) = previous.lock().await.close().await {
+                warn!("Failed to close replaced transport for peer {}: {}", peer_id, e);
+            }
+        }
+
+        self.spawn_receive_loop(peer_id, transport);
+    }
+
+    /// Drain a transport in the background until the peer disconnects
+    ///
+    /// Pings are answered and pongs recorded here; everything else goes to
+    /// the message handler.
+    fn spawn_receive_loop(&self, peer_id: PeerId, transport: SharedTransport) {
+        let message_handler = Arc::clone(&self.message_handler);
+        let connections = Arc::clone(&self.connections);
+        let transports = Arc::clone(&self.transports);
+
+        tokio::spawn(async move {
+            let mut poll = tokio::time::interval(RECEIVE_POLL_INTERVAL);
+
+            loop {
+                poll.tick().await;
+
+                // Only hold the transport lock while draining so sends are not blocked
+                let mut received = Vec::new();
+                let connected = {
+                    let mut transport = transport.lock().await;
+                    loop {
+                        match transport.receive().await {
+                            Ok(Some(message)) => received.push(message),
+                            Ok(None) => break,
+                            Err(e) => {
+                                warn!("Failed to receive message from peer {}: {}", peer_id, e);
+                                break;
+                            }
+                        }
+                    }
+                    transport.is_connected()
+                };
+
+                for message in received {
+                    match message {
+                        PeerMessage::Ping => {
+                            if let Err(e) = transport.lock().await.send(&PeerMessage::Pong).await {
+                                warn!("Failed to answer ping from peer {}: {}", peer_id, e);
+                            }
+                        }
+                        PeerMessage::Pong => {
+                            if let Some(connection) = connections.write().await.get_mut(&peer_id) {
+                                connection.record_pong();
+                            }
+                        }
+                        message => {
+                            if let Err(e) =
+                                Self::dispatch_message(&message_handler, peer_id.clone(), message).await
+                            {
+                                warn!("Failed to handle message from peer {}: {}", peer_id, e);
+                            }
+                        }
+                    }
+                }
+
+                if !connected {
+                    break;
+                }
+            }
+
+            // Forget the peer unless it has already reconnected on a new transport
+            let mut transports = transports.write().await;
+            if transports
+                .get(&peer_id)
+                .is_some_and(|current| Arc::ptr_eq(current, &transport))
+            {
+                transports.remove(&peer_id);
+                connections.write().await.remove(&peer_id);
+                info!("Peer {} disconnected", peer_id);
+            }
+        });
+    }
+
     /// Called when a new connection is established
     /// 
     /// This allows external services to be notified of new connections
@@ -220,7 +370,10 @@ impl PeerConnectionManager {
     }
 
     /// Internal method to establish connection with exponential backoff retry
-    async fn establish_connection_with_retry(&self, peer_id: &PeerId) -> Result<PeerConnection> {
+    async fn establish_connection_with_retry(
+        &self,
+        peer_id: &PeerId,
+    ) -> Result<(PeerConnection, Option<Box<dyn PlatformConnection>>)> {
         let mut attempt = 0;
         let mut backoff_ms = self.retry_config.initial_backoff_ms;
 
@@ -258,10 +411,23 @@ impl PeerConnectionManager {
     }
 
     /// Try to establish a single connection (platform-specific)
-    async fn try_establish_connection(&self, peer_id: &PeerId) -> Result<PeerConnection> {
+    async fn try_establish_connection(
+        &self,
+        peer_id: &PeerId,
+    ) -> Result<(PeerConnection, Option<Box<dyn PlatformConnection>>)> {
         // Determine connection type based on platform
         let connection_type = self.determine_connection_type();
 
+        if let Some(factory) = &self.connection_factory {
+            debug!(
+                "Opening {} connection to peer {}",
+                factory.platform_name(), peer_id
+            );
+            let mut transport = factory.create_connection(peer_id.clone()).await?;
+            transport.connect(peer_id).await?;
+            return Ok((PeerConnection::new(peer_id.clone(), connection_type), Some(transport)));
+        }
+
         debug!(
             "Attempting {:?} connection to peer {}",
             connection_type, peer_id
@@ -273,20 +439,20 @@ impl PeerConnectionManager {
                 // WebRTC connection for web platform
                 // In a real implementation, this would use WebRTC APIs
                 debug!("Establishing WebRTC connection to {}", peer_id);
-                Ok(PeerConnection::new(peer_id.clone(), ConnectionType::WebRTC))
+                Ok((PeerConnection::new(peer_id.clone(), ConnectionType::WebRTC), None))
             }
             ConnectionType::TcpSocket => {
                 // TCP socket connection for native platforms
                 // In a real implementation, this would establish a TCP connection
                 debug!("Establishing TCP socket connection to {}", peer_id);
-                Ok(PeerConnection::new(peer_id.clone(), ConnectionType::TcpSocket))
+                Ok((PeerConnection::new(peer_id.clone(), ConnectionType::TcpSocket), None))
             }
             ConnectionType::BleConnection => {
                 // BLE connection for Bluetooth-based discovery
                 debug!("Establishing BLE connection to {}", peer_id);
-                Ok(PeerConnection::new(
-                    peer_id.clone(),
-                    ConnectionType::BleConnection,
+                Ok((
+                    PeerConnection::new(peer_id.clone(), ConnectionType::BleConnection),
+                    None,
                 ))
             }
         }
@@ -315,10 +481,14 @@ impl PeerConnectionManager {
             .get(&peer_id)
             .ok_or_else(|| ProximityError::PeerNotFound(peer_id.clone()))?;
 
-        // In a real implementation, this would send the message over the connection
-        // For now, we'll just serialize it to verify it's valid
-        let _serialized = serde_json::to_string(&message)
-            .map_err(|e| ProximityError::SerializationError(e.to_string()))?;
+        let transport = self.transports.read().await.get(&peer_id).cloned();
+        if let Some(transport) = transport {
+            transport.lock().await.send(&message).await?;
+        } else {
+            // Simulated connection: just verify the message serializes
+            let _serialized = serde_json::to_string(&message)
+                .map_err(|e| ProximityError::SerializationError(e.to_string()))?;
+        }
 
         debug!(
             "Message sent to peer {} via {:?}",
@@ -335,6 +505,13 @@ impl PeerConnectionManager {
         if connections.remove(&peer_id).is_some() {
             debug!("Connection to peer {} closed", peer_id);
             drop(connections); // Release lock before notification
+
+            let transport = self.transports.write().await.remove(&peer_id);
+            if let Some(transport) = transport {
+                if let Err(e) = transport.lock().await.close().await {
+                    warn!("Error closing transport for peer {}: {}", peer_id, e);
+                }
+            }
             
             // Notify about disconnection
             self.on_connection_closed(&peer_id).await;
@@ -429,8 +606,24 @@ impl PeerConnectionManager {
 
     /// Wait for pong response from a peer
     async fn wait_for_pong(&self, peer_id: &PeerId) -> Result<ConnectionQuality> {
-        // In a real implementation, this would wait for an actual Pong message
-        // For now, we'll simulate receiving a pong after a short delay
+        if self.transports.read().await.contains_key(peer_id) {
+            // The receive loop records the pong when it arrives
+            loop {
+                {
+                    let mut connections = self.connections.write().await;
+                    let connection = connections
+                        .get_mut(peer_id)
+                        .ok_or_else(|| ProximityError::PeerNotFound(peer_id.clone()))?;
+                    if connection.last_ping_sent.is_none() {
+                        connection.quality.packet_loss_percent = connection.calculate_packet_loss();
+                        return Ok(connection.quality.clone());
+                    }
+                }
+                tokio::time::sleep(RECEIVE_POLL_INTERVAL).await;
+            }
+        }
+
+        // Simulated connection: pretend a pong arrived after a short delay
         tokio::time::sleep(Duration::from_millis(50)).await;
 
         let mut connections = self.connections.write().await;
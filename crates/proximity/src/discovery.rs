@@ -4,6 +4,7 @@ use crate::mdns::{MdnsAnnouncer, MdnsListener};
 use crate::{DiscoveredPeer, DiscoveryMethod, PeerId, ProximityError, Result};
 use chrono::{Duration, Utc};
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::{RwLock, Notify};
 use tokio::time::{interval, Duration as TokioDuration};
@@ -23,6 +24,7 @@ pub struct DiscoveryService {
     device_id: String,
     wallet_address: String,
     max_peers: usize,
+    transport_port: u16,
 }
 
 impl DiscoveryService {
@@ -37,9 +39,16 @@ impl DiscoveryService {
             device_id,
             wallet_address,
             max_peers: 50, // Default capacity limit
+            transport_port: 0,
         }
     }
 
+    /// Advertise the port of the local transfer listener via mDNS
+    pub fn with_transport_port(mut self, port: u16) -> Self {
+        self.transport_port = port;
+        self
+    }
+
     /// Start discovery session with the specified method
     pub async fn start_discovery(&self, method: DiscoveryMethod) -> Result<()> {
         let mut active = self.active_method.write().await;
@@ -122,6 +131,20 @@ impl DiscoveryService {
         *self.active_method.read().await
     }
 
+    /// Get a discovered peer by ID
+    pub async fn get_peer(&self, peer_id: &PeerId) -> Option<DiscoveredPeer> {
+        self.discovered_peers.read().await.get(peer_id).cloned()
+    }
+
+    /// Resolve the transfer address a peer advertised via mDNS
+    pub async fn resolve_peer_address(&self, peer_id: &PeerId) -> Option<SocketAddr> {
+        let listener = self.mdns_listener.read().await;
+        match listener.as_ref() {
+            Some(listener) => listener.resolve_peer_address(peer_id).await,
+            None => None,
+        }
+    }
+
     /// Get the maximum peer capacity
     pub fn get_max_peers(&self) -> usize {
         self.max_peers
@@ -217,7 +240,8 @@ impl DiscoveryService {
             self.user_tag.clone(),
             self.device_id.clone(),
             self.wallet_address.clone(),
-        )?;
+        )?
+        .with_port(self.transport_port);
         announcer.start().await?;
 
         let mut announcer_guard = self.mdns_announcer.write().await;
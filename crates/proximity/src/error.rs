@@ -84,6 +84,12 @@ impl From<serde_json::Error> for ProximityError {
     }
 }
 
+impl From<std::io::Error> for ProximityError {
+    fn from(err: std::io::Error) -> Self {
+        ProximityError::NetworkError(err.to_string())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ProximityError>;
 
 /// Context for error logging
@@ -4,6 +4,7 @@ pub mod authentication;
 pub mod transfer;
 pub mod session;
 pub mod connection;
+pub mod codec;
 pub mod error;
 pub mod mdns;
 pub mod ble;
@@ -16,13 +17,20 @@ pub mod lifecycle;
 pub use types::*;
 pub use error::{ProximityError, Result, ErrorContext, ErrorCategory};
 pub use discovery::DiscoveryService;
-pub use authentication::{AuthenticationService, AuthenticationProof, Challenge};
+pub use authentication::{
+    handshake_transcript, AuthenticationService, AuthenticationProof, Challenge, HandshakeRole,
+    HANDSHAKE_DOMAIN,
+};
 pub use connection::{PeerConnection, PeerConnectionManager, RetryConfig};
 pub use transfer::TransferService;
 pub use session::SessionManager;
 pub use receipt_helper::ProximityReceiptData;
 pub use qr::QrCodeService;
-pub use platform::{PlatformConnection, PlatformConnectionFactory, get_default_factory};
+pub use codec::PeerMessageCodec;
+pub use platform::{
+    PeerAddressResolver, PlatformConnection, PlatformConnectionFactory, ResolvedPeer,
+    StaticPeerResolver, get_default_factory,
+};
 pub use permissions::{PermissionManager, PermissionStatus};
 pub use lifecycle::{LifecycleManager, AppState};
 
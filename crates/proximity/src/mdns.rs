@@ -4,6 +4,7 @@ use crate::{DiscoveredPeer, DiscoveryMethod, PeerId, ProximityError, Result};
 use chrono::Utc;
 use mdns_sd::{ServiceDaemon, ServiceInfo};
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
@@ -18,6 +19,7 @@ pub struct MdnsAnnouncer {
     user_tag: String,
     device_id: String,
     wallet_address: String,
+    port: u16,
 }
 
 impl MdnsAnnouncer {
@@ -32,9 +34,16 @@ impl MdnsAnnouncer {
             user_tag,
             device_id,
             wallet_address,
+            port: 0,
         })
     }
 
+    /// Advertise the TCP port peers should connect to for transfers
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
     /// Start broadcasting presence via mDNS
     pub async fn start(&self) -> Result<()> {
         let mut service_guard = self.service_info.write().await;
@@ -58,15 +67,18 @@ impl MdnsAnnouncer {
 
         // Create service info
         // ServiceInfo::new(type, instance, hostname, addresses, port, properties)
+        // Host addresses are filled in by the daemon so peers can resolve us
+        let host_name = format!("{}.local.", instance_name);
         let service_info = ServiceInfo::new(
             SERVICE_TYPE,
             &instance_name,
-            &instance_name,
+            &host_name,
             "",
-            0, // Port 0 means the OS will assign a dynamic port
+            self.port,
             Some(properties),
         )
-        .map_err(|e| ProximityError::NetworkError(format!("Failed to create service info: {}", e)))?;
+        .map_err(|e| ProximityError::NetworkError(format!("Failed to create service info: {}", e)))?
+        .enable_addr_auto();
 
         // Register the service
         self.daemon
@@ -109,6 +121,7 @@ impl MdnsAnnouncer {
 pub struct MdnsListener {
     daemon: Arc<ServiceDaemon>,
     discovered_peers: Arc<RwLock<HashMap<PeerId, DiscoveredPeer>>>,
+    peer_addresses: Arc<RwLock<HashMap<PeerId, SocketAddr>>>,
     running: Arc<RwLock<bool>>,
 }
 
@@ -121,6 +134,7 @@ impl MdnsListener {
         Ok(Self {
             daemon: Arc::new(daemon),
             discovered_peers: Arc::new(RwLock::new(HashMap::new())),
+            peer_addresses: Arc::new(RwLock::new(HashMap::new())),
             running: Arc::new(RwLock::new(false)),
         })
     }
@@ -148,6 +162,7 @@ impl MdnsListener {
         drop(running);
 
         let peers = Arc::clone(&self.discovered_peers);
+        let addresses = Arc::clone(&self.peer_addresses);
         let running_flag = Arc::clone(&self.running);
         let on_peer_discovered = Arc::new(on_peer_discovered);
 
@@ -168,6 +183,11 @@ impl MdnsListener {
                         if let Some(peer) = Self::parse_service_info(&info) {
                             let peer_id = peer.peer_id.clone();
 
+                            // Remember where the peer accepts connections
+                            if let Some(addr) = Self::parse_socket_addr(&info) {
+                                addresses.write().await.insert(peer_id.clone(), addr);
+                            }
+
                             // Add to discovered peers
                             let mut peer_map = peers.write().await;
                             peer_map.insert(peer_id.clone(), peer.clone());
@@ -184,6 +204,7 @@ impl MdnsListener {
                         if let Some(device_id) = Self::extract_device_id(&fullname) {
                             let mut peer_map = peers.write().await;
                             peer_map.remove(&device_id);
+                            addresses.write().await.remove(&device_id);
                             info!("Removed peer: {}", device_id);
                         }
                     }
@@ -221,6 +242,7 @@ impl MdnsListener {
         // Clear discovered peers
         let mut peers = self.discovered_peers.write().await;
         peers.clear();
+        self.peer_addresses.write().await.clear();
 
         info!("mDNS listener stopped");
         Ok(())
@@ -237,6 +259,33 @@ impl MdnsListener {
         peers.values().cloned().collect()
     }
 
+    /// Get a discovered peer by ID
+    pub async fn get_peer(&self, peer_id: &PeerId) -> Option<DiscoveredPeer> {
+        self.discovered_peers.read().await.get(peer_id).cloned()
+    }
+
+    /// Resolve the socket address a discovered peer advertised for transfers
+    pub async fn resolve_peer_address(&self, peer_id: &PeerId) -> Option<SocketAddr> {
+        self.peer_addresses.read().await.get(peer_id).copied()
+    }
+
+    /// Pick a connectable address from a resolved service, preferring IPv4
+    fn parse_socket_addr(info: &ServiceInfo) -> Option<SocketAddr> {
+        let port = info.get_port();
+        if port == 0 {
+            return None;
+        }
+
+        let addresses = info.get_addresses();
+        let ip = addresses
+            .iter()
+            .find(|ip| ip.is_ipv4())
+            .or_else(|| addresses.iter().next())
+            .copied()?;
+
+        Some(SocketAddr::new(ip, port))
+    }
+
     /// Parse ServiceInfo into DiscoveredPeer
     fn parse_service_info(info: &ServiceInfo) -> Option<DiscoveredPeer> {
         let properties = info.get_properties();
@@ -290,6 +339,7 @@ impl Default for MdnsListener {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::net::IpAddr;
 
     #[tokio::test]
     async fn test_mdns_announcer_creation() {
@@ -371,6 +421,34 @@ mod tests {
         assert!(!listener.is_running().await);
     }
 
+    #[test]
+    fn test_parse_socket_addr() {
+        let info = ServiceInfo::new(
+            SERVICE_TYPE,
+            "crypto-p2p-device123",
+            "crypto-p2p-device123.local.",
+            "192.168.1.20",
+            47000,
+            None::<HashMap<String, String>>,
+        )
+        .unwrap();
+        assert_eq!(
+            MdnsListener::parse_socket_addr(&info),
+            Some(SocketAddr::new(IpAddr::from([192, 168, 1, 20]), 47000))
+        );
+
+        let no_port = ServiceInfo::new(
+            SERVICE_TYPE,
+            "crypto-p2p-device123",
+            "crypto-p2p-device123.local.",
+            "192.168.1.20",
+            0,
+            None::<HashMap<String, String>>,
+        )
+        .unwrap();
+        assert_eq!(MdnsListener::parse_socket_addr(&no_port), None);
+    }
+
     #[tokio::test]
     async fn test_extract_device_id() {
         let fullname = "crypto-p2p-device123._crypto-p2p._tcp.local.";
@@ -1,9 +1,13 @@
 // Platform abstraction layer for proximity transfers
 // Provides traits and implementations for platform-specific functionality
 
-use crate::{PeerId, PeerMessage, ProximityError, Result};
+use crate::mdns::MdnsListener;
+use crate::{DiscoveryService, PeerId, PeerMessage, ProximityError, Result};
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::net::SocketAddr;
+use tokio::sync::RwLock;
 
 /// Trait for platform-specific connection implementations
 #[async_trait]
@@ -37,6 +41,80 @@ pub trait PlatformConnectionFactory: Send + Sync {
     fn platform_name(&self) -> &str;
 }
 
+/// Network location of a peer, as learned from discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPeer {
+    pub addr: SocketAddr,
+    /// Wallet the peer advertised; the handshake must prove ownership of it
+    pub wallet_address: Option<String>,
+}
+
+/// Resolves peer IDs to connectable socket addresses
+#[async_trait]
+pub trait PeerAddressResolver: Send + Sync {
+    async fn resolve(&self, peer_id: &PeerId) -> Result<ResolvedPeer>;
+}
+
+#[async_trait]
+impl PeerAddressResolver for MdnsListener {
+    async fn resolve(&self, peer_id: &PeerId) -> Result<ResolvedPeer> {
+        let addr = self
+            .resolve_peer_address(peer_id)
+            .await
+            .ok_or_else(|| ProximityError::PeerNotFound(peer_id.clone()))?;
+        let wallet_address = self.get_peer(peer_id).await.map(|peer| peer.wallet_address);
+
+        Ok(ResolvedPeer { addr, wallet_address })
+    }
+}
+
+#[async_trait]
+impl PeerAddressResolver for DiscoveryService {
+    async fn resolve(&self, peer_id: &PeerId) -> Result<ResolvedPeer> {
+        let addr = self
+            .resolve_peer_address(peer_id)
+            .await
+            .ok_or_else(|| ProximityError::PeerNotFound(peer_id.clone()))?;
+        let wallet_address = self.get_peer(peer_id).await.map(|peer| peer.wallet_address);
+
+        Ok(ResolvedPeer { addr, wallet_address })
+    }
+}
+
+/// Fixed address table for manually paired peers (e.g. scanned from a QR code)
+#[derive(Default)]
+pub struct StaticPeerResolver {
+    peers: RwLock<HashMap<PeerId, ResolvedPeer>>,
+}
+
+impl StaticPeerResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register or replace the address of a peer
+    pub async fn insert(&self, peer_id: PeerId, peer: ResolvedPeer) {
+        self.peers.write().await.insert(peer_id, peer);
+    }
+
+    /// Forget a peer
+    pub async fn remove(&self, peer_id: &PeerId) {
+        self.peers.write().await.remove(peer_id);
+    }
+}
+
+#[async_trait]
+impl PeerAddressResolver for StaticPeerResolver {
+    async fn resolve(&self, peer_id: &PeerId) -> Result<ResolvedPeer> {
+        self.peers
+            .read()
+            .await
+            .get(peer_id)
+            .cloned()
+            .ok_or_else(|| ProximityError::PeerNotFound(peer_id.clone()))
+    }
+}
+
 /// WebRTC connection implementation for web platform
 #[cfg(target_arch = "wasm32")]
 pub mod webrtc {
@@ -136,128 +214,519 @@ pub mod webrtc {
 }
 
 /// Native TCP socket connection implementation for mobile/desktop
+///
+/// Peers are reached at the address they advertised via mDNS. Every frame on
+/// the wire is a length-prefixed JSON `PeerMessage` (see [`crate::codec`]).
+/// Before any application traffic, both sides run the mutual
+/// `Challenge`/`ChallengeResponse` handshake backed by `AuthenticationService`:
+///
+/// 1. each side sends a `Challenge` with a fresh 32-byte nonce and its wallet
+///    public key
+/// 2. each side signs the handshake transcript (both nonces and both keys,
+///    see [`crate::handshake_transcript`]) with its wallet key and replies with
+///    a `ChallengeResponse`
+/// 3. each side verifies the response against the same transcript and the
+///    wallet it expects
 #[cfg(not(target_arch = "wasm32"))]
 pub mod native {
     use super::*;
-    use tokio::net::TcpStream;
-    use tokio::io::AsyncWriteExt;
+    use crate::codec::PeerMessageCodec;
+    use crate::{handshake_transcript, AuthenticationProof, AuthenticationService, HandshakeRole};
+    use futures::stream::{SplitSink, SplitStream};
+    use futures::{SinkExt, Stream, StreamExt};
+    use solana_sdk::signature::{Keypair, Signer};
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc::{self, error::TryRecvError};
+    use tokio::task::JoinHandle;
+    use tokio_util::codec::Framed;
     use tracing::{debug, info, warn};
-    
-    #[derive(Debug)]
+
+    type FramedStream = Framed<TcpStream, PeerMessageCodec>;
+
+    /// Timeouts and buffering for native socket connections
+    #[derive(Debug, Clone)]
+    pub struct NativeTransportConfig {
+        /// Maximum time to wait for the TCP connection to open
+        pub connect_timeout: Duration,
+        /// Maximum time to complete the challenge-response handshake
+        pub handshake_timeout: Duration,
+        /// Number of received messages buffered before the socket is back-pressured
+        pub receive_buffer: usize,
+    }
+
+    impl Default for NativeTransportConfig {
+        fn default() -> Self {
+            Self {
+                connect_timeout: Duration::from_secs(5),
+                handshake_timeout: Duration::from_secs(10),
+                receive_buffer: 256,
+            }
+        }
+    }
+
     pub struct NativeSocketConnection {
         peer_id: PeerId,
-        stream: Option<TcpStream>,
+        identity: Option<Arc<Keypair>>,
+        resolver: Option<Arc<dyn PeerAddressResolver>>,
+        auth: Arc<AuthenticationService>,
+        config: NativeTransportConfig,
+        writer: Option<SplitSink<FramedStream, PeerMessage>>,
+        incoming: Option<mpsc::Receiver<Result<PeerMessage>>>,
+        reader_task: Option<JoinHandle<()>>,
+        remote_addr: Option<SocketAddr>,
+        remote_wallet: Option<String>,
     }
-    
+
     impl NativeSocketConnection {
         pub fn new(peer_id: PeerId) -> Self {
             Self {
                 peer_id,
-                stream: None,
+                identity: None,
+                resolver: None,
+                auth: Arc::new(AuthenticationService::new()),
+                config: NativeTransportConfig::default(),
+                writer: None,
+                incoming: None,
+                reader_task: None,
+                remote_addr: None,
+                remote_wallet: None,
             }
         }
+
+        /// Set the wallet keypair used to answer the peer's challenge
+        pub fn with_identity(mut self, identity: Arc<Keypair>) -> Self {
+            self.identity = Some(identity);
+            self
+        }
+
+        /// Set the resolver used to look up the peer's address on connect
+        pub fn with_resolver(mut self, resolver: Arc<dyn PeerAddressResolver>) -> Self {
+            self.resolver = Some(resolver);
+            self
+        }
+
+        /// Share an authentication service (challenge cache and rate limits)
+        pub fn with_authentication(mut self, auth: Arc<AuthenticationService>) -> Self {
+            self.auth = auth;
+            self
+        }
+
+        pub fn with_config(mut self, config: NativeTransportConfig) -> Self {
+            self.config = config;
+            self
+        }
+
+        /// Socket address of the connected peer
+        pub fn remote_addr(&self) -> Option<SocketAddr> {
+            self.remote_addr
+        }
+
+        /// Wallet address the peer proved ownership of during the handshake
+        pub fn remote_wallet(&self) -> Option<&str> {
+            self.remote_wallet.as_deref()
+        }
+
+        /// Take over an authenticated stream and start the background reader
+        fn attach(&mut self, framed: FramedStream, remote_addr: SocketAddr, remote_wallet: String) {
+            let (writer, reader) = framed.split();
+            let (tx, rx) = mpsc::channel(self.config.receive_buffer.max(1));
+
+            self.reader_task = Some(spawn_reader(self.peer_id.clone(), reader, tx));
+            self.writer = Some(writer);
+            self.incoming = Some(rx);
+            self.remote_addr = Some(remote_addr);
+            self.remote_wallet = Some(remote_wallet);
+        }
+
+        fn teardown(&mut self) {
+            if let Some(task) = self.reader_task.take() {
+                task.abort();
+            }
+            self.incoming = None;
+        }
     }
-    
+
+    impl Debug for NativeSocketConnection {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("NativeSocketConnection")
+                .field("peer_id", &self.peer_id)
+                .field("remote_addr", &self.remote_addr)
+                .field("remote_wallet", &self.remote_wallet)
+                .field("connected", &self.is_connected())
+                .finish()
+        }
+    }
+
+    impl Drop for NativeSocketConnection {
+        fn drop(&mut self) {
+            self.teardown();
+        }
+    }
+
+    /// Messages received from the peer, in order. Ends when the peer disconnects.
+    impl Stream for NativeSocketConnection {
+        type Item = Result<PeerMessage>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            match self.get_mut().incoming.as_mut() {
+                Some(incoming) => incoming.poll_recv(cx),
+                None => Poll::Ready(None),
+            }
+        }
+    }
+
     #[async_trait]
     impl PlatformConnection for NativeSocketConnection {
         async fn connect(&mut self, peer_id: &PeerId) -> Result<()> {
             info!("Establishing TCP socket connection to peer: {}", peer_id);
-            
-            // In a real implementation, this would:
-            // 1. Resolve peer address from discovery info
-            // 2. Connect to peer's TCP socket
-            // 3. Perform handshake
-            
-            // For now, we'll simulate a connection
-            // In production, you would connect to an actual address:
-            // let stream = TcpStream::connect("peer_address:port").await
-            //     .map_err(|e| ProximityError::ConnectionFailed(e.to_string()))?;
-            // self.stream = Some(stream);
-            
+
+            if self.is_connected() {
+                debug!("TCP socket connection to peer {} already open", peer_id);
+                return Ok(());
+            }
+
+            let identity = self.identity.clone().ok_or_else(|| {
+                ProximityError::ConnectionFailed("No local identity configured".to_string())
+            })?;
+            let resolver = self.resolver.clone().ok_or_else(|| {
+                ProximityError::ConnectionFailed("No peer address resolver configured".to_string())
+            })?;
+
+            let resolved = resolver.resolve(peer_id).await?;
+            debug!("Resolved peer {} to {}", peer_id, resolved.addr);
+
+            let stream = tokio::time::timeout(
+                self.config.connect_timeout,
+                TcpStream::connect(resolved.addr),
+            )
+            .await
+            .map_err(|_| {
+                ProximityError::Timeout(format!("Connecting to peer {} at {}", peer_id, resolved.addr))
+            })?
+            .map_err(|e| {
+                ProximityError::ConnectionFailed(format!("Failed to connect to {}: {}", resolved.addr, e))
+            })?;
+            stream.set_nodelay(true)?;
+
+            let mut framed = Framed::new(stream, PeerMessageCodec::new());
+            let remote_wallet = tokio::time::timeout(
+                self.config.handshake_timeout,
+                perform_handshake(
+                    &mut framed,
+                    &self.auth,
+                    &identity,
+                    peer_id.clone(),
+                    resolved.wallet_address.as_deref(),
+                    HandshakeRole::Initiator,
+                ),
+            )
+            .await
+            .map_err(|_| ProximityError::Timeout(format!("Handshake with peer {}", peer_id)))??;
+
+            self.peer_id = peer_id.clone();
+            self.attach(framed, resolved.addr, remote_wallet);
+
             debug!("TCP socket connection established to peer: {}", peer_id);
             Ok(())
         }
-        
+
         async fn send(&mut self, message: &PeerMessage) -> Result<()> {
-            if self.stream.is_none() {
-                return Err(ProximityError::ConnectionFailed(
-                    "Not connected".to_string()
-                ));
-            }
-            
+            let writer = self.writer.as_mut().ok_or_else(|| {
+                ProximityError::ConnectionFailed("Not connected".to_string())
+            })?;
+
             debug!("Sending message via TCP socket to peer: {}", self.peer_id);
-            
-            let _serialized = serde_json::to_string(message)
-                .map_err(|e| ProximityError::SerializationError(e.to_string()))?;
-            
-            // In a real implementation, this would write to the TCP stream
-            // if let Some(stream) = &mut self.stream {
-            //     stream.write_all(serialized.as_bytes()).await
-            //         .map_err(|e| ProximityError::NetworkError(e.to_string()))?;
-            // }
-            
-            Ok(())
+
+            writer.send(message.clone()).await
         }
-        
+
         async fn receive(&mut self) -> Result<Option<PeerMessage>> {
-            if self.stream.is_none() {
-                return Ok(None);
+            let incoming = match self.incoming.as_mut() {
+                Some(incoming) => incoming,
+                None => return Ok(None),
+            };
+
+            match incoming.try_recv() {
+                Ok(message) => message.map(Some),
+                Err(TryRecvError::Empty) => Ok(None),
+                Err(TryRecvError::Disconnected) => {
+                    debug!("TCP socket closed by peer: {}", self.peer_id);
+                    self.incoming = None;
+                    self.writer = None;
+                    Ok(None)
+                }
             }
-            
-            // In a real implementation, this would read from the TCP stream
-            // if let Some(stream) = &mut self.stream {
-            //     let mut buffer = vec![0u8; 4096];
-            //     match stream.read(&mut buffer).await {
-            //         Ok(0) => return Ok(None), // Connection closed
-            //         Ok(n) => {
-            //             let data = &buffer[..n];
-            //             let message = serde_json::from_slice(data)
-            //                 .map_err(|e| ProximityError::SerializationError(e.to_string()))?;
-            //             return Ok(Some(message));
-            //         }
-            //         Err(e) => return Err(ProximityError::NetworkError(e.to_string())),
-            //     }
-            // }
-            
-            Ok(None)
         }
-        
+
         async fn close(&mut self) -> Result<()> {
             debug!("Closing TCP socket connection to peer: {}", self.peer_id);
-            
-            if let Some(mut stream) = self.stream.take() {
+
+            self.teardown();
+            if let Some(mut writer) = self.writer.take() {
                 // Gracefully shutdown the connection
-                if let Err(e) = stream.shutdown().await {
+                if let Err(e) = writer.close().await {
                     warn!("Error shutting down TCP connection: {}", e);
                 }
             }
-            
+
             Ok(())
         }
-        
+
         fn is_connected(&self) -> bool {
-            self.stream.is_some()
+            self.writer.is_some()
+                && self
+                    .reader_task
+                    .as_ref()
+                    .is_some_and(|task| !task.is_finished())
         }
-        
+
         fn peer_id(&self) -> &PeerId {
             &self.peer_id
         }
     }
-    
-    pub struct NativeSocketConnectionFactory;
-    
+
+    /// Accepts inbound TCP connections and authenticates them
+    pub struct NativeSocketListener {
+        listener: TcpListener,
+        identity: Arc<Keypair>,
+        auth: Arc<AuthenticationService>,
+        config: NativeTransportConfig,
+    }
+
+    impl NativeSocketListener {
+        /// Bind to `addr`; use port 0 to let the OS choose and advertise
+        /// `local_addr()` via mDNS
+        pub async fn bind(addr: SocketAddr, identity: Arc<Keypair>) -> Result<Self> {
+            let listener = TcpListener::bind(addr).await.map_err(|e| {
+                ProximityError::NetworkError(format!("Failed to bind TCP listener on {}: {}", addr, e))
+            })?;
+
+            info!("Listening for peer connections on {}", listener.local_addr()?);
+
+            Ok(Self {
+                listener,
+                identity,
+                auth: Arc::new(AuthenticationService::new()),
+                config: NativeTransportConfig::default(),
+            })
+        }
+
+        pub fn with_authentication(mut self, auth: Arc<AuthenticationService>) -> Self {
+            self.auth = auth;
+            self
+        }
+
+        pub fn with_config(mut self, config: NativeTransportConfig) -> Self {
+            self.config = config;
+            self
+        }
+
+        pub fn local_addr(&self) -> Result<SocketAddr> {
+            Ok(self.listener.local_addr()?)
+        }
+
+        /// Accept the next peer and run the handshake
+        ///
+        /// Inbound peers have not been resolved through discovery, so the
+        /// returned connection is identified by the wallet address the peer
+        /// proved ownership of.
+        pub async fn accept(&self) -> Result<NativeSocketConnection> {
+            let (stream, remote_addr) = self.listener.accept().await?;
+            stream.set_nodelay(true)?;
+            debug!("Accepted TCP connection from {}", remote_addr);
+
+            let mut framed = Framed::new(stream, PeerMessageCodec::new());
+            let remote_wallet = tokio::time::timeout(
+                self.config.handshake_timeout,
+                perform_handshake(
+                    &mut framed,
+                    &self.auth,
+                    &self.identity,
+                    remote_addr.to_string(),
+                    None,
+                    HandshakeRole::Responder,
+                ),
+            )
+            .await
+            .map_err(|_| ProximityError::Timeout(format!("Handshake with {}", remote_addr)))??;
+
+            info!("Authenticated inbound peer {} from {}", remote_wallet, remote_addr);
+
+            let mut connection = NativeSocketConnection::new(remote_wallet.clone())
+                .with_identity(Arc::clone(&self.identity))
+                .with_authentication(Arc::clone(&self.auth))
+                .with_config(self.config.clone());
+            connection.attach(framed, remote_addr, remote_wallet);
+
+            Ok(connection)
+        }
+    }
+
+    /// Run the mutual challenge-response handshake and return the peer's
+    /// authenticated wallet address
+    async fn perform_handshake(
+        framed: &mut FramedStream,
+        auth: &AuthenticationService,
+        identity: &Keypair,
+        challenge_key: PeerId,
+        expected_wallet: Option<&str>,
+        role: HandshakeRole,
+    ) -> Result<String> {
+        // Both sides challenge first, so neither waits on the other
+        let challenge = auth.create_challenge(challenge_key.clone()).await?;
+        let local_key = identity.pubkey().to_bytes();
+        framed
+            .send(PeerMessage::Challenge {
+                nonce: challenge.nonce.to_vec(),
+                public_key: local_key.to_vec(),
+            })
+            .await?;
+
+        let (peer_nonce, public_key) = match next_handshake_message(framed).await? {
+            PeerMessage::Challenge { nonce, public_key }
+                if nonce.len() == challenge.nonce.len() && public_key.len() == local_key.len() =>
+            {
+                (nonce, public_key)
+            }
+            _ => {
+                return Err(ProximityError::AuthenticationFailed(
+                    "Expected a 32-byte challenge and public key from peer".to_string(),
+                ))
+            }
+        };
+
+        let transcript =
+            handshake_transcript(role, &challenge.nonce, &peer_nonce, &local_key, &public_key);
+        let signature = identity.sign_message(&transcript);
+        framed
+            .send(PeerMessage::ChallengeResponse {
+                signature: signature.as_ref().to_vec(),
+            })
+            .await?;
+
+        let signature = match next_handshake_message(framed).await? {
+            PeerMessage::ChallengeResponse { signature } => signature,
+            _ => {
+                return Err(ProximityError::AuthenticationFailed(
+                    "Expected a challenge response from peer".to_string(),
+                ))
+            }
+        };
+
+        let wallet_address = match expected_wallet {
+            Some(wallet) => wallet.to_string(),
+            None => bs58::encode(&public_key).into_string(),
+        };
+        let proof = AuthenticationProof {
+            wallet_address: wallet_address.clone(),
+            signature,
+            public_key,
+        };
+
+        if !auth
+            .verify_handshake(challenge_key, proof, role, &peer_nonce, &local_key)
+            .await?
+        {
+            return Err(ProximityError::AuthenticationFailed(format!(
+                "Peer could not prove ownership of wallet {}",
+                wallet_address
+            )));
+        }
+
+        Ok(wallet_address)
+    }
+
+    async fn next_handshake_message(framed: &mut FramedStream) -> Result<PeerMessage> {
+        framed.next().await.unwrap_or_else(|| {
+            Err(ProximityError::ConnectionFailed(
+                "Connection closed during handshake".to_string(),
+            ))
+        })
+    }
+
+    /// Forward decoded frames to the connection until the socket closes
+    fn spawn_reader(
+        peer_id: PeerId,
+        mut reader: SplitStream<FramedStream>,
+        tx: mpsc::Sender<Result<PeerMessage>>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(frame) = reader.next().await {
+                let failed = frame.is_err();
+                if tx.send(frame).await.is_err() || failed {
+                    break;
+                }
+            }
+            debug!("TCP reader for peer {} finished", peer_id);
+        })
+    }
+
+    pub struct NativeSocketConnectionFactory {
+        identity: Option<Arc<Keypair>>,
+        resolver: Option<Arc<dyn PeerAddressResolver>>,
+        auth: Arc<AuthenticationService>,
+        config: NativeTransportConfig,
+    }
+
     impl NativeSocketConnectionFactory {
         pub fn new() -> Self {
-            Self
+            Self {
+                identity: None,
+                resolver: None,
+                auth: Arc::new(AuthenticationService::new()),
+                config: NativeTransportConfig::default(),
+            }
+        }
+
+        /// Set the wallet keypair used to authenticate outbound connections
+        pub fn with_identity(mut self, identity: Arc<Keypair>) -> Self {
+            self.identity = Some(identity);
+            self
+        }
+
+        /// Set the resolver used to find peers (typically `DiscoveryService`)
+        pub fn with_resolver(mut self, resolver: Arc<dyn PeerAddressResolver>) -> Self {
+            self.resolver = Some(resolver);
+            self
+        }
+
+        pub fn with_authentication(mut self, auth: Arc<AuthenticationService>) -> Self {
+            self.auth = auth;
+            self
+        }
+
+        pub fn with_config(mut self, config: NativeTransportConfig) -> Self {
+            self.config = config;
+            self
         }
     }
-    
+
+    impl Default for NativeSocketConnectionFactory {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     #[async_trait]
     impl PlatformConnectionFactory for NativeSocketConnectionFactory {
         async fn create_connection(&self, peer_id: PeerId) -> Result<Box<dyn PlatformConnection>> {
-            Ok(Box::new(NativeSocketConnection::new(peer_id)))
+            let mut connection = NativeSocketConnection::new(peer_id)
+                .with_authentication(Arc::clone(&self.auth))
+                .with_config(self.config.clone());
+            if let Some(identity) = &self.identity {
+                connection = connection.with_identity(Arc::clone(identity));
+            }
+            if let Some(resolver) = &self.resolver {
+                connection = connection.with_resolver(Arc::clone(resolver));
+            }
+            Ok(Box::new(connection))
         }
-        
+
         fn platform_name(&self) -> &str {
             "Native TCP Socket"
         }
@@ -295,17 +764,56 @@ mod tests {
     #[cfg(not(target_arch = "wasm32"))]
     #[tokio::test]
     async fn test_native_connection_lifecycle() {
+        use solana_sdk::signature::{Keypair, Signer};
+        use std::sync::Arc;
+
+        let server_identity = Arc::new(Keypair::new());
+        let listener = native::NativeSocketListener::bind(
+            "127.0.0.1:0".parse().unwrap(),
+            Arc::clone(&server_identity),
+        )
+        .await
+        .unwrap();
+
         let peer_id: PeerId = "test-peer".to_string();
-        let mut connection = native::NativeSocketConnection::new(peer_id.clone());
+        let resolver = Arc::new(StaticPeerResolver::new());
+        resolver
+            .insert(
+                peer_id.clone(),
+                ResolvedPeer {
+                    addr: listener.local_addr().unwrap(),
+                    wallet_address: Some(server_identity.pubkey().to_string()),
+                },
+            )
+            .await;
+
+        let mut connection = native::NativeSocketConnection::new(peer_id.clone())
+            .with_identity(Arc::new(Keypair::new()))
+            .with_resolver(resolver);
         
         assert!(!connection.is_connected());
         assert_eq!(connection.peer_id(), &peer_id);
         
         // Connect
-        connection.connect(&peer_id).await.unwrap();
+        let (connected, accepted) = tokio::join!(connection.connect(&peer_id), listener.accept());
+        connected.unwrap();
+        let _accepted = accepted.unwrap();
+        assert!(connection.is_connected());
         
         // Close
         connection.close().await.unwrap();
         assert!(!connection.is_connected());
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_native_connect_requires_resolver() {
+        let peer_id: PeerId = "test-peer".to_string();
+        let mut connection = native::NativeSocketConnection::new(peer_id.clone())
+            .with_identity(std::sync::Arc::new(solana_sdk::signature::Keypair::new()));
+
+        let result = connection.connect(&peer_id).await;
+        assert!(matches!(result, Err(ProximityError::ConnectionFailed(_))));
+        assert!(!connection.is_connected());
+    }
 }
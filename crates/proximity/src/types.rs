@@ -107,8 +107,8 @@ pub struct ConnectionQuality {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum PeerMessage {
-    Challenge { nonce: Vec<u8> },
-    ChallengeResponse { signature: Vec<u8>, public_key: Vec<u8> },
+    Challenge { nonce: Vec<u8>, public_key: Vec<u8> },
+    ChallengeResponse { signature: Vec<u8> },
     TransferRequest { request: TransferRequest },
     TransferAccepted { request_id: Uuid },
     TransferRejected { request_id: Uuid, reason: Option<String> },
@@ -123,6 +123,12 @@ pub enum PeerMessage {
         timestamp: DateTime<Utc>,
         prices: serde_json::Value, // HashMap<String, PriceData> serialized
         ttl: u32,
+        /// Base58 Ed25519 key of the originating provider
+        #[serde(default)]
+        signer: Option<String>,
+        /// Provider signature over every field except `ttl`
+        #[serde(default)]
+        signature: Option<String>,
     },
     NetworkStatus {
         node_id: Uuid,
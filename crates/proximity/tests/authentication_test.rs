@@ -1,5 +1,8 @@
 use chrono::Utc;
-use proximity::{AuthenticationProof, AuthenticationService, ProximityError};
+use proximity::{
+    handshake_transcript, AuthenticationProof, AuthenticationService, HandshakeRole, ProximityError,
+};
+use solana_sdk::signature::{Keypair, Signer};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -179,3 +182,82 @@ async fn test_different_peers_have_independent_rate_limits() {
     // Should fail for other reasons, not rate limit
     assert!(!matches!(result2, Err(ProximityError::RateLimitExceeded)));
 }
+
+#[tokio::test]
+async fn test_verify_handshake_requires_signed_transcript() {
+    let service = AuthenticationService::new();
+    let peer_id = Uuid::new_v4().to_string();
+    let local_key = Keypair::new().pubkey().to_bytes();
+    let peer = Keypair::new();
+    let peer_key = peer.pubkey().to_bytes().to_vec();
+    let peer_nonce = [3u8; 32];
+
+    let proof = |signature: &[u8]| AuthenticationProof {
+        wallet_address: peer.pubkey().to_string(),
+        signature: signature.to_vec(),
+        public_key: peer_key.clone(),
+    };
+
+    // A signature over the bare nonce, as a relayed response would carry
+    let challenge = service.create_challenge(peer_id.clone()).await.unwrap();
+    let bare = peer.sign_message(&challenge.nonce);
+    let verified = service
+        .verify_handshake(
+            peer_id.clone(),
+            proof(bare.as_ref()),
+            HandshakeRole::Initiator,
+            &peer_nonce,
+            &local_key,
+        )
+        .await
+        .unwrap();
+    assert!(!verified);
+
+    // The peer's transcript puts our nonce and key on the initiator side
+    let challenge = service.create_challenge(peer_id.clone()).await.unwrap();
+    let transcript = handshake_transcript(
+        HandshakeRole::Responder,
+        &peer_nonce,
+        &challenge.nonce,
+        &peer_key,
+        &local_key,
+    );
+    assert_eq!(
+        transcript,
+        handshake_transcript(
+            HandshakeRole::Initiator,
+            &challenge.nonce,
+            &peer_nonce,
+            &local_key,
+            &peer_key,
+        )
+    );
+    let signature = peer.sign_message(&transcript);
+
+    // Relayed into another handshake, the same signature does not verify
+    let other_id = Uuid::new_v4().to_string();
+    service.create_challenge(other_id.clone()).await.unwrap();
+    let verified = service
+        .verify_handshake(
+            other_id,
+            proof(signature.as_ref()),
+            HandshakeRole::Initiator,
+            &[4u8; 32],
+            &local_key,
+        )
+        .await
+        .unwrap();
+    assert!(!verified);
+
+    let verified = service
+        .verify_handshake(
+            peer_id,
+            proof(signature.as_ref()),
+            HandshakeRole::Initiator,
+            &peer_nonce,
+            &local_key,
+        )
+        .await
+        .unwrap();
+    assert!(verified);
+}
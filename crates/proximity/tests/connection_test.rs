@@ -283,10 +283,10 @@ async fn test_send_all_message_types() {
         PeerMessage::Pong,
         PeerMessage::Challenge {
             nonce: vec![1, 2, 3, 4],
+            public_key: vec![9, 10, 11, 12],
         },
         PeerMessage::ChallengeResponse {
             signature: vec![5, 6, 7, 8],
-            public_key: vec![9, 10, 11, 12],
         },
     ];
 
@@ -0,0 +1,208 @@
+// Integration tests for the native TCP transport over localhost
+
+use futures::StreamExt;
+use proximity::platform::native::{NativeSocketConnection, NativeSocketListener};
+use proximity::{
+    PeerConnectionManager, PeerMessage, PlatformConnection, ProximityError, ResolvedPeer,
+    StaticPeerResolver,
+};
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+async fn bind_listener(identity: &Arc<Keypair>) -> NativeSocketListener {
+    NativeSocketListener::bind("127.0.0.1:0".parse().unwrap(), Arc::clone(identity))
+        .await
+        .unwrap()
+}
+
+async fn resolver_for(
+    peer_id: &str,
+    listener: &NativeSocketListener,
+    wallet_address: Option<String>,
+) -> Arc<StaticPeerResolver> {
+    let resolver = Arc::new(StaticPeerResolver::new());
+    resolver
+        .insert(
+            peer_id.to_string(),
+            ResolvedPeer {
+                addr: listener.local_addr().unwrap(),
+                wallet_address,
+            },
+        )
+        .await;
+    resolver
+}
+
+#[tokio::test]
+async fn test_handshake_and_bidirectional_messages() {
+    let server_identity = Arc::new(Keypair::new());
+    let client_identity = Arc::new(Keypair::new());
+    let listener = bind_listener(&server_identity).await;
+    let peer_id = format!("peer_{}", Uuid::new_v4());
+    let resolver = resolver_for(&peer_id, &listener, Some(server_identity.pubkey().to_string())).await;
+
+    let mut client = NativeSocketConnection::new(peer_id.clone())
+        .with_identity(Arc::clone(&client_identity))
+        .with_resolver(resolver);
+
+    let (connected, accepted) = tokio::join!(client.connect(&peer_id), listener.accept());
+    connected.unwrap();
+    let mut server = accepted.unwrap();
+
+    // Each side knows which wallet the other proved
+    let client_wallet = client_identity.pubkey().to_string();
+    let server_wallet = server_identity.pubkey().to_string();
+    assert_eq!(client.remote_wallet(), Some(server_wallet.as_str()));
+    assert_eq!(server.remote_wallet(), Some(client_wallet.as_str()));
+    assert_eq!(server.peer_id(), &client_wallet);
+
+    // Nothing has been sent yet, so receive must not block
+    assert!(client.receive().await.unwrap().is_none());
+
+    client.send(&PeerMessage::Ping).await.unwrap();
+    let request_id = Uuid::new_v4();
+    client
+        .send(&PeerMessage::TransferAccepted { request_id })
+        .await
+        .unwrap();
+
+    let first = tokio::time::timeout(Duration::from_secs(5), server.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(matches!(first, PeerMessage::Ping));
+    match tokio::time::timeout(Duration::from_secs(5), server.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+    {
+        PeerMessage::TransferAccepted { request_id: received } => assert_eq!(received, request_id),
+        other => panic!("Unexpected message: {:?}", other),
+    }
+
+    server.send(&PeerMessage::Pong).await.unwrap();
+    let reply = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(matches!(reply, PeerMessage::Pong));
+
+    // Closing one side ends the other side's stream
+    client.close().await.unwrap();
+    let end = tokio::time::timeout(Duration::from_secs(5), server.next())
+        .await
+        .unwrap();
+    assert!(end.is_none());
+    assert!(!server.is_connected());
+}
+
+#[tokio::test]
+async fn test_handshake_rejects_unexpected_wallet() {
+    let server_identity = Arc::new(Keypair::new());
+    let impostor_wallet = Keypair::new().pubkey().to_string();
+    let listener = bind_listener(&server_identity).await;
+    let peer_id = format!("peer_{}", Uuid::new_v4());
+    let resolver = resolver_for(&peer_id, &listener, Some(impostor_wallet)).await;
+
+    let mut client = NativeSocketConnection::new(peer_id.clone())
+        .with_identity(Arc::new(Keypair::new()))
+        .with_resolver(resolver);
+
+    let (connected, _accepted) = tokio::join!(client.connect(&peer_id), listener.accept());
+    assert!(matches!(
+        connected,
+        Err(ProximityError::AuthenticationFailed(_))
+    ));
+    assert!(!client.is_connected());
+}
+
+#[tokio::test]
+async fn test_connect_to_unknown_peer_fails() {
+    let peer_id = format!("peer_{}", Uuid::new_v4());
+    let mut client = NativeSocketConnection::new(peer_id.clone())
+        .with_identity(Arc::new(Keypair::new()))
+        .with_resolver(Arc::new(StaticPeerResolver::new()));
+
+    let result = client.connect(&peer_id).await;
+    assert!(matches!(result, Err(ProximityError::PeerNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_connection_manager_routes_live_messages() {
+    let server_identity = Arc::new(Keypair::new());
+    let listener = bind_listener(&server_identity).await;
+    let peer_id = format!("peer_{}", Uuid::new_v4());
+    let resolver = resolver_for(&peer_id, &listener, Some(server_identity.pubkey().to_string())).await;
+
+    let factory = proximity::platform::native::NativeSocketConnectionFactory::new()
+        .with_identity(Arc::new(Keypair::new()))
+        .with_resolver(resolver);
+    let client_manager = PeerConnectionManager::new().with_connection_factory(Arc::new(factory));
+    let server_manager = PeerConnectionManager::new();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    server_manager
+        .set_message_handler(move |peer_id, message| {
+            let tx = tx.clone();
+            async move {
+                tx.send((peer_id, message)).unwrap();
+                Ok(())
+            }
+        })
+        .await;
+
+    let (connected, accepted) = tokio::join!(
+        client_manager.establish_connection(peer_id.clone()),
+        listener.accept()
+    );
+    connected.unwrap();
+    let inbound = server_manager
+        .attach_connection(Box::new(accepted.unwrap()))
+        .await
+        .unwrap();
+
+    let request_id = Uuid::new_v4();
+    client_manager
+        .send_message(
+            peer_id.clone(),
+            PeerMessage::TransferRejected {
+                request_id,
+                reason: Some("busy".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+    let (from, message) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(from, inbound.peer_id);
+    assert!(matches!(
+        message,
+        PeerMessage::TransferRejected { request_id: received, .. } if received == request_id
+    ));
+
+    // Pings are answered by the remote receive loop
+    let quality = client_manager
+        .measure_quality_with_ping(peer_id.clone())
+        .await
+        .unwrap();
+    assert_eq!(quality.packet_loss_percent, 0.0);
+
+    // Closing the client tears down the server side as well
+    client_manager.close_connection(peer_id).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while server_manager.has_connection(&inbound.peer_id).await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -6,6 +6,7 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub solana: SolanaConfig,
+    pub chains: ChainsConfig,
     pub claude: ClaudeConfig,
     pub stripe: StripeConfig,
     pub jwt: JwtConfig,
@@ -15,6 +16,8 @@ pub struct Config {
     pub birdeye: BirdeyeConfig,
     pub sideshift: SideShiftConfig,
     pub mesh_network: MeshNetworkConfig,
+    pub price_feed: PriceFeedConfig,
+    pub notifications: NotificationConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -36,6 +39,13 @@ pub struct SolanaConfig {
     pub network: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainsConfig {
+    /// JSON file of chains that override or extend the built-in Solana,
+    /// Ethereum, BSC and Polygon entries (unset: built-in chains only)
+    pub config_path: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ClaudeConfig {
     pub api_key: String,
@@ -108,6 +118,62 @@ pub struct MeshNetworkConfig {
     pub offline_indicator_threshold_secs: u64,
     /// Price discrepancy threshold as percentage (default: 5.0 = 5%)
     pub price_discrepancy_threshold_percent: f64,
+    /// Age in seconds after which a provider's quote stops counting toward
+    /// the consensus price (default: 120)
+    pub price_consensus_window_secs: u64,
+    /// Distinct providers that must agree before a price is accepted (default: 3)
+    pub price_consensus_min_providers: usize,
+    /// Base58 Ed25519 keys of providers whose updates are accepted
+    pub trusted_provider_keys: Vec<String>,
+    /// Accept any provider and pin its key on first use instead of trusting
+    /// only `trusted_provider_keys` (default: false)
+    pub trust_providers_on_first_use: bool,
+    /// Keypair file this node signs its price updates with when acting as a
+    /// provider (default: ephemeral key generated at startup)
+    pub provider_keypair_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceFeedConfig {
+    /// Price sources in priority order: any of "birdeye", "coinmarketcap"
+    /// and "mesh" (default: birdeye,coinmarketcap,mesh)
+    pub sources: Vec<String>,
+    /// Oldest Birdeye quote accepted, in seconds (default: 60)
+    pub birdeye_max_age_secs: u64,
+    /// Oldest CoinMarketCap quote accepted, in seconds (default: 300)
+    pub coinmarketcap_max_age_secs: u64,
+    /// Oldest mesh consensus price accepted, in seconds (default: 300)
+    pub mesh_max_age_secs: u64,
+    /// How long resolved prices are cached in Redis, in seconds (default: 30)
+    pub cache_ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// 465 connects with implicit TLS, anything else upgrades with STARTTLS
+    /// (default: 587)
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub from_email: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationConfig {
+    /// SMTP relay for email delivery (unset: email is not delivered)
+    pub smtp: Option<SmtpConfig>,
+    /// Chat bot deliveries go through: "telegram" or "discord"
+    /// (unset: bot messages are not delivered)
+    pub bot_provider: Option<String>,
+    /// Bot token, required for Telegram
+    pub bot_token: Option<String>,
+    /// Delivery attempts before a notification is dead-lettered (default: 5)
+    pub max_delivery_attempts: u32,
+    /// Delay before the first retry, doubled on each later one (default: 30)
+    pub retry_backoff_secs: u64,
+    /// How often failed deliveries are checked for retry, in seconds (default: 15)
+    pub retry_interval_secs: u64,
 }
 
 impl Config {
@@ -132,6 +198,9 @@ impl Config {
                 rpc_fallback_url: env::var("SOLANA_RPC_FALLBACK_URL")?,
                 network: env::var("SOLANA_NETWORK")?,
             },
+            chains: ChainsConfig {
+                config_path: env::var("CHAINS_CONFIG_PATH").ok(),
+            },
             claude: ClaudeConfig {
                 api_key: env::var("CLAUDE_API_KEY")?,
                 model: env::var("CLAUDE_MODEL")?,
@@ -214,6 +283,69 @@ impl Config {
                 price_discrepancy_threshold_percent: env::var("MESH_PRICE_DISCREPANCY_THRESHOLD_PERCENT")
                     .unwrap_or_else(|_| "5.0".to_string())
                     .parse()?,
+                price_consensus_window_secs: env::var("MESH_PRICE_CONSENSUS_WINDOW_SECS")
+                    .unwrap_or_else(|_| "120".to_string())
+                    .parse()?,
+                price_consensus_min_providers: env::var("MESH_PRICE_CONSENSUS_MIN_PROVIDERS")
+                    .unwrap_or_else(|_| "3".to_string())
+                    .parse()?,
+                trusted_provider_keys: env::var("MESH_TRUSTED_PROVIDER_KEYS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|key| key.trim().to_string())
+                    .filter(|key| !key.is_empty())
+                    .collect(),
+                trust_providers_on_first_use: env::var("MESH_TRUST_PROVIDERS_ON_FIRST_USE")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()?,
+                provider_keypair_path: env::var("MESH_PROVIDER_KEYPAIR_PATH").ok(),
+            },
+            price_feed: PriceFeedConfig {
+                sources: env::var("PRICE_FEED_SOURCES")
+                    .unwrap_or_else(|_| "birdeye,coinmarketcap,mesh".to_string())
+                    .split(',')
+                    .map(|source| source.trim().to_lowercase())
+                    .filter(|source| !source.is_empty())
+                    .collect(),
+                birdeye_max_age_secs: env::var("PRICE_FEED_BIRDEYE_MAX_AGE_SECS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()?,
+                coinmarketcap_max_age_secs: env::var("PRICE_FEED_COINMARKETCAP_MAX_AGE_SECS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()?,
+                mesh_max_age_secs: env::var("PRICE_FEED_MESH_MAX_AGE_SECS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()?,
+                cache_ttl_secs: env::var("PRICE_FEED_CACHE_TTL_SECS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()?,
+            },
+            notifications: NotificationConfig {
+                smtp: match env::var("SMTP_HOST") {
+                    Ok(host) => Some(SmtpConfig {
+                        host,
+                        port: env::var("SMTP_PORT")
+                            .unwrap_or_else(|_| "587".to_string())
+                            .parse()?,
+                        username: env::var("SMTP_USERNAME")?,
+                        password: env::var("SMTP_PASSWORD")?,
+                        from_email: env::var("SMTP_FROM_EMAIL")?,
+                    }),
+                    Err(_) => None,
+                },
+                bot_provider: env::var("NOTIFICATION_BOT_PROVIDER")
+                    .ok()
+                    .map(|provider| provider.trim().to_lowercase()),
+                bot_token: env::var("NOTIFICATION_BOT_TOKEN").ok(),
+                max_delivery_attempts: env::var("NOTIFICATION_MAX_DELIVERY_ATTEMPTS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()?,
+                retry_backoff_secs: env::var("NOTIFICATION_RETRY_BACKOFF_SECS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()?,
+                retry_interval_secs: env::var("NOTIFICATION_RETRY_INTERVAL_SECS")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()?,
             },
         })
     }
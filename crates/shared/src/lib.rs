@@ -4,4 +4,4 @@ pub mod config;
 pub mod price_feed;
 
 pub use error::{Error, Result};
-pub use price_feed::PriceFeedService;
+pub use price_feed::{PriceFeedService, PriceSource, StaticPriceSource, TokenPrice};
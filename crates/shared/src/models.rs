@@ -140,6 +140,20 @@ pub struct NotificationPreferences {
     pub frequency: String,
     pub minimum_movement_percent: f64,
     pub minimum_confidence: i32,
+    #[serde(default)]
+    pub webhook_enabled: bool,
+    /// URL signed notification payloads are POSTed to
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Key this user's webhook payloads are signed with; generated by the
+    /// server whenever the webhook URL changes
+    #[serde(default, skip_deserializing)]
+    pub webhook_secret: Option<String>,
+    #[serde(default)]
+    pub bot_enabled: bool,
+    /// Telegram chat ID, or Discord webhook URL
+    #[serde(default)]
+    pub bot_destination: Option<String>,
 }
 
 // Subscription models
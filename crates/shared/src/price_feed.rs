@@ -1,17 +1,31 @@
-use crate::Result;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, warn};
 
-/// Price feed service for fetching USD values of tokens
-/// 
-/// This is a simplified implementation that can be extended to use
-/// real price APIs like CoinGecko, Jupiter, or Pyth Network
-#[derive(Clone)]
-pub struct PriceFeedService {
-    // In a real implementation, this would connect to a price API
-    // For now, we'll use a simple in-memory cache with mock prices
-    mock_prices: HashMap<String, f64>,
+/// Wrapped SOL mint
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// USDC mint
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+/// USDT mint
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// Default lifetime of a quote in the Redis cache
+const DEFAULT_CACHE_TTL_SECS: u64 = 30;
+
+/// Ticker symbol of a well-known mint, for sources that quote by symbol
+pub fn known_symbol(token_mint: &str) -> Option<&'static str> {
+    match token_mint {
+        SOL_MINT => Some("SOL"),
+        USDC_MINT => Some("USDC"),
+        USDT_MINT => Some("USDT"),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,54 +34,152 @@ pub struct TokenPrice {
     pub symbol: String,
     pub price_usd: f64,
     pub last_updated: chrono::DateTime<chrono::Utc>,
+    /// Name of the price source that produced this quote
+    #[serde(default)]
+    pub source: String,
+}
+
+/// A backend that quotes USD prices for tokens
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Short name used in logs and recorded on quotes
+    fn name(&self) -> &str;
+
+    /// Quote `token_mint`, or `Ok(None)` if this source does not price it
+    async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>>;
+}
+
+/// A price source together with the oldest quote it may serve
+#[derive(Clone)]
+struct ChainedSource {
+    source: Arc<dyn PriceSource>,
+    max_age: chrono::Duration,
+}
+
+/// Price feed service for fetching USD values of tokens
+///
+/// Sources are tried in the order they were added. A source's quote is used
+/// only if it is positive and no older than that source's staleness limit;
+/// otherwise the next source is tried. Accepted quotes are cached in Redis
+/// when a connection is configured.
+///
+/// ## Example
+/// ```rust,ignore
+/// let price_feed = PriceFeedService::new()
+///     .with_source(birdeye, Duration::from_secs(60))
+///     .with_source(coinmarketcap, Duration::from_secs(300))
+///     .with_redis_cache(redis, 30);
+/// let sol_usd = price_feed.get_token_price(SOL_MINT).await?;
+/// ```
+#[derive(Clone)]
+pub struct PriceFeedService {
+    sources: Vec<ChainedSource>,
+    redis: Option<redis::aio::ConnectionManager>,
+    cache_ttl_secs: u64,
 }
 
 impl PriceFeedService {
-    /// Create a new price feed service
+    /// Create a price feed with no sources
+    ///
+    /// Every lookup fails until sources are added with `with_source`.
     pub fn new() -> Self {
-        let mut mock_prices = HashMap::new();
-        
-        // Add some common Solana token prices (mock data)
-        // In production, these would come from a real price API
-        mock_prices.insert(
-            "So11111111111111111111111111111111111111112".to_string(), // SOL
-            100.0,
-        );
-        mock_prices.insert(
-            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(), // USDC
-            1.0,
-        );
-        mock_prices.insert(
-            "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(), // USDT
-            1.0,
-        );
-        
-        Self { mock_prices }
-    }
-    
+        Self {
+            sources: Vec::new(),
+            redis: None,
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+        }
+    }
+
+    /// Append a source to the fallback chain
+    ///
+    /// Quotes whose `last_updated` is older than `max_age` are ignored.
+    pub fn with_source(mut self, source: Arc<dyn PriceSource>, max_age: Duration) -> Self {
+        let max_age = chrono::Duration::from_std(max_age)
+            .unwrap_or_else(|_| chrono::Duration::days(365));
+        self.sources.push(ChainedSource { source, max_age });
+        self
+    }
+
+    /// Cache accepted quotes in Redis for `ttl_secs`
+    pub fn with_redis_cache(mut self, redis: redis::aio::ConnectionManager, ttl_secs: u64) -> Self {
+        self.redis = Some(redis);
+        self.cache_ttl_secs = ttl_secs;
+        self
+    }
+
+    /// Names of the configured sources in priority order
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|chained| chained.source.name()).collect()
+    }
+
+    /// Get the freshest acceptable quote for a token
+    ///
+    /// Returns `Error::ExternalService` when no source has a fresh price.
+    pub async fn get_token_quote(&self, token_mint: &str) -> Result<TokenPrice> {
+        if let Some(cached) = self.get_cached(token_mint).await {
+            debug!("Cache hit for price of {} (source: {})", token_mint, cached.source);
+            return Ok(cached);
+        }
+
+        let now = Utc::now();
+        for chained in &self.sources {
+            let name = chained.source.name();
+            match chained.source.fetch_price(token_mint).await {
+                Ok(Some(quote)) => {
+                    if !quote.price_usd.is_finite() || quote.price_usd <= 0.0 {
+                        warn!("Price source {} returned invalid price {} for {}", name, quote.price_usd, token_mint);
+                        continue;
+                    }
+                    if now - quote.last_updated > chained.max_age {
+                        debug!(
+                            "Skipping stale quote for {} from {} (last updated {})",
+                            token_mint,
+                            name,
+                            quote.last_updated.to_rfc3339()
+                        );
+                        continue;
+                    }
+
+                    let quote = TokenPrice {
+                        source: name.to_string(),
+                        ..quote
+                    };
+                    self.set_cached(&quote).await;
+                    return Ok(quote);
+                }
+                Ok(None) => {
+                    debug!("Price source {} does not price {}", name, token_mint);
+                }
+                Err(e) => {
+                    warn!("Price source {} failed for {}: {}", name, token_mint, e);
+                }
+            }
+        }
+
+        Err(Error::ExternalService(format!(
+            "No fresh price for {} from any source",
+            token_mint
+        )))
+    }
+
     /// Get the USD price for a token by its mint address
-    /// 
+    ///
     /// **Validates: Requirements 1.4, 1.5**
     pub async fn get_token_price(&self, token_mint: &str) -> Result<f64> {
         debug!("Fetching price for token: {}", token_mint);
-        
-        // Check mock prices first
-        if let Some(&price) = self.mock_prices.get(token_mint) {
-            return Ok(price);
-        }
-        
-        // In a real implementation, this would call an external API
-        // For now, return a default price for unknown tokens
-        warn!("No price found for token {}, using default", token_mint);
-        Ok(0.0)
+        self.get_token_quote(token_mint)
+            .await
+            .map(|quote| quote.price_usd)
     }
-    
+
     /// Get prices for multiple tokens at once
-    /// 
+    ///
+    /// Tokens without a price are reported as 0.0.
+    ///
     /// **Validates: Requirements 1.4, 1.5**
     pub async fn get_token_prices(&self, token_mints: &[String]) -> Result<HashMap<String, f64>> {
         let mut prices = HashMap::new();
-        
+
         for mint in token_mints {
             match self.get_token_price(mint).await {
                 Ok(price) => {
@@ -79,17 +191,54 @@ impl PriceFeedService {
                 }
             }
         }
-        
+
         Ok(prices)
     }
-    
+
     /// Calculate USD value for a token amount
-    /// 
+    ///
     /// **Validates: Requirements 1.4, 1.5**
     pub async fn calculate_usd_value(&self, token_mint: &str, amount: f64) -> Result<f64> {
         let price = self.get_token_price(token_mint).await?;
         Ok(amount * price)
     }
+
+    fn cache_key(token_mint: &str) -> String {
+        format!("price_feed:{}", token_mint)
+    }
+
+    async fn get_cached(&self, token_mint: &str) -> Option<TokenPrice> {
+        let mut conn = self.redis.clone()?;
+        match conn.get::<_, Option<String>>(Self::cache_key(token_mint)).await {
+            Ok(Some(json)) => serde_json::from_str(&json)
+                .map_err(|e| warn!("Failed to deserialize cached price for {}: {}", token_mint, e))
+                .ok(),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Redis error reading price for {}: {}", token_mint, e);
+                None
+            }
+        }
+    }
+
+    async fn set_cached(&self, quote: &TokenPrice) {
+        let Some(mut conn) = self.redis.clone() else {
+            return;
+        };
+        let json = match serde_json::to_string(quote) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize price for {}: {}", quote.token_mint, e);
+                return;
+            }
+        };
+        if let Err(e) = conn
+            .set_ex::<_, _, ()>(Self::cache_key(&quote.token_mint), json, self.cache_ttl_secs)
+            .await
+        {
+            warn!("Redis error caching price for {}: {}", quote.token_mint, e);
+        }
+    }
 }
 
 impl Default for PriceFeedService {
@@ -98,49 +247,181 @@ impl Default for PriceFeedService {
     }
 }
 
+/// Fixed prices, for pegged assets or as a last resort in the chain
+pub struct StaticPriceSource {
+    name: String,
+    prices: HashMap<String, f64>,
+}
+
+impl StaticPriceSource {
+    pub fn new(name: impl Into<String>, prices: HashMap<String, f64>) -> Self {
+        Self {
+            name: name.into(),
+            prices,
+        }
+    }
+
+    /// USDC and USDT pegged at $1
+    pub fn stablecoins() -> Self {
+        Self::new(
+            "stablecoin_peg",
+            HashMap::from([(USDC_MINT.to_string(), 1.0), (USDT_MINT.to_string(), 1.0)]),
+        )
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPriceSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>> {
+        Ok(self.prices.get(token_mint).map(|&price_usd| TokenPrice {
+            token_mint: token_mint.to_string(),
+            symbol: known_symbol(token_mint).unwrap_or_default().to_string(),
+            price_usd,
+            last_updated: Utc::now(),
+            source: self.name.clone(),
+        }))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use chrono::DateTime;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Source returning a fixed outcome and counting how often it is asked
+    struct StubSource {
+        name: &'static str,
+        outcome: Result<Option<(f64, DateTime<Utc>)>>,
+        calls: AtomicUsize,
+    }
+
+    impl StubSource {
+        fn new(name: &'static str, outcome: Result<Option<(f64, DateTime<Utc>)>>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                outcome,
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn quoting(name: &'static str, price: f64, age_secs: i64) -> Arc<Self> {
+            Self::new(name, Ok(Some((price, Utc::now() - chrono::Duration::seconds(age_secs)))))
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl PriceSource for StubSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn fetch_price(&self, token_mint: &str) -> Result<Option<TokenPrice>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match &self.outcome {
+                Ok(Some((price_usd, last_updated))) => Ok(Some(TokenPrice {
+                    token_mint: token_mint.to_string(),
+                    symbol: "SOL".to_string(),
+                    price_usd: *price_usd,
+                    last_updated: *last_updated,
+                    source: String::new(),
+                })),
+                Ok(None) => Ok(None),
+                Err(e) => Err(Error::ExternalService(e.to_string())),
+            }
+        }
+    }
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
     #[tokio::test]
-    async fn test_get_sol_price() {
-        let service = PriceFeedService::new();
-        let price = service.get_token_price("So11111111111111111111111111111111111111112").await;
-        assert!(price.is_ok());
-        assert_eq!(price.unwrap(), 100.0);
+    async fn test_highest_priority_source_wins() {
+        let primary = StubSource::quoting("primary", 150.0, 0);
+        let secondary = StubSource::quoting("secondary", 149.0, 0);
+        let service = PriceFeedService::new()
+            .with_source(primary.clone(), MINUTE)
+            .with_source(secondary.clone(), MINUTE);
+
+        let quote = service.get_token_quote(SOL_MINT).await.unwrap();
+
+        assert_eq!(quote.price_usd, 150.0);
+        assert_eq!(quote.source, "primary");
+        assert_eq!(secondary.calls(), 0);
+        assert_eq!(service.source_names(), vec!["primary", "secondary"]);
     }
-    
+
     #[tokio::test]
-    async fn test_get_unknown_token_price() {
-        let service = PriceFeedService::new();
-        let price = service.get_token_price("UnknownToken123").await;
-        assert!(price.is_ok());
-        assert_eq!(price.unwrap(), 0.0);
+    async fn test_falls_back_on_error_and_missing_price() {
+        let failing = StubSource::new("failing", Err(Error::ExternalService("HTTP 500".to_string())));
+        let unpriced = StubSource::new("unpriced", Ok(None));
+        let fallback = StubSource::quoting("fallback", 148.5, 0);
+        let service = PriceFeedService::new()
+            .with_source(failing.clone(), MINUTE)
+            .with_source(unpriced.clone(), MINUTE)
+            .with_source(fallback, MINUTE);
+
+        let quote = service.get_token_quote(SOL_MINT).await.unwrap();
+
+        assert_eq!(quote.price_usd, 148.5);
+        assert_eq!(quote.source, "fallback");
+        assert_eq!(failing.calls(), 1);
+        assert_eq!(unpriced.calls(), 1);
     }
-    
+
+    #[tokio::test]
+    async fn test_stale_and_invalid_quotes_are_skipped() {
+        let stale = StubSource::quoting("stale", 90.0, 600);
+        let zero = StubSource::quoting("zero", 0.0, 0);
+        let fresh = StubSource::quoting("fresh", 151.0, 120);
+        let service = PriceFeedService::new()
+            .with_source(stale, MINUTE)
+            .with_source(zero, MINUTE)
+            // This source tolerates older quotes
+            .with_source(fresh, Duration::from_secs(300));
+
+        let quote = service.get_token_quote(SOL_MINT).await.unwrap();
+
+        assert_eq!(quote.price_usd, 151.0);
+        assert_eq!(quote.source, "fresh");
+    }
+
+    #[tokio::test]
+    async fn test_no_fresh_price_is_an_error() {
+        let service = PriceFeedService::new().with_source(StubSource::quoting("stale", 90.0, 600), MINUTE);
+
+        assert!(matches!(
+            service.get_token_price(SOL_MINT).await,
+            Err(Error::ExternalService(_))
+        ));
+        assert!(PriceFeedService::new().get_token_price(SOL_MINT).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_calculate_usd_value() {
-        let service = PriceFeedService::new();
-        let value = service.calculate_usd_value(
-            "So11111111111111111111111111111111111111112",
-            10.0
-        ).await;
-        assert!(value.is_ok());
+        let service = PriceFeedService::new().with_source(StubSource::quoting("primary", 100.0, 0), MINUTE);
+
+        let value = service.calculate_usd_value(SOL_MINT, 10.0).await;
         assert_eq!(value.unwrap(), 1000.0); // 10 SOL * $100
     }
-    
+
     #[tokio::test]
     async fn test_get_multiple_prices() {
-        let service = PriceFeedService::new();
-        let mints = vec![
-            "So11111111111111111111111111111111111111112".to_string(),
-            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-        ];
-        let prices = service.get_token_prices(&mints).await;
-        assert!(prices.is_ok());
-        let prices = prices.unwrap();
+        let service = PriceFeedService::new()
+            .with_source(Arc::new(StaticPriceSource::stablecoins()), MINUTE);
+        let mints = vec![USDC_MINT.to_string(), "UnknownToken123".to_string()];
+
+        let prices = service.get_token_prices(&mints).await.unwrap();
+
         assert_eq!(prices.len(), 2);
-        assert_eq!(prices.get(&mints[0]), Some(&100.0));
-        assert_eq!(prices.get(&mints[1]), Some(&1.0));
+        assert_eq!(prices.get(&mints[0]), Some(&1.0));
+        assert_eq!(prices.get(&mints[1]), Some(&0.0));
     }
 }
@@ -34,6 +34,28 @@ impl StealthCrypto {
         Ok(montgomery_point.to_bytes())
     }
 
+    /// Derive the ECDH scalar for an Ed25519 secret key
+    /// 
+    /// An Ed25519 public key is `clamp(SHA512(seed)[..32]) * G`, not `seed * G`,
+    /// so both parties must use this expanded scalar with `ecdh` for the shared
+    /// secrets computed from each side to agree.
+    /// 
+    /// # Requirements
+    /// Validates: Requirements 4.2
+    pub fn ed25519_secret_to_scalar(secret_key: &[u8; 32]) -> [u8; 32] {
+        use sha2::Sha512;
+
+        let hash = Sha512::digest(secret_key);
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&hash[..32]);
+
+        // Clamp exactly as Ed25519 key expansion does
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+        scalar
+    }
+
     /// Perform ECDH key exchange using Curve25519
     /// 
     /// Computes the shared secret between a secret key and a public key.
@@ -199,6 +221,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ecdh_symmetry_with_ed25519_keys() {
+        use ed25519_dalek::{PublicKey, SecretKey};
+
+        let secret_a = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let secret_b = SecretKey::from_bytes(&[9u8; 32]).unwrap();
+        let public_a: PublicKey = (&secret_a).into();
+        let public_b: PublicKey = (&secret_b).into();
+
+        let curve_a = StealthCrypto::ed25519_to_curve25519(&public_a.to_bytes()).unwrap();
+        let curve_b = StealthCrypto::ed25519_to_curve25519(&public_b.to_bytes()).unwrap();
+
+        let scalar_a = StealthCrypto::ed25519_secret_to_scalar(&secret_a.to_bytes());
+        let scalar_b = StealthCrypto::ed25519_secret_to_scalar(&secret_b.to_bytes());
+
+        let shared_ab = StealthCrypto::ecdh(&scalar_a, &curve_b).unwrap();
+        let shared_ba = StealthCrypto::ecdh(&scalar_b, &curve_a).unwrap();
+
+        assert_eq!(shared_ab, shared_ba, "Ed25519-derived ECDH should be symmetric");
+    }
+
     #[test]
     fn test_ecdh_produces_valid_shared_secret() {
         let secret_bytes = [42u8; 32];
@@ -99,8 +99,9 @@ impl StealthAddressGenerator {
         let viewing_public_bytes = viewing_public.to_bytes();
         let viewing_curve25519 = StealthCrypto::ed25519_to_curve25519(&viewing_public_bytes)?;
         
-        // Convert ephemeral secret key to Curve25519 for ECDH
-        let ephemeral_secret_bytes = ephemeral_kp.secret.to_bytes();
+        // Expand ephemeral secret key to the scalar behind its public key for ECDH
+        let ephemeral_secret_bytes =
+            StealthCrypto::ed25519_secret_to_scalar(&ephemeral_kp.secret.to_bytes());
         
         // Compute shared secret using ECDH (Requirement 2.3, 2.4)
         let shared_secret = StealthCrypto::ecdh(&ephemeral_secret_bytes, &viewing_curve25519)?;
@@ -199,8 +200,9 @@ impl StealthAddressGenerator {
         let viewing_public_bytes = viewing_public.to_bytes();
         let viewing_curve25519 = StealthCrypto::ed25519_to_curve25519(&viewing_public_bytes)?;
         
-        // Convert ephemeral secret key to Curve25519 for ECDH
-        let ephemeral_secret_bytes = ephemeral_kp.secret.to_bytes();
+        // Expand ephemeral secret key to the scalar behind its public key for ECDH
+        let ephemeral_secret_bytes =
+            StealthCrypto::ed25519_secret_to_scalar(&ephemeral_kp.secret.to_bytes());
         
         // Compute shared secret using ECDH (Requirement 2.3, 2.4)
         let shared_secret = StealthCrypto::ecdh(&ephemeral_secret_bytes, &viewing_curve25519)?;
@@ -5,14 +5,58 @@ use crate::error::{StealthError, StealthResult};
 use crate::keypair::StealthKeyPair;
 use curve25519_dalek::edwards::CompressedEdwardsY;
 use curve25519_dalek::scalar::Scalar;
-use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+use ed25519_dalek::{ExpandedSecretKey, Keypair, PublicKey, SecretKey};
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcBlockConfig;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use solana_sdk::signer::{Signer, SignerError};
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
 use std::sync::Arc;
 use tracing::{debug, info};
 
+/// SPL Memo program, which carries stealth payment metadata
+pub const MEMO_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Length of encoded stealth metadata: version (1) + viewing tag (4) + ephemeral key (32)
+const STEALTH_METADATA_LEN: usize = 37;
+
+/// Stealth payment metadata carried in a memo instruction
+#[derive(Debug, Clone, PartialEq)]
+pub struct StealthMetadata {
+    pub version: u8,
+    pub viewing_tag: [u8; 4],
+    pub ephemeral_public_key: Pubkey,
+}
+
+/// Parse stealth metadata written by `create_stealth_metadata_instruction`
+/// 
+/// Returns None if the data is not stealth metadata of a known version.
+pub fn parse_stealth_metadata(data: &[u8]) -> Option<StealthMetadata> {
+    if data.len() != STEALTH_METADATA_LEN {
+        return None;
+    }
+
+    let version = data[0];
+    if version != 1 && version != 2 {
+        return None;
+    }
+
+    let mut viewing_tag = [0u8; 4];
+    viewing_tag.copy_from_slice(&data[1..5]);
+
+    let mut ephemeral_pk_bytes = [0u8; 32];
+    ephemeral_pk_bytes.copy_from_slice(&data[5..37]);
+
+    Some(StealthMetadata {
+        version,
+        viewing_tag,
+        ephemeral_public_key: Pubkey::new_from_array(ephemeral_pk_bytes),
+    })
+}
+
 /// Scanner for detecting incoming stealth payments
 /// 
 /// The scanner uses the viewing key to scan the blockchain for incoming stealth payments
@@ -60,21 +104,6 @@ impl StealthScanner {
         }
     }
 
-    /// Scan blockchain for incoming stealth payments
-    /// 
-    /// This method scans the blockchain for transactions that may contain stealth payments
-    /// to this wallet. It uses viewing tag filtering to optimize performance by avoiding
-    /// expensive ECDH computations for transactions that don't match.
-    /// 
-    /// # Arguments
-    /// * `from_slot` - Starting slot for scanning (defaults to scan_index)
-    /// * `to_slot` - Ending slot for scanning (defaults to current slot)
-    /// 
-    /// # Returns
-    /// A vector of detected stealth payments
-    /// 
-    /// # Requirements
-    /// Validates: Requirements 3.1, 3.2, 3.3, 3.5, 3.6
     /// Scan blockchain for incoming stealth payments
     /// 
     /// This method scans the blockchain for transactions that may contain stealth payments
@@ -110,23 +139,67 @@ impl StealthScanner {
             start_slot, end_slot
         );
 
-        let detected_payments = Vec::new();
-
-        // Note: This is a simplified implementation that demonstrates the structure.
-        // A full implementation would:
-        // 1. Fetch blocks for each slot in the range
-        // 2. Parse transactions for stealth payment metadata
-        // 3. Extract ephemeral public keys and viewing tags
-        // 4. Use viewing tag filtering to optimize scanning
-        // 5. Verify ownership using ECDH
-        // 6. Return detected payments
-        //
-        // The actual implementation requires defining the on-chain transaction format
-        // for stealth payments, which will be done in later tasks when integrating
-        // with the blockchain client.
+        let mut detected_payments = Vec::new();
+
+        if start_slot > end_slot {
+            return Ok(detected_payments);
+        }
+
+        // List the slots that actually produced blocks, so skipped slots are
+        // not mistaken for RPC failures
+        let slots = self
+            .rpc_client
+            .get_blocks(start_slot, Some(end_slot))
+            .map_err(|e| StealthError::BlockchainError(format!("Failed to list blocks: {}", e)))?;
+
+        let block_config = RpcBlockConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            transaction_details: Some(TransactionDetails::Full),
+            rewards: Some(false),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+
+        for slot in slots {
+            // Fail without advancing the scan index so the next scan retries this block
+            let block = self
+                .rpc_client
+                .get_block_with_config(slot, block_config)
+                .map_err(|e| {
+                    StealthError::BlockchainError(format!("Failed to fetch block {}: {}", slot, e))
+                })?;
+
+            for tx in block.transactions.unwrap_or_default() {
+                let Some(meta) = tx.meta else {
+                    continue;
+                };
+
+                // Failed transactions never moved funds
+                if meta.err.is_some() {
+                    continue;
+                }
+
+                let Some(transaction) = tx.transaction.decode() else {
+                    continue;
+                };
+
+                if let Some(payment) = self.check_transaction(
+                    &transaction,
+                    &meta.pre_balances,
+                    &meta.post_balances,
+                    slot,
+                ) {
+                    debug!(
+                        "Detected stealth payment of {} lamports to {} in slot {}",
+                        payment.amount, payment.stealth_address, slot
+                    );
+                    detected_payments.push(payment);
+                }
+            }
+        }
 
         // Update scan index to avoid re-scanning
-        if end_slot > self.scan_index {
+        if end_slot >= self.scan_index {
             self.scan_index = end_slot + 1;
         }
 
@@ -137,41 +210,81 @@ impl StealthScanner {
     /// Check if a transaction contains a stealth payment for this wallet
     /// 
     /// This method implements the viewing tag filtering optimization:
-    /// 1. Extract potential ephemeral public keys from transaction
+    /// 1. Extract stealth metadata (ephemeral public key and viewing tag) from memo instructions
     /// 2. Compute viewing tag using ECDH with viewing key
     /// 3. Only perform full verification if viewing tag matches
+    /// 4. Confirm the derived stealth address received funds in this transaction
+    /// 
+    /// Version 2 (hybrid) metadata is skipped, since recovering the shared secret
+    /// also needs the Kyber ciphertext, which is not carried on-chain.
     /// 
     /// # Requirements
     /// Validates: Requirements 3.2, 3.3, 3.4
     fn check_transaction(
         &self,
-        tx: &solana_sdk::transaction::Transaction,
-        signature: Signature,
+        tx: &VersionedTransaction,
+        pre_balances: &[u64],
+        post_balances: &[u64],
         slot: u64,
     ) -> Option<DetectedPayment> {
-        // For now, we'll implement a simplified version that looks for stealth payment metadata
-        // In a full implementation, this would:
-        // 1. Parse transaction instructions for stealth payment metadata
-        // 2. Extract ephemeral public key and viewing tag
-        // 3. Verify viewing tag matches before doing full ECDH
-        // 4. Compute shared secret and verify ownership
-        
-        // This is a placeholder implementation that demonstrates the structure
-        // A real implementation would need to parse the transaction data format
-        
-        // Extract account keys from transaction
-        let account_keys = &tx.message.account_keys;
-        
-        // Look for potential stealth addresses and ephemeral keys in the transaction
-        // This would be based on the specific transaction format used for stealth payments
-        
-        // For demonstration, we'll return None (no payment detected)
-        // In a real implementation, this would:
-        // - Parse instruction data for StealthPaymentMetadata
-        // - Extract ephemeral_public_key and viewing_tag
-        // - Call check_viewing_tag() to filter
-        // - Call verify_ownership() to confirm
-        
+        let signature = *tx.signatures.first()?;
+        let account_keys = tx.message.static_account_keys();
+
+        for instruction in tx.message.instructions() {
+            let program_id = account_keys.get(instruction.program_id_index as usize)?;
+            if *program_id != MEMO_PROGRAM_ID {
+                continue;
+            }
+
+            let Some(metadata) = parse_stealth_metadata(&instruction.data) else {
+                continue;
+            };
+
+            if metadata.version != 1 {
+                debug!("Skipping version {} stealth metadata in {}", metadata.version, signature);
+                continue;
+            }
+
+            // Cheap filter first: most payments belong to someone else
+            match self.check_viewing_tag(&metadata.ephemeral_public_key, &metadata.viewing_tag) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    debug!("Invalid ephemeral key in {}: {}", signature, e);
+                    continue;
+                }
+            }
+
+            let stealth_address = match self.derive_stealth_address(&metadata.ephemeral_public_key) {
+                Ok(address) => address,
+                Err(e) => {
+                    debug!("Failed to derive stealth address for {}: {}", signature, e);
+                    continue;
+                }
+            };
+
+            // A viewing tag can collide; ownership requires the derived address
+            // to actually appear in the transaction
+            let Some(index) = account_keys.iter().position(|key| *key == stealth_address) else {
+                debug!("Viewing tag matched but stealth address absent in {}", signature);
+                continue;
+            };
+
+            let amount = match (pre_balances.get(index), post_balances.get(index)) {
+                (Some(pre), Some(post)) => post.saturating_sub(*pre),
+                _ => 0,
+            };
+
+            return Some(DetectedPayment {
+                stealth_address,
+                amount,
+                ephemeral_public_key: metadata.ephemeral_public_key,
+                viewing_tag: metadata.viewing_tag,
+                slot,
+                signature,
+            });
+        }
+
         None
     }
 
@@ -187,12 +300,7 @@ impl StealthScanner {
         ephemeral_public_key: &Pubkey,
         viewing_tag: &[u8; 4],
     ) -> StealthResult<bool> {
-        // Convert viewing keypair to Curve25519 for ECDH
-        let viewing_secret = self.viewing_keypair.secret.to_bytes();
-        let ephemeral_curve = StealthCrypto::ed25519_to_curve25519(&ephemeral_public_key.to_bytes())?;
-
-        // Compute shared secret using ECDH
-        let shared_secret = StealthCrypto::ecdh(&viewing_secret, &ephemeral_curve)?;
+        let shared_secret = self.shared_secret(ephemeral_public_key)?;
 
         // Derive viewing tag from shared secret
         let computed_tag = StealthCrypto::derive_viewing_tag(&shared_secret);
@@ -213,16 +321,25 @@ impl StealthScanner {
         ephemeral_public_key: &Pubkey,
         stealth_address: &Pubkey,
     ) -> StealthResult<bool> {
-        // Convert keys to Curve25519 for ECDH
-        let viewing_secret = self.viewing_keypair.secret.to_bytes();
+        let computed_stealth_address = self.derive_stealth_address(ephemeral_public_key)?;
+        Ok(&computed_stealth_address == stealth_address)
+    }
+
+    /// Compute the ECDH shared secret between our viewing key and an ephemeral key
+    fn shared_secret(&self, ephemeral_public_key: &Pubkey) -> StealthResult<[u8; 32]> {
+        let viewing_scalar =
+            StealthCrypto::ed25519_secret_to_scalar(&self.viewing_keypair.secret.to_bytes());
         let ephemeral_curve = StealthCrypto::ed25519_to_curve25519(&ephemeral_public_key.to_bytes())?;
 
-        // Compute shared secret
-        let shared_secret = StealthCrypto::ecdh(&viewing_secret, &ephemeral_curve)?;
+        StealthCrypto::ecdh(&viewing_scalar, &ephemeral_curve)
+    }
+
+    /// Derive the stealth address a sender would have generated for us
+    /// 
+    /// stealth_address = spending_public_key + hash(shared_secret) * G
+    fn derive_stealth_address(&self, ephemeral_public_key: &Pubkey) -> StealthResult<Pubkey> {
+        let shared_secret = self.shared_secret(ephemeral_public_key)?;
 
-        // Derive the stealth address that should have been generated
-        // stealth_address = spending_public_key + hash(shared_secret) * G
-        
         // Hash the shared secret to get a scalar
         use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
@@ -238,17 +355,8 @@ impl StealthScanner {
         // Add to spending public key using point_add
         let spending_pk_bytes = self.spending_public_key.to_bytes();
         let computed_stealth_bytes = StealthCrypto::point_add(&spending_pk_bytes, &offset_compressed)?;
-        let computed_stealth_address = Pubkey::new_from_array(computed_stealth_bytes);
-
-        #[cfg(test)]
-        {
-            println!("Scanner verify_ownership:");
-            println!("  Spending public key: {}", self.spending_public_key);
-            println!("  Computed stealth: {}", computed_stealth_address);
-            println!("  Expected stealth: {}", stealth_address);
-        }
 
-        Ok(&computed_stealth_address == stealth_address)
+        Ok(Pubkey::new_from_array(computed_stealth_bytes))
     }
 
     /// Derive private key for spending detected payment
@@ -261,7 +369,7 @@ impl StealthScanner {
     /// * `spending_secret_key` - The spending secret key (must be provided securely)
     /// 
     /// # Returns
-    /// A keypair that can spend the stealth payment
+    /// A signing key whose public key is the stealth address
     /// 
     /// # Requirements
     /// Validates: Requirements 3.4
@@ -269,37 +377,47 @@ impl StealthScanner {
         &self,
         ephemeral_public_key: &Pubkey,
         spending_secret_key: &[u8; 32],
-    ) -> StealthResult<Keypair> {
-        // Convert viewing keypair to Curve25519 for ECDH
-        let viewing_secret = self.viewing_keypair.secret.to_bytes();
-        let ephemeral_curve = StealthCrypto::ed25519_to_curve25519(&ephemeral_public_key.to_bytes())?;
-
+    ) -> StealthResult<StealthSpendingKey> {
         // Compute shared secret using viewing key
-        let shared_secret = StealthCrypto::ecdh(&viewing_secret, &ephemeral_curve)?;
+        let shared_secret = self.shared_secret(ephemeral_public_key)?;
 
         // Hash the shared secret to get a scalar
-        use sha2::{Digest, Sha256};
+        use sha2::{Digest, Sha256, Sha512};
         let mut hasher = Sha256::new();
         hasher.update(&shared_secret);
         let hash = hasher.finalize();
         let hash_scalar = Scalar::from_bytes_mod_order(hash.into());
 
-        // Derive the stealth private key: stealth_secret = spending_secret + hash(shared_secret)
-        let spending_scalar = Scalar::from_bytes_mod_order(*spending_secret_key);
+        // The spending public key is the expanded (hashed and clamped) seed times G,
+        // so the stealth scalar has to be built from that same expanded scalar:
+        // stealth_secret = expand(spending_secret) + hash(shared_secret)
+        let spending_scalar = Scalar::from_bytes_mod_order(
+            StealthCrypto::ed25519_secret_to_scalar(spending_secret_key),
+        );
         let stealth_scalar = spending_scalar + hash_scalar;
 
-        // Convert back to Ed25519 secret key
-        let stealth_secret_bytes = stealth_scalar.to_bytes();
-        let stealth_secret = SecretKey::from_bytes(&stealth_secret_bytes)
+        // The stealth scalar has no seed behind it, so the signing nonce prefix is
+        // derived from the spending key's own prefix and the shared secret
+        let expanded_spending = Sha512::digest(spending_secret_key);
+        let mut nonce_hasher = Sha512::new();
+        nonce_hasher.update(&expanded_spending[32..]);
+        nonce_hasher.update(&shared_secret);
+        let nonce = nonce_hasher.finalize();
+
+        let mut expanded_bytes = [0u8; 64];
+        expanded_bytes[..32].copy_from_slice(&stealth_scalar.to_bytes());
+        expanded_bytes[32..].copy_from_slice(&nonce[..32]);
+        let secret = ExpandedSecretKey::from_bytes(&expanded_bytes)
             .map_err(|e| StealthError::KeyDerivationFailed(format!("Failed to create stealth secret key: {}", e)))?;
 
-        // Derive public key
-        let stealth_public: PublicKey = (&stealth_secret).into();
+        // Public key is stealth_scalar * G; PublicKey::from(&ExpandedSecretKey) would
+        // clamp the scalar again and produce a different point
+        use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+        let public_bytes = (stealth_scalar * ED25519_BASEPOINT_POINT).compress().to_bytes();
+        let public = PublicKey::from_bytes(&public_bytes)
+            .map_err(|e| StealthError::KeyDerivationFailed(format!("Failed to create stealth public key: {}", e)))?;
 
-        Ok(Keypair {
-            secret: stealth_secret,
-            public: stealth_public,
-        })
+        Ok(StealthSpendingKey { secret, public })
     }
 
     /// Get the current scan index
@@ -323,6 +441,37 @@ impl StealthScanner {
     }
 }
 
+/// Signing key for a stealth address
+/// 
+/// A stealth private key is a scalar rather than an Ed25519 seed, so it cannot be
+/// held in an `ed25519_dalek::Keypair`. It signs with the expanded key directly and
+/// implements Solana's `Signer` so it can sign transactions from the stealth address.
+pub struct StealthSpendingKey {
+    secret: ExpandedSecretKey,
+    pub public: PublicKey,
+}
+
+impl StealthSpendingKey {
+    /// Sign a message with the stealth private key
+    pub fn sign(&self, message: &[u8]) -> ed25519_dalek::Signature {
+        self.secret.sign(message, &self.public)
+    }
+}
+
+impl Signer for StealthSpendingKey {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(Pubkey::new_from_array(self.public.to_bytes()))
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        Ok(Signature::from(self.sign(message).to_bytes()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
 /// A detected stealth payment
 /// 
 /// Contains all information needed to spend a detected stealth payment.
@@ -391,6 +540,37 @@ mod tests {
         assert_eq!(derived_keypair.public.to_bytes().len(), 32, "Public key should be 32 bytes");
     }
 
+    #[test]
+    fn test_derive_spending_key_controls_stealth_address() {
+        let keypair = StealthKeyPair::generate_standard().unwrap();
+        let scanner = StealthScanner::new(&keypair, "https://api.devnet.solana.com");
+
+        let output = crate::generator::StealthAddressGenerator::generate_stealth_address_uncached(
+            &keypair.to_meta_address(),
+            None,
+        )
+        .unwrap();
+
+        let spending_secret = keypair.spending_keypair().secret.to_bytes();
+        let derived = scanner
+            .derive_spending_key(&output.ephemeral_public_key, &spending_secret)
+            .unwrap();
+
+        assert_eq!(
+            Pubkey::new_from_array(derived.public.to_bytes()),
+            output.stealth_address,
+            "Derived public key should be the stealth address"
+        );
+
+        // Signatures from the derived key verify against the stealth address
+        let message = b"unshield";
+        let signature = derived.sign(message);
+        use ed25519_dalek::Verifier;
+        assert!(derived.public.verify(message, &signature).is_ok());
+        assert!(Signer::sign_message(&derived, message)
+            .verify(&output.stealth_address.to_bytes(), message));
+    }
+
     #[test]
     fn test_derive_spending_key_deterministic() {
         let keypair = StealthKeyPair::generate_standard().unwrap();
@@ -427,7 +607,7 @@ mod tests {
         let ephemeral_public = Pubkey::new_from_array(ephemeral_keypair.public.to_bytes());
         
         // Compute the expected viewing tag
-        let viewing_secret = scanner.viewing_keypair.secret.to_bytes();
+        let viewing_secret = StealthCrypto::ed25519_secret_to_scalar(&scanner.viewing_keypair.secret.to_bytes());
         let ephemeral_curve = StealthCrypto::ed25519_to_curve25519(&ephemeral_public.to_bytes()).unwrap();
         let shared_secret = StealthCrypto::ecdh(&viewing_secret, &ephemeral_curve).unwrap();
         let expected_tag = StealthCrypto::derive_viewing_tag(&shared_secret);
@@ -457,7 +637,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // TODO: Fix ECDH computation mismatch between generator and scanner
     fn test_verify_ownership() {
         use crate::generator::StealthAddressGenerator;
         use crate::crypto::StealthCrypto;
@@ -482,7 +661,7 @@ mod tests {
         println!("Ephemeral public key: {}", stealth_output.ephemeral_public_key);
         
         // Manually verify the computation matches
-        let viewing_secret = scanner.viewing_keypair.secret.to_bytes();
+        let viewing_secret = StealthCrypto::ed25519_secret_to_scalar(&scanner.viewing_keypair.secret.to_bytes());
         let ephemeral_curve = StealthCrypto::ed25519_to_curve25519(&stealth_output.ephemeral_public_key.to_bytes()).unwrap();
         let shared_secret = StealthCrypto::ecdh(&viewing_secret, &ephemeral_curve).unwrap();
         
@@ -534,4 +713,110 @@ mod tests {
         
         assert!(!is_owner, "Scanner should not verify ownership of address for different receiver");
     }
+
+    fn build_payment_transaction(
+        stealth_output: &crate::generator::StealthAddressOutput,
+        amount: u64,
+    ) -> VersionedTransaction {
+        use crate::wallet_manager::create_stealth_metadata_instruction;
+        use solana_sdk::hash::Hash;
+        use solana_sdk::signature::{Keypair as SolanaKeypair, Signer};
+        use solana_sdk::system_instruction;
+        use solana_sdk::transaction::Transaction;
+
+        let payer = SolanaKeypair::new();
+        let transfer = system_instruction::transfer(&payer.pubkey(), &stealth_output.stealth_address, amount);
+        let metadata = create_stealth_metadata_instruction(
+            &stealth_output.ephemeral_public_key,
+            &stealth_output.viewing_tag,
+            1,
+        );
+
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer, metadata],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::new_unique(),
+        );
+        VersionedTransaction::from(tx)
+    }
+
+    fn balances_for(tx: &VersionedTransaction, stealth_address: &Pubkey, amount: u64) -> (Vec<u64>, Vec<u64>) {
+        let keys = tx.message.static_account_keys();
+        let pre = vec![1_000_000_000; keys.len()];
+        let post = keys
+            .iter()
+            .zip(pre.iter())
+            .map(|(key, balance)| if key == stealth_address { balance + amount } else { *balance })
+            .collect();
+        (pre, post)
+    }
+
+    #[test]
+    fn test_parse_stealth_metadata_round_trip() {
+        use crate::wallet_manager::create_stealth_metadata_instruction;
+
+        let ephemeral_pk = Pubkey::new_unique();
+        let viewing_tag = [0x12, 0x34, 0x56, 0x78];
+        let instruction = create_stealth_metadata_instruction(&ephemeral_pk, &viewing_tag, 1);
+
+        assert_eq!(instruction.program_id, MEMO_PROGRAM_ID);
+        let parsed = parse_stealth_metadata(&instruction.data).expect("Should parse metadata");
+        assert_eq!(parsed.version, 1);
+        assert_eq!(parsed.viewing_tag, viewing_tag);
+        assert_eq!(parsed.ephemeral_public_key, ephemeral_pk);
+
+        assert!(parse_stealth_metadata(&[1, 2, 3]).is_none(), "Should reject short data");
+        let mut bad_version = instruction.data.clone();
+        bad_version[0] = 99;
+        assert!(parse_stealth_metadata(&bad_version).is_none(), "Should reject unknown version");
+    }
+
+    #[test]
+    fn test_check_transaction_detects_own_payment() {
+        use crate::generator::StealthAddressGenerator;
+
+        let receiver_keypair = StealthKeyPair::generate_standard().unwrap();
+        let scanner = StealthScanner::new(&receiver_keypair, "https://api.devnet.solana.com");
+
+        let stealth_output = StealthAddressGenerator::generate_stealth_address_uncached(
+            &receiver_keypair.to_meta_address(),
+            None,
+        )
+        .unwrap();
+
+        let tx = build_payment_transaction(&stealth_output, 250_000);
+        let (pre, post) = balances_for(&tx, &stealth_output.stealth_address, 250_000);
+
+        let detected = scanner
+            .check_transaction(&tx, &pre, &post, 42)
+            .expect("Scanner should detect a payment addressed to it");
+
+        assert_eq!(detected.stealth_address, stealth_output.stealth_address);
+        assert_eq!(detected.ephemeral_public_key, stealth_output.ephemeral_public_key);
+        assert_eq!(detected.viewing_tag, stealth_output.viewing_tag);
+        assert_eq!(detected.amount, 250_000);
+        assert_eq!(detected.slot, 42);
+        assert_eq!(detected.signature, tx.signatures[0]);
+    }
+
+    #[test]
+    fn test_check_transaction_ignores_other_receivers() {
+        use crate::generator::StealthAddressGenerator;
+
+        let receiver_keypair = StealthKeyPair::generate_standard().unwrap();
+        let other_keypair = StealthKeyPair::generate_standard().unwrap();
+        let scanner = StealthScanner::new(&receiver_keypair, "https://api.devnet.solana.com");
+
+        let stealth_output = StealthAddressGenerator::generate_stealth_address_uncached(
+            &other_keypair.to_meta_address(),
+            None,
+        )
+        .unwrap();
+
+        let tx = build_payment_transaction(&stealth_output, 250_000);
+        let (pre, post) = balances_for(&tx, &stealth_output.stealth_address, 250_000);
+
+        assert!(scanner.check_transaction(&tx, &pre, &post, 42).is_none());
+    }
 }
@@ -348,14 +348,11 @@ impl StealthWalletManager {
             transfer_amount,
         );
         
-        // Convert Ed25519 keypair to Solana Keypair for signing
-        let stealth_signer = ed25519_to_solana_keypair(&stealth_keypair)?;
-        
         // Build and sign transaction
         let transaction = Transaction::new_signed_with_payer(
             &[transfer_instruction],
             Some(&detected_payment.stealth_address),
-            &[&stealth_signer],
+            &[&stealth_keypair],
             recent_blockhash,
         );
         
@@ -394,7 +391,7 @@ pub struct PreparedPayment {
 /// * `ephemeral_public_key` - The ephemeral public key used for ECDH
 /// * `viewing_tag` - The 4-byte viewing tag for efficient scanning
 /// * `version` - Stealth address version (1 for standard, 2 for hybrid)
-fn create_stealth_metadata_instruction(
+pub(crate) fn create_stealth_metadata_instruction(
     ephemeral_public_key: &Pubkey,
     viewing_tag: &[u8; 4],
     version: u8,
@@ -416,21 +413,6 @@ fn create_stealth_metadata_instruction(
     }
 }
 
-/// Convert Ed25519 Keypair to Solana SDK Keypair
-/// 
-/// This is needed because the stealth scanner uses ed25519-dalek keypairs,
-/// but Solana transaction signing requires solana_sdk::signature::Keypair.
-fn ed25519_to_solana_keypair(ed_keypair: &ed25519_dalek::Keypair) -> StealthResult<Keypair> {
-    // Combine secret and public key bytes (Solana format: 64 bytes = 32 secret + 32 public)
-    let mut keypair_bytes = [0u8; 64];
-    keypair_bytes[..32].copy_from_slice(&ed_keypair.secret.to_bytes());
-    keypair_bytes[32..].copy_from_slice(&ed_keypair.public.to_bytes());
-    
-    Keypair::from_bytes(&keypair_bytes).map_err(|e| {
-        StealthError::KeyDerivationFailed(format!("Failed to convert Ed25519 keypair: {}", e))
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -463,66 +445,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_ed25519_to_solana_keypair_conversion() {
-        // Generate an Ed25519 keypair
-        let mut secret_bytes = [0u8; 32];
-        use rand::RngCore;
-        rand::thread_rng().fill_bytes(&mut secret_bytes);
-        
-        let ed_secret = ed25519_dalek::SecretKey::from_bytes(&secret_bytes).unwrap();
-        let ed_public: ed25519_dalek::PublicKey = (&ed_secret).into();
-        let ed_keypair = ed25519_dalek::Keypair {
-            secret: ed_secret,
-            public: ed_public,
-        };
-        
-        // Convert to Solana keypair
-        let result = ed25519_to_solana_keypair(&ed_keypair);
-        assert!(result.is_ok(), "Conversion should succeed");
-        
-        let solana_keypair = result.unwrap();
-        
-        // Verify the public keys match
-        let ed_pubkey = Pubkey::new_from_array(ed_keypair.public.to_bytes());
-        assert_eq!(
-            solana_keypair.pubkey(),
-            ed_pubkey,
-            "Public keys should match after conversion"
-        );
-    }
-
-    #[test]
-    fn test_ed25519_to_solana_keypair_deterministic() {
-        // Create the same Ed25519 keypair twice
-        let secret_bytes = [42u8; 32];
-        
-        let ed_secret1 = ed25519_dalek::SecretKey::from_bytes(&secret_bytes).unwrap();
-        let ed_public1: ed25519_dalek::PublicKey = (&ed_secret1).into();
-        let ed_keypair1 = ed25519_dalek::Keypair {
-            secret: ed_secret1,
-            public: ed_public1,
-        };
-        
-        let ed_secret2 = ed25519_dalek::SecretKey::from_bytes(&secret_bytes).unwrap();
-        let ed_public2: ed25519_dalek::PublicKey = (&ed_secret2).into();
-        let ed_keypair2 = ed25519_dalek::Keypair {
-            secret: ed_secret2,
-            public: ed_public2,
-        };
-        
-        // Convert both to Solana keypairs
-        let solana_keypair1 = ed25519_to_solana_keypair(&ed_keypair1).unwrap();
-        let solana_keypair2 = ed25519_to_solana_keypair(&ed_keypair2).unwrap();
-        
-        // They should produce the same result
-        assert_eq!(
-            solana_keypair1.pubkey(),
-            solana_keypair2.pubkey(),
-            "Same Ed25519 keypair should produce same Solana keypair"
-        );
-    }
-
     #[test]
     fn test_metadata_instruction_different_versions() {
         let ephemeral_pk = Pubkey::new_unique();
@@ -225,7 +225,7 @@ async fn test_stealth_payment_scanning_end_to_end() {
     ).expect("Failed to derive spending key");
     
     // Verify derived key can control the stealth address
-    assert_ne!(derived_keypair.public.to_bytes(), [0u8; 32]);
+    assert_eq!(derived_keypair.public.to_bytes(), stealth_output.stealth_address.to_bytes());
     
     // Verify the derived key is different from the original spending key
     assert_ne!(derived_keypair.public.to_bytes(), receiver_stealth_keypair.spending_public_key().to_bytes());
@@ -270,7 +270,7 @@ async fn test_shield_unshield_flow_end_to_end() {
     ).expect("Failed to derive key for unshield");
     
     // Verify unshield key can control the stealth address
-    assert_ne!(unshield_keypair.public.to_bytes(), [0u8; 32]);
+    assert_eq!(unshield_keypair.public.to_bytes(), shield_output.stealth_address.to_bytes());
     
     // Step 4: Verify no on-chain linkage
     // The derived key should be unique and not linkable to original keys
@@ -1,4 +1,5 @@
 use shared::models::{Recommendation, Subscription, TradeExecution, UserSettings};
+use solana_sdk::signature::Signer;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -6,12 +7,17 @@ use tracing::{info, warn};
 use uuid::Uuid;
 
 pub mod error;
+pub mod quote;
 pub mod validation;
 pub mod transaction;
 
 pub use error::{TradingError, Result};
+pub use quote::{JupiterQuoteSource, QuoteRequest, SwapInstructions, SwapMode, SwapQuote, SwapQuoteSource};
 pub use validation::{TradeValidator, ValidationResult};
-pub use transaction::{TransactionBuilder, SolanaTransaction, TransactionStatus};
+pub use transaction::{
+    SolanaTransaction, StaticKeypairSigner, TradeSigner, TransactionBuilder,
+    TransactionBuilderConfig, TransactionStatus,
+};
 
 // Re-export notification types for convenience
 pub use notification::{NotificationService, NotificationError};
@@ -30,7 +36,8 @@ pub struct TradeRequest {
 /// Trading service for executing trades
 pub struct TradingService {
     validator: TradeValidator,
-    transaction_builder: TransactionBuilder,
+    transaction_builder: Option<TransactionBuilder>,
+    trade_signer: Option<Arc<dyn TradeSigner>>,
     trade_history: Arc<RwLock<Vec<TradeExecution>>>,
     daily_trade_counts: Arc<RwLock<HashMap<Uuid, usize>>>,
     notification_service: Option<Arc<NotificationService>>,
@@ -41,7 +48,8 @@ impl TradingService {
     pub fn new() -> Self {
         Self {
             validator: TradeValidator::new(),
-            transaction_builder: TransactionBuilder::new(),
+            transaction_builder: None,
+            trade_signer: None,
             trade_history: Arc::new(RwLock::new(Vec::new())),
             daily_trade_counts: Arc::new(RwLock::new(HashMap::new())),
             notification_service: None,
@@ -52,13 +60,27 @@ impl TradingService {
     pub fn with_notification_service(notification_service: Arc<NotificationService>) -> Self {
         Self {
             validator: TradeValidator::new(),
-            transaction_builder: TransactionBuilder::new(),
+            transaction_builder: None,
+            trade_signer: None,
             trade_history: Arc::new(RwLock::new(Vec::new())),
             daily_trade_counts: Arc::new(RwLock::new(HashMap::new())),
             notification_service: Some(notification_service),
         }
     }
 
+    /// Enable on-chain execution with the given transaction builder and signer
+    ///
+    /// Without an executor, trades are validated but never submitted.
+    pub fn with_executor(
+        mut self,
+        transaction_builder: TransactionBuilder,
+        trade_signer: Arc<dyn TradeSigner>,
+    ) -> Self {
+        self.transaction_builder = Some(transaction_builder);
+        self.trade_signer = Some(trade_signer);
+        self
+    }
+
     /// Validate a trade request against user limits and safety checks
     pub async fn validate_trade(
         &self,
@@ -228,39 +250,59 @@ impl TradingService {
             )));
         }
 
+        let execution = self.execute_trade(trade_request, user_email).await?;
+
+        info!(
+            "Auto-trader executed trade {} for user {}",
+            execution.id, user_settings.user_id
+        );
+
+        Ok(execution)
+    }
+
+    /// Build, sign, submit and confirm a validated trade on-chain, then log it
+    ///
+    /// Callers are responsible for validating the trade first.
+    pub async fn execute_trade(
+        &self,
+        trade_request: TradeRequest,
+        user_email: Option<&str>,
+    ) -> Result<TradeExecution> {
+        let (builder, trade_signer) = match (&self.transaction_builder, &self.trade_signer) {
+            (Some(builder), Some(signer)) => (builder, signer),
+            _ => {
+                return Err(TradingError::TransactionError(
+                    "On-chain trade execution is not configured".to_string(),
+                ))
+            }
+        };
+
+        let payer = trade_signer.signer_for(trade_request.user_id).await?;
+
         // Build transaction
-        let transaction = self
-            .transaction_builder
-            .build_transaction(&trade_request)
+        let mut transaction = builder
+            .build_transaction(&trade_request, &payer.pubkey())
             .await?;
-
-        // Validate signature
-        if !self
-            .transaction_builder
-            .validate_signature(&transaction.signature)?
-        {
-            return Err(TradingError::TransactionError(
-                "Invalid transaction signature".to_string(),
-            ));
-        }
+        transaction.sign(&payer)?;
 
         // Submit transaction
-        let tx_signature = transaction.submit().await?;
+        let client = builder.solana_client();
+        let tx_signature = transaction.submit(client).await?;
 
         // Monitor confirmation
-        let status = transaction.check_status().await?;
+        let status = transaction.wait_for_confirmation(client).await?;
 
         // Create trade execution record
         let execution = TradeExecution {
             id: Uuid::new_v4(),
-            user_id: user_settings.user_id,
-            recommendation_id: Some(recommendation.id),
+            user_id: trade_request.user_id,
+            recommendation_id: trade_request.recommendation_id,
             transaction_signature: tx_signature,
             action: trade_request.action,
             token_mint: trade_request.token_mint,
             amount: trade_request.amount,
-            price_usd: None, // Would be populated from actual trade
-            total_value_usd: None,
+            price_usd: Some(transaction.price_in_quote),
+            total_value_usd: Some(transaction.value_in_quote),
             status: match status {
                 TransactionStatus::Confirmed => "CONFIRMED".to_string(),
                 TransactionStatus::Pending => "PENDING".to_string(),
@@ -277,10 +319,12 @@ impl TradingService {
         // Log the trade and send notification
         self.log_trade(&execution, user_email).await?;
 
-        info!(
-            "Auto-trader executed trade {} for user {}",
-            execution.id, user_settings.user_id
-        );
+        if status == TransactionStatus::Failed {
+            return Err(TradingError::TransactionError(format!(
+                "Transaction {} failed on-chain",
+                execution.transaction_signature
+            )));
+        }
 
         Ok(execution)
     }
@@ -295,6 +339,27 @@ impl Default for TradingService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transaction::tests::{create_test_builder, MockQuoteSource};
+    use solana_sdk::signature::Keypair;
+
+    fn with_test_executor(service: TradingService) -> TradingService {
+        service.with_executor(
+            create_test_builder(MockQuoteSource::new()),
+            Arc::new(StaticKeypairSigner::new(Keypair::new())),
+        )
+    }
+
+    /// Notification service whose database cannot be reached
+    async fn unreachable_notification_service() -> Arc<NotificationService> {
+        // Pools connect lazily, so creating one for a closed port succeeds
+        let db_pool = database::create_pool("postgresql://127.0.0.1:1/unreachable", 1)
+            .await
+            .unwrap();
+        Arc::new(NotificationService::with_email_service(
+            db_pool,
+            Arc::new(notification::MockEmailService),
+        ))
+    }
 
     fn create_test_settings(auto_trader_enabled: bool) -> UserSettings {
         UserSettings {
@@ -489,9 +554,9 @@ mod tests {
         assert_eq!(count_after_reset, 0);
     }
 
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread")]
     async fn test_execute_auto_trade_success() {
-        let service = TradingService::new();
+        let service = with_test_executor(TradingService::new());
         let user_id = Uuid::new_v4();
         let settings = create_test_settings(true);
         let subscription = create_test_subscription("PREMIUM", "ACTIVE");
@@ -518,6 +583,34 @@ mod tests {
         assert_eq!(execution.user_id, settings.user_id);
         assert_eq!(execution.action, "BUY");
         assert_eq!(execution.status, "CONFIRMED");
+        assert!(execution.price_usd.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_execute_auto_trade_without_executor() {
+        let service = TradingService::new();
+        let settings = create_test_settings(true);
+        let subscription = create_test_subscription("PREMIUM", "ACTIVE");
+
+        let recommendation = Recommendation {
+            id: Uuid::new_v4(),
+            movement_id: Uuid::new_v4(),
+            user_id: settings.user_id,
+            action: "BUY".to_string(),
+            confidence: 85,
+            reasoning: "Strong signal".to_string(),
+            suggested_amount: Some("100".to_string()),
+            timeframe: None,
+            risks: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        let result = service
+            .execute_auto_trade(&recommendation, &settings, Some(&subscription), 10000.0, None)
+            .await;
+
+        assert!(matches!(result, Err(TradingError::TransactionError(_))));
+        assert!(service.get_trade_history(settings.user_id).await.unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -547,16 +640,9 @@ mod tests {
         assert!(result.is_err());
     }
 
-    #[tokio::test]
-    async fn test_trade_notification_integration() {
-        use notification::MockEmailService;
-        
-        let notification_service = Arc::new(NotificationService::with_email_service(
-            Arc::new(MockEmailService),
-        ));
-        let service = TradingService::with_notification_service(notification_service.clone());
+    #[test]
+    fn test_trade_notification() {
         let user_id = Uuid::new_v4();
-
         let trade = TradeExecution {
             id: Uuid::new_v4(),
             user_id,
@@ -572,33 +658,28 @@ mod tests {
             confirmed_at: Some(chrono::Utc::now()),
         };
 
-        // Log trade with notification
-        service.log_trade(&trade, Some("user@example.com")).await.unwrap();
-
-        // Verify notification was created
-        let notifications = notification_service.get_unread_notifications(user_id).await.unwrap();
-        assert_eq!(notifications.len(), 1);
-        assert_eq!(notifications[0].notification_type, "TRADE_EXECUTED");
-        assert_eq!(notifications[0].priority, "HIGH");
+        let notification = notification::trade_notification(user_id, &trade);
+        assert_eq!(notification.user_id, user_id);
+        assert_eq!(notification.notification_type, "TRADE_EXECUTED");
+        assert_eq!(notification.priority, "HIGH");
+        assert!(!notification.read);
+        let data = notification.data.unwrap();
+        assert_eq!(data["trade_id"], trade.id.to_string());
+        assert_eq!(data["transaction_signature"], "sig_test");
     }
 
-    #[tokio::test]
-    async fn test_execute_auto_trade_with_notification() {
-        use notification::MockEmailService;
-        
-        let notification_service = Arc::new(NotificationService::with_email_service(
-            Arc::new(MockEmailService),
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_auto_trade_when_notification_fails() {
+        let service = with_test_executor(TradingService::with_notification_service(
+            unreachable_notification_service().await,
         ));
-        let service = TradingService::with_notification_service(notification_service.clone());
-        let user_id = Uuid::new_v4();
-        let mut settings = create_test_settings(true);
-        settings.user_id = user_id;
+        let settings = create_test_settings(true);
         let subscription = create_test_subscription("PREMIUM", "ACTIVE");
 
         let recommendation = Recommendation {
             id: Uuid::new_v4(),
             movement_id: Uuid::new_v4(),
-            user_id,
+            user_id: settings.user_id,
             action: "BUY".to_string(),
             confidence: 85,
             reasoning: "Strong signal".to_string(),
@@ -608,15 +689,14 @@ mod tests {
             created_at: chrono::Utc::now(),
         };
 
-        let result = service
+        // A notification that cannot be stored must not fail the trade
+        let execution = service
             .execute_auto_trade(&recommendation, &settings, Some(&subscription), 10000.0, Some("user@example.com"))
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_ok());
-        
-        // Verify notification was created
-        let notifications = notification_service.get_unread_notifications(user_id).await.unwrap();
-        assert_eq!(notifications.len(), 1);
-        assert_eq!(notifications[0].notification_type, "TRADE_EXECUTED");
+        let history = service.get_trade_history(settings.user_id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, execution.id);
     }
 }
@@ -0,0 +1,403 @@
+use crate::{Result, TradingError};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Deserialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use tracing::debug;
+
+/// Default Jupiter v6 quote API endpoint
+pub const DEFAULT_JUPITER_API_URL: &str = "https://quote-api.jup.ag/v6";
+
+/// Which side of the swap is fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapMode {
+    /// Spend exactly `amount` of the input mint
+    ExactIn,
+    /// Receive exactly `amount` of the output mint
+    ExactOut,
+}
+
+impl SwapMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SwapMode::ExactIn => "ExactIn",
+            SwapMode::ExactOut => "ExactOut",
+        }
+    }
+}
+
+/// Request for a swap quote, with amounts in base units
+#[derive(Debug, Clone)]
+pub struct QuoteRequest {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount: u64,
+    pub swap_mode: SwapMode,
+    pub slippage_bps: u16,
+}
+
+/// Swap route returned by a quote source
+#[derive(Debug, Clone)]
+pub struct SwapQuote {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// Minimum output (ExactIn) or maximum input (ExactOut) after slippage
+    pub other_amount_threshold: u64,
+    pub swap_mode: SwapMode,
+    pub slippage_bps: u16,
+    /// Price impact as a percentage (1.0 = 1%)
+    pub price_impact_pct: f64,
+    /// Human-readable labels of the AMMs along the route
+    pub route_labels: Vec<String>,
+    /// Source-specific payload needed to turn the quote into instructions
+    pub raw: serde_json::Value,
+}
+
+/// Instructions needed to execute a quoted swap
+///
+/// Compute budget instructions are intentionally not part of this, since the
+/// transaction builder sets its own limit and priority fee.
+#[derive(Debug, Clone, Default)]
+pub struct SwapInstructions {
+    pub setup_instructions: Vec<Instruction>,
+    pub swap_instruction: Option<Instruction>,
+    pub cleanup_instruction: Option<Instruction>,
+}
+
+impl SwapInstructions {
+    /// All instructions in execution order
+    pub fn into_instructions(self) -> Vec<Instruction> {
+        let mut instructions = self.setup_instructions;
+        instructions.extend(self.swap_instruction);
+        instructions.extend(self.cleanup_instruction);
+        instructions
+    }
+}
+
+/// Pluggable source of swap routes
+#[async_trait]
+pub trait SwapQuoteSource: Send + Sync {
+    /// Find the best route for the requested swap
+    async fn quote(&self, request: &QuoteRequest) -> Result<SwapQuote>;
+
+    /// Build the instructions that execute `quote` for `user`
+    async fn swap_instructions(&self, quote: &SwapQuote, user: &Pubkey) -> Result<SwapInstructions>;
+}
+
+/// Swap quote source backed by the Jupiter aggregator API
+///
+/// Quotes are requested as legacy transactions so the resulting
+/// instructions fit in a `solana_sdk::Transaction` without lookup tables.
+pub struct JupiterQuoteSource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl JupiterQuoteSource {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_JUPITER_API_URL.to_string())
+    }
+
+    pub fn with_base_url(base_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for JupiterQuoteSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterQuoteResponse {
+    input_mint: String,
+    output_mint: String,
+    in_amount: String,
+    out_amount: String,
+    other_amount_threshold: String,
+    swap_mode: String,
+    slippage_bps: u16,
+    #[serde(default)]
+    price_impact_pct: Option<String>,
+    #[serde(default)]
+    route_plan: Vec<JupiterRoutePlan>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterRoutePlan {
+    swap_info: JupiterSwapInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapInfo {
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterSwapInstructionsResponse {
+    #[serde(default)]
+    setup_instructions: Vec<JupiterInstruction>,
+    swap_instruction: JupiterInstruction,
+    #[serde(default)]
+    cleanup_instruction: Option<JupiterInstruction>,
+    #[serde(default)]
+    address_lookup_table_addresses: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterInstruction {
+    program_id: String,
+    accounts: Vec<JupiterAccountMeta>,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|e| TradingError::TransactionError(format!("Invalid pubkey {}: {}", value, e)))
+}
+
+fn parse_amount(value: &str) -> Result<u64> {
+    value
+        .parse::<u64>()
+        .map_err(|e| TradingError::TransactionError(format!("Invalid amount {}: {}", value, e)))
+}
+
+impl JupiterInstruction {
+    fn into_instruction(self) -> Result<Instruction> {
+        let accounts = self
+            .accounts
+            .into_iter()
+            .map(|meta| {
+                Ok(AccountMeta {
+                    pubkey: parse_pubkey(&meta.pubkey)?,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(&self.data)
+            .map_err(|e| {
+                TradingError::TransactionError(format!("Invalid instruction data: {}", e))
+            })?;
+
+        Ok(Instruction {
+            program_id: parse_pubkey(&self.program_id)?,
+            accounts,
+            data,
+        })
+    }
+}
+
+impl JupiterQuoteResponse {
+    fn into_quote(self, raw: serde_json::Value) -> Result<SwapQuote> {
+        let swap_mode = match self.swap_mode.as_str() {
+            "ExactIn" => SwapMode::ExactIn,
+            "ExactOut" => SwapMode::ExactOut,
+            other => {
+                return Err(TradingError::TransactionError(format!(
+                    "Unknown swap mode: {}",
+                    other
+                )))
+            }
+        };
+
+        let price_impact_pct = self
+            .price_impact_pct
+            .as_deref()
+            .map(|p| p.parse::<f64>().unwrap_or(0.0))
+            .unwrap_or(0.0)
+            // Jupiter reports impact as a fraction (0.01 = 1%)
+            * 100.0;
+
+        Ok(SwapQuote {
+            input_mint: parse_pubkey(&self.input_mint)?,
+            output_mint: parse_pubkey(&self.output_mint)?,
+            in_amount: parse_amount(&self.in_amount)?,
+            out_amount: parse_amount(&self.out_amount)?,
+            other_amount_threshold: parse_amount(&self.other_amount_threshold)?,
+            swap_mode,
+            slippage_bps: self.slippage_bps,
+            price_impact_pct,
+            route_labels: self
+                .route_plan
+                .into_iter()
+                .filter_map(|r| r.swap_info.label)
+                .collect(),
+            raw,
+        })
+    }
+}
+
+#[async_trait]
+impl SwapQuoteSource for JupiterQuoteSource {
+    async fn quote(&self, request: &QuoteRequest) -> Result<SwapQuote> {
+        let url = format!("{}/quote", self.base_url);
+        debug!(
+            "Requesting Jupiter quote: {} {} -> {} ({})",
+            request.amount,
+            request.input_mint,
+            request.output_mint,
+            request.swap_mode.as_str()
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[
+                ("inputMint", request.input_mint.to_string()),
+                ("outputMint", request.output_mint.to_string()),
+                ("amount", request.amount.to_string()),
+                ("slippageBps", request.slippage_bps.to_string()),
+                ("swapMode", request.swap_mode.as_str().to_string()),
+                ("asLegacyTransaction", "true".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| TradingError::TransactionError(format!("Quote request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(TradingError::TransactionError(format!(
+                "Quote request returned {}: {}",
+                status, body
+            )));
+        }
+
+        let raw: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| TradingError::TransactionError(format!("Invalid quote response: {}", e)))?;
+
+        let parsed: JupiterQuoteResponse = serde_json::from_value(raw.clone())
+            .map_err(|e| TradingError::TransactionError(format!("Invalid quote response: {}", e)))?;
+
+        parsed.into_quote(raw)
+    }
+
+    async fn swap_instructions(&self, quote: &SwapQuote, user: &Pubkey) -> Result<SwapInstructions> {
+        let url = format!("{}/swap-instructions", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({
+                "quoteResponse": quote.raw,
+                "userPublicKey": user.to_string(),
+                "wrapAndUnwrapSol": true,
+                "asLegacyTransaction": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| {
+                TradingError::TransactionError(format!("Swap instructions request failed: {}", e))
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(TradingError::TransactionError(format!(
+                "Swap instructions request returned {}: {}",
+                status, body
+            )));
+        }
+
+        let parsed: JupiterSwapInstructionsResponse = response.json().await.map_err(|e| {
+            TradingError::TransactionError(format!("Invalid swap instructions response: {}", e))
+        })?;
+
+        if !parsed.address_lookup_table_addresses.is_empty() {
+            return Err(TradingError::TransactionError(
+                "Route requires address lookup tables, which legacy transactions cannot use"
+                    .to_string(),
+            ));
+        }
+
+        Ok(SwapInstructions {
+            setup_instructions: parsed
+                .setup_instructions
+                .into_iter()
+                .map(JupiterInstruction::into_instruction)
+                .collect::<Result<Vec<_>>>()?,
+            swap_instruction: Some(parsed.swap_instruction.into_instruction()?),
+            cleanup_instruction: parsed
+                .cleanup_instruction
+                .map(JupiterInstruction::into_instruction)
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jupiter_quote() {
+        let raw = serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "inAmount": "1000000000",
+            "outAmount": "150000000",
+            "otherAmountThreshold": "148500000",
+            "swapMode": "ExactIn",
+            "slippageBps": 100,
+            "priceImpactPct": "0.0012",
+            "routePlan": [
+                { "swapInfo": { "label": "Orca" }, "percent": 100 }
+            ]
+        });
+
+        let parsed: JupiterQuoteResponse = serde_json::from_value(raw.clone()).unwrap();
+        let quote = parsed.into_quote(raw).unwrap();
+
+        assert_eq!(quote.in_amount, 1_000_000_000);
+        assert_eq!(quote.out_amount, 150_000_000);
+        assert_eq!(quote.other_amount_threshold, 148_500_000);
+        assert_eq!(quote.swap_mode, SwapMode::ExactIn);
+        assert!((quote.price_impact_pct - 0.12).abs() < 1e-9);
+        assert_eq!(quote.route_labels, vec!["Orca".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_jupiter_instruction() {
+        let ix = JupiterInstruction {
+            program_id: "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string(),
+            accounts: vec![JupiterAccountMeta {
+                pubkey: "11111111111111111111111111111111".to_string(),
+                is_signer: false,
+                is_writable: true,
+            }],
+            data: base64::engine::general_purpose::STANDARD.encode([1u8, 2, 3]),
+        };
+
+        let instruction = ix.into_instruction().unwrap();
+        assert_eq!(instruction.accounts.len(), 1);
+        assert!(instruction.accounts[0].is_writable);
+        assert_eq!(instruction.data, vec![1, 2, 3]);
+    }
+}
@@ -1,103 +1,432 @@
+use crate::quote::{QuoteRequest, SwapMode, SwapQuote, SwapQuoteSource};
 use crate::{TradeRequest, TradingError, Result};
-use tracing::{info, warn};
+use async_trait::async_trait;
+use blockchain::SolanaClient;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::transaction::Transaction;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Wrapped SOL mint, used when a trade names the native token
+pub const NATIVE_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// USDC mint, the default quote currency for trades
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+/// Base fee charged per transaction signature, in lamports
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Slippage of the whole amount, in basis points; no tolerance goes beyond it
+const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+/// Tunables for building swap transactions
+#[derive(Debug, Clone)]
+pub struct TransactionBuilderConfig {
+    /// Mint the traded token is bought with and sold into
+    pub quote_mint: String,
+    /// Compute unit limit requested for each swap transaction
+    pub compute_unit_limit: u32,
+    /// Priority fee in micro-lamports per compute unit
+    pub priority_fee_micro_lamports: u64,
+    /// Upper bound on the slippage a trade request may ask for, in basis points
+    pub max_slippage_bps: u16,
+    /// How often to poll signature status while waiting for confirmation
+    pub confirmation_poll_interval: Duration,
+    /// How long to wait for confirmation before reporting the trade as pending
+    pub confirmation_timeout: Duration,
+}
+
+impl Default for TransactionBuilderConfig {
+    fn default() -> Self {
+        Self {
+            quote_mint: USDC_MINT.to_string(),
+            compute_unit_limit: 400_000,
+            priority_fee_micro_lamports: 10_000,
+            max_slippage_bps: 500,
+            confirmation_poll_interval: Duration::from_millis(500),
+            confirmation_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Provides the keypair that pays for and signs a user's trades
+#[async_trait]
+pub trait TradeSigner: Send + Sync {
+    async fn signer_for(&self, user_id: Uuid) -> Result<Arc<Keypair>>;
+}
+
+/// Signs every trade with a single operator wallet
+pub struct StaticKeypairSigner {
+    keypair: Arc<Keypair>,
+}
+
+impl StaticKeypairSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self {
+            keypair: Arc::new(keypair),
+        }
+    }
+}
+
+#[async_trait]
+impl TradeSigner for StaticKeypairSigner {
+    async fn signer_for(&self, _user_id: Uuid) -> Result<Arc<Keypair>> {
+        Ok(self.keypair.clone())
+    }
+}
 
 /// Transaction builder for Solana trades
-pub struct TransactionBuilder;
+///
+/// Routes are obtained from a pluggable [`SwapQuoteSource`], wrapped with
+/// compute budget and priority fee instructions, and guarded against
+/// slippage beyond the trade's tolerance.
+pub struct TransactionBuilder {
+    quote_source: Arc<dyn SwapQuoteSource>,
+    solana_client: Arc<SolanaClient>,
+    config: TransactionBuilderConfig,
+}
 
 impl TransactionBuilder {
-    pub fn new() -> Self {
-        Self
+    pub fn new(quote_source: Arc<dyn SwapQuoteSource>, solana_client: Arc<SolanaClient>) -> Self {
+        Self {
+            quote_source,
+            solana_client,
+            config: TransactionBuilderConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: TransactionBuilderConfig) -> Self {
+        self.config = config;
+        self
     }
 
-    /// Build a Solana transaction for a trade
-    /// Note: This is a simplified implementation for MVP
-    /// Production would integrate with Jupiter Aggregator and solana-sdk
-    pub async fn build_transaction(&self, trade: &TradeRequest) -> Result<SolanaTransaction> {
+    pub fn solana_client(&self) -> &Arc<SolanaClient> {
+        &self.solana_client
+    }
+
+    /// Build an unsigned Solana swap transaction for a trade
+    ///
+    /// BUY trades receive exactly `amount` of `token_mint` paid for in the
+    /// quote mint; SELL trades spend exactly `amount` of `token_mint`.
+    pub async fn build_transaction(
+        &self,
+        trade: &TradeRequest,
+        payer: &Pubkey,
+    ) -> Result<SolanaTransaction> {
         info!(
             "Building transaction for user {}: {} {} {}",
             trade.user_id, trade.action, trade.amount, trade.token_mint
         );
 
-        // In production, this would:
-        // 1. Query Jupiter Aggregator for best swap route
-        // 2. Build Solana transaction using solana-sdk
-        // 3. Set compute budget and priority fees
-        // 4. Add slippage protection
+        let slippage_bps = self.slippage_bps(trade.slippage_tolerance)?;
+        let token_mint = resolve_mint(&trade.token_mint)?;
+        let quote_mint = resolve_mint(&self.config.quote_mint)?;
+
+        let (input_mint, output_mint, swap_mode) = match trade.action.to_uppercase().as_str() {
+            "BUY" => (quote_mint, token_mint, SwapMode::ExactOut),
+            "SELL" => (token_mint, quote_mint, SwapMode::ExactIn),
+            other => {
+                return Err(TradingError::ValidationError(format!(
+                    "Unsupported trade action: {}",
+                    other
+                )))
+            }
+        };
+
+        let token_decimals = self.mint_decimals(&token_mint).await?;
+        let quote_decimals = self.mint_decimals(&quote_mint).await?;
+        let amount = to_base_units(&trade.amount, token_decimals)?;
+
+        let quote = self
+            .quote_source
+            .quote(&QuoteRequest {
+                input_mint,
+                output_mint,
+                amount,
+                swap_mode,
+                slippage_bps,
+            })
+            .await?;
+
+        debug!(
+            "Quote for {}: in {} out {} via {:?}",
+            trade.user_id, quote.in_amount, quote.out_amount, quote.route_labels
+        );
+
+        check_slippage(&quote, slippage_bps, trade.slippage_tolerance)?;
+
+        let swap_instructions = self.quote_source.swap_instructions(&quote, payer).await?;
+        let recent_blockhash = self
+            .solana_client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| TradingError::TransactionError(e.to_string()))?;
+
+        let mut instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(self.config.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(
+                self.config.priority_fee_micro_lamports,
+            ),
+        ];
+        instructions.extend(swap_instructions.into_instructions());
+
+        if instructions.len() == 2 {
+            return Err(TradingError::TransactionError(
+                "Quote source returned no swap instructions".to_string(),
+            ));
+        }
+
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(payer));
+        transaction.message.recent_blockhash = recent_blockhash;
+
+        let (token_units, quote_units) = match quote.swap_mode {
+            SwapMode::ExactOut => (quote.out_amount, quote.in_amount),
+            SwapMode::ExactIn => (quote.in_amount, quote.out_amount),
+        };
+        let token_amount = from_base_units(token_units, token_decimals);
+        let value_in_quote = from_base_units(quote_units, quote_decimals);
 
-        // For MVP, return a mock transaction
         Ok(SolanaTransaction {
-            signature: format!("tx_{}", uuid::Uuid::new_v4()),
-            instructions: vec![format!(
-                "{} {} {}",
-                trade.action, trade.amount, trade.token_mint
-            )],
-            recent_blockhash: "mock_blockhash".to_string(),
-            fee_payer: trade.user_id.to_string(),
+            transaction,
+            quote,
+            price_in_quote: if token_amount > 0.0 { value_in_quote / token_amount } else { 0.0 },
+            value_in_quote,
+            confirmation_poll_interval: self.config.confirmation_poll_interval,
+            confirmation_timeout: self.config.confirmation_timeout,
         })
     }
 
     /// Validate transaction signature
     pub fn validate_signature(&self, signature: &str) -> Result<bool> {
-        // Basic validation
         if signature.is_empty() {
             return Err(TradingError::ValidationError(
                 "Empty transaction signature".to_string(),
             ));
         }
 
-        // In production, this would verify the signature cryptographically
-        // For MVP, just check format
-        if signature.len() < 10 {
-            warn!("Suspicious transaction signature: {}", signature);
+        if Signature::from_str(signature).is_err() {
+            warn!("Malformed transaction signature: {}", signature);
             return Ok(false);
         }
 
         Ok(true)
     }
 
-    /// Estimate transaction fee
+    /// Estimate transaction fee in SOL (base fee plus priority fee)
     pub fn estimate_fee(&self, _trade: &TradeRequest) -> Result<f64> {
-        // In production, this would calculate actual Solana transaction fees
-        // For MVP, return a fixed estimate
-        Ok(0.000005) // ~0.000005 SOL per transaction
+        let priority_fee_lamports = (self.config.compute_unit_limit as u64)
+            .saturating_mul(self.config.priority_fee_micro_lamports)
+            / 1_000_000;
+
+        Ok((LAMPORTS_PER_SIGNATURE + priority_fee_lamports) as f64 / LAMPORTS_PER_SOL)
+    }
+
+    fn slippage_bps(&self, slippage_tolerance: f64) -> Result<u16> {
+        if !slippage_tolerance.is_finite() || slippage_tolerance <= 0.0 {
+            return Err(TradingError::ValidationError(format!(
+                "Slippage tolerance must be positive, got {}%",
+                slippage_tolerance
+            )));
+        }
+
+        let bps = (slippage_tolerance * 100.0).round();
+        let max_bps = self.config.max_slippage_bps.min(MAX_SLIPPAGE_BPS);
+        if bps > max_bps as f64 {
+            return Err(TradingError::ValidationError(format!(
+                "Slippage tolerance {}% exceeds maximum of {}%",
+                slippage_tolerance,
+                max_bps as f64 / 100.0
+            )));
+        }
+
+        Ok(bps.max(1.0) as u16)
+    }
+
+    async fn mint_decimals(&self, mint: &Pubkey) -> Result<u8> {
+        match mint.to_string().as_str() {
+            NATIVE_SOL_MINT => return Ok(9),
+            USDC_MINT => return Ok(6),
+            _ => {}
+        }
+
+        self.solana_client
+            .get_mint_decimals(mint)
+            .await
+            .map_err(|e| TradingError::TransactionError(e.to_string()))
     }
 }
 
-impl Default for TransactionBuilder {
-    fn default() -> Self {
-        Self::new()
+/// Resolve a token symbol or mint address to a mint pubkey
+fn resolve_mint(token: &str) -> Result<Pubkey> {
+    let mint = match token.to_uppercase().as_str() {
+        "SOL" | "WSOL" => NATIVE_SOL_MINT,
+        "USDC" => USDC_MINT,
+        _ => token,
+    };
+
+    Pubkey::from_str(mint)
+        .map_err(|_| TradingError::ValidationError(format!("Unknown token mint: {}", token)))
+}
+
+/// Convert a decimal amount string into integer base units
+fn to_base_units(amount: &str, decimals: u8) -> Result<u64> {
+    let invalid = || TradingError::ValidationError(format!("Invalid trade amount: {}", amount));
+
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if fraction.len() > decimals as usize
+        || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let scale = 10u64.checked_pow(decimals as u32).ok_or_else(invalid)?;
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = decimals as usize)
+            .parse()
+            .map_err(|_| invalid())?
+    };
+
+    let units = whole
+        .checked_mul(scale)
+        .and_then(|w| w.checked_add(fraction))
+        .ok_or_else(invalid)?;
+
+    if units == 0 {
+        return Err(TradingError::ValidationError(
+            "Trade amount must be greater than zero".to_string(),
+        ));
     }
+
+    Ok(units)
 }
 
-/// Simplified Solana transaction structure
+fn from_base_units(units: u64, decimals: u8) -> f64 {
+    units as f64 / 10f64.powi(decimals as i32)
+}
+
+/// Reject quotes whose slippage bound or price impact exceeds the tolerance
+fn check_slippage(quote: &SwapQuote, slippage_bps: u16, slippage_tolerance: f64) -> Result<()> {
+    let bps = slippage_bps as u128;
+
+    let within_bound = match quote.swap_mode {
+        SwapMode::ExactIn => {
+            let min_out = quote.out_amount as u128 * 10_000u128.saturating_sub(bps) / 10_000;
+            quote.other_amount_threshold as u128 >= min_out
+        }
+        SwapMode::ExactOut => {
+            let max_in = quote.in_amount as u128 * (10_000 + bps) / 10_000;
+            quote.other_amount_threshold as u128 <= max_in
+        }
+    };
+
+    if !within_bound {
+        return Err(TradingError::TransactionError(format!(
+            "Quote threshold {} is outside the {} bps slippage tolerance",
+            quote.other_amount_threshold, slippage_bps
+        )));
+    }
+
+    if quote.price_impact_pct > slippage_tolerance {
+        return Err(TradingError::TransactionError(format!(
+            "Price impact {:.2}% exceeds slippage tolerance {}%",
+            quote.price_impact_pct, slippage_tolerance
+        )));
+    }
+
+    Ok(())
+}
+
+/// Swap transaction built for a trade, together with the quote it executes
 #[derive(Debug, Clone)]
 pub struct SolanaTransaction {
-    pub signature: String,
-    pub instructions: Vec<String>,
-    pub recent_blockhash: String,
-    pub fee_payer: String,
+    pub transaction: Transaction,
+    pub quote: SwapQuote,
+    /// Quoted price of one token in units of the quote mint
+    pub price_in_quote: f64,
+    /// Quoted amount of the quote mint paid or received
+    pub value_in_quote: f64,
+    confirmation_poll_interval: Duration,
+    confirmation_timeout: Duration,
 }
 
 impl SolanaTransaction {
+    /// Sign the transaction with the fee payer
+    pub fn sign(&mut self, payer: &Keypair) -> Result<()> {
+        let blockhash = self.transaction.message.recent_blockhash;
+        self.transaction
+            .try_sign(&[payer], blockhash)
+            .map_err(|e| TradingError::TransactionError(format!("Failed to sign transaction: {}", e)))
+    }
+
+    /// Signature of the transaction, once it has been signed
+    pub fn signature(&self) -> Option<Signature> {
+        self.transaction
+            .signatures
+            .first()
+            .filter(|sig| **sig != Signature::default())
+            .copied()
+    }
+
     /// Submit transaction to Solana blockchain
-    /// Note: This is a mock implementation for MVP
-    pub async fn submit(&self) -> Result<String> {
-        info!("Submitting transaction: {}", self.signature);
+    pub async fn submit(&self, client: &SolanaClient) -> Result<String> {
+        if self.signature().is_none() {
+            return Err(TradingError::TransactionError(
+                "Transaction must be signed before submission".to_string(),
+            ));
+        }
 
-        // In production, this would:
-        // 1. Send transaction to Solana RPC
-        // 2. Wait for confirmation
-        // 3. Handle errors and retries
+        let signature = client
+            .send_transaction(&self.transaction)
+            .await
+            .map_err(|e| TradingError::TransactionError(e.to_string()))?;
 
-        // For MVP, return the signature
-        Ok(self.signature.clone())
+        info!("Submitted transaction: {}", signature);
+        Ok(signature.to_string())
     }
 
     /// Check transaction status
-    pub async fn check_status(&self) -> Result<TransactionStatus> {
-        // In production, this would query Solana RPC for transaction status
-        // For MVP, return confirmed
-        Ok(TransactionStatus::Confirmed)
+    pub async fn check_status(&self, client: &SolanaClient) -> Result<TransactionStatus> {
+        let signature = self.signature().ok_or_else(|| {
+            TradingError::TransactionError("Transaction has not been signed".to_string())
+        })?;
+
+        let status = client
+            .get_signature_status(&signature)
+            .await
+            .map_err(|e| TradingError::TransactionError(e.to_string()))?;
+
+        Ok(match status {
+            None => TransactionStatus::Pending,
+            Some(Ok(())) => TransactionStatus::Confirmed,
+            Some(Err(e)) => {
+                warn!("Transaction {} failed: {}", signature, e);
+                TransactionStatus::Failed
+            }
+        })
+    }
+
+    /// Poll status until the transaction lands, fails, or the timeout elapses
+    pub async fn wait_for_confirmation(&self, client: &SolanaClient) -> Result<TransactionStatus> {
+        let deadline = tokio::time::Instant::now() + self.confirmation_timeout;
+
+        loop {
+            let status = self.check_status(client).await?;
+            if status != TransactionStatus::Pending || tokio::time::Instant::now() >= deadline {
+                return Ok(status);
+            }
+
+            tokio::time::sleep(self.confirmation_poll_interval).await;
+        }
     }
 }
 
@@ -110,9 +439,79 @@ pub enum TransactionStatus {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use uuid::Uuid;
+    use crate::quote::SwapInstructions;
+    use solana_client::rpc_client::RpcClient;
+    use solana_sdk::signature::Signer;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+
+    /// Quote source returning a fixed route, for tests
+    pub(crate) struct MockQuoteSource {
+        pub threshold_slack_bps: u64,
+        pub price_impact_pct: f64,
+    }
+
+    impl MockQuoteSource {
+        pub(crate) fn new() -> Self {
+            Self {
+                threshold_slack_bps: 0,
+                price_impact_pct: 0.1,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SwapQuoteSource for MockQuoteSource {
+        async fn quote(&self, request: &QuoteRequest) -> Result<SwapQuote> {
+            // 1 base unit = 150 quote base units; slack widens the threshold past the request
+            let bps = request.slippage_bps as u64 + self.threshold_slack_bps;
+            let (in_amount, out_amount, threshold) = match request.swap_mode {
+                SwapMode::ExactIn => {
+                    let out = request.amount * 150;
+                    (request.amount, out, out * (10_000 - bps) / 10_000)
+                }
+                SwapMode::ExactOut => {
+                    let input = request.amount * 150;
+                    (input, request.amount, input * (10_000 + bps) / 10_000)
+                }
+            };
+
+            Ok(SwapQuote {
+                input_mint: request.input_mint,
+                output_mint: request.output_mint,
+                in_amount,
+                out_amount,
+                other_amount_threshold: threshold,
+                swap_mode: request.swap_mode,
+                slippage_bps: request.slippage_bps,
+                price_impact_pct: self.price_impact_pct,
+                route_labels: vec!["Mock".to_string()],
+                raw: serde_json::Value::Null,
+            })
+        }
+
+        async fn swap_instructions(
+            &self,
+            _quote: &SwapQuote,
+            user: &Pubkey,
+        ) -> Result<SwapInstructions> {
+            Ok(SwapInstructions {
+                setup_instructions: vec![],
+                swap_instruction: Some(Instruction {
+                    program_id: Pubkey::new_unique(),
+                    accounts: vec![AccountMeta::new(*user, true)],
+                    data: vec![1, 2, 3],
+                }),
+                cleanup_instruction: None,
+            })
+        }
+    }
+
+    pub(crate) fn create_test_builder(quote_source: MockQuoteSource) -> TransactionBuilder {
+        let client = SolanaClient::from_rpc_client(RpcClient::new_mock("succeeds".to_string()));
+        TransactionBuilder::new(Arc::new(quote_source), Arc::new(client))
+    }
 
     fn create_test_trade() -> TradeRequest {
         TradeRequest {
@@ -125,21 +524,104 @@ mod tests {
         }
     }
 
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread")]
     async fn test_build_transaction() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let trade = create_test_trade();
+        let payer = Keypair::new();
 
-        let tx = builder.build_transaction(&trade).await.unwrap();
+        let tx = builder.build_transaction(&trade, &payer.pubkey()).await.unwrap();
 
-        assert!(!tx.signature.is_empty());
-        assert!(tx.signature.starts_with("tx_"));
-        assert!(!tx.instructions.is_empty());
+        // Compute limit, priority fee, swap
+        assert_eq!(tx.transaction.message.instructions.len(), 3);
+        assert_eq!(tx.transaction.message.account_keys[0], payer.pubkey());
+        assert_eq!(tx.quote.swap_mode, SwapMode::ExactOut);
+        assert_eq!(tx.quote.out_amount, 10_000_000_000);
+        assert!((tx.price_in_quote - 150_000.0).abs() < 1e-6);
+        assert!(tx.signature().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_build_transaction_rejects_loose_quote() {
+        let mut quote_source = MockQuoteSource::new();
+        quote_source.threshold_slack_bps = 200;
+        let builder = create_test_builder(quote_source);
+        let trade = create_test_trade();
+
+        let result = builder.build_transaction(&trade, &Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(TradingError::TransactionError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_build_transaction_rejects_price_impact() {
+        let mut quote_source = MockQuoteSource::new();
+        quote_source.price_impact_pct = 3.0;
+        let builder = create_test_builder(quote_source);
+        let trade = create_test_trade();
+
+        let result = builder.build_transaction(&trade, &Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(TradingError::TransactionError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_build_transaction_rejects_invalid_slippage() {
+        let builder = create_test_builder(MockQuoteSource::new());
+        let mut trade = create_test_trade();
+
+        trade.slippage_tolerance = 0.0;
+        let result = builder.build_transaction(&trade, &Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(TradingError::ValidationError(_))));
+
+        trade.slippage_tolerance = 10.0;
+        let result = builder.build_transaction(&trade, &Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(TradingError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_build_transaction_caps_configured_slippage() {
+        let builder = create_test_builder(MockQuoteSource::new()).with_config(TransactionBuilderConfig {
+            max_slippage_bps: u16::MAX,
+            ..TransactionBuilderConfig::default()
+        });
+        let mut trade = create_test_trade();
+
+        trade.slippage_tolerance = 150.0;
+        let result = builder.build_transaction(&trade, &Pubkey::new_unique()).await;
+        assert!(matches!(result, Err(TradingError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_check_slippage_beyond_whole_amount() {
+        let quote = SwapQuote {
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            in_amount: 1_000,
+            out_amount: 150_000,
+            other_amount_threshold: 0,
+            swap_mode: SwapMode::ExactIn,
+            slippage_bps: 20_000,
+            price_impact_pct: 0.1,
+            route_labels: Vec::new(),
+            raw: serde_json::Value::Null,
+        };
+
+        assert!(check_slippage(&quote, 20_000, 1.0).is_ok());
+    }
+
+    #[test]
+    fn test_to_base_units() {
+        assert_eq!(to_base_units("10", 9).unwrap(), 10_000_000_000);
+        assert_eq!(to_base_units("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(to_base_units("0.000001", 6).unwrap(), 1);
+        assert!(to_base_units("0.0000001", 6).is_err());
+        assert!(to_base_units("0", 6).is_err());
+        assert!(to_base_units("-1", 6).is_err());
+        assert!(to_base_units("abc", 6).is_err());
     }
 
     #[test]
     fn test_validate_signature_valid() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let signature = "5j7s6NiJS3JAkvgkoc18WVAsiSaci2pxB2A6ueCJP4tprA2TFg9wSyTLeYouxPBJEMzJinENTkpA52YStRW5Dia7";
 
         let result = builder.validate_signature(signature).unwrap();
@@ -148,21 +630,21 @@ mod tests {
 
     #[test]
     fn test_validate_signature_empty() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let result = builder.validate_signature("");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_validate_signature_too_short() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let result = builder.validate_signature("short").unwrap();
         assert!(!result);
     }
 
     #[test]
     fn test_estimate_fee() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let trade = create_test_trade();
 
         let fee = builder.estimate_fee(&trade).unwrap();
@@ -170,24 +652,40 @@ mod tests {
         assert!(fee < 0.01); // Reasonable fee range
     }
 
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_requires_signature() {
+        let builder = create_test_builder(MockQuoteSource::new());
+        let trade = create_test_trade();
+        let payer = Keypair::new();
+
+        let tx = builder.build_transaction(&trade, &payer.pubkey()).await.unwrap();
+        let result = tx.submit(builder.solana_client()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
     async fn test_submit_transaction() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let trade = create_test_trade();
+        let payer = Keypair::new();
 
-        let tx = builder.build_transaction(&trade).await.unwrap();
-        let signature = tx.submit().await.unwrap();
+        let mut tx = builder.build_transaction(&trade, &payer.pubkey()).await.unwrap();
+        tx.sign(&payer).unwrap();
+        let signature = tx.submit(builder.solana_client()).await.unwrap();
 
-        assert_eq!(signature, tx.signature);
+        assert_eq!(signature, tx.signature().unwrap().to_string());
     }
 
-    #[tokio::test]
+    #[tokio::test(flavor = "multi_thread")]
     async fn test_check_transaction_status() {
-        let builder = TransactionBuilder::new();
+        let builder = create_test_builder(MockQuoteSource::new());
         let trade = create_test_trade();
+        let payer = Keypair::new();
 
-        let tx = builder.build_transaction(&trade).await.unwrap();
-        let status = tx.check_status().await.unwrap();
+        let mut tx = builder.build_transaction(&trade, &payer.pubkey()).await.unwrap();
+        tx.sign(&payer).unwrap();
+        let status = tx.wait_for_confirmation(builder.solana_client()).await.unwrap();
 
         assert_eq!(status, TransactionStatus::Confirmed);
     }
@@ -0,0 +1,75 @@
+use database::{create_pool, run_migrations, DbPool};
+use notification::{MockEmailService, NotificationService, DEFAULT_PAGE_SIZE};
+use shared::models::TradeExecution;
+use std::sync::Arc;
+use trading::TradingService;
+use uuid::Uuid;
+
+async fn create_test_db() -> DbPool {
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set for trade notification tests");
+
+    let pool = create_pool(&database_url, 2)
+        .await
+        .expect("Failed to create database pool");
+    run_migrations(&pool).await.expect("Failed to run migrations");
+    pool
+}
+
+/// Create a user for notifications to belong to
+async fn create_test_user(db_pool: &DbPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    let client = db_pool.get().await.unwrap();
+    client
+        .execute(
+            "INSERT INTO users (id, email, password_hash) VALUES ($1, $2, $3)",
+            &[&user_id, &format!("trader-{}@example.com", user_id), &"hash"],
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+#[tokio::test]
+#[ignore] // Only run with DATABASE_URL set
+async fn test_logged_trade_is_stored_as_notification() {
+    // Run with: cargo test --package trading --test trade_notification_test -- --ignored
+
+    let db_pool = create_test_db().await;
+    let notification_service = Arc::new(NotificationService::with_email_service(
+        db_pool.clone(),
+        Arc::new(MockEmailService),
+    ));
+    let service = TradingService::with_notification_service(notification_service.clone());
+    let user_id = create_test_user(&db_pool).await;
+
+    let trade = TradeExecution {
+        id: Uuid::new_v4(),
+        user_id,
+        recommendation_id: Some(Uuid::new_v4()),
+        transaction_signature: "sig_test".to_string(),
+        action: "BUY".to_string(),
+        token_mint: "SOL".to_string(),
+        amount: "100".to_string(),
+        price_usd: Some(150.0),
+        total_value_usd: Some(15000.0),
+        status: "CONFIRMED".to_string(),
+        executed_at: chrono::Utc::now(),
+        confirmed_at: Some(chrono::Utc::now()),
+    };
+
+    service.log_trade(&trade, Some("user@example.com")).await.unwrap();
+
+    let notifications = notification_service
+        .get_unread_notifications(user_id, None, DEFAULT_PAGE_SIZE)
+        .await
+        .unwrap()
+        .notifications;
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].notification_type, "TRADE_EXECUTED");
+    assert_eq!(notifications[0].priority, "HIGH");
+    assert_eq!(
+        notifications[0].data.as_ref().unwrap()["trade_id"],
+        trade.id.to_string()
+    );
+}
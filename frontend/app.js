@@ -1,8 +1,55 @@
 // API Configuration
 let API_BASE_URL = localStorage.getItem('apiUrl') || 'http://localhost:3000';
 
-// Global demo user UUID (valid UUID format for all API calls)
-const DEMO_USER_ID = '00000000-0000-0000-0000-000000000001';
+// Session: the JWT and user returned by /api/users/login
+const AUTH_TOKEN_KEY = 'authToken';
+const AUTH_USER_KEY = 'authUser';
+
+function getAuthToken() {
+    return localStorage.getItem(AUTH_TOKEN_KEY);
+}
+
+function getCurrentUser() {
+    try {
+        return JSON.parse(localStorage.getItem(AUTH_USER_KEY));
+    } catch (error) {
+        return null;
+    }
+}
+
+// Id of the signed-in user, which the API requires in user-scoped routes
+function currentUserId() {
+    const user = getCurrentUser();
+    return user ? user.id : null;
+}
+
+function storeSession(token, user) {
+    localStorage.setItem(AUTH_TOKEN_KEY, token);
+    localStorage.setItem(AUTH_USER_KEY, JSON.stringify(user));
+}
+
+function clearSession() {
+    localStorage.removeItem(AUTH_TOKEN_KEY);
+    localStorage.removeItem(AUTH_USER_KEY);
+}
+
+// fetch() for API calls: sends the session token and asks the user to sign
+// in again when the API rejects the request as unauthenticated
+async function apiFetch(url, options = {}) {
+    const headers = new Headers(options.headers || {});
+    const token = getAuthToken();
+    if (token) {
+        headers.set('Authorization', `Bearer ${token}`);
+    }
+
+    const response = await fetch(url, { ...options, headers });
+    if (response.status === 401) {
+        clearSession();
+        updateAuthUI();
+        showToast(token ? 'Your session has expired, please sign in again' : 'Please sign in first', 'error');
+    }
+    return response;
+}
 
 // State Management
 const state = {
@@ -25,6 +72,7 @@ document.addEventListener('DOMContentLoaded', () => {
 
 function initializeApp() {
     setupEventListeners();
+    updateAuthUI();
     checkHealthStatus();
     loadSavedWallet();
     
@@ -60,6 +108,13 @@ function setupEventListeners() {
         });
     });
 
+    // Account
+    document.getElementById('signIn').addEventListener('click', signIn);
+    document.getElementById('loginPassword').addEventListener('keypress', (e) => {
+        if (e.key === 'Enter') signIn();
+    });
+    document.getElementById('signOut').addEventListener('click', signOut);
+
     // Settings
     document.getElementById('saveSettings').addEventListener('click', saveSettings);
     document.getElementById('apiUrl').value = API_BASE_URL;
@@ -202,7 +257,7 @@ async function connectWallet() {
 
 async function loadPortfolio(walletAddress) {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/wallets/${walletAddress}/multi-chain-portfolio`);
+        const response = await apiFetch(`${API_BASE_URL}/api/wallets/${walletAddress}/multi-chain-portfolio`);
         
         if (!response.ok) {
             const errorData = await response.json().catch(() => ({ error: 'Failed to load portfolio' }));
@@ -355,7 +410,7 @@ async function loadWhales() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/whales/tracked`);
+        const response = await apiFetch(`${API_BASE_URL}/api/whales/tracked`);
         
         if (!response.ok) {
             throw new Error('Failed to load whales');
@@ -451,7 +506,7 @@ async function loadAnalytics() {
 
 async function loadPortfolioPerformance() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/analytics/portfolio-performance`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/analytics/portfolio-performance`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -510,11 +565,11 @@ function displayMockPerformance() {
 
 async function loadWhaleImpact() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/analytics/whale-impact`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/analytics/whale-impact`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
-                user_id: DEMO_USER_ID,
+                user_id: currentUserId(),
                 period: state.currentPeriod
             })
         });
@@ -573,11 +628,11 @@ function displayMockWhaleImpact() {
 
 async function loadRecommendationAccuracy() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/analytics/recommendation-accuracy`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/analytics/recommendation-accuracy`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
-                user_id: DEMO_USER_ID,
+                user_id: currentUserId(),
                 period: state.currentPeriod
             })
         });
@@ -630,6 +685,60 @@ function displayMockAnalytics() {
     displayMockRecommendationAccuracy();
 }
 
+// Account
+async function signIn() {
+    const email = document.getElementById('loginEmail').value.trim();
+    const password = document.getElementById('loginPassword').value;
+    const errorDiv = document.getElementById('authError');
+
+    if (!email || !password) {
+        errorDiv.textContent = 'Please enter your email and password';
+        return;
+    }
+
+    showLoading();
+    errorDiv.textContent = '';
+
+    try {
+        const response = await fetch(`${API_BASE_URL}/api/users/login`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ email, password })
+        });
+        const result = await response.json();
+
+        if (!response.ok || !result.success) {
+            throw new Error(result.error || 'Sign in failed');
+        }
+
+        storeSession(result.data.token, result.data.user);
+        document.getElementById('loginPassword').value = '';
+        updateAuthUI();
+        showToast('Signed in successfully!', 'success');
+    } catch (error) {
+        errorDiv.textContent = error.message || 'Sign in failed';
+        showToast('Failed to sign in', 'error');
+    } finally {
+        hideLoading();
+    }
+}
+
+function signOut() {
+    clearSession();
+    updateAuthUI();
+    showToast('Signed out', 'success');
+}
+
+// Show the sign-in form or the signed-in user
+function updateAuthUI() {
+    const user = getAuthToken() ? getCurrentUser() : null;
+
+    document.getElementById('signInForm').classList.toggle('hidden', !!user);
+    document.getElementById('signedInSection').classList.toggle('hidden', !user);
+    document.getElementById('signedInEmail').textContent = user ? user.email : '';
+    document.getElementById('accountButton').textContent = user ? user.email : 'Sign In';
+}
+
 // Settings
 function saveSettings() {
     const apiUrl = document.getElementById('apiUrl').value.trim();
@@ -780,7 +889,7 @@ async function getConversionQuote() {
     errorDiv.textContent = '';
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/conversions/quote`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/conversions/quote`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -898,7 +1007,7 @@ async function executeConversion() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
+        const userId = currentUserId();
         
         const payload = {
             quote_id: state.currentQuote.quote_id,
@@ -917,7 +1026,7 @@ async function executeConversion() {
             refund_address: state.connectedWallet
         };
         
-        const response = await fetch(`${API_BASE_URL}/api/conversions/${userId}/execute`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/conversions/${userId}/execute`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify(payload)
@@ -970,9 +1079,9 @@ async function loadConversionHistory() {
     
     try {
         // Use a demo user ID for now
-        const userId = DEMO_USER_ID;
+        const userId = currentUserId();
         
-        const response = await fetch(`${API_BASE_URL}/api/conversions/${userId}/history`);
+        const response = await apiFetch(`${API_BASE_URL}/api/conversions/${userId}/history`);
         
         if (!response.ok) {
             throw new Error('Failed to load conversion history');
@@ -1167,7 +1276,7 @@ async function saveBenchmark() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
+        const userId = currentUserId();
         
         const payload = {
             asset,
@@ -1183,7 +1292,7 @@ async function saveBenchmark() {
             payload.trade_amount = parseFloat(tradeAmount).toString();
         }
         
-        const response = await fetch(`${API_BASE_URL}/api/benchmarks/${userId}`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/benchmarks/${userId}`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify(payload)
@@ -1224,8 +1333,8 @@ async function loadBenchmarks() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/benchmarks/${userId}`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/benchmarks/${userId}`);
         
         if (!response.ok) {
             throw new Error('Failed to load benchmarks');
@@ -1326,8 +1435,8 @@ async function deleteBenchmark(benchmarkId) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/benchmarks/${userId}/${benchmarkId}`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/benchmarks/${userId}/${benchmarkId}`, {
             method: 'DELETE'
         });
         
@@ -1438,7 +1547,7 @@ async function saveOffer() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
+        const userId = currentUserId();
         
         const payload = {
             offer_type: offerType,
@@ -1450,7 +1559,7 @@ async function saveOffer() {
             is_proximity_offer: false
         };
         
-        const response = await fetch(`${API_BASE_URL}/api/p2p/${userId}/offers`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/p2p/${userId}/offers`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify(payload)
@@ -1490,8 +1599,8 @@ async function loadMyOffers() {
     }
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/p2p/${userId}/offers`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/p2p/${userId}/offers`);
         
         if (!response.ok) throw new Error('Failed to load offers');
         
@@ -1506,8 +1615,8 @@ async function loadMyOffers() {
 
 async function loadMarketplace() {
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/p2p/${userId}/marketplace`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/p2p/${userId}/marketplace`);
         
         if (!response.ok) throw new Error('Failed to load marketplace');
         
@@ -1671,8 +1780,8 @@ async function cancelOffer(offerId) {
     
     showLoading();
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/p2p/${userId}/offers/${offerId}/cancel`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/p2p/${userId}/offers/${offerId}/cancel`, {
             method: 'POST'
         });
         
@@ -1697,8 +1806,8 @@ async function acceptOffer(offerId) {
     
     showLoading();
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/p2p/${userId}/offers/${offerId}/accept`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/p2p/${userId}/offers/${offerId}/accept`, {
             method: 'POST'
         });
         
@@ -1763,7 +1872,7 @@ async function loadConversationById(conversationId) {
         `;
         
         // Optionally fetch actual messages if the endpoint exists
-        const response = await fetch(`${API_BASE_URL}/api/chat/conversations/${conversationId}/messages`);
+        const response = await apiFetch(`${API_BASE_URL}/api/chat/conversations/${conversationId}/messages`);
         if (response.ok) {
             const data = await response.json();
             displayChatMessages(data.data || []);
@@ -1842,7 +1951,7 @@ function initializeChatView() {
 
 async function loadContacts() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/chat/proximity-contacts`);
+        const response = await apiFetch(`${API_BASE_URL}/api/chat/proximity-contacts`);
         
         if (!response.ok) {
             throw new Error('Failed to load proximity contacts');
@@ -1932,7 +2041,7 @@ function displayChatMessages(messages) {
     
     messages.forEach(message => {
         const messageEl = document.createElement('div');
-        const isSent = message.from_user_id === DEMO_USER_ID;
+        const isSent = message.from_user_id === currentUserId();
         messageEl.className = `chat-message ${isSent ? 'sent' : 'received'}`;
         
         const verificationBadge = message.blockchain_hash ? 
@@ -1964,7 +2073,7 @@ function displayMockChatHistory() {
         },
         {
             id: '2',
-            from_user_id: DEMO_USER_ID,
+            from_user_id: currentUserId(),
             content: 'Sure! The rate is 200 USDC per SOL.',
             blockchain_hash: '0x123abc...',
             created_at: new Date(Date.now() - 3000000).toISOString()
@@ -2013,8 +2122,8 @@ async function verifyMessage(messageId) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/chat/${userId}/verify/${messageId}`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/chat/${userId}/verify/${messageId}`);
         
         if (!response.ok) throw new Error('Failed to verify message');
         
@@ -2040,8 +2149,8 @@ async function reportMessage(messageId) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/chat/${userId}/report/${messageId}`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/chat/${userId}/report/${messageId}`, {
             method: 'POST'
         });
         
@@ -2146,8 +2255,8 @@ async function createTempWallet() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -2177,8 +2286,8 @@ async function createTempWallet() {
 
 async function loadTempWallets() {
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets`);
         
         if (!response.ok) throw new Error('Failed to load temporary wallets');
         
@@ -2284,8 +2393,8 @@ async function deleteTempWallet(walletId) {
         return;
         
         /* Uncomment when backend implements DELETE endpoint
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets/${walletId}`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets/${walletId}`, {
             method: 'DELETE'
         });
         
@@ -2307,8 +2416,8 @@ async function setWalletAsPrimary(walletId) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets/${walletId}/primary`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/temporary-wallets/${walletId}/primary`, {
             method: 'PUT',
             headers: { 'Content-Type': 'application/json' }
         });
@@ -2337,8 +2446,8 @@ async function freezeTempWallet(walletAddress) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/freeze`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/freeze`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({})
@@ -2368,8 +2477,8 @@ async function unfreezeTempWallet(walletAddress) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/unfreeze`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/unfreeze`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({ password: password })
@@ -2393,8 +2502,8 @@ async function unfreezeTempWallet(walletAddress) {
 
 async function loadWalletFreezeStatus() {
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/wallets`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/wallets`);
         
         if (!response.ok) throw new Error('Failed to load wallet freeze status');
         
@@ -2467,8 +2576,8 @@ async function freezeWallet(walletAddress) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/freeze`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/freeze`, {
             method: 'POST'
         });
         
@@ -2493,8 +2602,8 @@ async function unfreezeWallet(walletAddress) {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/unfreeze`, {
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/privacy/${userId}/wallets/${walletAddress}/unfreeze`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({ password: password })
@@ -2536,17 +2645,25 @@ function initializeWebSocket() {
         websocket.close();
     }
     
+    // The dashboard socket only accepts authenticated users
+    const authToken = localStorage.getItem('authToken');
+    if (!authToken) {
+        startPolling();
+        return;
+    }
+    
     try {
-        // Connect to WebSocket server
-        const wsUrl = API_BASE_URL.replace('http', 'ws') + '/ws/dashboard';
+        // Connect to WebSocket server; browsers cannot set headers here, so
+        // the token goes in the query string
+        const wsUrl = API_BASE_URL.replace('http', 'ws') + '/ws/dashboard?token=' + encodeURIComponent(authToken);
         websocket = new WebSocket(wsUrl);
         
         websocket.onopen = () => {
             console.log('WebSocket connected');
-            // Subscribe to updates for this wallet
+            // Prices for every asset are sent by default; also follow mesh health
             websocket.send(JSON.stringify({
-                type: 'subscribe',
-                wallet_address: state.connectedWallet
+                action: 'subscribe',
+                topics: ['mesh_status']
             }));
         };
         
@@ -2610,6 +2727,14 @@ function handleWebSocketMessage(data) {
             showToast(`Benchmark triggered: ${data.benchmark.asset} reached $${data.benchmark.target_price}`, 'warning');
             break;
             
+        case 'subscriptions':
+            console.log('WebSocket subscriptions:', data.topics);
+            break;
+            
+        case 'error':
+            console.error('WebSocket error message:', data.message);
+            break;
+            
         default:
             console.log('Unknown WebSocket message type:', data.type);
     }
@@ -2638,8 +2763,8 @@ async function loadAIActions() {
     if (!state.connectedWallet) return;
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/analytics/${userId}/ai-actions`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/analytics/${userId}/ai-actions`);
         
         if (!response.ok) throw new Error('Failed to load AI actions');
         
@@ -2878,8 +3003,8 @@ async function loadTrimConfiguration() {
     if (!state.connectedWallet) return;
     
     try {
-        const userId = DEMO_USER_ID;
-        const response = await fetch(`${API_BASE_URL}/api/trim/${userId}/config`);
+        const userId = currentUserId();
+        const response = await apiFetch(`${API_BASE_URL}/api/trim/${userId}/config`);
         
         if (!response.ok) {
             // Config doesn't exist yet, use defaults
@@ -2937,7 +3062,7 @@ async function saveTrimConfiguration() {
     showLoading();
     
     try {
-        const userId = DEMO_USER_ID;
+        const userId = currentUserId();
         const payload = {
             enabled,
             minimum_profit_percent: minProfitPercent,
@@ -2945,7 +3070,7 @@ async function saveTrimConfiguration() {
             max_trims_per_day: maxTrimsPerDay
         };
         
-        const response = await fetch(`${API_BASE_URL}/api/trim/${userId}/config`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/trim/${userId}/config`, {
             method: 'PUT',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify(payload)
@@ -3002,7 +3127,7 @@ async function saveProviderConfig() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/provider/enable`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/provider/enable`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({ api_key: apiKey })
@@ -3038,7 +3163,7 @@ async function disableProviderMode() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/provider/disable`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/provider/disable`, {
             method: 'POST'
         });
         
@@ -3063,7 +3188,7 @@ async function disableProviderMode() {
 
 async function loadProviderStatus() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/provider/status`);
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/provider/status`);
         
         if (!response.ok) {
             throw new Error('Failed to load provider status');
@@ -3144,7 +3269,7 @@ function createFreshnessIndicator(timestamp, sourceNodeId) {
 
 async function loadNetworkStatus() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/network/status`);
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/network/status`);
         
         if (!response.ok) {
             throw new Error('Failed to load network status');
@@ -3261,7 +3386,7 @@ function displayNetworkStatus(status) {
 
 async function loadMeshPrices() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/prices`);
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/prices`);
         
         if (!response.ok) {
             throw new Error('Failed to load mesh prices');
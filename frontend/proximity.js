@@ -75,11 +75,11 @@ async function startDiscovery() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/proximity/discovery/start`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/proximity/discovery/start`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
-                user_id: DEMO_USER_ID,
+                user_id: currentUserId(),
                 method: proximityState.discoveryMethod,
                 duration_minutes: 30
             })
@@ -127,7 +127,7 @@ async function stopDiscovery() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/proximity/discovery/stop`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/proximity/discovery/stop`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -259,7 +259,7 @@ async function refreshPeers() {
     if (!proximityState.discoveryActive) return;
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/proximity/peers`);
+        const response = await apiFetch(`${API_BASE_URL}/api/proximity/peers`);
         
         if (!response.ok) {
             throw new Error('Failed to load peers');
@@ -429,11 +429,11 @@ async function confirmTransfer() {
     errorDiv.textContent = '';
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/proximity/transfers`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/proximity/transfers`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
-                sender_user_id: DEMO_USER_ID,
+                sender_user_id: currentUserId(),
                 sender_wallet: state.connectedWallet,
                 recipient_user_id: proximityState.selectedPeer.peer_id,
                 recipient_wallet: proximityState.selectedPeer.wallet_address,
@@ -529,7 +529,7 @@ async function acceptTransfer() {
     showLoading();
     
     try {
-        const response = await fetch(
+        const response = await apiFetch(
             `${API_BASE_URL}/api/proximity/transfers/${proximityState.incomingTransfer.transfer_id}/accept`,
             { method: 'POST' }
         );
@@ -556,7 +556,7 @@ async function rejectTransfer() {
     showLoading();
     
     try {
-        const response = await fetch(
+        const response = await apiFetch(
             `${API_BASE_URL}/api/proximity/transfers/${proximityState.incomingTransfer.transfer_id}/reject`,
             {
                 method: 'POST',
@@ -594,12 +594,12 @@ async function loadProximityHistory() {
     
     try {
         const params = new URLSearchParams({
-            user_id: DEMO_USER_ID,
+            user_id: currentUserId(),
             limit: '50',
             offset: '0'
         });
         
-        const response = await fetch(`${API_BASE_URL}/api/proximity/transfers/history?${params}`);
+        const response = await apiFetch(`${API_BASE_URL}/api/proximity/transfers/history?${params}`);
         
         if (!response.ok) {
             throw new Error('Failed to load history');
@@ -666,7 +666,7 @@ function displayProximityHistory(transfers) {
     
     transfers.forEach(transfer => {
         const item = document.createElement('div');
-        const isSent = transfer.sender_user_id === DEMO_USER_ID;
+        const isSent = transfer.sender_user_id === currentUserId();
         const statusClass = transfer.status.toLowerCase();
         
         item.className = `history-transfer-item ${isSent ? 'sent' : 'received'} ${statusClass}`;
@@ -708,7 +708,7 @@ function displayMockProximityHistory() {
     const mockTransfers = [
         {
             id: '1',
-            sender_user_id: DEMO_USER_ID,
+            sender_user_id: currentUserId(),
             recipient_user_id: '2',
             asset: 'SOL',
             amount: '1.5',
@@ -720,7 +720,7 @@ function displayMockProximityHistory() {
         {
             id: '2',
             sender_user_id: '3',
-            recipient_user_id: DEMO_USER_ID,
+            recipient_user_id: currentUserId(),
             asset: 'USDC',
             amount: '50.00',
             status: 'Completed',
@@ -73,7 +73,7 @@ async function generateStealthAddress() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/generate`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/generate`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -123,7 +123,7 @@ async function prepareStealthPayment() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/prepare-payment`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/prepare-payment`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -177,7 +177,7 @@ async function sendStealthPayment() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/send`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/send`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -229,7 +229,7 @@ async function scanForPayments() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/scan`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/scan`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -361,7 +361,7 @@ async function shieldFunds() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/shield`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/shield`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -409,7 +409,7 @@ async function unshieldFunds() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/unshield`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/unshield`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -458,7 +458,7 @@ async function showStealthQR() {
     }
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/qr-encode`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/qr-encode`, {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -507,7 +507,7 @@ async function scanStealthQR() {
         formData.append('qr_image', file);
         
         try {
-            const response = await fetch(`${API_BASE_URL}/api/stealth/qr-decode`, {
+            const response = await apiFetch(`${API_BASE_URL}/api/stealth/qr-decode`, {
                 method: 'POST',
                 body: formData
             });
@@ -537,7 +537,7 @@ async function refreshPaymentQueue() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/stealth/queue`);
+        const response = await apiFetch(`${API_BASE_URL}/api/stealth/queue`);
         
         if (!response.ok) {
             throw new Error('Failed to load payment queue');
@@ -612,7 +612,7 @@ function displayPaymentQueue(payments) {
 
 async function checkMeshStatus() {
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/status`);
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/status`);
         
         if (!response.ok) {
             throw new Error('Failed to check mesh status');
@@ -653,7 +653,7 @@ async function toggleMeshNetwork() {
     showLoading();
     
     try {
-        const response = await fetch(`${API_BASE_URL}/api/mesh/${action}`, {
+        const response = await apiFetch(`${API_BASE_URL}/api/mesh/${action}`, {
             method: 'POST'
         });
         